@@ -0,0 +1,54 @@
+use huff_utils::{
+    artifact::{Artifact, DebugRange},
+    decompiler,
+    disassembler::{annotate, disassemble},
+};
+use std::{fs, path::Path};
+
+/// Disassembles runtime bytecode given as hex, a file of hex or an artifact
+///
+/// The instructions are cross-referenced with the debug ranges of `artifact`, or of the input
+/// itself if it's an artifact.
+pub(crate) fn disasm(input: &str, artifact: Option<&str>) -> Result<String, String> {
+    let (bytecode, mut debug) = read_bytecode(input)?;
+    if let Some(path) = artifact {
+        debug = read_debug(path)?;
+    }
+    Ok(annotate(&disassemble(&bytecode), &debug))
+}
+
+/// Decompiles runtime bytecode given as hex, a file of hex or an artifact into a Huff skeleton
+pub(crate) fn decompile(input: &str) -> Result<String, String> {
+    read_bytecode(input).map(|(bytecode, _)| decompiler::decompile(&bytecode))
+}
+
+/// Reads runtime bytecode given as hex, a file of hex or an artifact, with the debug ranges of
+/// an artifact
+fn read_bytecode(input: &str) -> Result<(String, Vec<DebugRange>), String> {
+    let (bytecode, debug) = if Path::new(input).is_file() {
+        let contents =
+            fs::read_to_string(input).map_err(|e| format!("Failed to read \"{input}\": {e}"))?;
+        match serde_json::from_str::<Artifact>(&contents) {
+            Ok(artifact) => (artifact.runtime, artifact.debug.runtime),
+            Err(_) => (contents, vec![]),
+        }
+    } else {
+        (input.to_string(), vec![])
+    };
+
+    let hex = bytecode.trim();
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if hex.len() % 2 != 0 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid bytecode \"{input}\", expected hex or a file"));
+    }
+    Ok((hex.to_string(), debug))
+}
+
+/// Reads the debug ranges of the runtime code from an artifact
+fn read_debug(path: &str) -> Result<Vec<DebugRange>, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read \"{path}\": {e}"))?;
+    serde_json::from_str::<Artifact>(&contents)
+        .map(|artifact| artifact.debug.runtime)
+        .map_err(|e| format!("Invalid artifact \"{path}\": {e}"))
+}
@@ -0,0 +1,33 @@
+use huff_fmt::{config::FormatConfig, format};
+use huff_utils::prelude::{unpack_files, CompilerError};
+use std::fs;
+use yansi::Paint;
+
+/// Formats the Huff files of each path in place, returning whether every file was already
+/// formatted.
+///
+/// With `check`, the unformatted files are only listed instead of written.
+pub(crate) fn fmt(paths: &[String], check: bool, config: &FormatConfig) -> Result<bool, String> {
+    let mut formatted = true;
+    for path in paths {
+        let files =
+            unpack_files(path).map_err(|e| CompilerError::FileUnpackError(e).to_string())?;
+        for file in files {
+            let source =
+                fs::read_to_string(&file).map_err(|e| format!("Failed to read \"{file}\": {e}"))?;
+            let output =
+                format(&source, config).map_err(|e| format!("Failed to format \"{file}\": {e}"))?;
+            if output == source {
+                continue
+            }
+            formatted = false;
+            if check {
+                println!("{} {file}", Paint::yellow("[UNFORMATTED]"));
+            } else {
+                fs::write(&file, output).map_err(|e| format!("Failed to write \"{file}\": {e}"))?;
+                println!("{} {file}", Paint::green("[FORMATTED]"));
+            }
+        }
+    }
+    Ok(formatted)
+}
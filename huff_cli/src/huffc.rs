@@ -7,31 +7,46 @@
 #![forbid(where_clauses_object_safety)]
 #![allow(deprecated)]
 
-use clap::{App, CommandFactory, Parser as ClapParser, Subcommand};
+use clap::{App, ArgEnum, CommandFactory, Parser as ClapParser, Subcommand};
 use comfy_table::{modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL, Cell, Color, Row, Table};
-use ethers_core::utils::hex;
-use huff_codegen::Codegen;
+use ethers_core::{
+    types::Address,
+    utils::{hex, to_checksum},
+};
+use huff_codegen::{passes::Pipeline, Codegen};
 use huff_core::Compiler;
+use huff_fmt::config::FormatConfig;
+use huff_lints::{config::LintConfig, Linter};
 use huff_tests::{
-    prelude::{print_test_report, ReportKind},
+    prelude::{print_test_report, ReportKind, TestStatus},
     HuffTester,
 };
 use huff_utils::{
-    file_provider::FileSystemFileProvider,
+    diagnostics::{render_stderr, to_sarif, Diagnostic},
+    docs::ContractDocs,
+    error_codes::{explain, ERROR_CODES},
+    file_provider::{FileProvider, FileSystemFileProvider, RemoteFileProvider},
+    metadata::{included_files, ipfs_cid, pin},
     prelude::{
-        export_interfaces, gen_sol_interfaces, str_to_bytes32, unpack_files, AstSpan, BytecodeRes,
-        CodegenError, CodegenErrorKind, CompilerError, EVMVersion, FileSource, Literal,
-        OutputLocation, Span,
+        export_interfaces, gen_sol_interfaces, parse_decimal_literal, parse_hex_literal,
+        unpack_files, Artifact, AstSpan, BootstrapSettings, BytecodeRes, CodegenError,
+        CodegenErrorKind, CompilerError, CompilerWarning, ConstantDefinition, CustomBuiltins,
+        Deployment, EVMVersion, FileSource, Literal, MetadataHash, OutputLocation, Severity, Span,
     },
+    remote::{self, RemoteCache},
 };
 use isatty::stdout_isatty;
 use spinners::{Spinner, Spinners};
 use std::{collections::BTreeMap, io::Write, path::Path, rc::Rc, sync::Arc, time::Instant};
 use yansi::Paint;
 
+mod disasm;
+mod fmt;
+mod install;
+
 /// The Huff CLI Args
 #[derive(ClapParser, Debug, Clone)]
-#[clap(name = "huffc", version, about, long_about = None)]
+#[clap(name = "huffc", version, about, long_about = None, disable_version_flag = true)]
 struct Huff {
     /// The contract(s) to compile.
     pub path: Option<String>,
@@ -48,8 +63,13 @@ struct Huff {
     #[clap(short = 'd', long = "output-directory", default_value = "./artifacts")]
     outputdir: String,
 
-    /// The input constructor arguments
-    #[clap(short = 'i', long = "inputs", multiple_values = true)]
+    /// The input constructor arguments, ABI encoded using the constructor's parameter types
+    #[clap(
+        short = 'i',
+        long = "inputs",
+        visible_alias = "constructor-args",
+        multiple_values = true
+    )]
     inputs: Option<Vec<String>>,
 
     /// Interactively input the constructor args
@@ -60,10 +80,28 @@ struct Huff {
     #[clap(short = 'a', long = "artifacts")]
     artifacts: bool,
 
-    /// Optimize compilation [WIP]
+    /// Run the optimization passes over the generated code
     #[clap(short = 'z', long = "optimize")]
     optimize: bool,
 
+    /// Outline repeatedly invoked macros into subroutines, trading gas for code size
+    #[clap(long = "outline")]
+    outline: bool,
+
+    /// Fail the compilation when the code exceeds the EIP-170 or EIP-3860 size limits
+    #[clap(long = "strict-size")]
+    strict_size: bool,
+
+    /// Append a CBOR metadata trailer to the runtime code, with the hash of the metadata: none,
+    /// ipfs or keccak
+    #[clap(long = "metadata-hash")]
+    metadata_hash: Option<MetadataHash>,
+
+    /// Pin the metadata and sources to the IPFS node behind an HTTP API url, with
+    /// `--metadata-hash ipfs`
+    #[clap(long = "ipfs-api")]
+    ipfs_api: Option<String>,
+
     /// Generate solidity interface for a Huff artifact
     #[clap(short = 'g', min_values = 0, long = "interface")]
     interface: Option<String>,
@@ -76,6 +114,14 @@ struct Huff {
     #[clap(short = 'r', long = "bin-runtime")]
     bin_runtime: bool,
 
+    /// Prints the keccak256 hash of the deployed bytecode and constructor arguments, for CREATE2.
+    #[clap(long = "initcode-hash")]
+    initcode_hash: bool,
+
+    /// Prints out the creation and runtime code as assembly, annotated with labels and macros.
+    #[clap(long = "asm")]
+    asm: bool,
+
     /// Prints out to the terminal.
     #[clap(short = 'p', long = "print")]
     print: bool,
@@ -84,10 +130,22 @@ struct Huff {
     #[clap(short = 'v', long = "verbose")]
     verbose: bool,
 
+    /// Print version information, with the compiler fingerprint of the given settings if verbose.
+    #[clap(short = 'V', long = "version")]
+    version: bool,
+
     /// Prints out the jump label PC indices for the specified contract.
     #[clap(short = 'l', long = "label-indices")]
     label_indices: bool,
 
+    /// Prints out the storage slots assigned to constants.
+    #[clap(long = "storage-layout")]
+    storage_layout: bool,
+
+    /// Prints out the parsed AST of each contract as JSON.
+    #[clap(long = "ast")]
+    ast: bool,
+
     /// Override / set constants for the compilation environment.
     #[clap(short = 'c', long = "constants", multiple_values = true)]
     constants: Option<Vec<String>>,
@@ -104,13 +162,106 @@ struct Huff {
     #[clap(short = 'e', long = "evm-version")]
     evm_version: Option<String>,
 
-    /// Test subcommand
+    /// Import remappings of the form `from=to`, e.g. `@openzeppelin/=lib/oz/`.
+    #[clap(long = "remappings", multiple_values = true)]
+    remappings: Option<Vec<String>>,
+
+    /// The memory offset the default bootstrap copies the runtime code to.
+    #[clap(long = "bootstrap-offset", default_value = "0")]
+    bootstrap_offset: usize,
+
+    /// A macro replacing the default bootstrap, taking `[runtime_offset, runtime_size]`.
+    #[clap(long = "bootstrap-shim")]
+    bootstrap_shim: Option<String>,
+
+    /// A code table the bootstrap returns as the runtime code, in place of `MAIN`.
+    #[clap(long = "bootstrap-table")]
+    bootstrap_table: Option<String>,
+
+    /// Define flags for `#ifdef` and `#ifndef` conditional compilation.
+    #[clap(long = "define", multiple_values = true)]
+    define: Option<Vec<String>>,
+
+    /// Allow including files over HTTPS and IPFS, pinning their content hashes in `huff.lock`.
+    #[clap(long = "remote-imports")]
+    remote_imports: bool,
+
+    /// The severity of address literals with an invalid EIP-55 checksum: allow, warn or error.
+    #[clap(long = "address-checksum")]
+    address_checksum: Option<Severity>,
+
+    /// The maximum depth of nested macro invocations.
+    #[clap(long = "max-expansion-depth")]
+    max_expansion_depth: Option<usize>,
+
+    /// Make the labels of every macro unique to each of its invocations.
+    #[clap(long = "unique-labels")]
+    unique_labels: bool,
+
+    /// The severity of stack comments not matching the simulated stack: allow, warn or error.
+    #[clap(long = "stack-comments")]
+    stack_comments: Option<Severity>,
+
+    /// The severity of macros whose stack effect doesn't match their declared takes and
+    /// returns: allow, warn or error.
+    #[clap(long = "stack-effects")]
+    stack_effects: Option<Severity>,
+
+    /// The severity of stack underflows and overflows in the main macro: allow, warn or error.
+    #[clap(long = "stack-analysis")]
+    stack_analysis: Option<Severity>,
+
+    /// The severity of macros never invoked from the main or constructor macro: allow, warn or
+    /// error.
+    #[clap(long = "unused-macros")]
+    unused_macros: Option<Severity>,
+
+    /// The severity of constants never referenced from the main or constructor macro: allow,
+    /// warn or error.
+    #[clap(long = "unused-constants")]
+    unused_constants: Option<Severity>,
+
+    /// Set the severity of lint rules by id, e.g. `unused-macros=error`, overriding `huff.toml`.
+    #[clap(long = "lint", multiple_values = true)]
+    lints: Option<Vec<String>>,
+
+    /// The format errors and warnings are reported in.
+    #[clap(
+        long = "output-format",
+        visible_alias = "error-format",
+        arg_enum,
+        default_value = "human"
+    )]
+    output_format: OutputFormat,
+
+    /// The subcommand to run instead of compiling
     #[clap(subcommand)]
-    test: Option<TestCommands>,
+    command: Option<Commands>,
+}
+
+/// The format errors and warnings are reported in
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    /// Human-readable messages with the source, on stderr
+    Human,
+    /// A SARIF report, on stdout
+    Sarif,
+    /// A JSON object per line for each error and warning, on stderr
+    Json,
+}
+
+/// The format of generated documentation
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum DocFormat {
+    /// A Markdown file per contract
+    Markdown,
+    /// A standalone HTML page per contract
+    Html,
 }
 
+/// The subcommands of huffc
 #[derive(Subcommand, Clone, Debug)]
-enum TestCommands {
+enum Commands {
     /// Test subcommand
     Test {
         /// Format the test output as a list, table, or JSON.
@@ -121,6 +272,77 @@ enum TestCommands {
         #[clap(short = 'm', long = "match")]
         match_: Option<String>,
     },
+    /// Install Huff packages from git into `lib/`
+    Install {
+        /// Packages of the form `owner/repo[@rev]` on GitHub, or `<git url>[@rev]`. Installs
+        /// the packages in the lockfile if empty.
+        packages: Vec<String>,
+    },
+    /// Print the extended explanation of an error code, e.g. `huffc explain H0101`
+    Explain {
+        /// The error code
+        code: String,
+    },
+    /// Generate documentation from the doc comments of each contract, e.g. `huffc src/ doc`
+    Doc {
+        /// The format of the documentation.
+        #[clap(short = 'f', long = "format", arg_enum, default_value = "markdown")]
+        format: DocFormat,
+
+        /// The directory the documentation is written to.
+        #[clap(long = "out", default_value = "./docs")]
+        out: String,
+    },
+    /// Format Huff files in place, e.g. `huffc fmt src/`
+    Fmt {
+        /// The files or directories to format. Formats the source path if empty.
+        paths: Vec<String>,
+
+        /// List the unformatted files and fail instead of formatting them, e.g. in CI
+        #[clap(long = "check")]
+        check: bool,
+
+        /// The number of spaces each level of a body is indented with, overriding `huff.toml`
+        #[clap(long = "indent-width")]
+        indent_width: Option<usize>,
+
+        /// Whether to align the trailing comments of a body, overriding `huff.toml`
+        #[clap(long = "align-stack-comments")]
+        align_stack_comments: Option<bool>,
+    },
+    /// Disassemble runtime bytecode, e.g. `huffc disasm 0x5f35...`
+    Disasm {
+        /// The runtime bytecode as hex, or a file of hex or an artifact.
+        bytecode: String,
+
+        /// An artifact whose debug ranges name the labels and macros of the bytecode.
+        #[clap(long = "artifact")]
+        artifact: Option<String>,
+    },
+    /// Decompile runtime bytecode into a best-effort Huff skeleton, e.g. `huffc decompile
+    /// 0x5f35...`
+    Decompile {
+        /// The runtime bytecode as hex, or a file of hex or an artifact.
+        bytecode: String,
+
+        /// The file the skeleton is written to, printing it if unset.
+        #[clap(long = "out")]
+        out: Option<String>,
+    },
+    /// Predict the address of each contract deployed by a deployer with CREATE2 or CREATE, e.g.
+    /// `huffc src/Token.huff predict-address 0x4e59... --salt 0x01`
+    PredictAddress {
+        /// The address of the deployer.
+        deployer: String,
+
+        /// The CREATE2 salt, as hex or a decimal number.
+        #[clap(long = "salt", required_unless_present = "nonce")]
+        salt: Option<String>,
+
+        /// The nonce of the deployer, for CREATE.
+        #[clap(long = "nonce", conflicts_with = "salt")]
+        nonce: Option<u64>,
+    },
 }
 
 /// Helper function to read an stdin input
@@ -143,12 +365,95 @@ fn main() {
     let mut cli = Huff::parse();
 
     // Initiate Tracing if Verbose
-    if cli.verbose {
+    if cli.verbose && !cli.version {
         Compiler::init_tracing_subscriber(Some(vec![tracing::Level::DEBUG.into()]));
     }
 
+    // Install packages
+    if let Some(Commands::Install { packages }) = &cli.command {
+        if let Err(e) = install::install(packages) {
+            eprintln!("{}", Paint::red(e));
+            std::process::exit(1);
+        }
+        return
+    }
+
+    // Explain an error code
+    if let Some(Commands::Explain { code }) = &cli.command {
+        match explain(code) {
+            Some(error_code) => {
+                println!(
+                    "{}: {}\n\n{}",
+                    Paint::red(error_code.code),
+                    error_code.title,
+                    error_code.explanation
+                )
+            }
+            None => {
+                eprintln!("{}", Paint::red(format!("Unknown error code \"{code}\"")));
+                std::process::exit(1);
+            }
+        }
+        return
+    }
+
+    // Format files
+    if let Some(Commands::Fmt { paths, check, indent_width, align_stack_comments }) = &cli.command {
+        let mut config = match FormatConfig::load(".") {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        };
+        config.indent_width = indent_width.unwrap_or(config.indent_width);
+        config.align_stack_comments = align_stack_comments.unwrap_or(config.align_stack_comments);
+
+        let paths = if paths.is_empty() { vec![cli.source.clone()] } else { paths.clone() };
+        match fmt::fmt(&paths, *check, &config) {
+            Ok(true) => {}
+            Ok(false) if !*check => {}
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        }
+        return
+    }
+
+    // Disassemble bytecode
+    if let Some(Commands::Disasm { bytecode, artifact }) = &cli.command {
+        match disasm::disasm(bytecode, artifact.as_deref()) {
+            Ok(disassembly) => println!("{disassembly}"),
+            Err(e) => {
+                eprintln!("{}", Paint::red(e));
+                std::process::exit(1);
+            }
+        }
+        return
+    }
+
+    // Decompile bytecode
+    if let Some(Commands::Decompile { bytecode, out }) = &cli.command {
+        let written = disasm::decompile(bytecode).and_then(|source| match out {
+            Some(out) => {
+                std::fs::write(out, source).map_err(|e| format!("Failed to write \"{out}\": {e}"))
+            }
+            None => {
+                print!("{source}");
+                Ok(())
+            }
+        });
+        if let Err(e) = written {
+            eprintln!("{}", Paint::red(e));
+            std::process::exit(1);
+        }
+        return
+    }
+
     // Check if no argument is provided
-    if cli.path.is_none() {
+    if cli.path.is_none() && !cli.version {
         // Print help and exit
         app.print_help().unwrap();
         return
@@ -156,6 +461,7 @@ fn main() {
 
     // Create compiler from the Huff Args
     let sources: Arc<Vec<String>> = match cli.get_inputs() {
+        _ if cli.version => Arc::new(vec![]),
         Ok(s) => Arc::new(s),
         Err(e) => {
             eprintln!("{}", Paint::red(format!("{e}")));
@@ -167,29 +473,25 @@ fn main() {
     let constants: Option<BTreeMap<&str, Literal>> = cli.constants.as_ref().map(|_constants| {
         _constants
             .iter()
-            .map(|c: &String| {
-                let parts = c.as_str().split('=').collect::<Vec<_>>();
-
-                // Check that constant override argument is valid
-                // Key rule: Alphabetic chars + underscore
-                // Value rule: Valid literal string (0x...)
-                if parts.len() != 2 ||
-                    parts[0].chars().any(|c| !(c.is_alphabetic() || c == '_')) ||
-                    !parts[1].starts_with("0x") ||
-                    parts[1][2..].chars().any(|c| {
-                        !(c.is_numeric() ||
-                            matches!(c, '\u{0041}'..='\u{0046}' | '\u{0061}'..='\u{0066}'))
-                    })
-                {
-                    eprintln!("Invalid constant override argument: {}", Paint::red(c.to_string()));
+            .map(|c: &String| match ConstantDefinition::parse_override(c) {
+                Ok(constant) => constant,
+                Err(e) => {
+                    eprintln!("Invalid constant override argument: {}", Paint::red(e));
                     std::process::exit(1);
                 }
-
-                (parts[0], str_to_bytes32(&parts[1][2..]))
             })
             .collect()
     });
 
+    // Configure the lints from `huff.toml` and the command line
+    let lints = match cli.get_lints() {
+        Ok(lints) => lints,
+        Err(e) => {
+            eprintln!("{}", Paint::red(e));
+            std::process::exit(1);
+        }
+    };
+
     // Parse the EVM version
     let evm_version = EVMVersion::from(cli.evm_version);
 
@@ -211,6 +513,13 @@ fn main() {
         _ => None,
     };
 
+    let file_provider: Arc<dyn FileProvider> = if cli.remote_imports {
+        let cache = RemoteCache::new(remote::DEFAULT_CACHE_DIR, remote::DEFAULT_LOCKFILE);
+        Arc::new(RemoteFileProvider::new(cache))
+    } else {
+        Arc::new(FileSystemFileProvider {})
+    };
+
     let compiler: Compiler = Compiler {
         evm_version: &evm_version,
         sources: Arc::clone(&sources),
@@ -220,11 +529,88 @@ fn main() {
         construct_args: cli.inputs,
         constant_overrides: constants,
         optimize: cli.optimize,
+        outline: cli.outline,
+        strict_size: cli.strict_size,
+        metadata_hash: cli.metadata_hash,
         bytecode: cli.bytecode,
         cached: use_cache,
-        file_provider: Arc::new(FileSystemFileProvider {}),
+        file_provider,
+        remappings: cli.remappings.unwrap_or_default(),
+        defines: cli.define.unwrap_or_default(),
+        bootstrap: BootstrapSettings {
+            memory_offset: cli.bootstrap_offset,
+            shim: cli.bootstrap_shim.clone(),
+            table: cli.bootstrap_table.clone(),
+        },
+        max_expansion_depth: cli.max_expansion_depth,
+        unique_labels: cli.unique_labels,
+        lints,
+        pipeline: Pipeline::default(),
+        builtins: CustomBuiltins::default(),
+        env: None,
     };
 
+    // Print the version, and the fingerprint artifacts compiled with the settings would carry
+    if cli.version {
+        println!("huffc {}", env!("CARGO_PKG_VERSION"));
+        if cli.verbose {
+            let fingerprint = compiler.fingerprint();
+            println!("{}", serde_json::to_string_pretty(&fingerprint).unwrap_or_default());
+        }
+        return
+    }
+
+    if cli.ast {
+        match compiler.grab_contracts() {
+            Ok(contracts) => {
+                println!("{}", serde_json::to_string_pretty(&contracts).unwrap_or_default())
+            }
+            Err(e) => {
+                tracing::error!(target: "cli", "PARSER ERRORED!");
+                report_diagnostics(cli.output_format, &compiler, &[], Some(&e));
+                std::process::exit(1);
+            }
+        }
+        return
+    }
+
+    if let Some(Commands::Doc { format, out }) = &cli.command {
+        match compiler.grab_contracts() {
+            Ok(contracts) => {
+                if let Err(e) = std::fs::create_dir_all(out) {
+                    eprintln!("{}", Paint::red(format!("Failed to create \"{out}\": {e}")));
+                    std::process::exit(1);
+                }
+                for (source, contract) in sources.iter().zip(&contracts) {
+                    let title = Path::new(source)
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().to_string())
+                        .unwrap_or_else(|| source.clone());
+                    let docs = ContractDocs::new(&title, contract);
+                    let (content, extension) = match format {
+                        DocFormat::Markdown => (docs.to_markdown(), "md"),
+                        DocFormat::Html => (docs.to_html(), "html"),
+                    };
+                    let path = Path::new(out).join(format!("{title}.{extension}"));
+                    if let Err(e) = std::fs::write(&path, content) {
+                        eprintln!(
+                            "{}",
+                            Paint::red(format!("Failed to write \"{}\": {e}", path.display()))
+                        );
+                        std::process::exit(1);
+                    }
+                    println!("{} {}", Paint::green("[DOC]"), path.display());
+                }
+            }
+            Err(e) => {
+                tracing::error!(target: "cli", "PARSER ERRORED!");
+                report_diagnostics(cli.output_format, &compiler, &[], Some(&e));
+                std::process::exit(1);
+            }
+        }
+        return
+    }
+
     if cli.label_indices {
         match compiler.grab_contracts() {
             Ok(contracts) => {
@@ -256,11 +642,13 @@ fn main() {
                         &evm_version,
                         macro_def,
                         contract,
+                        &compiler.codegen_settings(),
                         &mut vec![macro_def],
                         0,
                         &mut Vec::default(),
                         false,
                         None,
+                        None,
                     )
                     .unwrap();
 
@@ -299,24 +687,65 @@ fn main() {
             }
             Err(e) => {
                 tracing::error!(target: "cli", "PARSER ERRORED!");
-                eprintln!("{}", Paint::red(e));
+                report_diagnostics(cli.output_format, &compiler, &[], Some(&e));
+            }
+        }
+        return
+    }
+
+    if let Some(Commands::PredictAddress { deployer, salt, nonce }) = &cli.command {
+        let Ok(deployer) = deployer.parse::<Address>() else {
+            eprintln!("{}", Paint::red(format!("Invalid deployer address \"{deployer}\"")));
+            std::process::exit(1);
+        };
+        let deployment = match (salt, nonce) {
+            (_, Some(nonce)) => Deployment::Create { nonce: *nonce },
+            (Some(salt), _) => {
+                match parse_hex_literal(salt).or_else(|| parse_decimal_literal(salt)) {
+                    Some(salt) => Deployment::Create2 { salt },
+                    None => {
+                        eprintln!("{}", Paint::red(format!("Invalid salt \"{salt}\"")));
+                        std::process::exit(1);
+                    }
+                }
+            }
+            (None, None) => unreachable!("clap requires a salt or nonce"),
+        };
+        match compiler.execute() {
+            Ok(artifacts) => {
+                for artifact in &artifacts {
+                    let address = artifact.predict_address(deployer, &deployment);
+                    let address = to_checksum(&address, None);
+                    if artifacts.len() > 1 {
+                        println!("\"{}\" address: {address}", artifact.file.path);
+                    } else {
+                        println!("{address}");
+                    }
+                }
+            }
+            Err(e) => {
+                tracing::error!(target: "cli", "COMPILER ERRORED: {}", e);
+                report_diagnostics(cli.output_format, &compiler, &[], Some(&e));
                 std::process::exit(1);
             }
         }
         return
     }
 
-    if let Some(TestCommands::Test { format, match_ }) = cli.test {
+    if let Some(Commands::Test { format, match_ }) = cli.command {
         match compiler.grab_contracts() {
             Ok(contracts) => {
                 let match_ = Rc::new(match_);
+                let mut failed = false;
 
                 for contract in &contracts {
-                    let tester = HuffTester::new(contract, Rc::clone(&match_));
+                    let tester = HuffTester::new(contract, Rc::clone(&match_))
+                        .with_settings(compiler.codegen_settings());
 
                     let start = Instant::now();
                     match tester.execute() {
                         Ok(res) => {
+                            failed |= res.iter().any(|r| matches!(r.status, TestStatus::Revert));
                             print_test_report(res, ReportKind::from(&format), start);
                         }
                         Err(e) => {
@@ -325,10 +754,15 @@ fn main() {
                         }
                     };
                 }
+
+                // Exit with a non-zero status code if any test reverted so that CI picks it up
+                if failed {
+                    std::process::exit(1);
+                }
             }
             Err(e) => {
                 tracing::error!(target: "cli", "PARSER ERRORED!");
-                eprintln!("{}", Paint::red(e));
+                report_diagnostics(cli.output_format, &compiler, &[], Some(&e));
                 std::process::exit(1);
             }
         }
@@ -338,8 +772,8 @@ fn main() {
     // Create compiling spinner
     tracing::debug!(target: "cli", "[⠔] COMPILING");
     let mut sp: Option<Spinner> = None;
-    // If stdout is a TTY, create a spinner
-    if stdout_isatty() {
+    // If stdout is a TTY and not used for the diagnostics, create a spinner
+    if stdout_isatty() && cli.output_format == OutputFormat::Human {
         sp = Some(Spinner::new(Spinners::Dots, "Compiling...".into()));
     }
 
@@ -372,10 +806,20 @@ fn main() {
                             .collect::<Vec<Span>>(),
                     ),
                     token: None,
+                    hint: None,
                 });
                 tracing::error!(target: "cli", "COMPILER ERRORED: {}", e);
-                eprintln!("{}", Paint::red(format!("{e}")));
-                std::process::exit(1);
+                report_diagnostics(cli.output_format, &compiler, &[], Some(&e));
+            }
+
+            // Report the warnings raised while compiling
+            let warnings =
+                artifacts.iter().flat_map(|a| a.warnings.iter()).cloned().collect::<Vec<_>>();
+            report_diagnostics(cli.output_format, &compiler, &warnings, None);
+
+            // Write the metadata verification services retrieve by the CID in the trailer
+            if cli.metadata_hash == Some(MetadataHash::Ipfs) {
+                export_metadata(&artifacts, &cli.outputdir, cli.ipfs_api.as_deref());
             }
 
             if app.get_matches().is_present("interface") {
@@ -450,16 +894,28 @@ fn main() {
                                                 .then(|| format!(" \"{}\"", input.name))
                                                 .unwrap_or_default()
                                         ));
+                                        let tokens = Codegen::encode_typed_constructor_args(
+                                            vec![arg_input],
+                                            &[input.kind.clone()],
+                                        )
+                                        .unwrap_or_else(|e| {
+                                            eprintln!(
+                                                "{}",
+                                                Paint::red(format!(
+                                                    "{}",
+                                                    CompilerError::CodegenError(e)
+                                                ))
+                                            );
+                                            std::process::exit(1);
+                                        });
                                         let encoded =
-                                            Codegen::encode_constructor_args(vec![arg_input])
-                                                .iter()
-                                                .fold(String::default(), |acc, str| {
-                                                    let inner: Vec<u8> =
-                                                        ethers_core::abi::encode(&[str.clone()]);
-                                                    let hex_args: String =
-                                                        hex::encode(inner.as_slice());
-                                                    format!("{acc}{hex_args}")
-                                                });
+                                            tokens.iter().fold(String::default(), |acc, str| {
+                                                let inner: Vec<u8> =
+                                                    ethers_core::abi::encode(&[str.clone()]);
+                                                let hex_args: String =
+                                                    hex::encode(inner.as_slice());
+                                                format!("{acc}{hex_args}")
+                                            });
                                         appended_args.push_str(&encoded);
                                     }
                                 }
@@ -474,6 +930,7 @@ fn main() {
                         match Arc::get_mut(artifact) {
                             Some(art) => {
                                 art.bytecode = format!("{}{appended_args}", art.bytecode);
+                                art.initcode_hash = art.hash_initcode();
                             }
                             None => {
                                 tracing::warn!(target: "cli", "FAILED TO ACQUIRE MUTABLE REF TO ARTIFACT")
@@ -515,12 +972,138 @@ fn main() {
                         .for_each(|a| println!("\"{}\" runtime: {}", a.file.path, a.runtime)),
                 }
             }
+
+            if cli.initcode_hash {
+                match sources.len() {
+                    1 => {
+                        if cli.bytecode || cli.bin_runtime {
+                            println!("\ninitcode hash: {}", artifacts[0].initcode_hash)
+                        } else {
+                            println!("{}", artifacts[0].initcode_hash)
+                        }
+                    }
+                    _ => artifacts.iter().for_each(|a| {
+                        println!("\"{}\" initcode hash: {}", a.file.path, a.initcode_hash)
+                    }),
+                }
+            }
+
+            if cli.asm {
+                for artifact in &artifacts {
+                    if sources.len() > 1 {
+                        println!("\"{}\" assembly:", artifact.file.path);
+                    } else if cli.bytecode || cli.bin_runtime || cli.initcode_hash {
+                        println!("\nassembly:");
+                    }
+                    print!("{}", artifact.assembly());
+                }
+            }
+
+            if cli.storage_layout {
+                for artifact in &artifacts {
+                    if sources.len() > 1 {
+                        println!("\"{}\" storage layout:", artifact.file.path);
+                    } else if cli.bytecode || cli.bin_runtime || cli.initcode_hash || cli.asm {
+                        println!("\nstorage layout:");
+                    }
+                    // Format the storage layout nicely in a table
+                    let mut table = Table::new();
+                    table.load_preset(UTF8_FULL).apply_modifier(UTF8_ROUND_CORNERS);
+                    table
+                        .set_header(vec![
+                            Cell::new("Constant").fg(Color::Cyan),
+                            Cell::new("Storage slot").fg(Color::Cyan),
+                        ])
+                        .add_rows(artifact.storage_layout.storage.iter().map(|entry| {
+                            Row::from(vec![Cell::new(&entry.label), Cell::new(&entry.slot)])
+                        }));
+                    println!("{table}");
+                }
+            }
         }
         Err(e) => {
             tracing::error!(target: "cli", "COMPILER ERRORED: {}", e);
-            eprintln!("{}", Paint::red(format!("{e}")));
+            report_diagnostics(cli.output_format, &compiler, &[], Some(&e));
+        }
+    }
+}
+
+/// Writes the metadata JSON of each artifact next to where the artifact is exported, printing its
+/// IPFS CID, and pins the metadata and the sources it references if an IPFS API url is given
+fn export_metadata(artifacts: &[Arc<Artifact>], outputdir: &str, ipfs_api: Option<&str>) {
+    for artifact in artifacts {
+        let Some(metadata) = &artifact.metadata else { continue };
+        let name = artifact.file.path.to_uppercase().replacen("./", "", 1);
+        let path = Path::new(outputdir).join(format!("{name}.metadata.json"));
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, metadata));
+        if let Err(e) = written {
+            eprintln!("{}", Paint::red(format!("Failed to write \"{}\": {e}", path.display())));
             std::process::exit(1);
         }
+        let cid = ipfs_cid(metadata);
+        eprintln!("{} {} ({cid})", Paint::green("[METADATA]"), path.display());
+
+        let Some(api) = ipfs_api else { continue };
+        let files = included_files(&artifact.file)
+            .into_iter()
+            .map(|f| (f.path.clone(), f.source.clone().unwrap_or_default()))
+            .chain(std::iter::once((path.display().to_string(), metadata.clone())));
+        for (name, content) in files {
+            match pin(api, &name, content.as_bytes()) {
+                Ok(pinned) if pinned == ipfs_cid(&content) => {
+                    eprintln!("{} {name} ({pinned})", Paint::green("[PINNED]"));
+                }
+                Ok(pinned) => {
+                    eprintln!(
+                        "{}",
+                        Paint::yellow(format!(
+                            "Pinned \"{name}\" as {pinned}, but expected {}",
+                            ipfs_cid(&content)
+                        ))
+                    );
+                }
+                Err(e) => {
+                    eprintln!("{}", Paint::red(format!("Failed to pin \"{name}\": {e}")));
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
+
+/// Reports the warnings and the error of a compilation in the given format, exiting if it failed
+fn report_diagnostics(
+    format: OutputFormat,
+    compiler: &Compiler,
+    warnings: &[CompilerWarning],
+    error: Option<&CompilerError>,
+) {
+    warnings.iter().for_each(|w| tracing::warn!(target: "cli", "COMPILER WARNING: {:?}", w.kind));
+    let diagnostics = warnings
+        .iter()
+        .map(Diagnostic::from_warning)
+        .chain(error.into_iter().flat_map(Diagnostic::from_error))
+        .collect::<Vec<_>>();
+    match format {
+        OutputFormat::Human => render_stderr(&diagnostics),
+        OutputFormat::Json => diagnostics.iter().for_each(|d| eprintln!("{}", d.to_json())),
+        OutputFormat::Sarif => {
+            let rules = compiler
+                .lints
+                .rules()
+                .iter()
+                .map(|r| (r.id(), r.description()))
+                .chain(ERROR_CODES.iter().map(|c| (c.code, c.title)))
+                .collect::<Vec<_>>();
+            let report = to_sarif(&diagnostics, &rules, env!("CARGO_PKG_VERSION"));
+            println!("{}", serde_json::to_string_pretty(&report).unwrap_or_default());
+        }
+    }
+    if error.is_some() {
+        std::process::exit(1);
     }
 }
 
@@ -546,4 +1129,31 @@ impl Huff {
             }
         }
     }
+
+    /// Configures the lint rules from the `huff.toml` in the working directory, then the lint
+    /// flags
+    pub fn get_lints(&self) -> Result<Linter, String> {
+        let mut lints = Linter::default();
+        lints.configure(&LintConfig::load(".")?)?;
+        let flags = [
+            ("address-checksum", self.address_checksum),
+            ("stack-comments", self.stack_comments),
+            ("stack-effects", self.stack_effects),
+            ("stack-analysis", self.stack_analysis),
+            ("unused-macros", self.unused_macros),
+            ("unused-constants", self.unused_constants),
+        ];
+        for (id, severity) in flags {
+            if let Some(severity) = severity {
+                lints.set_severity(id, severity)?;
+            }
+        }
+        for lint in self.lints.iter().flatten() {
+            let (id, severity) = lint
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid lint \"{lint}\", expected `<id>=<severity>`"))?;
+            lints.set_severity(id, severity.parse()?)?;
+        }
+        Ok(lints)
+    }
 }
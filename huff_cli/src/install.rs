@@ -0,0 +1,65 @@
+use huff_utils::packages::{self, LockedPackage, PackageSpec, LIB_DIR};
+use std::{path::Path, process::Command};
+use yansi::Paint;
+
+/// Installs packages from git into `lib/`, recording their commits in the packages lockfile.
+///
+/// Without any packages, (re)installs every package in the lockfile at its locked commit.
+pub(crate) fn install(specs: &[String]) -> Result<(), String> {
+    let root = "./";
+    let mut locked = packages::read_lockfile(root);
+
+    let specs = if specs.is_empty() {
+        locked
+            .iter()
+            .map(|(name, p)| {
+                PackageSpec::new(name.clone(), p.url.clone(), Some(p.rev.clone()))
+                    .ok_or(format!("Invalid package \"{name}\" in the lockfile"))
+            })
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        specs
+            .iter()
+            .map(|s| PackageSpec::parse(s).ok_or(format!("Invalid package \"{s}\"")))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+
+    for spec in specs {
+        let dir = Path::new(root).join(LIB_DIR).join(&spec.name);
+        let dir_str = dir.to_string_lossy().to_string();
+        println!("{} {} from {}", Paint::blue("[INSTALL]"), spec.name, spec.url);
+
+        if dir.is_dir() {
+            if spec.rev.is_some() {
+                git(&["-C", &dir_str, "fetch", "--quiet", "--tags", "origin"])?;
+            }
+        } else {
+            git(&["clone", "--quiet", "--", &spec.url, &dir_str])?;
+        }
+        if let Some(rev) = &spec.rev {
+            git(&["-C", &dir_str, "checkout", "--quiet", rev, "--"])?;
+        }
+
+        let rev = git(&["-C", &dir_str, "rev-parse", "HEAD"])?;
+        println!("{} {} at {}", Paint::green("[INSTALLED]"), spec.name, rev);
+        locked.insert(spec.name, LockedPackage { url: spec.url, rev });
+    }
+
+    packages::write_lockfile(root, &locked)
+        .map_err(|e| format!("Failed to write \"{}\": {e}", packages::PACKAGES_LOCKFILE))
+}
+
+/// Runs a git command, returning its trimmed stdout
+fn git(args: &[&str]) -> Result<String, String> {
+    tracing::debug!(target: "install", "RUNNING git {}", args.join(" "));
+    let output =
+        Command::new("git").args(args).output().map_err(|e| format!("Failed to run git: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
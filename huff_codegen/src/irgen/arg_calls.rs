@@ -1,5 +1,10 @@
 use huff_utils::prelude::*;
-use std::str::FromStr;
+use std::{collections::BTreeMap, str::FromStr};
+
+use crate::{
+    irgen::{constants::immutable_gen, statements::statement_gen},
+    CodegenSettings,
+};
 
 // Arguments can be literals, labels, opcodes, or constants
 // !! IF THERE IS AMBIGUOUS NOMENCLATURE
@@ -8,16 +13,28 @@ use std::str::FromStr;
 
 /// Arg Call Bubbling
 #[allow(clippy::too_many_arguments)]
-pub fn bubble_arg_call(
+pub fn bubble_arg_call<'a>(
+    evm_version: &EVMVersion,
     arg_name: &str,
-    bytes: &mut Vec<(usize, Bytes)>,
-    macro_def: &MacroDefinition,
-    contract: &Contract,
-    scope: &mut [&MacroDefinition],
+    bytes: &mut Vec<IRInstruction>,
+    macro_def: &'a MacroDefinition,
+    contract: &'a Contract,
+    settings: &CodegenSettings,
+    scope: &mut [&'a MacroDefinition],
     offset: &mut usize,
     // mis: Parent macro invocations and their indices
     mis: &mut [(usize, MacroInvocation)],
     jump_table: &mut JumpTable,
+    label_indices: &mut LabelIndices,
+    label_definitions: &mut LabelDefinitions,
+    table_instances: &mut Jumps,
+    utilized_tables: &mut Vec<TableDefinition>,
+    macro_ranges: &mut Vec<MacroRange>,
+    source_spans: &mut Vec<BytecodeSpan>,
+    label_references: &mut Vec<usize>,
+    constants: &mut BTreeMap<String, String>,
+    circular_codesize_invocations: &mut CircularCodeSizeIndices,
+    expansions: &mut ExpansionCache,
 ) -> Result<(), CodegenError> {
     let starting_offset = *offset;
 
@@ -37,11 +54,9 @@ pub fn bubble_arg_call(
                     MacroArg::Literal(l) => {
                         tracing::info!(target: "codegen", "GOT LITERAL {} ARG FROM MACRO INVOCATION", bytes32_to_string(l, false));
 
-                        let hex_literal: String = bytes32_to_string(l, false);
-                        let push_bytes = format!("{:02x}{hex_literal}", 95 + hex_literal.len() / 2);
-                        let b = Bytes(push_bytes);
-                        *offset += b.0.len() / 2;
-                        bytes.push((starting_offset, b));
+                        let push = IRInstructionType::push(bytes32_to_string(l, false));
+                        *offset += push.size();
+                        bytes.push(IRInstruction::at(starting_offset, push));
                     }
                     MacroArg::ArgCall(ac) => {
                         tracing::info!(target: "codegen", "GOT ARG CALL \"{}\" ARG FROM MACRO INVOCATION", ac);
@@ -63,6 +78,7 @@ pub fn bubble_arg_call(
                                     ),
                                     span: bubbled_macro_invocation.span.clone(),
                                     token: None,
+                                    hint: None,
                                 })
                             }
                         };
@@ -70,50 +86,109 @@ pub fn bubble_arg_call(
                         let ac_ = &ac.to_string();
                         return if last_mi.1.macro_name.eq(&macro_def.name) {
                             bubble_arg_call(
+                                evm_version,
                                 ac_,
                                 bytes,
                                 bubbled_macro_invocation,
                                 contract,
+                                settings,
                                 new_scope,
                                 offset,
                                 &mut mis[..mis_len.saturating_sub(1)],
                                 jump_table,
+                                label_indices,
+                                label_definitions,
+                                table_instances,
+                                utilized_tables,
+                                macro_ranges,
+                                source_spans,
+                                label_references,
+                                constants,
+                                circular_codesize_invocations,
+                                expansions,
                             )
                         } else {
                             bubble_arg_call(
+                                evm_version,
                                 ac_,
                                 bytes,
                                 bubbled_macro_invocation,
                                 contract,
+                                settings,
                                 new_scope,
                                 offset,
                                 mis,
                                 jump_table,
+                                label_indices,
+                                label_definitions,
+                                table_instances,
+                                utilized_tables,
+                                macro_ranges,
+                                source_spans,
+                                label_references,
+                                constants,
+                                circular_codesize_invocations,
+                                expansions,
                             )
                         }
                     }
+                    MacroArg::Opcode(o) => {
+                        tracing::info!(target: "codegen", "GOT OPCODE {} ARG FROM MACRO INVOCATION", o);
+                        *offset += 1;
+                        bytes.push(IRInstruction::at(
+                            starting_offset,
+                            IRInstructionType::Opcode(*o),
+                        ));
+                    }
+                    MacroArg::MacroCall(inner_mi) => {
+                        tracing::info!(target: "codegen", "GOT MACRO CALL \"{}\" ARG FROM MACRO INVOCATION", inner_mi.macro_name);
+
+                        // The nested invocation was written in the invoking macro, so expand it
+                        // within that macro's scope
+                        let mut outer_scope = scope[..scope.len().saturating_sub(1)].to_vec();
+                        let mut outer_mis = mis[..mis.len().saturating_sub(1)].to_vec();
+                        let statement = Statement {
+                            ty: StatementType::MacroInvocation(inner_mi.clone()),
+                            span: inner_mi.span.clone(),
+                        };
+                        let mut push_bytes = statement_gen(
+                            evm_version,
+                            &statement,
+                            contract,
+                            settings,
+                            macro_def,
+                            &mut outer_scope,
+                            offset,
+                            &mut outer_mis,
+                            jump_table,
+                            label_indices,
+                            label_definitions,
+                            table_instances,
+                            utilized_tables,
+                            macro_ranges,
+                            source_spans,
+                            label_references,
+                            constants,
+                            circular_codesize_invocations,
+                            expansions,
+                            starting_offset,
+                        )?;
+                        bytes.append(&mut push_bytes);
+                    }
                     MacroArg::Ident(iden) => {
                         tracing::debug!(target: "codegen", "Found MacroArg::Ident IN \"{}\" Macro Invocation: \"{}\"!", macro_invoc.1.macro_name, iden);
 
                         // Check for a constant first
-                        if let Some(constant) = contract
-                            .constants
-                            .lock()
-                            .map_err(|_| {
-                                CodegenError::new(
-                                    CodegenErrorKind::LockingError,
-                                    AstSpan(vec![]),
-                                    None,
-                                )
-                            })?
-                            .iter()
-                            .find(|const_def| const_def.name.eq(iden))
+                        let contract_constants = contract.constants.lock().map_err(|_| {
+                            CodegenError::new(CodegenErrorKind::LockingError, AstSpan(vec![]), None)
+                        })?;
+                        if let Some(constant) =
+                            contract_constants.iter().find(|const_def| const_def.name.eq(iden))
                         {
                             tracing::info!(target: "codegen", "ARGCALL IS CONSTANT: {:?}", constant);
-                            let push_bytes = match &constant.value {
-                                ConstVal::Literal(l) => {
-                                    let hex_literal: String = bytes32_to_string(l, false);
-                                    format!("{:02x}{hex_literal}", 95 + hex_literal.len() / 2)
+                            let hex_literal = match &constant.value {
+                                ConstVal::Literal(l) | ConstVal::StorageSlot(l) => {
+                                    bytes32_to_string(l, false)
                                 }
                                 ConstVal::FreeStoragePointer(fsp) => {
                                     // If this is reached in codegen stage,
@@ -124,28 +199,63 @@ pub fn bubble_arg_call(
                                         kind: CodegenErrorKind::StoragePointersNotDerived,
                                         span: AstSpan(vec![]),
                                         token: None,
+                                        hint: None,
+                                    })
+                                }
+                                ConstVal::Expression(expr) => {
+                                    let l = expr.evaluate(&contract_constants, &constant.span)?;
+                                    bytes32_to_string(&l, false)
+                                }
+                                ConstVal::Env(var) => {
+                                    tracing::error!(target: "codegen", "ENVIRONMENT VARIABLE NOT RESOLVED FOR \"{}\"", var);
+                                    return Err(CodegenError {
+                                        kind: CodegenErrorKind::MissingEnvironmentVariable(
+                                            var.to_string(),
+                                        ),
+                                        span: constant.span.clone(),
+                                        token: None,
+                                        hint: None,
                                     })
                                 }
                             };
-                            *offset += push_bytes.len() / 2;
-                            tracing::info!(target: "codegen", "OFFSET: {}, PUSH BYTES: {:?}", offset, push_bytes);
-                            bytes.push((starting_offset, Bytes(push_bytes)));
+                            let push = IRInstructionType::Push(
+                                hex_literal.len() / 2,
+                                IRValue::Constant(constant.name.clone()),
+                            );
+                            constants.insert(constant.name.clone(), hex_literal);
+                            *offset += push.size();
+                            tracing::info!(target: "codegen", "OFFSET: {}, PUSH: {:?}", offset, push);
+                            bytes.push(IRInstruction::at(starting_offset, push));
+                        } else if let Some(index) =
+                            contract.immutables.iter().position(|i| i.name.eq(iden))
+                        {
+                            tracing::info!(target: "codegen", "ARGCALL IS IMMUTABLE: {}", iden);
+                            let push = immutable_gen(index);
+                            *offset += push.size();
+                            bytes.push(IRInstruction::at(starting_offset, push));
                         } else if let Ok(o) = Opcode::from_str(iden) {
                             tracing::debug!(target: "codegen", "Found Opcode: {}", o);
-                            let b = Bytes(o.to_string());
-                            *offset += b.0.len() / 2;
-                            bytes.push((starting_offset, b));
+                            *offset += 1;
+                            bytes.push(IRInstruction::at(
+                                starting_offset,
+                                IRInstructionType::Opcode(o),
+                            ));
                         } else {
                             tracing::debug!(target: "codegen", "Found Label Call: {}", iden);
 
-                            // This should be equivalent to a label call.
-                            bytes.push((*offset, Bytes(format!("{}xxxx", Opcode::Push2))));
+                            // This should be equivalent to a label call, but the label is looked
+                            // up from the macro the argument was written in
+                            bytes.push(IRInstruction::at(
+                                *offset,
+                                IRInstructionType::Push(2, IRValue::Label(iden.to_owned())),
+                            ));
                             jump_table.insert(
                                 *offset,
                                 vec![Jump {
                                     label: iden.to_owned(),
                                     bytecode_index: 0,
                                     span: macro_invoc.1.span.clone(),
+                                    scope_depth: Some(scope.len().saturating_sub(1)),
                                 }],
                             );
                             *offset += 3;
@@ -156,7 +266,19 @@ pub fn bubble_arg_call(
                 tracing::warn!(target: "codegen", "\"{}\" FOUND IN MACRO DEF BUT NOT IN MACRO INVOCATION!", arg_name);
             }
         } else {
-            tracing::warn!(target: "codegen", "\"{}\" NOT IN ARG LIST", arg_name);
+            tracing::error!(target: "codegen", "\"{}\" NOT IN ARG LIST", arg_name);
+            return Err(CodegenError {
+                kind: CodegenErrorKind::UndefinedArgument(
+                    arg_name.to_string(),
+                    macro_def.name.clone(),
+                ),
+                span: macro_invoc.1.span.clone(),
+                token: None,
+                hint: did_you_mean(
+                    arg_name,
+                    macro_def.parameters.iter().filter_map(|p| p.name.as_deref()),
+                ),
+            })
         }
     } else {
         // This is a label call
@@ -167,9 +289,17 @@ pub fn bubble_arg_call(
         };
         jump_table.insert(
             mis.last().map(|mi| mi.0).unwrap_or_else(|| 0),
-            vec![Jump { label: arg_name.to_owned(), bytecode_index: 0, span: new_span }],
+            vec![Jump {
+                label: arg_name.to_owned(),
+                bytecode_index: 0,
+                span: new_span,
+                scope_depth: None,
+            }],
         );
-        bytes.push((*offset, Bytes(format!("{}xxxx", Opcode::Push2))));
+        bytes.push(IRInstruction::at(
+            *offset,
+            IRInstructionType::Push(2, IRValue::Label(arg_name.to_owned())),
+        ));
         *offset += 3;
     }
 
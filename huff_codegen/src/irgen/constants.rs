@@ -1,14 +1,20 @@
 use huff_utils::prelude::{
-    literal_gen, AstSpan, CodegenError, CodegenErrorKind, ConstVal, Contract, EVMVersion,
+    bytes32_to_string, did_you_mean, AstSpan, CodegenError, CodegenErrorKind, ConstVal, Contract,
+    EVMVersion, IRInstructionType, IRValue,
 };
+use std::collections::BTreeMap;
 
-/// Transforms a constant definition into it's respective bytecode
+/// Transforms a constant definition into the push of its value, which is recorded in `values`
+///
+/// The constant is pushed with the narrowest push its value fits in, zero with `PUSH0` if the EVM
+/// version supports it.
 pub fn constant_gen(
     evm_version: &EVMVersion,
     name: &str,
     contract: &Contract,
     ir_byte_span: &AstSpan,
-) -> Result<String, CodegenError> {
+    values: &mut BTreeMap<String, String>,
+) -> Result<IRInstructionType, CodegenError> {
     // Get the first `ConstantDefinition` that matches the constant's name
     let constants = contract
         .constants
@@ -16,6 +22,9 @@ pub fn constant_gen(
         .map_err(|_| CodegenError::new(CodegenErrorKind::LockingError, AstSpan(vec![]), None))?;
     let constant = if let Some(m) = constants.iter().find(|const_def| const_def.name.eq(&name)) {
         m
+    } else if let Some(index) = contract.immutables.iter().position(|i| i.name.eq(&name)) {
+        tracing::info!(target: "codegen", "FOUND IMMUTABLE DEFINITION: {}", name);
+        return Ok(immutable_gen(index))
     } else {
         tracing::error!(target: "codegen", "MISSING CONSTANT DEFINITION \"{}\"", name);
 
@@ -23,6 +32,13 @@ pub fn constant_gen(
             kind: CodegenErrorKind::MissingConstantDefinition(name.to_string()),
             span: ir_byte_span.clone(),
             token: None,
+            hint: did_you_mean(
+                name,
+                constants
+                    .iter()
+                    .map(|c| c.name.as_str())
+                    .chain(contract.immutables.iter().map(|i| i.name.as_str())),
+            ),
         })
     };
 
@@ -30,8 +46,8 @@ pub fn constant_gen(
     // Should always be a `Literal` if storage pointers were derived in the AST
     // prior to generating the IR bytes.
     tracing::info!(target: "codegen", "FOUND CONSTANT DEFINITION: {}", constant.name);
-    let push_bytes = match &constant.value {
-        ConstVal::Literal(l) => literal_gen(evm_version, l),
+    let value = match &constant.value {
+        ConstVal::Literal(l) | ConstVal::StorageSlot(l) => *l,
         ConstVal::FreeStoragePointer(fsp) => {
             // If this is reached in codegen stage, the `derive_storage_pointers`
             // method was not called on the AST.
@@ -40,9 +56,31 @@ pub fn constant_gen(
                 kind: CodegenErrorKind::StoragePointersNotDerived,
                 span: constant.span.clone(),
                 token: None,
+                hint: None,
+            })
+        }
+        ConstVal::Expression(expr) => expr.evaluate(&constants, &constant.span)?,
+        ConstVal::Env(var) => {
+            // If this is reached in codegen stage, the `resolve_env_constants`
+            // method was not called on the AST.
+            tracing::error!(target: "codegen", "ENVIRONMENT VARIABLE NOT RESOLVED FOR \"{}\"", var);
+            return Err(CodegenError {
+                kind: CodegenErrorKind::MissingEnvironmentVariable(var.to_string()),
+                span: constant.span.clone(),
+                token: None,
+                hint: None,
             })
         }
     };
 
-    Ok(push_bytes)
+    let hex = bytes32_to_string(&value, false);
+    let width = if hex == "00" && evm_version.has_push0() { 0 } else { hex.len() / 2 };
+    values.insert(constant.name.clone(), hex);
+    Ok(IRInstructionType::Push(width, IRValue::Constant(constant.name.clone())))
+}
+
+/// Generates the push of an immutable, emitted as the placeholder filled by
+/// [fill_immutable_placeholders](crate::Codegen::fill_immutable_placeholders)
+pub fn immutable_gen(index: usize) -> IRInstructionType {
+    IRInstructionType::Push(32, IRValue::Immutable(index))
 }
@@ -0,0 +1,133 @@
+use huff_utils::prelude::*;
+
+/// Emits the bytecode of an [IRProgram], followed by its tables
+///
+/// Program counters are assigned to the labels as the instructions are laid out, and the labels,
+/// tables and constants referenced by pushes and table entries are filled in. Offsets that don't
+/// fit in the bytes reserved for them are an error, rather than being truncated. Values only
+/// known once the contract is deployed are emitted as placeholders, filled by
+/// [churn](crate::Codegen::churn).
+pub fn emit(program: &IRProgram) -> Result<String, CodegenError> {
+    let label_indices = program.label_indices();
+    let table_offsets = program.table_offsets();
+
+    let label = |name: &str, span: &AstSpan| {
+        label_indices.get(name).copied().ok_or_else(|| {
+            tracing::error!(target: "codegen", "Definition not found for label: \"{}\"", name);
+            CodegenError {
+                kind: CodegenErrorKind::UnmatchedJumpLabel,
+                span: span.clone(),
+                token: None,
+                hint: did_you_mean(name, label_indices.keys().map(String::as_str)),
+            }
+        })
+    };
+
+    let fill = |name: &str, offset: usize, width: usize, span: &AstSpan| {
+        let needed = bytes_needed(offset);
+        if needed > width {
+            tracing::error!(target: "codegen", "OFFSET OF \"{}\" EXCEEDS ITS PUSH{}", name, width);
+            return Err(CodegenError {
+                kind: CodegenErrorKind::OffsetExceedsPushWidth(name.to_string(), width, needed),
+                span: span.clone(),
+                token: None,
+                hint: None,
+            })
+        }
+        Ok(format!("{offset:0w$x}", w = width * 2))
+    };
+
+    let mut bytecode = String::new();
+    for instruction in &program.instructions {
+        match &instruction.ty {
+            IRInstructionType::Opcode(opcode) => bytecode.push_str(&opcode.to_string()),
+            IRInstructionType::Push(width, value) => {
+                let data = match value {
+                    IRValue::Literal(data) => data.clone(),
+                    IRValue::Label(name) => {
+                        fill(name, label(name, &instruction.span)?, *width, &instruction.span)?
+                    }
+                    IRValue::TableStart(name) => match table_offsets.get(name) {
+                        Some(offset) => fill(name, *offset, *width, &instruction.span)?,
+                        None => {
+                            tracing::error!(
+                                target: "codegen",
+                                "Jump table offset not present for jump label \"{}\"",
+                                name
+                            );
+                            "xx".repeat(*width)
+                        }
+                    },
+                    IRValue::Constant(name) => {
+                        let Some(value) = program.constants.get(name) else {
+                            tracing::error!(target: "codegen", "Constant \"{}\" not defined", name);
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::MissingConstantDefinition(name.clone()),
+                                span: instruction.span.clone(),
+                                token: None,
+                                hint: None,
+                            })
+                        };
+                        let value = value.trim_start_matches("00");
+                        if value.len() > width * 2 {
+                            tracing::error!(target: "codegen", "VALUE OF \"{}\" EXCEEDS ITS PUSH{}", name, width);
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::OffsetExceedsPushWidth(
+                                    name.clone(),
+                                    *width,
+                                    value.len() / 2,
+                                ),
+                                span: instruction.span.clone(),
+                                token: None,
+                                hint: None,
+                            })
+                        }
+                        format!("{value:0>w$}", w = width * 2)
+                    }
+                    IRValue::Immutable(index) => {
+                        format!("{:z>w$}", format!("{index:04x}"), w = width * 2)
+                    }
+                    IRValue::DynArgLength(index) => dyn_arg_length_placeholder(*index),
+                    IRValue::DynArgOffset(index) => dyn_arg_offset_placeholder(*index),
+                    IRValue::Codesize(name) => {
+                        tracing::error!(
+                            target: "codegen",
+                            "Codesize of macro \"{}\" not filled",
+                            name
+                        );
+                        "cc".repeat(*width)
+                    }
+                };
+                bytecode.push_str(&format!("{:02x}{data}", 0x5f + width));
+            }
+            IRInstructionType::Label(_) => {}
+            IRInstructionType::Raw(hex) => bytecode.push_str(hex),
+        }
+    }
+
+    for table in &program.tables {
+        tracing::info!(target: "codegen", "GENERATING BYTECODE FOR TABLE: \"{}\"", table.name);
+        for entry in &table.entries {
+            match entry {
+                IRTableEntry::Label(name) => {
+                    let width = if table.packed { 0x02 } else { 0x20 };
+                    bytecode.push_str(&fill(name, label(name, &table.span)?, width, &table.span)?);
+                }
+                IRTableEntry::Code(code) => bytecode.push_str(code),
+            }
+        }
+    }
+
+    Ok(bytecode)
+}
+
+/// The placeholder the length of the dynamic constructor argument at `index` is emitted as
+pub fn dyn_arg_length_placeholder(index: usize) -> String {
+    format!("x{index:02x}l")
+}
+
+/// The placeholder the code offset of the contents of the dynamic constructor argument at `index`
+/// is emitted as
+pub fn dyn_arg_offset_placeholder(index: usize) -> String {
+    format!("x{index:02x}o")
+}
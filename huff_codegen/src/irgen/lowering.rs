@@ -0,0 +1,95 @@
+use huff_utils::prelude::*;
+use std::collections::BTreeMap;
+
+/// Lowers the expanded code of a macro into an [IRProgram]
+///
+/// The instructions are generated as the macro is expanded, with the pushes of labels, tables and
+/// constants referencing them by name. Lowering lays out the tables the code uses after it.
+pub fn lower(res: &BytecodeRes) -> Result<IRProgram, CodegenError> {
+    let offset = res.instructions.first().and_then(|i| i.origin).unwrap_or_default();
+
+    // Table entries reference the label instruction at the program counter of their label
+    let labels = res
+        .instructions
+        .iter()
+        .filter_map(|i| match (&i.ty, i.origin) {
+            (IRInstructionType::Label(name), Some(pc)) => Some((pc, name.clone())),
+            _ => None,
+        })
+        .collect::<BTreeMap<usize, String>>();
+
+    let tables = res
+        .utilized_tables
+        .iter()
+        .map(|jt| lower_table(jt, &res.label_indices, &labels))
+        .collect::<Result<Vec<IRTable>, CodegenError>>()?;
+
+    Ok(IRProgram {
+        offset,
+        instructions: res.instructions.clone(),
+        tables,
+        constants: res.constants.clone(),
+    })
+}
+
+/// Lowers a table used by the code, referencing the labels it contains by their name
+fn lower_table(
+    jt: &TableDefinition,
+    label_indices: &LabelIndices,
+    labels: &BTreeMap<usize, String>,
+) -> Result<IRTable, CodegenError> {
+    let size = hex_to_usize(&bytes32_to_string(&jt.size, false)).map_err(|e| {
+        tracing::error!(target: "codegen", "Errored converting bytes32 to str. Bytes {:?} with error: {:?}", jt.size, e);
+        CodegenError {
+            kind: CodegenErrorKind::UsizeConversion(format!("{:?}", jt.size)),
+            span: jt.span.clone(),
+            token: None,
+            hint: None,
+        }
+    })?;
+    let packed = matches!(jt.kind, TableKind::JumpTablePacked);
+
+    let entries = jt
+        .statements
+        .iter()
+        .map(|s| match &s.ty {
+            StatementType::LabelCall(label) => {
+                let Some(offset) = label_indices.get(label) else {
+                    tracing::error!(target: "codegen", "Definition not found for Jump Table Label: \"{}\"", label);
+                    return Err(CodegenError {
+                        kind: CodegenErrorKind::UnmatchedJumpLabel,
+                        span: s.span.clone(),
+                        token: None,
+                        hint: did_you_mean(label, label_indices.keys().map(String::as_str)),
+                    })
+                };
+                if let Some(name) = labels.get(offset) {
+                    Ok(IRTableEntry::Label(name.clone()))
+                } else {
+                    let hex = format_even_bytes(format!("{offset:02x}"));
+                    Ok(IRTableEntry::Code(pad_n_bytes(&hex, if packed { 0x02 } else { 0x20 })))
+                }
+            }
+            StatementType::Code(code) => {
+                // Check if code length is even
+                if code.len() % 2 != 0 {
+                    return Err(CodegenError {
+                        kind: CodegenErrorKind::InvalidCodeLength(code.len()),
+                        span: s.span.clone(),
+                        token: None,
+                        hint: None,
+                    })
+                }
+                Ok(IRTableEntry::Code(code.clone()))
+            }
+            _ => Err(CodegenError {
+                kind: CodegenErrorKind::InvalidMacroStatement,
+                span: jt.span.clone(),
+                token: None,
+                hint: None,
+            }),
+        })
+        .collect::<Result<Vec<IRTableEntry>, CodegenError>>()?;
+
+    Ok(IRTable { name: jt.name.clone(), size, packed, entries, span: jt.span.clone() })
+}
@@ -0,0 +1,55 @@
+use huff_utils::prelude::*;
+
+use crate::CodegenSettings;
+
+/// Gets the key the expansion of the invocation on top of `mis` is cached by, `None` if its
+/// expansion can't be reused
+///
+/// An expansion only depends on its offset through the program counters it records, except for
+/// labels made unique by the offset of their invocation.
+pub fn expansion_key(
+    contract: &Contract,
+    settings: &CodegenSettings,
+    scope: &[&MacroDefinition],
+    mis: &[(usize, MacroInvocation)],
+) -> Option<(Vec<String>, Vec<Vec<MacroArg>>)> {
+    let unique_labels = settings.unique_labels ||
+        contract.macros.iter().any(|m| {
+            m.decorator.as_ref().map_or(false, |d| d.flags.contains(&DecoratorFlag::UniqueLabels))
+        });
+    if unique_labels {
+        return None
+    }
+    Some((
+        scope.iter().map(|m| m.name.clone()).collect(),
+        mis.iter().map(|(_, mi)| mi.args.clone()).collect(),
+    ))
+}
+
+/// Moves the code of an expansion cached at offset `from` to offset `to`
+///
+/// The pushes of its labels reference them by name, so only its program counters move.
+pub fn relocate_expansion(res: &BytecodeRes, from: usize, to: usize) -> BytecodeRes {
+    let shift = |pc: usize| pc - from + to;
+    let mut res = res.clone();
+
+    for instruction in res.instructions.iter_mut() {
+        instruction.origin = instruction.origin.map(shift);
+    }
+    res.label_references.iter_mut().for_each(|pc| *pc = shift(*pc));
+    res.label_indices.values_mut().for_each(|pc| *pc = shift(*pc));
+    res.defined_labels.values_mut().for_each(|pc| *pc = shift(*pc));
+    for jump in res.unmatched_jumps.iter_mut().chain(res.table_instances.iter_mut()) {
+        jump.bytecode_index = shift(jump.bytecode_index);
+    }
+    for range in res.macro_ranges.iter_mut() {
+        range.start = shift(range.start);
+        range.end = shift(range.end);
+        range.labels.values_mut().for_each(|pc| *pc = shift(*pc));
+    }
+    for span in res.source_spans.iter_mut() {
+        span.start = shift(span.start);
+        span.end = shift(span.end);
+    }
+    res
+}
@@ -7,7 +7,25 @@ pub mod statements;
 /// Argument Call Module
 pub mod arg_calls;
 
+/// Repeat Expansion Module
+pub mod repeats;
+
+/// IR Lowering Module
+pub mod lowering;
+
+/// Bytecode Emission Module
+pub mod emission;
+
+/// Relocation Module
+pub mod relocation;
+
+/// Expansion Memoization Module
+pub mod memoization;
+
 /// Prelude wraps common utilities.
 pub mod prelude {
-    pub use super::{arg_calls::*, constants::*, statements::*};
+    pub use super::{
+        arg_calls::*, constants::*, emission::*, lowering::*, relocation::*, repeats::*,
+        statements::*,
+    };
 }
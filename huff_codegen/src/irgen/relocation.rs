@@ -0,0 +1,66 @@
+use huff_utils::prelude::*;
+use std::collections::BTreeMap;
+
+/// Moves the program counters of the expanded code to where its instructions ended up in an
+/// [IRProgram] passes transformed, so the source spans and macro ranges match the emitted code
+///
+/// A program counter moves to the first instruction lowered from it or from any code following
+/// it, instructions the passes added not being attributed to the statements around them. Labels
+/// the passes removed are dropped.
+pub fn relocate(res: &mut BytecodeRes, program: &IRProgram) {
+    let mut moved: Vec<(usize, usize)> = vec![];
+    let mut labels: BTreeMap<usize, usize> = BTreeMap::new();
+    let mut label_references = vec![];
+    let mut instructions = vec![];
+    let mut pc = program.offset;
+    for instruction in &program.instructions {
+        if let Some(origin) = instruction.origin {
+            moved.push((origin, pc));
+            if matches!(instruction.ty, IRInstructionType::Label(_)) {
+                labels.insert(origin, pc);
+            }
+        }
+        if matches!(instruction.ty, IRInstructionType::Push(_, IRValue::Label(_))) {
+            label_references.push(pc);
+        }
+        instructions.push(IRInstruction { origin: Some(pc), ..instruction.clone() });
+        pc += instruction.ty.size();
+    }
+    let end = pc;
+
+    // Instructions may be reordered, each program counter moves to the lowest one following it
+    moved.sort_unstable();
+    for i in (1..moved.len()).rev() {
+        moved[i - 1].1 = moved[i - 1].1.min(moved[i].1);
+    }
+    let move_pc = |pc: usize| {
+        let i = moved.partition_point(|(origin, _)| *origin < pc);
+        moved.get(i).map_or(end, |(_, moved)| *moved)
+    };
+
+    // Labels move with the label instruction at their program counter
+    let move_labels = |indices: &mut LabelIndices| {
+        indices.retain(|_, pc| labels.contains_key(pc));
+        indices.values_mut().for_each(|pc| *pc = labels[pc]);
+    };
+    move_labels(&mut res.label_indices);
+    move_labels(&mut res.defined_labels);
+
+    for range in res.macro_ranges.iter_mut() {
+        range.start = move_pc(range.start);
+        range.end = move_pc(range.end);
+        move_labels(&mut range.labels);
+    }
+    for span in res.source_spans.iter_mut() {
+        span.start = move_pc(span.start);
+        span.end = move_pc(span.end);
+    }
+    res.source_spans.retain(|span| span.start < span.end);
+    for jump in res.unmatched_jumps.iter_mut().chain(res.table_instances.iter_mut()) {
+        jump.bytecode_index = move_pc(jump.bytecode_index);
+    }
+
+    res.instructions = instructions;
+    res.constants = program.constants.clone();
+    res.label_references = label_references;
+}
@@ -0,0 +1,104 @@
+use ethers_core::types::U256;
+use huff_utils::prelude::{
+    AstSpan, CodegenError, CodegenErrorKind, Contract, MacroArg, RepeatBlock, Statement,
+    StatementType, MAX_REPEAT_COUNT,
+};
+use std::borrow::Cow;
+
+/// Unrolls the `__REPEAT` blocks of a list of statements
+///
+/// Each block is replaced by `count` copies of its body, with arg calls to the index variable
+/// replaced by the index of the iteration. Statements without repeats are returned as-is.
+pub fn expand_repeats<'a>(
+    statements: &'a [Statement],
+    contract: &Contract,
+) -> Result<Cow<'a, [Statement]>, CodegenError> {
+    if !statements.iter().any(has_repeat) {
+        return Ok(Cow::Borrowed(statements))
+    }
+
+    let mut expanded = Vec::with_capacity(statements.len());
+    for statement in statements {
+        match &statement.ty {
+            StatementType::Repeat(r) => {
+                let count = repeat_count(r, contract)?;
+                tracing::info!(target: "codegen", "UNROLLING REPEAT {} TIMES", count);
+                for i in 0..count {
+                    let mut body = r.statements.clone();
+                    if let Some(index) = &r.index {
+                        substitute_index(&mut body, index, i);
+                    }
+                    expanded.extend(expand_repeats(&body, contract)?.into_owned());
+                }
+            }
+            StatementType::Label(l) => {
+                let mut label = l.clone();
+                label.inner = expand_repeats(&l.inner, contract)?.into_owned();
+                expanded.push(Statement {
+                    ty: StatementType::Label(label),
+                    span: statement.span.clone(),
+                });
+            }
+            _ => expanded.push(statement.clone()),
+        }
+    }
+    Ok(Cow::Owned(expanded))
+}
+
+/// Whether a statement is or contains a `__REPEAT` block
+fn has_repeat(statement: &Statement) -> bool {
+    match &statement.ty {
+        StatementType::Repeat(_) => true,
+        StatementType::Label(l) => l.inner.iter().any(has_repeat),
+        _ => false,
+    }
+}
+
+/// Evaluates the number of iterations of a `__REPEAT` block
+fn repeat_count(r: &RepeatBlock, contract: &Contract) -> Result<usize, CodegenError> {
+    let constants = contract
+        .constants
+        .lock()
+        .map_err(|_| CodegenError::new(CodegenErrorKind::LockingError, AstSpan(vec![]), None))?;
+    let count = U256::from_big_endian(&r.count.evaluate(&constants, &r.span)?);
+    if count > U256::from(MAX_REPEAT_COUNT) {
+        tracing::error!(target: "codegen", "REPEAT COUNT {:#x} EXCEEDS MAXIMUM", count);
+        return Err(CodegenError::new(
+            CodegenErrorKind::InvalidRepeatCount(format!("{count:#x}")),
+            r.span.clone(),
+            None,
+        ))
+    }
+    Ok(count.as_usize())
+}
+
+/// Replaces arg calls to the index variable with the literal index
+///
+/// Nested repeats declaring an index of the same name shadow the outer index.
+fn substitute_index(statements: &mut [Statement], index: &str, i: usize) {
+    fn substitute_args(args: &mut [MacroArg], index: &str, literal: [u8; 32]) {
+        for arg in args {
+            match arg {
+                MacroArg::ArgCall(name) if name == index => *arg = MacroArg::Literal(literal),
+                MacroArg::MacroCall(mi) => substitute_args(&mut mi.args, index, literal),
+                _ => {}
+            }
+        }
+    }
+
+    let mut literal = [0u8; 32];
+    U256::from(i).to_big_endian(&mut literal);
+    for statement in statements {
+        match &mut statement.ty {
+            StatementType::ArgCall(name) if name == index => {
+                statement.ty = StatementType::Literal(literal)
+            }
+            StatementType::MacroInvocation(mi) => substitute_args(&mut mi.args, index, literal),
+            StatementType::Label(l) => substitute_index(&mut l.inner, index, i),
+            StatementType::Repeat(r) if r.index.as_deref() != Some(index) => {
+                substitute_index(&mut r.statements, index, i)
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,24 +1,38 @@
 use huff_utils::prelude::*;
+use std::collections::BTreeMap;
 
-use crate::Codegen;
+use crate::{
+    irgen::memoization::{expansion_key, relocate_expansion},
+    Codegen, CodegenSettings,
+};
 
-/// Generates the respective Bytecode for a given Statement
+/// Generates the instructions of a given Statement
+///
+/// Pushes of labels start out referencing the label by its name and are resolved by
+/// [fill_unmatched](Codegen::fill_unmatched), once the label is found in scope.
 #[allow(clippy::too_many_arguments)]
 pub fn statement_gen<'a>(
     evm_version: &EVMVersion,
     s: &Statement,
     contract: &'a Contract,
+    settings: &CodegenSettings,
     macro_def: &MacroDefinition,
     scope: &mut Vec<&'a MacroDefinition>,
     offset: &mut usize,
     mis: &mut Vec<(usize, MacroInvocation)>,
     jump_table: &mut JumpTable,
     label_indices: &mut LabelIndices,
+    label_definitions: &mut LabelDefinitions,
     table_instances: &mut Jumps,
     utilized_tables: &mut Vec<TableDefinition>,
+    macro_ranges: &mut Vec<MacroRange>,
+    source_spans: &mut Vec<BytecodeSpan>,
+    label_references: &mut Vec<usize>,
+    constants: &mut BTreeMap<String, String>,
     circular_codesize_invocations: &mut CircularCodeSizeIndices,
+    expansions: &mut ExpansionCache,
     starting_offset: usize,
-) -> Result<Vec<(usize, Bytes)>, CodegenError> {
+) -> Result<Vec<IRInstruction>, CodegenError> {
     let mut bytes = vec![];
 
     tracing::debug!(target: "codegen", "Got Statement: {}", s.ty);
@@ -38,6 +52,10 @@ pub fn statement_gen<'a>(
                     kind: CodegenErrorKind::InvalidMacroInvocation(mi.macro_name.clone()),
                     span: mi.span.clone(),
                     token: None,
+                    hint: did_you_mean(
+                        &mi.macro_name,
+                        contract.macros.iter().filter(|m| !m.test).map(|m| m.name.as_str()),
+                    ),
                 })
             };
 
@@ -50,6 +68,33 @@ pub fn statement_gen<'a>(
                     kind: CodegenErrorKind::TestInvocation(ir_macro.name.clone()),
                     span: ir_macro.span.clone(),
                     token: None,
+                    hint: None,
+                })
+            }
+
+            // The invocation must pass an argument for each of the macro's parameters. Their kinds
+            // aren't checked: parameters are only expanded in place by `<arg>`, where a literal,
+            // opcode, label or nested invocation are all valid, and a label that doesn't exist is
+            // reported once the jumps are filled.
+            if mi.args.len() != ir_macro.parameters.len() {
+                tracing::error!(
+                    target: "codegen",
+                    "MACRO \"{}\" TAKES {} ARGUMENTS, GOT {}",
+                    ir_macro.name,
+                    ir_macro.parameters.len(),
+                    mi.args.len()
+                );
+                return Err(CodegenError {
+                    kind: CodegenErrorKind::InvalidMacroArgCount(
+                        ir_macro.name.clone(),
+                        ir_macro.parameters.len(),
+                        mi.args.len(),
+                    ),
+                    span: AstSpan(
+                        mi.span.0.iter().chain(ir_macro.span.0.first()).cloned().collect(),
+                    ),
+                    token: None,
+                    hint: None,
                 })
             }
 
@@ -61,7 +106,7 @@ pub fn statement_gen<'a>(
                 // PC of the return jumpdest should be below the function's stack inputs
                 let stack_swaps = (0..ir_macro.takes)
                     .rev()
-                    .map(|i| format!("{:02x}", 0x90 + i))
+                    .filter_map(|i| Opcode::from_byte(0x90 + i as u8))
                     .collect::<Vec<_>>();
 
                 // Insert a jump to the outlined macro's code
@@ -71,51 +116,109 @@ pub fn statement_gen<'a>(
                         label: format!("goto_{}", &ir_macro.name),
                         bytecode_index: 0,
                         span: s.span.clone(),
+                        scope_depth: None,
                     }],
                 );
 
                 // Store return JUMPDEST PC on the stack and re-order the stack so that
                 // the return JUMPDEST PC is below the function's stack inputs
-                bytes.push((
+                let return_pc = *offset + stack_swaps.len() + 7;
+                let return_label = format!("__pc_{return_pc:04x}");
+                bytes.push(IRInstruction::at(
                     *offset,
-                    Bytes(format!(
-                        "{}{:04x}{}",
-                        Opcode::Push2,
-                        *offset + stack_swaps.len() + 7,
-                        stack_swaps.join("")
-                    )),
+                    IRInstructionType::Push(2, IRValue::Label(return_label.clone())),
                 ));
+                label_references.push(*offset);
+                for (i, swap) in stack_swaps.iter().enumerate() {
+                    let swap = IRInstructionType::Opcode(*swap);
+                    bytes.push(IRInstruction::at(*offset + 3 + i, swap));
+                }
                 // Insert jump to outlined macro + jumpdest to return to
-                bytes.push((
-                    *offset + stack_swaps.len() + 3, // PUSH2 + 2 bytes + stack_swaps.len()
-                    Bytes(format!("{}xxxx{}{}", Opcode::Push2, Opcode::Jump, Opcode::Jumpdest)),
-                ));
+                let goto = format!("goto_{}", &ir_macro.name);
+                bytes.extend([
+                    IRInstruction::at(
+                        *offset + stack_swaps.len() + 3, // PUSH2 + 2 bytes + stack_swaps.len()
+                        IRInstructionType::Push(2, IRValue::Label(goto)),
+                    ),
+                    IRInstruction::at(return_pc - 1, IRInstructionType::Opcode(Opcode::Jump)),
+                    IRInstruction::at(return_pc, IRInstructionType::Label(return_label)),
+                    IRInstruction::at(return_pc, IRInstructionType::Opcode(Opcode::Jumpdest)),
+                ]);
                 // PUSH2 + 2 bytes + stack_swaps.len() + PUSH2 + 2 bytes + JUMP + JUMPDEST
                 *offset += stack_swaps.len() + 8;
             } else {
+                // Inlining a macro within its own expansion would never terminate
+                if let Some(start) = scope.iter().position(|m| m.name == ir_macro.name) {
+                    let cycle = scope[start..]
+                        .iter()
+                        .map(|m| m.name.clone())
+                        .chain(std::iter::once(ir_macro.name.clone()))
+                        .collect::<Vec<String>>();
+                    tracing::error!(target: "codegen", "RECURSIVE MACRO INVOCATION: {}", cycle.join(" -> "));
+                    return Err(CodegenError {
+                        kind: CodegenErrorKind::RecursiveMacro(cycle),
+                        span: mi.span.clone(),
+                        token: None,
+                        hint: None,
+                    })
+                }
+                let max_depth = settings.max_expansion_depth.unwrap_or(MAX_EXPANSION_DEPTH);
+                if scope.len() >= max_depth {
+                    tracing::error!(target: "codegen", "MAXIMUM EXPANSION DEPTH {} EXCEEDED", max_depth);
+                    return Err(CodegenError {
+                        kind: CodegenErrorKind::MaxExpansionDepth(max_depth),
+                        span: mi.span.clone(),
+                        token: None,
+                        hint: None,
+                    })
+                }
+
                 // Recurse into macro invocation
                 scope.push(ir_macro);
                 mis.push((*offset, mi.clone()));
 
-                let mut res: BytecodeRes = match Codegen::macro_to_bytecode(
-                    evm_version,
-                    ir_macro,
-                    contract,
-                    scope,
-                    *offset,
-                    mis,
-                    false,
-                    Some(circular_codesize_invocations),
-                ) {
-                    Ok(r) => r,
-                    Err(e) => {
-                        tracing::error!(
-                            target: "codegen",
-                            "FAILED TO RECURSE INTO MACRO \"{}\"",
-                            ir_macro.name
-                        );
-                        return Err(e)
+                // Invocations expanding to the same code reuse the first expansion
+                let key = expansion_key(contract, settings, scope, mis);
+                let cached = key
+                    .as_ref()
+                    .and_then(|key| expansions.get(key))
+                    .map(|(from, res)| relocate_expansion(res, *from, *offset));
+                let mut res: BytecodeRes = if let Some(res) = cached {
+                    tracing::debug!(target: "codegen", "REUSING EXPANSION OF MACRO \"{}\"", ir_macro.name);
+                    mis.pop();
+                    scope.pop();
+                    res
+                } else {
+                    let placeholders = circular_codesize_invocations.len();
+                    let res = match Codegen::macro_to_bytecode(
+                        evm_version,
+                        ir_macro,
+                        contract,
+                        settings,
+                        scope,
+                        *offset,
+                        mis,
+                        false,
+                        Some(circular_codesize_invocations),
+                        Some(expansions),
+                    ) {
+                        Ok(r) => r,
+                        Err(e) => {
+                            tracing::error!(
+                                target: "codegen",
+                                "FAILED TO RECURSE INTO MACRO \"{}\"",
+                                ir_macro.name
+                            );
+                            return Err(e)
+                        }
+                    };
+                    // Placeholders for circular codesizes are filled in by their offset
+                    if let Some(key) =
+                        key.filter(|_| circular_codesize_invocations.len() == placeholders)
+                    {
+                        expansions.insert(key, (*offset, res.clone()));
                     }
+                    res
                 };
 
                 // Set jump table values
@@ -131,32 +234,75 @@ pub fn statement_gen<'a>(
                     new_jumps.push(j.clone());
                     jump_table.insert(new_index, new_jumps);
                 }
+
+                let macro_start = *offset;
+
+                // Suffix the labels of macros with unique labels by the invocation's offset,
+                // so that invocations neither clash nor resolve the caller's jumps
+                let unique_labels = settings.unique_labels ||
+                    ir_macro
+                        .decorator
+                        .as_ref()
+                        .map_or(false, |d| d.flags.contains(&DecoratorFlag::UniqueLabels));
+                let suffix = |name: String| {
+                    if unique_labels {
+                        format!("{name}_{macro_start}")
+                    } else {
+                        name
+                    }
+                };
                 table_instances.extend(res.table_instances);
-                label_indices.extend(res.label_indices);
+                label_indices.extend(res.label_indices.into_iter().map(|(n, i)| (suffix(n), i)));
+                for (name, spans) in res.label_definitions {
+                    label_definitions.entry(suffix(name)).or_default().extend(spans);
+                }
                 utilized_tables.extend(res.utilized_tables);
+                macro_ranges.extend(res.macro_ranges);
+                source_spans.extend(res.source_spans);
+                label_references.extend(res.label_references);
+                constants.extend(res.constants);
 
                 // Increase offset by byte length of recursed macro
-                *offset += res.bytes.iter().map(|(_, b)| b.0.len()).sum::<usize>() / 2;
-                // Add the macro's bytecode to the final result
-                bytes = [bytes, res.bytes].concat()
+                *offset += res.instructions.iter().map(|i| i.ty.size()).sum::<usize>();
+                macro_ranges.push(MacroRange {
+                    name: ir_macro.name.clone(),
+                    start: macro_start,
+                    end: *offset,
+                    labels: res.defined_labels,
+                });
+                // Add the macro's instructions to the final result
+                bytes.extend(res.instructions)
             }
         }
         StatementType::Label(label) => {
             // Add JUMPDEST opcode to final result and add to label_indices
             tracing::info!(target: "codegen", "RECURSE BYTECODE GOT LABEL: {:?}", label.name);
             label_indices.insert(label.name.clone(), *offset);
-            bytes.push((*offset, Bytes(Opcode::Jumpdest.to_string())));
+            label_definitions
+                .entry(label.name.clone())
+                .or_default()
+                .extend(label.span.0.first().cloned());
+            bytes.push(IRInstruction::at(*offset, IRInstructionType::Label(label.name.clone())));
+            bytes.push(IRInstruction::at(*offset, IRInstructionType::Opcode(Opcode::Jumpdest)));
             *offset += 1;
         }
         StatementType::LabelCall(label) => {
             // Generate code for a `LabelCall`
-            // PUSH2 + 2 byte destination (placeholder for now, filled in `Codegen::fill_unmatched`
+            // PUSH2 of the label, resolved in `Codegen::fill_unmatched`
             tracing::info!(target: "codegen", "RECURSE BYTECODE GOT LABEL CALL: {}", label);
             jump_table.insert(
                 *offset,
-                vec![Jump { label: label.to_string(), bytecode_index: 0, span: s.span.clone() }],
+                vec![Jump {
+                    label: label.to_string(),
+                    bytecode_index: 0,
+                    span: s.span.clone(),
+                    scope_depth: None,
+                }],
             );
-            bytes.push((*offset, Bytes(format!("{}xxxx", Opcode::Push2))));
+            bytes.push(IRInstruction::at(
+                *offset,
+                IRInstructionType::Push(2, IRValue::Label(label.to_string())),
+            ));
             *offset += 3;
         }
         StatementType::BuiltinFunctionCall(bf) => {
@@ -181,6 +327,7 @@ pub fn statement_gen<'a>(
                             ),
                             span: bf.span.clone(),
                             token: None,
+                            hint: None,
                         })
                     };
 
@@ -196,6 +343,19 @@ pub fn statement_gen<'a>(
                     // We also need to avoid if the codesize arg is any of the previous macros to
                     // avoid a circular reference
                     if is_previous_parent || macro_def.name.eq(codesize_arg) {
+                        // The passes would change the size after it was pushed
+                        if !settings.pipeline.is_empty() {
+                            tracing::error!(target: "codegen", "CIRCULAR CODESIZE OF \"{}\" WITH PASSES ENABLED", codesize_arg);
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::OptimizedCircularCodesize(
+                                    codesize_arg.to_string(),
+                                ),
+                                span: bf.span.clone(),
+                                token: None,
+                                hint: None,
+                            })
+                        }
+
                         tracing::debug!(target: "codegen", "CIRCULAR CODESIZE INVOCATION DETECTED INJECTING PLACEHOLDER | macro: {}", ir_macro.name);
 
                         // Save the invocation for later
@@ -203,18 +363,23 @@ pub fn statement_gen<'a>(
 
                         // Progress offset by placeholder size
                         *offset += 2;
-                        bytes.push((starting_offset, Bytes("cccc".to_string())));
+                        bytes.push(IRInstruction::at(
+                            starting_offset,
+                            IRInstructionType::Push(1, IRValue::Codesize(codesize_arg.to_string())),
+                        ));
                     } else {
                         // We will still need to recurse to get accurate values
                         let res: BytecodeRes = match Codegen::macro_to_bytecode(
                             evm_version,
                             ir_macro,
                             contract,
+                            settings,
                             scope,
                             *offset,
                             mis,
                             ir_macro.name.eq("CONSTRUCTOR"),
                             Some(circular_codesize_invocations),
+                            Some(expansions),
                         ) {
                             Ok(r) => r,
                             Err(e) => {
@@ -227,14 +392,15 @@ pub fn statement_gen<'a>(
                             }
                         };
 
+                        // The size of the code the macro is emitted as, after any passes
                         let size = format_even_bytes(format!(
                             "{:02x}",
-                            (res.bytes.iter().map(|(_, b)| b.0.len()).sum::<usize>() / 2)
+                            Codegen::codesize(evm_version, ir_macro, contract, settings, &res)?
                         ));
-                        let push_bytes = format!("{:02x}{size}", 95 + size.len() / 2);
+                        let push = IRInstructionType::push(size);
 
-                        *offset += push_bytes.len() / 2;
-                        bytes.push((starting_offset, Bytes(push_bytes)));
+                        *offset += push.size();
+                        bytes.push(IRInstruction::at(starting_offset, push));
                     }
                 }
                 BuiltinFunctionKind::Tablesize => {
@@ -254,18 +420,21 @@ pub fn statement_gen<'a>(
                             ),
                             span: bf.span.clone(),
                             token: None,
+                            hint: did_you_mean(
+                                bf.args[0].name.as_ref().unwrap(),
+                                contract.tables.iter().map(|t| t.name.as_str()),
+                            ),
                         })
                     };
 
-                    let size = bytes32_to_string(&ir_table.size, false);
-                    let push_bytes = format!("{:02x}{size}", 95 + size.len() / 2);
+                    let push = IRInstructionType::push(bytes32_to_string(&ir_table.size, false));
 
                     if !utilized_tables.contains(&ir_table) {
                         utilized_tables.push(ir_table);
                     }
 
-                    *offset += push_bytes.len() / 2;
-                    bytes.push((starting_offset, Bytes(push_bytes)));
+                    *offset += push.size();
+                    bytes.push(IRInstruction::at(starting_offset, push));
                 }
                 BuiltinFunctionKind::Tablestart => {
                     // Make sure the table exists
@@ -275,12 +444,15 @@ pub fn statement_gen<'a>(
                             label: bf.args[0].name.as_ref().unwrap().to_owned(),
                             bytecode_index: *offset,
                             span: bf.span.clone(),
+                            scope_depth: None,
                         });
+                        bytes.push(IRInstruction::at(
+                            *offset,
+                            IRInstructionType::Push(2, IRValue::TableStart(t.name.clone())),
+                        ));
                         if !utilized_tables.contains(&t) {
                             utilized_tables.push(t);
                         }
-
-                        bytes.push((*offset, Bytes(format!("{}xxxx", Opcode::Push2))));
                         *offset += 3;
                     } else {
                         tracing::error!(
@@ -294,6 +466,10 @@ pub fn statement_gen<'a>(
                             ),
                             span: bf.span.clone(),
                             token: None,
+                            hint: did_you_mean(
+                                bf.args[0].name.as_ref().unwrap(),
+                                contract.tables.iter().map(|t| t.name.as_str()),
+                            ),
                         })
                     }
                 }
@@ -313,6 +489,7 @@ pub fn statement_gen<'a>(
                             ),
                             span: bf.span.clone(),
                             token: None,
+                            hint: None,
                         });
                     }
 
@@ -321,26 +498,24 @@ pub fn statement_gen<'a>(
                         .iter()
                         .find(|f| bf.args[0].name.as_ref().unwrap().eq(&f.name))
                     {
-                        let push_bytes =
-                            format!("{}{}", Opcode::Push4, hex::encode(func.signature));
-                        *offset += push_bytes.len() / 2;
-                        bytes.push((starting_offset, Bytes(push_bytes)));
+                        let push = IRInstructionType::push(hex::encode(func.signature));
+                        *offset += push.size();
+                        bytes.push(IRInstruction::at(starting_offset, push));
                     } else if let Some(error) = contract
                         .errors
                         .iter()
                         .find(|e| bf.args[0].name.as_ref().unwrap().eq(&e.name))
                     {
-                        let push_bytes =
-                            format!("{}{}", Opcode::Push4, hex::encode(error.selector));
-                        *offset += push_bytes.len() / 2;
-                        bytes.push((starting_offset, Bytes(push_bytes)));
+                        let push = IRInstructionType::push(hex::encode(error.selector));
+                        *offset += push.size();
+                        bytes.push(IRInstruction::at(starting_offset, push));
                     } else if let Some(s) = &bf.args[0].name {
                         let mut signature = [0u8; 4]; // Only keep first 4 bytes
                         hash_bytes(&mut signature, s);
 
-                        let push_bytes = format!("{}{}", Opcode::Push4, hex::encode(signature));
-                        *offset += push_bytes.len() / 2;
-                        bytes.push((starting_offset, Bytes(push_bytes)));
+                        let push = IRInstructionType::push(hex::encode(signature));
+                        *offset += push.size();
+                        bytes.push(IRInstruction::at(starting_offset, push));
                     } else {
                         tracing::error!(
                             target: "codegen",
@@ -353,6 +528,7 @@ pub fn statement_gen<'a>(
                             ),
                             span: bf.span.clone(),
                             token: None,
+                            hint: None,
                         })
                     }
                 }
@@ -372,6 +548,7 @@ pub fn statement_gen<'a>(
                             ),
                             span: bf.span.clone(),
                             token: None,
+                            hint: None,
                         });
                     }
 
@@ -380,17 +557,16 @@ pub fn statement_gen<'a>(
                         .iter()
                         .find(|e| bf.args[0].name.as_ref().unwrap().eq(&e.name))
                     {
-                        let hash = bytes32_to_string(&event.hash, false);
-                        let push_bytes = format!("{}{hash}", Opcode::Push32);
-                        *offset += push_bytes.len() / 2;
-                        bytes.push((starting_offset, Bytes(push_bytes)));
+                        let push = IRInstructionType::push(hex::encode(event.hash));
+                        *offset += push.size();
+                        bytes.push(IRInstruction::at(starting_offset, push));
                     } else if let Some(s) = &bf.args[0].name {
                         let mut hash = [0u8; 32];
                         hash_bytes(&mut hash, s);
 
-                        let push_bytes = format!("{}{}", Opcode::Push32, hex::encode(hash));
-                        *offset += push_bytes.len() / 2;
-                        bytes.push((starting_offset, Bytes(push_bytes)));
+                        let push = IRInstructionType::push(hex::encode(hash));
+                        *offset += push.size();
+                        bytes.push(IRInstruction::at(starting_offset, push));
                     } else {
                         tracing::error!(
                             target: "codegen",
@@ -403,6 +579,7 @@ pub fn statement_gen<'a>(
                             ),
                             span: bf.span.clone(),
                             token: None,
+                            hint: None,
                         })
                     }
                 }
@@ -420,6 +597,7 @@ pub fn statement_gen<'a>(
                             )),
                             span: bf.span.clone(),
                             token: None,
+                            hint: None,
                         })
                     }
 
@@ -431,16 +609,17 @@ pub fn statement_gen<'a>(
                         // Add 28 bytes to left-pad the 4 byte selector
                         let selector =
                             format!("{}{}", hex::encode(error.selector), "00".repeat(28));
-                        let push_bytes = format!("{}{selector}", Opcode::Push32);
-                        *offset += push_bytes.len() / 2;
-                        bytes.push((starting_offset, Bytes(push_bytes)));
-                    } else if let Some(s) = &bf.args[0].name {
+                        let push = IRInstructionType::push(selector);
+                        *offset += push.size();
+                        bytes.push(IRInstruction::at(starting_offset, push));
+                    } else if let Some(s) = bf.args[0].name.as_ref().filter(|s| s.contains('(')) {
+                        // A full error signature, ie. `__ERROR("Error(string)")`
                         let mut signature = [0u8; 4]; // Only keep first 4 bytes
                         hash_bytes(&mut signature, s);
 
-                        let push_bytes = format!("{}{}", Opcode::Push4, hex::encode(signature));
-                        *offset += push_bytes.len() / 2;
-                        bytes.push((starting_offset, Bytes(push_bytes)));
+                        let push = IRInstructionType::push(hex::encode(signature));
+                        *offset += push.size();
+                        bytes.push(IRInstruction::at(starting_offset, push));
                     } else {
                         tracing::error!(
                             target: "codegen",
@@ -453,6 +632,7 @@ pub fn statement_gen<'a>(
                             ),
                             span: bf.span.clone(),
                             token: None,
+                            hint: None,
                         })
                     }
                 }
@@ -470,14 +650,77 @@ pub fn statement_gen<'a>(
                             )),
                             span: bf.span.clone(),
                             token: None,
+                            hint: None,
                         });
                     }
 
                     let hex = format_even_bytes(bf.args[0].name.as_ref().unwrap().clone());
-                    let push_bytes =
-                        format!("{}{hex}{}", Opcode::Push32, "0".repeat(64 - hex.len()));
-                    *offset += push_bytes.len() / 2;
-                    bytes.push((starting_offset, Bytes(push_bytes)));
+                    if hex.len() > 64 {
+                        tracing::error!(
+                            target = "codegen",
+                            "Value passed to __RIGHTPAD exceeds 32 bytes: {}",
+                            hex
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Value passed to __RIGHTPAD exceeds 32 bytes: {hex}"
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                            hint: None,
+                        })
+                    }
+                    let push =
+                        IRInstructionType::push(format!("{hex}{}", "0".repeat(64 - hex.len())));
+                    *offset += push.size();
+                    bytes.push(IRInstruction::at(starting_offset, push));
+                }
+                BuiltinFunctionKind::LeftPad => {
+                    if bf.args.len() != 2 {
+                        tracing::error!(
+                            target = "codegen",
+                            "Incorrect number of arguments passed to __LEFTPAD, should be 2: {}",
+                            bf.args.len()
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Incorrect number of arguments passed to __LEFTPAD, should be 2: {}",
+                                bf.args.len()
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                            hint: None,
+                        })
+                    }
+
+                    let hex = format_even_bytes(bf.args[0].name.as_ref().unwrap().clone());
+                    let width = bf.args[1].name.as_ref().and_then(|w| hex_to_usize(w).ok());
+
+                    // The width must be a valid push size that fits the value
+                    let width = match width {
+                        Some(w) if (1..=32).contains(&w) && hex.len() / 2 <= w => w,
+                        _ => {
+                            tracing::error!(
+                                target = "codegen",
+                                "Invalid width passed to __LEFTPAD for value {}: {:?}",
+                                hex,
+                                bf.args[1].name
+                            );
+                            return Err(CodegenError {
+                                kind: CodegenErrorKind::InvalidArguments(format!(
+                                    "__LEFTPAD width must be between 1 and 32 bytes and fit the value: {}",
+                                    bf.args[1].name.as_ref().unwrap()
+                                )),
+                                span: bf.span.clone(),
+                                token: None,
+                                hint: None,
+                            })
+                        }
+                    };
+
+                    let push = IRInstructionType::push(pad_n_bytes(&hex, width));
+                    *offset += push.size();
+                    bytes.push(IRInstruction::at(starting_offset, push));
                 }
                 BuiltinFunctionKind::DynConstructorArg => {
                     if bf.args.len() != 2 {
@@ -493,6 +736,7 @@ pub fn statement_gen<'a>(
                             )),
                             span: bf.span.clone(),
                             token: None,
+                            hint: None,
                         });
                     }
 
@@ -501,7 +745,9 @@ pub fn statement_gen<'a>(
 
                     // Enforce that the arg index is 1 byte and that the dest offset is at max
                     // 2 bytes.
-                    if arg_index.len() != 2 || dest_offset.len() > 4 {
+                    let index = hex_to_usize(arg_index).ok().filter(|_| arg_index.len() == 2);
+                    let dest = hex_to_usize(dest_offset).ok().filter(|_| dest_offset.len() <= 4);
+                    let (Some(index), Some(dest)) = (index, dest) else {
                         tracing::error!(
                             target = "codegen",
                             "Incorrect number of bytes in argument passed to __CODECOPY_DYN_ARG. Should be (1 byte, <= 2 bytes)"
@@ -512,39 +758,46 @@ pub fn statement_gen<'a>(
                             ),
                             span: bf.span.clone(),
                             token: None,
+                            hint: None,
                         });
-                    }
+                    };
 
-                    // Insert a 17 byte placeholder- will be filled when constructor args are added
-                    // to the end of the runtime code.
+                    // Copy the length and contents of the argument to memory, the length and
+                    // offset of the argument being filled once the constructor args are added to
+                    // the end of the runtime code.
                     // <len (2 bytes)> <dest_mem_ptr (2 bytes)> mstore
                     // <len (2 bytes)> <contents_code_ptr (2 bytes)> <dest_mem_ptr + 0x20 (2 bytes)>
                     // codecopy
-                    *offset += 17;
-                    bytes.push((
-                        starting_offset,
-                        Bytes(format!(
-                            "{}{}{}",
-                            "xx".repeat(14),
-                            bf.args[0].name.as_ref().unwrap(),
-                            pad_n_bytes(bf.args[1].name.as_ref().unwrap(), 2)
-                        )),
-                    ));
+                    let code = [
+                        IRInstructionType::Push(2, IRValue::DynArgLength(index)),
+                        IRInstructionType::push(format!("{dest:04x}")),
+                        IRInstructionType::Opcode(Opcode::Mstore),
+                        IRInstructionType::Push(2, IRValue::DynArgLength(index)),
+                        IRInstructionType::Push(2, IRValue::DynArgOffset(index)),
+                        IRInstructionType::push(format!("{:04x}", dest + 0x20)),
+                        IRInstructionType::Opcode(Opcode::Codecopy),
+                    ];
+                    for ty in code {
+                        let size = ty.size();
+                        bytes.push(IRInstruction::at(*offset, ty));
+                        *offset += size;
+                    }
                 }
                 BuiltinFunctionKind::Verbatim => {
                     if bf.args.len() != 1 {
                         tracing::error!(
                             target = "codegen",
-                            "Incorrect number of arguments passed to __INJECT, should be 1: {}",
+                            "Incorrect number of arguments passed to __VERBATIM, should be 1: {}",
                             bf.args.len()
                         );
                         return Err(CodegenError {
                             kind: CodegenErrorKind::InvalidArguments(format!(
-                                "Incorrect number of arguments passed to __INJECT, should be 1: {}",
+                                "Incorrect number of arguments passed to __VERBATIM, should be 1: {}",
                                 bf.args.len()
                             )),
                             span: bf.span.clone(),
                             token: None,
+                            hint: None,
                         })
                     }
 
@@ -567,15 +820,145 @@ pub fn statement_gen<'a>(
                             kind: CodegenErrorKind::InvalidHex(verbatim_str.to_string()),
                             span: bf.span.clone(),
                             token: None,
+                            hint: None,
                         })
                     }
 
                     tracing::debug!(target: "codegen", "INJECTING as verbatim: {}", verbatim_str);
                     let hex = format_even_bytes(verbatim_str.clone());
-                    let push_bytes = hex.to_string();
                     *offset += hex.len() / 2;
 
-                    bytes.push((starting_offset, Bytes(push_bytes)));
+                    bytes.push(IRInstruction::at(starting_offset, IRInstructionType::Raw(hex)));
+                }
+                BuiltinFunctionKind::Keccak => {
+                    if bf.args.len() != 1 {
+                        tracing::error!(
+                            target = "codegen",
+                            "Incorrect number of arguments passed to __keccak, should be 1: {}",
+                            bf.args.len()
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Incorrect number of arguments passed to __keccak, should be 1: {}",
+                                bf.args.len()
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                            hint: None,
+                        })
+                    }
+
+                    let arg = &bf.args[0];
+                    let preimage = if arg.arg_type.as_deref() == Some("bytes32") {
+                        // Constants are hashed as a full 32 byte word
+                        let constants = contract.constants.lock().map_err(|_| {
+                            CodegenError::new(CodegenErrorKind::LockingError, AstSpan(vec![]), None)
+                        })?;
+                        ConstExpression::Constant(arg.name.clone().unwrap_or_default())
+                            .evaluate(&constants, &arg.span)?
+                            .to_vec()
+                    } else {
+                        hex::decode(arg.name.as_ref().unwrap()).map_err(|_| {
+                            tracing::error!(target: "codegen", "INVALID HEX STRING PASSED TO __keccak: \"{}\"", arg.name.as_ref().unwrap());
+                            CodegenError {
+                                kind: CodegenErrorKind::InvalidHex(
+                                    arg.name.as_ref().unwrap().to_string(),
+                                ),
+                                span: bf.span.clone(),
+                                token: None,
+                                hint: None,
+                            }
+                        })?
+                    };
+
+                    let push = IRInstructionType::push(hex::encode(ethers_core::utils::keccak256(
+                        preimage,
+                    )));
+                    *offset += push.size();
+                    bytes.push(IRInstruction::at(starting_offset, push));
+                }
+                BuiltinFunctionKind::Bytes => {
+                    if bf.args.len() != 1 {
+                        tracing::error!(
+                            target = "codegen",
+                            "Incorrect number of arguments passed to __BYTES, should be 1: {}",
+                            bf.args.len()
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Incorrect number of arguments passed to __BYTES, should be 1: {}",
+                                bf.args.len()
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                            hint: None,
+                        })
+                    }
+
+                    // Push the string with the push matching its length, an empty string is zero
+                    let hex = bf.args[0].name.clone().unwrap_or_default();
+                    let push = if hex.is_empty() {
+                        IRInstructionType::literal(evm_version, &[0u8; 32])
+                    } else {
+                        IRInstructionType::push(hex)
+                    };
+                    *offset += push.size();
+                    bytes.push(IRInstruction::at(starting_offset, push));
+                }
+                BuiltinFunctionKind::Custom(ref name) => {
+                    let Some(builtin) = contract.custom_builtins.get(name) else {
+                        tracing::error!(target: "codegen", "MISSING CUSTOM BUILTIN \"{}\"", name);
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::MissingBuiltinDefinition(name.clone()),
+                            span: bf.span.clone(),
+                            token: None,
+                            hint: None,
+                        })
+                    };
+                    if bf.args.len() != builtin.arity {
+                        tracing::error!(
+                            target = "codegen",
+                            "Incorrect number of arguments passed to {}, should be {}: {}",
+                            name,
+                            builtin.arity,
+                            bf.args.len()
+                        );
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidArguments(format!(
+                                "Incorrect number of arguments passed to {}, should be {}: {}",
+                                name,
+                                builtin.arity,
+                                bf.args.len()
+                            )),
+                            span: bf.span.clone(),
+                            token: None,
+                            hint: None,
+                        })
+                    }
+
+                    let expanded = (builtin.expand)(bf, contract).map_err(|mut e| {
+                        if e.span.0.is_empty() {
+                            e.span = bf.span.clone();
+                        }
+                        e
+                    })?;
+                    if expanded.len() % 2 != 0 || !expanded.chars().all(|c| c.is_ascii_hexdigit())
+                    {
+                        tracing::error!(target: "codegen", "INVALID HEX STRING EXPANDED FROM {}: \"{}\"", name, expanded);
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::InvalidHex(expanded),
+                            span: bf.span.clone(),
+                            token: None,
+                            hint: None,
+                        })
+                    }
+
+                    tracing::debug!(target: "codegen", "EXPANDED CUSTOM BUILTIN {}: {}", name, expanded);
+                    *offset += expanded.len() / 2;
+                    bytes.push(IRInstruction::at(
+                        starting_offset,
+                        IRInstructionType::Raw(expanded.to_lowercase()),
+                    ));
                 }
             }
         }
@@ -586,6 +969,7 @@ pub fn statement_gen<'a>(
                 kind: CodegenErrorKind::InvalidMacroStatement,
                 span: s.span.clone(),
                 token: None,
+                hint: None,
             })
         }
     }
@@ -4,6 +4,13 @@
 #![forbid(unsafe_code)]
 #![forbid(where_clauses_object_safety)]
 
+use ethers_core::{
+    abi::{
+        token::{LenientTokenizer, Tokenizer},
+        ParamType,
+    },
+    types::U256,
+};
 use huff_utils::{
     abi::*,
     artifact::*,
@@ -12,15 +19,28 @@ use huff_utils::{
     bytes_util,
     error::CodegenError,
     evm::Opcode,
-    prelude::{format_even_bytes, pad_n_bytes, CodegenErrorKind, EVMVersion, FileSource, Span},
+    ir::{IRInstruction, IRInstructionType, IRProgram, IRValue},
+    prelude::{
+        did_you_mean, format_even_bytes, pad_n_bytes, CodegenErrorKind, EVMVersion, FileSource,
+        Span,
+    },
+    source_map::source_map,
     types::EToken,
 };
 use regex::Regex;
-use std::{cmp::Ordering, collections::HashMap, fs, path::Path, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+    sync::Arc,
+};
 
 mod irgen;
 use crate::irgen::prelude::*;
 
+pub mod passes;
+use crate::passes::{PassContext, Pipeline};
+
 /// ### Codegen
 ///
 /// Code Generation Manager responsible for generating bytecode from a
@@ -46,12 +66,40 @@ pub struct Codegen {
     pub main_bytecode: Option<String>,
     /// Intermediate constructor bytecode store
     pub constructor_bytecode: Option<String>,
+    /// The settings to generate the bootstrap code with
+    pub bootstrap: BootstrapSettings,
+    /// Intermediate bootstrap shim bytecode store, replacing the default bootstrap code
+    pub bootstrap_shim: Option<String>,
+    /// The statements the main bytecode was generated from, for the artifact's source maps
+    pub main_spans: Vec<BytecodeSpan>,
+    /// The statements the constructor bytecode was generated from
+    pub constructor_spans: Vec<BytecodeSpan>,
+}
+
+/// The settings code is generated with
+#[derive(Debug, Default, Clone)]
+pub struct CodegenSettings {
+    /// The passes run over the IR of the code before it's emitted
+    pub pipeline: Pipeline,
+    /// The maximum depth of nested macro invocations, [MAX_EXPANSION_DEPTH] if unset
+    pub max_expansion_depth: Option<usize>,
+    /// Whether the labels of every macro are unique to each of its invocations
+    pub unique_labels: bool,
 }
 
 impl Codegen {
     /// Public associated function to instantiate a new Codegen instance.
     pub fn new() -> Self {
-        Self { ast: None, artifact: None, main_bytecode: None, constructor_bytecode: None }
+        Self {
+            ast: None,
+            artifact: None,
+            main_bytecode: None,
+            constructor_bytecode: None,
+            bootstrap: BootstrapSettings::default(),
+            bootstrap_shim: None,
+            main_spans: vec![],
+            constructor_spans: vec![],
+        }
     }
 
     /// Generates main bytecode from a Contract AST
@@ -60,6 +108,25 @@ impl Codegen {
         contract: &Contract,
         alternative_main: Option<String>,
     ) -> Result<String, CodegenError> {
+        Codegen::generate_main_bytecode_with_res(
+            evm_version,
+            contract,
+            alternative_main,
+            &CodegenSettings::default(),
+        )
+        .map(|(bytecode, _)| bytecode)
+    }
+
+    /// Generates main bytecode from a Contract AST, along with the [BytecodeRes] of its code,
+    /// eg. for the source spans and macro ranges behind it
+    ///
+    /// The passes of the settings' pipeline run over the IR of the code before it's emitted.
+    pub fn generate_main_bytecode_with_res(
+        evm_version: &EVMVersion,
+        contract: &Contract,
+        alternative_main: Option<String>,
+        settings: &CodegenSettings,
+    ) -> Result<(String, BytecodeRes), CodegenError> {
         // If an alternative main is provided, then use it as the compilation target
         let main_macro = alternative_main.unwrap_or_else(|| String::from("MAIN"));
 
@@ -67,21 +134,32 @@ impl Codegen {
         let m_macro = Codegen::get_macro_by_name(&main_macro, contract)?;
 
         // For each MacroInvocation Statement, recurse into bytecode
-        let bytecode_res: BytecodeRes = Codegen::macro_to_bytecode(
+        let mut bytecode_res: BytecodeRes = Codegen::macro_to_bytecode(
             evm_version,
             m_macro,
             contract,
+            settings,
             &mut vec![m_macro],
             0,
             &mut Vec::default(),
             false,
             None,
+            None,
         )?;
 
         tracing::debug!(target: "codegen", "Generated main bytecode. Appending table bytecode...");
 
         // Generate the fully baked bytecode
-        Codegen::gen_table_bytecode(bytecode_res)
+        let macro_ranges = bytecode_res.macro_ranges.clone();
+        let cx = PassContext {
+            evm_version,
+            contract,
+            macro_name: &main_macro,
+            macro_ranges: &macro_ranges,
+        };
+        let bytecode =
+            Codegen::gen_bytecode_with_passes(&mut bytecode_res, &settings.pipeline, &cx)?;
+        Ok((bytecode, bytecode_res))
     }
 
     /// Generates constructor bytecode from a Contract AST
@@ -90,6 +168,25 @@ impl Codegen {
         contract: &Contract,
         alternative_constructor: Option<String>,
     ) -> Result<(String, bool), CodegenError> {
+        Codegen::generate_constructor_bytecode_with_res(
+            evm_version,
+            contract,
+            alternative_constructor,
+            &CodegenSettings::default(),
+        )
+        .map(|(bytecode, has_custom_bootstrap, _)| (bytecode, has_custom_bootstrap))
+    }
+
+    /// Generates constructor bytecode from a Contract AST, along with the [BytecodeRes] of its
+    /// code
+    ///
+    /// The passes of the settings' pipeline run over the IR of the code before it's emitted.
+    pub fn generate_constructor_bytecode_with_res(
+        evm_version: &EVMVersion,
+        contract: &Contract,
+        alternative_constructor: Option<String>,
+        settings: &CodegenSettings,
+    ) -> Result<(String, bool, BytecodeRes), CodegenError> {
         // If an alternative constructor macro is provided, then use it as the compilation target
         let constructor_macro =
             alternative_constructor.unwrap_or_else(|| String::from("CONSTRUCTOR"));
@@ -98,25 +195,244 @@ impl Codegen {
         let c_macro = Codegen::get_macro_by_name(&constructor_macro, contract)?;
 
         // For each MacroInvocation Statement, recurse into bytecode
-        let bytecode_res: BytecodeRes = Codegen::macro_to_bytecode(
+        let mut bytecode_res: BytecodeRes = Codegen::macro_to_bytecode(
             evm_version,
             c_macro,
             contract,
+            settings,
             &mut vec![c_macro],
             0,
             &mut Vec::default(),
             false,
             None,
+            None,
         )?;
 
+        // Immutables are only written into the runtime code, after the constructor ran
+        Codegen::check_constructor_immutables(&bytecode_res, contract)?;
+
         // Check if the constructor performs its own code generation
-        let has_custom_bootstrap = bytecode_res.bytes.iter().any(|bytes| bytes.1 .0 == *"f3");
+        let has_custom_bootstrap = bytecode_res
+            .instructions
+            .iter()
+            .any(|i| i.ty == IRInstructionType::Opcode(Opcode::Return));
 
         tracing::info!(target: "codegen", "Constructor is self-generating: {}", has_custom_bootstrap);
 
-        let bytecode = Codegen::gen_table_bytecode(bytecode_res)?;
+        let macro_ranges = bytecode_res.macro_ranges.clone();
+        let cx = PassContext {
+            evm_version,
+            contract,
+            macro_name: &constructor_macro,
+            macro_ranges: &macro_ranges,
+        };
+        let bytecode =
+            Codegen::gen_bytecode_with_passes(&mut bytecode_res, &settings.pipeline, &cx)?;
+
+        Ok((bytecode, has_custom_bootstrap, bytecode_res))
+    }
+
+    /// Generates the bytecode of a bootstrap shim macro, which follows the constructor code and
+    /// the pushes of the runtime code's size and offset in the creation code
+    ///
+    /// The shim is generated at the offset it's placed at, for its labels to resolve. The passes
+    /// of the settings' pipeline aren't run over it, they could resize it and move that offset.
+    pub fn generate_shim_bytecode(
+        evm_version: &EVMVersion,
+        contract: &Contract,
+        shim: &str,
+        settings: &CodegenSettings,
+        constructor_length: usize,
+        contract_length: usize,
+    ) -> Result<String, CodegenError> {
+        let s_macro = Codegen::get_macro_by_name(shim, contract)?;
+        let generate = |offset| {
+            let res = Codegen::macro_to_bytecode(
+                evm_version,
+                s_macro,
+                contract,
+                settings,
+                &mut vec![s_macro],
+                offset,
+                &mut Vec::default(),
+                false,
+                None,
+                None,
+            )?;
+            Codegen::gen_table_bytecode(res)
+        };
+
+        // The size of the shim doesn't depend on its offset, but the width of the pushes in
+        // front of it depends on its size
+        let mut bootstrap_code_size = 4 + generate(0)?.len() / 2;
+        let (contract_size, contract_code_offset) =
+            Codegen::runtime_pushes(constructor_length, contract_length, &mut bootstrap_code_size);
+        generate(constructor_length + (contract_size.len() + contract_code_offset.len()) / 2)
+    }
 
-        Ok((bytecode, has_custom_bootstrap))
+    /// Gets the contents of a code table, which the bootstrap returns as the runtime code in place
+    /// of the code generated from `MAIN`
+    pub fn generate_table_runtime(
+        contract: &Contract,
+        table: &str,
+    ) -> Result<String, CodegenError> {
+        let code_tables = || contract.tables.iter().filter(|t| t.kind == TableKind::CodeTable);
+        let Some(t) = code_tables().find(|t| t.name == table) else {
+            tracing::error!(target: "codegen", "MISSING CODE TABLE \"{}\"", table);
+            return Err(CodegenError {
+                kind: CodegenErrorKind::MissingCodeTable(table.to_string()),
+                span: AstSpan(vec![Span { start: 0, end: 0, file: None }]),
+                token: None,
+                hint: did_you_mean(table, code_tables().map(|t| t.name.as_str())),
+            })
+        };
+        t.statements
+            .iter()
+            .map(|s| match &s.ty {
+                StatementType::Code(code) => Ok(code.clone()),
+                _ => Err(CodegenError {
+                    kind: CodegenErrorKind::InvalidMacroStatement,
+                    span: s.span.clone(),
+                    token: None,
+                    hint: None,
+                }),
+            })
+            .collect()
+    }
+
+    /// Gets the pushes of the runtime code's size and offset the bootstrap code starts with,
+    /// growing `bootstrap_code_size` by each push that needs a `PUSH2`
+    fn runtime_pushes(
+        constructor_length: usize,
+        contract_length: usize,
+        bootstrap_code_size: &mut usize,
+    ) -> (String, String) {
+        let contract_size = if contract_length < 256 {
+            format!("60{}", pad_n_bytes(format!("{contract_length:x}").as_str(), 1))
+        } else {
+            *bootstrap_code_size += 1;
+
+            format!("61{}", pad_n_bytes(format!("{contract_length:x}").as_str(), 2))
+        };
+        let contract_code_offset = if (*bootstrap_code_size + constructor_length) < 256 {
+            format!(
+                "60{}",
+                pad_n_bytes(format!("{:x}", *bootstrap_code_size + constructor_length).as_str(), 1)
+            )
+        } else {
+            *bootstrap_code_size += 1;
+
+            format!(
+                "61{}",
+                pad_n_bytes(format!("{:x}", *bootstrap_code_size + constructor_length).as_str(), 2)
+            )
+        };
+        (contract_size, contract_code_offset)
+    }
+
+    /// Errors if constructor code reads an immutable, whose placeholder would be left unfilled
+    fn check_constructor_immutables(
+        res: &BytecodeRes,
+        contract: &Contract,
+    ) -> Result<(), CodegenError> {
+        for instruction in &res.instructions {
+            let IRInstructionType::Push(_, IRValue::Immutable(index)) = &instruction.ty else {
+                continue
+            };
+            let name = contract.immutables.get(*index).map(|i| i.name.clone()).unwrap_or_default();
+            tracing::error!(target: "codegen", "IMMUTABLE \"{}\" READ IN CONSTRUCTOR", name);
+            return Err(CodegenError {
+                kind: CodegenErrorKind::ImmutableInConstructor(name),
+                span: instruction.span.clone(),
+                token: None,
+                hint: None,
+            })
+        }
+        Ok(())
+    }
+
+    /// Checks the compile-time assertions of a Contract AST
+    ///
+    /// `__codesize(MACRO)` operands evaluate to the same size the `__codesize` builtin pushes.
+    pub fn check_assertions(
+        evm_version: &EVMVersion,
+        contract: &Contract,
+        settings: &CodegenSettings,
+    ) -> Result<(), CodegenError> {
+        let evaluate = |operand: &AssertionOperand, span: &AstSpan| match operand {
+            AssertionOperand::Expression(expr) => {
+                let constants = contract.constants.lock().map_err(|_| {
+                    CodegenError::new(CodegenErrorKind::LockingError, AstSpan(vec![]), None)
+                })?;
+                Ok(U256::from_big_endian(&expr.evaluate(&constants, span)?))
+            }
+            AssertionOperand::Codesize(name) => {
+                let m = contract.find_macro_by_name(name).ok_or_else(|| {
+                    tracing::error!(target: "codegen", "MISSING MACRO PASSED TO __codesize \"{}\"", name);
+                    CodegenError::new(
+                        CodegenErrorKind::MissingMacroDefinition(name.to_string()),
+                        span.clone(),
+                        None,
+                    )
+                })?;
+                let res = Codegen::macro_to_bytecode(
+                    evm_version,
+                    m,
+                    contract,
+                    settings,
+                    &mut vec![m],
+                    0,
+                    &mut Vec::default(),
+                    m.name.eq("CONSTRUCTOR"),
+                    None,
+                    None,
+                )?;
+                Ok(U256::from(Codegen::codesize(evm_version, m, contract, settings, &res)?))
+            }
+        };
+
+        for assertion in &contract.assertions {
+            let lhs = evaluate(&assertion.lhs, &assertion.span)?;
+            let rhs = evaluate(&assertion.rhs, &assertion.span)?;
+            if !assertion.op.compare(lhs, rhs) {
+                tracing::error!(target: "codegen", "ASSERTION FAILED: {:#x} {} {:#x}", lhs, assertion.op, rhs);
+                return Err(CodegenError::new(
+                    CodegenErrorKind::FailedAssertion(format!(
+                        "{lhs:#x} {} {rhs:#x}",
+                        assertion.op
+                    )),
+                    assertion.span.clone(),
+                    None,
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets the size `__codesize` resolves to for the code a macro expanded to
+    ///
+    /// Without passes that's the size of the expanded code. Otherwise the passes run over it as
+    /// if it was generated at offset 0, like the macro is when compiled on its own.
+    pub(crate) fn codesize(
+        evm_version: &EVMVersion,
+        macro_def: &MacroDefinition,
+        contract: &Contract,
+        settings: &CodegenSettings,
+        res: &BytecodeRes,
+    ) -> Result<usize, CodegenError> {
+        if settings.pipeline.is_empty() {
+            return Ok(res.instructions.iter().map(|i| i.ty.size()).sum())
+        }
+        let mut program = Codegen::lower(res)?;
+        program.offset = 0;
+        let cx = PassContext {
+            evm_version,
+            contract,
+            macro_name: &macro_def.name,
+            macro_ranges: &res.macro_ranges,
+        };
+        settings.pipeline.run(&mut program, &cx)?;
+        Ok(program.code_size())
     }
 
     /// Helper function to find a macro or generate a CodegenError
@@ -132,13 +448,62 @@ impl Codegen {
                 kind: CodegenErrorKind::MissingMacroDefinition(name.to_string()),
                 span: AstSpan(vec![Span { start: 0, end: 0, file: None }]),
                 token: None,
+                hint: None,
             })
         }
     }
 
     /// Appends table bytecode to the end of the BytecodeRes output.
     /// Fills table JUMPDEST placeholders.
+    ///
+    /// The expanded code is validated, then lowered into an [IRProgram] and emitted.
     pub fn gen_table_bytecode(res: BytecodeRes) -> Result<String, CodegenError> {
+        Codegen::validate_jumps(&res)?;
+
+        // Lay out the code and its tables from the IR
+        let program = Codegen::lower(&res)?;
+        let bytecode = Codegen::emit(&program)?;
+        Codegen::validate_jumpdests(&res, &program, &bytecode)?;
+        Ok(bytecode)
+    }
+
+    /// Like [gen_table_bytecode](Codegen::gen_table_bytecode), running the passes of a pipeline
+    /// over the IR before it's emitted
+    ///
+    /// The program counters of the [BytecodeRes] are moved along with the instructions the
+    /// passes transformed.
+    pub fn gen_bytecode_with_passes(
+        res: &mut BytecodeRes,
+        pipeline: &Pipeline,
+        cx: &PassContext,
+    ) -> Result<String, CodegenError> {
+        Codegen::validate_jumps(res)?;
+
+        let mut program = Codegen::lower(res)?;
+        let bytecode = Codegen::emit(&program)?;
+        Codegen::validate_jumpdests(res, &program, &bytecode)?;
+        if pipeline.is_empty() {
+            return Ok(bytecode)
+        }
+        pipeline.run(&mut program, cx)?;
+        let bytecode = Codegen::emit(&program)?;
+        relocate(res, &program);
+        Ok(bytecode)
+    }
+
+    /// Checks that every jump of the expanded code is matched
+    fn validate_jumps(res: &BytecodeRes) -> Result<(), CodegenError> {
+        // Labels passed as macro arguments must be defined by the invoking macro or its callers
+        if let Some(jump) = res.unmatched_jumps.iter().find(|uj| uj.scope_depth.is_some()) {
+            tracing::error!(target: "codegen", "LABEL ARGUMENT \"{}\" IS NOT DEFINED", jump.label);
+            return Err(CodegenError {
+                kind: CodegenErrorKind::UndefinedLabelArgument(jump.label.clone()),
+                span: jump.span.clone(),
+                token: None,
+                hint: did_you_mean(&jump.label, res.label_indices.keys().map(String::as_str)),
+            })
+        }
+
         if !res.unmatched_jumps.is_empty() {
             tracing::error!(
                 target: "codegen",
@@ -154,106 +519,70 @@ impl Codegen {
                         .collect::<Vec<Span>>(),
                 ),
                 token: None,
+                hint: res.unmatched_jumps.iter().find_map(|uj| {
+                    did_you_mean(&uj.label, res.label_indices.keys().map(String::as_str))
+                }),
             })
         }
 
         tracing::info!(target: "codegen", "GENERATING JUMPTABLE BYTECODE");
+        Ok(())
+    }
 
-        let mut bytecode = res.bytes.into_iter().map(|(_, b)| b.0).collect::<String>();
-        let mut table_offsets: HashMap<String, usize> = HashMap::new(); // table name -> bytecode offset
-        let mut table_offset = bytecode.len() / 2;
-
-        res.utilized_tables.iter().try_for_each(|jt| {
-            table_offsets.insert(jt.name.to_string(), table_offset);
-            let size = match bytes_util::hex_to_usize(bytes_util::bytes32_to_string(&jt.size, false).as_str()) {
-                Ok(s) => s,
-                Err(e) => {
-                    tracing::error!(target: "codegen", "Errored converting bytes32 to str. Bytes {:?} with error: {:?}", jt.size, e);
-                    return Err(CodegenError {
-                        kind: CodegenErrorKind::UsizeConversion(format!("{:?}", jt.size)),
-                        span: jt.span.clone(),
-                        token: None
-                    })
-                }
-            };
-            table_offset += size;
-
-            tracing::info!(target: "codegen", "GENERATING BYTECODE FOR TABLE: \"{}\"", jt.name);
-
-            let mut table_code = String::new();
-            jt
-                .statements
+    /// Checks that the labels jumped to are valid JUMPDESTs in the code emitted from the
+    /// [IRProgram] of the expanded code
+    fn validate_jumpdests(
+        res: &BytecodeRes,
+        program: &IRProgram,
+        bytecode: &str,
+    ) -> Result<(), CodegenError> {
+        let code_offset = program.offset;
+        let bytecode = &bytecode[..(program.code_size() * 2).min(bytecode.len())];
+
+        // Labels jumped to must end up as JUMPDESTs outside of push data, e.g. a label following
+        // a truncated push in `__VERBATIM` code would be swallowed by the push. The code may not
+        // start at offset zero, e.g. for tests that are prefixed with their inputs.
+        let instructions = bytes_util::split_instructions(bytecode);
+        let pushed = instructions
+            .iter()
+            .filter_map(|(_, _, data)| {
+                bytes_util::hex_to_usize(data).ok().filter(|_| !data.is_empty())
+            })
+            .collect::<BTreeSet<usize>>();
+        let tabled = res
+            .utilized_tables
+            .iter()
+            .flat_map(|t| t.statements.iter())
+            .filter_map(|s| match &s.ty {
+                StatementType::LabelCall(label) => Some(label.as_str()),
+                _ => None,
+            })
+            .collect::<BTreeSet<&str>>();
+        for (label, index) in res.label_indices.iter() {
+            let is_jumpdest = instructions
                 .iter()
-                .try_for_each(|s| {
-                    match &s.ty {
-                        StatementType::LabelCall(label) => {
-                            let offset = match res.label_indices.get(label) {
-                                Some(l) => l,
-                                None => {
-                                    tracing::error!(
-                                    target: "codegen",
-                                    "Definition not found for Jump Table Label: \"{}\"",
-                                    label
-                                );
-                                    return Err(CodegenError {
-                                        kind: CodegenErrorKind::UnmatchedJumpLabel,
-                                        span: s.span.clone(),
-                                        token: None,
-                                    });
-                                }
-                            };
-                            let hex = format_even_bytes(format!("{offset:02x}"));
-
-                            table_code = format!("{table_code}{}", pad_n_bytes(
-                                hex.as_str(),
-                                if matches!(jt.kind, TableKind::JumpTablePacked) { 0x02 } else { 0x20 },
-                            ));
-                        }
-                        StatementType::Code(code) => {
-                            // Check if code length is even
-                            if code.len() % 2 != 0 {
-                                return Err(CodegenError {
-                                    kind: CodegenErrorKind::InvalidCodeLength(code.len()),
-                                    span: s.span.clone(),
-                                    token: None,
-                                });
-                            }
-
-                            table_code = format!("{table_code}{code}");
-                        }
-                        _ => {
-                            return Err(CodegenError {
-                                kind: CodegenErrorKind::InvalidMacroStatement,
-                                span: jt.span.clone(),
-                                token: None
-                            })
-                        }
-                    }
-                    Ok(())
-                })?;
-            tracing::info!(target: "codegen", "SUCCESSFULLY GENERATED BYTECODE FOR TABLE: \"{}\"", jt.name);
-            bytecode = format!("{bytecode}{table_code}");
-            Ok(())
-        })?;
-
-        res.table_instances.iter().for_each(|jump| {
-            if let Some(o) = table_offsets.get(&jump.label) {
-                let before = &bytecode[0..jump.bytecode_index * 2 + 2];
-                let after = &bytecode[jump.bytecode_index * 2 + 6..];
-
-                bytecode =
-                    format!("{before}{}{after}", pad_n_bytes(format!("{o:02x}").as_str(), 2));
-                tracing::info!(target: "codegen", "FILLED JUMPDEST FOR LABEL \"{}\"", jump.label);
-            } else {
-                tracing::error!(
-                    target: "codegen",
-                    "Jump table offset not present for jump label \"{}\"",
-                    jump.label
-                );
+                .any(|(pc, opcode, _)| pc + code_offset == *index && *opcode == 0x5b);
+            if !is_jumpdest && (pushed.contains(index) || tabled.contains(label.as_str())) {
+                tracing::error!(target: "codegen", "LABEL \"{}\" IS NOT A VALID JUMPDEST", label);
+                return Err(CodegenError {
+                    kind: CodegenErrorKind::InvalidJumpDestination(label.clone()),
+                    span: AstSpan(res.label_definitions.get(label).cloned().unwrap_or_default()),
+                    token: None,
+                    hint: None,
+                })
             }
-        });
+        }
+        Ok(())
+    }
 
-        Ok(bytecode)
+    /// Lowers the expanded code of a macro into the [IRProgram] its bytecode is emitted from
+    pub fn lower(res: &BytecodeRes) -> Result<IRProgram, CodegenError> {
+        lower(res)
+    }
+
+    /// Emits the bytecode of an [IRProgram], resolving the labels and tables it references
+    pub fn emit(program: &IRProgram) -> Result<String, CodegenError> {
+        emit(program)
     }
 
     /// Recurses a MacroDefinition to generate Bytecode
@@ -262,12 +591,12 @@ impl Codegen {
     ///
     /// `macro_to_bytecode` first transforms the macro definition into "IR" Bytecode - a vec of
     /// intermediate bytes. It then iterates over each byte, converting the
-    /// [IRByte](struct.IRByte.html) into a `Bytes`. Once done iterating over the macro
+    /// [IRByte](struct.IRByte.html) into IR instructions. Once done iterating over the macro
     /// definition IRBytes, we use the JumpTable to match any unmatched jumps. If jumps are not
     /// matched, they are appended to a vec of unmatched jumps.
     ///
     /// On success, a [BytecodeRes](struct.BytecodeRes.html) is returned,
-    /// containing the generated bytes, label indices, unmatched jumps, and table indices.
+    /// containing the generated instructions, label indices, unmatched jumps, and table indices.
     ///
     /// ## Arguments
     ///
@@ -281,37 +610,53 @@ impl Codegen {
         evm_version: &EVMVersion,
         macro_def: &'a MacroDefinition,
         contract: &'a Contract,
+        settings: &CodegenSettings,
         scope: &mut Vec<&'a MacroDefinition>,
         mut offset: usize,
         mis: &mut Vec<(usize, MacroInvocation)>,
         recursing_constructor: bool,
         circular_codesize_invocations: Option<&mut CircularCodeSizeIndices>,
+        expansions: Option<&mut ExpansionCache>,
     ) -> Result<BytecodeRes, CodegenError> {
         // Get intermediate bytecode representation of the macro definition
-        let mut bytes: Vec<(usize, Bytes)> = Vec::default();
-        let ir_bytes = macro_def.to_irbytecode(evm_version)?.0;
+        let mut bytes: Vec<IRInstruction> = Vec::default();
+        let mut constants: BTreeMap<String, String> = BTreeMap::new();
+        let scope_depth = scope.len();
+        // Unroll `__REPEAT` blocks first, their counts are only known once constants are evaluated
+        let statements = expand_repeats(&macro_def.statements, contract)?;
+        let ir_bytes = MacroDefinition::to_irbytes(evm_version, &statements);
 
         // Define outer loop variables
         let mut jump_table = JumpTable::new();
         let mut label_indices = LabelIndices::new();
+        let mut label_definitions = LabelDefinitions::new();
+        let mut defined_labels = LabelIndices::new();
         let mut table_instances = Jumps::new();
         let mut utilized_tables: Vec<TableDefinition> = Vec::new();
+        let mut macro_ranges: Vec<MacroRange> = Vec::new();
+        let mut source_spans: Vec<BytecodeSpan> = Vec::new();
+        let mut label_references: Vec<usize> = Vec::new();
         let mut ccsi = CircularCodeSizeIndices::new();
         let circular_codesize_invocations = circular_codesize_invocations.unwrap_or(&mut ccsi);
+        let mut cache = ExpansionCache::new();
+        let expansions = expansions.unwrap_or(&mut cache);
 
         // Loop through all intermediate bytecode representations generated from the AST
         for (_ir_bytes_index, ir_byte) in ir_bytes.iter().enumerate() {
             let starting_offset = offset;
+            let recorded_spans = source_spans.len();
+            let generated = bytes.len();
             match &ir_byte.ty {
-                IRByteType::Bytes(b) => {
-                    offset += b.0.len() / 2;
-                    bytes.push((starting_offset, b.to_owned()));
+                IRByteType::Instruction(ty) => {
+                    offset += ty.size();
+                    bytes.push(IRInstruction::at(starting_offset, ty.clone()));
                 }
                 IRByteType::Constant(name) => {
-                    let push_bytes = constant_gen(evm_version, name, contract, ir_byte.span)?;
-                    offset += push_bytes.len() / 2;
-                    tracing::debug!(target: "codegen", "OFFSET: {}, PUSH BYTES: {:?}", offset, push_bytes);
-                    bytes.push((starting_offset, Bytes(push_bytes)));
+                    let push =
+                        constant_gen(evm_version, name, contract, ir_byte.span, &mut constants)?;
+                    offset += push.size();
+                    tracing::debug!(target: "codegen", "OFFSET: {}, PUSH: {:?}", offset, push);
+                    bytes.push(IRInstruction::at(starting_offset, push));
                 }
                 IRByteType::Statement(s) => {
                     // if we have a codesize call for the constructor here, from within the
@@ -319,38 +664,78 @@ impl Codegen {
                     if recursing_constructor {
                         continue
                     }
-                    let mut push_bytes = statement_gen(
+                    if let StatementType::Label(label) = &s.ty {
+                        defined_labels.insert(label.name.clone(), starting_offset);
+                    }
+                    let instructions = statement_gen(
                         evm_version,
                         s,
                         contract,
+                        settings,
                         macro_def,
                         scope,
                         &mut offset,
                         mis,
                         &mut jump_table,
                         &mut label_indices,
+                        &mut label_definitions,
                         &mut table_instances,
                         &mut utilized_tables,
+                        &mut macro_ranges,
+                        &mut source_spans,
+                        &mut label_references,
+                        &mut constants,
                         circular_codesize_invocations,
+                        expansions,
                         starting_offset,
                     )?;
-                    bytes.append(&mut push_bytes);
+                    Codegen::append_instructions(&mut bytes, instructions);
                 }
                 IRByteType::ArgCall(arg_name) => {
                     // Bubble up arg call by looking through the previous scopes.
                     // Once the arg value is found, add it to `bytes`
+                    let mut instructions = vec![];
                     bubble_arg_call(
+                        evm_version,
                         arg_name,
-                        &mut bytes,
+                        &mut instructions,
                         macro_def,
                         contract,
+                        settings,
                         scope,
                         &mut offset,
                         mis,
                         &mut jump_table,
-                    )?
+                        &mut label_indices,
+                        &mut label_definitions,
+                        &mut table_instances,
+                        &mut utilized_tables,
+                        &mut macro_ranges,
+                        &mut source_spans,
+                        &mut label_references,
+                        &mut constants,
+                        circular_codesize_invocations,
+                        expansions,
+                    )?;
+                    Codegen::append_instructions(&mut bytes, instructions);
                 }
             }
+
+            // Attribute the generated instructions to the statement, unless they come from an
+            // expanded macro with statements of its own
+            for instruction in bytes[generated..].iter_mut().filter(|i| i.span.0.is_empty()) {
+                instruction.span = ir_byte.span.clone();
+            }
+
+            // Map the generated bytes to the statement, unless they come from an expanded macro
+            // with statements of its own
+            if offset > starting_offset && source_spans.len() == recorded_spans {
+                source_spans.push(BytecodeSpan {
+                    start: starting_offset,
+                    end: offset,
+                    span: ir_byte.span.clone(),
+                });
+            }
         }
 
         // We're done, let's pop off the macro invocation
@@ -364,12 +749,17 @@ impl Codegen {
             bytes = Codegen::append_functions(
                 evm_version,
                 contract,
+                settings,
                 scope,
                 &mut offset,
                 mis,
                 &mut jump_table,
                 &mut label_indices,
                 &mut table_instances,
+                &mut macro_ranges,
+                &mut source_spans,
+                &mut label_references,
+                &mut constants,
                 bytes,
             )?;
         } else {
@@ -379,7 +769,14 @@ impl Codegen {
         }
 
         // Fill JUMPDEST placeholders
-        let (bytes, unmatched_jumps) = Codegen::fill_unmatched(bytes, &jump_table, &label_indices)?;
+        let (bytes, unmatched_jumps) = Codegen::fill_unmatched(
+            bytes,
+            &jump_table,
+            &label_indices,
+            &label_definitions,
+            &mut label_references,
+            scope_depth,
+        )?;
 
         // Fill in circular codesize invocations
         // Workout how to increase the offset the correct amount within here if it is longer than 2
@@ -388,76 +785,168 @@ impl Codegen {
             bytes,
             circular_codesize_invocations,
             &macro_def.name,
+            &mut label_indices,
+            &mut defined_labels,
         )?;
 
-        Ok(BytecodeRes { bytes, label_indices, unmatched_jumps, table_instances, utilized_tables })
+        Ok(BytecodeRes {
+            instructions: bytes,
+            constants,
+            label_indices,
+            defined_labels,
+            label_definitions,
+            unmatched_jumps,
+            table_instances,
+            utilized_tables,
+            macro_ranges,
+            source_spans,
+            label_references,
+        })
+    }
+
+    /// Appends generated instructions to the code of a macro
+    ///
+    /// Labels the code already defines, eg. when the same macro is expanded twice, are renamed
+    /// after their program counter along with the pushes referencing them, so that every label
+    /// of the IR is unique.
+    fn append_instructions(bytes: &mut Vec<IRInstruction>, mut instructions: Vec<IRInstruction>) {
+        let defined = bytes
+            .iter()
+            .filter_map(|i| match &i.ty {
+                IRInstructionType::Label(name) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect::<BTreeSet<&str>>();
+        let renamed = instructions
+            .iter()
+            .filter_map(|i| match (&i.ty, i.origin) {
+                (IRInstructionType::Label(name), Some(pc)) if defined.contains(name.as_str()) => {
+                    Some((name.clone(), format!("{name}@{pc:04x}")))
+                }
+                _ => None,
+            })
+            .collect::<BTreeMap<String, String>>();
+        for instruction in instructions.iter_mut() {
+            match &mut instruction.ty {
+                IRInstructionType::Label(name) |
+                IRInstructionType::Push(_, IRValue::Label(name)) => {
+                    if let Some(new_name) = renamed.get(name) {
+                        *name = new_name.clone();
+                    }
+                }
+                _ => {}
+            }
+        }
+        bytes.append(&mut instructions);
     }
 
     /// Helper associated function to fill unmatched jump dests.
     ///
     /// ## Overview
     ///
-    /// Iterates over the vec of generated bytes. At each index, check if a jump is tracked.
-    /// If one is, find the index of label and inplace the formatted location.
+    /// Iterates over the generated instructions. At each index, check if a jump is tracked.
+    /// If one is, find the label at the index of the jump's label and reference it by the push.
     /// If there is no label matching the jump, we append the jump to a list of unmatched jumps,
     /// updating the jump's bytecode index.
     ///
-    /// On success, returns a tuple of generated bytes and unmatched jumps.
-    /// On failure, returns a CodegenError.
-    #[allow(clippy::type_complexity)]
+    /// Jumps to labels passed as macro arguments are left unmatched until `scope_depth` reaches
+    /// the macro the argument was written in, so that they resolve to the caller's labels. The
+    /// program counters of the filled pushes are added to `label_references`.
+    ///
+    /// On success, returns a tuple of generated instructions and unmatched jumps.
+    /// On failure, returns a CodegenError, e.g. if a jump's label is defined more than once or
+    /// its offset doesn't fit in the `PUSH2` reserved for it.
     pub fn fill_unmatched(
-        bytes: Vec<(usize, Bytes)>,
+        bytes: Vec<IRInstruction>,
         jump_table: &JumpTable,
         label_indices: &LabelIndices,
-    ) -> Result<(Vec<(usize, Bytes)>, Vec<Jump>), CodegenError> {
+        label_definitions: &LabelDefinitions,
+        label_references: &mut Vec<usize>,
+        scope_depth: usize,
+    ) -> Result<(Vec<IRInstruction>, Vec<Jump>), CodegenError> {
+        let in_scope = |jump: &Jump| jump.scope_depth.map_or(true, |depth| depth >= scope_depth);
+
+        // Jumps to a label defined more than once would silently go to the last definition
+        for jump in jump_table.values().flatten().filter(|j| in_scope(j)) {
+            if let Some(spans) = label_definitions.get(&jump.label).filter(|s| s.len() > 1) {
+                tracing::error!(target: "codegen", "LABEL \"{}\" IS DEFINED MORE THAN ONCE", jump.label);
+                return Err(CodegenError {
+                    kind: CodegenErrorKind::DuplicateLabel(jump.label.clone()),
+                    span: AstSpan(spans.clone()),
+                    token: None,
+                    hint: None,
+                })
+            }
+        }
+
+        // Pushes of a label reference the label instruction at its program counter
+        let labels = bytes
+            .iter()
+            .filter_map(|i| match (&i.ty, i.origin) {
+                (IRInstructionType::Label(name), Some(pc)) => Some((pc, name.clone())),
+                _ => None,
+            })
+            .collect::<BTreeMap<usize, String>>();
+
         let mut unmatched_jumps = Jumps::default();
-        let bytes =
-            bytes.into_iter().fold(Vec::default(), |mut acc, (code_index, mut formatted_bytes)| {
-                // Check if a jump table exists at `code_index` (starting offset of `b`)
-                if let Some(jt) = jump_table.get(&code_index) {
-                    // Loop through jumps inside of the found JumpTable
-                    for jump in jt {
-                        // Check if the jump label has been defined. If not, add `jump` to the
-                        // unmatched jumps and define its `bytecode_index`
-                        // at `code_index`
-                        if let Some(jump_index) = label_indices.get(jump.label.as_str()) {
-                            // Format the jump index as a 2 byte hex number
-                            let jump_value = format!("{jump_index:04x}");
-
-                            // Get the bytes before & after the placeholder
-                            let before = &formatted_bytes.0[0..jump.bytecode_index + 2];
-                            let after = &formatted_bytes.0[jump.bytecode_index + 6..];
-
-                            // Check if a jump dest placeholder is present
-                            if !&formatted_bytes.0[jump.bytecode_index + 2..jump.bytecode_index + 6]
-                                .eq("xxxx")
-                            {
-                                tracing::error!(
-                                    target: "codegen",
-                                    "JUMP DESTINATION PLACEHOLDER NOT FOUND FOR JUMPLABEL {}",
-                                    jump.label
-                                );
-                            }
-
-                            // Replace the "xxxx" placeholder with the jump value
-                            formatted_bytes = Bytes(format!("{before}{jump_value}{after}"));
-                        } else {
-                            // The jump did not have a corresponding label index. Add it to the
-                            // unmatched jumps vec.
-                            unmatched_jumps.push(Jump {
-                                label: jump.label.clone(),
-                                bytecode_index: code_index,
-                                span: jump.span.clone(),
-                            });
-                        }
+        let mut filled = Vec::with_capacity(bytes.len());
+        for mut instruction in bytes {
+            // Check if a jump table exists at the program counter of the push
+            let code_index = instruction.origin.unwrap_or_default();
+            let jt = jump_table
+                .get(&code_index)
+                .filter(|_| matches!(instruction.ty, IRInstructionType::Push(..)));
+            // Loop through jumps inside of the found JumpTable
+            for jump in jt.into_iter().flatten() {
+                // Check if the jump label has been defined. If not, add `jump` to the
+                // unmatched jumps and define its `bytecode_index` at `code_index`
+                if let Some(jump_index) =
+                    label_indices.get(jump.label.as_str()).filter(|_| in_scope(jump))
+                {
+                    // Jump destinations past the first 64 KiB would be truncated
+                    let needed = bytes_util::bytes_needed(*jump_index);
+                    if needed > 2 {
+                        tracing::error!(target: "codegen", "OFFSET OF \"{}\" EXCEEDS ITS PUSH2", jump.label);
+                        return Err(CodegenError {
+                            kind: CodegenErrorKind::OffsetExceedsPushWidth(
+                                jump.label.clone(),
+                                2,
+                                needed,
+                            ),
+                            span: jump.span.clone(),
+                            token: None,
+                            hint: None,
+                        })
                     }
+
+                    let Some(label) = labels.get(jump_index) else {
+                        tracing::error!(
+                            target: "codegen",
+                            "JUMP DESTINATION NOT FOUND FOR JUMPLABEL {}",
+                            jump.label
+                        );
+                        continue
+                    };
+
+                    // Reference the label by the push
+                    instruction.ty = IRInstructionType::Push(2, IRValue::Label(label.clone()));
+                    label_references.push(code_index);
+                } else {
+                    // The jump did not have a corresponding label index. Add it to the
+                    // unmatched jumps vec.
+                    unmatched_jumps.push(Jump {
+                        label: jump.label.clone(),
+                        bytecode_index: code_index,
+                        span: jump.span.clone(),
+                        scope_depth: jump.scope_depth,
+                    });
                 }
+            }
 
-                acc.push((code_index, formatted_bytes));
-                acc
-            });
+            filled.push(instruction);
+        }
 
-        Ok((bytes, unmatched_jumps))
+        Ok((filled, unmatched_jumps))
     }
 
     /// Helper associated function to fill circular codesize invocations.
@@ -471,21 +960,29 @@ impl Codegen {
     /// If there are multiple invocations of the same macro, the function will take into
     /// account the total number of invocations and increase its offset accordingly.
     ///
-    /// On success, returns a tuple of generated bytes.
+    /// The program counters of the labels following a placeholder are moved along with the code.
+    ///
+    /// On success, returns the generated instructions.
     /// On failure, returns a CodegenError.
     pub fn fill_circular_codesize_invocations(
-        bytes: Vec<(usize, Bytes)>,
+        bytes: Vec<IRInstruction>,
         circular_codesize_invocations: &mut CircularCodeSizeIndices,
         macro_name: &str,
-    ) -> Result<Vec<(usize, Bytes)>, CodegenError> {
+        label_indices: &mut LabelIndices,
+        defined_labels: &mut LabelIndices,
+    ) -> Result<Vec<IRInstruction>, CodegenError> {
+        let is_placeholder = |i: &IRInstruction| {
+            matches!(&i.ty, IRInstructionType::Push(_, IRValue::Codesize(name)) if name == macro_name)
+        };
+
         // Get the length of the macro
-        let num_invocations = circular_codesize_invocations.len();
-        if num_invocations == 0 {
+        let num_invocations = bytes.iter().filter(|i| is_placeholder(i)).count();
+        if circular_codesize_invocations.is_empty() || num_invocations == 0 {
             return Ok(bytes)
         }
 
-        tracing::debug!(target: "codegen", "Circular Codesize Invocation: Bytes before expansion: {:#?}", bytes);
-        let length: usize = bytes.iter().map(|(_, b)| b.0.len()).sum::<usize>() / 2;
+        tracing::debug!(target: "codegen", "Circular Codesize Invocation: Instructions before expansion: {:#?}", bytes);
+        let length: usize = bytes.iter().map(|i| i.ty.size()).sum();
 
         // If there are more than 256 opcodes in a macro, we need 2 bytes to represent it
         // The next threshold is 65536 opcodes which is past the codesize limit
@@ -496,38 +993,34 @@ impl Codegen {
         // Codesize will increase by 1 byte for every codesize that exists
         let extended_length = length + (offset_increase * num_invocations);
 
-        let push_size = format_even_bytes(format!("{extended_length:02x}"));
-        let push_bytes = format!("{:02x}{push_size}", 95 + push_size.len() / 2);
+        let push = IRInstructionType::push(format_even_bytes(format!("{extended_length:02x}")));
 
         // Track the number of bytes added if there is an offset increase with codesize
         let mut running_increase = 0;
-        let bytes = bytes.into_iter().fold(
-            Vec::default(),
-            |mut acc, (mut code_index, mut formatted_bytes)| {
-                // Check if a jump table exists at `code_index` (starting offset of `b`)
-                if let Some((_, _index)) =
-                    circular_codesize_invocations.get(&(macro_name.to_string(), code_index))
-                {
-                    // Check if a jump dest placeholder is present
-                    if !&formatted_bytes.0.eq("cccc") {
-                        tracing::error!(
-                            target: "codegen",
-                            "CIRCULAR CODESIZE PLACEHOLDER NOT FOUND"
-                        );
-                    }
-
-                    // Replace the "cccc" placeholder with the jump value
-                    formatted_bytes = Bytes(push_bytes.to_string());
+        let mut increases: Vec<(usize, usize)> = vec![];
+        let bytes = bytes
+            .into_iter()
+            .map(|mut instruction| {
+                // Increase the code index by the number of bytes added before the instruction
+                let origin = instruction.origin.unwrap_or_default();
+                instruction.origin = instruction.origin.map(|pc| pc + running_increase);
+                if is_placeholder(&instruction) {
+                    instruction.ty = push.clone();
                     running_increase += offset_increase;
-                } else {
-                    // Increase the code index by the number of bytes added past the placeholder
-                    code_index += running_increase;
+                    increases.push((origin, running_increase));
                 }
+                instruction
+            })
+            .collect::<Vec<IRInstruction>>();
 
-                acc.push((code_index, formatted_bytes));
-                acc
-            },
-        );
+        // Labels move by the bytes added before them
+        let move_labels = |labels: &mut LabelIndices| {
+            for pc in labels.values_mut() {
+                *pc += increases.iter().rev().find(|(at, _)| at < pc).map_or(0, |(_, i)| *i);
+            }
+        };
+        move_labels(label_indices);
+        move_labels(defined_labels);
 
         Ok(bytes)
     }
@@ -545,14 +1038,19 @@ impl Codegen {
     pub fn append_functions<'a>(
         evm_version: &EVMVersion,
         contract: &'a Contract,
+        settings: &CodegenSettings,
         scope: &mut Vec<&'a MacroDefinition>,
         offset: &mut usize,
         mis: &mut Vec<(usize, MacroInvocation)>,
         jump_table: &mut JumpTable,
         label_indices: &mut LabelIndices,
         table_instances: &mut Jumps,
-        mut bytes: Vec<(usize, Bytes)>,
-    ) -> Result<Vec<(usize, Bytes)>, CodegenError> {
+        macro_ranges: &mut Vec<MacroRange>,
+        source_spans: &mut Vec<BytecodeSpan>,
+        label_references: &mut Vec<usize>,
+        constants: &mut BTreeMap<String, String>,
+        mut bytes: Vec<IRInstruction>,
+    ) -> Result<Vec<IRInstruction>, CodegenError> {
         for macro_def in contract.macros.iter().filter(|m| m.outlined) {
             // Push the function to the scope
             scope.push(macro_def);
@@ -562,11 +1060,13 @@ impl Codegen {
                 evm_version,
                 macro_def,
                 contract,
+                settings,
                 scope,
                 *offset + 1,
                 mis,
                 false,
                 None,
+                None,
             )?;
 
             for j in res.unmatched_jumps.iter_mut() {
@@ -580,25 +1080,42 @@ impl Codegen {
                 new_jumps.push(j.clone());
                 jump_table.insert(new_index, new_jumps);
             }
+            // Labels defined inside of a function are scoped to the function body, so they are
+            // not merged into the caller's label indices.
             table_instances.extend(res.table_instances);
-            label_indices.extend(res.label_indices);
+            macro_ranges.extend(res.macro_ranges);
+            source_spans.extend(res.source_spans);
+            label_references.extend(res.label_references);
+            constants.extend(res.constants);
 
-            let macro_code_len = res.bytes.iter().map(|(_, b)| b.0.len()).sum::<usize>() / 2;
+            let macro_code_len = res.instructions.iter().map(|i| i.ty.size()).sum::<usize>();
 
             // Get necessary swap ops to reorder stack
             // PC of the return jumpdest should be above the function's outputs on the stack
-            let stack_swaps =
-                (0..macro_def.returns).map(|i| format!("{:02x}", 0x90 + i)).collect::<Vec<_>>();
+            let stack_swaps = (0..macro_def.returns)
+                .filter_map(|i| Opcode::from_byte(0x90 + i as u8))
+                .collect::<Vec<Opcode>>();
 
             // Insert JUMPDEST, stack swaps, and final JUMP back to the location of invocation.
-            bytes.push((*offset, Bytes(Opcode::Jumpdest.to_string())));
-            res.bytes.push((
-                *offset + macro_code_len + 1,
-                Bytes(format!("{}{}", stack_swaps.join(""), Opcode::Jump)),
-            ));
-            bytes = [bytes, res.bytes].concat();
+            let goto = format!("goto_{}", macro_def.name);
+            let mut function = vec![
+                IRInstruction::at(*offset, IRInstructionType::Label(goto.clone())),
+                IRInstruction::at(*offset, IRInstructionType::Opcode(Opcode::Jumpdest)),
+            ];
+            function.append(&mut res.instructions);
+            let end = *offset + macro_code_len + 1;
+            for (i, opcode) in stack_swaps.iter().chain([Opcode::Jump].iter()).enumerate() {
+                function.push(IRInstruction::at(end + i, IRInstructionType::Opcode(*opcode)));
+            }
+            Codegen::append_instructions(&mut bytes, function);
             // Add the jumpdest to the beginning of the outlined macro.
-            label_indices.insert(format!("goto_{}", macro_def.name.clone()), *offset);
+            label_indices.insert(goto, *offset);
+            macro_ranges.push(MacroRange {
+                name: macro_def.name.clone(),
+                start: *offset,
+                end: *offset + macro_code_len + stack_swaps.len() + 2,
+                labels: res.defined_labels,
+            });
             *offset += macro_code_len + stack_swaps.len() + 2; // JUMPDEST + MACRO_CODE_LEN +
                                                                // stack_swaps.len() + JUMP
         }
@@ -615,7 +1132,7 @@ impl Codegen {
     pub fn churn(
         &mut self,
         file: Arc<FileSource>,
-        mut args: Vec<ethers_core::abi::token::Token>,
+        args: Vec<ethers_core::abi::token::Token>,
         main_bytecode: &str,
         constructor_bytecode: &str,
         has_custom_bootstrap: bool,
@@ -633,71 +1150,29 @@ impl Codegen {
         let contract_length = main_bytecode.len() / 2;
         let constructor_length = constructor_bytecode.len() / 2;
 
-        // Sort constructor arguments so that statically sized args are inserted last.
-        args.sort_by(|a, b| {
-            if a.is_dynamic() && !b.is_dynamic() {
-                Ordering::Less
-            } else if !a.is_dynamic() && b.is_dynamic() {
-                Ordering::Greater
-            } else {
-                Ordering::Equal
-            }
-        });
-
-        let mut arg_offset_acc = contract_length;
-        let encoded: Vec<Vec<u8>> = args
-            .into_iter()
-            .enumerate()
-            .map(|(i, tok)| {
-                if tok.is_dynamic() {
-                    let encoded = ethers_core::abi::encode(&[tok]);
-
-                    // Check for "__CODECOPY_DYN_ARG" calls for this specific argument. If any
-                    // exist, fill the placeholders.
-                    let tok_len = hex::encode(&encoded[62..64]);
-                    let rep_regex =
-                        Regex::new(format!("xxxxxxxxxxxxxxxxxxxxxxxxxxxx{i:02x}\\d{{4}}").as_str())
-                            .unwrap();
-                    rep_regex.find_iter(main_bytecode.clone().as_str()).for_each(|s| {
-                        // TODO: Enforce that the arg type is a literal so that this unwrap is safe.
-                        let len_ptr = usize::from_str_radix(&s.as_str()[30..34], 16).unwrap();
-                        let contents_ptr = len_ptr + 0x20;
-
-                        // Replace 17 reserved bytes.
-                        main_bytecode.replace_range(
-                            s.range(),
-                            format!(
-                                "{}{}{}{:04x}{}{}{}{}{:04x}{}{:04x}{}",
-                                Opcode::Push2,    // PUSH2
-                                &tok_len,         // len(bytes)
-                                Opcode::Push2,    // PUSH2
-                                len_ptr,          // <len_mem_ptr>
-                                Opcode::Mstore,   // MSTORE
-                                Opcode::Push2,    // PUSH2
-                                &tok_len,         // len(bytes)
-                                Opcode::Push2,    // PUSH2
-                                arg_offset_acc,   // <contents_code_ptr>
-                                Opcode::Push2,    // PUSH2
-                                contents_ptr,     // <contents_mem_ptr>
-                                Opcode::Codecopy  // CODECOPY
-                            )
-                            .as_str(),
-                        );
-                    });
-
-                    // Increase argument offset accumulator.
-                    arg_offset_acc += encoded.len() - 64;
-
-                    // We don't need to store the pointer nor the length of dynamically sized
-                    // elements in the code.
-                    encoded[64..].into()
-                } else {
-                    ethers_core::abi::encode(&[tok])
-                }
+        // Constructor arguments are appended with their standard ABI encoding, in declaration order
+        let encoded_args = ethers_core::abi::encode(&args);
+        let constructor_args = hex::encode(&encoded_args);
+
+        // Fill the "__CODECOPY_DYN_ARG" placeholders of each dynamic argument. The head of a
+        // dynamic argument holds the offset of its length, which its contents follow.
+        let mut head = 0;
+        let dyn_heads = args
+            .iter()
+            .filter_map(|tok| {
+                let offset = head;
+                head += Codegen::head_size(&Codegen::token_param_type(tok));
+                tok.is_dynamic().then_some(offset)
             })
-            .collect();
-        let hex_args: Vec<String> = encoded.iter().map(|tok| hex::encode(tok.as_slice())).collect();
-        let constructor_args = hex_args.join("");
+            .collect::<Vec<usize>>();
+        for (i, head) in dyn_heads.into_iter().enumerate() {
+            let tail = U256::from_big_endian(&encoded_args[head..head + 32]).as_usize();
+            let tok_len = hex::encode(&encoded_args[tail + 30..tail + 32]);
+            let contents_code_ptr = format!("{:04x}", contract_length + tail + 0x20);
+            main_bytecode = main_bytecode
+                .replace(&dyn_arg_length_placeholder(i), &tok_len)
+                .replace(&dyn_arg_offset_placeholder(i), &contents_code_ptr);
+        }
 
         // Sucks that we can't provide a span on this error. Need to refactor at some point.
         if main_bytecode.contains('x') {
@@ -706,36 +1181,91 @@ impl Codegen {
                 kind: CodegenErrorKind::InvalidDynArgIndex,
                 span: AstSpan(vec![Span { start: 0, end: 0, file: None }]),
                 token: None,
+                hint: None,
             })
         }
 
-        // Constructor size optimizations
-        let mut bootstrap_code_size = 9;
-        let contract_size = if contract_length < 256 {
-            format!("60{}", pad_n_bytes(format!("{contract_length:x}").as_str(), 1))
-        } else {
-            bootstrap_code_size += 1;
+        // Fill immutable placeholders, the bootstrap code copies their values from the
+        // constructor arguments into the runtime code
+        let (main_bytecode, immutable_refs) = Codegen::fill_immutable_placeholders(&main_bytecode);
+        if (has_custom_bootstrap || self.bootstrap_shim.is_some()) && !immutable_refs.is_empty() {
+            tracing::error!(target: "codegen", "IMMUTABLES CAN'T BE FILLED BY A CUSTOM BOOTSTRAP");
+            return Err(CodegenError {
+                kind: CodegenErrorKind::ImmutablesWithCustomBootstrap,
+                span: AstSpan(vec![Span { start: 0, end: 0, file: None }]),
+                token: None,
+                hint: None,
+            })
+        }
 
-            format!("61{}", pad_n_bytes(format!("{contract_length:x}").as_str(), 2))
-        };
-        let contract_code_offset = if (bootstrap_code_size + constructor_length) < 256 {
-            format!(
-                "60{}",
-                pad_n_bytes(format!("{:x}", bootstrap_code_size + constructor_length).as_str(), 1)
-            )
+        // The memory pointer the runtime code is copied to
+        let memory_offset = self.bootstrap.memory_offset;
+        let memory_ptr = if memory_offset == 0 {
+            Opcode::Returndatasize.to_string()
         } else {
-            bootstrap_code_size += 1;
-
-            format!(
-                "61{}",
-                pad_n_bytes(format!("{:x}", bootstrap_code_size + constructor_length).as_str(), 2)
-            )
+            let hex_offset = bytes_util::bytes32_to_string(
+                &bytes_util::str_to_bytes32(&format!("{memory_offset:x}")),
+                false,
+            );
+            format!("{:02x}{hex_offset}", 95 + hex_offset.len() / 2)
         };
 
-        let bootstrap_code = if has_custom_bootstrap {
-            String::default()
-        } else {
-            format!("{contract_size}80{contract_code_offset}3d393df3")
+        // Constructor size optimizations
+        // A shim only follows the runtime code size and offset pushes, while the default
+        // bootstrap takes 9 bytes to fill each immutable reference
+        let mut bootstrap_code_size = match &self.bootstrap_shim {
+            Some(shim) => 4 + shim.len() / 2,
+            None => 7 + memory_ptr.len() + 9 * immutable_refs.len(),
+        };
+        let (contract_size, contract_code_offset) =
+            Codegen::runtime_pushes(constructor_length, contract_length, &mut bootstrap_code_size);
+
+        // The n-th immutable is filled with the head of the n-th constructor argument, laid out by
+        // the constructor's parameter types, or else by the arguments themselves
+        let constructor_types = self
+            .ast
+            .clone()
+            .and_then(|contract| Abi::from(contract).constructor)
+            .map(|c| c.inputs.into_iter().map(|input| input.kind.as_param_type()).collect())
+            .filter(|types: &Vec<ParamType>| !types.is_empty())
+            .unwrap_or_else(|| args.iter().map(Codegen::token_param_type).collect());
+        let args_offset = constructor_length + bootstrap_code_size + contract_length;
+        let immutable_code = immutable_refs
+            .iter()
+            .map(|(index, runtime_offset)| {
+                if constructor_types.get(*index).map_or(false, |ty| ty.is_dynamic()) {
+                    let immutable = self.ast.as_ref().and_then(|c| c.immutables.get(*index));
+                    let name = immutable.map_or_else(|| index.to_string(), |i| i.name.clone());
+                    tracing::error!(target: "codegen", "IMMUTABLE \"{}\" FILLED FROM A DYNAMIC ARGUMENT", name);
+                    return Err(CodegenError {
+                        kind: CodegenErrorKind::DynamicImmutableArgument(name),
+                        span: immutable.map(|i| i.span.clone()).unwrap_or_default(),
+                        token: None,
+                        hint: None,
+                    })
+                }
+                // Immutables past the constructor's parameters are filled with the words after
+                // the heads
+                let head: usize = constructor_types.iter().take(*index).map(Codegen::head_size).sum();
+                let head = head + index.saturating_sub(constructor_types.len()) * 32;
+                Ok(format!(
+                    "{}20{}{:04x}{}{:04x}{}",
+                    Opcode::Push1,                  // PUSH1
+                    Opcode::Push2,                  // PUSH2
+                    args_offset + head,             // <arg_code_ptr>
+                    Opcode::Push2,                  // PUSH2
+                    memory_offset + runtime_offset, // <immutable_mem_ptr>
+                    Opcode::Codecopy                // CODECOPY
+                ))
+            })
+            .collect::<Result<String, CodegenError>>()?;
+
+        let bootstrap_code = match &self.bootstrap_shim {
+            _ if has_custom_bootstrap => String::default(),
+            Some(shim) => format!("{contract_size}{contract_code_offset}{shim}"),
+            None => format!(
+                "{contract_size}80{contract_code_offset}{memory_ptr}39{immutable_code}{memory_ptr}f3"
+            ),
         };
 
         // Generate the final bytecode
@@ -743,10 +1273,130 @@ impl Codegen {
         artifact.bytecode =
             format!("{constructor_code}{main_bytecode}{constructor_args}").to_lowercase();
         artifact.runtime = main_bytecode.to_lowercase();
+        artifact.initcode_hash = artifact.hash_initcode();
+
+        // Map the instructions back to their statements, the runtime code following the
+        // constructor code in the deployed bytecode
+        let mut sources = vec![];
+        artifact.runtime_source_map = source_map(&artifact.runtime, &self.main_spans, &mut sources);
+        let runtime_offset = constructor_code.len() / 2;
+        let deployed_spans = self
+            .constructor_spans
+            .iter()
+            .cloned()
+            .chain(self.main_spans.iter().map(|s| BytecodeSpan {
+                start: s.start + runtime_offset,
+                end: s.end + runtime_offset,
+                span: s.span.clone(),
+            }))
+            .collect::<Vec<BytecodeSpan>>();
+        artifact.source_map = source_map(&artifact.bytecode, &deployed_spans, &mut sources);
+        artifact.source_list = sources;
+        artifact.bootstrap = self.bootstrap.clone();
         artifact.file = file;
         Ok(artifact.clone())
     }
 
+    /// The size of the head of an argument in its ABI encoding, a pointer to the tail of
+    /// dynamic arguments or the whole encoding of static ones
+    fn head_size(ty: &ParamType) -> usize {
+        match ty {
+            _ if ty.is_dynamic() => 32,
+            ParamType::FixedArray(inner, size) => size * Codegen::head_size(inner),
+            ParamType::Tuple(inner) => inner.iter().map(Codegen::head_size).sum(),
+            _ => 32,
+        }
+    }
+
+    /// The ABI type of an argument
+    fn token_param_type(tok: &ethers_core::abi::token::Token) -> ParamType {
+        use ethers_core::abi::token::Token;
+        match tok {
+            Token::Address(_) => ParamType::Address,
+            Token::FixedBytes(b) => ParamType::FixedBytes(b.len()),
+            Token::Bytes(_) => ParamType::Bytes,
+            Token::Int(_) => ParamType::Int(256),
+            Token::Uint(_) => ParamType::Uint(256),
+            Token::Bool(_) => ParamType::Bool,
+            Token::String(_) => ParamType::String,
+            Token::FixedArray(t) => ParamType::FixedArray(
+                Box::new(t.first().map_or(ParamType::Uint(256), Codegen::token_param_type)),
+                t.len(),
+            ),
+            Token::Array(t) => ParamType::Array(Box::new(
+                t.first().map_or(ParamType::Uint(256), Codegen::token_param_type),
+            )),
+            Token::Tuple(t) => ParamType::Tuple(t.iter().map(Codegen::token_param_type).collect()),
+        }
+    }
+
+    /// Encode constructor arguments as ethers_core::abi::token::Token using the types of the
+    /// constructor's parameters
+    pub fn encode_typed_constructor_args(
+        args: Vec<String>,
+        types: &[FunctionParamType],
+    ) -> Result<Vec<ethers_core::abi::token::Token>, CodegenError> {
+        let invalid_args = |msg: String| {
+            tracing::error!(target: "codegen", "INVALID CONSTRUCTOR ARGUMENTS: {}", msg);
+            CodegenError {
+                kind: CodegenErrorKind::InvalidArguments(msg),
+                span: AstSpan(vec![Span { start: 0, end: 0, file: None }]),
+                token: None,
+                hint: None,
+            }
+        };
+        if args.len() != types.len() {
+            return Err(invalid_args(format!(
+                "Expected {} constructor arguments, found {}",
+                types.len(),
+                args.len()
+            )))
+        }
+        args.iter()
+            .zip(types)
+            .map(|(arg, ty)| {
+                // Arguments may be passed as a comma separated list, eg. `100, 0xdead..`
+                let arg = arg.trim().trim_end_matches(',');
+                let arg = match ty {
+                    // Allow spaces between the elements of arrays and tuples, eg. `[1, 2]`
+                    FunctionParamType::Array(..) | FunctionParamType::Tuple(_) => {
+                        arg.split(',').map(str::trim).collect::<Vec<_>>().join(",")
+                    }
+                    // Allow hex unsigned integers, eg. `0x64`
+                    FunctionParamType::Uint(_) if arg.starts_with("0x") => {
+                        ethers_core::types::U256::from_str_radix(&arg[2..], 16)
+                            .map_err(|e| {
+                                invalid_args(format!(
+                                    "Invalid {ty} constructor argument \"{arg}\": {e}"
+                                ))
+                            })?
+                            .to_string()
+                    }
+                    _ => arg.to_string(),
+                };
+                LenientTokenizer::tokenize(&ty.as_param_type(), &arg).map_err(|e| {
+                    invalid_args(format!("Invalid {ty} constructor argument \"{arg}\": {e}"))
+                })
+            })
+            .collect()
+    }
+
+    /// Fills the immutable placeholders in a bytecode string with zeros
+    ///
+    /// Returns the filled bytecode, with the index of the immutable and the bytecode offset of
+    /// its value for each placeholder.
+    pub fn fill_immutable_placeholders(bytecode: &str) -> (String, Vec<(usize, usize)>) {
+        let placeholder_regex = Regex::new("z{60}([0-9a-f]{4})").unwrap();
+        let refs = placeholder_regex
+            .captures_iter(bytecode)
+            .map(|c| {
+                let index = usize::from_str_radix(&c[1], 16).unwrap();
+                (index, c.get(0).unwrap().start() / 2)
+            })
+            .collect();
+        (placeholder_regex.replace_all(bytecode, "0".repeat(64)).to_string(), refs)
+    }
+
     /// Encode constructor arguments as ethers_core::abi::token::Token
     pub fn encode_constructor_args(args: Vec<String>) -> Vec<ethers_core::abi::token::Token> {
         let tokens: Vec<ethers_core::abi::token::Token> =
@@ -781,6 +1431,7 @@ impl Codegen {
                         })),
                     }]),
                     token: None,
+                    hint: None,
                 })
             }
         }
@@ -799,6 +1450,7 @@ impl Codegen {
                     })),
                 }]),
                 token: None,
+                hint: None,
             })
         }
         Ok(())
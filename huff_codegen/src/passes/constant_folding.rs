@@ -0,0 +1,83 @@
+use huff_utils::prelude::*;
+
+use crate::passes::{CodegenPass, PassContext};
+
+/// Pushes literals with the cheapest instruction that leaves the same value on the stack
+///
+/// Literals and constants are pushed with the narrowest `PUSHX` they fit in, explicit push widths
+/// like `push4 0x01` included, and zero with `PUSH0` when the EVM version supports it. A literal
+/// that was pushed earlier within the same run of pushes, eg. the second `0x20` of
+/// `0x20 0x00 0x20`, is duplicated with a `DUPX` instead.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ConstantFolding;
+
+impl CodegenPass for ConstantFolding {
+    fn id(&self) -> &'static str {
+        "constant-folding"
+    }
+
+    fn description(&self) -> &'static str {
+        "Pushes literals with the narrowest push, and duplicates repeated pushes"
+    }
+
+    fn run(&self, program: &mut IRProgram, cx: &PassContext) -> Result<(), CodegenError> {
+        // Constants are folded by their value, but keep being pushed by their name
+        let constants = &program.constants;
+        let value_of = |value: &IRValue| match value {
+            IRValue::Literal(data) => minimal_value(data),
+            IRValue::Constant(name) => constants.get(name).and_then(|v| minimal_value(v)),
+            _ => None,
+        };
+
+        // The values pushed since the last instruction that wasn't a push, `None` if unknown
+        let mut pushed: Vec<Option<String>> = vec![];
+        for instruction in program.instructions.iter_mut() {
+            if let IRInstructionType::Push(width, value) = &instruction.ty {
+                if let Some(minimal) = value_of(value) {
+                    let narrowed = |data: String| match value {
+                        IRValue::Literal(_) => IRValue::Literal(data),
+                        _ => value.clone(),
+                    };
+                    instruction.ty = if minimal.is_empty() && cx.evm_version.has_push0() {
+                        IRInstructionType::Opcode(Opcode::Push0)
+                    } else if minimal.is_empty() {
+                        IRInstructionType::Push(1, narrowed("00".to_string()))
+                    } else if minimal.len() / 2 < *width {
+                        IRInstructionType::Push(minimal.len() / 2, narrowed(minimal))
+                    } else {
+                        instruction.ty.clone()
+                    };
+                }
+            }
+
+            let value = match &instruction.ty {
+                IRInstructionType::Opcode(Opcode::Push0) => Some(String::new()),
+                IRInstructionType::Push(_, value) => value_of(value),
+                _ => {
+                    pushed.clear();
+                    continue
+                }
+            };
+
+            // `PUSH0` is cheaper than duplicating the zero
+            if value.is_some() && instruction.ty != IRInstructionType::Opcode(Opcode::Push0) {
+                let depth = pushed.iter().rev().take(16).position(|v| *v == value);
+                if let Some(dup) = depth.and_then(|d| Opcode::from_byte(0x80 + d as u8)) {
+                    instruction.ty = IRInstructionType::Opcode(dup);
+                }
+            }
+            pushed.push(value);
+        }
+        Ok(())
+    }
+}
+
+/// The hex value of a literal without leading zero bytes, empty for zero, `None` for data that
+/// isn't hex, eg. placeholders
+fn minimal_value(data: &str) -> Option<String> {
+    if data.len() % 2 != 0 || !data.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None
+    }
+    let start = data.as_bytes().chunks(2).take_while(|byte| byte == b"00").count() * 2;
+    Some(data[start..].to_lowercase())
+}
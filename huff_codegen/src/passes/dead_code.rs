@@ -0,0 +1,103 @@
+use huff_utils::prelude::*;
+use std::collections::BTreeSet;
+
+use crate::passes::{CodegenPass, PassContext};
+
+/// Removes the code that can't be reached, and the tables that are never referenced
+///
+/// Code following a terminating instruction, eg. `STOP` or `JUMP`, is only reached through a
+/// label that is jumped to. Labels are jumped to when their program counter is pushed, or is an
+/// entry of a table whose start is pushed. Unreachable code containing raw bytes, eg. from
+/// `__VERBATIM`, is kept as it may be read as data.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DeadCodeElimination;
+
+impl CodegenPass for DeadCodeElimination {
+    fn id(&self) -> &'static str {
+        "dead-code"
+    }
+
+    fn description(&self) -> &'static str {
+        "Removes unreachable label blocks and unreferenced tables"
+    }
+
+    fn run(&self, program: &mut IRProgram, _: &PassContext) -> Result<(), CodegenError> {
+        // Removing code may leave other labels and tables without references
+        loop {
+            let tables = referenced_tables(program);
+            let unused = program.tables.len() - tables.len();
+            program.tables.retain(|t| tables.contains(&t.name));
+
+            let dead = dead_instructions(program);
+            if dead.is_empty() && unused == 0 {
+                return Ok(())
+            }
+            tracing::debug!(target: "codegen", "REMOVING {} UNREACHABLE INSTRUCTIONS", dead.len());
+            let mut index = 0;
+            program.instructions.retain(|_| {
+                let keep = !dead.contains(&index);
+                index += 1;
+                keep
+            });
+        }
+    }
+}
+
+/// The names of the tables whose start is pushed
+fn referenced_tables(program: &IRProgram) -> BTreeSet<String> {
+    program
+        .instructions
+        .iter()
+        .filter_map(|i| match &i.ty {
+            IRInstructionType::Push(_, IRValue::TableStart(name)) => Some(name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The names of the labels pushed, or in a table
+fn referenced_labels(program: &IRProgram) -> BTreeSet<&str> {
+    let pushed = program.instructions.iter().filter_map(|i| match &i.ty {
+        IRInstructionType::Push(_, IRValue::Label(name)) => Some(name.as_str()),
+        _ => None,
+    });
+    let tabled = program.tables.iter().flat_map(|t| &t.entries).filter_map(|e| match e {
+        IRTableEntry::Label(name) => Some(name.as_str()),
+        IRTableEntry::Code(_) => None,
+    });
+    pushed.chain(tabled).collect()
+}
+
+/// The indices of the instructions that can't be reached
+fn dead_instructions(program: &IRProgram) -> BTreeSet<usize> {
+    let referenced = referenced_labels(program);
+
+    let mut dead = BTreeSet::new();
+    let mut region: Vec<usize> = vec![];
+    let mut has_raw = false;
+    let mut reachable = true;
+    for (index, instruction) in program.instructions.iter().enumerate() {
+        if let IRInstructionType::Label(name) = &instruction.ty {
+            if referenced.contains(name.as_str()) {
+                if !has_raw {
+                    dead.extend(region.drain(..));
+                }
+                region.clear();
+                has_raw = false;
+                reachable = true;
+            }
+        }
+        if !reachable {
+            region.push(index);
+            has_raw |= matches!(instruction.ty, IRInstructionType::Raw(_));
+            continue
+        }
+        if matches!(&instruction.ty, IRInstructionType::Opcode(o) if o.is_terminating()) {
+            reachable = false;
+        }
+    }
+    if !has_raw {
+        dead.extend(region);
+    }
+    dead
+}
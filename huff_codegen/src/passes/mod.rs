@@ -0,0 +1,114 @@
+//! ## Codegen Passes
+//!
+//! Passes transform the [IRProgram] of an expanded macro between lowering and byte emission,
+//! eg. to optimize, instrument or obfuscate the code. Downstream crates implement
+//! [CodegenPass] and register it with the [Pipeline] of a compiler.
+
+use huff_utils::prelude::*;
+use std::{fmt, sync::Arc};
+
+mod constant_folding;
+mod dead_code;
+mod outlining;
+mod peephole;
+mod push_sizing;
+
+pub use constant_folding::*;
+pub use dead_code::*;
+pub use outlining::*;
+pub use peephole::*;
+pub use push_sizing::*;
+
+/// Gets the builtin optimization passes, in the order they're run
+pub fn optimization_passes() -> Vec<Arc<dyn CodegenPass>> {
+    vec![
+        Arc::new(ConstantFolding),
+        Arc::new(Peephole),
+        Arc::new(DeadCodeElimination),
+        Arc::new(PushSizing),
+    ]
+}
+
+/// A pass over the IR of a contract's code
+pub trait CodegenPass: Send + Sync {
+    /// The unique id of the pass, e.g. `peephole`
+    fn id(&self) -> &'static str;
+
+    /// A short description of what the pass does
+    fn description(&self) -> &'static str;
+
+    /// Transforms the program in place
+    fn run(&self, program: &mut IRProgram, cx: &PassContext) -> Result<(), CodegenError>;
+}
+
+/// The code a pass runs over
+#[derive(Debug, Clone, Copy)]
+pub struct PassContext<'a> {
+    /// The EVM version the code is compiled for
+    pub evm_version: &'a EVMVersion,
+    /// The contract the code is generated from
+    pub contract: &'a Contract,
+    /// The name of the macro the code is expanded from, e.g. `MAIN` or `CONSTRUCTOR`
+    pub macro_name: &'a str,
+    /// The ranges of the macros invoked in the code, by the program counters the instructions
+    /// were lowered from
+    pub macro_ranges: &'a [MacroRange],
+}
+
+/// Runs the registered passes in order
+#[derive(Clone, Default)]
+pub struct Pipeline {
+    /// The registered passes
+    passes: Vec<Arc<dyn CodegenPass>>,
+}
+
+impl fmt::Debug for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.passes.iter().map(|p| p.id())).finish()
+    }
+}
+
+impl Pipeline {
+    /// Creates a pipeline without any passes
+    pub fn new() -> Self {
+        Self { passes: vec![] }
+    }
+
+    /// Creates a pipeline with the builtin optimization passes
+    pub fn optimizer() -> Self {
+        let mut pipeline = Self::new();
+        optimization_passes().into_iter().for_each(|pass| pipeline.register(pass));
+        pipeline
+    }
+
+    /// Registers a pass to run after the registered ones, replacing any pass registered with
+    /// the same id in place
+    pub fn register(&mut self, pass: Arc<dyn CodegenPass>) {
+        match self.passes.iter_mut().find(|p| p.id() == pass.id()) {
+            Some(registered) => *registered = pass,
+            None => self.passes.push(pass),
+        }
+    }
+
+    /// Gets the registered passes
+    pub fn passes(&self) -> &[Arc<dyn CodegenPass>] {
+        &self.passes
+    }
+
+    /// Whether no passes are registered
+    pub fn is_empty(&self) -> bool {
+        self.passes.is_empty()
+    }
+
+    /// Runs the passes over a program, stopping at the first failing pass
+    pub fn run(&self, program: &mut IRProgram, cx: &PassContext) -> Result<(), CodegenError> {
+        for pass in self.passes.iter() {
+            tracing::debug!(target: "codegen", "RUNNING PASS [{}] OVER \"{}\"", pass.id(), cx.macro_name);
+            if let Err(e) = pass.run(program, cx) {
+                tracing::error!(target: "codegen", "PASS [{}] FAILED: {:?}", pass.id(), e.kind);
+                return Err(e)
+            }
+        }
+        Ok(())
+    }
+}
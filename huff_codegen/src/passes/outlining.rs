@@ -0,0 +1,223 @@
+use huff_utils::prelude::*;
+use std::ops::Range;
+
+use crate::passes::{CodegenPass, PassContext};
+
+/// Outlines the code of macros invoked repeatedly into subroutines, trading gas for size
+///
+/// The invocations of a macro expanding to the same code are replaced by a jump to a single copy
+/// of it placed after the code, which jumps back to the return address pushed by the invocation,
+/// like the code of a `#define fn`. Unlike functions, the stack items the code takes and returns
+/// are counted from the code itself rather than the macro's declaration. Macros are only outlined
+/// when it makes the code smaller, and not when their code contains labels, jumps or `PC`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Outlining;
+
+impl CodegenPass for Outlining {
+    fn id(&self) -> &'static str {
+        "outlining"
+    }
+
+    fn description(&self) -> &'static str {
+        "Outlines the code of repeatedly invoked macros into subroutines"
+    }
+
+    fn run(&self, program: &mut IRProgram, cx: &PassContext) -> Result<(), CodegenError> {
+        // The macros saving the most go first, the ones they invoke may still be outlined from
+        // their subroutine afterwards
+        while let Some(candidate) = best_candidate(program, cx.macro_ranges) {
+            tracing::debug!(
+                target: "codegen",
+                "OUTLINING {} INVOCATIONS OF \"{}\", SAVING {} BYTES",
+                candidate.invocations.len(),
+                candidate.name,
+                candidate.savings
+            );
+            outline(program, candidate);
+        }
+        Ok(())
+    }
+}
+
+/// The invocations of a macro expanding to the same code
+#[derive(Debug)]
+struct Candidate {
+    /// The name of the invoked macro
+    name: String,
+    /// The instructions of each invocation
+    invocations: Vec<Range<usize>>,
+    /// The stack items the code takes
+    takes: usize,
+    /// The stack items the code leaves on the stack
+    returns: usize,
+    /// Whether execution stops at the end of the code
+    terminating: bool,
+    /// The bytes outlining the invocations saves
+    savings: usize,
+}
+
+/// Gets the invocations outlining saves the most bytes for
+fn best_candidate(program: &IRProgram, macro_ranges: &[MacroRange]) -> Option<Candidate> {
+    let mut groups: Vec<(&str, Vec<Range<usize>>)> = vec![];
+    for range in macro_ranges {
+        // Labels defined in the macro, or the macros it invokes, are placed with the code
+        let nested = macro_ranges.iter().filter(|r| range.start <= r.start && r.end <= range.end);
+        if nested.clone().any(|r| !r.labels.is_empty()) {
+            continue
+        }
+        let Some(invocation) = invocation(program, range) else { continue };
+        let code = &program.instructions[invocation.clone()];
+        let group = groups.iter_mut().find(|(name, invocations)| {
+            *name == range.name &&
+                program.instructions[invocations[0].clone()]
+                    .iter()
+                    .map(|i| &i.ty)
+                    .eq(code.iter().map(|i| &i.ty))
+        });
+        match group {
+            Some((_, invocations)) => invocations.push(invocation),
+            None => groups.push((&range.name, vec![invocation])),
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter(|(_, invocations)| invocations.len() > 1)
+        .filter_map(|(name, invocations)| {
+            let code = &program.instructions[invocations[0].clone()];
+            let (takes, returns, terminating) = stack_effect(code)?;
+            if takes > 16 || returns > 16 {
+                return None
+            }
+
+            // `PUSH2 return PUSH2 subroutine JUMP JUMPDEST` for each invocation, and the
+            // `JUMPDEST`, swaps and `JUMP` around the subroutine, after a `STOP` if needed
+            let size = code.iter().map(|i| i.ty.size()).sum::<usize>();
+            let (call, subroutine) =
+                if terminating { (4, size + 1) } else { (8, size + takes + returns + 2) };
+            let outlined = invocations.len() * call + subroutine + usize::from(!stops(program));
+            let savings = (invocations.len() * size).checked_sub(outlined)?;
+            (savings > 0).then(|| Candidate {
+                name: name.to_string(),
+                invocations,
+                takes,
+                returns,
+                terminating,
+                savings,
+            })
+        })
+        .max_by_key(|c| c.savings)
+}
+
+/// Gets the instructions lowered from the code of an invoked macro, without the labels placed in
+/// front of it
+fn invocation(program: &IRProgram, range: &MacroRange) -> Option<Range<usize>> {
+    let indices = program
+        .instructions
+        .iter()
+        .enumerate()
+        .filter(|(_, i)| i.origin.map_or(false, |origin| range.contains(origin)))
+        .map(|(index, _)| index)
+        .collect::<Vec<usize>>();
+    let (first, last) = (*indices.first()?, *indices.last()?);
+    if last - first + 1 != indices.len() {
+        return None
+    }
+    let start = first +
+        program.instructions[first..=last]
+            .iter()
+            .take_while(|i| matches!(i.ty, IRInstructionType::Label(_)))
+            .count();
+    (start <= last).then_some(start..last + 1)
+}
+
+/// Gets the stack items straight-line code takes and returns, and whether it ends execution
+///
+/// Code with labels, label pushes, jumps, `PC` or raw bytes, and code that ends execution before
+/// its last instruction, can't be moved.
+fn stack_effect(code: &[IRInstruction]) -> Option<(usize, usize, bool)> {
+    let (mut takes, mut height) = (0, 0);
+    for (index, instruction) in code.iter().enumerate() {
+        let (inputs, outputs) = match &instruction.ty {
+            IRInstructionType::Push(
+                _,
+                IRValue::Literal(_) | IRValue::Constant(_) | IRValue::Immutable(_),
+            ) => (0, 1),
+            IRInstructionType::Opcode(
+                Opcode::Jump | Opcode::Jumpi | Opcode::Jumpdest | Opcode::Pc,
+            ) => return None,
+            IRInstructionType::Opcode(opcode) => opcode.stack_effect(),
+            _ => return None,
+        };
+        takes += inputs.saturating_sub(height);
+        height = height.saturating_sub(inputs) + outputs;
+        if matches!(&instruction.ty, IRInstructionType::Opcode(o) if o.is_terminating()) {
+            return (index == code.len() - 1).then_some((takes, 0, true))
+        }
+    }
+    Some((takes, height, false))
+}
+
+/// Whether execution stops at the end of the code
+fn stops(program: &IRProgram) -> bool {
+    let last = program.instructions.last().map(|i| &i.ty);
+    matches!(last, Some(IRInstructionType::Opcode(o)) if o.is_terminating())
+}
+
+/// Replaces the invocations with jumps to a subroutine placed after the code
+fn outline(program: &mut IRProgram, candidate: Candidate) {
+    // Invocations of the same macro expanding to other code get a subroutine of their own
+    let labels = program.label_indices();
+    let subroutine = (0..)
+        .map(|n| format!("__outlined_{}_{n}", candidate.name))
+        .find(|name| !labels.contains_key(name))
+        .unwrap();
+    let code = program.instructions[candidate.invocations[0].clone()].to_vec();
+    let added = |ty: IRInstructionType, span: &AstSpan| IRInstruction {
+        ty,
+        span: span.clone(),
+        origin: None,
+    };
+
+    // From the last invocation, so the instructions of the earlier ones stay in place
+    for (index, invocation) in candidate.invocations.iter().enumerate().rev() {
+        let span = program.instructions[invocation.start].span.clone();
+        let mut call = vec![];
+        let jump = IRInstructionType::Push(2, IRValue::Label(subroutine.clone()));
+        if candidate.terminating {
+            call.extend([jump, IRInstructionType::Opcode(Opcode::Jump)]);
+        } else {
+            let ret = format!("{subroutine}_return_{index}");
+            call.extend([
+                IRInstructionType::Push(2, IRValue::Label(ret.clone())),
+                jump,
+                IRInstructionType::Opcode(Opcode::Jump),
+                IRInstructionType::Label(ret),
+                IRInstructionType::Opcode(Opcode::Jumpdest),
+            ]);
+        }
+        program
+            .instructions
+            .splice(invocation.clone(), call.into_iter().map(|ty| added(ty, &span)));
+    }
+
+    // Execution mustn't run into the subroutine
+    let span = code[0].span.clone();
+    if !stops(program) {
+        program.instructions.push(added(IRInstructionType::Opcode(Opcode::Stop), &span));
+    }
+
+    // The return address is swapped below the items the code takes, and back above the items it
+    // returns
+    let swap = |n: usize| IRInstructionType::Opcode(Opcode::from_byte(0x8f + n as u8).unwrap());
+    program.instructions.push(added(IRInstructionType::Label(subroutine), &span));
+    program.instructions.push(added(IRInstructionType::Opcode(Opcode::Jumpdest), &span));
+    if !candidate.terminating {
+        program.instructions.extend((1..=candidate.takes).rev().map(|n| added(swap(n), &span)));
+    }
+    program.instructions.extend(code);
+    if !candidate.terminating {
+        program.instructions.extend((1..=candidate.returns).map(|n| added(swap(n), &span)));
+        program.instructions.push(added(IRInstructionType::Opcode(Opcode::Jump), &span));
+    }
+}
@@ -0,0 +1,118 @@
+use huff_utils::prelude::*;
+
+use crate::passes::{CodegenPass, PassContext};
+
+/// Removes obviously wasteful instruction sequences, eg. `PUSH1 0x01 POP` or `SWAP1 SWAP1`
+///
+/// Sequences are only matched within straight-line code, a label in between two instructions
+/// marking a jump destination. Instructions that are kept keep their source spans.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Peephole;
+
+impl CodegenPass for Peephole {
+    fn id(&self) -> &'static str {
+        "peephole"
+    }
+
+    fn description(&self) -> &'static str {
+        "Removes redundant instruction sequences, eg. `PUSH POP` or `SWAP1 SWAP1`"
+    }
+
+    fn run(&self, program: &mut IRProgram, _: &PassContext) -> Result<(), CodegenError> {
+        let mut optimized: Vec<IRInstruction> = Vec::with_capacity(program.instructions.len());
+        for instruction in std::mem::take(&mut program.instructions) {
+            optimized.push(instruction);
+            // Rewriting the tail may expose another sequence, eg. `PUSH DUP1 POP POP`
+            while let Some((len, kept)) = rewrite(&optimized) {
+                let kept = kept.map(|i| optimized[optimized.len() - len + i].clone());
+                optimized.truncate(optimized.len() - len);
+                optimized.extend(kept);
+            }
+        }
+        program.instructions = optimized;
+        Ok(())
+    }
+}
+
+/// Matches a wasteful sequence at the end of the instructions, returning its length and the
+/// index within it of the instruction it's replaced by, if any
+fn rewrite(instructions: &[IRInstruction]) -> Option<(usize, Option<usize>)> {
+    let tail = |n: usize| {
+        instructions.len().checked_sub(n).map(|start| {
+            instructions[start..].iter().map(|i| &i.ty).collect::<Vec<&IRInstructionType>>()
+        })
+    };
+    let opcode = |ty: &IRInstructionType| match ty {
+        IRInstructionType::Opcode(o) => Some(*o),
+        _ => None,
+    };
+
+    if let Some(pair) = tail(2) {
+        let second = opcode(pair[1]);
+        match (pair[0], second) {
+            // Values pushed and popped right away, `DUP1 POP` included
+            (ty, Some(Opcode::Pop)) if pushes_value(ty) => return Some((2, None)),
+            (IRInstructionType::Opcode(first), Some(second))
+                if first == &second && (is_swap(second) || second == Opcode::Not) =>
+            {
+                return Some((2, None))
+            }
+            // Adding, or-ing or xor-ing zero leaves the value as is
+            (ty, Some(Opcode::Add | Opcode::Or | Opcode::Xor)) if pushes_zero(ty) => {
+                return Some((2, None))
+            }
+            // Commutative operations don't need their operands swapped
+            (
+                IRInstructionType::Opcode(Opcode::Swap1),
+                Some(
+                    Opcode::Add |
+                    Opcode::Mul |
+                    Opcode::And |
+                    Opcode::Or |
+                    Opcode::Xor |
+                    Opcode::Eq,
+                ),
+            ) => return Some((2, Some(1))),
+            _ => {}
+        }
+    }
+    if let Some(triple) = tail(3) {
+        if triple.iter().all(|ty| opcode(ty) == Some(Opcode::Iszero)) {
+            return Some((3, Some(0)))
+        }
+    }
+    None
+}
+
+/// Whether the instruction only pushes a value onto the stack
+fn pushes_value(ty: &IRInstructionType) -> bool {
+    match ty {
+        IRInstructionType::Push(..) => true,
+        IRInstructionType::Opcode(o) => *o == Opcode::Push0 || is_dup(*o),
+        _ => false,
+    }
+}
+
+/// Whether the instruction pushes a zero onto the stack
+fn pushes_zero(ty: &IRInstructionType) -> bool {
+    match ty {
+        IRInstructionType::Push(_, IRValue::Literal(data)) => data.chars().all(|c| c == '0'),
+        IRInstructionType::Opcode(o) => *o == Opcode::Push0,
+        _ => false,
+    }
+}
+
+/// The byte of an opcode
+fn byte(opcode: Opcode) -> u8 {
+    u8::from_str_radix(&opcode.string(), 16).unwrap_or_default()
+}
+
+/// Whether the opcode is a `DUPX`
+fn is_dup(opcode: Opcode) -> bool {
+    (0x80..=0x8f).contains(&byte(opcode))
+}
+
+/// Whether the opcode is a `SWAPX`
+fn is_swap(opcode: Opcode) -> bool {
+    (0x90..=0x9f).contains(&byte(opcode))
+}
@@ -0,0 +1,73 @@
+use huff_utils::prelude::*;
+
+use crate::passes::{CodegenPass, PassContext};
+
+/// Pushes each label and table offset with the narrowest `PUSHX` it fits in
+///
+/// Jump destinations are pushed as `PUSH2` by default. Narrowing a push moves the code after it,
+/// so the offsets are relaxed iteratively: every reference starts out as a `PUSH1`, and the ones
+/// whose target doesn't fit are widened until the offsets are stable. Widening only ever moves
+/// code further, so the relaxation converges.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PushSizing;
+
+impl CodegenPass for PushSizing {
+    fn id(&self) -> &'static str {
+        "push-sizing"
+    }
+
+    fn description(&self) -> &'static str {
+        "Pushes label and table offsets with the narrowest push they fit in"
+    }
+
+    fn run(&self, program: &mut IRProgram, _: &PassContext) -> Result<(), CodegenError> {
+        let references = program
+            .instructions
+            .iter()
+            .enumerate()
+            .filter(|(_, i)| match &i.ty {
+                IRInstructionType::Push(_, value) => {
+                    matches!(value, IRValue::Label(_) | IRValue::TableStart(_))
+                }
+                _ => false,
+            })
+            .map(|(index, _)| index)
+            .collect::<Vec<usize>>();
+        for index in &references {
+            if let IRInstructionType::Push(width, _) = &mut program.instructions[*index].ty {
+                *width = 1;
+            }
+        }
+
+        let mut iterations = 0;
+        loop {
+            iterations += 1;
+            let label_indices = program.label_indices();
+            let table_offsets = program.table_offsets();
+
+            let mut widened = false;
+            for index in &references {
+                let IRInstructionType::Push(width, value) = &mut program.instructions[*index].ty
+                else {
+                    continue
+                };
+                let target = match value {
+                    IRValue::Label(name) => label_indices.get(name),
+                    IRValue::TableStart(name) => table_offsets.get(name),
+                    _ => None,
+                };
+                // Unresolved references are reported on emission
+                let Some(target) = target else { continue };
+                let needed = bytes_needed(*target);
+                if needed > *width {
+                    *width = needed;
+                    widened = true;
+                }
+            }
+            if !widened {
+                tracing::debug!(target: "codegen", "PUSH SIZES STABLE AFTER {} ITERATIONS", iterations);
+                return Ok(())
+            }
+        }
+    }
+}
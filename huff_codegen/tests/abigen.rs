@@ -23,11 +23,19 @@ fn constructs_valid_abi() {
         macros: vec![constructor],
         invocations: vec![],
         imports: vec![],
+        selective_imports: vec![],
+        full_imports: vec![],
+        namespaced_imports: vec![],
         constants: Arc::new(Mutex::new(vec![])),
+        immutables: vec![],
         errors: vec![],
         functions: vec![],
         events: vec![],
         tables: vec![],
+        assertions: vec![],
+        invalid_definitions: vec![],
+        trivia: TriviaTable::default(),
+        custom_builtins: CustomBuiltins::default(),
     };
 
     // Generate the abi from the contract
@@ -63,11 +71,19 @@ fn missing_constructor_fails() {
         macros: vec![],
         invocations: vec![],
         imports: vec![],
+        selective_imports: vec![],
+        full_imports: vec![],
+        namespaced_imports: vec![],
         constants: Arc::new(Mutex::new(vec![])),
+        immutables: vec![],
         errors: vec![],
         functions: vec![],
         events: vec![],
         tables: vec![],
+        assertions: vec![],
+        invalid_definitions: vec![],
+        trivia: TriviaTable::default(),
+        custom_builtins: CustomBuiltins::default(),
     };
 
     // Generate the abi from the contract
@@ -1,6 +1,10 @@
 use std::sync::Arc;
 
-use ethers_core::abi::Token;
+use ethers_core::{
+    abi::Token,
+    types::U256,
+    utils::{hex, keccak256},
+};
 use huff_codegen::Codegen;
 use huff_utils::prelude::FileSource;
 
@@ -87,3 +91,34 @@ fn churns_constructor_args() {
     assert!(churn_res.is_ok());
     assert_ne!(churn_res.unwrap().bytecode, "336000556101ac806100116000396000f360003560E01c8063a9059cbb1461004857806340c10f19146100de57806370a082311461014e57806318160ddd1461016b578063095ea7b314610177578063dd62ed3e1461018e575b600435336024358160016000526000602001526040600020548082116100d8578190038260016000526000602001526040600020558281906001600052600060200152604060002054018360016000526000602001526040600020556000527fDDF252AD1BE2C89B69C2B068FC378DAA952BA7F163C4A11628F55A4DF523B3EF60206000a3600160005260206000f35b60006000fd5b60005433146100ed5760006000fd5b600435600060243582819060016000526000602001526040600020540183600160005260006020015260406000205580600254016002556000527fDDF252AD1BE2C89B69C2B068FC378DAA952BA7F163C4A11628F55A4DF523B3EF60206000a35b600435600160005260006020015260406000205460005260206000f35b60025460005260206000f35b602435600435336000526000602001526040600020555b60243560043560005260006020015260406000205460005260206000f3".to_lowercase());
 }
+
+#[test]
+fn churns_initcode_hash_with_constructor_args() {
+    let mut cg = Codegen::new();
+    let inputs = vec![Token::Uint(U256::from(42))];
+    let artifact =
+        cg.churn(Arc::new(FileSource::default()), inputs, "6001600216", "33600055", false).unwrap();
+
+    // The hash covers the constructor arguments following the runtime code
+    assert!(artifact.bytecode.ends_with(&format!("{:064x}", 42)));
+    let initcode = hex::decode(&artifact.bytecode).unwrap();
+    assert_eq!(artifact.initcode_hash, format!("0x{}", hex::encode(keccak256(initcode))));
+}
+
+#[test]
+fn churns_abi_encoded_constructor_args() {
+    let mut cg = Codegen::new();
+    let inputs = vec![Token::String("tst".to_string()), Token::Uint(U256::from(42))];
+    let artifact =
+        cg.churn(Arc::new(FileSource::default()), inputs, "6001600216", "33600055", false).unwrap();
+
+    // The arguments follow the runtime code as one ABI encoded tuple, in declaration order
+    let encoded = [
+        "0000000000000000000000000000000000000000000000000000000000000040",
+        "000000000000000000000000000000000000000000000000000000000000002a",
+        "0000000000000000000000000000000000000000000000000000000000000003",
+        "7473740000000000000000000000000000000000000000000000000000000000",
+    ]
+    .concat();
+    assert_eq!(artifact.bytecode, format!("33600055600580600d3d393df36001600216{encoded}"));
+}
@@ -3,7 +3,11 @@ use ethers_core::{
     types::*,
 };
 use huff_codegen::Codegen;
-use huff_utils::bytes_util::*;
+use huff_utils::{
+    bytes_util::*,
+    prelude::{CodegenErrorKind, FunctionParamType},
+};
+use std::str::FromStr;
 
 #[test]
 fn encode_simple_constructor_args() {
@@ -135,3 +139,48 @@ fn encode_missing_brackets_array_constructor_args() {
     assert_eq!(results[4], expected_array);
     assert_eq!(results[5], expected_array);
 }
+
+#[test]
+fn encode_typed_constructor_args() {
+    let types = [
+        FunctionParamType::Address,
+        FunctionParamType::Uint(256),
+        FunctionParamType::FixedBytes(4),
+        FunctionParamType::Array(Box::new(FunctionParamType::Uint(8)), vec![0]),
+    ];
+    let args: Vec<String> =
+        ["0x646dB8ffC21e7ddc2B6327448dd9Fa560Df41087", "100", "0xa9059cbb", "[1, 2]"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+    let results = Codegen::encode_typed_constructor_args(args, &types).unwrap();
+    assert_eq!(
+        results[0],
+        Token::Address(H160::from_str("0x646dB8ffC21e7ddc2B6327448dd9Fa560Df41087").unwrap())
+    );
+    // Decimal values are encoded as the declared type, not inferred from their format
+    assert_eq!(results[1], Token::Uint(U256::from(100)));
+    assert_eq!(results[2], Token::FixedBytes(vec![0xa9, 0x05, 0x9c, 0xbb]));
+    assert_eq!(results[3], Token::Array(vec![Token::Uint(1.into()), Token::Uint(2.into())]));
+}
+
+#[test]
+fn encode_invalid_typed_constructor_args() {
+    let types = [FunctionParamType::Address, FunctionParamType::Uint(256)];
+
+    let err = Codegen::encode_typed_constructor_args(vec!["100".to_string()], &types).unwrap_err();
+    assert_eq!(
+        err.kind,
+        CodegenErrorKind::InvalidArguments("Expected 2 constructor arguments, found 1".to_string())
+    );
+
+    let err = Codegen::encode_typed_constructor_args(
+        vec!["0x646dB8ffC21e7ddc2B6327448dd9Fa560Df41087".to_string(), "true".to_string()],
+        &types,
+    )
+    .unwrap_err();
+    assert!(
+        matches!(err.kind, CodegenErrorKind::InvalidArguments(msg) if msg.starts_with("Invalid uint256 constructor argument \"true\""))
+    );
+}
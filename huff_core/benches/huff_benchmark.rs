@@ -66,7 +66,8 @@ fn parse_erc20_benchmark(c: &mut Criterion) {
     c.bench_function("Parser: ERC-20", |b| {
         b.iter(|| {
             let mut parser =
-                Parser::new(*tokens.clone(), Some("../huff-examples/erc20/contracts".to_string()));
+                Parser::new(*tokens.clone(), Some("../huff-examples/erc20/contracts".to_string()))
+                    .unwrap();
             let mut contract = parser.parse().unwrap();
             contract.derive_storage_pointers();
         })
@@ -98,7 +99,8 @@ fn codegen_erc20_benchmark(c: &mut Criterion) {
     let lexer = Lexer::new(full_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
 
-    let mut parser = Parser::new(tokens, Some("../huff-examples/erc20/contracts".to_string()));
+    let mut parser =
+        Parser::new(tokens, Some("../huff-examples/erc20/contracts".to_string())).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
@@ -147,7 +149,7 @@ fn erc20_compilation_benchmark(c: &mut Criterion) {
         };
         let lexer = Lexer::new(full_source.source);
         let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-        let mut parser = Parser::new(tokens, Some("../huff-examples/erc20/contracts".to_string()));
+        let mut parser = Parser::new(tokens, Some("../huff-examples/erc20/contracts".to_string())).unwrap();
         let mut contract = parser.parse().unwrap();
         contract.derive_storage_pointers();
 
@@ -194,7 +196,7 @@ fn erc721_compilation_benchmark(c: &mut Criterion) {
         };
         let lexer = Lexer::new(full_source.source);
         let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-        let mut parser = Parser::new(tokens, Some("../huff-examples/erc20/contracts".to_string()));
+        let mut parser = Parser::new(tokens, Some("../huff-examples/erc20/contracts".to_string())).unwrap();
         let mut contract = parser.parse().unwrap();
         contract.derive_storage_pointers();
 
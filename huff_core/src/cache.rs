@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use huff_utils::prelude::{Artifact, FileSource, OutputLocation};
+use huff_utils::prelude::{Artifact, CompilerFingerprint, FileSource, OutputLocation};
 use walkdir::WalkDir;
 
 /// Parallelized Artifact Cachcing
@@ -8,11 +8,12 @@ pub fn get_cached_artifacts(
     files: &[Arc<FileSource>],
     out: &OutputLocation,
     constructor_args: String,
+    compiler: &CompilerFingerprint,
 ) -> Option<Vec<Arc<Artifact>>> {
     // Check if the file artifacts are already generated the the default "./artifacts/" directory or
     // the specified output dir
     let artifacts: Vec<(Arc<FileSource>, Artifact)> =
-        resolve_existing_artifacts(files, out, constructor_args)?;
+        resolve_existing_artifacts(files, out, constructor_args, compiler)?;
 
     // Return the artifacts if cached
     Some(artifacts.into_iter().map(|(_, artifact)| Arc::new(artifact)).collect())
@@ -23,6 +24,7 @@ pub fn resolve_existing_artifacts(
     files: &[Arc<FileSource>],
     output: &OutputLocation,
     constructor_args: String,
+    compiler: &CompilerFingerprint,
 ) -> Option<Vec<(Arc<FileSource>, Artifact)>> {
     let mut artifacts: Vec<(Arc<FileSource>, Artifact)> = Vec::new();
 
@@ -39,6 +41,7 @@ pub fn resolve_existing_artifacts(
         .into_iter()
         .filter_map(Result::ok)
         .filter(|e| !e.file_type().is_dir())
+        .filter(|e| !e.path().to_string_lossy().ends_with(".metadata.json"))
     {
         // Are we expecting this file to be compiled
         let formatted_path = entry
@@ -60,6 +63,10 @@ pub fn resolve_existing_artifacts(
                             tracing::warn!(target: "core", "Mismatched Constructor Args for Cached Artifact \"{}\"", artifact.file.path);
                             return None
                         }
+                        if artifact.compiler != *compiler {
+                            tracing::warn!(target: "core", "Mismatched Compiler Settings for Cached Artifact \"{}\"", artifact.file.path);
+                            return None
+                        }
                         if artifact.file.source != expected_fs.source {
                             tracing::warn!(target: "core", "Cache Resolution Failed: \"{}\" Artifact Outdated", artifact.file.path);
                             return None
@@ -5,15 +5,19 @@
 #![forbid(where_clauses_object_safety)]
 
 use ethers_core::utils::hex;
-use huff_codegen::*;
+use huff_codegen::{
+    passes::{optimization_passes, Outlining, Pipeline},
+    *,
+};
 use huff_lexer::*;
+use huff_lints::{LintContext, Linter};
 use huff_parser::*;
 #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
 use huff_utils::wasm::IntoParallelIterator;
 use huff_utils::{
     file_provider::{FileProvider, FileSystemFileProvider, InMemoryFileProvider},
     prelude::*,
-    time,
+    stdlib, time,
 };
 #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
 use rayon::prelude::*;
@@ -26,6 +30,7 @@ use std::{
     sync::Arc,
 };
 use tracing_subscriber::{filter::Directive, EnvFilter};
+use uuid::Uuid;
 
 pub(crate) mod cache;
 
@@ -74,14 +79,41 @@ pub struct Compiler<'a, 'l> {
     pub construct_args: Option<Vec<String>>,
     /// Constant Overrides
     pub constant_overrides: Option<BTreeMap<&'a str, Literal>>,
-    /// Whether to optimize compilation or not.
+    /// Whether to run the optimization passes over the code, eg. the peephole optimizer
     pub optimize: bool,
+    /// Whether to outline the code of repeatedly invoked macros into subroutines, trading gas for
+    /// size
+    pub outline: bool,
+    /// Whether code exceeding the EIP-170 or EIP-3860 size limits fails the compilation, rather
+    /// than raising a warning
+    pub strict_size: bool,
+    /// The hash of the metadata embedded in a CBOR trailer after the runtime code, without a
+    /// trailer if `None`
+    pub metadata_hash: Option<MetadataHash>,
     /// Generate and log bytecode
     pub bytecode: bool,
     /// Whether to check cached artifacts
     pub cached: bool,
     /// The implementation of a FileReader
     pub file_provider: Arc<dyn FileProvider<'a>>,
+    /// Import remappings of the form `from=to`, applied on top of the configured remappings
+    pub remappings: Vec<String>,
+    /// Flags defined for `#ifdef` and `#ifndef` conditional compilation
+    pub defines: Vec<String>,
+    /// Settings for generating the bootstrap code
+    pub bootstrap: BootstrapSettings,
+    /// The maximum depth of nested macro invocations, overriding the default
+    pub max_expansion_depth: Option<usize>,
+    /// Whether the labels of every macro are unique to each of its invocations
+    pub unique_labels: bool,
+    /// The lint rules run over every contract, with their severities
+    pub lints: Linter,
+    /// The codegen passes run over the IR of the main and constructor code, in order
+    pub pipeline: Pipeline,
+    /// The custom builtins macros can call, besides the builtins of the language
+    pub builtins: CustomBuiltins,
+    /// The variables `env("VAR")` constants are read from, the process environment if `None`
+    pub env: Option<BTreeMap<String, String>>,
 }
 
 impl<'a, 'l> Compiler<'a, 'l> {
@@ -110,9 +142,21 @@ impl<'a, 'l> Compiler<'a, 'l> {
             construct_args,
             constant_overrides,
             optimize: false,
+            outline: false,
+            strict_size: false,
+            metadata_hash: None,
             bytecode: false,
             cached,
             file_provider: Arc::new(FileSystemFileProvider {}),
+            remappings: vec![],
+            defines: vec![],
+            bootstrap: BootstrapSettings::default(),
+            max_expansion_depth: None,
+            unique_labels: false,
+            lints: Linter::default(),
+            pipeline: Pipeline::default(),
+            builtins: CustomBuiltins::default(),
+            env: None,
         }
     }
 
@@ -141,9 +185,21 @@ impl<'a, 'l> Compiler<'a, 'l> {
             construct_args,
             constant_overrides,
             optimize: false,
+            outline: false,
+            strict_size: false,
+            metadata_hash: None,
             bytecode: false,
             cached: false,
             file_provider: Arc::new(InMemoryFileProvider::new(file_sources)),
+            remappings: vec![],
+            defines: vec![],
+            bootstrap: BootstrapSettings::default(),
+            max_expansion_depth: None,
+            unique_labels: false,
+            lints: Linter::default(),
+            pipeline: Pipeline::default(),
+            builtins: CustomBuiltins::default(),
+            env: None,
         }
     }
 
@@ -212,21 +268,36 @@ impl<'a, 'l> Compiler<'a, 'l> {
         // Get our constructor arguments as a hex encoded string to compare to the cache
         let inputs = self.get_constructor_args();
         let encoded_inputs = Codegen::encode_constructor_args(inputs);
-        let encoded: Vec<Vec<u8>> =
-            encoded_inputs.iter().map(|tok| ethers_core::abi::encode(&[tok.clone()])).collect();
-        let constructor_args = encoded.iter().map(|tok| hex::encode(tok.as_slice())).collect();
+        let constructor_args = hex::encode(ethers_core::abi::encode(&encoded_inputs));
 
         // Get Cached or Generate Artifacts
         tracing::debug!(target: "core", "Output directory: {}", output.0);
-        match cache::get_cached_artifacts(&files, &output, constructor_args) {
-            Some(arts) => artifacts = arts,
+        let fingerprint = self.fingerprint();
+        match cache::get_cached_artifacts(&files, &output, constructor_args, &fingerprint) {
+            Some(arts) => {
+                // Cached artifacts don't store their warnings, so the checks raising them are rerun
+                let mut gen_errors: Vec<CompilerError> = vec![];
+                for artifact in arts {
+                    match self.check_cached_artifact((*artifact).clone()) {
+                        Ok(a) => artifacts.push(Arc::new(a)),
+                        Err(ce) => gen_errors.push(ce),
+                    }
+                }
+                if !gen_errors.is_empty() {
+                    return Err(Arc::new(CompilerError::FailedCompiles(gen_errors)))
+                }
+            }
             None => {
                 tracing::debug!(target: "core", "FINISHED RECURSING DEPENDENCIES!");
                 // Parallel Dependency Resolution
                 let recursed_file_sources: Vec<Result<Arc<FileSource>, Arc<CompilerError>>> = files
                     .into_par_iter()
                     .map(|v| {
-                        Self::recurse_deps(v, &Remapper::new("./"), self.file_provider.clone())
+                        Self::recurse_deps(
+                            v,
+                            &self.file_provider.remapper().with_remappings(&self.remappings),
+                            self.file_provider.clone(),
+                        )
                     })
                     .collect();
 
@@ -310,7 +381,7 @@ impl<'a, 'l> Compiler<'a, 'l> {
             .map(|f| {
                 Self::recurse_deps(
                     f,
-                    &huff_utils::files::Remapper::new("./"),
+                    &self.file_provider.remapper().with_remappings(&self.remappings),
                     self.file_provider.clone(),
                 )
             })
@@ -350,21 +421,44 @@ impl<'a, 'l> Compiler<'a, 'l> {
 
                 // Perform Lexical Analysis
                 // Create a new lexer from the FileSource, flattening dependencies
-                let lexer = Lexer::new(full_source.source);
+                let lexer =
+                    Lexer::new(full_source.source).with_custom_builtins(self.builtins.names());
 
                 // Grab the tokens from the lexer
-                let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+                let tokens = lexer
+                    .into_iter()
+                    .collect::<Result<Vec<Token>, _>>()
+                    .map_err(CompilerError::LexicalError)?;
+                let tokens = Lexer::apply_conditionals(tokens, &self.defines)
+                    .map_err(CompilerError::LexicalError)?;
                 tracing::info!(target: "core", "LEXICAL ANALYSIS COMPLETE FOR \"{}\"", file.path);
                 tracing::info!(target: "core", "└─ TOKEN COUNT: {}", tokens.len());
 
                 // Parser incantation
-                let mut parser = Parser::new(tokens, Some(file.path.clone()));
+                let mut parser = Parser::new(tokens, Some(file.path.clone()))
+                    .map_err(CompilerError::ParserError)?;
 
                 // Parse into an AST
-                let parse_res = parser.parse().map_err(CompilerError::ParserError);
+                let parse_res =
+                    parser.parse_recovering().map_err(|errors| parser_errors(errors, &full_source));
                 let mut contract = parse_res?;
+                let remapper = self.file_provider.remapper().with_remappings(&self.remappings);
+                contract
+                    .apply_selective_imports(&full_source, &remapper)
+                    .map_err(CompilerError::ParserError)?;
+                contract.apply_namespaced_imports(&full_source, &remapper);
+                contract.apply_shadowed_definitions(&full_source);
+                contract
+                    .check_duplicate_definitions(&full_source)
+                    .map_err(CompilerError::ParserError)?;
+                contract
+                    .resolve_env_constants(|var| self.env_var(var))
+                    .map_err(CompilerError::CodegenError)?;
                 contract.derive_storage_pointers();
                 contract.add_override_constants(&self.constant_overrides);
+                contract.custom_builtins = self.builtins.clone();
+                contract.evaluate_constants().map_err(CompilerError::CodegenError)?;
+                contract.storage_layout().map_err(CompilerError::CodegenError)?;
                 tracing::info!(target: "core", "PARSED CONTRACT [{}]", file.path);
                 Ok(contract)
             })
@@ -386,32 +480,38 @@ impl<'a, 'l> Compiler<'a, 'l> {
         };
         tracing::debug!(target: "core", "GOT FULL SOURCE FOR PATH: {:?}", file.path);
 
-        // Perform Lexical Analysis
-        // Create a new lexer from the FileSource, flattening dependencies
-        let lexer = Lexer::new(full_source.source);
-
-        // Grab the tokens from the lexer
-        let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-        tracing::info!(target: "core", "LEXICAL ANALYSIS COMPLETE FOR \"{}\"", file.path);
-        tracing::info!(target: "core", "└─ TOKEN COUNT: {}", tokens.len());
+        let (contract, warnings) = self.parse_contract(&file, &full_source)?;
 
-        // Parser incantation
-        let mut parser = Parser::new(tokens, Some(file.path.clone()));
+        contract.evaluate_constants().map_err(CompilerError::CodegenError)?;
 
-        // Parse into an AST
-        let parse_res = parser.parse().map_err(CompilerError::ParserError);
-        let mut contract = parse_res?;
-        contract.derive_storage_pointers();
-        contract.add_override_constants(&self.constant_overrides);
+        let storage_layout = contract.storage_layout().map_err(CompilerError::CodegenError)?;
+        tracing::info!(target: "core", "STORAGE LAYOUT: {:?}", storage_layout);
         tracing::info!(target: "core", "PARSED CONTRACT [{}]", file.path);
 
+        // Check the compile-time assertions
+        let settings = self.codegen_settings();
+        if let Err(mut e) = Codegen::check_assertions(self.evm_version, &contract, &settings) {
+            tracing::error!(target: "core", "COMPILE-TIME ASSERTION FAILED FOR CONTRACT");
+            // Add File Source to Span
+            e.span.0.iter_mut().for_each(|s| s.file = Some(Arc::clone(&file)));
+            return Err(CompilerError::CodegenError(e))
+        }
+
         // Primary Bytecode Generation
         let mut cg = Codegen::new();
-        let main_bytecode = match Codegen::generate_main_bytecode(
-            self.evm_version,
-            &contract,
-            self.alternative_main.clone(),
-        ) {
+        cg.ast = Some(contract.clone());
+        // The bootstrap may return a code table as the runtime code instead of `MAIN`
+        let main = match &self.bootstrap.table {
+            Some(table) => Codegen::generate_table_runtime(&contract, table)
+                .map(|code| (code, BytecodeRes::default())),
+            None => Codegen::generate_main_bytecode_with_res(
+                self.evm_version,
+                &contract,
+                self.alternative_main.clone(),
+                &settings,
+            ),
+        };
+        let (main_bytecode, main_res) = match main {
             Ok(mb) => mb,
             Err(mut e) => {
                 tracing::error!(target: "core", "FAILED TO GENERATE MAIN BYTECODE FOR CONTRACT");
@@ -432,13 +532,25 @@ impl<'a, 'l> Compiler<'a, 'l> {
         };
         tracing::info!(target: "core", "MAIN BYTECODE GENERATED [{}]", main_bytecode);
 
+        // Identify the compiler and sources in a CBOR trailer after the runtime code
+        let metadata = self.metadata_hash.map(|hash| {
+            let metadata = Metadata::new(&file, env!("CARGO_PKG_VERSION"), self.settings());
+            tracing::info!(target: "core", "APPENDING {} METADATA TRAILER", hash);
+            (metadata.cbor_trailer(hash), metadata)
+        });
+        let main_bytecode = match &metadata {
+            Some((trailer, _)) => format!("{main_bytecode}{trailer}"),
+            None => main_bytecode,
+        };
+
         // Generate Constructor Bytecode
         let inputs = self.get_constructor_args();
-        let (constructor_bytecode, has_custom_bootstrap) =
-            match Codegen::generate_constructor_bytecode(
+        let (constructor_bytecode, has_custom_bootstrap, constructor_res) =
+            match Codegen::generate_constructor_bytecode_with_res(
                 self.evm_version,
                 &contract,
                 self.alternative_constructor.clone(),
+                &settings,
             ) {
                 Ok(mb) => mb,
                 Err(mut e) => {
@@ -465,13 +577,52 @@ impl<'a, 'l> Compiler<'a, 'l> {
 
                     // If the kind is a missing constructor we can ignore it
                     tracing::warn!(target: "codegen", "Contract has no \"CONSTRUCTOR\" macro definition!");
-                    (String::default(), false)
+                    (String::default(), false, BytecodeRes::default())
                 }
             };
         tracing::info!(target: "core", "CONSTRUCTOR BYTECODE GENERATED [{}]", constructor_bytecode);
 
-        // Encode Constructor Arguments
-        let encoded_inputs = Codegen::encode_constructor_args(inputs);
+        // Locate the statements within their files for the source maps
+        let locate_spans = |spans: Vec<BytecodeSpan>| {
+            spans
+                .into_iter()
+                .map(|mut s| {
+                    s.span =
+                        AstSpan(s.span.0.iter().map(|span| full_source.locate(span)).collect());
+                    s
+                })
+                .collect::<Vec<BytecodeSpan>>()
+        };
+        cg.main_spans = locate_spans(main_res.source_spans.clone());
+        cg.constructor_spans = locate_spans(constructor_res.source_spans.clone());
+
+        // Generate Bootstrap Shim Bytecode
+        cg.bootstrap = self.bootstrap.clone();
+        if let Some(shim) = &self.bootstrap.shim {
+            let shim_bytecode = Codegen::generate_shim_bytecode(
+                self.evm_version,
+                &contract,
+                shim,
+                &settings,
+                constructor_bytecode.len() / 2,
+                main_bytecode.len() / 2,
+            )
+            .map_err(CompilerError::CodegenError)?;
+            tracing::info!(target: "core", "BOOTSTRAP SHIM BYTECODE GENERATED [{}]", shim_bytecode);
+            cg.bootstrap_shim = Some(shim_bytecode);
+        }
+
+        // Encode Constructor Arguments, using the constructor's parameter types if it has any
+        let constructor_types = Abi::from(contract.clone())
+            .constructor
+            .map(|c| c.inputs.into_iter().map(|input| input.kind).collect::<Vec<_>>())
+            .unwrap_or_default();
+        let encoded_inputs = if inputs.is_empty() || constructor_types.is_empty() {
+            Codegen::encode_constructor_args(inputs)
+        } else {
+            Codegen::encode_typed_constructor_args(inputs, &constructor_types)
+                .map_err(CompilerError::CodegenError)?
+        };
         tracing::info!(target: "core", "ENCODED {} INPUTS", encoded_inputs.len());
 
         // Generate Artifact with ABI
@@ -484,6 +635,19 @@ impl<'a, 'l> Compiler<'a, 'l> {
         );
         match churn_res {
             Ok(mut artifact) => {
+                artifact.storage_layout = StorageLayout::from(storage_layout);
+                artifact.userdoc = UserDoc::new(&contract);
+                artifact.devdoc = DevDoc::new(&contract);
+                let entry_macros = self.entry_macros();
+                artifact.debug = DebugInfo {
+                    constructor: DebugInfo::ranges(entry_macros[1], &constructor_res),
+                    runtime: DebugInfo::ranges(entry_macros[0], &main_res),
+                };
+                artifact.compiler = self.fingerprint();
+                artifact.metadata = metadata.map(|(_, metadata)| metadata.to_json());
+                artifact.warnings = warnings;
+                self.check_code_size(&mut artifact, &contract, &full_source)?;
+
                 // Then we can have the code gen output the artifact
                 let abiout = cg.abi_gen(contract, None);
                 match abiout {
@@ -504,12 +668,245 @@ impl<'a, 'l> Compiler<'a, 'l> {
         }
     }
 
+    /// Looks up a variable `env("VAR")` constants are read from
+    fn env_var(&self, var: &str) -> Option<String> {
+        match &self.env {
+            Some(env) => env.get(var).cloned(),
+            None => std::env::var(var).ok(),
+        }
+    }
+
+    /// Parses a flattened file into its contract, running the lints over it
+    ///
+    /// Returns the contract, whose constants are yet to be evaluated, and the warnings raised.
+    fn parse_contract(
+        &self,
+        file: &Arc<FileSource>,
+        full_source: &FullFileSource,
+    ) -> Result<(Contract, Vec<CompilerWarning>), CompilerError> {
+        // Perform Lexical Analysis
+        // Create a new lexer from the FileSource, flattening dependencies
+        let lexer = Lexer::new(full_source.source).with_custom_builtins(self.builtins.names());
+
+        // Grab the tokens from the lexer
+        let tokens = lexer
+            .into_iter()
+            .collect::<Result<Vec<Token>, _>>()
+            .map_err(CompilerError::LexicalError)?;
+        let tokens = Lexer::apply_conditionals(tokens, &self.defines)
+            .map_err(CompilerError::LexicalError)?;
+        tracing::info!(target: "core", "LEXICAL ANALYSIS COMPLETE FOR \"{}\"", file.path);
+        tracing::info!(target: "core", "└─ TOKEN COUNT: {}", tokens.len());
+
+        // Keep the tokens around for the lints, which need the comments
+        let lint_tokens = tokens.clone();
+
+        // Parser incantation
+        let mut parser =
+            Parser::new(tokens, Some(file.path.clone())).map_err(CompilerError::ParserError)?;
+
+        // Parse into an AST
+        let parse_res =
+            parser.parse_recovering().map_err(|errors| parser_errors(errors, full_source));
+        let mut contract = parse_res?;
+        let remapper = self.file_provider.remapper().with_remappings(&self.remappings);
+        contract
+            .apply_selective_imports(full_source, &remapper)
+            .map_err(CompilerError::ParserError)?;
+        contract.apply_namespaced_imports(full_source, &remapper);
+        let mut warnings = contract.apply_shadowed_definitions(full_source);
+        contract.check_duplicate_definitions(full_source).map_err(CompilerError::ParserError)?;
+        contract
+            .resolve_env_constants(|var| self.env_var(var))
+            .map_err(CompilerError::CodegenError)?;
+        contract.derive_storage_pointers();
+        contract.add_override_constants(&self.constant_overrides);
+        contract.custom_builtins = self.builtins.clone();
+
+        // Run the lints, before constant expressions are evaluated and lose their references
+        let entry_macros = self.entry_macros();
+        let cx = LintContext {
+            contract: &contract,
+            source: full_source.source,
+            tokens: &lint_tokens,
+            main: entry_macros[0],
+            entry_macros: &entry_macros,
+            max_expansion_depth: self.max_expansion_depth.unwrap_or(MAX_EXPANSION_DEPTH),
+        };
+        let locate = |warning| locate_warning(warning, full_source);
+        match self.lints.run(&cx) {
+            Ok(lint_warnings) => warnings.extend(lint_warnings.into_iter().map(locate)),
+            Err(warning) => return Err(CompilerError::Warning(locate(warning))),
+        }
+
+        Ok((contract, warnings))
+    }
+
+    /// Reruns the checks raising warnings over a cached artifact, which doesn't store them
+    fn check_cached_artifact(&self, mut artifact: Artifact) -> Result<Artifact, CompilerError> {
+        let file = Arc::clone(&artifact.file);
+        let flattened = FileSource::fully_flatten(Arc::clone(&file));
+        let full_source = FullFileSource {
+            source: &flattened.0,
+            file: Some(Arc::clone(&file)),
+            spans: flattened.1,
+        };
+        let (contract, warnings) = self.parse_contract(&file, &full_source)?;
+        artifact.warnings = warnings;
+        self.check_code_size(&mut artifact, &contract, &full_source)?;
+        Ok(artifact)
+    }
+
+    /// Gets the settings affecting the generated code
+    pub fn settings(&self) -> MetadataSettings {
+        let entry_macros = self.entry_macros();
+        let constants = self.constant_overrides.iter().flatten().map(|(name, value)| {
+            (name.to_string(), format!("0x{}", bytes32_to_string(value, false)))
+        });
+        MetadataSettings {
+            evm_version: self.evm_version.to_string(),
+            main: entry_macros[0].to_string(),
+            constructor: entry_macros[1].to_string(),
+            optimize: self.optimize,
+            outline: self.outline,
+            unique_labels: self.unique_labels,
+            max_expansion_depth: self.max_expansion_depth,
+            defines: self.defines.clone(),
+            constants: constants.collect(),
+            bootstrap: self.bootstrap.clone(),
+            metadata_hash: self.metadata_hash,
+        }
+    }
+
+    /// Gets the name and version of the compiler, with the hash of its [settings](Self::settings)
+    pub fn fingerprint(&self) -> CompilerFingerprint {
+        CompilerFingerprint {
+            name: "huffc".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            settings_hash: self.settings().hash(),
+        }
+    }
+
+    /// Adds the warnings of code exceeding the size limits, which fails to deploy, to an artifact.
+    /// Fails on the first one if the limits are strictly enforced.
+    fn check_code_size(
+        &self,
+        artifact: &mut Artifact,
+        contract: &Contract,
+        full_source: &FullFileSource,
+    ) -> Result<(), CompilerError> {
+        for warning in self.code_size_warnings(artifact, contract) {
+            let warning = locate_warning(warning, full_source);
+            if self.strict_size {
+                return Err(CompilerError::Warning(warning))
+            }
+            artifact.warnings.push(warning);
+        }
+        Ok(())
+    }
+
+    /// Checks the runtime code and initcode of an artifact against the EIP-170 and EIP-3860 size
+    /// limits, listing the largest macros expanded into the code exceeding its limit
+    fn code_size_warnings(&self, artifact: &Artifact, contract: &Contract) -> Vec<CompilerWarning> {
+        let entry_macros = self.entry_macros();
+        let span = |name: &str| {
+            contract.find_macro_by_name(name).map(|m| m.span.clone()).unwrap_or_default()
+        };
+        let largest = |sizes: Vec<(String, usize)>| sizes.into_iter().take(5).collect::<Vec<_>>();
+
+        let mut warnings = vec![];
+        let runtime_size = artifact.runtime.len() / 2;
+        if runtime_size > MAX_CODE_SIZE {
+            tracing::warn!(target: "core", "RUNTIME CODE OF {} BYTES EXCEEDS EIP-170 LIMIT", runtime_size);
+            warnings.push(CompilerWarning::new(
+                WarningKind::CodeSizeExceeded(
+                    "Runtime".to_string(),
+                    runtime_size,
+                    MAX_CODE_SIZE,
+                    largest(DebugInfo::macro_sizes(&artifact.debug.runtime)),
+                ),
+                span(entry_macros[0]),
+            ));
+        }
+        let initcode_size = artifact.bytecode.len() / 2;
+        if self.evm_version.has_initcode_limit() && initcode_size > MAX_INITCODE_SIZE {
+            tracing::warn!(target: "core", "INITCODE OF {} BYTES EXCEEDS EIP-3860 LIMIT", initcode_size);
+            let ranges = artifact.debug.constructor.iter().chain(&artifact.debug.runtime);
+            warnings.push(CompilerWarning::new(
+                WarningKind::CodeSizeExceeded(
+                    "Initcode".to_string(),
+                    initcode_size,
+                    MAX_INITCODE_SIZE,
+                    largest(DebugInfo::macro_sizes(ranges)),
+                ),
+                span(entry_macros[1]),
+            ));
+        }
+        warnings
+    }
+
+    /// Gets the settings the code is generated with
+    pub fn codegen_settings(&self) -> CodegenSettings {
+        CodegenSettings {
+            pipeline: self.pipeline(),
+            max_expansion_depth: self.max_expansion_depth,
+            unique_labels: self.unique_labels,
+        }
+    }
+
+    /// Gets the codegen passes run over the code: macro outlining and the optimization passes
+    /// when enabled, followed by the registered passes
+    fn pipeline(&self) -> Pipeline {
+        let mut pipeline = Pipeline::new();
+        if self.outline {
+            pipeline.register(Arc::new(Outlining));
+        }
+        if self.optimize {
+            optimization_passes().into_iter().for_each(|pass| pipeline.register(pass));
+        }
+        self.pipeline.passes().iter().for_each(|pass| pipeline.register(Arc::clone(pass)));
+        pipeline
+    }
+
+    /// Gets the names of the macros compiled into the bytecode directly: the main and
+    /// constructor macros, and the bootstrap shim
+    fn entry_macros(&self) -> Vec<&str> {
+        let mut entries = vec![
+            self.alternative_main.as_deref().unwrap_or("MAIN"),
+            self.alternative_constructor.as_deref().unwrap_or("CONSTRUCTOR"),
+        ];
+        entries.extend(self.bootstrap.shim.as_deref());
+        entries
+    }
+
     /// Get the file sources for a vec of PathBufs
     pub fn fetch_sources(
         paths: Vec<PathBuf>,
         reader: Arc<dyn FileProvider<'a>>,
     ) -> Vec<Result<Arc<FileSource>, CompilerError>> {
-        paths.into_par_iter().map(|pb| reader.read_file(pb)).collect()
+        paths
+            .into_par_iter()
+            .map(|pb| {
+                // Serve the standard library from the bundled sources
+                let path = pb.to_string_lossy();
+                match stdlib::source(&path) {
+                    Some(source) => Ok(Arc::new(FileSource {
+                        id: Uuid::new_v4(),
+                        path: path.to_string(),
+                        source: Some(source.to_string()),
+                        access: Some(time::get_current_time()),
+                        dependencies: None,
+                    })),
+                    None if stdlib::is_stdlib(&path) => {
+                        tracing::error!(target: "core", "MISSING STANDARD LIBRARY FILE: \"{}\"!", path);
+                        Err(CompilerError::FileUnpackError(UnpackError::MissingFile(
+                            path.to_string(),
+                        )))
+                    }
+                    None => reader.read_file(pb),
+                }
+            })
+            .collect()
     }
 
     /// Recurses file dependencies
@@ -517,6 +914,18 @@ impl<'a, 'l> Compiler<'a, 'l> {
         fs: Arc<FileSource>,
         remapper: &Remapper,
         reader: Arc<dyn FileProvider<'a>>,
+    ) -> Result<Arc<FileSource>, Arc<CompilerError>> {
+        Self::recurse_deps_with_ancestors(fs, remapper, reader, &[])
+    }
+
+    /// Recurses file dependencies, erroring on any import of a file in `ancestors`.
+    ///
+    /// Each ancestor is paired with the span of its `#include` that leads to `fs`.
+    fn recurse_deps_with_ancestors(
+        fs: Arc<FileSource>,
+        remapper: &Remapper,
+        reader: Arc<dyn FileProvider<'a>>,
+        ancestors: &[(String, Span)],
     ) -> Result<Arc<FileSource>, Arc<CompilerError>> {
         tracing::debug!(target: "core", "RECURSING DEPENDENCIES FOR {}", fs.path);
         let mut new_fs = FileSource { path: fs.path.clone(), ..Default::default() };
@@ -535,28 +944,14 @@ impl<'a, 'l> Compiler<'a, 'l> {
             new_source
         };
         let imports: Vec<String> = Lexer::lex_imports(&file_source);
-        new_fs.source = Some(file_source);
+        new_fs.source = Some(file_source.clone());
         if !imports.is_empty() {
             tracing::info!(target: "core", "IMPORT LEXICAL ANALYSIS COMPLETE ON {:?}", imports);
         }
 
         let localized_imports: Vec<String> = imports
-            .into_iter()
-            .map(|mut import| {
-                // Check for foundry toml remappings
-                match remapper.remap(&import) {
-                    Some(remapped) => {
-                        tracing::debug!(target: "core", "REMAPPED IMPORT PATH \"{}\"", import);
-                        import = remapped;
-                    }
-                    None => {
-                        import = FileSource::localize_file(&fs.path, &import)
-                            .unwrap_or_default()
-                            .replacen("contracts/contracts", "contracts", 1);
-                    }
-                }
-                import
-            })
+            .iter()
+            .map(|import| FileSource::resolve_import(remapper, &fs.path, import))
             .collect();
         if !localized_imports.is_empty() {
             tracing::info!(target: "core", "LOCALIZED IMPORTS {:?}", localized_imports);
@@ -572,17 +967,63 @@ impl<'a, 'l> Compiler<'a, 'l> {
             tracing::info!(target: "core", "FETCHED {} FILE SOURCES", file_sources.len());
         }
 
+        // The span of the `#include` in this file that resolved to the given path
+        let include_span = |path: &str| {
+            let file = Arc::new(FileSource {
+                path: fs.path.clone(),
+                source: Some(file_source.clone()),
+                ..Default::default()
+            });
+            imports
+                .iter()
+                .zip(localized_imports.iter())
+                .find(|(_, localized)| same_path(localized, path))
+                .and_then(|(import, _)| {
+                    file_source.find(import.as_str()).map(|start| Span {
+                        start,
+                        end: start + import.len() - 1,
+                        file: Some(Arc::clone(&file)),
+                    })
+                })
+                .unwrap_or(Span { start: 0, end: 0, file: Some(file) })
+        };
+
         // Now that we have all the file sources, we have to recurse and get their source
         file_sources = file_sources
             .into_par_iter()
-            .map(|inner_fs| match Self::recurse_deps(Arc::clone(&inner_fs), remapper, reader.clone()) {
-                Ok(new_fs) => new_fs,
-                Err(e) => {
-                    tracing::error!(target: "core", "NESTED DEPENDENCY RESOLUTION FAILED: \"{:?}\"", e);
-                    Arc::clone(&inner_fs)
+            .map(|inner_fs| {
+                let mut chain = ancestors.to_vec();
+                chain.push((fs.path.clone(), include_span(&inner_fs.path)));
+
+                // Check if the file includes one of the files that (transitively) include it
+                if let Some(pos) = chain.iter().position(|(p, _)| same_path(p, &inner_fs.path)) {
+                    let cycle = &chain[pos..];
+                    tracing::error!(target: "core", "CIRCULAR IMPORT OF \"{}\"", inner_fs.path);
+                    return Err(Arc::new(CompilerError::CircularImport(
+                        cycle
+                            .iter()
+                            .map(|(p, _)| p.clone())
+                            .chain(std::iter::once(inner_fs.path.clone()))
+                            .collect(),
+                        AstSpan(cycle.iter().map(|(_, s)| s.clone()).collect()),
+                    )))
+                }
+
+                match Self::recurse_deps_with_ancestors(
+                    Arc::clone(&inner_fs),
+                    remapper,
+                    reader.clone(),
+                    &chain,
+                ) {
+                    Ok(new_fs) => Ok(new_fs),
+                    Err(e) if matches!(*e, CompilerError::CircularImport(..)) => Err(e),
+                    Err(e) => {
+                        tracing::error!(target: "core", "NESTED DEPENDENCY RESOLUTION FAILED: \"{:?}\"", e);
+                        Ok(Arc::clone(&inner_fs))
+                    }
                 }
             })
-            .collect();
+            .collect::<Result<Vec<_>, _>>()?;
 
         // Finally set the parent deps
         new_fs.dependencies = Some(file_sources);
@@ -645,3 +1086,28 @@ impl<'a, 'l> Compiler<'a, 'l> {
         }
     }
 }
+
+/// Whether two localized file paths point to the same file
+fn same_path(a: &str, b: &str) -> bool {
+    normalize_path(a) == normalize_path(b)
+}
+
+/// Locates the spans of a warning within the files they come from
+fn locate_warning(mut warning: CompilerWarning, full_source: &FullFileSource) -> CompilerWarning {
+    warning.span = AstSpan(warning.span.0.iter().map(|s| full_source.locate(s)).collect());
+    warning
+}
+
+/// Reports the errors found while parsing a file, every one of them if there are several, with
+/// their spans located in the files they come from
+fn parser_errors(mut errors: Vec<ParserError>, full_source: &FullFileSource) -> CompilerError {
+    for error in errors.iter_mut() {
+        error.spans = AstSpan(error.spans.0.iter().map(|s| full_source.locate(s)).collect());
+    }
+    match errors.len() {
+        1 => CompilerError::ParserError(errors.remove(0)),
+        _ => CompilerError::FailedCompiles(
+            errors.into_iter().map(CompilerError::ParserError).collect(),
+        ),
+    }
+}
@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+mod common;
+use common::compile_with_lint;
+
+#[test]
+fn test_valid_checksums() {
+    let source = r#"
+    #define constant OWNER = 0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed
+
+    #define macro MAIN() = takes(0) returns(0) {
+        [OWNER] 0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359 0xDBF03B407C01E7CD3CBEA99509D93F8DDDC8C6FB
+    }
+    "#;
+
+    let artifact = compile_with_lint(source, "address-checksum", Severity::Warn).unwrap();
+    assert!(artifact.warnings.is_empty());
+}
+
+#[test]
+fn test_invalid_checksum_warns() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        0x5aaeb6053F3E94C9b9A09f33669435E7Ef1BeAed
+    }
+    "#;
+
+    let artifact = compile_with_lint(source, "address-checksum", Severity::Warn).unwrap();
+    assert_eq!(artifact.runtime, "735aaeb6053f3e94c9b9a09f33669435e7ef1beaed");
+    assert_eq!(artifact.warnings.len(), 1);
+    assert_eq!(
+        artifact.warnings[0].kind,
+        WarningKind::InvalidAddressChecksum(
+            "0x5aaeb6053F3E94C9b9A09f33669435E7Ef1BeAed".to_string(),
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".to_string()
+        )
+    );
+    assert_eq!(artifact.warnings[0].span.0[0].start, 60);
+    assert_eq!(artifact.warnings[0].span.0[0].end, 99);
+}
+
+#[test]
+fn test_invalid_checksum_severities() {
+    let source = r#"
+    #define constant OWNER = 0x5aaeb6053F3E94C9b9A09f33669435E7Ef1BeAed
+
+    #define macro MAIN() = takes(0) returns(0) {
+        [OWNER]
+    }
+    "#;
+
+    let artifact = compile_with_lint(source, "address-checksum", Severity::Allow).unwrap();
+    assert!(artifact.warnings.is_empty());
+
+    match compile_with_lint(source, "address-checksum", Severity::Error) {
+        Err(CompilerError::Warning(w)) => assert!(matches!(
+            w.kind,
+            WarningKind::InvalidAddressChecksum(found, _)
+                if found == "0x5aaeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+        )),
+        res => panic!("expected an invalid checksum error, got {res:?}"),
+    }
+}
+
+#[test]
+fn test_invalid_checksum_warns_on_cached_artifacts() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        0x5aaeb6053F3E94C9b9A09f33669435E7Ef1BeAed
+    }
+    "#;
+    let dir = format!("huff_cached_checksums_{}", std::process::id());
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = format!("./{dir}/main.huff");
+    std::fs::write(&path, source).unwrap();
+
+    let evm_version = EVMVersion::default();
+    let compiler = Compiler::new(
+        &evm_version,
+        Arc::new(vec![path]),
+        Some(format!("{dir}/artifacts")),
+        None,
+        None,
+        None,
+        None,
+        false,
+        false,
+    );
+
+    // The second run is served from the artifacts exported by the first
+    let compiled = compiler.execute();
+    let cached = compiler.execute();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let warnings = |artifacts: Vec<Arc<Artifact>>| {
+        artifacts[0]
+            .warnings
+            .iter()
+            .map(|w| (w.kind.clone(), w.span.0[0].start))
+            .collect::<Vec<_>>()
+    };
+    let compiled = warnings(compiled.unwrap());
+    assert_eq!(compiled.len(), 1);
+    assert_eq!(warnings(cached.unwrap()), compiled);
+}
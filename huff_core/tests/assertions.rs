@@ -0,0 +1,50 @@
+use huff_utils::prelude::*;
+
+mod common;
+use common::compile;
+
+#[test]
+fn test_passing_assertions() {
+    let source = r#"
+    #define constant SLOT_A = FREE_STORAGE_POINTER()
+    #define constant SLOT_B = FREE_STORAGE_POINTER()
+
+    #assert [SLOT_A] != [SLOT_B]
+    #assert add([SLOT_A], 0x01) == [SLOT_B]
+    #assert __codesize(MAIN) <= 0x03
+    #assert __codesize(MAIN) > 0x02
+
+    #define macro MAIN() = takes(0) returns (0) {
+        [SLOT_A] [SLOT_B]
+    }
+    "#;
+
+    let artifact = compile(source).unwrap();
+    assert_eq!(artifact.runtime, "5f6001");
+}
+
+#[test]
+fn test_failing_assertion() {
+    let source = r#"
+    #define constant SLOT_A = FREE_STORAGE_POINTER()
+    #define constant SLOT_B = STORAGE_SLOT(0x00)
+
+    #assert __codesize(MAIN) < 0x6000
+    #assert [SLOT_A] == [SLOT_B]
+
+    #define macro MAIN() = takes(0) returns (0) {
+        [SLOT_A] [SLOT_B]
+    }
+    "#;
+
+    match compile(source).unwrap_err() {
+        CompilerError::CodegenError(e) => {
+            assert_eq!(e.kind, CodegenErrorKind::FailedAssertion("0x1 == 0x0".to_string()));
+            // The error spans the failing assertion
+            let span = e.span.0.first().unwrap();
+            assert_eq!(span.start, source.find("#assert [SLOT_A]").unwrap());
+            assert!(span.file.is_some());
+        }
+        e => panic!("Unexpected error: {e:?}"),
+    }
+}
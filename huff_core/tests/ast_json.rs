@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+#[test]
+fn test_contract_serializes_to_json() {
+    let source = r#"
+    #define constant OWNER = FREE_STORAGE_POINTER()
+    #define constant ONE = 0x01
+    #define event Transfer(address indexed, uint256)
+
+    #define macro MAIN() = takes(0) returns(0) {
+        [ONE] 0x02 add
+        done jump
+        done:
+            __FUNC_SIG("balanceOf(address)") pop
+    }
+    "#;
+    let file = Arc::new(FileSource {
+        path: "src/Main.huff".to_string(),
+        source: Some(source.to_string()),
+        ..Default::default()
+    });
+    let tokens = Lexer::new(source)
+        .map(|x| x.unwrap())
+        .map(|mut t| {
+            t.span.file = Some(Arc::clone(&file));
+            t
+        })
+        .collect::<Vec<Token>>();
+    let contract = Parser::new(tokens, None).unwrap().parse().unwrap();
+    let json = serde_json::to_value(&contract).unwrap();
+
+    let constants = &json["constants"];
+    assert_eq!(constants[0]["name"], "OWNER");
+    assert_eq!(constants[1]["value"]["Literal"], "0x01");
+
+    let event = &json["events"][0];
+    assert_eq!(event["name"], "Transfer");
+    assert_eq!(event["parameters"][0]["indexed"], true);
+    assert_eq!(event["hash"], bytes32_to_string(&contract.events[0].hash, true));
+
+    // Opcodes are their mnemonics, and spans carry the path of their file rather than its source
+    let statements = &json["macros"][0]["statements"];
+    assert_eq!(statements[0]["ty"]["Constant"], "ONE");
+    assert_eq!(statements[1]["ty"]["Literal"], "0x02");
+    assert_eq!(statements[2]["ty"]["Opcode"], "add");
+    assert_eq!(statements[5]["ty"]["Label"]["name"], "done");
+    let span = &statements[2]["span"][0];
+    assert_eq!(span["file"], "src/Main.huff");
+    let (start, end) = (span["start"].as_u64().unwrap(), span["end"].as_u64().unwrap());
+    assert_eq!(&source[start as usize..=end as usize], "add");
+}
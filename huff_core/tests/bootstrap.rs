@@ -0,0 +1,116 @@
+use std::{collections::HashMap, sync::Arc};
+
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"
+    #define macro SHIM() = takes(2) returns (0) {
+        // [runtime_offset, runtime_size]
+        dup2 swap1 0x00 codecopy
+        0x00 return
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        0x01
+    }
+"#;
+
+fn compile(
+    source: &str,
+    bootstrap: BootstrapSettings,
+) -> Result<Arc<Artifact>, Arc<CompilerError>> {
+    let main_file_name = String::from("main.huff");
+    let mut file_sources = HashMap::new();
+    file_sources.insert(main_file_name.clone(), String::from(source));
+
+    let evm_version = EVMVersion::default();
+    let mut compiler = Compiler::new_in_memory(
+        &evm_version,
+        Arc::new(vec![main_file_name]),
+        file_sources,
+        None,
+        None,
+        None,
+        None,
+        false,
+    );
+    compiler.bootstrap = bootstrap;
+    compiler.execute().map(|artifacts| Arc::clone(&artifacts[0]))
+}
+
+#[test]
+fn test_bootstrap_memory_offset() {
+    let bootstrap = BootstrapSettings { memory_offset: 0x20, ..Default::default() };
+    let artifact = compile(SOURCE, bootstrap.clone()).unwrap();
+    assert_eq!(artifact.bytecode, "600280600b6020396020f36001");
+    assert_eq!(artifact.bootstrap, bootstrap);
+}
+
+#[test]
+fn test_bootstrap_shim() {
+    let bootstrap = BootstrapSettings { shim: Some("SHIM".to_string()), ..Default::default() };
+    let artifact = compile(SOURCE, bootstrap).unwrap();
+    assert_eq!(artifact.bytecode, "6002600a81905f395ff36001");
+}
+
+#[test]
+fn test_bootstrap_shim_with_labels() {
+    let source = SOURCE.replace("// [runtime_offset, runtime_size]", "copy jump\n        copy:");
+    let bootstrap = BootstrapSettings { shim: Some("SHIM".to_string()), ..Default::default() };
+    let artifact = compile(&source, bootstrap).unwrap();
+
+    // The label resolves to its offset after the runtime code size and offset pushes
+    assert_eq!(artifact.bytecode, "6002600f610008565b81905f395ff36001");
+}
+
+#[test]
+fn test_bootstrap_missing_shim() {
+    let bootstrap = BootstrapSettings { shim: Some("MISSING".to_string()), ..Default::default() };
+    match compile(SOURCE, bootstrap).unwrap_err().as_ref() {
+        CompilerError::FailedCompiles(errors) => assert!(errors.iter().any(|e| matches!(
+            e,
+            CompilerError::CodegenError(ce)
+                if ce.kind == CodegenErrorKind::MissingMacroDefinition("MISSING".to_string())
+        ))),
+        e => panic!("Unexpected error: {e:?}"),
+    }
+}
+
+#[test]
+fn test_bootstrap_shim_with_immutables() {
+    let source = format!("{SOURCE}\n#define immutable OWNER\n#define macro GET() = {{ [OWNER] }}");
+    let source = source.replace("0x01\n", "GET()\n");
+    let bootstrap = BootstrapSettings { shim: Some("SHIM".to_string()), ..Default::default() };
+    match compile(&source, bootstrap).unwrap_err().as_ref() {
+        CompilerError::FailedCompiles(errors) => assert!(errors.iter().any(|e| matches!(
+            e,
+            CompilerError::CodegenError(ce)
+                if ce.kind == CodegenErrorKind::ImmutablesWithCustomBootstrap
+        ))),
+        e => panic!("Unexpected error: {e:?}"),
+    }
+}
+
+#[test]
+fn test_bootstrap_table() {
+    let source = "#define table RUNTIME { 0x6001 0x6002 }";
+    let bootstrap = BootstrapSettings { table: Some("RUNTIME".to_string()), ..Default::default() };
+    let artifact = compile(source, bootstrap).unwrap();
+
+    // The contents of the table are returned as the runtime code
+    assert_eq!(artifact.runtime, "60016002");
+    assert_eq!(artifact.bytecode, "60048060093d393df360016002");
+}
+
+#[test]
+fn test_bootstrap_missing_table() {
+    let bootstrap = BootstrapSettings { table: Some("RUNTIME".to_string()), ..Default::default() };
+    match compile(SOURCE, bootstrap).unwrap_err().as_ref() {
+        CompilerError::FailedCompiles(errors) => assert!(errors.iter().any(|e| matches!(
+            e,
+            CompilerError::CodegenError(ce)
+                if ce.kind == CodegenErrorKind::MissingCodeTable("RUNTIME".to_string())
+        ))),
+        e => panic!("Unexpected error: {e:?}"),
+    }
+}
@@ -135,7 +135,7 @@ fn test_breaking_jump_table() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Parse the AST
     let mut contract = parser.parse().unwrap();
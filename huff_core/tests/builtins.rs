@@ -26,7 +26,7 @@ fn test_codesize_builtin() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Parse the AST
     let mut contract = parser.parse().unwrap();
@@ -62,7 +62,7 @@ fn test_dyn_constructor_arg_builtin() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Parse the AST
     let mut contract = parser.parse().unwrap();
@@ -92,7 +92,8 @@ fn test_dyn_constructor_arg_builtin() {
         has_custom_bootstrap,
     );
 
-    assert_eq!(final_bytecode.unwrap().bytecode, String::from("60118060093d393df3610007610020526100076100116100403974657374696e6700000000000000000000000000000000000000000000000000"));
+    // The contents of the argument follow its offset and length in the ABI encoded arguments
+    assert_eq!(final_bytecode.unwrap().bytecode, String::from("60118060093d393df361000761002052610007610051610040390000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000000774657374696e6700000000000000000000000000000000000000000000000000"));
 }
 
 #[test]
@@ -153,7 +154,7 @@ fn test_tablesize_builtin() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Parse the AST
     let mut contract = parser.parse().unwrap();
@@ -217,7 +218,7 @@ fn test_tablestart_builtin() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Parse the AST
     let mut contract = parser.parse().unwrap();
@@ -282,7 +283,7 @@ fn test_jump_table_exhaustive_usage() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Parse the AST
     let mut contract = parser.parse().unwrap();
@@ -342,7 +343,7 @@ fn test_jump_table_packed_exhaustive_usage() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Parse the AST
     let mut contract = parser.parse().unwrap();
@@ -409,7 +410,7 @@ fn test_label_clashing() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Parse the AST
     let mut contract = parser.parse().unwrap();
@@ -453,7 +454,7 @@ fn test_func_sig_builtin() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Parse the AST
     let mut contract = parser.parse().unwrap();
@@ -498,7 +499,7 @@ fn test_event_hash_builtin() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     let evm_version = EVMVersion::default();
 
@@ -575,7 +576,7 @@ fn test_error_selector_builtin() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Parse the AST
     let mut contract = parser.parse().unwrap();
@@ -615,7 +616,7 @@ fn test_rightpad_builtin() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Parse the AST
     let mut contract = parser.parse().unwrap();
@@ -648,3 +649,239 @@ fn test_rightpad_builtin() {
         )
     );
 }
+
+#[test]
+fn test_rightpad_builtin_keeps_leading_zeros() {
+    let source: &str = r#"
+        #define macro MAIN() = takes (0) returns (0) {
+            __RIGHTPAD(0x0001)
+            __RIGHTPAD(0x00)
+        }
+    "#;
+
+    // Parse tokens
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+
+    // Parse the AST
+    let mut contract = parser.parse().unwrap();
+
+    // Derive storage pointers
+    contract.derive_storage_pointers();
+
+    // Have Codegen create the runtime bytecode
+    let r_bytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(r_bytes, format!("7f0001{}7f{}", "0".repeat(60), "0".repeat(64)));
+}
+
+#[test]
+fn test_leftpad_builtin() {
+    let source: &str = r#"
+        #define macro MAIN() = takes (0) returns (0) {
+            __LEFTPAD(0xdead, 0x04)
+            __LEFTPAD(0x01, 0x20)
+            __LEFTPAD(0x00ff, 0x02)
+        }
+    "#;
+
+    // Parse tokens
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+
+    // Parse the AST
+    let mut contract = parser.parse().unwrap();
+
+    // Derive storage pointers
+    contract.derive_storage_pointers();
+
+    // Have Codegen create the runtime bytecode
+    let r_bytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(r_bytes, format!("630000dead7f{}016100ff", "0".repeat(62)));
+}
+
+#[test]
+fn test_leftpad_builtin_invalid_width() {
+    for (value, width) in [("0xdeadbeef", "0x02"), ("0x01", "0x00"), ("0x01", "0x21")] {
+        let source = &format!(
+            r#"
+            #define macro MAIN() = takes (0) returns (0) {{
+                __LEFTPAD({value}, {width})
+            }}
+        "#
+        );
+
+        // Parse tokens
+        let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+        let lexer = Lexer::new(flattened_source.source);
+        let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let mut parser = Parser::new(tokens, None).unwrap();
+
+        // Parse the AST
+        let mut contract = parser.parse().unwrap();
+
+        // Derive storage pointers
+        contract.derive_storage_pointers();
+
+        let err =
+            Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap_err();
+        assert!(matches!(err.kind, CodegenErrorKind::InvalidArguments(_)));
+    }
+}
+
+#[test]
+fn test_keccak_builtin() {
+    let source: &str = r#"
+        #define constant ZERO = 0x00
+        #define constant SLOT = FREE_STORAGE_POINTER()
+
+        #define macro MAIN() = takes (0) returns (0) {
+            __keccak("eip1967.proxy.implementation")
+            __keccak([ZERO])
+            __keccak([SLOT])
+            __keccak(0x0001)
+        }
+    "#;
+
+    // Parse tokens
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+
+    // Parse the AST
+    let mut contract = parser.parse().unwrap();
+
+    // Derive storage pointers
+    contract.derive_storage_pointers();
+
+    // Strings and literals are hashed as raw bytes, constants as a full word
+    let zero_word_hash = "290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563";
+    let r_bytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(
+        r_bytes,
+        format!(
+            "7f{}7f{zero_word_hash}7f{zero_word_hash}7f{}",
+            "360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbd",
+            ethers_core::utils::hex::encode(ethers_core::utils::keccak256([0x00, 0x01]))
+        )
+    );
+}
+
+#[test]
+fn test_keccak_builtin_invalid_argument() {
+    let source: &str = r#"
+        #define macro MAIN() = takes (0) returns (0) {
+            __keccak(MAIN)
+        }
+    "#;
+
+    // Parse tokens
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+
+    let err = parser.parse().unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::InvalidArgs(TokenKind::Ident("MAIN".to_string())));
+}
+
+#[test]
+fn test_bytes_builtin() {
+    let source: &str = r#"
+        #define macro MAIN() = takes (0) returns (0) {
+            __BYTES("ERC20")
+            __BYTES("")
+            __BYTES("abcdefghijklmnopqrstuvwxyz012345")
+        }
+    "#;
+
+    // Parse tokens
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+
+    // Parse the AST
+    let mut contract = parser.parse().unwrap();
+
+    // Derive storage pointers
+    contract.derive_storage_pointers();
+
+    // Each string is pushed with the push matching its length
+    let r_bytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(
+        r_bytes,
+        format!(
+            "644552433230{}7f{}",
+            Opcode::Push0,
+            ethers_core::utils::hex::encode("abcdefghijklmnopqrstuvwxyz012345")
+        )
+    );
+}
+
+#[test]
+fn test_bytes_builtin_too_long() {
+    let source: &str = r#"
+        #define macro MAIN() = takes (0) returns (0) {
+            __BYTES("abcdefghijklmnopqrstuvwxyz0123456")
+        }
+    "#;
+
+    // Parse tokens
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+
+    let err = parser.parse().unwrap_err();
+    assert_eq!(
+        err.kind,
+        ParserErrorKind::InvalidArgs(TokenKind::Str(
+            "abcdefghijklmnopqrstuvwxyz0123456".to_string()
+        ))
+    );
+    assert_eq!(
+        err.hint,
+        Some(
+            "`__BYTES` pushes at most 32 bytes, \"abcdefghijklmnopqrstuvwxyz0123456\" is 33 bytes long"
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn test_code_table_with_strings() {
+    let source: &str = r#"
+        #define table STRINGS {
+            "Insufficient balance"
+            0xff
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            __tablesize(STRINGS) __tablestart(STRINGS) 0x00 codecopy
+        }
+    "#;
+
+    // Parse tokens
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+
+    // Parse the AST
+    let mut contract = parser.parse().unwrap();
+
+    // Derive storage pointers
+    contract.derive_storage_pointers();
+
+    // The string is 20 bytes, followed by a single raw byte
+    assert_eq!(contract.tables[0].size, str_to_bytes32("15"));
+
+    // Have Codegen create the runtime bytecode
+    let r_bytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(r_bytes, String::from("60156100075f39496e73756666696369656e742062616c616e6365ff"));
+}
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+/// The size of the code of `FILL`, 373 `PUSH32`s
+const FILL_SIZE: usize = 373 * 33;
+
+/// Code invoking a macro of just over 12 KiB `n` times in `MAIN` and `m` times in the constructor
+fn source(n: usize, m: usize) -> String {
+    let fill = format!("push32 0x{} ", "ff".repeat(0x20)).repeat(373);
+    format!(
+        r#"
+        #define macro FILL() = takes(0) returns(0) {{
+            {fill}
+        }}
+
+        #define macro CONSTRUCTOR() = takes(0) returns(0) {{
+            {}
+        }}
+
+        #define macro MAIN() = takes(0) returns(0) {{
+            {} stop
+        }}
+        "#,
+        "FILL() ".repeat(m),
+        "FILL() ".repeat(n)
+    )
+}
+
+fn compile(source: String, strict_size: bool) -> Result<Artifact, CompilerError> {
+    let file = Arc::new(FileSource {
+        source: Some(source),
+        path: "Main.huff".to_string(),
+        ..Default::default()
+    });
+    let evm_version = EVMVersion::default();
+    let mut compiler =
+        Compiler::new(&evm_version, Arc::new(vec![]), None, None, None, None, None, false, false);
+    compiler.strict_size = strict_size;
+    compiler.gen_artifact(file)
+}
+
+#[test]
+fn test_warns_when_runtime_code_exceeds_limit() {
+    let artifact = compile(source(2, 0), false).unwrap();
+
+    assert_eq!(artifact.warnings.len(), 1);
+    assert_eq!(
+        artifact.warnings[0].kind,
+        WarningKind::CodeSizeExceeded(
+            "Runtime".to_string(),
+            2 * FILL_SIZE + 1,
+            MAX_CODE_SIZE,
+            vec![("FILL".to_string(), 2 * FILL_SIZE), ("MAIN".to_string(), 1)]
+        )
+    );
+}
+
+#[test]
+fn test_warns_when_initcode_exceeds_limit() {
+    let artifact = compile(source(1, 3), false).unwrap();
+
+    assert_eq!(artifact.warnings.len(), 1);
+    let WarningKind::CodeSizeExceeded(kind, size, limit, largest) = &artifact.warnings[0].kind
+    else {
+        panic!("Expected a code size warning")
+    };
+    assert_eq!((kind.as_str(), *limit), ("Initcode", MAX_INITCODE_SIZE));
+    assert!(*size > MAX_INITCODE_SIZE);
+    assert_eq!(largest[0], ("FILL".to_string(), 4 * FILL_SIZE));
+}
+
+#[test]
+fn test_code_within_limits_has_no_warnings() {
+    let artifact = compile(source(1, 0), true).unwrap();
+    assert!(artifact.warnings.is_empty());
+}
+
+#[test]
+fn test_strict_size_fails_compilation() {
+    let err = compile(source(2, 0), true).unwrap_err();
+    assert!(matches!(
+        err,
+        CompilerError::Warning(w) if matches!(w.kind, WarningKind::CodeSizeExceeded(..))
+    ));
+}
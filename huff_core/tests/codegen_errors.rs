@@ -35,7 +35,7 @@ fn test_storage_pointers_not_derived() {
     let full_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(full_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
     let contract = parser.parse().unwrap();
 
     // Create main and constructor bytecode
@@ -53,7 +53,8 @@ fn test_storage_pointers_not_derived() {
                         Span { start: 44, end: 44, file: None },
                         Span { start: 46, end: 67, file: None }
                     ]),
-                    token: None
+                    token: None,
+                    hint: None,
                 }
             )
         }
@@ -94,7 +95,7 @@ fn test_invalid_constant_definition() {
     let full_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(full_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
@@ -109,7 +110,8 @@ fn test_invalid_constant_definition() {
                         "UNKNOWN_CONSTANT_DEFINITION".to_string()
                     ),
                     span: AstSpan(vec![Span { start: const_start, end: const_end, file: None }]),
-                    token: None
+                    token: None,
+                    hint: None,
                 }
             )
         }
@@ -137,7 +139,7 @@ fn test_missing_constructor() {
     let full_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(full_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
@@ -150,7 +152,8 @@ fn test_missing_constructor() {
                 CodegenError {
                     kind: CodegenErrorKind::MissingMacroDefinition("CONSTRUCTOR".to_string()),
                     span: AstSpan(vec![Span { start: 0, end: 0, file: None }]),
-                    token: None
+                    token: None,
+                    hint: None,
                 }
             )
         }
@@ -170,7 +173,7 @@ fn test_missing_main() {
     let full_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(full_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
@@ -183,7 +186,8 @@ fn test_missing_main() {
                 CodegenError {
                     kind: CodegenErrorKind::MissingMacroDefinition("MAIN".to_string()),
                     span: AstSpan(vec![Span { start: 0, end: 0, file: None }]),
-                    token: None
+                    token: None,
+                    hint: None,
                 }
             )
         }
@@ -203,7 +207,7 @@ fn test_missing_when_alternative_main_provided() {
     let full_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(full_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
@@ -218,7 +222,8 @@ fn test_missing_when_alternative_main_provided() {
                 CodegenError {
                     kind: CodegenErrorKind::MissingMacroDefinition("NAH".to_string()),
                     span: AstSpan(vec![Span { start: 0, end: 0, file: None }]),
-                    token: None
+                    token: None,
+                    hint: None,
                 }
             )
         }
@@ -246,7 +251,7 @@ fn test_unknown_macro_definition() {
     let full_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(full_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
@@ -263,7 +268,8 @@ fn test_unknown_macro_definition() {
                         Span { start: 351, end: 351, file: None },
                         Span { start: 352, end: 352, file: None }
                     ]),
-                    token: None
+                    token: None,
+                    hint: None,
                 }
             )
         }
@@ -293,7 +299,7 @@ fn test_unmatched_jump_label() {
     let full_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(full_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
@@ -304,16 +310,323 @@ fn test_unmatched_jump_label() {
             assert_eq!(
                 e,
                 CodegenError {
-                    kind: CodegenErrorKind::UnmatchedJumpLabel,
+                    kind: CodegenErrorKind::UndefinedLabelArgument("err".to_string()),
                     span: AstSpan(vec![
                         Span { start: 372, end: 375, file: None },
                         Span { start: 376, end: 376, file: None },
                         Span { start: 377, end: 379, file: None },
                         Span { start: 380, end: 380, file: None }
                     ]),
-                    token: None
+                    token: None,
+                    hint: None,
                 }
             )
         }
     }
 }
+
+#[test]
+fn test_missing_error_definition() {
+    let source = r#"
+    #define error PanicError(uint256)
+
+    #define macro MAIN() = takes(0) returns (0) {
+        __ERROR(PanicErorr)
+    }
+    "#;
+
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(full_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    match Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None) {
+        Ok(_) => panic!("moose"),
+        Err(e) => {
+            assert_eq!(e.kind, CodegenErrorKind::MissingErrorDefinition("PanicErorr".to_string()))
+        }
+    }
+}
+
+#[test]
+fn test_duplicate_label() {
+    let source = r#"
+    #define macro LOOP_A() = takes(0) returns (0) {
+        loop:
+            0x01
+    }
+
+    #define macro LOOP_B() = takes(0) returns (0) {
+        loop:
+            0x02
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        LOOP_A() LOOP_B()
+        loop jump
+    }
+    "#;
+
+    let lexer = Lexer::new(source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
+    let contract = parser.parse().unwrap();
+
+    match Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None) {
+        Ok(_) => panic!("Expected a duplicate label error"),
+        Err(e) => {
+            assert_eq!(e.kind, CodegenErrorKind::DuplicateLabel("loop".to_string()));
+            let starts = e.span.0.iter().map(|s| s.start).collect::<Vec<usize>>();
+            assert_eq!(starts, vec![source.find("loop:").unwrap(), source.rfind("loop:").unwrap()]);
+        }
+    }
+}
+
+#[test]
+fn test_reused_macro_labels() {
+    // Jumps resolved within each invocation don't clash
+    let source = r#"
+    #define macro SKIP() = takes(0) returns (0) {
+        skip jump
+        skip:
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        SKIP() SKIP()
+    }
+    "#;
+
+    let lexer = Lexer::new(source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
+    let contract = parser.parse().unwrap();
+
+    let main_bytecode =
+        Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(main_bytecode, "610004565b610009565b");
+}
+
+#[test]
+fn test_invalid_macro_arg_count() {
+    let source = r#"
+    #define macro STORE(slot, value) = takes(0) returns (0) {
+        <value> <slot> sstore
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        STORE(0x01)
+    }
+    "#;
+
+    let lexer = Lexer::new(source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
+    let contract = parser.parse().unwrap();
+
+    match Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None) {
+        Ok(_) => panic!("Expected an invalid macro argument count error"),
+        Err(e) => {
+            assert_eq!(e.kind, CodegenErrorKind::InvalidMacroArgCount("STORE".to_string(), 2, 1));
+            // Points at both the invocation and the definition
+            assert_eq!(e.span.0.first().unwrap().start, source.find("STORE(0x01)").unwrap());
+            assert_eq!(e.span.0.last().unwrap().start, source.find("#define").unwrap());
+        }
+    }
+}
+
+#[test]
+fn test_recursive_macro() {
+    let source = r#"
+    #define macro PING() = takes(0) returns (0) {
+        0x01 PONG()
+    }
+
+    #define macro PONG() = takes(0) returns (0) {
+        0x02 PING()
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        PING()
+    }
+    "#;
+
+    let lexer = Lexer::new(source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
+    let contract = parser.parse().unwrap();
+
+    match Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None) {
+        Ok(_) => panic!("Expected a recursive macro error"),
+        Err(e) => {
+            assert_eq!(
+                e.kind,
+                CodegenErrorKind::RecursiveMacro(vec![
+                    "PING".to_string(),
+                    "PONG".to_string(),
+                    "PING".to_string()
+                ])
+            );
+            assert_eq!(e.span.0.first().unwrap().start, source.find("0x02 PING()").unwrap() + 5);
+        }
+    }
+}
+
+#[test]
+fn test_max_expansion_depth() {
+    let source = r#"
+    #define macro ONE() = takes(0) returns (0) {
+        0x01
+    }
+
+    #define macro TWO() = takes(0) returns (0) {
+        ONE()
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        TWO()
+    }
+    "#;
+
+    let lexer = Lexer::new(source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
+    let contract = parser.parse().unwrap();
+    let generate = |max_expansion_depth| {
+        let settings = CodegenSettings { max_expansion_depth, ..Default::default() };
+        Codegen::generate_main_bytecode_with_res(&EVMVersion::default(), &contract, None, &settings)
+            .map(|(bytecode, _)| bytecode)
+            .map_err(|e| e.kind)
+    };
+
+    assert_eq!(generate(Some(3)).unwrap(), "6001");
+
+    match generate(Some(2)) {
+        Ok(_) => panic!("Expected a maximum expansion depth error"),
+        Err(kind) => assert_eq!(kind, CodegenErrorKind::MaxExpansionDepth(2)),
+    }
+}
+
+#[test]
+fn test_invalid_jump_destination() {
+    // The JUMPDEST of the label is swallowed by the verbatim PUSH2
+    let source = r#"
+    #define macro MAIN() = takes(0) returns (0) {
+        dest jump
+        __VERBATIM(0x61)
+        dest:
+            stop
+    }
+    "#;
+
+    let lexer = Lexer::new(source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
+    let contract = parser.parse().unwrap();
+
+    match Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None) {
+        Ok(_) => panic!("Expected an invalid jump destination error"),
+        Err(e) => {
+            assert_eq!(e.kind, CodegenErrorKind::InvalidJumpDestination("dest".to_string()));
+            assert_eq!(e.span.0[0].start, source.find("dest:").unwrap());
+        }
+    }
+}
+
+#[test]
+fn test_offset_exceeds_push_width() {
+    // Over 64 KiB of code in front of the label and the table
+    let fill = format!("push32 0x{} ", "ff".repeat(0x20)).repeat(0x10000 / 33 + 1);
+    let source = format!(
+        r#"
+    #define table TABLE {{
+        0x01
+    }}
+
+    #define macro MAIN() = takes(0) returns (0) {{
+        done jump
+        {fill}
+        done:
+            __tablestart(TABLE) stop
+    }}
+    "#
+    );
+
+    let tokens = Lexer::new(&source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let contract = Parser::new(tokens, None).unwrap().parse().unwrap();
+    match Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None) {
+        Ok(_) => panic!("Expected an offset exceeding its push width"),
+        Err(e) => {
+            assert_eq!(e.kind, CodegenErrorKind::OffsetExceedsPushWidth("done".to_string(), 2, 3));
+            assert_eq!(e.span.0[0].start, source.find("done jump").unwrap());
+        }
+    }
+
+    let source = source.replace("done jump", "");
+    let tokens = Lexer::new(&source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let contract = Parser::new(tokens, None).unwrap().parse().unwrap();
+    match Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None) {
+        Ok(_) => panic!("Expected an offset exceeding its push width"),
+        Err(e) => {
+            assert_eq!(e.kind, CodegenErrorKind::OffsetExceedsPushWidth("TABLE".to_string(), 2, 3))
+        }
+    }
+}
+
+#[test]
+fn test_suggests_closest_identifier() {
+    let source = r#"
+    #define constant OWNER_SLOT = 0x00
+
+    #define macro TRANSFER(amount) = takes(0) returns (0) {
+        <amout> [OWNR_SLOT] sstore
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        success jump
+        TRANFER(0x01)
+        sucess:
+            stop
+    }
+    "#;
+
+    let generate = |source: &str| {
+        let tokens = Lexer::new(source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+        let contract = Parser::new(tokens, None).unwrap().parse().unwrap();
+        Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap_err()
+    };
+
+    // Macro invocation
+    let e = generate(source);
+    assert_eq!(e.kind, CodegenErrorKind::InvalidMacroInvocation("TRANFER".to_string()));
+    assert_eq!(e.hint, Some("Did you mean \"TRANSFER\"?".to_string()));
+    assert!(CompilerError::CodegenError(e).to_string().contains("Did you mean \"TRANSFER\"?"));
+
+    // Argument call
+    let e = generate(&source.replace("TRANFER(", "TRANSFER("));
+    assert_eq!(
+        e.kind,
+        CodegenErrorKind::UndefinedArgument("amout".to_string(), "TRANSFER".to_string())
+    );
+    assert_eq!(e.hint, Some("Did you mean \"amount\"?".to_string()));
+
+    // Constant push
+    let e = generate(&source.replace("TRANFER(", "TRANSFER(").replace("<amout>", "<amount>"));
+    assert_eq!(e.kind, CodegenErrorKind::MissingConstantDefinition("OWNR_SLOT".to_string()));
+    assert_eq!(e.hint, Some("Did you mean \"OWNER_SLOT\"?".to_string()));
+
+    // Label call
+    let e = generate(
+        &source
+            .replace("TRANFER(", "TRANSFER(")
+            .replace("<amout>", "<amount>")
+            .replace("OWNR_SLOT", "OWNER_SLOT"),
+    );
+    assert_eq!(e.kind, CodegenErrorKind::UnmatchedJumpLabel);
+    assert_eq!(e.hint, Some("Did you mean \"sucess\"?".to_string()));
+
+    // Unrelated names aren't suggested
+    let e = generate(&source.replace("TRANFER(", "APPROVE("));
+    assert_eq!(e.hint, None);
+}
@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use huff_codegen::passes::{CodegenPass, PassContext, Pipeline};
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        0x00 calldataload success jumpi
+        0x00 0x00 revert
+        success:
+            stop
+    }
+"#;
+
+/// Rejects calls with value at the start of the main code
+struct NonPayable;
+
+impl CodegenPass for NonPayable {
+    fn id(&self) -> &'static str {
+        "non-payable"
+    }
+
+    fn description(&self) -> &'static str {
+        "Reverts calls with value"
+    }
+
+    fn run(&self, program: &mut IRProgram, cx: &PassContext) -> Result<(), CodegenError> {
+        if cx.macro_name != "MAIN" {
+            return Ok(())
+        }
+        let guard = [
+            IRInstructionType::Opcode(Opcode::Callvalue),
+            IRInstructionType::Push(2, IRValue::Label("__payable".to_string())),
+            IRInstructionType::Opcode(Opcode::Jumpi),
+        ];
+        let revert = [
+            IRInstructionType::Label("__payable".to_string()),
+            IRInstructionType::Opcode(Opcode::Jumpdest),
+            IRInstructionType::Opcode(Opcode::Push0),
+            IRInstructionType::Opcode(Opcode::Push0),
+            IRInstructionType::Opcode(Opcode::Revert),
+        ];
+        let at = |ty| IRInstruction { ty, span: AstSpan(vec![]), origin: None };
+        program.instructions.splice(0..0, guard.into_iter().map(at));
+        program.instructions.extend(revert.into_iter().map(at));
+        Ok(())
+    }
+}
+
+/// Always fails
+struct Failing;
+
+impl CodegenPass for Failing {
+    fn id(&self) -> &'static str {
+        "non-payable"
+    }
+
+    fn description(&self) -> &'static str {
+        "Fails"
+    }
+
+    fn run(&self, _: &mut IRProgram, _: &PassContext) -> Result<(), CodegenError> {
+        Err(CodegenError {
+            kind: CodegenErrorKind::InvalidMacroStatement,
+            span: AstSpan(vec![]),
+            token: None,
+            hint: None,
+        })
+    }
+}
+
+fn compile(pipeline: Pipeline) -> Result<Artifact, CompilerError> {
+    let file = Arc::new(FileSource {
+        source: Some(SOURCE.to_string()),
+        path: "Main.huff".to_string(),
+        ..Default::default()
+    });
+    let evm_version = EVMVersion::default();
+    let mut compiler =
+        Compiler::new(&evm_version, Arc::new(vec![]), None, None, None, None, None, false, false);
+    compiler.pipeline = pipeline;
+    compiler.gen_artifact(file)
+}
+
+#[test]
+fn test_runs_registered_passes() {
+    let mut pipeline = Pipeline::new();
+    pipeline.register(Arc::new(NonPayable));
+    let artifact = compile(pipeline).unwrap();
+
+    // The jumps of the code are relocated after the guard
+    assert_eq!(artifact.runtime, "34610010575f3561000e575f5ffd5b005b5f5ffd");
+}
+
+#[test]
+fn test_replaces_passes_by_id() {
+    let mut pipeline = Pipeline::new();
+    pipeline.register(Arc::new(NonPayable));
+    pipeline.register(Arc::new(Failing));
+    assert_eq!(pipeline.passes().len(), 1);
+
+    let err = compile(pipeline).unwrap_err();
+    assert!(matches!(
+        err,
+        CompilerError::CodegenError(e) if e.kind == CodegenErrorKind::InvalidMacroStatement
+    ));
+}
+
+#[test]
+fn test_empty_pipeline_keeps_bytecode() {
+    let artifact = compile(Pipeline::new()).unwrap();
+    assert_eq!(artifact.runtime, "5f35610009575f5ffd5b00");
+}
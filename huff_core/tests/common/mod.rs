@@ -0,0 +1,38 @@
+//! Helpers shared by the compiler tests
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+/// Compiles a source with the default settings
+pub fn compile(source: &str) -> Result<Artifact, CompilerError> {
+    compile_with(source, |_| {})
+}
+
+/// Compiles a source with a lint raised at the given severity
+pub fn compile_with_lint(
+    source: &str,
+    lint: &str,
+    severity: Severity,
+) -> Result<Artifact, CompilerError> {
+    compile_with(source, |compiler| compiler.lints.set_severity(lint, severity).unwrap())
+}
+
+/// Compiles a source, with the settings of the compiler changed by `configure`
+pub fn compile_with(
+    source: &str,
+    configure: impl FnOnce(&mut Compiler),
+) -> Result<Artifact, CompilerError> {
+    let file = Arc::new(FileSource {
+        source: Some(source.to_string()),
+        path: "Main.huff".to_string(),
+        ..Default::default()
+    });
+    let evm_version = EVMVersion::default();
+    let mut compiler =
+        Compiler::new(&evm_version, Arc::new(vec![]), None, None, None, None, None, false, false);
+    configure(&mut compiler);
+    compiler.gen_artifact(file)
+}
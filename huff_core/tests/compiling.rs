@@ -42,7 +42,7 @@ fn compiles_constructor_bytecode() {
     let flattened_source = FullFileSource { source: SOURCE, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Grab the first macro
     let mut contract = parser.parse().unwrap();
@@ -69,7 +69,7 @@ fn compiles_runtime_bytecode() {
     let flattened_source = FullFileSource { source: SOURCE, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Grab the first macro
     let mut contract = parser.parse().unwrap();
@@ -0,0 +1,103 @@
+use huff_codegen::*;
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+fn parse_contract(source: &str) -> Contract {
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+    contract
+}
+
+#[test]
+fn test_evaluates_constant_expressions() {
+    let source = r#"
+        #define constant BASE = 0x20
+        #define constant OFFSET = add([BASE], mul(0x02, 0x10))
+        #define constant MASK = not(0x00)
+        #define constant SHIFTED = shl(0x08, [BASE])
+        #define constant WRAPPED = sub(0x00, 0x01)
+        #define constant DIV_ZERO = div([BASE], 0x00)
+
+        #define macro MAIN() = takes(0) returns(0) {
+            [OFFSET] [MASK] [SHIFTED] [WRAPPED] [DIV_ZERO]
+        }
+    "#;
+    let contract = parse_contract(source);
+    contract.evaluate_constants().unwrap();
+
+    let main_bytecode =
+        Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    let max = "ff".repeat(32);
+    assert_eq!(main_bytecode, format!("60407f{max}6120007f{max}5f"));
+}
+
+#[test]
+fn test_constant_expression_references_storage_pointer() {
+    let source = r#"
+        #define constant SLOT_A = FREE_STORAGE_POINTER()
+        #define constant SLOT_B = FREE_STORAGE_POINTER()
+        #define constant NEXT = add([SLOT_B], 0x01)
+
+        #define macro MAIN() = takes(0) returns(0) {
+            [SLOT_A] [NEXT]
+        }
+    "#;
+    let contract = parse_contract(source);
+    contract.evaluate_constants().unwrap();
+
+    let main_bytecode =
+        Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(main_bytecode, "5f6002");
+}
+
+#[test]
+fn test_constant_expression_evaluated_in_codegen() {
+    let source = r#"
+        #define constant OFFSET = add(0x20, 0x20)
+
+        #define macro MAIN() = takes(0) returns(0) {
+            [OFFSET]
+        }
+    "#;
+    let contract = parse_contract(source);
+
+    let main_bytecode =
+        Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(main_bytecode, "6040");
+}
+
+#[test]
+fn test_circular_constant_expression() {
+    let source = r#"
+        #define constant A = add([B], 0x01)
+        #define constant B = add([A], 0x01)
+
+        #define macro MAIN() = takes(0) returns(0) {
+            [A]
+        }
+    "#;
+    let contract = parse_contract(source);
+
+    let err = contract.evaluate_constants().unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::CircularConstantReference("A".to_string()));
+}
+
+#[test]
+fn test_constant_expression_missing_reference() {
+    let source = r#"
+        #define constant A = add([MISSING], 0x01)
+
+        #define macro MAIN() = takes(0) returns(0) {
+            [A]
+        }
+    "#;
+    let contract = parse_contract(source);
+
+    let err = contract.evaluate_constants().unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::MissingConstantDefinition("MISSING".to_string()));
+}
@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use huff_codegen::passes::{ConstantFolding, Pipeline};
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        push32 0x20 push2 0x0000 mstore
+        __FUNC_SIG("transfer(address,uint256)") pop
+        0x20 0x00 0x20 0x00 return
+    }
+"#;
+
+fn compile(evm_version: EVMVersion, pipeline: Pipeline) -> Artifact {
+    let file = Arc::new(FileSource {
+        source: Some(SOURCE.to_string()),
+        path: "Main.huff".to_string(),
+        ..Default::default()
+    });
+    let mut compiler =
+        Compiler::new(&evm_version, Arc::new(vec![]), None, None, None, None, None, false, false);
+    compiler.pipeline = pipeline;
+    compiler.gen_artifact(file).unwrap()
+}
+
+fn folding() -> Pipeline {
+    let mut pipeline = Pipeline::new();
+    pipeline.register(Arc::new(ConstantFolding));
+    pipeline
+}
+
+#[test]
+fn test_folds_literal_pushes() {
+    let artifact = compile(EVMVersion::default(), folding());
+
+    // Explicit widths are narrowed, and the repeated `0x20` is duplicated
+    assert_eq!(artifact.runtime, "60205f5263a9059cbb5060205f815ff3");
+}
+
+#[test]
+fn test_folds_zero_without_push0() {
+    let artifact = compile(EVMVersion::from("paris".to_string()), folding());
+
+    // Without `PUSH0` zero is duplicated too
+    assert_eq!(artifact.runtime, "602060005263a9059cbb50602060008181f3");
+}
+
+#[test]
+fn test_keeps_pushes_by_default() {
+    let artifact = compile(EVMVersion::default(), Pipeline::new());
+    assert_eq!(
+        artifact.runtime,
+        "7f0000000000000000000000000000000000000000000000000000000000000020610000\
+         5263a9059cbb5060205f60205ff3"
+    );
+}
@@ -0,0 +1,98 @@
+use huff_utils::prelude::*;
+
+mod common;
+use common::{compile, compile_with};
+
+/// Pushes the argument of the call, doubled
+fn double() -> CustomBuiltin {
+    CustomBuiltin::new("__DOUBLE", 1, |call, _| {
+        let arg = call.args[0].name.clone().unwrap_or_default();
+        let value = u8::from_str_radix(&arg, 16).map_err(|_| CodegenError {
+            kind: CodegenErrorKind::InvalidHex(arg.clone()),
+            span: AstSpan(vec![]),
+            token: None,
+            hint: None,
+        })?;
+        Ok(format!("60{:02x}", value.wrapping_mul(2)))
+    })
+}
+
+#[test]
+fn test_expands_custom_builtins() {
+    let source = r#"
+        #define macro MAIN() = takes(0) returns(0) {
+            __DOUBLE(0x21) 0x00 mstore
+        }
+    "#;
+    let mut builtins = CustomBuiltins::new();
+    builtins.register(double());
+
+    let artifact = compile_with(source, |compiler| compiler.builtins = builtins).unwrap();
+    assert_eq!(artifact.runtime, "60425f52");
+}
+
+#[test]
+fn test_custom_builtin_arity() {
+    let source = r#"
+        #define macro MAIN() = takes(0) returns(0) {
+            __DOUBLE(0x21, 0x01)
+        }
+    "#;
+    let mut builtins = CustomBuiltins::new();
+    builtins.register(double());
+
+    let err = compile_with(source, |compiler| compiler.builtins = builtins).unwrap_err();
+    let CompilerError::CodegenError(e) = err else { panic!("Expected a codegen error: {err:?}") };
+    assert_eq!(
+        e.kind,
+        CodegenErrorKind::InvalidArguments(
+            "Incorrect number of arguments passed to __DOUBLE, should be 1: 2".to_string()
+        )
+    );
+}
+
+#[test]
+fn test_custom_builtin_errors_keep_their_call_span() {
+    let source = r#"
+        #define macro MAIN() = takes(0) returns(0) {
+            __DOUBLE(0x0101)
+        }
+    "#;
+    let mut builtins = CustomBuiltins::new();
+    builtins.register(double());
+
+    let err = compile_with(source, |compiler| compiler.builtins = builtins).unwrap_err();
+    let CompilerError::CodegenError(e) = err else { panic!("Expected a codegen error: {err:?}") };
+    assert_eq!(e.kind, CodegenErrorKind::InvalidHex("0101".to_string()));
+    assert!(!e.span.0.is_empty());
+}
+
+#[test]
+fn test_unregistered_builtins_are_not_lexed() {
+    let source = r#"
+        #define macro MAIN() = takes(0) returns(0) {
+            __DOUBLE(0x21)
+        }
+    "#;
+    assert!(compile(source).is_err());
+}
+
+#[test]
+fn test_missing_builtin_definition() {
+    let source = r#"
+        #define macro MAIN() = takes(0) returns(0) {
+            __DOUBLE(0x21)
+        }
+    "#;
+    let tokens = huff_lexer::Lexer::new(source)
+        .with_custom_builtins(vec!["__DOUBLE".to_string()])
+        .collect::<Result<Vec<Token>, _>>()
+        .unwrap();
+    let mut parser = huff_parser::Parser::new(tokens, None).unwrap();
+    let contract = parser.parse().unwrap();
+
+    let err =
+        huff_codegen::Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None)
+            .unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::MissingBuiltinDefinition("__DOUBLE".to_string()));
+}
@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use huff_codegen::passes::{DeadCodeElimination, Pipeline};
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"
+    #define jumptable__packed ROUTES {
+        kept
+    }
+
+    #define jumptable__packed SIZES {
+        sized
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        __tablesize(SIZES) pop
+        __tablestart(ROUTES) mload 0xf0 shr jump
+        dead:
+            0x01 0x00 sstore
+            stop
+        kept:
+            stop
+        sized:
+            0x00 0x00 revert
+    }
+"#;
+
+fn compile(pipeline: Pipeline) -> Artifact {
+    let file = Arc::new(FileSource {
+        source: Some(SOURCE.to_string()),
+        path: "Main.huff".to_string(),
+        ..Default::default()
+    });
+    let evm_version = EVMVersion::default();
+    let mut compiler =
+        Compiler::new(&evm_version, Arc::new(vec![]), None, None, None, None, None, false, false);
+    compiler.pipeline = pipeline;
+    compiler.gen_artifact(file).unwrap()
+}
+
+#[test]
+fn test_removes_unreachable_code() {
+    let mut pipeline = Pipeline::new();
+    pipeline.register(Arc::new(DeadCodeElimination));
+    let artifact = compile(pipeline);
+
+    // The `dead` block is never jumped to, `sized` is only in a table that is never read
+    assert_eq!(artifact.runtime, "60025061000d5160f01c565b00000b");
+    assert_eq!(artifact.debug.runtime.iter().filter(|r| r.label.is_some()).count(), 1);
+}
+
+#[test]
+fn test_keeps_code_by_default() {
+    let artifact = compile(Pipeline::new());
+    assert_eq!(artifact.runtime, "6002506100195160f01c565b60015f55005b005b5f5ffd00130011");
+}
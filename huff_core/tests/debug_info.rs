@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"#define macro REQUIRE_OWNER() = takes(0) returns(0) {
+    caller 0x00 sload eq owner jumpi
+    error:
+        0x00 0x00 revert
+    owner:
+}
+
+#define macro TRANSFER() = takes(0) returns(0) {
+    REQUIRE_OWNER()
+    0x01 0x00 sstore
+}
+
+#define macro MAIN() = takes(0) returns(0) {
+    0x00 calldataload
+    transfer jumpi
+    0x00 0x00 revert
+    transfer:
+        TRANSFER()
+        stop
+}
+"#;
+
+fn compile() -> Artifact {
+    let file = Arc::new(FileSource {
+        source: Some(SOURCE.to_string()),
+        path: "Main.huff".to_string(),
+        ..Default::default()
+    });
+    let evm_version = EVMVersion::default();
+    let compiler =
+        Compiler::new(&evm_version, Arc::new(vec![]), None, None, None, None, None, false, false);
+    compiler.gen_artifact(file).unwrap()
+}
+
+#[test]
+fn test_debug_info() {
+    let artifact = compile();
+    let debug = &artifact.debug;
+    assert!(debug.constructor.is_empty());
+
+    let traces = debug.runtime.iter().map(|r| (r.start, r.end, r.trace())).collect::<Vec<_>>();
+    assert_eq!(
+        traces,
+        vec![
+            (0, 9, "MAIN".to_string()),
+            (9, 10, "MAIN > transfer:".to_string()),
+            (10, 18, "MAIN > TRANSFER > REQUIRE_OWNER".to_string()),
+            (18, 22, "MAIN > TRANSFER > REQUIRE_OWNER > error:".to_string()),
+            (22, 23, "MAIN > TRANSFER > REQUIRE_OWNER > owner:".to_string()),
+            (23, 27, "MAIN > TRANSFER".to_string()),
+            (27, 28, "MAIN > transfer:".to_string()),
+        ]
+    );
+
+    // The revert of the owner check
+    let revert = debug.runtime_at(21).unwrap();
+    assert_eq!(revert.macros, vec!["MAIN", "TRANSFER", "REQUIRE_OWNER"]);
+    assert_eq!(revert.label.as_deref(), Some("error"));
+}
+
+#[test]
+fn test_assembly() {
+    let assembly = compile().assembly();
+
+    // The bootstrap copying the runtime code, without the runtime code itself
+    assert!(assembly.starts_with("creation {\n    0x0000  push1 0x1c\n"));
+    assert!(assembly.contains("    0x0008  return\n}\n\nruntime {\n    // MAIN\n"));
+
+    assert!(assembly.contains("    0x0002  push2 0x0009            // -> transfer\n"));
+    assert!(assembly.contains(
+        "    transfer:\n    0x0009  jumpdest\n    // MAIN > TRANSFER > REQUIRE_OWNER\n    0x000a  caller\n"
+    ));
+    assert!(assembly.ends_with("    // MAIN\n    0x001b  stop\n}\n"));
+}
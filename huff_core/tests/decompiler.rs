@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use huff_core::Compiler;
+use huff_utils::{decompiler::decompile, prelude::*};
+
+const SOURCE: &str = r#"#define function transfer(address,uint256) nonpayable returns ()
+#define function owner() view returns (address)
+
+#define macro MAIN() = takes(0) returns(0) {
+    0x00 calldataload 0xe0 shr
+    dup1 __FUNC_SIG(transfer) eq transfer jumpi
+    dup1 __FUNC_SIG(owner) eq owner jumpi
+    0x00 0x00 revert
+
+    transfer:
+        0x04 calldataload 0x00 sstore
+        stop
+    owner:
+        0x00 sload 0x00 mstore
+        0x20 0x00 return
+}
+"#;
+
+fn runtime(source: &str) -> String {
+    let file = Arc::new(FileSource {
+        source: Some(source.to_string()),
+        path: "Main.huff".to_string(),
+        ..Default::default()
+    });
+    let evm_version = EVMVersion::default();
+    let compiler =
+        Compiler::new(&evm_version, Arc::new(vec![]), None, None, None, None, None, false, false);
+    compiler.gen_artifact(file).unwrap().runtime
+}
+
+#[test]
+fn test_decompile_dispatcher() {
+    let bytecode = runtime(SOURCE);
+    let decompiled = decompile(&bytecode);
+
+    assert!(decompiled.contains("#define function func_a9059cbb() nonpayable returns ()\n"));
+    assert!(decompiled.contains("#define function func_8da5cb5b() nonpayable returns ()\n"));
+    assert!(decompiled.contains("    dup1\n    push4 0xa9059cbb\n    eq\n    func_a9059cbb_dest\n"));
+    assert!(decompiled.contains("    func_8da5cb5b_dest:\n        push0\n        sload\n"));
+    assert!(!decompiled.contains("#define table"));
+
+    // The skeleton compiles back to the same bytecode
+    assert_eq!(runtime(&decompiled), bytecode);
+}
+
+#[test]
+fn test_decompile_trailing_data() {
+    // `stop` followed by solc's metadata, a CBOR map and its length
+    let decompiled = decompile("0x600160005500a16474657374f50007");
+    assert!(decompiled.contains("    push1 0x01\n    push1 0x00\n    sstore\n    stop\n}\n"));
+    assert!(decompiled.contains("#define table DATA {\n    0xa16474657374f50007\n}\n"));
+
+    // The code ends at an unknown opcode
+    let decompiled = decompile("600c0c");
+    assert!(decompiled.contains("    push1 0x0c\n}\n"));
+    assert!(decompiled.contains("    0x0c\n"));
+}
@@ -30,7 +30,8 @@ fn test_erc20_compile() {
     };
     let lexer = Lexer::new(full_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, Some("../huff-examples/erc20/contracts".to_string()));
+    let mut parser =
+        Parser::new(tokens, Some("../huff-examples/erc20/contracts".to_string())).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
@@ -18,7 +18,7 @@ fn test_abi_uint_events() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
@@ -66,7 +66,9 @@ fn test_abi_uint_events() {
                         indexed: false
                     }
                 ],
-                anonymous: false
+                anonymous: false,
+                topic: "0xcf75c6acba61bcd498dd4f47654944c67aa30410ac807d1ed931361adc35e43c"
+                    .to_string()
             }
         )])
     );
@@ -86,7 +88,7 @@ fn test_abi_int_events() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
@@ -134,7 +136,9 @@ fn test_abi_int_events() {
                         indexed: false
                     }
                 ],
-                anonymous: false
+                anonymous: false,
+                topic: "0x0586c2451a8c768925bc83679d5ea0d1c824866e7cfe16bb69589c719ab7b4ff"
+                    .to_string()
             }
         )])
     );
@@ -154,7 +158,7 @@ fn test_abi_simple_events() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
@@ -192,7 +196,9 @@ fn test_abi_simple_events() {
                         indexed: false
                     }
                 ],
-                anonymous: false
+                anonymous: false,
+                topic: "0xd08ed3816d85ca50ad912d51b01bb6f052745266dbaab34082143594e274fdba"
+                    .to_string()
             }
         )])
     );
@@ -213,7 +219,7 @@ fn test_abi_tuple_array_events() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
@@ -251,7 +257,9 @@ fn test_abi_tuple_array_events() {
                         indexed: false
                     }
                 ],
-                anonymous: false
+                anonymous: false,
+                topic: "0xd08ed3816d85ca50ad912d51b01bb6f052745266dbaab34082143594e274fdba"
+                    .to_string()
             }
         )])
     );
@@ -272,7 +280,7 @@ fn test_abi_nested_tuple_array_events() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
@@ -310,7 +318,9 @@ fn test_abi_nested_tuple_array_events() {
                         indexed: false
                     }
                 ],
-                anonymous: false
+                anonymous: false,
+                topic: "0xd08ed3816d85ca50ad912d51b01bb6f052745266dbaab34082143594e274fdba"
+                    .to_string()
             }
         )])
     );
@@ -97,3 +97,35 @@ fn test_transform_paths_no_dir() {
         }
     }
 }
+
+#[test]
+fn test_circular_import_through_parent_dir() {
+    let dir_name = format!("huff_circular_imports_{}", std::process::id());
+    let dir = std::env::temp_dir().join(&dir_name);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    // The import of a.huff goes up and back into the same directory
+    let main_path = dir.join("a.huff");
+    std::fs::write(&main_path, "#include \"./b.huff\"").unwrap();
+    std::fs::write(dir.join("b.huff"), format!("#include \"./../{dir_name}/a.huff\"")).unwrap();
+
+    let evm_version = EVMVersion::default();
+    let compiler = Compiler::new(
+        &evm_version,
+        Arc::new(vec![main_path.to_string_lossy().to_string()]),
+        None,
+        None,
+        None,
+        None,
+        None,
+        false,
+        false,
+    );
+    let res = compiler.execute();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    match &*res.unwrap_err() {
+        CompilerError::CircularImport(cycle, _) => assert_eq!(cycle.len(), 3),
+        e => panic!("Expected a circular import error, got {e:?}"),
+    }
+}
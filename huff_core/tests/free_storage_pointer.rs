@@ -26,7 +26,7 @@ fn test_set_free_storage_pointers() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Parse AST
     let mut contract = parser.parse().unwrap();
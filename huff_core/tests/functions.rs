@@ -95,7 +95,7 @@ fn test_function() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Parse the AST
     let mut contract = parser.parse().unwrap();
@@ -189,7 +189,7 @@ fn test_nested_function() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Parse the AST
     let mut contract = parser.parse().unwrap();
@@ -211,3 +211,41 @@ fn test_nested_function() {
         cg.churn(Arc::clone(&Arc::new(FileSource::default())), vec![], &rbytes, "", false).unwrap();
     assert_eq!(artifact.bytecode, String::from("60638060093d393df35f3560e01c80630759002014610010575b604435602435600435610024929190610055565b5f5260205ff35b828282025f521515908015905f5104831417161561004e57505f51046001610052575b5f5ffd5b90565b61006092919061002b565b9056"));
 }
+
+#[test]
+fn test_function_labels_are_scoped() {
+    let source: &str = r#"
+        #define fn CHECK() = takes (1) returns (1) {
+            dup1 done jumpi
+            0x00 0x00 revert
+            done:
+        }
+
+        #define macro MAIN() = takes (0) returns (0) {
+            0x00 calldataload CHECK()
+            done jump
+            0x01 0x00 mstore
+            done:
+                stop
+        }
+    "#;
+
+    // Parse tokens
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+
+    // Parse the AST
+    let mut contract = parser.parse().unwrap();
+
+    // Derive storage pointers
+    contract.derive_storage_pointers();
+
+    // The `done` label of MAIN must not resolve to the `done` label inside of CHECK
+    let rbytes = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(
+        rbytes,
+        String::from("5f3561000a90610015565b6100135660015f525b005b8061001e575f5ffd5b9056")
+    );
+}
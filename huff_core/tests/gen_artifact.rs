@@ -102,9 +102,119 @@ fn test_missing_constructor_with_inputs() {
                 CompilerError::CodegenError(CodegenError {
                     kind: CodegenErrorKind::MissingMacroDefinition("CONSTRUCTOR".to_string()),
                     span: AstSpan(vec![Span { start: 0, end: 0, file: Some(arc_source) }]),
-                    token: None
+                    token: None,
+                    hint: None,
                 })
             )
         }
     }
 }
+
+#[test]
+fn test_storage_layout() {
+    let source = r#"
+    #define constant OWNER_SLOT = STORAGE_SLOT(0x10)
+    #define constant BALANCES_SLOT = FREE_STORAGE_POINTER()
+
+    #define macro MAIN() = takes(0) returns (0) {
+        [OWNER_SLOT] sload
+        [BALANCES_SLOT] sload
+    }
+    "#;
+
+    // Full source
+    let full_source = FileSource {
+        source: Some(source.to_string()),
+        id: uuid::Uuid::new_v4(),
+        path: "".to_string(),
+        access: None,
+        dependencies: None,
+    };
+
+    let evm_version = EVMVersion::default();
+
+    // Instantiate a new compiler
+    let compiler =
+        Compiler::new(&evm_version, Arc::new(vec![]), None, None, None, None, None, false, false);
+
+    // Generate the compile artifact
+    let artifact = compiler.gen_artifact(Arc::new(full_source)).unwrap();
+    assert_eq!(
+        artifact.storage_layout,
+        StorageLayout {
+            storage: vec![
+                StorageLayoutEntry { label: "BALANCES_SLOT".to_string(), slot: "0".to_string() },
+                StorageLayoutEntry { label: "OWNER_SLOT".to_string(), slot: "16".to_string() },
+            ]
+        }
+    );
+    assert_eq!(
+        serde_json::to_value(&artifact).unwrap()["storageLayout"],
+        serde_json::json!({ "storage": [
+            { "label": "BALANCES_SLOT", "slot": "0" },
+            { "label": "OWNER_SLOT", "slot": "16" },
+        ]})
+    );
+}
+
+#[test]
+fn test_natspec() {
+    let source = r#"
+    /// @notice Transfers tokens to a recipient
+    /// @dev Reverts if the balance is
+    ///      too low
+    /// @param to The recipient
+    /// @param amount The amount of tokens
+    /// @return success Whether the transfer succeeded
+    #define function transfer(address to, uint256 amount) nonpayable returns (bool success)
+
+    /** Returns the balance of an account */
+    #define function balanceOf(address) view returns (uint256)
+
+    /// @return The total supply
+    #define function totalSupply() view returns (uint256)
+
+    #define function undocumented() view returns ()
+
+    #define macro MAIN() = takes(0) returns (0) {}
+    "#;
+
+    let full_source = FileSource {
+        source: Some(source.to_string()),
+        id: uuid::Uuid::new_v4(),
+        path: "".to_string(),
+        access: None,
+        dependencies: None,
+    };
+    let evm_version = EVMVersion::default();
+    let compiler =
+        Compiler::new(&evm_version, Arc::new(vec![]), None, None, None, None, None, false, false);
+    let artifact = compiler.gen_artifact(Arc::new(full_source)).unwrap();
+
+    assert_eq!(
+        serde_json::to_value(&artifact).unwrap()["userdoc"],
+        serde_json::json!({
+            "kind": "user",
+            "methods": {
+                "balanceOf(address)": { "notice": "Returns the balance of an account" },
+                "transfer(address,uint256)": { "notice": "Transfers tokens to a recipient" },
+            },
+            "version": 1,
+        })
+    );
+    assert_eq!(
+        serde_json::to_value(&artifact).unwrap()["devdoc"],
+        serde_json::json!({
+            "kind": "dev",
+            "methods": {
+                "totalSupply()": { "returns": { "_0": "The total supply" } },
+                "transfer(address,uint256)": {
+                    "details": "Reverts if the balance is too low",
+                    "params": { "amount": "The amount of tokens", "to": "The recipient" },
+                    "returns": { "success": "Whether the transfer succeeded" },
+                },
+            },
+            "version": 1,
+        })
+    );
+}
@@ -0,0 +1,196 @@
+use std::{collections::HashMap, sync::Arc};
+
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"
+    #define immutable OWNER
+    #define immutable FEE
+
+    #define macro STORE(value, ptr) = takes(0) returns (0) {
+        <value> <ptr> mstore
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        [OWNER] 0x00 mstore
+        STORE(FEE, 0x20)
+        0x40 0x00 return
+    }
+"#;
+
+#[test]
+fn test_immutables_filled_from_constructor_args() {
+    let main_file_name = String::from("main.huff");
+    let mut file_sources = HashMap::new();
+    let source = format!("{SOURCE}\n#define macro CONSTRUCTOR() = takes(0) returns (0) {{}}");
+    file_sources.insert(main_file_name.clone(), source);
+
+    let evm_version = EVMVersion::default();
+    let compiler = Compiler::new_in_memory(
+        &evm_version,
+        Arc::new(vec![main_file_name.clone()]),
+        file_sources,
+        None,
+        None,
+        Some(vec!["0x646dB8ffC21e7ddc2B6327448dd9Fa560Df41087".to_string(), "100".to_string()]),
+        None,
+        false,
+    );
+
+    let artifacts = compiler.execute().unwrap();
+    let artifact = artifacts.iter().find(|a| main_file_name.eq(&a.file.path)).unwrap();
+
+    // The runtime code pushes zeroed placeholders
+    let placeholder = format!("7f{}", "00".repeat(32));
+    assert_eq!(artifact.runtime, format!("{placeholder}5f52{placeholder}60205260405ff3"));
+
+    // The bootstrap copies each constructor argument over its placeholders in the runtime code
+    let args =
+        format!("000000000000000000000000646db8ffc21e7ddc2b6327448dd9fa560df41087{:064x}", 100);
+    assert_eq!(
+        artifact.bytecode,
+        format!("604b80601b3d396020610066610001396020610086610024393df3{}{args}", artifact.runtime)
+    );
+}
+
+#[test]
+fn test_immutables_with_custom_bootstrap() {
+    let source = format!(
+        "{SOURCE}\n#define macro CONSTRUCTOR() = takes(0) returns (0) {{ 0x00 0x00 return }}"
+    );
+    let main_file_name = String::from("main.huff");
+    let mut file_sources = HashMap::new();
+    file_sources.insert(main_file_name.clone(), source);
+
+    let evm_version = EVMVersion::default();
+    let compiler = Compiler::new_in_memory(
+        &evm_version,
+        Arc::new(vec![main_file_name]),
+        file_sources,
+        None,
+        None,
+        None,
+        None,
+        false,
+    );
+
+    match compiler.execute().unwrap_err().as_ref() {
+        CompilerError::FailedCompiles(errors) => assert!(errors.iter().any(|e| matches!(
+            e,
+            CompilerError::CodegenError(ce)
+                if ce.kind == CodegenErrorKind::ImmutablesWithCustomBootstrap
+        ))),
+        e => panic!("Unexpected error: {e:?}"),
+    }
+}
+
+#[test]
+fn test_immutables_in_constructor() {
+    let source = format!(
+        "{SOURCE}\n#define macro CONSTRUCTOR() = takes(0) returns (0) {{ STORE(OWNER, 0x00) }}"
+    );
+    let main_file_name = String::from("main.huff");
+    let mut file_sources = HashMap::new();
+    file_sources.insert(main_file_name.clone(), source);
+
+    let evm_version = EVMVersion::default();
+    let compiler = Compiler::new_in_memory(
+        &evm_version,
+        Arc::new(vec![main_file_name]),
+        file_sources,
+        None,
+        None,
+        None,
+        None,
+        false,
+    );
+
+    // The placeholder would be left unfilled in the creation code
+    match compiler.execute().unwrap_err().as_ref() {
+        CompilerError::FailedCompiles(errors) => assert!(errors.iter().any(|e| matches!(
+            e,
+            CompilerError::CodegenError(ce)
+                if ce.kind == CodegenErrorKind::ImmutableInConstructor("OWNER".to_string()) &&
+                    !ce.span.0.is_empty()
+        ))),
+        e => panic!("Unexpected error: {e:?}"),
+    }
+}
+
+#[test]
+fn test_immutables_filled_next_to_dynamic_args() {
+    let source = format!(
+        "{SOURCE}\n#define function constructor(uint256[2], address, string) nonpayable returns ()\n#define macro CONSTRUCTOR() = takes(0) returns (0) {{}}"
+    );
+    let main_file_name = String::from("main.huff");
+    let mut file_sources = HashMap::new();
+    file_sources.insert(main_file_name.clone(), source);
+
+    let evm_version = EVMVersion::default();
+    let compiler = Compiler::new_in_memory(
+        &evm_version,
+        Arc::new(vec![main_file_name.clone()]),
+        file_sources,
+        None,
+        None,
+        Some(vec![
+            "[1, 2]".to_string(),
+            "0x646dB8ffC21e7ddc2B6327448dd9Fa560Df41087".to_string(),
+            "tst".to_string(),
+        ]),
+        None,
+        false,
+    );
+
+    let artifacts = compiler.execute().unwrap();
+    let artifact = artifacts.iter().find(|a| main_file_name.eq(&a.file.path)).unwrap();
+
+    // The immutables are copied from the heads of their arguments, the fixed size array taking
+    // two words and the string's head only holding the offset of its value
+    let args = [
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        "0000000000000000000000000000000000000000000000000000000000000002",
+        "000000000000000000000000646db8ffc21e7ddc2b6327448dd9fa560df41087",
+        "0000000000000000000000000000000000000000000000000000000000000080",
+        "0000000000000000000000000000000000000000000000000000000000000003",
+        "7473740000000000000000000000000000000000000000000000000000000000",
+    ]
+    .concat();
+    assert_eq!(
+        artifact.bytecode,
+        format!("604b80601b3d3960206100666100013960206100a6610024393df3{}{args}", artifact.runtime)
+    );
+}
+
+#[test]
+fn test_immutables_filled_from_dynamic_args() {
+    let source = format!(
+        "{SOURCE}\n#define function constructor(string, uint256) nonpayable returns ()\n#define macro CONSTRUCTOR() = takes(0) returns (0) {{}}"
+    );
+    let main_file_name = String::from("main.huff");
+    let mut file_sources = HashMap::new();
+    file_sources.insert(main_file_name.clone(), source);
+
+    let evm_version = EVMVersion::default();
+    let compiler = Compiler::new_in_memory(
+        &evm_version,
+        Arc::new(vec![main_file_name]),
+        file_sources,
+        None,
+        None,
+        None,
+        None,
+        false,
+    );
+
+    // The head of the string only holds the offset of its value
+    match compiler.execute().unwrap_err().as_ref() {
+        CompilerError::FailedCompiles(errors) => assert!(errors.iter().any(|e| matches!(
+            e,
+            CompilerError::CodegenError(ce)
+                if ce.kind == CodegenErrorKind::DynamicImmutableArgument("OWNER".to_string()) &&
+                    !ce.span.0.is_empty()
+        ))),
+        e => panic!("Unexpected error: {e:?}"),
+    }
+}
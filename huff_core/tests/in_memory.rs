@@ -1,7 +1,13 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
 
 use huff_core::Compiler;
-use huff_utils::prelude::EVMVersion;
+use huff_utils::prelude::{
+    CodegenErrorKind, CompilerError, ConstantDefinition, EVMVersion, ParserErrorKind, TokenKind,
+    WarningKind,
+};
 
 #[test]
 fn test_in_memory_compiler() {
@@ -55,3 +61,755 @@ fn test_in_memory_compiler() {
         "60188060093d393df35f3560e01c806340c10f1914610010575b6004355f602435".to_string()
     );
 }
+
+#[test]
+fn test_in_memory_compiler_with_remappings() {
+    let source_main = r#"
+    #include "@mint/mint.huff"
+
+    #define macro MAIN() = takes(0) returns (0) {
+        MINT()
+    }
+    "#;
+
+    let source_mint = r#"
+    #define macro MINT() = takes(0) returns (0) {
+        0x04 calldataload
+    }
+    "#;
+
+    let main_file_name = String::from("contracts/main.huff");
+
+    let mut file_sources = HashMap::new();
+    file_sources.insert(main_file_name.clone(), String::from(source_main));
+    file_sources.insert(String::from("lib/mint/src/mint.huff"), String::from(source_mint));
+
+    // Instantiate a new compiler
+    let evm_version = EVMVersion::default();
+    let mut compiler = Compiler::new_in_memory(
+        &evm_version,
+        Arc::new(vec![main_file_name.clone()]),
+        file_sources,
+        None,
+        None,
+        None,
+        None,
+        false,
+    );
+    compiler.remappings = vec![String::from("@mint/=lib/mint/src/")];
+
+    let artifacts = compiler.execute().unwrap();
+    let artifact = artifacts.iter().find(|a| main_file_name.eq(&a.file.path)).unwrap();
+
+    assert_eq!(artifact.bytecode, "60038060093d393df3600435".to_string());
+}
+
+#[test]
+fn test_in_memory_compiler_circular_import() {
+    let source_a = r#"
+    #include "./b.huff"
+
+    #define macro MAIN() = takes(0) returns (0) {
+        B()
+    }
+    "#;
+
+    let source_b = r#"
+    #include "./a.huff"
+
+    #define macro B() = takes(0) returns (0) {
+        0x01
+    }
+    "#;
+
+    let mut file_sources = HashMap::new();
+    file_sources.insert(String::from("contracts/a.huff"), String::from(source_a));
+    file_sources.insert(String::from("contracts/b.huff"), String::from(source_b));
+
+    // Instantiate a new compiler
+    let evm_version = EVMVersion::default();
+    let compiler = Compiler::new_in_memory(
+        &evm_version,
+        Arc::new(vec![String::from("contracts/a.huff")]),
+        file_sources,
+        None,
+        None,
+        None,
+        None,
+        false,
+    );
+
+    match &*compiler.execute().unwrap_err() {
+        CompilerError::CircularImport(cycle, spans) => {
+            assert_eq!(cycle, &vec!["contracts/a.huff", "contracts/b.huff", "contracts/a.huff"]);
+            let spans = spans.0.iter().map(|s| (s.start, s.end)).collect::<Vec<_>>();
+            let start_a = source_a.find("./b.huff").unwrap();
+            let start_b = source_b.find("./a.huff").unwrap();
+            assert_eq!(spans, vec![(start_a, start_a + 7), (start_b, start_b + 7)]);
+        }
+        e => panic!("Expected a circular import error, got {e:?}"),
+    }
+}
+
+#[test]
+fn test_in_memory_compiler_selective_import() {
+    let source_main = r#"
+    #include "./utils.huff" { ADD_ONE }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        0x00 ADD_ONE()
+    }
+    "#;
+
+    let source_utils = r#"
+    #define constant ONE = 0x01
+
+    #define macro INCREMENT(value) = takes(1) returns (1) {
+        <value> add
+    }
+
+    #define macro ADD_ONE() = takes(1) returns (1) {
+        INCREMENT(ONE)
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        0x02
+    }
+    "#;
+
+    let main_file_name = String::from("contracts/main.huff");
+
+    let mut file_sources = HashMap::new();
+    file_sources.insert(main_file_name.clone(), String::from(source_main));
+    file_sources.insert(String::from("contracts/utils.huff"), String::from(source_utils));
+
+    // Instantiate a new compiler
+    let evm_version = EVMVersion::default();
+    let compiler = Compiler::new_in_memory(
+        &evm_version,
+        Arc::new(vec![main_file_name.clone()]),
+        file_sources.clone(),
+        None,
+        None,
+        None,
+        None,
+        false,
+    );
+
+    let artifacts = compiler.execute().unwrap();
+    let artifact = artifacts.iter().find(|a| main_file_name.eq(&a.file.path)).unwrap();
+    assert_eq!(artifact.bytecode, "60048060093d393df35f600101".to_string());
+
+    // Selecting a definition the file doesn't contain fails
+    file_sources
+        .insert(main_file_name.clone(), source_main.replace("{ ADD_ONE }", "{ ADD_ONE, ADD_TWO }"));
+    let compiler = Compiler::new_in_memory(
+        &evm_version,
+        Arc::new(vec![main_file_name]),
+        file_sources,
+        None,
+        None,
+        None,
+        None,
+        false,
+    );
+    match &*compiler.execute().unwrap_err() {
+        CompilerError::FailedCompiles(errors) => match &errors[..] {
+            [CompilerError::ParserError(pe)] => {
+                assert_eq!(pe.kind, ParserErrorKind::InvalidImportSelection("ADD_TWO".to_string()))
+            }
+            e => panic!("Expected an invalid import selection error, got: {e:?}"),
+        },
+        e => panic!("Expected an invalid import selection error, got: {e:?}"),
+    }
+}
+
+#[test]
+fn test_in_memory_compiler_file_imported_selectively_and_in_full() {
+    let source_main = r#"
+    #include "./utils.huff" { ADD_ONE }
+    #include "./math.huff"
+
+    #define macro MAIN() = takes(0) returns (0) {
+        0x00 ADD_ONE() QUADRUPLE()
+    }
+    "#;
+
+    let source_math = r#"
+    #include "./utils.huff"
+
+    #define macro QUADRUPLE() = takes(1) returns (1) {
+        DOUBLE() DOUBLE()
+    }
+    "#;
+
+    let source_utils = r#"
+    #define constant ONE = 0x01
+
+    #define macro DOUBLE() = takes(1) returns (1) {
+        dup1 add
+    }
+
+    #define macro ADD_ONE() = takes(1) returns (1) {
+        [ONE] add
+    }
+    "#;
+
+    let main_file_name = String::from("contracts/main.huff");
+    let compile = |source_math: &str| {
+        let file_sources = HashMap::from([
+            (main_file_name.clone(), String::from(source_main)),
+            (String::from("contracts/math.huff"), String::from(source_math)),
+            (String::from("contracts/utils.huff"), String::from(source_utils)),
+        ]);
+        let evm_version = EVMVersion::default();
+        let compiler = Compiler::new_in_memory(
+            &evm_version,
+            Arc::new(vec![main_file_name.clone()]),
+            file_sources,
+            None,
+            None,
+            None,
+            None,
+            false,
+        );
+        let artifacts = compiler.execute().unwrap();
+        artifacts.iter().find(|a| main_file_name.eq(&a.file.path)).unwrap().bytecode.clone()
+    };
+
+    // The full import keeps the definitions the selective import doesn't list
+    let bytecode = "60088060093d393df35f60010180018001";
+    assert_eq!(compile(source_math), bytecode);
+
+    // The selections of both imports are kept
+    let selective_math = source_math.replace("\"./utils.huff\"", "\"./utils.huff\" { DOUBLE }");
+    assert_eq!(compile(&selective_math), bytecode);
+}
+
+#[test]
+fn test_in_memory_compiler_namespaced_import() {
+    let source_main = r#"
+    #include "./math.huff" as Math
+
+    #define constant ONE = 0x02
+
+    #define macro INCREMENT() = takes(1) returns(1) {
+        [ONE] add
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        [Math.ONE] INCREMENT() Math.INCREMENT()
+    }
+    "#;
+
+    let source_math = r#"
+    #define constant ONE = 0x01
+    #define constant TWO = add([ONE], [ONE])
+
+    #define macro ADD(value) = takes(1) returns (1) {
+        <value> add
+    }
+
+    #define macro INCREMENT() = takes(1) returns (1) {
+        ADD(ONE) [TWO] add
+    }
+    "#;
+
+    let main_file_name = String::from("contracts/main.huff");
+
+    let mut file_sources = HashMap::new();
+    file_sources.insert(main_file_name.clone(), String::from(source_main));
+    file_sources.insert(String::from("contracts/math.huff"), String::from(source_math));
+
+    // Instantiate a new compiler
+    let evm_version = EVMVersion::default();
+    let compiler = Compiler::new_in_memory(
+        &evm_version,
+        Arc::new(vec![main_file_name.clone()]),
+        file_sources,
+        None,
+        None,
+        None,
+        None,
+        false,
+    );
+
+    let artifacts = compiler.execute().unwrap();
+    let artifact = artifacts.iter().find(|a| main_file_name.eq(&a.file.path)).unwrap();
+    assert_eq!(artifact.bytecode, "600b8060093d393df36001600201600101600201".to_string());
+}
+
+#[test]
+fn test_in_memory_compiler_with_remappings_file() {
+    let source_main = r#"
+    #include "@mint/mint.huff"
+
+    #define macro MAIN() = takes(0) returns (0) {
+        MINT()
+    }
+    "#;
+
+    let source_mint = r#"
+    #define macro MINT() = takes(0) returns (0) {
+        0x04 calldataload
+    }
+    "#;
+
+    let main_file_name = String::from("contracts/main.huff");
+
+    let mut file_sources = HashMap::new();
+    file_sources.insert(main_file_name.clone(), String::from(source_main));
+    file_sources.insert(String::from("lib/mint/src/mint.huff"), String::from(source_mint));
+    file_sources.insert(String::from("./remappings.txt"), String::from("@mint/=lib/mint/src/\n"));
+
+    // Instantiate a new compiler
+    let evm_version = EVMVersion::default();
+    let compiler = Compiler::new_in_memory(
+        &evm_version,
+        Arc::new(vec![main_file_name.clone()]),
+        file_sources,
+        None,
+        None,
+        None,
+        None,
+        false,
+    );
+
+    let artifacts = compiler.execute().unwrap();
+    let artifact = artifacts.iter().find(|a| main_file_name.eq(&a.file.path)).unwrap();
+
+    assert_eq!(artifact.bytecode, "60038060093d393df3600435".to_string());
+}
+
+#[test]
+fn test_in_memory_compiler_conditional_compilation() {
+    let source = r#"
+    #ifdef TESTNET
+    #define constant FEE = 0x01
+    #else
+    #define constant FEE = 0x02
+    #endif
+
+    #define macro MAIN() = takes(0) returns (0) {
+        [FEE]
+        #ifndef TESTNET
+        caller sstore
+        #endif
+    }
+    "#;
+
+    let main_file_name = String::from("main.huff");
+    let mut file_sources = HashMap::new();
+    file_sources.insert(main_file_name.clone(), String::from(source));
+
+    let compile = |defines: Vec<String>| {
+        let evm_version = EVMVersion::default();
+        let mut compiler = Compiler::new_in_memory(
+            &evm_version,
+            Arc::new(vec![main_file_name.clone()]),
+            file_sources.clone(),
+            None,
+            None,
+            None,
+            None,
+            false,
+        );
+        compiler.defines = defines;
+        let artifacts = compiler.execute().unwrap();
+        artifacts.iter().find(|a| main_file_name.eq(&a.file.path)).unwrap().bytecode.clone()
+    };
+
+    assert_eq!(compile(vec![]), "60048060093d393df360023355");
+    assert_eq!(compile(vec![String::from("TESTNET")]), "60028060093d393df36001");
+}
+
+#[test]
+fn test_in_memory_compiler_constant_overrides() {
+    let source = r#"
+    #define constant FEE = 0x01
+    #define constant OWNER = FREE_STORAGE_POINTER()
+
+    #define macro MAIN() = takes(0) returns (0) {
+        [FEE] [OWNER] sstore
+    }
+    "#;
+
+    let main_file_name = String::from("main.huff");
+    let mut file_sources = HashMap::new();
+    file_sources.insert(main_file_name.clone(), String::from(source));
+
+    let overrides = ["FEE=0x64", "OWNER=0x0100"]
+        .iter()
+        .map(|arg| ConstantDefinition::parse_override(arg).unwrap())
+        .collect::<BTreeMap<_, _>>();
+
+    let evm_version = EVMVersion::default();
+    let compiler = Compiler::new_in_memory(
+        &evm_version,
+        Arc::new(vec![main_file_name.clone()]),
+        file_sources,
+        None,
+        None,
+        None,
+        Some(overrides),
+        false,
+    );
+
+    let artifacts = compiler.execute().unwrap();
+    let artifact = artifacts.iter().find(|a| main_file_name.eq(&a.file.path)).unwrap();
+    assert_eq!(artifact.bytecode, "60068060093d393df3606461010055");
+}
+
+#[test]
+fn test_in_memory_compiler_env_constants() {
+    let source = r#"
+    #define constant DEPLOYER = env("HUFF_IN_MEMORY_TEST_DEPLOYER")
+
+    #define macro MAIN() = takes(0) returns (0) {
+        [DEPLOYER]
+    }
+    "#;
+
+    let main_file_name = String::from("main.huff");
+    let mut file_sources = HashMap::new();
+    file_sources.insert(main_file_name.clone(), String::from(source));
+
+    let compile = |env: BTreeMap<String, String>| {
+        let evm_version = EVMVersion::default();
+        let mut compiler = Compiler::new_in_memory(
+            &evm_version,
+            Arc::new(vec![main_file_name.clone()]),
+            file_sources.clone(),
+            None,
+            None,
+            None,
+            None,
+            false,
+        );
+        compiler.env = Some(env);
+        compiler.execute()
+    };
+
+    match compile(BTreeMap::new()).unwrap_err().as_ref() {
+        CompilerError::FailedCompiles(errors) => assert!(errors.iter().any(|e| matches!(
+            e,
+            CompilerError::CodegenError(ce)
+                if ce.kind == CodegenErrorKind::MissingEnvironmentVariable(
+                    "HUFF_IN_MEMORY_TEST_DEPLOYER".to_string()
+                )
+        ))),
+        e => panic!("Unexpected error: {e:?}"),
+    }
+
+    let env = BTreeMap::from([("HUFF_IN_MEMORY_TEST_DEPLOYER".to_string(), "0xa57b".to_string())]);
+    let artifacts = compile(env).unwrap();
+    let artifact = artifacts.iter().find(|a| main_file_name.eq(&a.file.path)).unwrap();
+    assert_eq!(artifact.bytecode, "60038060093d393df361a57b");
+}
+
+#[test]
+fn test_in_memory_compiler_typed_constructor_args() {
+    let source = r#"
+    #define function constructor(bytes4, uint256) nonpayable returns ()
+
+    #define macro CONSTRUCTOR() = takes(0) returns (0) {}
+    #define macro MAIN() = takes(0) returns (0) {}
+    "#;
+
+    let main_file_name = String::from("main.huff");
+    let mut file_sources = HashMap::new();
+    file_sources.insert(main_file_name.clone(), String::from(source));
+
+    let evm_version = EVMVersion::default();
+    let compiler = Compiler::new_in_memory(
+        &evm_version,
+        Arc::new(vec![main_file_name.clone()]),
+        file_sources,
+        None,
+        None,
+        Some(vec!["0xa9059cbb".to_string(), "0x64".to_string()]),
+        None,
+        false,
+    );
+
+    let artifacts = compiler.execute().unwrap();
+    let artifact = artifacts.iter().find(|a| main_file_name.eq(&a.file.path)).unwrap();
+
+    // The bytes4 argument is left aligned and the uint256 argument right aligned
+    assert_eq!(
+        artifact.bytecode,
+        format!("60008060093d393df3a9059cbb{}{:064x}", "00".repeat(28), 100)
+    );
+}
+
+#[test]
+fn test_in_memory_compiler_storage_slot_collision() {
+    let source_a = r#"
+    #include "./b.huff"
+
+    #define constant OWNER_SLOT = STORAGE_SLOT(0x00)
+
+    #define macro MAIN() = takes(0) returns (0) {
+        [OWNER_SLOT] sload
+        B()
+    }
+    "#;
+
+    let source_b = r#"
+    #define constant BALANCES_SLOT = STORAGE_SLOT(0x00)
+
+    #define macro B() = takes(0) returns (0) {
+        [BALANCES_SLOT] sload
+    }
+    "#;
+
+    let mut file_sources = HashMap::new();
+    file_sources.insert(String::from("contracts/a.huff"), String::from(source_a));
+    file_sources.insert(String::from("contracts/b.huff"), String::from(source_b));
+
+    // Instantiate a new compiler
+    let evm_version = EVMVersion::default();
+    let compiler = Compiler::new_in_memory(
+        &evm_version,
+        Arc::new(vec![String::from("contracts/a.huff")]),
+        file_sources,
+        None,
+        None,
+        None,
+        None,
+        false,
+    );
+
+    match compiler.execute().unwrap_err().as_ref() {
+        CompilerError::FailedCompiles(errors) => assert!(errors.iter().any(|e| matches!(
+            e,
+            CompilerError::CodegenError(ce)
+                if ce.kind == CodegenErrorKind::StorageSlotCollision(
+                    "OWNER_SLOT".to_string(),
+                    "BALANCES_SLOT".to_string()
+                )
+        ))),
+        e => panic!("Unexpected error: {e:?}"),
+    }
+}
+
+#[test]
+fn test_in_memory_compiler_duplicate_definitions() {
+    let source_main = r#"
+    #include "./utils.huff"
+    #include "./math.huff"
+
+    #define function transfer(address,uint256) nonpayable returns ()
+    #define function transfer(address) nonpayable returns ()
+
+    #define macro MAIN() = takes(0) returns (0) {
+        HELPER()
+    }
+    "#;
+
+    let source_utils = r#"
+    #define macro HELPER() = takes(0) returns (0) {
+        0x01
+    }
+    "#;
+
+    let source_math = r#"
+    #define macro HELPER() = takes(0) returns (0) {
+        0x02
+    }
+    "#;
+
+    let mut file_sources = HashMap::new();
+    file_sources.insert(String::from("contracts/main.huff"), String::from(source_main));
+    file_sources.insert(String::from("contracts/utils.huff"), String::from(source_utils));
+    file_sources.insert(String::from("contracts/math.huff"), String::from(source_math));
+
+    // Instantiate a new compiler
+    let evm_version = EVMVersion::default();
+    let compiler = Compiler::new_in_memory(
+        &evm_version,
+        Arc::new(vec![String::from("contracts/main.huff")]),
+        file_sources,
+        None,
+        None,
+        None,
+        None,
+        false,
+    );
+
+    // Overloaded functions are fine, but both included files define `HELPER`
+    match compiler.execute().unwrap_err().as_ref() {
+        CompilerError::FailedCompiles(errors) => match &errors[..] {
+            [CompilerError::ParserError(pe)] => {
+                assert_eq!(pe.kind, ParserErrorKind::DuplicateDefinition("HELPER".to_string()));
+                let locations = pe
+                    .spans
+                    .0
+                    .iter()
+                    .map(|s| (s.file.as_ref().unwrap().path.as_str(), s.start))
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    locations,
+                    vec![("contracts/utils.huff", 5), ("contracts/math.huff", 5)]
+                );
+            }
+            e => panic!("Expected a duplicate definition error, got: {e:?}"),
+        },
+        e => panic!("Expected a duplicate definition error, got: {e:?}"),
+    }
+}
+
+#[test]
+fn test_in_memory_compiler_shadowed_definitions() {
+    let source_main = r#"
+    #include "./utils.huff"
+
+    #define constant VALUE = 0x01
+
+    #define macro TRANSFER() = takes(0) returns (0) {
+        [VALUE]
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        TRANSFER()
+    }
+    "#;
+
+    let source_utils = r#"
+    #define constant VALUE = 0x02
+
+    #define macro TRANSFER() = takes(0) returns (0) {
+        [VALUE] [VALUE]
+    }
+    "#;
+
+    let mut file_sources = HashMap::new();
+    file_sources.insert(String::from("contracts/main.huff"), String::from(source_main));
+    file_sources.insert(String::from("contracts/utils.huff"), String::from(source_utils));
+
+    // Instantiate a new compiler
+    let evm_version = EVMVersion::default();
+    let compiler = Compiler::new_in_memory(
+        &evm_version,
+        Arc::new(vec![String::from("contracts/main.huff")]),
+        file_sources,
+        None,
+        None,
+        None,
+        None,
+        false,
+    );
+
+    // The definitions of the including file win
+    let artifacts = compiler.execute().unwrap();
+    assert_eq!(artifacts[0].runtime, "6001");
+    let warnings = artifacts[0]
+        .warnings
+        .iter()
+        .filter(|w| matches!(w.kind, WarningKind::ShadowedDefinition(..)))
+        .map(|w| {
+            let locations = w
+                .span
+                .0
+                .iter()
+                .map(|s| (s.file.as_ref().unwrap().path.clone(), s.start))
+                .collect::<Vec<_>>();
+            (w.kind.clone(), locations)
+        })
+        .collect::<Vec<_>>();
+    let shadowed = |name: &str| {
+        WarningKind::ShadowedDefinition(
+            name.to_string(),
+            "contracts/utils.huff".to_string(),
+            "contracts/main.huff".to_string(),
+        )
+    };
+    assert_eq!(
+        warnings,
+        vec![
+            (
+                shadowed("TRANSFER"),
+                vec![
+                    (
+                        "contracts/main.huff".to_string(),
+                        source_main.find("#define macro TRANSFER").unwrap()
+                    ),
+                    (
+                        "contracts/utils.huff".to_string(),
+                        source_utils.find("#define macro TRANSFER").unwrap()
+                    ),
+                ]
+            ),
+            (
+                shadowed("VALUE"),
+                vec![
+                    (
+                        "contracts/main.huff".to_string(),
+                        source_main.find("#define constant").unwrap()
+                    ),
+                    (
+                        "contracts/utils.huff".to_string(),
+                        source_utils.find("#define constant").unwrap()
+                    ),
+                ]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_in_memory_compiler_reports_every_parser_error() {
+    let source = r#"
+    #define function f() internal returns ()
+
+    #define macro MAIN() = takes(0) returns (0) {
+        0x01
+    }
+
+    #define foo
+    "#;
+    let file_sources = HashMap::from([("contracts/main.huff".to_string(), source.to_string())]);
+    let evm_version = EVMVersion::default();
+    let compiler = Compiler::new_in_memory(
+        &evm_version,
+        Arc::new(vec!["contracts/main.huff".to_string()]),
+        file_sources,
+        None,
+        None,
+        None,
+        None,
+        false,
+    );
+
+    let CompilerError::FailedCompiles(errors) = &*compiler.execute().unwrap_err() else {
+        panic!("Expected failed compiles")
+    };
+    let [CompilerError::FailedCompiles(errors)] = &errors[..] else {
+        panic!("Expected the parser errors of the file, got: {errors:?}")
+    };
+    let errors = errors
+        .iter()
+        .map(|e| match e {
+            CompilerError::ParserError(pe) => {
+                let span = &pe.spans.0[0];
+                (pe.kind.clone(), span.file.as_ref().unwrap().path.clone(), span.start)
+            }
+            e => panic!("Expected a parser error, got: {e:?}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        errors,
+        vec![
+            (
+                ParserErrorKind::UnexpectedType(TokenKind::Ident("internal".to_string())),
+                "contracts/main.huff".to_string(),
+                source.find("internal").unwrap(),
+            ),
+            (
+                ParserErrorKind::InvalidDefinition(TokenKind::Ident("foo".to_string())),
+                "contracts/main.huff".to_string(),
+                source.find("foo").unwrap(),
+            ),
+        ]
+    );
+}
@@ -0,0 +1,167 @@
+use huff_codegen::*;
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"
+    #define jumptable__packed TABLE {
+        success fail
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        __tablestart(TABLE) 0x00 mstore
+        0x00 calldataload success jumpi
+        fail:
+            0x00 0x00 revert
+        success:
+            stop
+    }
+"#;
+
+fn parse(source: &str) -> Contract {
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+    contract
+}
+
+#[test]
+fn test_lowers_symbolic_references() {
+    let contract = parse(SOURCE);
+    let (bytecode, res) = Codegen::generate_main_bytecode_with_res(
+        &EVMVersion::default(),
+        &contract,
+        None,
+        &CodegenSettings::default(),
+    )
+    .unwrap();
+    let program = Codegen::lower(&res).unwrap();
+
+    let types = program.instructions.iter().map(|i| i.ty.clone()).collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        vec![
+            IRInstructionType::Push(2, IRValue::TableStart("TABLE".to_string())),
+            IRInstructionType::Opcode(Opcode::Push0),
+            IRInstructionType::Opcode(Opcode::Mstore),
+            IRInstructionType::Opcode(Opcode::Push0),
+            IRInstructionType::Opcode(Opcode::Calldataload),
+            IRInstructionType::Push(2, IRValue::Label("success".to_string())),
+            IRInstructionType::Opcode(Opcode::Jumpi),
+            IRInstructionType::Label("fail".to_string()),
+            IRInstructionType::Opcode(Opcode::Jumpdest),
+            IRInstructionType::Opcode(Opcode::Push0),
+            IRInstructionType::Opcode(Opcode::Push0),
+            IRInstructionType::Opcode(Opcode::Revert),
+            IRInstructionType::Label("success".to_string()),
+            IRInstructionType::Opcode(Opcode::Jumpdest),
+            IRInstructionType::Opcode(Opcode::Stop),
+        ]
+    );
+    assert_eq!(
+        program.tables[0].entries,
+        vec![IRTableEntry::Label("success".to_string()), IRTableEntry::Label("fail".to_string())]
+    );
+    assert_eq!(program.tables[0].size, 4);
+
+    // The instructions keep the spans of their statements
+    assert!(program.instructions.iter().all(|i| !i.span.0.is_empty()));
+
+    // Emitting the IR gives back the bytecode
+    assert_eq!(Codegen::emit(&program).unwrap(), bytecode);
+}
+
+#[test]
+fn test_emits_relocated_labels() {
+    let contract = parse(SOURCE);
+    let (_, res) = Codegen::generate_main_bytecode_with_res(
+        &EVMVersion::default(),
+        &contract,
+        None,
+        &CodegenSettings::default(),
+    )
+    .unwrap();
+    let mut program = Codegen::lower(&res).unwrap();
+
+    // Inserting an instruction moves the labels and tables following it
+    program.instructions.insert(
+        0,
+        IRInstruction {
+            ty: IRInstructionType::Opcode(Opcode::Callvalue),
+            span: AstSpan(vec![]),
+            origin: None,
+        },
+    );
+    assert_eq!(program.label_indices().get("success"), Some(&0x10));
+    assert_eq!(Codegen::emit(&program).unwrap(), "346100125f525f35610010575b5f5ffd5b000010000c");
+}
+
+#[test]
+fn test_generates_ir_from_expanded_statements() {
+    let source = r#"
+        #define constant OWNER = 0x0101
+        #define macro CHECK() = takes(0) returns(0) {
+            [OWNER] done jumpi
+            0x00 0x00 revert
+            done:
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            CHECK() CHECK()
+        }
+    "#;
+    let contract = parse(source);
+    let (bytecode, res) = Codegen::generate_main_bytecode_with_res(
+        &EVMVersion::default(),
+        &contract,
+        None,
+        &CodegenSettings::default(),
+    )
+    .unwrap();
+
+    // Constants are pushed by their name, with their value carried by the IR
+    assert_eq!(
+        res.instructions[0].ty,
+        IRInstructionType::Push(2, IRValue::Constant("OWNER".to_string()))
+    );
+    assert_eq!(res.constants.get("OWNER"), Some(&"0101".to_string()));
+
+    // The labels of both expansions are distinct, and each jump goes to its own
+    let labels = res
+        .instructions
+        .iter()
+        .filter_map(|i| match &i.ty {
+            IRInstructionType::Label(name) => Some(name.clone()),
+            _ => None,
+        })
+        .collect::<Vec<String>>();
+    assert_eq!(labels, vec!["done".to_string(), "done@0015".to_string()]);
+    assert_eq!(bytecode, "61010161000a575f5ffd5b610101610015575f5ffd5b");
+}
+
+#[test]
+fn test_lays_out_tables_after_offset_code() {
+    let contract = parse(SOURCE);
+    let main = contract.find_macro_by_name("MAIN").unwrap();
+    let res = Codegen::macro_to_bytecode(
+        &EVMVersion::default(),
+        main,
+        &contract,
+        &CodegenSettings::default(),
+        &mut vec![&main],
+        5,
+        &mut Vec::default(),
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+    let program = Codegen::lower(&res).unwrap();
+
+    // Tables follow the code, wherever the code starts
+    assert_eq!(program.offset, 5);
+    assert_eq!(program.table_offsets().get("TABLE"), Some(&(5 + program.code_size())));
+}
@@ -0,0 +1,99 @@
+use huff_codegen::*;
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+fn main_bytecode(source: &str) -> Result<String, CodegenError> {
+    let lexer = Lexer::new(source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
+    let contract = parser.parse().unwrap();
+    Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None)
+}
+
+#[test]
+fn test_label_arg_resolves_to_caller() {
+    let source = r#"
+    #[unique_labels]
+    #define macro CHECK(target) = takes(1) returns (0) {
+        done jumpi
+        <target> jump
+        done:
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        0x01 CHECK(done)
+        0x02
+        done:
+    }
+    "#;
+
+    assert_eq!(main_bytecode(source).unwrap(), "600161000a5761000d565b60025b");
+}
+
+#[test]
+fn test_label_arg_shadowed_by_callee() {
+    // The callee's own `done` label must not silently capture the caller's label argument
+    let source = r#"
+    #define macro CHECK(target) = takes(1) returns (0) {
+        done jumpi
+        <target> jump
+        done:
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        0x01 CHECK(done)
+        0x02
+        done:
+    }
+    "#;
+
+    match main_bytecode(source) {
+        Err(e) => assert_eq!(e.kind, CodegenErrorKind::DuplicateLabel("done".to_string())),
+        Ok(b) => panic!("Expected a duplicate label error, got {b}"),
+    }
+}
+
+#[test]
+fn test_label_arg_through_nested_macros() {
+    let source = r#"
+    #define macro INNER(target) = takes(0) returns (0) {
+        <target> jump
+    }
+
+    #define macro OUTER(target) = takes(0) returns (0) {
+        INNER(<target>)
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        OUTER(fail)
+        fail:
+    }
+    "#;
+
+    assert_eq!(main_bytecode(source).unwrap(), "610004565b");
+}
+
+#[test]
+fn test_undefined_label_arg() {
+    // Unique labels of the callee are out of reach of the caller's arguments
+    let source = r#"
+    #[unique_labels]
+    #define macro CHECK(target) = takes(0) returns (0) {
+        <target> jump
+        fail:
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        CHECK(fail)
+    }
+    "#;
+
+    match main_bytecode(source) {
+        Err(e) => {
+            assert_eq!(e.kind, CodegenErrorKind::UndefinedLabelArgument("fail".to_string()));
+            assert_eq!(e.span.0[0].start, source.find("CHECK(fail)").unwrap());
+        }
+        Ok(b) => panic!("Expected an undefined label argument error, got {b}"),
+    }
+}
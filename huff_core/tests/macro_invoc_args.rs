@@ -21,7 +21,7 @@ fn test_opcode_macro_args() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
@@ -59,7 +59,7 @@ fn test_all_opcodes_in_macro_args() {
         let flattened_source = FullFileSource { source: &source, file: None, spans: vec![] };
         let lexer = Lexer::new(flattened_source.source);
         let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-        let mut parser = Parser::new(tokens, None);
+        let mut parser = Parser::new(tokens, None).unwrap();
         let mut contract = parser.parse().unwrap();
         contract.derive_storage_pointers();
 
@@ -97,7 +97,7 @@ fn test_constant_macro_arg() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
@@ -137,7 +137,7 @@ fn test_bubbled_label_call_macro_arg() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
@@ -176,7 +176,7 @@ fn test_bubbled_literal_macro_arg() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
@@ -215,7 +215,7 @@ fn test_bubbled_opcode_macro_arg() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
@@ -256,7 +256,7 @@ fn test_bubbled_constant_macro_arg() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
@@ -293,7 +293,7 @@ fn test_bubbled_arg_with_different_name() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
@@ -308,3 +308,81 @@ fn test_bubbled_arg_with_different_name() {
     // Check the bytecode
     assert_eq!(main_bytecode, expected_bytecode);
 }
+
+#[test]
+fn test_nested_macro_invocation_args() {
+    let source = r#"
+        #define constant SLOT = FREE_STORAGE_POINTER()
+
+        #define macro INNER(value) = takes(0) returns(1) {
+            <value> [SLOT] add
+        }
+
+        #define macro OUTER(load) = takes(0) returns(0) {
+            <load> sload
+            <load> 0x00 mstore
+        }
+
+        #define macro WRAPPER(inner) = takes(0) returns(0) {
+            OUTER(<inner>)
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            OUTER(INNER(0x01))
+            WRAPPER(INNER(callvalue))
+        }
+    "#;
+
+    // Lex + Parse
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    let evm_version = EVMVersion::default();
+
+    // Create main bytecode
+    let main_bytecode = Codegen::generate_main_bytecode(&evm_version, &contract, None).unwrap();
+
+    // INNER(0x01) is expanded at each use of <load>, and INNER(callvalue) is bubbled through
+    // WRAPPER before being expanded within OUTER
+    let expected_bytecode = "60015f015460015f015f52345f0154345f015f52";
+    assert_eq!(main_bytecode, expected_bytecode);
+}
+
+#[test]
+fn test_opcode_macro_arg_substitution() {
+    let source = r#"
+        #define macro BINOP(op) = takes(2) returns(1) {
+            <op>
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            0x01 0x02 BINOP(add)
+            0x03 BINOP(mul)
+            0x00 BINOP(mstore)
+        }
+    "#;
+
+    // Lex + Parse
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    // The opcodes are substituted into the invoked macro
+    match &contract.macros[1].statements[2].ty {
+        StatementType::MacroInvocation(mi) => {
+            assert_eq!(mi.args, vec![MacroArg::Opcode(Opcode::Add)])
+        }
+        ty => panic!("Expected a macro invocation, got: {ty}"),
+    }
+
+    let main_bytecode =
+        Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(main_bytecode, "60016002016003025f52");
+}
@@ -0,0 +1,63 @@
+use huff_codegen::{Codegen, CodegenSettings};
+use huff_lexer::Lexer;
+use huff_parser::Parser;
+use huff_utils::prelude::*;
+
+fn generate(source: &str) -> (String, BytecodeRes) {
+    let tokens = Lexer::new(source).collect::<Result<Vec<Token>, _>>().unwrap();
+    let contract = Parser::new(tokens, None).unwrap().parse().unwrap();
+    let evm_version = EVMVersion::default();
+    Codegen::generate_main_bytecode_with_res(
+        &evm_version,
+        &contract,
+        None,
+        &CodegenSettings::default(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_reused_expansions_jump_within_their_invocation() {
+    let source = r#"
+    #define macro REQUIRE() = takes(1) returns(0) {
+        ok jumpi
+        0x00 0x00 revert
+        ok:
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x04 calldataload REQUIRE()
+        0x24 calldataload REQUIRE()
+        0x44 calldataload REQUIRE()
+    }
+    "#;
+    let (bytecode, res) = generate(source);
+
+    assert_eq!(bytecode, "60043561000a575f5ffd5b602435610015575f5ffd5b604435610020575f5ffd5b");
+    let starts = res
+        .macro_ranges
+        .iter()
+        .filter(|r| r.name == "REQUIRE")
+        .map(|r| (r.start, r.end, r.labels["ok"]))
+        .collect::<Vec<_>>();
+    assert_eq!(starts, vec![(3, 11, 10), (14, 22, 21), (25, 33, 32)]);
+    assert_eq!(res.label_references, vec![3, 14, 25]);
+}
+
+#[test]
+fn test_reused_expansions_resolve_label_arguments() {
+    let source = r#"
+    #define macro JUMP_TO(dest) = takes(0) returns(0) {
+        <dest> jump
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        a: JUMP_TO(b)
+        b: JUMP_TO(a)
+        JUMP_TO(b)
+    }
+    "#;
+    let (bytecode, _) = generate(source);
+
+    assert_eq!(bytecode, "5b610005565b6100005661000556");
+}
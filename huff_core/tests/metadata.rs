@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use ethers_core::utils::{hex, keccak256};
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        0x01 0x00 mstore 0x20 0x00 return
+    }
+"#;
+
+fn compile(metadata_hash: Option<MetadataHash>) -> Artifact {
+    let file = Arc::new(FileSource {
+        source: Some(SOURCE.to_string()),
+        path: "Main.huff".to_string(),
+        ..Default::default()
+    });
+    let evm_version = EVMVersion::default();
+    let mut compiler =
+        Compiler::new(&evm_version, Arc::new(vec![]), None, None, None, None, None, false, false);
+    compiler.metadata_hash = metadata_hash;
+    compiler.gen_artifact(file).unwrap()
+}
+
+#[test]
+fn test_no_metadata_trailer_by_default() {
+    let artifact = compile(None);
+    assert_eq!(artifact.runtime, "60015f5260205ff3");
+    assert_eq!(artifact.metadata, None);
+}
+
+#[test]
+fn test_appends_metadata_trailer() {
+    let artifact = compile(Some(MetadataHash::Keccak));
+
+    let json = artifact.metadata.unwrap();
+    let metadata: Metadata = serde_json::from_str(&json).unwrap();
+    assert_eq!(metadata.compiler.name, "huffc");
+    assert_eq!(metadata.compiler.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(metadata.settings.evm_version, "shanghai");
+    assert_eq!(metadata.settings.metadata_hash, Some(MetadataHash::Keccak));
+    assert!(metadata.sources.contains_key("Main.huff"));
+
+    // The trailer is part of the deployed runtime code
+    let trailer = metadata.cbor_trailer(MetadataHash::Keccak);
+    assert!(trailer.contains(&hex::encode(keccak256(json))));
+    assert_eq!(artifact.runtime, format!("60015f5260205ff3{trailer}"));
+    assert!(artifact.bytecode.ends_with(&artifact.runtime));
+}
+
+#[test]
+fn test_artifacts_carry_the_compiler_fingerprint() {
+    let evm_version = EVMVersion::default();
+    let mut compiler =
+        Compiler::new(&evm_version, Arc::new(vec![]), None, None, None, None, None, false, false);
+    let artifact = compile(None);
+    assert_eq!(artifact.compiler, compiler.fingerprint());
+    assert_eq!(artifact.compiler.name, "huffc");
+    assert_eq!(artifact.compiler.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(artifact.compiler.settings_hash, compiler.settings().hash());
+
+    // Settings affecting the code change the fingerprint
+    compiler.optimize = true;
+    assert_ne!(artifact.compiler, compiler.fingerprint());
+    compiler.optimize = false;
+    compiler.defines = vec!["DEBUG".to_string()];
+    assert_ne!(artifact.compiler, compiler.fingerprint());
+}
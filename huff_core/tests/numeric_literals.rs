@@ -0,0 +1,107 @@
+use huff_utils::prelude::*;
+
+mod common;
+use common::compile;
+
+#[test]
+fn test_decimal_literals() {
+    let source = r#"
+    #define constant SUPPLY = 1000000
+    #define constant DOUBLE = mul([SUPPLY], 2)
+
+    #define macro PUSH(value) = takes(0) returns(1) {
+        <value>
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        [DOUBLE] 255 0 PUSH(16) __RIGHTPAD(1) push2 10
+    }
+
+    #assert [SUPPLY] == 0x0f4240
+    #assert __codesize(MAIN) <= 45
+    "#;
+
+    let artifact = compile(source).unwrap();
+    assert_eq!(artifact.runtime, format!("621e848060ff5f60107f01{}61000a", "00".repeat(31)));
+}
+
+#[test]
+fn test_literal_separators() {
+    let source = r#"
+    #define constant SUPPLY = 1_000_000
+    #define constant MASK = 0xffff_ffff
+
+    #define macro PUSH(value) = takes(0) returns(1) {
+        <value>
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        [SUPPLY] [MASK] 0x00_01 PUSH(1_0) PUSH(0xab_cd)
+    }
+    "#;
+
+    let artifact = compile(source).unwrap();
+    assert_eq!(artifact.runtime, "620f424063ffffffff6001600a61abcd");
+}
+
+#[test]
+fn test_literal_separators_in_builtin_args() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        __VERBATIM(0x6001_6001) __RIGHTPAD(0x12_34) __RIGHTPAD(0x00_01)
+    }
+    "#;
+
+    // Separators don't count towards the written width of the literal
+    let artifact = compile(source).unwrap();
+    let padding = "00".repeat(30);
+    assert_eq!(artifact.runtime, format!("600160017f1234{padding}7f0001{padding}"));
+}
+
+#[test]
+fn test_binary_literals() {
+    let source = r#"
+    #define constant MASK = 0b1111_0000
+
+    #define macro MAIN() = takes(0) returns(0) {
+        [MASK] 0b1 0b0 __RIGHTPAD(0b101)
+    }
+    "#;
+
+    let artifact = compile(source).unwrap();
+    assert_eq!(artifact.runtime, format!("60f060015f7f05{}", "00".repeat(31)));
+}
+
+#[test]
+fn test_ether_units() {
+    let source = r#"
+    #define constant PRICE = 1 ether
+    #define constant TIP = 2 gwei
+
+    #define macro MAIN() = takes(0) returns(0) {
+        [PRICE] [TIP] 1e3 callvalue
+    }
+    "#;
+
+    let artifact = compile(source).unwrap();
+    assert_eq!(artifact.runtime, "670de0b6b3a764000063773594006103e834");
+}
+
+#[test]
+fn test_negative_literals() {
+    let source = r#"
+    #define constant OFFSET = -0x20
+
+    #define macro ADD(value) = takes(1) returns(1) {
+        <value> add
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x40 [OFFSET] add -1 ADD(-2)
+    }
+    "#;
+
+    let max = "ff".repeat(31);
+    let artifact = compile(source).unwrap();
+    assert_eq!(artifact.runtime, format!("60407f{max}e0017f{max}ff7f{max}fe01"));
+}
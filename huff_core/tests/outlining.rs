@@ -0,0 +1,55 @@
+use huff_utils::prelude::*;
+
+mod common;
+use common::{compile, compile_with};
+
+const SOURCE: &str = r#"
+    #define macro HASH() = takes(1) returns(1) {
+        0x00 mstore 0x20 0x00 sha3
+        0x01 add 0x02 mul 0x03 xor 0x04 or
+    }
+
+    #define macro CHECK() = takes(1) returns(0) {
+        ok jumpi
+        0x00 0x00 revert
+        ok:
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x04 calldataload HASH()
+        0x24 calldataload HASH()
+        0x44 calldataload HASH()
+        add add
+        0x00 mstore 0x20 0x00 return
+    }
+"#;
+
+#[test]
+fn test_outlines_repeated_invocations() {
+    let artifact = compile_with(SOURCE, |compiler| compiler.outline = true).unwrap();
+
+    // Each invocation jumps to the subroutine after the code, which swaps the return address
+    // below the item it takes and back above the item it returns
+    let hash = "5f5260205f20600101600202600318600417";
+    assert_eq!(
+        artifact.runtime,
+        format!(
+            "60043561000a610029565b602435610015610029565b604435610020610029565b\
+             01015f5260205ff35b90{hash}9056"
+        )
+    );
+}
+
+#[test]
+fn test_outlining_is_opt_in() {
+    let artifact = compile(SOURCE).unwrap();
+    let hash = "5f5260205f20600101600202600318600417";
+    assert_eq!(artifact.runtime, format!("600435{hash}602435{hash}604435{hash}01015f5260205ff3"));
+}
+
+#[test]
+fn test_keeps_macros_with_labels_inlined() {
+    let source = SOURCE.replace("0x04 calldataload HASH()", "CHECK() CHECK() CHECK() CHECK()");
+    let outlined = compile_with(&source, |compiler| compiler.outline = true).unwrap();
+    assert_eq!(outlined.runtime, compile(&source).unwrap().runtime);
+}
@@ -30,7 +30,7 @@ fn test_invalid_macro_statement() {
     let full_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(full_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
 
     // This should be caught before codegen invalid macro statement
     match parser.parse() {
@@ -55,7 +55,7 @@ fn test_unexpected_type() {
     let full_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(full_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
 
     match parser.parse() {
         Ok(_) => panic!("moose"),
@@ -85,7 +85,7 @@ fn test_invalid_definition() {
     let full_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(full_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
 
     match parser.parse() {
         Ok(_) => panic!("moose"),
@@ -95,7 +95,7 @@ fn test_invalid_definition() {
                 ParserError {
                     kind: ParserErrorKind::InvalidDefinition(TokenKind::Ident("invalid".to_string())),
                     hint: Some(
-                        "Definition must be one of: `function`, `event`, `constant`, `error`, `macro`, `fn`, or `test`."
+                        "Definition must be one of: `function`, `event`, `constant`, `immutable`, `error`, `macro`, `fn`, or `test`."
                             .to_string()
                     ),
                     spans: AstSpan(vec![Span {
@@ -112,10 +112,8 @@ fn test_invalid_definition() {
 #[test]
 fn test_invalid_constant_value() {
     let invalid_constant_values = vec![
-        ("ident", TokenKind::Ident("ident".to_string())),
         ("<", TokenKind::LeftAngle),
         ("{", TokenKind::OpenBrace),
-        ("[", TokenKind::OpenBracket),
         ("(", TokenKind::OpenParen),
         (":", TokenKind::Colon),
         (",", TokenKind::Comma),
@@ -129,7 +127,7 @@ fn test_invalid_constant_value() {
         let full_source = FullFileSource { source, file: None, spans: vec![] };
         let lexer = Lexer::new(full_source.source);
         let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-        let mut parser = Parser::new(tokens, Some("".to_string()));
+        let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
 
         match parser.parse() {
             Ok(_) => panic!("moose"),
@@ -139,7 +137,7 @@ fn test_invalid_constant_value() {
                     ParserError {
                         kind: ParserErrorKind::InvalidConstantValue(kind),
                         hint: Some(
-                            "Expected constant value to be a literal or `FREE_STORAGE_POINTER()`"
+                            "Expected constant value to be a literal, an expression, `env(\"VAR\")`, `STORAGE_SLOT(n)` or `FREE_STORAGE_POINTER()`"
                                 .to_string()
                         ),
                         spans: AstSpan(vec![Span {
@@ -176,7 +174,7 @@ fn test_invalid_token_in_macro_body() {
         let full_source = FullFileSource { source, file: None, spans: vec![] };
         let lexer = Lexer::new(full_source.source);
         let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-        let mut parser = Parser::new(tokens, Some("".to_string()));
+        let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
 
         match parser.parse() {
             Ok(_) => panic!("moose"),
@@ -221,7 +219,7 @@ fn test_invalid_token_in_label_definition() {
         let full_source = FullFileSource { source, file: None, spans: vec![] };
         let lexer = Lexer::new(full_source.source);
         let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-        let mut parser = Parser::new(tokens, Some("".to_string()));
+        let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
 
         match parser.parse() {
             Ok(_) => panic!("moose"),
@@ -266,7 +264,7 @@ fn test_invalid_single_arg() {
                 }
             })
             .collect::<Vec<Token>>();
-        let mut parser = Parser::new(tokens, Some("".to_string()));
+        let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
 
         match parser.parse() {
             Ok(_) => panic!("moose"),
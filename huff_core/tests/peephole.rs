@@ -0,0 +1,143 @@
+use std::sync::Arc;
+
+use huff_codegen::{
+    passes::{PassContext, Pipeline},
+    Codegen, CodegenSettings,
+};
+use huff_core::Compiler;
+use huff_lexer::Lexer;
+use huff_parser::Parser;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"
+    #define macro CHECK() = takes(1) returns(1) {
+        iszero iszero iszero
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x04 calldataload
+        0x01 pop
+        dup1 pop
+        swap1 swap1
+        CHECK()
+        success jumpi
+        0x00 0x00 revert
+        success:
+            stop
+    }
+"#;
+
+fn compile(optimize: bool) -> Artifact {
+    let file = Arc::new(FileSource {
+        source: Some(SOURCE.to_string()),
+        path: "Main.huff".to_string(),
+        ..Default::default()
+    });
+    let evm_version = EVMVersion::default();
+    let mut compiler =
+        Compiler::new(&evm_version, Arc::new(vec![]), None, None, None, None, None, false, false);
+    compiler.optimize = optimize;
+    compiler.gen_artifact(file).unwrap()
+}
+
+#[test]
+fn test_removes_redundant_sequences() {
+    let artifact = compile(true);
+    assert_eq!(artifact.runtime, "60043515600a575f5ffd5b00");
+}
+
+#[test]
+fn test_optimizer_is_opt_in() {
+    let artifact = compile(false);
+    assert_eq!(artifact.runtime, "60043560015080509090151515610014575f5ffd5b00");
+}
+
+#[test]
+fn test_relocates_debug_info() {
+    let artifact = compile(true);
+    let ranges = &artifact.debug.runtime;
+
+    // The macro and label ranges follow the optimized code
+    let check = ranges.iter().find(|r| r.macros.last().map(String::as_str) == Some("CHECK"));
+    assert_eq!(check.map(|r| (r.start, r.end)), Some((3, 4)));
+    let success = ranges.iter().find(|r| r.label.as_deref() == Some("success"));
+    assert_eq!(success.map(|r| (r.start, r.end)), Some((0x0a, 0x0c)));
+}
+
+#[test]
+fn test_keeps_source_spans() {
+    let tokens = Lexer::new(SOURCE).collect::<Result<Vec<Token>, _>>().unwrap();
+    let contract = Parser::new(tokens, None).unwrap().parse().unwrap();
+    let evm_version = EVMVersion::default();
+    let (bytecode, res) = Codegen::generate_main_bytecode_with_res(
+        &evm_version,
+        &contract,
+        None,
+        &CodegenSettings { pipeline: Pipeline::optimizer(), ..Default::default() },
+    )
+    .unwrap();
+
+    // The spans cover the optimized code only, the kept `iszero` with its own span
+    assert!(res.source_spans.iter().all(|s| s.end <= bytecode.len() / 2));
+    let iszero = res.source_spans.iter().find(|s| s.start == 3 && s.end == 4).unwrap();
+    assert_eq!(&SOURCE[iszero.span.0[0].start..=iszero.span.0[0].end], "iszero");
+
+    // Running the pass again leaves the code as is
+    let mut program = Codegen::lower(&res).unwrap();
+    let macro_def = contract.find_macro_by_name("MAIN").unwrap();
+    let cx = PassContext {
+        evm_version: &evm_version,
+        contract: &contract,
+        macro_name: &macro_def.name,
+        macro_ranges: &res.macro_ranges,
+    };
+    Pipeline::optimizer().run(&mut program, &cx).unwrap();
+    assert_eq!(Codegen::emit(&program).unwrap(), bytecode);
+}
+
+fn compile_source(source: &str) -> Result<Artifact, CompilerError> {
+    let file = Arc::new(FileSource {
+        source: Some(source.to_string()),
+        path: "Main.huff".to_string(),
+        ..Default::default()
+    });
+    let evm_version = EVMVersion::default();
+    let mut compiler =
+        Compiler::new(&evm_version, Arc::new(vec![]), None, None, None, None, None, false, false);
+    compiler.optimize = true;
+    compiler.gen_artifact(file)
+}
+
+#[test]
+fn test_codesize_of_optimized_code() {
+    let source = r#"
+        #define macro CONSTRUCTOR() = takes(0) returns(0) {
+            __codesize(MAIN) 0x00 sstore
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            0x01 pop 0x02 pop 0x03 pop 0x00 mstore
+        }
+    "#;
+    let artifact = compile_source(source).unwrap();
+
+    // `__codesize(MAIN)` pushes the size of the optimized runtime code
+    assert_eq!(artifact.runtime, "5f52");
+    assert!(artifact.bytecode.starts_with("60025f55"), "{}", artifact.bytecode);
+}
+
+#[test]
+fn test_rejects_circular_codesize_of_optimized_code() {
+    let source = r#"
+        #define macro CONSTRUCTOR() = takes(0) returns(0) {
+            0x01 pop __codesize(CONSTRUCTOR) 0x00 mstore
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            stop
+        }
+    "#;
+    let err = compile_source(source).unwrap_err();
+    let CompilerError::CodegenError(e) = err else { panic!("unexpected error: {err:?}") };
+    assert_eq!(e.kind, CodegenErrorKind::OptimizedCircularCodesize("CONSTRUCTOR".to_string()));
+}
@@ -17,7 +17,7 @@ fn test_gracefully_pads_push_override() {
     let flattened_source = FullFileSource { source: OVERRIDEN_PUSH, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Grab the first macro
     let mut contract = parser.parse().unwrap();
@@ -54,7 +54,7 @@ fn test_constructs_exact_push_override() {
     let flattened_source = FullFileSource { source: OVERRIDEN_PUSH, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Grab the first macro
     let mut contract = parser.parse().unwrap();
@@ -88,7 +88,7 @@ fn test_no_push0_override() {
     let flattened_source = FullFileSource { source: OVERRIDEN_PUSH, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Grab the first macro
     let mut contract = parser.parse().unwrap();
@@ -121,7 +121,7 @@ fn test_fails_on_push_underflow() {
     let flattened_source = FullFileSource { source: OVERRIDEN_PUSH, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     parser.parse().unwrap();
 }
 
@@ -144,7 +144,7 @@ fn test_literal_to_push0() {
     let flattened_source = FullFileSource { source: LITERAL_PUSH, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Grab the first macro
     let mut contract = parser.parse().unwrap();
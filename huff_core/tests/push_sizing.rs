@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use huff_codegen::{
+    passes::{PassContext, Pipeline, PushSizing},
+    Codegen, CodegenSettings,
+};
+use huff_core::Compiler;
+use huff_lexer::Lexer;
+use huff_parser::Parser;
+use huff_utils::prelude::*;
+
+#[test]
+fn test_pushes_small_offsets_with_push1() {
+    let source = r#"
+        #define jumptable__packed TABLE {
+            success
+        }
+
+        #define macro MAIN() = takes(0) returns(0) {
+            __tablestart(TABLE) pop
+            0x00 calldataload success jumpi
+            0x00 0x00 revert
+            success:
+                stop
+        }
+    "#;
+    let file = Arc::new(FileSource {
+        source: Some(source.to_string()),
+        path: "Main.huff".to_string(),
+        ..Default::default()
+    });
+    let evm_version = EVMVersion::default();
+    let mut compiler =
+        Compiler::new(&evm_version, Arc::new(vec![]), None, None, None, None, None, false, false);
+    compiler.pipeline.register(Arc::new(PushSizing));
+    let artifact = compiler.gen_artifact(file).unwrap();
+
+    assert_eq!(artifact.runtime, "600d505f35600b575f5ffd5b00000b");
+}
+
+#[test]
+fn test_relaxes_offsets_past_one_byte() {
+    // The label references following the filler are pushed past the first 256 bytes
+    let source = format!(
+        r#"
+        #define macro MAIN() = takes(0) returns(0) {{
+            near jump
+            {}
+            near:
+                far jump
+            far:
+                near jump
+        }}
+        "#,
+        format!("__VERBATIM(0x{}) ", "5b".repeat(31)).repeat(8) + "__VERBATIM(0x5b)"
+    );
+    let tokens = Lexer::new(&source).collect::<Result<Vec<Token>, _>>().unwrap();
+    let contract = Parser::new(tokens, None).unwrap().parse().unwrap();
+    let evm_version = EVMVersion::default();
+    let settings = CodegenSettings::default();
+    let (_, res) =
+        Codegen::generate_main_bytecode_with_res(&evm_version, &contract, None, &settings).unwrap();
+
+    let mut program = Codegen::lower(&res).unwrap();
+    let cx = PassContext {
+        evm_version: &evm_version,
+        contract: &contract,
+        macro_name: "MAIN",
+        macro_ranges: &res.macro_ranges,
+    };
+    let mut pipeline = Pipeline::new();
+    pipeline.register(Arc::new(PushSizing));
+    pipeline.run(&mut program, &cx).unwrap();
+
+    // `near` fits in a byte, `far` doesn't once the push in front of it is widened
+    let label_indices = program.label_indices();
+    assert_eq!(label_indices["near"], 0xfc);
+    assert_eq!(label_indices["far"], 0x101);
+    let widths = program
+        .instructions
+        .iter()
+        .filter_map(|i| match &i.ty {
+            IRInstructionType::Push(width, IRValue::Label(name)) => Some((name.as_str(), *width)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(widths, vec![("near", 1), ("far", 2), ("near", 1)]);
+
+    let bytecode = Codegen::emit(&program).unwrap();
+    assert!(bytecode.starts_with("60fc56"));
+    assert!(bytecode.ends_with("5b610101565b60fc56"));
+}
@@ -48,7 +48,7 @@ fn recurse_macro_bytecode() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
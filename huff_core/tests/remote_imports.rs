@@ -0,0 +1,67 @@
+use std::{fs, sync::Arc};
+
+use huff_core::Compiler;
+use huff_utils::{
+    file_provider::{FileSystemFileProvider, RemoteFileProvider},
+    prelude::*,
+    remote::{self, RemoteCache, RemoteImportError},
+};
+
+#[test]
+fn test_remote_imports_are_disabled_by_default() {
+    let url = "https://example.com/huff/mint.huff";
+    let source_main =
+        format!("#include \"{url}\"\n#define macro MAIN() = takes(0) returns (0) {{ MINT() }}");
+    let fs = Arc::new(FileSource {
+        path: "./main.huff".to_string(),
+        source: Some(source_main),
+        ..Default::default()
+    });
+
+    let res = Compiler::recurse_deps(fs, &Remapper::new("./"), Arc::new(FileSystemFileProvider {}));
+    assert_eq!(
+        *res.unwrap_err(),
+        CompilerError::RemoteImportError(RemoteImportError::Disabled(url.to_string()))
+    );
+}
+
+#[test]
+fn test_compile_with_cached_remote_import() {
+    let dir = std::env::temp_dir().join(format!("huff_remote_imports_{}", std::process::id()));
+    let cache_dir = dir.join("cache");
+    fs::create_dir_all(&cache_dir).unwrap();
+
+    // Pin and cache the remote file
+    let url = "https://example.com/huff/mint.huff";
+    let source_mint = "#define macro MINT() = takes(0) returns (0) { 0x04 calldataload }";
+    let hash = remote::content_hash(source_mint);
+    fs::write(cache_dir.join(&hash), source_mint).unwrap();
+    fs::write(dir.join("huff.lock"), format!("{{\"{url}\": \"{hash}\"}}")).unwrap();
+
+    let main_path = dir.join("main.huff");
+    fs::write(
+        &main_path,
+        format!("#include \"{url}\"\n#define macro MAIN() = takes(0) returns (0) {{ MINT() }}"),
+    )
+    .unwrap();
+
+    let evm_version = EVMVersion::default();
+    let mut compiler = Compiler::new(
+        &evm_version,
+        Arc::new(vec![main_path.to_string_lossy().to_string()]),
+        None,
+        None,
+        None,
+        None,
+        None,
+        false,
+        false,
+    );
+    compiler.file_provider =
+        Arc::new(RemoteFileProvider::new(RemoteCache::new(&cache_dir, dir.join("huff.lock"))));
+
+    let artifacts = compiler.execute().unwrap();
+    assert_eq!(artifacts[0].bytecode, "60038060093d393df3600435".to_string());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,56 @@
+use huff_utils::prelude::*;
+
+mod common;
+use common::compile;
+
+#[test]
+fn test_repeat_unrolls_body() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns (0) {
+        __REPEAT(0x03, i) { <i> }
+        __REPEAT(0x02) { caller }
+        __REPEAT(0x00) { pop }
+    }
+    "#;
+
+    let artifact = compile(source).unwrap();
+    assert_eq!(artifact.runtime, "5f600160023333");
+}
+
+#[test]
+fn test_repeat_substitutes_nested_indices() {
+    let source = r#"
+    #define constant ROWS = 0x02
+
+    #define macro STORE(slot) = takes(1) returns (0) {
+        <slot> sstore
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        __REPEAT([ROWS], i) {
+            __REPEAT(0x02, j) {
+                <j> STORE(<i>)
+            }
+        }
+    }
+    "#;
+
+    let artifact = compile(source).unwrap();
+    assert_eq!(artifact.runtime, "5f60005560016000555f6001556001600155");
+}
+
+#[test]
+fn test_repeat_count_exceeds_maximum() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns (0) {
+        __REPEAT(0x010000) { caller }
+    }
+    "#;
+
+    match compile(source).unwrap_err() {
+        CompilerError::CodegenError(e) => {
+            assert_eq!(e.kind, CodegenErrorKind::InvalidRepeatCount("0x10000".to_string()));
+        }
+        e => panic!("Expected a codegen error, got {e:?}"),
+    }
+}
@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use huff_core::Compiler;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"#define macro ADD() = takes(2) returns(1) {
+    add
+}
+
+#define macro CONSTRUCTOR() = takes(0) returns(0) {
+    caller 0x00 sstore
+}
+
+#define macro MAIN() = takes(0) returns(0) {
+    0x01 0x02 ADD()
+    0x00 mstore
+}
+"#;
+
+#[test]
+fn test_source_maps() {
+    let file = Arc::new(FileSource {
+        source: Some(SOURCE.to_string()),
+        path: "Main.huff".to_string(),
+        ..Default::default()
+    });
+    let evm_version = EVMVersion::default();
+    let compiler =
+        Compiler::new(&evm_version, Arc::new(vec![]), None, None, None, None, None, false, false);
+    let artifact = compiler.gen_artifact(file).unwrap();
+    assert_eq!(artifact.source_list, vec!["Main.huff".to_string()]);
+
+    // Each instruction maps to the source of the statement it was generated from, and the
+    // instructions of an invoked macro to the macro's own statements
+    assert_eq!(artifact.runtime_source_map, "184:2:0:-;189;48:3;204:2;207:6");
+    assert_eq!(sources(&artifact.runtime_source_map), vec!["01", "02", "add", "00", "mstore"]);
+
+    // The runtime code follows the constructor and bootstrap code, which isn't mapped
+    let deployed = sources(&artifact.source_map);
+    assert_eq!(deployed[..3], ["caller", "00", "sstore"]);
+    assert!(deployed[3..10].iter().all(|s| s.is_empty()));
+    assert_eq!(deployed[10..], ["01", "02", "add", "00", "mstore"]);
+}
+
+/// The source of each instruction of a compressed source map, empty if it isn't mapped
+fn sources(source_map: &str) -> Vec<String> {
+    let mut fields = ["", "", "", ""].map(String::from);
+    source_map
+        .split(';')
+        .map(|entry| {
+            for (field, value) in fields.iter_mut().zip(entry.split(':')) {
+                if !value.is_empty() {
+                    *field = value.to_string();
+                }
+            }
+            match (fields[0].parse::<i64>().unwrap(), fields[1].parse::<i64>().unwrap()) {
+                (-1, _) => String::new(),
+                (start, length) => SOURCE[start as usize..(start + length) as usize].to_string(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,81 @@
+use huff_utils::prelude::*;
+
+mod common;
+use common::compile_with_lint;
+
+fn warnings(artifact: &Artifact) -> Vec<(WarningKind, usize)> {
+    artifact.warnings.iter().map(|w| (w.kind.clone(), w.span.0[0].start)).collect()
+}
+
+#[test]
+fn test_balanced_stack() {
+    let source = r#"
+    #define macro REQUIRE(err) = takes(1) returns(0) {
+        continue jumpi
+        <err> jump
+        continue:
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x04 calldataload dup1 REQUIRE(fail)
+        loop:
+            0x01 swap1 sub
+            dup1 loop jumpi
+        pop stop
+
+        fail:
+            0x00 dup1 revert
+    }
+    "#;
+
+    let artifact = compile_with_lint(source, "stack-analysis", Severity::Warn).unwrap();
+    assert!(artifact.warnings.is_empty(), "{:?}", artifact.warnings);
+}
+
+#[test]
+fn test_stack_underflow() {
+    let source = r#"
+    #define macro STORE() = takes(2) returns(0) {
+        sstore
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x01 flag jumpi
+        0x00 STORE()
+        stop
+
+        flag:
+            0x00 return
+    }
+    "#;
+
+    let artifact = compile_with_lint(source, "stack-analysis", Severity::Warn).unwrap();
+    assert_eq!(
+        warnings(&artifact),
+        vec![
+            (WarningKind::StackUnderflow(2, 1), source.find("sstore").unwrap()),
+            (WarningKind::StackUnderflow(2, 1), source.rfind("return").unwrap()),
+        ]
+    );
+}
+
+#[test]
+fn test_stack_overflow() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        loop:
+            0x01 loop jump
+    }
+    "#;
+
+    let artifact = compile_with_lint(source, "stack-analysis", Severity::Warn).unwrap();
+    assert_eq!(
+        warnings(&artifact),
+        vec![(WarningKind::StackOverflow(1025), source.find("loop jump").unwrap())]
+    );
+
+    match compile_with_lint(source, "stack-analysis", Severity::Error) {
+        Err(CompilerError::Warning(w)) => assert_eq!(w.kind, WarningKind::StackOverflow(1025)),
+        res => panic!("expected a stack overflow error, got {res:?}"),
+    }
+}
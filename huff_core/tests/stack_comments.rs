@@ -0,0 +1,91 @@
+use huff_utils::prelude::*;
+
+mod common;
+use common::compile_with_lint;
+
+#[test]
+fn test_valid_stack_comments() {
+    let source = r#"
+    #define macro ADD_ONE() = takes(1) returns(1) {
+        // [x]
+        0x01 add        // [x + 1]
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x04 calldataload   // [a]
+        0x24 calldataload   // [b, a]
+        swap1               // [a, b]
+        dup2                // [b, a, b]
+        ADD_ONE()           // [b + 1, a, b]
+        pop pop             // [b]
+        push1 0x01          // [one, b]
+        sstore              // []
+        __FUNC_SIG("transfer(address,uint256)") // [sig]
+        is_sig jumpi        // []
+        0x00 dup1 revert
+
+        is_sig:
+            // [unknown, stack]
+            dup1 pop        // [unknown, stack]
+            stop
+    }
+    "#;
+
+    let artifact = compile_with_lint(source, "stack-comments", Severity::Warn).unwrap();
+    assert!(artifact.warnings.is_empty(), "{:?}", artifact.warnings);
+}
+
+#[test]
+fn test_stack_comment_mismatches() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        0x04 calldataload   // [a]
+        0x24 calldataload   // [b, a]
+        swap1               // [b, a]
+        0x00 mstore         // [a, b]
+        pop                 // []
+    }
+    "#;
+
+    let artifact = compile_with_lint(source, "stack-comments", Severity::Warn).unwrap();
+    let warnings =
+        artifact.warnings.iter().map(|w| (w.kind.clone(), w.span.0[0].start)).collect::<Vec<_>>();
+    assert_eq!(
+        warnings,
+        vec![
+            (
+                WarningKind::StackCommentMismatch("[b, a]".to_string(), "[a, b]".to_string()),
+                source.find("// [b, a]\n        0x00").unwrap()
+            ),
+            (
+                WarningKind::StackCommentMismatch("[a, b]".to_string(), "[a]".to_string()),
+                source.find("// [a, b]\n        pop").unwrap()
+            ),
+            (
+                WarningKind::StackCommentMismatch("[]".to_string(), "[b]".to_string()),
+                source.find("// []").unwrap()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_stack_comment_severities() {
+    let source = r#"
+    #define macro MAIN() = takes(1) returns(0) {
+        // [a, b]
+        pop
+    }
+    "#;
+
+    let artifact = compile_with_lint(source, "stack-comments", Severity::Allow).unwrap();
+    assert!(artifact.warnings.is_empty());
+
+    match compile_with_lint(source, "stack-comments", Severity::Error) {
+        Err(CompilerError::Warning(w)) => assert_eq!(
+            w.kind,
+            WarningKind::StackCommentMismatch("[a, b]".to_string(), "[_]".to_string())
+        ),
+        res => panic!("expected a stack comment mismatch error, got {res:?}"),
+    }
+}
@@ -0,0 +1,87 @@
+use huff_utils::prelude::*;
+
+mod common;
+use common::compile_with_lint;
+
+fn mismatches(artifact: &Artifact) -> Vec<WarningKind> {
+    artifact.warnings.iter().map(|w| w.kind.clone()).collect()
+}
+
+#[test]
+fn test_matching_stack_effects() {
+    let source = r#"
+    #define macro REQUIRE_NOT_ZERO() = takes(1) returns(0) {
+        continue jumpi
+        0x00 dup1 revert
+        continue:
+    }
+
+    #define macro APPLY(op) = takes(2) returns(1) {
+        <op>
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x04 calldataload dup1 REQUIRE_NOT_ZERO()
+        0x24 calldataload APPLY(add)
+        push1 0x00 mstore
+        0x20 0x00 return
+    }
+    "#;
+
+    let artifact = compile_with_lint(source, "stack-effects", Severity::Warn).unwrap();
+    assert!(artifact.warnings.is_empty(), "{:?}", artifact.warnings);
+}
+
+#[test]
+fn test_stack_effect_mismatches() {
+    let source = r#"
+    #define macro TOO_FEW_TAKES() = takes(1) returns(1) {
+        add
+    }
+
+    #define macro TOO_MANY_RETURNS() = takes(0) returns(2) {
+        caller
+    }
+
+    #define macro APPLY(op) = takes(2) returns(1) {
+        <op>
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x01 0x02 TOO_FEW_TAKES() TOO_MANY_RETURNS()
+        0x03 APPLY(add) APPLY(addmod) APPLY(0x04)
+        stop
+    }
+    "#;
+
+    let artifact = compile_with_lint(source, "stack-effects", Severity::Warn).unwrap();
+    assert_eq!(
+        mismatches(&artifact),
+        vec![
+            WarningKind::StackEffectMismatch("TOO_FEW_TAKES".to_string(), (1, 1), (2, 1)),
+            WarningKind::StackEffectMismatch("TOO_MANY_RETURNS".to_string(), (0, 2), (0, 1)),
+            WarningKind::StackEffectMismatch("APPLY".to_string(), (2, 1), (3, 1)),
+            WarningKind::StackEffectMismatch("APPLY".to_string(), (2, 1), (2, 3)),
+        ]
+    );
+    assert_eq!(artifact.warnings[0].span.0[0].start, source.find("#define macro TOO_FEW").unwrap());
+}
+
+#[test]
+fn test_stack_effect_severities() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        0x00 mstore
+    }
+    "#;
+
+    let artifact = compile_with_lint(source, "stack-effects", Severity::Allow).unwrap();
+    assert!(artifact.warnings.is_empty());
+
+    match compile_with_lint(source, "stack-effects", Severity::Error) {
+        Err(CompilerError::Warning(w)) => {
+            assert_eq!(w.kind, WarningKind::StackEffectMismatch("MAIN".to_string(), (0, 0), (1, 0)))
+        }
+        res => panic!("expected a stack effect mismatch error, got {res:?}"),
+    }
+}
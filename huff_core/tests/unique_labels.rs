@@ -0,0 +1,51 @@
+use huff_utils::prelude::*;
+
+mod common;
+use common::{compile, compile_with};
+
+#[test]
+fn test_unique_labels_decorator() {
+    let source = r#"
+    #[unique_labels]
+    #define macro SKIP() = takes(0) returns (0) {
+        skip jump
+        skip:
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        SKIP() SKIP()
+        skip:
+        skip jump
+    }
+    "#;
+
+    // The caller's jump resolves to its own label rather than one of the invocations'
+    let artifact = compile(source).unwrap();
+    assert_eq!(artifact.runtime, "610004565b610009565b5b61000a56");
+}
+
+#[test]
+fn test_unique_labels_flag() {
+    let source = r#"
+    #define macro SKIP() = takes(0) returns (0) {
+        skip jump
+        skip:
+    }
+
+    #define macro MAIN() = takes(0) returns (0) {
+        SKIP() SKIP()
+        skip:
+        skip jump
+    }
+    "#;
+
+    match compile(source) {
+        Err(CompilerError::CodegenError(ce)) => {
+            assert_eq!(ce.kind, CodegenErrorKind::DuplicateLabel("skip".to_string()))
+        }
+        res => panic!("Expected a duplicate label error, got {res:?}"),
+    }
+
+    let artifact = compile_with(source, |compiler| compiler.unique_labels = true).unwrap();
+    assert_eq!(artifact.runtime, "610004565b610009565b5b61000a56");
+}
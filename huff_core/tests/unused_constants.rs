@@ -0,0 +1,59 @@
+use huff_utils::prelude::*;
+
+mod common;
+use common::compile_with_lint;
+
+#[test]
+fn test_unused_constants() {
+    let source = r#"
+    #define constant USED = 0x01
+    #define constant USED_BY_EXPRESSION = 0x02
+    #define constant USED_BY_ASSERTION = 0x03
+    #define constant EXPRESSION = add([USED_BY_EXPRESSION], 0x01)
+    #define constant UNUSED = 0x04
+    #define constant USED_BY_UNUSED_MACRO = 0x05
+    #define constant UNUSED_POINTER = FREE_STORAGE_POINTER()
+
+    #assert [USED_BY_ASSERTION] > 0x00
+
+    #define macro UNUSED_MACRO() = takes(0) returns(0) {
+        [USED_BY_UNUSED_MACRO]
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        [USED] [EXPRESSION]
+    }
+    "#;
+
+    let artifact = compile_with_lint(source, "unused-constants", Severity::Warn).unwrap();
+    let warnings = artifact
+        .warnings
+        .iter()
+        .filter(|w| matches!(w.kind, WarningKind::UnusedConstant(_)))
+        .map(|w| (w.kind.clone(), w.span.0[0].start))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        warnings,
+        vec![
+            (
+                WarningKind::UnusedConstant("UNUSED".to_string()),
+                source.find("#define constant UNUSED ").unwrap()
+            ),
+            (
+                WarningKind::UnusedConstant("USED_BY_UNUSED_MACRO".to_string()),
+                source.find("#define constant USED_BY_UNUSED_MACRO").unwrap()
+            ),
+            (
+                WarningKind::UnusedConstant("UNUSED_POINTER".to_string()),
+                source.find("#define constant UNUSED_POINTER").unwrap()
+            ),
+        ]
+    );
+
+    match compile_with_lint(source, "unused-constants", Severity::Error) {
+        Err(CompilerError::Warning(w)) => {
+            assert_eq!(w.kind, WarningKind::UnusedConstant("UNUSED".to_string()))
+        }
+        res => panic!("expected an unused constant error, got {res:?}"),
+    }
+}
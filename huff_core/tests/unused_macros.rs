@@ -0,0 +1,65 @@
+use huff_utils::prelude::*;
+
+mod common;
+use common::compile_with_lint;
+
+#[test]
+fn test_unused_macros() {
+    let source = r#"
+    #define macro USED_BY_ARG() = takes(0) returns(1) {
+        0x01
+    }
+
+    #define macro USED(value) = takes(0) returns(1) {
+        <value>
+    }
+
+    #define macro USED_BY_TABLE() = takes(0) returns(0) {
+        0x02
+    }
+
+    #define macro UNUSED() = takes(0) returns(0) {
+        USED_BY_UNUSED()
+    }
+
+    #define macro USED_BY_UNUSED() = takes(0) returns(0) {
+        0x03
+    }
+
+    #define test TEST_USED() = {
+        USED(0x04)
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        USED(USED_BY_ARG()) pop
+        __codesize(USED_BY_TABLE)
+    }
+    "#;
+
+    let artifact = compile_with_lint(source, "unused-macros", Severity::Warn).unwrap();
+    let warnings =
+        artifact.warnings.iter().map(|w| (w.kind.clone(), w.span.0[0].start)).collect::<Vec<_>>();
+    assert_eq!(
+        warnings,
+        vec![
+            (
+                WarningKind::UnusedMacro("UNUSED".to_string()),
+                source.find("#define macro UNUSED()").unwrap()
+            ),
+            (
+                WarningKind::UnusedMacro("USED_BY_UNUSED".to_string()),
+                source.find("#define macro USED_BY_UNUSED()").unwrap()
+            ),
+        ]
+    );
+
+    let artifact = compile_with_lint(source, "unused-macros", Severity::Allow).unwrap();
+    assert!(artifact.warnings.is_empty());
+
+    match compile_with_lint(source, "unused-macros", Severity::Error) {
+        Err(CompilerError::Warning(w)) => {
+            assert_eq!(w.kind, WarningKind::UnusedMacro("UNUSED".to_string()))
+        }
+        res => panic!("expected an unused macro error, got {res:?}"),
+    }
+}
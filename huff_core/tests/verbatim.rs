@@ -14,7 +14,7 @@ fn test_verbatim() {
     let full_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(full_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
@@ -36,10 +36,33 @@ fn test_verbatim_invalid_hex() {
     let full_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(full_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, Some("".to_string()));
+    let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
     let mut contract = parser.parse().unwrap();
     contract.derive_storage_pointers();
 
     // Expect failure to generate bytecode with verbatim
     assert!(Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).is_err());
 }
+
+#[test]
+fn test_verbatim_label_offsets() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        __VERBATIM(0x00600160015b)
+        end jump
+        end:
+            stop
+    }
+    "#;
+
+    let full_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(full_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, Some("".to_string())).unwrap();
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    // The injected bytes, including the leading zero byte, shift the label by their full size
+    let mb = Codegen::generate_main_bytecode(&EVMVersion::default(), &contract, None).unwrap();
+    assert_eq!(mb, "00600160015b61000a565b00".to_string());
+}
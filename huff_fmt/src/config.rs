@@ -0,0 +1,69 @@
+//! ## Format Configuration
+//!
+//! Reads the formatting options from the `[fmt]` table of a `huff.toml` file.
+//!
+//! ```toml
+//! [fmt]
+//! indent_width = 2
+//! align_stack_comments = false
+//! ```
+
+use std::{fs, path::Path};
+
+/// The name of the configuration file
+pub const CONFIG_FILE: &str = "huff.toml";
+
+/// The options of the formatter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatConfig {
+    /// The number of spaces each level of a body is indented with
+    pub indent_width: usize,
+    /// Whether to align the trailing comments of consecutive lines in a body, eg. stack comments
+    pub align_stack_comments: bool,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self { indent_width: 4, align_stack_comments: true }
+    }
+}
+
+impl FormatConfig {
+    /// Reads the `huff.toml` file in `root`, defaulting to the default options if it doesn't
+    /// exist
+    pub fn load(root: &str) -> Result<Self, String> {
+        let path = Path::new(root).join(CONFIG_FILE);
+        if !path.exists() {
+            tracing::debug!(target: "fmt", "NO \"{}\" FOUND IN \"{}\"", CONFIG_FILE, root);
+            return Ok(Self::default())
+        }
+        let data = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read \"{}\": {e}", path.display()))?;
+        Self::parse(&data).map_err(|e| format!("Invalid \"{}\": {e}", path.display()))
+    }
+
+    /// Parses the `[fmt]` table of a `huff.toml` file's contents
+    pub fn parse(data: &str) -> Result<Self, String> {
+        let toml = data.parse::<toml::Value>().map_err(|e| e.to_string())?;
+        let mut config = Self::default();
+        let Some(fmt) = toml.get("fmt") else { return Ok(config) };
+        let fmt = fmt.as_table().ok_or_else(|| "\"fmt\" must be a table".to_string())?;
+        for (key, value) in fmt {
+            match key.as_str() {
+                "indent_width" => {
+                    config.indent_width =
+                        value
+                            .as_integer()
+                            .and_then(|w| usize::try_from(w).ok())
+                            .ok_or_else(|| format!("\"{key}\" must be a positive integer"))?
+                }
+                "align_stack_comments" => {
+                    config.align_stack_comments =
+                        value.as_bool().ok_or_else(|| format!("\"{key}\" must be a boolean"))?
+                }
+                _ => return Err(format!("Unknown formatting option \"{key}\"")),
+            }
+        }
+        Ok(config)
+    }
+}
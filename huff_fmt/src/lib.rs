@@ -0,0 +1,343 @@
+#![doc = include_str!("../README.md")]
+#![warn(missing_docs)]
+#![warn(unused_extern_crates)]
+#![forbid(unsafe_code)]
+
+use huff_lexer::Lexer;
+use huff_parser::Parser;
+use huff_utils::prelude::*;
+
+pub mod config;
+
+use config::FormatConfig;
+
+/// Formats a source, failing with the first lexical or parser error if it's invalid
+///
+/// Only the whitespace between tokens changes: comments are kept, and the tokens are printed
+/// as they're written in the source.
+pub fn format(source: &str, config: &FormatConfig) -> Result<String, CompilerError> {
+    let tokens = Lexer::new(source)
+        .collect::<Result<Vec<Token>, _>>()
+        .map_err(CompilerError::LexicalError)?;
+    let contract = Parser::new(tokens.clone(), None)
+        .and_then(|mut parser| parser.parse())
+        .map_err(CompilerError::ParserError)?;
+
+    let chars = source.chars().collect::<Vec<char>>();
+    let tokens = tokens
+        .into_iter()
+        .filter(|t| {
+            !matches!(t.kind, TokenKind::Whitespace | TokenKind::Comment(_) | TokenKind::Eof)
+        })
+        .collect::<Vec<Token>>();
+
+    let mut printer = Printer::new(config, &chars);
+    for token in &tokens {
+        printer.trivia(contract.trivia.0.get(&token.span.start).into_iter().flatten());
+        printer.token(token);
+    }
+    // The trivia following the last token, keyed by the EOF token or the end of the source
+    let end = tokens.last().map_or(0, |t| t.span.start + 1);
+    printer.trivia(contract.trivia.0.range(end..).flat_map(|(_, trivia)| trivia));
+    Ok(printer.finish())
+}
+
+/// Whether a token starts a top-level item on a new line
+fn starts_item(kind: &TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::Define |
+            TokenKind::Include |
+            TokenKind::Assert |
+            TokenKind::IfDef |
+            TokenKind::IfNDef |
+            TokenKind::Else |
+            TokenKind::EndIf |
+            TokenKind::Pound
+    )
+}
+
+/// A formatted line
+#[derive(Debug, Default)]
+struct Line {
+    /// The indentation level
+    indent: usize,
+    /// The code of the line, or a comment on its own line
+    code: String,
+    /// The trailing line comment
+    comment: Option<String>,
+    /// Whether the line is within a body, where trailing comments are aligned
+    body: bool,
+}
+
+/// A body being printed, eg. of a macro or jump table
+#[derive(Debug)]
+struct Frame {
+    /// The indentation level of the body
+    indent: usize,
+    /// Whether a label was defined, indenting the following lines
+    labelled: bool,
+}
+
+/// Prints the tokens and trivia of a source as formatted lines
+struct Printer<'a> {
+    config: &'a FormatConfig,
+    /// The characters of the source, for the text of tokens and whitespace
+    chars: &'a [char],
+    /// The finished lines
+    lines: Vec<Line>,
+    /// The line being printed
+    line: Line,
+    /// The open bodies
+    frames: Vec<Frame>,
+    /// The depth of open parentheses, within which lines aren't broken
+    parens: usize,
+    /// Whether the printer is within the angle brackets of an argument call, eg. `<value>`
+    arg_call: bool,
+    /// The kind of the current definition, eg. `TokenKind::Function`
+    definition: Option<TokenKind>,
+    /// The previous token
+    previous: Option<TokenKind>,
+    /// The newlines since the previous token or comment
+    newlines: usize,
+    /// The first line of the comments on their own lines since the previous token
+    comments: Option<usize>,
+}
+
+impl<'a> Printer<'a> {
+    fn new(config: &'a FormatConfig, chars: &'a [char]) -> Self {
+        Self {
+            config,
+            chars,
+            lines: vec![],
+            line: Line::default(),
+            frames: vec![],
+            parens: 0,
+            arg_call: false,
+            definition: None,
+            previous: None,
+            newlines: 0,
+            comments: None,
+        }
+    }
+
+    /// The source text of a span, whose end is inclusive
+    fn text(&self, span: &Span) -> String {
+        let end = (span.end + 1).min(self.chars.len());
+        self.chars[span.start.min(end)..end].iter().collect()
+    }
+
+    /// The source text of a token
+    fn token_text(&self, token: &Token) -> String {
+        let text = self.text(&token.span);
+        // The spans of hex literals don't include their `0x` prefix
+        let start = token.span.start;
+        match token.kind {
+            TokenKind::Literal(..) if start >= 2 && self.chars[start - 2..start] == ['0', 'x'] => {
+                format!("0x{text}")
+            }
+            _ => text,
+        }
+    }
+
+    /// The indentation of a new line starting with a token
+    fn indent(&self, kind: &TokenKind) -> usize {
+        self.frames.last().map_or(0, |frame| {
+            frame.indent + usize::from(frame.labelled && !matches!(kind, TokenKind::Label(_)))
+        })
+    }
+
+    /// Finishes the current line, if it isn't empty
+    fn end_line(&mut self) {
+        let line = std::mem::take(&mut self.line);
+        if !line.code.is_empty() || line.comment.is_some() {
+            self.lines.push(line);
+        }
+    }
+
+    /// Starts a new line, preceded by a blank line unless it's the first of a body
+    fn new_line(&mut self, blank: bool, indent: usize) {
+        self.end_line();
+        let first = self.lines.last().map_or(true, |l| l.code.is_empty() || l.code.ends_with('{'));
+        if blank && !first {
+            self.lines.push(Line::default());
+        }
+        self.line = Line { indent, body: !self.frames.is_empty(), ..Default::default() };
+    }
+
+    /// Prints the comments of the trivia preceding a token
+    fn trivia<'t>(&mut self, trivia: impl Iterator<Item = &'t Trivia>) {
+        for trivia in trivia {
+            let comment = match &trivia.kind {
+                TriviaKind::Whitespace => {
+                    self.newlines += self.text(&trivia.span).matches('\n').count();
+                    continue
+                }
+                TriviaKind::Comment(comment) => comment.trim_end(),
+            };
+
+            if self.newlines == 0 && !self.line.code.is_empty() {
+                // A trailing comment, eg. a stack comment
+                if comment.starts_with("//") {
+                    self.line.comment = Some(comment.to_string());
+                    self.end_line();
+                } else {
+                    self.line.code.push(' ');
+                    self.block_comment(comment, trivia.span.start);
+                }
+            } else {
+                let indent = self.indent(&TokenKind::Whitespace);
+                self.new_line(self.newlines > 1, indent);
+                self.comments.get_or_insert(self.lines.len());
+                if comment.starts_with("//") {
+                    self.line.code = comment.to_string();
+                    self.end_line();
+                } else {
+                    self.block_comment(comment, trivia.span.start);
+                }
+            }
+            self.newlines = 0;
+        }
+    }
+
+    /// Prints a block comment, re-indenting its lines by the change of indentation of its first
+    fn block_comment(&mut self, comment: &str, start: usize) {
+        let column = self.chars[..start].iter().rev().take_while(|c| **c != '\n').count();
+        let mut lines = comment.lines();
+        self.line.code.push_str(lines.next().unwrap_or_default());
+        for line in lines {
+            let indent = self.line.indent;
+            self.new_line(false, indent);
+            let skipped = line.chars().take(column).take_while(|c| c.is_whitespace()).count();
+            self.line.code = line.chars().skip(skipped).collect::<String>().trim_end().to_string();
+        }
+    }
+
+    /// Whether a token is separated from the previous one on the same line by a space
+    fn spaced(&self, kind: &TokenKind) -> bool {
+        let Some(previous) = &self.previous else { return false };
+        match (previous, kind) {
+            (TokenKind::OpenParen | TokenKind::OpenBracket | TokenKind::Pound, _) => false,
+            (_, TokenKind::CloseParen | TokenKind::CloseBracket | TokenKind::Comma) => false,
+            (_, TokenKind::Colon) => false,
+            (TokenKind::LeftAngle, _) | (_, TokenKind::RightAngle) if self.arg_call => false,
+            // `returns (uint256)` in function definitions, `returns(1)` in macro definitions
+            (TokenKind::Returns, TokenKind::OpenParen) => {
+                self.definition == Some(TokenKind::Function)
+            }
+            (
+                TokenKind::Ident(_) |
+                TokenKind::Opcode(_) |
+                TokenKind::BuiltinFunction(_) |
+                TokenKind::Takes |
+                TokenKind::Repeat,
+                TokenKind::OpenParen,
+            ) => false,
+            (TokenKind::OpenBrace, TokenKind::CloseBrace) => false,
+            _ => true,
+        }
+    }
+
+    /// Prints a token
+    fn token(&mut self, token: &Token) {
+        let kind = &token.kind;
+        let in_body = !self.frames.is_empty();
+
+        if *kind == TokenKind::CloseBrace {
+            let frame = self.frames.pop();
+            if self.previous != Some(TokenKind::OpenBrace) || self.line.code.is_empty() {
+                self.new_line(false, frame.map_or(0, |f| f.indent.saturating_sub(1)));
+            }
+        } else {
+            let breaks = match kind {
+                _ if self.line.code.is_empty() => true,
+                _ if !in_body => starts_item(kind),
+                _ if self.parens > 0 || self.arg_call => false,
+                TokenKind::Label(_) => true,
+                _ => {
+                    self.newlines > 0 ||
+                        matches!(
+                            self.previous,
+                            Some(TokenKind::OpenBrace) | Some(TokenKind::Colon)
+                        )
+                }
+            };
+            if breaks {
+                // Decorators are kept right above their definition
+                let decorated = !in_body && self.previous == Some(TokenKind::CloseBracket);
+                let indent = self.indent(kind);
+                self.new_line(self.newlines > 1 && !decorated, indent);
+                if let (TokenKind::Label(_), Some(frame)) = (kind, self.frames.last_mut()) {
+                    // Comments preceding a label are indented like it
+                    let from = self.comments.unwrap_or(self.lines.len());
+                    for line in &mut self.lines[from..] {
+                        line.indent = line.indent.min(frame.indent);
+                    }
+                    frame.labelled = true;
+                }
+            }
+        }
+
+        if !self.line.code.is_empty() && self.spaced(kind) {
+            self.line.code.push(' ');
+        }
+        let text = self.token_text(token);
+        self.line.code.push_str(&text);
+
+        match kind {
+            TokenKind::OpenBrace => {
+                self.frames.push(Frame { indent: self.line.indent + 1, labelled: false })
+            }
+            TokenKind::OpenParen => self.parens += 1,
+            TokenKind::CloseParen => self.parens = self.parens.saturating_sub(1),
+            TokenKind::LeftAngle if in_body => self.arg_call = true,
+            TokenKind::RightAngle => self.arg_call = false,
+            _ if self.previous == Some(TokenKind::Define) => self.definition = Some(kind.clone()),
+            _ => {}
+        }
+        self.previous = Some(kind.clone());
+        self.newlines = 0;
+        self.comments = None;
+    }
+
+    /// Renders the printed lines, aligning the trailing comments of consecutive lines in a body
+    fn finish(mut self) -> String {
+        self.end_line();
+        while self.lines.last().map_or(false, |l| l.code.is_empty() && l.comment.is_none()) {
+            self.lines.pop();
+        }
+
+        let indent_width = self.config.indent_width;
+        let width = |line: &Line| line.indent * indent_width + line.code.chars().count();
+        let mut output = String::new();
+        let mut start = 0;
+        while start < self.lines.len() {
+            let grouped = |line: &Line| line.body && !line.code.is_empty();
+            let end = if grouped(&self.lines[start]) {
+                start + self.lines[start..].iter().take_while(|l| grouped(l)).count()
+            } else {
+                start + 1
+            };
+            let column = self.lines[start..end]
+                .iter()
+                .filter(|l| l.comment.is_some())
+                .map(width)
+                .max()
+                .filter(|_| self.config.align_stack_comments);
+
+            for line in &self.lines[start..end] {
+                let mut text = " ".repeat(line.indent * indent_width);
+                text.push_str(&line.code);
+                if let Some(comment) = &line.comment {
+                    let padding = column.map_or(0, |column| column - width(line));
+                    text.push_str(&" ".repeat(padding + usize::from(!line.code.is_empty())));
+                    text.push_str(comment);
+                }
+                output.push_str(text.trim_end());
+                output.push('\n');
+            }
+            start = end;
+        }
+        output
+    }
+}
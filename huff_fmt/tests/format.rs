@@ -0,0 +1,156 @@
+use huff_fmt::{config::FormatConfig, format};
+use huff_lexer::Lexer;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"
+#include   "./utils/Ownable.huff"
+
+
+/* Interface */
+#define function transfer(address,uint256) nonpayable returns (bool)
+#define   event Transfer(address indexed,address indexed,uint256)
+#define constant OWNER=FREE_STORAGE_POINTER()  // slot 0
+#define constant DOUBLE = add(0x20,mul( [OTHER] , 0x02))
+
+/**
+ * Transfers tokens
+ */
+#define macro TRANSFER(err) = takes (2) returns (0) {
+        [OWNER] sload // [owner]
+    caller eq success jumpi // []
+
+
+
+  __REPEAT(0x02) {
+  0x00 pop
+  }
+  // Revert with the error
+  fail:
+  <err> 0x00 mstore   /* inline */ 0x20 0x00 revert
+      success:
+    __FUNC_SIG(transfer) pop
+}
+
+#[calldata("0xa9059cbb"),value(0x01)]
+
+#define test TEST_TRANSFER() = takes (0) returns (0) {
+  TRANSFER(<err>,0x00)
+}
+
+#define macro EMPTY() = takes(0) returns(0) {}
+#define jumptable TABLE { success fail }
+"#;
+
+const FORMATTED: &str = r#"#include "./utils/Ownable.huff"
+
+/* Interface */
+#define function transfer(address, uint256) nonpayable returns (bool)
+#define event Transfer(address indexed, address indexed, uint256)
+#define constant OWNER = FREE_STORAGE_POINTER() // slot 0
+#define constant DOUBLE = add(0x20, mul([OTHER], 0x02))
+
+/**
+ * Transfers tokens
+ */
+#define macro TRANSFER(err) = takes(2) returns(0) {
+    [OWNER] sload           // [owner]
+    caller eq success jumpi // []
+
+    __REPEAT(0x02) {
+        0x00 pop
+    }
+    // Revert with the error
+    fail:
+        <err> 0x00 mstore /* inline */ 0x20 0x00 revert
+    success:
+        __FUNC_SIG(transfer) pop
+}
+
+#[calldata("0xa9059cbb"), value(0x01)]
+#define test TEST_TRANSFER() = takes(0) returns(0) {
+    TRANSFER(<err>, 0x00)
+}
+
+#define macro EMPTY() = takes(0) returns(0) {}
+#define jumptable TABLE {
+    success fail
+}
+"#;
+
+/// The kinds of the tokens of a source, without whitespace and comments
+fn token_kinds(source: &str) -> Vec<TokenKind> {
+    Lexer::new(source)
+        .map(|t| t.unwrap().kind)
+        .filter(|k| !matches!(k, TokenKind::Whitespace | TokenKind::Comment(_) | TokenKind::Eof))
+        .collect()
+}
+
+#[test]
+fn test_formats_canonically() {
+    let formatted = format(SOURCE, &FormatConfig::default()).unwrap();
+    assert_eq!(formatted, FORMATTED);
+
+    // Formatting only changes whitespace, and is idempotent
+    assert_eq!(token_kinds(&formatted), token_kinds(SOURCE));
+    assert_eq!(format(&formatted, &FormatConfig::default()).unwrap(), formatted);
+}
+
+#[test]
+fn test_indent_width_and_alignment() {
+    let config = FormatConfig { indent_width: 2, align_stack_comments: false };
+    let formatted = format(FORMATTED, &config).unwrap();
+    assert!(formatted.contains(
+        "  [OWNER] sload // [owner]\n  caller eq success jumpi // []\n\n  __REPEAT(0x02) {\n    0x00 pop\n  }"
+    ));
+    assert!(formatted.contains("  fail:\n    <err> 0x00 mstore"));
+}
+
+#[test]
+fn test_block_comments_are_reindented() {
+    let source = "#define macro MAIN() = takes(0) returns(0) {\n        /* Multi\n         * line */\n        stop\n}\n";
+    let formatted = format(source, &FormatConfig::default()).unwrap();
+    assert_eq!(
+        formatted,
+        "#define macro MAIN() = takes(0) returns(0) {\n    /* Multi\n     * line */\n    stop\n}\n"
+    );
+}
+
+#[test]
+fn test_literals_keep_their_source_text() {
+    let source = "#define constant A = 0xFF_FF\n#define macro MAIN() = takes(0) returns(0) { 0x00 -1 -0x20 }";
+    let formatted = format(source, &FormatConfig::default()).unwrap();
+    assert_eq!(
+        formatted,
+        "#define constant A = 0xFF_FF\n#define macro MAIN() = takes(0) returns(0) {\n    0x00 -1 -0x20\n}\n"
+    );
+}
+
+#[test]
+fn test_invalid_sources_are_not_formatted() {
+    let error =
+        format("#define macro MAIN() = takes(0) returns(0) {\n    )\n}", &Default::default());
+    assert!(matches!(error, Err(CompilerError::ParserError(_))));
+
+    let error = format("#define macro MAIN() = takes(0) returns(0) { $ }", &Default::default());
+    assert!(matches!(error, Err(CompilerError::LexicalError(_))));
+}
+
+#[test]
+fn test_config_from_toml() {
+    let config =
+        FormatConfig::parse("[fmt]\nindent_width = 2\nalign_stack_comments = false").unwrap();
+    assert_eq!(config, FormatConfig { indent_width: 2, align_stack_comments: false });
+    assert_eq!(
+        FormatConfig::parse("[lints]\nunused-macros = \"error\"").unwrap(),
+        FormatConfig::default()
+    );
+
+    assert_eq!(
+        FormatConfig::parse("[fmt]\nindent_width = -1").unwrap_err(),
+        "\"indent_width\" must be a positive integer"
+    );
+    assert_eq!(
+        FormatConfig::parse("[fmt]\ntabs = true").unwrap_err(),
+        "Unknown formatting option \"tabs\""
+    );
+}
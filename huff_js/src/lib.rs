@@ -9,7 +9,12 @@ use std::{collections::HashMap, sync::Arc};
 use wasm_bindgen::prelude::*;
 
 use huff_core::Compiler;
-use huff_utils::{abi::Abi, artifact::Artifact, error::CompilerError, prelude::EVMVersion};
+use huff_utils::{
+    abi::Abi,
+    artifact::{Artifact, DevDoc, StorageLayout, UserDoc},
+    error::CompilerError,
+    prelude::EVMVersion,
+};
 use serde::{Deserialize, Serialize};
 
 /// Converts a CompilerError into a returnable JsValue
@@ -31,8 +36,19 @@ struct CompilerInput {
 #[derive(Serialize, Deserialize)]
 struct CompilerArtifact {
     bytecode: String,
-    runtime: String,
+    #[serde(rename = "deployedBytecode")]
+    deployed_bytecode: String,
+    #[serde(rename = "sourceMap")]
+    source_map: String,
+    #[serde(rename = "deployedSourceMap")]
+    deployed_source_map: String,
+    #[serde(rename = "sourceList")]
+    source_list: Vec<String>,
     abi: Option<Abi>,
+    #[serde(rename = "storageLayout")]
+    storage_layout: StorageLayout,
+    userdoc: UserDoc,
+    devdoc: DevDoc,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -67,8 +83,14 @@ pub fn compile(input: JsValue) -> Result<JsValue, JsValue> {
             artifact.file.path.clone(),
             CompilerArtifact {
                 bytecode: artifact.bytecode.clone(),
-                runtime: artifact.runtime.clone(),
+                deployed_bytecode: artifact.runtime.clone(),
+                source_map: artifact.source_map.clone(),
+                deployed_source_map: artifact.runtime_source_map.clone(),
+                source_list: artifact.source_list.clone(),
                 abi: artifact.abi.clone(),
+                storage_layout: artifact.storage_layout.clone(),
+                userdoc: artifact.userdoc.clone(),
+                devdoc: artifact.devdoc.clone(),
             },
         );
     });
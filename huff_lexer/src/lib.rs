@@ -6,6 +6,8 @@ use std::{
     str::Chars,
 };
 
+pub mod semantic;
+
 /// Defines a context in which the lexing happens.
 /// Allows to differientate between EVM types and opcodes that can either
 /// be identical or the latter being a substring of the former (example : bytes32 and byte)
@@ -44,6 +46,14 @@ pub struct Lexer<'a> {
     pub eof: bool,
     /// Current context.
     pub context: Context,
+    /// Depth of nested parentheses within macro call arguments.
+    macro_args_depth: usize,
+    /// Depth of nested braces within a macro body, eg. `__REPEAT` bodies.
+    macro_body_depth: usize,
+    /// Whether the current macro arguments are those of a builtin function call.
+    builtin_args: bool,
+    /// The names of the custom builtins lexed as builtin functions.
+    custom_builtins: Vec<String>,
 }
 
 pub type TokenResult = Result<Token, LexicalError>;
@@ -57,9 +67,20 @@ impl<'a> Lexer<'a> {
             lookback: None,
             eof: false,
             context: Context::Global,
+            macro_args_depth: 0,
+            macro_body_depth: 0,
+            builtin_args: false,
+            custom_builtins: vec![],
         }
     }
 
+    /// Lexes the given names of custom builtins as builtin functions, besides the builtins of
+    /// the language
+    pub fn with_custom_builtins(mut self, names: Vec<String>) -> Self {
+        self.custom_builtins = names;
+        self
+    }
+
     /// Consumes the next character
     pub fn consume(&mut self) -> Option<char> {
         let (c, index) = self.chars.next()?;
@@ -139,7 +160,15 @@ impl<'a> Lexer<'a> {
 
                     let mut found_kind: Option<TokenKind> = None;
 
-                    let keys = [TokenKind::Define, TokenKind::Include];
+                    let keys = [
+                        TokenKind::Define,
+                        TokenKind::Include,
+                        TokenKind::IfDef,
+                        TokenKind::IfNDef,
+                        TokenKind::Else,
+                        TokenKind::EndIf,
+                        TokenKind::Assert,
+                    ];
                     for kind in keys.into_iter() {
                         let key = kind.to_string();
                         let peeked = word.clone();
@@ -151,7 +180,7 @@ impl<'a> Lexer<'a> {
 
                     if let Some(kind) = &found_kind {
                         Ok(kind.clone().into_span(start, end))
-                    } else if self.context == Context::Global && self.peek().unwrap() == '[' {
+                    } else if self.context == Context::Global && self.peek() == Some('[') {
                         Ok(TokenKind::Pound.into_single_span(self.position))
                     } else {
                         // Otherwise we don't support # prefixed indentifiers
@@ -168,9 +197,18 @@ impl<'a> Lexer<'a> {
                 }
                 // Alphabetical characters
                 ch if ch.is_alphabetic() || ch.eq(&'_') => {
-                    let (word, start, mut end) =
+                    let (mut word, start, mut end) =
                         self.eat_while(Some(ch), |c| c.is_alphanumeric() || c == '_');
 
+                    // Namespaced identifiers, e.g. `Math.SAFE_ADD`
+                    if let Some('.') = self.peek() {
+                        self.consume();
+                        let (member, _, member_end) =
+                            self.eat_while(Some('.'), |c| c.is_alphanumeric() || c == '_');
+                        word.push_str(&member);
+                        end = member_end;
+                    }
+
                     let mut found_kind: Option<TokenKind> = None;
                     let keys = [
                         TokenKind::Macro,
@@ -178,6 +216,7 @@ impl<'a> Lexer<'a> {
                         TokenKind::Test,
                         TokenKind::Function,
                         TokenKind::Constant,
+                        TokenKind::Immutable,
                         TokenKind::Error,
                         TokenKind::Takes,
                         TokenKind::Returns,
@@ -249,12 +288,8 @@ impl<'a> Lexer<'a> {
 
                     // Syntax sugar: true evaluates to 0x01, false evaluates to 0x00
                     if matches!(word.as_str(), "true" | "false") {
-                        found_kind =
-                            Some(TokenKind::Literal(str_to_bytes32(if word.as_str() == "true" {
-                                "1"
-                            } else {
-                                "0"
-                            })));
+                        let bit = if word.as_str() == "true" { "1" } else { "0" };
+                        found_kind = Some(TokenKind::Literal(str_to_bytes32(bit), 1));
                         self.eat_while(None, |c| c.is_alphanumeric());
                     }
 
@@ -265,8 +300,7 @@ impl<'a> Lexer<'a> {
                     }
 
                     if self.context == Context::AbiArgs {
-                        let curr_char = self.peek().unwrap();
-                        if !['(', ')'].contains(&curr_char) {
+                        if !matches!(self.peek(), Some('(' | ')') | None) {
                             let (partial_raw_type, _, abi_args_end) = self
                                 .eat_while(Some(ch), |c| {
                                     c.is_alphanumeric() || c == '[' || c == ']'
@@ -305,8 +339,7 @@ impl<'a> Lexer<'a> {
                                                     };
                                                     tracing::error!(target: "lexer", "{}", format!("{err:?}"));
                                                     err
-                                                })
-                                                .unwrap();
+                                                })?;
                                             size_vec.push(arr_size);
                                         }
                                     }
@@ -348,8 +381,11 @@ impl<'a> Lexer<'a> {
 
                     let kind = if let Some(kind) = &found_kind {
                         kind.clone()
+                    } else if self.context == Context::MacroBody && word == "__REPEAT" {
+                        TokenKind::Repeat
                     } else if self.context == Context::MacroBody &&
-                        BuiltinFunctionKind::try_from(&word).is_ok()
+                        (BuiltinFunctionKind::try_from(&word).is_ok() ||
+                            self.custom_builtins.contains(&word))
                     {
                         TokenKind::BuiltinFunction(word)
                     } else {
@@ -359,12 +395,25 @@ impl<'a> Lexer<'a> {
                     Ok(kind.into_span(start, end))
                 }
                 // If it's the start of a hex literal
-                ch if ch == '0' && self.peek().unwrap() == 'x' => self.eat_hex_digit(ch),
+                ch if ch == '0' && self.peek() == Some('x') => self.eat_hex_digit(ch),
+                // If it's the start of a binary literal
+                ch if ch == '0' && self.peek() == Some('b') => self.eat_binary_digit(ch),
+                // Comparisons of `#assert` directives
+                '=' if self.peek() == Some('=') => self.double_char_token(TokenKind::Equal),
+                '!' if self.peek() == Some('=') => self.double_char_token(TokenKind::NotEqual),
                 '=' => self.single_char_token(TokenKind::Assign),
                 '(' => {
                     match self.context {
                         Context::Abi => self.context = Context::AbiArgs,
-                        Context::MacroBody => self.context = Context::MacroArgs,
+                        Context::MacroBody => {
+                            self.builtin_args = matches!(
+                                self.lookback,
+                                Some(Token { kind: TokenKind::BuiltinFunction(_), .. })
+                            );
+                            self.context = Context::MacroArgs
+                        }
+                        // Nested macro invocation passed as an argument
+                        Context::MacroArgs => self.macro_args_depth += 1,
                         _ => {}
                     }
                     self.single_char_token(TokenKind::OpenParen)
@@ -372,7 +421,13 @@ impl<'a> Lexer<'a> {
                 ')' => {
                     match self.context {
                         Context::AbiArgs => self.context = Context::Abi,
-                        Context::MacroArgs => self.context = Context::MacroBody,
+                        Context::MacroArgs if self.macro_args_depth > 0 => {
+                            self.macro_args_depth -= 1
+                        }
+                        Context::MacroArgs => {
+                            self.builtin_args = false;
+                            self.context = Context::MacroBody
+                        }
                         _ => {}
                     }
                     self.single_char_token(TokenKind::CloseParen)
@@ -380,20 +435,43 @@ impl<'a> Lexer<'a> {
                 '[' => self.single_char_token(TokenKind::OpenBracket),
                 ']' => self.single_char_token(TokenKind::CloseBracket),
                 '{' => {
-                    if self.context == Context::MacroDefinition {
-                        self.context = Context::MacroBody;
+                    match self.context {
+                        Context::MacroDefinition => self.context = Context::MacroBody,
+                        // Nested block within the macro body
+                        Context::MacroBody => self.macro_body_depth += 1,
+                        _ => {}
                     }
                     self.single_char_token(TokenKind::OpenBrace)
                 }
                 '}' => {
-                    if matches!(self.context, Context::MacroBody | Context::CodeTableBody) {
-                        self.context = Context::Global;
+                    match self.context {
+                        Context::MacroBody if self.macro_body_depth > 0 => {
+                            self.macro_body_depth -= 1
+                        }
+                        Context::MacroBody | Context::CodeTableBody => {
+                            self.context = Context::Global
+                        }
+                        _ => {}
                     }
                     self.single_char_token(TokenKind::CloseBrace)
                 }
                 '+' => self.single_char_token(TokenKind::Add),
+                // Negative literals, eg. `-1` or `-0x20`
+                '-' if self.in_literal_context() &&
+                    self.peek().map_or(false, |c| c.is_ascii_digit()) =>
+                {
+                    self.eat_negative_literal()
+                }
                 '-' => self.single_char_token(TokenKind::Sub),
                 '*' => self.single_char_token(TokenKind::Mul),
+                // Standard library include path, e.g. `#include <huffstd/math/SafeMath.huff>`
+                '<' if self.checked_lookback(TokenKind::Include) => {
+                    let (path, start, end) = self.eat_while(Some(ch), |c| c != '>');
+                    self.consume();
+                    Ok(TokenKind::Str(format!("{path}>")).into_span(start, end + 1))
+                }
+                '<' if self.peek() == Some('=') => self.double_char_token(TokenKind::LessEqual),
+                '>' if self.peek() == Some('=') => self.double_char_token(TokenKind::GreaterEqual),
                 '<' => self.single_char_token(TokenKind::LeftAngle),
                 '>' => self.single_char_token(TokenKind::RightAngle),
                 // NOTE: TokenKind::Div is lexed further up since it overlaps with comment
@@ -443,6 +521,13 @@ impl<'a> Lexer<'a> {
         Ok(token_kind.into_single_span(self.position))
     }
 
+    /// Consumes the second character of a two character token
+    fn double_char_token(&mut self, token_kind: TokenKind) -> TokenResult {
+        let start = self.position;
+        self.consume();
+        Ok(token_kind.into_span(start, self.position))
+    }
+
     /// Keeps consuming tokens as long as the predicate is satisfied
     fn eat_while<F: Fn(char) -> bool>(
         &mut self,
@@ -478,19 +563,117 @@ impl<'a> Lexer<'a> {
     }
 
     fn eat_digit(&mut self, initial_char: char) -> TokenResult {
-        let (integer_str, start, end) =
-            self.eat_while(Some(initial_char), |ch| ch.is_ascii_digit());
+        let (mut raw_str, start, mut end) =
+            self.eat_while(Some(initial_char), |ch| ch.is_ascii_digit() || ch == '_');
+        let mut integer_str = Self::strip_separators(
+            &raw_str,
+            &raw_str,
+            &Span::new(start as usize..end as usize, None),
+        )?;
+
+        // Scientific notation, eg. `1e18`
+        let mut exponent = 0;
+        if self.peek() == Some('e') &&
+            self.chars.clone().nth(1).map_or(false, |(c, _)| c.is_ascii_digit())
+        {
+            self.consume();
+            let (exponent_str, _, exponent_end) = self.eat_while(None, |ch| ch.is_ascii_digit());
+            raw_str = format!("{raw_str}e{exponent_str}");
+            end = exponent_end;
+            exponent = exponent_str.parse().unwrap_or(usize::MAX);
+        }
 
-        let integer = integer_str.parse().unwrap();
+        // Ether units, eg. `5 gwei` or `1 ether`
+        if let Some((unit, unit_exponent, unit_end)) = self.eat_unit() {
+            raw_str = format!("{raw_str} {unit}");
+            end = unit_end;
+            exponent = exponent.saturating_add(unit_exponent);
+        }
 
-        let integer_token = TokenKind::Num(integer);
         let span = Span { start: start as usize, end: end as usize, file: None };
+        if exponent > 0 {
+            // Any larger power of ten overflows 32 bytes
+            if exponent > 77 {
+                return Err(LexicalError::new(LexicalErrorKind::InvalidLiteral(raw_str), span))
+            }
+            integer_str.push_str(&"0".repeat(exponent));
+        }
+
+        // Decimal literals, converted to the same 32 byte literal as their hex equivalent
+        if self.in_literal_context() {
+            return match parse_decimal_literal(&integer_str) {
+                Some(l) => Ok(Token { kind: TokenKind::Literal(l, literal_width(&l)), span }),
+                None => Err(LexicalError::new(LexicalErrorKind::InvalidLiteral(raw_str), span)),
+            }
+        }
+
+        let integer = integer_str.parse().map_err(|_| {
+            LexicalError::new(LexicalErrorKind::InvalidLiteral(raw_str), span.clone())
+        })?;
+
+        let integer_token = TokenKind::Num(integer);
         Ok(Token { kind: integer_token, span })
     }
 
+    /// Lexes a negative literal, encoded as its 32 byte two's complement
+    fn eat_negative_literal(&mut self) -> TokenResult {
+        let start = self.position;
+        let ch = self.consume().unwrap();
+        let token = match self.peek() {
+            Some('x') if ch == '0' => self.eat_hex_digit(ch)?,
+            Some('b') if ch == '0' => self.eat_binary_digit(ch)?,
+            _ => self.eat_digit(ch)?,
+        };
+        match token.kind {
+            TokenKind::Literal(l, _) => {
+                Ok(TokenKind::Literal(negate_literal(&l), 32)
+                    .into_span(start, token.span.end as u32))
+            }
+            _ => Ok(token),
+        }
+    }
+
+    /// Whether numbers are lexed as literals, ie. in macro bodies, macro arguments and constants
+    ///
+    /// Builtin arguments keep numbers, the width of their hex literals is significant.
+    fn in_literal_context(&self) -> bool {
+        matches!(self.context, Context::MacroBody | Context::MacroArgs | Context::Constant) &&
+            !self.builtin_args
+    }
+
+    /// Consumes an ether unit following a number, eg. the `gwei` of `5 gwei`.
+    ///
+    /// Returns the unit, its power of ten and the position of its last character.
+    fn eat_unit(&mut self) -> Option<(String, usize, u32)> {
+        let mut ahead = self.chars.clone();
+        while ahead.next_if(|(c, _)| *c == ' ' || *c == '\t').is_some() {}
+
+        let mut unit = String::new();
+        let mut end = self.position;
+        while let Some((c, i)) = ahead.next_if(|(c, _)| c.is_alphanumeric() || *c == '_') {
+            unit.push(c);
+            end = i;
+        }
+
+        let exponent = match unit.as_str() {
+            "wei" => 0,
+            "gwei" => 9,
+            "ether" => 18,
+            _ => return None,
+        };
+        self.chars = ahead;
+        self.position = end;
+        Some((unit, exponent, end))
+    }
+
     fn eat_hex_digit(&mut self, initial_char: char) -> TokenResult {
-        let (integer_str, mut start, end) =
-            self.eat_while(Some(initial_char), |ch| ch.is_ascii_hexdigit() | (ch == 'x'));
+        let (raw_str, mut start, end) = self
+            .eat_while(Some(initial_char), |ch| ch.is_ascii_hexdigit() | (ch == 'x') | (ch == '_'));
+
+        start += 2;
+        let span = Span { start: start as usize, end: end as usize, file: None };
+
+        let integer_str = format!("0x{}", Self::strip_separators(&raw_str, &raw_str[2..], &span)?);
 
         // TODO: check for sure that we have a correct hex string, eg. 0x56 and not 0x56x34
         let kind = if self.context == Context::CodeTableBody {
@@ -503,12 +686,47 @@ impl<'a> Lexer<'a> {
                 TokenKind::Ident(integer_str)
             }
         } else {
-            TokenKind::Literal(str_to_bytes32(integer_str[2..].as_ref()))
+            // Builtins that pad, inject or hash the raw value keep the leading zeros written
+            let digits = &integer_str[2..];
+            TokenKind::Literal(str_to_bytes32(digits), digits.len().div_ceil(2))
         };
 
+        Ok(Token { kind, span })
+    }
+
+    fn eat_binary_digit(&mut self, initial_char: char) -> TokenResult {
+        let (raw_str, mut start, end) = self
+            .eat_while(Some(initial_char), |ch| ch.is_ascii_digit() | (ch == 'b') | (ch == '_'));
+
         start += 2;
         let span = Span { start: start as usize, end: end as usize, file: None };
-        Ok(Token { kind, span })
+        let binary_str = Self::strip_separators(&raw_str, &raw_str[2..], &span)?;
+        let literal = parse_binary_literal(&binary_str).ok_or_else(|| {
+            LexicalError::new(LexicalErrorKind::InvalidLiteral(raw_str.clone()), span.clone())
+        })?;
+
+        // Like decimals, builtin arguments keep numbers
+        if self.builtin_args {
+            let integer = usize::from_str_radix(&binary_str, 2).map_err(|_| {
+                LexicalError::new(LexicalErrorKind::InvalidLiteral(raw_str), span.clone())
+            })?;
+            return Ok(Token { kind: TokenKind::Num(integer), span })
+        }
+
+        Ok(Token { kind: TokenKind::Literal(literal, literal_width(&literal)), span })
+    }
+
+    /// Removes the `_` separators from the digits of a numeric literal, eg. `1_000_000`.
+    ///
+    /// Separators are only allowed between two digits.
+    fn strip_separators(raw: &str, digits: &str, span: &Span) -> Result<String, LexicalError> {
+        if digits.contains('_') && digits.split('_').any(str::is_empty) {
+            return Err(LexicalError::new(
+                LexicalErrorKind::InvalidLiteral(raw.to_string()),
+                span.clone(),
+            ))
+        }
+        Ok(digits.replace('_', ""))
     }
 
     /// Skips white space. They are not significant in the source language
@@ -547,6 +765,7 @@ impl<'a> Lexer<'a> {
             Some(TokenKind::Test) |
             Some(TokenKind::Function) |
             Some(TokenKind::Constant) |
+            Some(TokenKind::Immutable) |
             Some(TokenKind::Error) |
             Some(TokenKind::Event) |
             Some(TokenKind::JumpTable) |
@@ -627,6 +846,11 @@ impl<'a> Lexer<'a> {
                     // Then we should have an import path between quotes
                     if let Some(char) = peekable_source.peek() {
                         match char {
+                            '<' => {
+                                let import: String =
+                                    peekable_source.by_ref().take_while(|c| *c != '>').collect();
+                                imports.push(format!("{import}>"));
+                            }
                             '"' | '\'' => {
                                 peekable_source.next();
                                 let mut import = String::new();
@@ -654,6 +878,75 @@ impl<'a> Lexer<'a> {
     }
 }
 
+impl<'a> Lexer<'a> {
+    /// Removes the tokens of inactive conditional compilation blocks.
+    ///
+    /// An `#ifdef FLAG` block is kept if `FLAG` is in `defines`, an `#ifndef FLAG` block if it
+    /// isn't, and their `#else` blocks otherwise. Blocks can be nested.
+    ///
+    /// NOTE: `#include`s are resolved before this pass, so they are not affected by conditionals.
+    pub fn apply_conditionals(
+        tokens: Vec<Token>,
+        defines: &[String],
+    ) -> Result<Vec<Token>, LexicalError> {
+        // The open blocks, with whether they are active and have seen an `#else`
+        let mut blocks: Vec<(bool, bool, Span)> = vec![];
+        let mut kept = vec![];
+        let mut tokens = tokens.into_iter();
+
+        while let Some(token) = tokens.next() {
+            match token.kind {
+                TokenKind::IfDef | TokenKind::IfNDef => {
+                    let flag = tokens
+                        .find(|t| !matches!(t.kind, TokenKind::Whitespace | TokenKind::Comment(_)));
+                    let defined = match flag.map(|t| t.kind) {
+                        Some(TokenKind::Ident(flag)) => defines.contains(&flag),
+                        _ => {
+                            tracing::error!(target: "lexer", "MISSING FLAG AFTER \"{}\"", token.kind);
+                            return Err(LexicalError::new(
+                                LexicalErrorKind::MissingConditionalFlag(token.kind),
+                                token.span,
+                            ))
+                        }
+                    };
+                    blocks.push((defined == (token.kind == TokenKind::IfDef), false, token.span));
+                }
+                TokenKind::Else => match blocks.last_mut() {
+                    Some((active, seen_else, _)) if !*seen_else => {
+                        *active = !*active;
+                        *seen_else = true;
+                    }
+                    _ => {
+                        tracing::error!(target: "lexer", "UNMATCHED \"#else\"");
+                        return Err(LexicalError::new(
+                            LexicalErrorKind::UnmatchedDirective(token.kind),
+                            token.span,
+                        ))
+                    }
+                },
+                TokenKind::EndIf => {
+                    if blocks.pop().is_none() {
+                        tracing::error!(target: "lexer", "UNMATCHED \"#endif\"");
+                        return Err(LexicalError::new(
+                            LexicalErrorKind::UnmatchedDirective(token.kind),
+                            token.span,
+                        ))
+                    }
+                }
+                _ if blocks.iter().all(|(active, _, _)| *active) => kept.push(token),
+                _ => {}
+            }
+        }
+
+        if let Some((_, _, span)) = blocks.pop() {
+            tracing::error!(target: "lexer", "CONDITIONAL BLOCK WITHOUT \"#endif\"");
+            return Err(LexicalError::new(LexicalErrorKind::UnterminatedConditional, span))
+        }
+
+        Ok(kept)
+    }
+}
+
 impl<'a> Iterator for Lexer<'a> {
     type Item = TokenResult;
 
@@ -0,0 +1,285 @@
+//! ## Semantic Tokens
+//!
+//! Classifies the tokens of a source for semantic highlighting, eg. telling a macro invocation
+//! from a label reference, which are both identifiers to the lexer. The classification only
+//! looks at the surrounding tokens, so it also works on sources that don't parse.
+
+use crate::Lexer;
+use huff_utils::prelude::*;
+
+/// The class of a semantic token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SemanticTokenKind {
+    /// A keyword, eg. `#define` or `takes`
+    Keyword,
+    /// The name of a decorator, eg. `calldata`
+    Decorator,
+    /// An opcode
+    Opcode,
+    /// A macro or function, defined or invoked
+    Macro,
+    /// An ABI function
+    Function,
+    /// An event
+    Event,
+    /// A custom error
+    Error,
+    /// A constant
+    Constant,
+    /// A jump table or code table
+    Table,
+    /// A label, defined or referenced
+    Label,
+    /// A macro or ABI parameter, including argument calls, eg. `<value>`
+    Parameter,
+    /// A builtin function, eg. `__FUNC_SIG`
+    Builtin,
+    /// An EVM type
+    Type,
+    /// A hex or decimal literal
+    Literal,
+    /// A string literal
+    String,
+    /// A comment
+    Comment,
+}
+
+impl SemanticTokenKind {
+    /// Every kind, in the order of their legend for editors
+    pub const ALL: [SemanticTokenKind; 16] = [
+        SemanticTokenKind::Keyword,
+        SemanticTokenKind::Decorator,
+        SemanticTokenKind::Opcode,
+        SemanticTokenKind::Macro,
+        SemanticTokenKind::Function,
+        SemanticTokenKind::Event,
+        SemanticTokenKind::Error,
+        SemanticTokenKind::Constant,
+        SemanticTokenKind::Table,
+        SemanticTokenKind::Label,
+        SemanticTokenKind::Parameter,
+        SemanticTokenKind::Builtin,
+        SemanticTokenKind::Type,
+        SemanticTokenKind::Literal,
+        SemanticTokenKind::String,
+        SemanticTokenKind::Comment,
+    ];
+}
+
+/// A classified token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SemanticToken {
+    /// The class of the token
+    pub kind: SemanticTokenKind,
+    /// The span of the token, including the `0x` prefix of hex literals
+    pub span: Span,
+    /// Whether the token defines its symbol, eg. the name of a macro definition
+    pub definition: bool,
+}
+
+/// What a pair of parentheses encloses
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Parens {
+    /// The parameters of a definition
+    Params,
+    /// The arguments of a builtin function
+    Builtin(String),
+    /// Anything else, eg. the arguments of a macro invocation
+    Other,
+}
+
+/// Classifies the tokens of a source, in order
+///
+/// Whitespace and punctuation aren't classified, and tokens with lexical errors are skipped.
+pub fn semantic_tokens(source: &str) -> Vec<SemanticToken> {
+    let chars = source.chars().collect::<Vec<char>>();
+    let tokens = Lexer::new(source)
+        .filter_map(Result::ok)
+        .filter(|t| !matches!(t.kind, TokenKind::Whitespace | TokenKind::Eof))
+        .collect::<Vec<Token>>();
+
+    let mut classified = vec![];
+    let mut depth = 0_usize;
+    let mut parens: Vec<Parens> = vec![];
+    let mut definition: Option<TokenKind> = None;
+    let mut decorator = false;
+    let mut defined_name = false;
+    let mut previous: Option<&TokenKind> = None;
+
+    let mut next_kinds =
+        tokens.iter().filter(|t| !matches!(t.kind, TokenKind::Comment(_))).map(|t| &t.kind).skip(1);
+    for token in &tokens {
+        if let TokenKind::Comment(_) = token.kind {
+            classified.push(SemanticToken {
+                kind: SemanticTokenKind::Comment,
+                span: token.span.clone(),
+                definition: false,
+            });
+            continue
+        }
+        let next = next_kinds.next();
+        let mut span = token.span.clone();
+        let mut is_name = false;
+
+        let class = match &token.kind {
+            TokenKind::Define => {
+                definition = None;
+                Some((SemanticTokenKind::Keyword, false))
+            }
+            kind @ (TokenKind::Macro |
+            TokenKind::Fn |
+            TokenKind::Test |
+            TokenKind::Function |
+            TokenKind::Event |
+            TokenKind::Error |
+            TokenKind::Constant |
+            TokenKind::Immutable |
+            TokenKind::JumpTable |
+            TokenKind::JumpTablePacked |
+            TokenKind::CodeTable) => {
+                if previous == Some(&TokenKind::Define) {
+                    definition = Some(kind.clone());
+                }
+                Some((SemanticTokenKind::Keyword, false))
+            }
+            TokenKind::Include |
+            TokenKind::IfDef |
+            TokenKind::IfNDef |
+            TokenKind::Else |
+            TokenKind::EndIf |
+            TokenKind::Assert |
+            TokenKind::Takes |
+            TokenKind::Returns |
+            TokenKind::View |
+            TokenKind::Pure |
+            TokenKind::Payable |
+            TokenKind::NonPayable |
+            TokenKind::Indexed |
+            TokenKind::Calldata |
+            TokenKind::Memory |
+            TokenKind::Storage => Some((SemanticTokenKind::Keyword, false)),
+            TokenKind::FreeStoragePointer | TokenKind::BuiltinFunction(_) | TokenKind::Repeat => {
+                Some((SemanticTokenKind::Builtin, false))
+            }
+            TokenKind::Opcode(_) => Some((SemanticTokenKind::Opcode, false)),
+            TokenKind::PrimitiveType(_) | TokenKind::ArrayType(..) => {
+                Some((SemanticTokenKind::Type, false))
+            }
+            TokenKind::Literal(..) => {
+                // The spans of hex literals don't include their `0x` prefix
+                if span.start >= 2 && chars.get(span.start - 2..span.start) == Some(&['0', 'x'][..])
+                {
+                    span.start -= 2;
+                }
+                Some((SemanticTokenKind::Literal, false))
+            }
+            TokenKind::Num(_) => Some((SemanticTokenKind::Literal, false)),
+            TokenKind::Str(_) => Some((SemanticTokenKind::String, false)),
+            TokenKind::Label(_) => Some((SemanticTokenKind::Label, true)),
+            TokenKind::Ident(name) => {
+                let class = classify_ident(
+                    name,
+                    depth,
+                    parens.last(),
+                    definition.as_ref(),
+                    decorator,
+                    previous,
+                    next,
+                );
+                is_name = class.map_or(false, |(_, definition)| definition) && depth == 0;
+                class
+            }
+            TokenKind::OpenBrace => {
+                depth += 1;
+                None
+            }
+            TokenKind::CloseBrace => {
+                depth = depth.saturating_sub(1);
+                None
+            }
+            TokenKind::OpenParen => {
+                parens.push(match previous {
+                    _ if depth == 0 && defined_name => Parens::Params,
+                    Some(TokenKind::Returns)
+                        if depth == 0 && definition == Some(TokenKind::Function) =>
+                    {
+                        Parens::Params
+                    }
+                    Some(TokenKind::BuiltinFunction(name)) => Parens::Builtin(name.clone()),
+                    Some(TokenKind::Ident(name)) if name.starts_with("__") => {
+                        Parens::Builtin(name.clone())
+                    }
+                    _ => Parens::Other,
+                });
+                None
+            }
+            TokenKind::CloseParen => {
+                parens.pop();
+                None
+            }
+            TokenKind::OpenBracket => {
+                decorator = previous == Some(&TokenKind::Pound);
+                None
+            }
+            TokenKind::CloseBracket => {
+                decorator = false;
+                None
+            }
+            _ => None,
+        };
+
+        if let Some((kind, definition)) = class {
+            classified.push(SemanticToken { kind, span, definition });
+        }
+        defined_name = is_name;
+        previous = Some(&token.kind);
+    }
+    classified.sort_by_key(|t| t.span.start);
+    classified
+}
+
+/// Classifies an identifier by the tokens around it, `None` if it can't be told
+fn classify_ident(
+    name: &str,
+    depth: usize,
+    parens: Option<&Parens>,
+    definition: Option<&TokenKind>,
+    decorator: bool,
+    previous: Option<&TokenKind>,
+    next: Option<&TokenKind>,
+) -> Option<(SemanticTokenKind, bool)> {
+    let defined = depth == 0 && previous.is_some() && previous == definition;
+    let class = match (definition, parens) {
+        _ if decorator => (SemanticTokenKind::Decorator, false),
+        (Some(TokenKind::Macro | TokenKind::Fn | TokenKind::Test), _) if defined => {
+            (SemanticTokenKind::Macro, true)
+        }
+        (Some(TokenKind::Function), _) if defined => (SemanticTokenKind::Function, true),
+        (Some(TokenKind::Event), _) if defined => (SemanticTokenKind::Event, true),
+        (Some(TokenKind::Error), _) if defined => (SemanticTokenKind::Error, true),
+        (Some(TokenKind::Constant | TokenKind::Immutable), _) if defined => {
+            (SemanticTokenKind::Constant, true)
+        }
+        (Some(_), _) if defined => (SemanticTokenKind::Table, true),
+        (_, Some(Parens::Params)) => (SemanticTokenKind::Parameter, true),
+        _ if previous == Some(&TokenKind::OpenBracket) => (SemanticTokenKind::Constant, false),
+        _ if depth > 0 && previous == Some(&TokenKind::LeftAngle) => {
+            (SemanticTokenKind::Parameter, false)
+        }
+        _ if name.starts_with("__") => (SemanticTokenKind::Builtin, false),
+        _ if next == Some(&TokenKind::OpenParen) => (SemanticTokenKind::Macro, false),
+        (_, Some(Parens::Builtin(builtin))) => match builtin.as_str() {
+            "__FUNC_SIG" => (SemanticTokenKind::Function, false),
+            "__EVENT_HASH" => (SemanticTokenKind::Event, false),
+            "__ERROR" => (SemanticTokenKind::Error, false),
+            "__tablesize" | "__tablestart" => (SemanticTokenKind::Table, false),
+            _ => (SemanticTokenKind::Macro, false),
+        },
+        _ if depth > 0 => (SemanticTokenKind::Label, false),
+        _ if matches!(previous, Some(TokenKind::IfDef | TokenKind::IfNDef)) => {
+            (SemanticTokenKind::Constant, false)
+        }
+        _ => return None,
+    };
+    Some(class)
+}
@@ -0,0 +1,31 @@
+use huff_lexer::*;
+use huff_utils::prelude::*;
+
+#[test]
+fn lexes_assertion_comparisons() {
+    let source = "#assert [A] != 0x01 == <= >= < >";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer
+        .map(|x| x.unwrap())
+        .filter(|t| !matches!(t.kind, TokenKind::Whitespace))
+        .collect::<Vec<Token>>();
+
+    assert_eq!(
+        tokens,
+        vec![
+            Token::new(TokenKind::Assert, Span::new(0..6, None)),
+            Token::new(TokenKind::OpenBracket, Span::new(8..8, None)),
+            Token::new(TokenKind::Ident("A".to_string()), Span::new(9..9, None)),
+            Token::new(TokenKind::CloseBracket, Span::new(10..10, None)),
+            Token::new(TokenKind::NotEqual, Span::new(12..13, None)),
+            Token::new(TokenKind::Literal(str_to_bytes32("01"), 1), Span::new(17..18, None)),
+            Token::new(TokenKind::Equal, Span::new(20..21, None)),
+            Token::new(TokenKind::LessEqual, Span::new(23..24, None)),
+            Token::new(TokenKind::GreaterEqual, Span::new(26..27, None)),
+            Token::new(TokenKind::LeftAngle, Span::new(29..29, None)),
+            Token::new(TokenKind::RightAngle, Span::new(31..31, None)),
+            Token::new(TokenKind::Eof, Span::new(31..31, None)),
+        ]
+    );
+}
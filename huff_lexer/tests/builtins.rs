@@ -11,6 +11,7 @@ fn parses_builtin_function_in_macro_body() {
         "__EVENT_HASH",
         "__ERROR",
         "__RIGHTPAD",
+        "__LEFTPAD",
         "__CODECOPY_DYN_ARG",
     ];
 
@@ -83,6 +84,7 @@ fn fails_to_parse_builtin_outside_macro_body() {
         "__EVENT_HASH",
         "__ERROR",
         "__RIGHTPAD",
+        "__LEFTPAD",
         "__CODECOPY_DYN_ARG",
     ];
 
@@ -0,0 +1,71 @@
+use huff_lexer::*;
+use huff_utils::prelude::*;
+
+fn lex(source: &str) -> Vec<Token> {
+    Lexer::new(source).map(|x| x.unwrap()).collect()
+}
+
+fn kinds(tokens: Vec<Token>) -> Vec<TokenKind> {
+    tokens
+        .into_iter()
+        .map(|t| t.kind)
+        .filter(|k| !matches!(k, TokenKind::Whitespace | TokenKind::Eof))
+        .collect()
+}
+
+#[test]
+fn lexes_conditional_directives() {
+    let source = "#ifdef A #else #endif #ifndef B";
+    let tokens = kinds(lex(source));
+    assert_eq!(
+        tokens,
+        vec![
+            TokenKind::IfDef,
+            TokenKind::Ident("A".to_string()),
+            TokenKind::Else,
+            TokenKind::EndIf,
+            TokenKind::IfNDef,
+            TokenKind::Ident("B".to_string()),
+        ]
+    );
+    let tokens = lex(source);
+    assert_eq!(tokens[0].span, Span::new(0..5, None));
+    assert_eq!(&source[tokens[0].span.start..=tokens[0].span.end], "#ifdef");
+}
+
+#[test]
+fn applies_conditionals() {
+    let source = "#ifdef A 0x01 #ifndef B 0x02 #else 0x03 #endif #else 0x04 #endif 0x05";
+    let literal = |n: u8| {
+        let mut lit = [0u8; 32];
+        lit[31] = n;
+        TokenKind::Literal(lit, 1)
+    };
+
+    let tokens = Lexer::apply_conditionals(lex(source), &[]).unwrap();
+    assert_eq!(kinds(tokens), vec![literal(4), literal(5)]);
+
+    let tokens = Lexer::apply_conditionals(lex(source), &["A".to_string()]).unwrap();
+    assert_eq!(kinds(tokens), vec![literal(1), literal(2), literal(5)]);
+
+    let tokens =
+        Lexer::apply_conditionals(lex(source), &["A".to_string(), "B".to_string()]).unwrap();
+    assert_eq!(kinds(tokens), vec![literal(1), literal(3), literal(5)]);
+}
+
+#[test]
+fn fails_on_unbalanced_conditionals() {
+    let err = Lexer::apply_conditionals(lex("0x01 #endif"), &[]).unwrap_err();
+    assert_eq!(err.kind, LexicalErrorKind::UnmatchedDirective(TokenKind::EndIf));
+    assert_eq!(err.span, Span::new(5..10, None));
+
+    let err = Lexer::apply_conditionals(lex("#ifdef A #else #else #endif"), &[]).unwrap_err();
+    assert_eq!(err.kind, LexicalErrorKind::UnmatchedDirective(TokenKind::Else));
+
+    let err = Lexer::apply_conditionals(lex("#ifndef A 0x01"), &[]).unwrap_err();
+    assert_eq!(err.kind, LexicalErrorKind::UnterminatedConditional);
+    assert_eq!(err.span, Span::new(0..6, None));
+
+    let err = Lexer::apply_conditionals(lex("#ifdef 0x01 #endif"), &[]).unwrap_err();
+    assert_eq!(err.kind, LexicalErrorKind::MissingConditionalFlag(TokenKind::IfDef));
+}
@@ -54,7 +54,7 @@ fn parses_decorator() {
         let returns_span = Span::new(26..27, None);
         assert_eq!(
             unwrapped,
-            Token::new(TokenKind::Literal(str_to_bytes32("01")), returns_span.clone())
+            Token::new(TokenKind::Literal(str_to_bytes32("01"), 1), returns_span.clone())
         );
 
         // )
@@ -19,3 +19,24 @@ fn end_of_file() {
     assert!(lexer.eof);
     assert!(lexer.next().is_none());
 }
+
+#[test]
+fn truncated_sources_dont_panic() {
+    for source in [
+        "#",
+        "0",
+        "#define function f(uint256",
+        "#define function f(uint256[99999999999999999999])",
+    ] {
+        let results = Lexer::new(source).collect::<Vec<_>>();
+        assert_eq!(results.last().unwrap().as_ref().unwrap().kind, TokenKind::Eof);
+    }
+
+    let errors = Lexer::new("#define function f(uint256[99999999999999999999])")
+        .filter_map(Result::err)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        errors[0].kind,
+        LexicalErrorKind::InvalidArraySize("99999999999999999999".to_string())
+    );
+}
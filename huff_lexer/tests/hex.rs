@@ -9,7 +9,10 @@ fn parses_single_hex() {
 
     // The first and only token should be lexed as Literal(0xa57B)
     let tok = lexer.next().unwrap().unwrap();
-    assert_eq!(tok, Token::new(TokenKind::Literal(str_to_bytes32("a57B")), Span::new(2..5, None)));
+    assert_eq!(
+        tok,
+        Token::new(TokenKind::Literal(str_to_bytes32("a57B"), 2), Span::new(2..5, None))
+    );
 
     // We covered the whole source
     lexer.next();
@@ -24,13 +27,13 @@ fn parses_bool() {
 
     // The first token should be lexed as a Literal representing 0x00
     let tok = lexer.next().unwrap().unwrap();
-    assert_eq!(tok, Token::new(TokenKind::Literal(str_to_bytes32("0")), Span::new(0..4, None)));
+    assert_eq!(tok, Token::new(TokenKind::Literal(str_to_bytes32("0"), 1), Span::new(0..4, None)));
 
     let _ = lexer.next(); // Whitespace
 
     // The second token should be lexed as a Literal representing 0x01
     let tok = lexer.next().unwrap().unwrap();
-    assert_eq!(tok, Token::new(TokenKind::Literal(str_to_bytes32("1")), Span::new(6..9, None)));
+    assert_eq!(tok, Token::new(TokenKind::Literal(str_to_bytes32("1"), 1), Span::new(6..9, None)));
 
     // We covered the whole source
     lexer.next();
@@ -45,13 +48,24 @@ fn parses_odd_len_hex() {
 
     // The first and only token should be lexed as Literal(0x1)
     let tok = lexer.next().unwrap().unwrap();
-    assert_eq!(tok, Token::new(TokenKind::Literal(str_to_bytes32("1")), Span::new(2..2, None)));
+    assert_eq!(tok, Token::new(TokenKind::Literal(str_to_bytes32("1"), 1), Span::new(2..2, None)));
 
     // We covered the whole source
     lexer.next();
     assert!(lexer.eof);
 }
 
+#[test]
+fn keeps_the_written_width_of_hex() {
+    let source = "0x0001";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let mut lexer = Lexer::new(flattened_source.source);
+
+    // Leading zeros count towards the width of the literal
+    let tok = lexer.next().unwrap().unwrap();
+    assert_eq!(tok, Token::new(TokenKind::Literal(str_to_bytes32("1"), 2), Span::new(2..5, None)));
+}
+
 // TODO: This doesn't exactly belong here.
 #[test]
 fn converts_literal_to_hex_string() {
@@ -68,3 +82,32 @@ fn converts_literal_to_hex_string() {
         assert_eq!(format!("0x{source}"), bytes32_to_string(&str_to_bytes32(source), true));
     }
 }
+
+#[test]
+fn parses_hex_with_separators() {
+    let source = "0xffff_ffff";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let mut lexer = Lexer::new(flattened_source.source);
+
+    // Separators are stripped, the span covers them
+    let tok = lexer.next().unwrap().unwrap();
+    assert_eq!(
+        tok,
+        Token::new(TokenKind::Literal(str_to_bytes32("ffffffff"), 4), Span::new(2..10, None))
+    );
+
+    // We covered the whole source
+    lexer.next();
+    assert!(lexer.eof);
+}
+
+#[test]
+fn rejects_misplaced_hex_separators() {
+    for source in ["0x_ff", "0xff_", "0xff__ff"] {
+        let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+        let mut lexer = Lexer::new(flattened_source.source);
+
+        let err = lexer.next().unwrap().unwrap_err();
+        assert_eq!(err.kind, LexicalErrorKind::InvalidLiteral(source.to_string()));
+    }
+}
@@ -173,3 +173,45 @@ fn include_with_string_single_quote() {
     // We should have reached EOF now
     assert!(lexer.eof);
 }
+
+#[test]
+fn lexes_namespaced_identifiers() {
+    let source = "#include \"./math.huff\" as Math\n#define macro MAIN() = takes(0) returns(0) {\n[Math.ONE] Math.SAFE_ADD()\n}";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer
+        .into_iter()
+        .map(|x| x.unwrap())
+        .filter(|x| !matches!(x.kind, TokenKind::Whitespace))
+        .collect::<Vec<Token>>();
+
+    assert_eq!(tokens[2].kind, TokenKind::Ident("as".to_string()));
+    assert_eq!(tokens[3].kind, TokenKind::Ident("Math".to_string()));
+
+    let namespaced = tokens.iter().rev().find(|t| t.kind == TokenKind::OpenBracket).unwrap();
+    let start = tokens.iter().position(|t| t == namespaced).unwrap();
+    assert_eq!(tokens[start + 1].kind, TokenKind::Ident("Math.ONE".to_string()));
+    let span = &tokens[start + 1].span;
+    assert_eq!(&source[span.start..=span.end], "Math.ONE");
+    assert_eq!(tokens[start + 3].kind, TokenKind::Ident("Math.SAFE_ADD".to_string()));
+}
+
+#[test]
+fn lexes_stdlib_import() {
+    let source =
+        "#include <huffstd/math/SafeMath.huff>\n#define macro MAIN() = takes(0) returns(0) {}";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer
+        .into_iter()
+        .map(|x| x.unwrap())
+        .filter(|x| !matches!(x.kind, TokenKind::Whitespace))
+        .collect::<Vec<Token>>();
+
+    let path = "<huffstd/math/SafeMath.huff>";
+    assert_eq!(tokens[1].kind, TokenKind::Str(path.to_string()));
+    assert_eq!(&source[tokens[1].span.start..=tokens[1].span.end], path);
+    assert_eq!(tokens[2].kind, TokenKind::Define);
+
+    assert_eq!(Lexer::lex_imports(source), vec![path.to_string()]);
+}
@@ -32,3 +32,158 @@ fn lexes_large_numbers() {
     // We covered the whole source
     assert!(lexer.eof);
 }
+
+#[test]
+fn lexes_decimal_literals_in_macro_bodies() {
+    let source = "#define macro MAIN() = takes(0) returns(0) { 1000000 }";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.map(|x| x.unwrap()).collect::<Vec<Token>>();
+
+    // Takes and returns stay numbers
+    assert_eq!(tokens[12], Token::new(TokenKind::Num(0), Span::new(29..29, None)));
+
+    // Literals in the body are converted to their hex value
+    let start = source.find("1000000").unwrap();
+    assert_eq!(
+        tokens[tokens.len() - 4],
+        Token::new(
+            TokenKind::Literal(str_to_bytes32("0f4240"), 3),
+            Span::new(start..start + 6, None)
+        )
+    );
+}
+
+#[test]
+fn lexes_decimal_literals_in_builtin_args_as_numbers() {
+    let source = "#define macro MAIN() = takes(0) returns(0) { __RIGHTPAD(100) }";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.map(|x| x.unwrap()).collect::<Vec<Token>>();
+
+    let start = source.find("100").unwrap();
+    assert!(tokens.contains(&Token::new(TokenKind::Num(100), Span::new(start..start + 2, None))));
+}
+
+#[test]
+fn rejects_decimal_literals_exceeding_32_bytes() {
+    // 2^256 - 1, times ten
+    let too_large = format!(
+        "{}0",
+        "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+    );
+    let source = &format!("#define constant TOO_LARGE = {too_large}");
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+
+    let err = lexer.map(|x| x.map(|_| ())).find(|x| x.is_err()).unwrap().unwrap_err();
+    assert_eq!(err.kind, LexicalErrorKind::InvalidLiteral(too_large));
+}
+
+#[test]
+fn lexes_decimal_literals_with_separators() {
+    let source = "#define constant SUPPLY = 1_000_000";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.map(|x| x.unwrap()).collect::<Vec<Token>>();
+
+    let start = source.find("1_000_000").unwrap();
+    assert_eq!(
+        tokens[tokens.len() - 2],
+        Token::new(
+            TokenKind::Literal(str_to_bytes32("0f4240"), 3),
+            Span::new(start..start + 8, None)
+        )
+    );
+}
+
+#[test]
+fn lexes_binary_literals() {
+    let source = "0b1010_0101";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let mut lexer = Lexer::new(flattened_source.source);
+
+    // The span excludes the `0b` prefix, like hex literals
+    let tok = lexer.next().unwrap().unwrap();
+    assert_eq!(
+        tok,
+        Token::new(TokenKind::Literal(str_to_bytes32("a5"), 1), Span::new(2..10, None))
+    );
+
+    lexer.next();
+
+    // We covered the whole source
+    assert!(lexer.eof);
+}
+
+#[test]
+fn rejects_invalid_binary_literals() {
+    for source in ["0b102", "0b", &format!("0b1{}", "0".repeat(256))] {
+        let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+        let mut lexer = Lexer::new(flattened_source.source);
+
+        let err = lexer.next().unwrap().unwrap_err();
+        assert_eq!(err.kind, LexicalErrorKind::InvalidLiteral(source.to_string()));
+    }
+}
+
+#[test]
+fn lexes_ether_units_and_scientific_notation() {
+    let source = "#define macro MAIN() = takes(0) returns(0) { 1e18 5 gwei 2e3 ether 7 wei }";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer
+        .map(|x| x.unwrap())
+        .filter(|t| matches!(t.kind, TokenKind::Literal(..)))
+        .collect::<Vec<Token>>();
+
+    let span = |s: &str| {
+        let start = source.find(s).unwrap();
+        Span::new(start..start + s.len() - 1, None)
+    };
+    assert_eq!(
+        tokens,
+        vec![
+            Token::new(TokenKind::Literal(str_to_bytes32("0de0b6b3a7640000"), 8), span("1e18")),
+            Token::new(TokenKind::Literal(str_to_bytes32("012a05f200"), 5), span("5 gwei")),
+            Token::new(
+                TokenKind::Literal(str_to_bytes32("6c6b935b8bbd400000"), 9),
+                span("2e3 ether")
+            ),
+            Token::new(TokenKind::Literal(str_to_bytes32("07"), 1), span("7 wei")),
+        ]
+    );
+}
+
+#[test]
+fn rejects_overflowing_ether_units() {
+    let source = "#define constant TOO_LARGE = 1e60 ether";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+
+    let err = lexer.map(|x| x.map(|_| ())).find(|x| x.is_err()).unwrap().unwrap_err();
+    assert_eq!(err.kind, LexicalErrorKind::InvalidLiteral("1e60 ether".to_string()));
+}
+
+#[test]
+fn lexes_negative_literals() {
+    let source = "#define constant MINUS_ONE = -1\n#define constant MINUS_WORD = -0x20";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer
+        .map(|x| x.unwrap())
+        .filter(|t| matches!(t.kind, TokenKind::Literal(..)))
+        .collect::<Vec<Token>>();
+
+    // Encoded as two's complement, the span includes the sign
+    let mut minus_word = [0xff; 32];
+    minus_word[31] = 0xe0;
+    let start = source.find("-0x20").unwrap();
+    assert_eq!(
+        tokens,
+        vec![
+            Token::new(TokenKind::Literal([0xff; 32], 32), Span::new(29..30, None)),
+            Token::new(TokenKind::Literal(minus_word, 32), Span::new(start..start + 4, None)),
+        ]
+    );
+}
@@ -0,0 +1,121 @@
+use huff_lexer::semantic::{semantic_tokens, SemanticToken, SemanticTokenKind};
+
+const SOURCE: &str = r#"#define function transfer(address to, uint256 amount) nonpayable returns (bool success)
+#define constant OWNER = FREE_STORAGE_POINTER()
+
+// Reverts unless the caller is the owner
+#define macro ONLY_OWNER(error) = takes(0) returns(0) {
+    [OWNER] sload caller eq authorized jumpi
+    <error> 0x00 mstore
+    authorized:
+}
+
+#define macro MAIN() = takes(0) returns(0) {
+    __FUNC_SIG(transfer) ONLY_OWNER(0x01)
+}
+"#;
+
+/// The text and class of every token
+fn classes(source: &str) -> Vec<(String, SemanticTokenKind, bool)> {
+    let chars = source.chars().collect::<Vec<char>>();
+    semantic_tokens(source)
+        .into_iter()
+        .map(|SemanticToken { kind, span, definition }| {
+            (chars[span.start..=span.end].iter().collect(), kind, definition)
+        })
+        .collect()
+}
+
+#[test]
+fn classifies_identifiers_by_context() {
+    use SemanticTokenKind::*;
+
+    let classes = classes(SOURCE);
+    let class = |text: &str, nth: usize| {
+        classes
+            .iter()
+            .filter(|(t, ..)| t == text)
+            .nth(nth)
+            .map(|(_, kind, definition)| (*kind, *definition))
+            .unwrap_or_else(|| panic!("No token {text}"))
+    };
+
+    assert_eq!(class("#define", 0), (Keyword, false));
+    assert_eq!(class("transfer", 0), (Function, true));
+    assert_eq!(class("address", 0), (Type, false));
+    assert_eq!(class("to", 0), (Parameter, true));
+    assert_eq!(class("success", 0), (Parameter, true));
+    assert_eq!(class("OWNER", 0), (Constant, true));
+    assert_eq!(class("FREE_STORAGE_POINTER()", 0), (Builtin, false));
+    assert_eq!(class("// Reverts unless the caller is the owner", 0), (Comment, false));
+    assert_eq!(class("ONLY_OWNER", 0), (Macro, true));
+    assert_eq!(class("error", 0), (Parameter, true));
+    assert_eq!(class("0", 0), (Literal, false));
+    assert_eq!(class("OWNER", 1), (Constant, false));
+    assert_eq!(class("sload", 0), (Opcode, false));
+    assert_eq!(class("authorized", 0), (Label, false));
+    assert_eq!(class("error", 1), (Parameter, false));
+    assert_eq!(class("0x00", 0), (Literal, false));
+    assert_eq!(class("authorized", 1), (Label, true));
+    assert_eq!(class("MAIN", 0), (Macro, true));
+    assert_eq!(class("__FUNC_SIG", 0), (Builtin, false));
+    assert_eq!(class("transfer", 1), (Function, false));
+    assert_eq!(class("ONLY_OWNER", 1), (Macro, false));
+    assert_eq!(class("0x01", 0), (Literal, false));
+
+    // Punctuation isn't classified
+    assert!(classes
+        .iter()
+        .all(|(t, ..)| !["(", ")", "{", "}", "[", "]", ":"].contains(&t.as_str())));
+}
+
+#[test]
+fn classifies_tables_and_decorators() {
+    use SemanticTokenKind::*;
+
+    let source = r#"#define jumptable SWITCH {
+    zero one
+}
+
+#define macro DISPATCH() = takes(0) returns(0) {
+    __tablesize(SWITCH) __tablestart(SWITCH)
+    zero: one:
+}
+
+#[calldata("0x01")]
+#define test TEST_DISPATCH() = {
+    DISPATCH()
+}
+"#;
+    let found = classes(source)
+        .into_iter()
+        .filter(|(_, kind, _)| !matches!(kind, Keyword | Literal))
+        .map(|(text, kind, definition)| (text, kind, definition))
+        .collect::<Vec<_>>();
+    let expected = [
+        ("SWITCH", Table, true),
+        ("zero", Label, false),
+        ("one", Label, false),
+        ("DISPATCH", Macro, true),
+        ("__tablesize", Builtin, false),
+        ("SWITCH", Table, false),
+        ("__tablestart", Builtin, false),
+        ("SWITCH", Table, false),
+        ("zero", Label, true),
+        ("one", Label, true),
+        ("calldata", Decorator, false),
+        ("\"0x01\"", String, false),
+        ("TEST_DISPATCH", Macro, true),
+        ("DISPATCH", Macro, false),
+    ]
+    .map(|(text, kind, definition)| (text.to_string(), kind, definition));
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn skips_lexical_errors() {
+    let source = "#define macro MAIN() = takes(0) returns(0) {\n    $ 0x01 add\n}";
+    let classes = classes(source);
+    assert!(classes.contains(&("0x01".to_string(), SemanticTokenKind::Literal, false)));
+    assert!(classes.contains(&("add".to_string(), SemanticTokenKind::Opcode, false)));
+}
@@ -0,0 +1,55 @@
+//! ## Lint Configuration
+//!
+//! Reads the severities of lint rules from the `[lints]` table of a `huff.toml` file.
+//!
+//! ```toml
+//! [lints]
+//! unused-macros = "error"
+//! stack-comments = "warn"
+//! ```
+
+use huff_utils::prelude::Severity;
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// The name of the configuration file
+pub const CONFIG_FILE: &str = "huff.toml";
+
+/// The severities of lint rules, by rule id
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LintConfig {
+    /// The configured severities, by rule id
+    pub severities: BTreeMap<String, Severity>,
+}
+
+impl LintConfig {
+    /// Reads the `huff.toml` file in `root`, defaulting to an empty configuration if it doesn't
+    /// exist
+    pub fn load(root: &str) -> Result<Self, String> {
+        let path = Path::new(root).join(CONFIG_FILE);
+        if !path.exists() {
+            tracing::debug!(target: "lints", "NO \"{}\" FOUND IN \"{}\"", CONFIG_FILE, root);
+            return Ok(Self::default())
+        }
+        let data = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read \"{}\": {e}", path.display()))?;
+        Self::parse(&data).map_err(|e| format!("Invalid \"{}\": {e}", path.display()))
+    }
+
+    /// Parses the `[lints]` table of a `huff.toml` file's contents
+    pub fn parse(data: &str) -> Result<Self, String> {
+        let toml = data.parse::<toml::Value>().map_err(|e| e.to_string())?;
+        let Some(lints) = toml.get("lints") else { return Ok(Self::default()) };
+        let lints = lints.as_table().ok_or_else(|| "\"lints\" must be a table".to_string())?;
+        let severities = lints
+            .iter()
+            .map(|(id, severity)| {
+                let severity = severity
+                    .as_str()
+                    .ok_or_else(|| format!("The severity of \"{id}\" must be a string"))?
+                    .parse::<Severity>()?;
+                Ok((id.clone(), severity))
+            })
+            .collect::<Result<BTreeMap<String, Severity>, String>>()?;
+        Ok(Self { severities })
+    }
+}
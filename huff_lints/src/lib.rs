@@ -0,0 +1,144 @@
+#![doc = include_str!("../README.md")]
+#![warn(missing_docs)]
+#![warn(unused_extern_crates)]
+#![forbid(unsafe_code)]
+#![forbid(where_clauses_object_safety)]
+
+use huff_utils::prelude::*;
+use std::{collections::BTreeMap, fmt, sync::Arc};
+
+pub mod config;
+pub mod rules;
+pub mod suppressions;
+
+use config::LintConfig;
+use suppressions::Suppressions;
+
+/// A lint rule
+pub trait Lint: Send + Sync {
+    /// The unique id of the rule, e.g. `unused-macros`, used to configure its severity
+    fn id(&self) -> &'static str;
+
+    /// A short description of what the rule reports
+    fn description(&self) -> &'static str;
+
+    /// The severity the rule is reported with unless configured otherwise
+    fn default_severity(&self) -> Severity {
+        Severity::Warn
+    }
+
+    /// Checks a contract, returning the warnings with spans in the flattened source
+    fn check(&self, cx: &LintContext) -> Vec<CompilerWarning>;
+}
+
+/// The contract a lint rule checks
+#[derive(Debug, Clone, Copy)]
+pub struct LintContext<'a> {
+    /// The parsed contract, before its constant expressions are evaluated
+    pub contract: &'a Contract,
+    /// The flattened source the contract is parsed from
+    pub source: &'a str,
+    /// The tokens of the flattened source, including comments
+    pub tokens: &'a [Token],
+    /// The name of the main macro
+    pub main: &'a str,
+    /// The names of the macros compiled into the bytecode directly, e.g. the main and
+    /// constructor macros
+    pub entry_macros: &'a [&'a str],
+    /// The maximum depth of nested macro invocations
+    pub max_expansion_depth: usize,
+}
+
+/// Runs the registered lint rules with their configured severities
+#[derive(Clone)]
+pub struct Linter {
+    /// The registered rules, run in order
+    rules: Vec<Arc<dyn Lint>>,
+    /// The severities configured by rule id
+    severities: BTreeMap<&'static str, Severity>,
+}
+
+impl Default for Linter {
+    /// Creates a linter with the builtin rules
+    fn default() -> Self {
+        let mut linter = Self::new();
+        rules::builtin_rules().into_iter().for_each(|rule| linter.register(rule));
+        linter
+    }
+}
+
+impl fmt::Debug for Linter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.rules.iter().map(|r| (r.id(), self.severity(r.id())))).finish()
+    }
+}
+
+impl Linter {
+    /// Creates a linter without any rules
+    pub fn new() -> Self {
+        Self { rules: vec![], severities: BTreeMap::new() }
+    }
+
+    /// Registers a rule, replacing any rule registered with the same id
+    pub fn register(&mut self, rule: Arc<dyn Lint>) {
+        match self.rules.iter_mut().find(|r| r.id() == rule.id()) {
+            Some(registered) => *registered = rule,
+            None => self.rules.push(rule),
+        }
+    }
+
+    /// Gets the registered rules
+    pub fn rules(&self) -> &[Arc<dyn Lint>] {
+        &self.rules
+    }
+
+    /// Gets the severity of a rule, `None` if no rule is registered with the id
+    pub fn severity(&self, id: &str) -> Option<Severity> {
+        let rule = self.rules.iter().find(|r| r.id() == id)?;
+        Some(self.severities.get(rule.id()).copied().unwrap_or_else(|| rule.default_severity()))
+    }
+
+    /// Sets the severity of a rule, failing if no rule is registered with the id
+    pub fn set_severity(&mut self, id: &str, severity: Severity) -> Result<(), String> {
+        let rule = self
+            .rules
+            .iter()
+            .find(|r| r.id() == id)
+            .ok_or_else(|| format!("Unknown lint \"{id}\""))?;
+        self.severities.insert(rule.id(), severity);
+        Ok(())
+    }
+
+    /// Applies the severities of a lint configuration, failing on the first unknown rule
+    pub fn configure(&mut self, config: &LintConfig) -> Result<(), String> {
+        config.severities.iter().try_for_each(|(id, severity)| self.set_severity(id, *severity))
+    }
+
+    /// Runs the rules that aren't allowed, skipping warnings allowed by inline comments
+    ///
+    /// Returns the warnings, tagged with the id of their rule, or the first warning of a rule
+    /// configured as an error.
+    pub fn run(&self, cx: &LintContext) -> Result<Vec<CompilerWarning>, CompilerWarning> {
+        let suppressions = Suppressions::new(cx.tokens, cx.source);
+        let mut warnings = vec![];
+        for rule in self.rules.iter() {
+            let severity = self.severity(rule.id()).unwrap_or_default();
+            if severity == Severity::Allow {
+                continue
+            }
+            for mut warning in rule.check(cx) {
+                if suppressions.allows(rule.id(), &warning.span) {
+                    continue
+                }
+                warning.lint = Some(rule.id());
+                if severity == Severity::Error {
+                    tracing::error!(target: "lints", "LINT [{}] FAILED: {:?}", rule.id(), warning.kind);
+                    return Err(warning)
+                }
+                tracing::warn!(target: "lints", "LINT [{}]: {:?}", rule.id(), warning.kind);
+                warnings.push(warning);
+            }
+        }
+        Ok(warnings)
+    }
+}
@@ -0,0 +1,54 @@
+use ethers_core::{types::Address, utils::to_checksum};
+use huff_utils::prelude::*;
+
+use crate::{Lint, LintContext};
+
+/// Reports address literals with an invalid checksum, see [check_address_checksums]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AddressChecksum;
+
+impl Lint for AddressChecksum {
+    fn id(&self) -> &'static str {
+        "address-checksum"
+    }
+
+    fn description(&self) -> &'static str {
+        "Mixed-case address literals with an invalid EIP-55 checksum"
+    }
+
+    fn check(&self, cx: &LintContext) -> Vec<CompilerWarning> {
+        check_address_checksums(cx.tokens, cx.source)
+    }
+}
+
+/// Validates the EIP-55 checksums of address literals
+///
+/// Only 20 byte hex literals mixing upper and lower case letters are checked, all lower or
+/// upper case addresses carry no checksum.
+pub fn check_address_checksums(tokens: &[Token], source: &str) -> Vec<CompilerWarning> {
+    tokens
+        .iter()
+        .filter(|t| matches!(t.kind, TokenKind::Literal(..)))
+        .filter_map(|t| {
+            let found = source.get(t.span.start..=t.span.end)?;
+            if t.span.start < 2 ||
+                source.get(t.span.start - 2..t.span.start) != Some("0x") ||
+                found.len() != 40 ||
+                !found.chars().all(|c| c.is_ascii_hexdigit()) ||
+                !found.chars().any(|c| c.is_ascii_uppercase()) ||
+                !found.chars().any(|c| c.is_ascii_lowercase())
+            {
+                return None
+            }
+            let address = found.parse::<Address>().ok()?;
+            let expected = to_checksum(&address, None);
+            let found = format!("0x{found}");
+            (found != expected).then(|| {
+                CompilerWarning::new(
+                    WarningKind::InvalidAddressChecksum(found, expected),
+                    AstSpan(vec![t.span.clone()]),
+                )
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,45 @@
+use ethers_core::utils::hex;
+use huff_utils::prelude::*;
+
+use crate::{Lint, LintContext};
+
+/// Reports events sharing the same topic, see [check_event_topics]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EventTopicCollisions;
+
+impl Lint for EventTopicCollisions {
+    fn id(&self) -> &'static str {
+        "event-topic-collisions"
+    }
+
+    fn description(&self) -> &'static str {
+        "Events defined more than once with the same topic"
+    }
+
+    fn check(&self, cx: &LintContext) -> Vec<CompilerWarning> {
+        check_event_topics(cx.contract)
+    }
+}
+
+/// Finds events sharing the same topic
+///
+/// Topics are hashes of the event signatures, so they only collide for events defined more
+/// than once, e.g. with differently indexed parameters, which makes their logs ambiguous.
+pub fn check_event_topics(contract: &Contract) -> Vec<CompilerWarning> {
+    contract
+        .events
+        .iter()
+        .enumerate()
+        .filter_map(|(i, event)| {
+            let first = contract.events[..i].iter().find(|e| e.hash == event.hash)?;
+            let topic = format!("0x{}", hex::encode(event.hash));
+            tracing::warn!(target: "lints", "EVENT TOPIC COLLISION: \"{}\" {}", event.name, topic);
+            Some(CompilerWarning::new(
+                WarningKind::EventTopicCollision(event.name.clone(), topic),
+                AstSpan(
+                    first.span.0.first().into_iter().chain(event.span.0.first()).cloned().collect(),
+                ),
+            ))
+        })
+        .collect()
+}
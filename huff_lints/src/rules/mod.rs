@@ -0,0 +1,32 @@
+//! ## Builtin Lint Rules
+
+use std::sync::Arc;
+
+use crate::Lint;
+
+mod address_checksum;
+mod event_topics;
+mod stack_analysis;
+mod stack_comments;
+mod stack_effects;
+mod unused;
+
+pub use address_checksum::*;
+pub use event_topics::*;
+pub use stack_analysis::*;
+pub use stack_comments::*;
+pub use stack_effects::*;
+pub use unused::*;
+
+/// Gets the builtin rules, in the order they're run
+pub fn builtin_rules() -> Vec<Arc<dyn Lint>> {
+    vec![
+        Arc::new(AddressChecksum),
+        Arc::new(EventTopicCollisions),
+        Arc::new(UnusedMacros),
+        Arc::new(UnusedConstants),
+        Arc::new(StackComments),
+        Arc::new(StackEffects),
+        Arc::new(StackAnalysis),
+    ]
+}
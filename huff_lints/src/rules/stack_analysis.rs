@@ -0,0 +1,271 @@
+use std::collections::BTreeSet;
+
+use huff_utils::prelude::*;
+
+use crate::{Lint, LintContext};
+
+/// The maximum height of the EVM stack
+pub const MAX_STACK_HEIGHT: usize = 1024;
+
+/// Reports stack underflows and overflows in the main macro, see [check_stack_heights]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StackAnalysis;
+
+impl Lint for StackAnalysis {
+    fn id(&self) -> &'static str {
+        "stack-analysis"
+    }
+
+    fn description(&self) -> &'static str {
+        "Stack underflows and overflows in the main macro"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Allow
+    }
+
+    fn check(&self, cx: &LintContext) -> Vec<CompilerWarning> {
+        check_stack_heights(cx.contract, cx.main, cx.max_expansion_depth)
+    }
+}
+
+/// An instruction of the expanded macro
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum InstructionKind {
+    /// Pushes a value
+    Push,
+    /// Pushes the position of a label, with the invocation path it's resolved from
+    PushLabel(String, Vec<usize>),
+    /// An opcode
+    Opcode(Opcode),
+    /// An outlined macro, taking and returning a fixed number of items
+    Outlined(usize, usize),
+    /// A label definition, with the invocation path it's defined in
+    Label(String, Vec<usize>),
+    /// An instruction with an unknown stack effect, e.g. `__VERBATIM`
+    Unknown,
+}
+
+/// An instruction of the expanded macro, with the span of its statement
+struct Instruction {
+    kind: InstructionKind,
+    span: AstSpan,
+}
+
+/// A macro argument, resolved in the scope of the invoking macro
+#[derive(Clone)]
+enum ResolvedArg<'a> {
+    Push,
+    Opcode(Opcode),
+    Label(String, Vec<usize>),
+    Macro(&'a MacroInvocation, Vec<(String, ResolvedArg<'a>)>, Vec<usize>),
+    Unknown,
+}
+
+/// The state of a macro expansion
+struct Expansion<'a> {
+    contract: &'a Contract,
+    instructions: Vec<Instruction>,
+    invocations: usize,
+    max_depth: usize,
+}
+
+/// Simulates the stack height over the control flow of a macro, reporting underflows and
+/// overflows
+///
+/// The macro is expanded with all of its invocations and arguments, and every path through it
+/// is followed from an empty stack. Jumps to labels pushed right before the `jump` or `jumpi`
+/// are followed, with the label resolved like codegen does, in the nearest invocation defining
+/// it. Paths end at jumps to unknown destinations, at terminating opcodes and at statements
+/// with an unknown stack effect, like `__VERBATIM` and `__REPEAT`.
+pub fn check_stack_heights(
+    contract: &Contract,
+    macro_name: &str,
+    max_depth: usize,
+) -> Vec<CompilerWarning> {
+    let Some(macro_def) = contract.find_macro_by_name(macro_name) else { return vec![] };
+    let mut expansion = Expansion { contract, instructions: vec![], invocations: 0, max_depth };
+    expansion.expand_statements(&macro_def.statements, &[], &[0], 1);
+    let instructions = expansion.instructions;
+
+    let mut warnings: Vec<(usize, CompilerWarning)> = vec![];
+    let mut report = |index: usize, kind: WarningKind| {
+        if warnings.iter().all(|(i, _)| *i != index) {
+            tracing::warn!(target: "lints", "{:?} IN MACRO \"{}\"", kind, macro_name);
+            warnings.push((index, CompilerWarning::new(kind, instructions[index].span.clone())));
+        }
+    };
+
+    let mut visited = BTreeSet::new();
+    let mut queue = vec![(0, 0)];
+    while let Some((index, height)) = queue.pop() {
+        if index >= instructions.len() || !visited.insert((index, height)) {
+            continue
+        }
+        let (takes, returns) = match &instructions[index].kind {
+            InstructionKind::Push | InstructionKind::PushLabel(..) => (0, 1),
+            InstructionKind::Opcode(o) => o.stack_effect(),
+            InstructionKind::Outlined(takes, returns) => (*takes, *returns),
+            InstructionKind::Label(..) => (0, 0),
+            InstructionKind::Unknown => continue,
+        };
+        if height < takes {
+            report(index, WarningKind::StackUnderflow(takes, height));
+            continue
+        }
+        let height = height - takes + returns;
+        if height > MAX_STACK_HEIGHT {
+            report(index, WarningKind::StackOverflow(height));
+            continue
+        }
+
+        match &instructions[index].kind {
+            InstructionKind::Opcode(o @ (Opcode::Jump | Opcode::Jumpi)) => {
+                let target = index
+                    .checked_sub(1)
+                    .and_then(|i| resolve_label(&instructions, &instructions[i].kind));
+                if let Some(target) = target {
+                    queue.push((target, height));
+                }
+                if *o == Opcode::Jumpi {
+                    queue.push((index + 1, height));
+                }
+            }
+            InstructionKind::Opcode(o) if o.is_terminating() => {}
+            _ => queue.push((index + 1, height)),
+        }
+    }
+
+    warnings.sort_by_key(|(i, _)| *i);
+    warnings.into_iter().map(|(_, w)| w).collect()
+}
+
+/// Resolves a pushed label to the index of its definition in the nearest invocation
+fn resolve_label(instructions: &[Instruction], pushed: &InstructionKind) -> Option<usize> {
+    let InstructionKind::PushLabel(name, path) = pushed else { return None };
+    instructions
+        .iter()
+        .enumerate()
+        .filter_map(|(i, instruction)| match &instruction.kind {
+            InstructionKind::Label(label, def_path) if label == name => {
+                let common = path.iter().zip(def_path.iter()).take_while(|(a, b)| a == b).count();
+                Some((common, i))
+            }
+            _ => None,
+        })
+        .max_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)))
+        .map(|(_, i)| i)
+}
+
+impl<'a> Expansion<'a> {
+    /// Expands a list of statements into instructions
+    fn expand_statements(
+        &mut self,
+        statements: &'a [Statement],
+        args: &[(String, ResolvedArg<'a>)],
+        path: &[usize],
+        depth: usize,
+    ) {
+        for statement in statements {
+            let kind = match &statement.ty {
+                StatementType::Label(label) => {
+                    self.push(InstructionKind::Label(label.name.clone(), path.to_vec()), statement);
+                    self.expand_statements(&label.inner, args, path, depth);
+                    continue
+                }
+                // The value of a push opcode is accounted for by the literal following it
+                StatementType::Opcode(o) if o.is_value_push() => continue,
+                StatementType::Opcode(o) => InstructionKind::Opcode(*o),
+                StatementType::Literal(_) | StatementType::Constant(_) => InstructionKind::Push,
+                StatementType::LabelCall(label) => {
+                    InstructionKind::PushLabel(label.clone(), path.to_vec())
+                }
+                StatementType::BuiltinFunctionCall(b) => match b.kind {
+                    BuiltinFunctionKind::Verbatim |
+                    BuiltinFunctionKind::DynConstructorArg |
+                    BuiltinFunctionKind::Custom(_) => {
+                        InstructionKind::Unknown
+                    }
+                    _ => InstructionKind::Push,
+                },
+                StatementType::ArgCall(name) => {
+                    match args.iter().find(|(param, _)| param == name).map(|(_, arg)| arg) {
+                        Some(ResolvedArg::Push) => InstructionKind::Push,
+                        Some(ResolvedArg::Opcode(o)) => InstructionKind::Opcode(*o),
+                        Some(ResolvedArg::Label(label, scope)) => {
+                            InstructionKind::PushLabel(label.clone(), scope.clone())
+                        }
+                        Some(ResolvedArg::Macro(mi, mi_args, scope)) => {
+                            let (mi, mi_args, scope) = (*mi, mi_args.clone(), scope.clone());
+                            self.expand_invocation(mi, statement, &mi_args, &scope, depth);
+                            continue
+                        }
+                        Some(ResolvedArg::Unknown) | None => InstructionKind::Unknown,
+                    }
+                }
+                StatementType::MacroInvocation(mi) => {
+                    self.expand_invocation(mi, statement, args, path, depth);
+                    continue
+                }
+                StatementType::Code(_) | StatementType::Repeat(_) => InstructionKind::Unknown,
+            };
+            self.push(kind, statement);
+        }
+    }
+
+    /// Expands a macro invocation, resolving its arguments in the scope of the invoking macro
+    fn expand_invocation(
+        &mut self,
+        invocation: &'a MacroInvocation,
+        statement: &Statement,
+        args: &[(String, ResolvedArg<'a>)],
+        path: &[usize],
+        depth: usize,
+    ) {
+        let Some(invoked) = self.contract.find_macro_by_name(&invocation.macro_name) else {
+            return self.push(InstructionKind::Unknown, statement)
+        };
+        if invoked.outlined {
+            return self.push(InstructionKind::Outlined(invoked.takes, invoked.returns), statement)
+        }
+        if depth >= self.max_depth {
+            return self.push(InstructionKind::Unknown, statement)
+        }
+
+        let resolved = invoked
+            .parameters
+            .iter()
+            .zip(invocation.args.iter())
+            .filter_map(|(param, arg)| {
+                let resolved = match arg {
+                    MacroArg::Literal(_) => ResolvedArg::Push,
+                    MacroArg::Opcode(o) => ResolvedArg::Opcode(*o),
+                    MacroArg::Ident(ident) => {
+                        if self.contract.constants.lock().unwrap().iter().any(|c| &c.name == ident)
+                        {
+                            ResolvedArg::Push
+                        } else {
+                            ResolvedArg::Label(ident.clone(), path.to_vec())
+                        }
+                    }
+                    MacroArg::ArgCall(name) => args
+                        .iter()
+                        .find(|(param, _)| param == name)
+                        .map_or(ResolvedArg::Unknown, |(_, arg)| arg.clone()),
+                    MacroArg::MacroCall(mi) => ResolvedArg::Macro(mi, args.to_vec(), path.to_vec()),
+                };
+                Some((param.name.clone()?, resolved))
+            })
+            .collect::<Vec<(String, ResolvedArg)>>();
+
+        self.invocations += 1;
+        let mut invoked_path = path.to_vec();
+        invoked_path.push(self.invocations);
+        self.expand_statements(&invoked.statements, &resolved, &invoked_path, depth + 1);
+    }
+
+    /// Adds an instruction, with the span of its statement
+    fn push(&mut self, kind: InstructionKind, statement: &Statement) {
+        self.instructions.push(Instruction { kind, span: statement.span.clone() });
+    }
+}
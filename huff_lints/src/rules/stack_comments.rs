@@ -0,0 +1,209 @@
+use huff_utils::prelude::*;
+
+use crate::{Lint, LintContext};
+
+/// Reports stack comments not matching the simulated stack, see [check_stack_comments]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StackComments;
+
+impl Lint for StackComments {
+    fn id(&self) -> &'static str {
+        "stack-comments"
+    }
+
+    fn description(&self) -> &'static str {
+        "Stack comments not matching the simulated stack"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Allow
+    }
+
+    fn check(&self, cx: &LintContext) -> Vec<CompilerWarning> {
+        check_stack_comments(cx.contract, cx.tokens)
+    }
+}
+
+/// A step of a macro body, in source order
+enum Step<'a> {
+    /// A statement with a known stack effect
+    Statement(&'a Statement),
+    /// A position where the stack can't be simulated anymore, e.g. a label that may be jumped to
+    Reset(usize),
+}
+
+/// Validates the stack comments of every macro against their simulated stack effects
+///
+/// Stack comments are line comments starting with the stack state enclosed in brackets, top
+/// first, e.g. `// [a, b]`. Each macro starts with `takes` unnamed items, and the stack is
+/// simulated statement by statement. Opcodes and macro invocations consume and produce items
+/// according to their stack effects, while `dup` and `swap` opcodes move named items around.
+/// A comment is flagged if its length differs from the simulated stack or if it renames a
+/// duplicated or swapped item. The stack is then taken from the comment, so one mismatch
+/// isn't reported again by every following comment.
+///
+/// Where the stack can't be simulated, e.g. after labels, argument calls or terminating
+/// opcodes, the next comment is trusted as is.
+pub fn check_stack_comments(contract: &Contract, comments: &[Token]) -> Vec<CompilerWarning> {
+    let comments = comments
+        .iter()
+        .filter_map(|t| match &t.kind {
+            TokenKind::Comment(c) => Some((parse_stack_comment(c)?, &t.span)),
+            _ => None,
+        })
+        .collect::<Vec<(Vec<String>, &Span)>>();
+
+    let mut warnings = vec![];
+    for macro_def in contract.macros.iter() {
+        let (Some(start), Some(end)) = (
+            macro_def.span.0.iter().map(|s| s.start).min(),
+            macro_def.span.0.iter().map(|s| s.end).max(),
+        ) else {
+            continue
+        };
+        let mut comments =
+            comments.iter().filter(|(_, s)| s.start > start && s.end <= end).peekable();
+
+        let mut steps = vec![];
+        flatten_steps(&macro_def.statements, &mut steps);
+
+        let mut stack: Option<Vec<Option<String>>> = Some(vec![None; macro_def.takes]);
+        let mut check = |stack: &mut Option<Vec<Option<String>>>,
+                         (names, span): &(Vec<String>, &Span)| {
+            if let Some(simulated) = stack {
+                let matches = simulated.len() == names.len() &&
+                    simulated
+                        .iter()
+                        .zip(names.iter())
+                        .all(|(s, n)| s.as_ref().map_or(true, |s| s == n));
+                if !matches {
+                    tracing::warn!(target: "lints", "STACK COMMENT MISMATCH IN MACRO \"{}\"", macro_def.name);
+                    warnings.push(CompilerWarning::new(
+                        WarningKind::StackCommentMismatch(
+                            format_stack(names.iter().map(String::as_str)),
+                            format_stack(simulated.iter().map(|s| s.as_deref().unwrap_or("_"))),
+                        ),
+                        AstSpan(vec![(*span).clone()]),
+                    ));
+                }
+            }
+            *stack = Some(names.iter().cloned().map(Some).collect());
+        };
+
+        for step in steps {
+            let position = match step {
+                Step::Statement(s) => span_start(&s.span),
+                Step::Reset(position) => position,
+            };
+            while let Some(comment) = comments.next_if(|(_, s)| s.start < position) {
+                check(&mut stack, comment);
+            }
+            stack = match step {
+                Step::Statement(s) => {
+                    stack.and_then(|stack| simulate_statement(contract, s, stack))
+                }
+                Step::Reset(_) => None,
+            };
+        }
+        for comment in comments {
+            check(&mut stack, comment);
+        }
+    }
+    warnings
+}
+
+/// Parses the stack of a stack comment, e.g. `// [a, b] comment` into `["a", "b"]`
+fn parse_stack_comment(comment: &str) -> Option<Vec<String>> {
+    let stack = comment.strip_prefix("//")?.trim_start().strip_prefix('[')?;
+    let stack = &stack[..stack.find(']')?];
+    if stack.trim().is_empty() {
+        return Some(vec![])
+    }
+    Some(stack.split(',').map(|item| item.trim().to_string()).collect())
+}
+
+/// Formats a stack as written in stack comments
+fn format_stack<'a>(items: impl Iterator<Item = &'a str>) -> String {
+    format!("[{}]", items.collect::<Vec<&str>>().join(", "))
+}
+
+/// Gets the start of a span, the earliest start of its parts
+fn span_start(span: &AstSpan) -> usize {
+    span.0.iter().map(|s| s.start).min().unwrap_or_default()
+}
+
+/// Flattens the statements of a macro body, including those nested in labels and loops
+fn flatten_steps<'a>(statements: &'a [Statement], steps: &mut Vec<Step<'a>>) {
+    for statement in statements {
+        match &statement.ty {
+            StatementType::Label(label) => {
+                steps.push(Step::Reset(span_start(&statement.span)));
+                flatten_steps(&label.inner, steps);
+            }
+            StatementType::Repeat(repeat) => {
+                steps.push(Step::Reset(span_start(&statement.span)));
+                flatten_steps(&repeat.statements, steps);
+                let end = statement.span.0.iter().map(|s| s.end).max().unwrap_or_default();
+                steps.push(Step::Reset(end));
+            }
+            _ => steps.push(Step::Statement(statement)),
+        }
+    }
+}
+
+/// Applies the stack effect of a statement, returning `None` if it can't be simulated
+fn simulate_statement(
+    contract: &Contract,
+    statement: &Statement,
+    mut stack: Vec<Option<String>>,
+) -> Option<Vec<Option<String>>> {
+    let (takes, returns) = match &statement.ty {
+        StatementType::Opcode(o) => {
+            if o.is_terminating() {
+                return None
+            }
+            // The value of a push opcode is accounted for by the literal following it
+            if o.is_value_push() {
+                return Some(stack)
+            }
+            let (takes, returns) = o.stack_effect();
+            if stack.len() < takes {
+                return None
+            }
+            let name = o.to_string();
+            match u8::from_str_radix(&name, 16).ok()? {
+                // Dup1 - Dup16
+                b @ 0x80..=0x8f => {
+                    stack.insert(0, stack[usize::from(b - 0x80)].clone());
+                    return Some(stack)
+                }
+                // Swap1 - Swap16
+                b @ 0x90..=0x9f => {
+                    stack.swap(0, usize::from(b - 0x8f));
+                    return Some(stack)
+                }
+                _ => (takes, returns),
+            }
+        }
+        StatementType::Literal(_) | StatementType::Constant(_) | StatementType::LabelCall(_) => {
+            (0, 1)
+        }
+        StatementType::BuiltinFunctionCall(b) => match b.kind {
+            BuiltinFunctionKind::Verbatim |
+            BuiltinFunctionKind::DynConstructorArg |
+            BuiltinFunctionKind::Custom(_) => return None,
+            _ => (0, 1),
+        },
+        StatementType::MacroInvocation(mi) => {
+            let invoked = contract.find_macro_by_name(&mi.macro_name)?;
+            if stack.len() < invoked.takes {
+                return None
+            }
+            (invoked.takes, invoked.returns)
+        }
+        _ => return None,
+    };
+    stack.drain(..takes);
+    stack.splice(0..0, vec![None; returns]);
+    Some(stack)
+}
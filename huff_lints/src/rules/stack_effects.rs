@@ -0,0 +1,223 @@
+use std::collections::BTreeMap;
+
+use huff_utils::prelude::*;
+
+use crate::{Lint, LintContext};
+
+/// The number of stack items taken and returned
+type Effect = (usize, usize);
+
+/// Reports macros whose stack effect doesn't match their declaration, see
+/// [check_stack_effects]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StackEffects;
+
+impl Lint for StackEffects {
+    fn id(&self) -> &'static str {
+        "stack-effects"
+    }
+
+    fn description(&self) -> &'static str {
+        "Macros whose stack effect doesn't match their declared takes and returns"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Allow
+    }
+
+    fn check(&self, cx: &LintContext) -> Vec<CompilerWarning> {
+        check_stack_effects(cx.contract)
+    }
+}
+
+/// The simulated stack height of a macro body, relative to its height on entry
+#[derive(Default)]
+struct Simulation {
+    /// The current height, `None` if the current statement is unreachable
+    height: Option<isize>,
+    /// The lowest height reached
+    min: isize,
+    /// The heights at which labels are jumped to
+    labels: BTreeMap<String, isize>,
+    /// The label pushed by the previous statement
+    pushed_label: Option<String>,
+}
+
+/// Validates the declared `takes` and `returns` of every macro against their stack effects
+///
+/// Macros without parameters are analyzed on their own, while macros with parameters are
+/// analyzed for every invocation, with the stack effects of the passed arguments. The stack
+/// height is simulated over the macro body, following jumps to labels defined later on in the
+/// body. A macro is flagged if it reaches deeper into the stack than it takes, or if the height
+/// it falls through with doesn't match its returns. Invoked macros are assumed to match their
+/// own declarations, which are validated separately.
+///
+/// Macros using `__REPEAT`, `__VERBATIM` or arguments with an unknown stack effect can't be
+/// simulated and are skipped.
+pub fn check_stack_effects(contract: &Contract) -> Vec<CompilerWarning> {
+    let mut warnings: Vec<CompilerWarning> = vec![];
+    let mut report = |macro_def: &MacroDefinition, args: &BTreeMap<&str, Effect>| {
+        let Some(computed) = simulate_macro(contract, macro_def, args) else { return };
+        if computed == (macro_def.takes, macro_def.returns) {
+            return
+        }
+        let kind = WarningKind::StackEffectMismatch(
+            macro_def.name.clone(),
+            (macro_def.takes, macro_def.returns),
+            computed,
+        );
+        if warnings.iter().all(|w| w.kind != kind) {
+            tracing::warn!(target: "lints", "STACK EFFECT MISMATCH IN MACRO \"{}\"", macro_def.name);
+            warnings.push(CompilerWarning::new(
+                kind,
+                AstSpan(macro_def.span.0.first().into_iter().cloned().collect()),
+            ));
+        }
+    };
+
+    for macro_def in contract.macros.iter() {
+        if macro_def.parameters.is_empty() {
+            report(macro_def, &BTreeMap::new());
+        }
+
+        let mut invocations = vec![];
+        collect_invocations(&macro_def.statements, &mut invocations);
+        for invocation in invocations {
+            let Some(invoked) = contract.find_macro_by_name(&invocation.macro_name) else {
+                continue
+            };
+            if invoked.parameters.is_empty() {
+                continue
+            }
+            let args = invoked
+                .parameters
+                .iter()
+                .zip(invocation.args.iter())
+                .filter_map(|(param, arg)| {
+                    Some((param.name.as_deref()?, arg_effect(contract, arg)?))
+                })
+                .collect::<BTreeMap<&str, Effect>>();
+            report(invoked, &args);
+        }
+    }
+    warnings
+}
+
+/// Collects the macro invocations of a macro body, including those passed as arguments
+fn collect_invocations<'a>(
+    statements: &'a [Statement],
+    invocations: &mut Vec<&'a MacroInvocation>,
+) {
+    fn collect_args<'a>(
+        invocation: &'a MacroInvocation,
+        invocations: &mut Vec<&'a MacroInvocation>,
+    ) {
+        invocations.push(invocation);
+        for arg in invocation.args.iter() {
+            if let MacroArg::MacroCall(nested) = arg {
+                collect_args(nested, invocations);
+            }
+        }
+    }
+
+    for statement in statements {
+        match &statement.ty {
+            StatementType::MacroInvocation(mi) => collect_args(mi, invocations),
+            StatementType::Label(label) => collect_invocations(&label.inner, invocations),
+            StatementType::Repeat(repeat) => collect_invocations(&repeat.statements, invocations),
+            _ => {}
+        }
+    }
+}
+
+/// Gets the stack effect of a macro argument, `None` if it depends on the invoking macro's
+/// arguments
+fn arg_effect(contract: &Contract, arg: &MacroArg) -> Option<Effect> {
+    match arg {
+        MacroArg::Literal(_) | MacroArg::Ident(_) => Some((0, 1)),
+        MacroArg::Opcode(o) if !o.is_terminating() => Some(o.stack_effect()),
+        MacroArg::MacroCall(mi) => {
+            contract.find_macro_by_name(&mi.macro_name).map(|m| (m.takes, m.returns))
+        }
+        _ => None,
+    }
+}
+
+/// Simulates a macro body, returning its computed `takes` and `returns`
+fn simulate_macro(
+    contract: &Contract,
+    macro_def: &MacroDefinition,
+    args: &BTreeMap<&str, Effect>,
+) -> Option<Effect> {
+    let mut sim = Simulation { height: Some(0), ..Default::default() };
+    simulate_statements(contract, &macro_def.statements, args, &mut sim)?;
+
+    // The computed takes are the declared ones, unless the body reaches deeper
+    let takes = macro_def.takes.max(sim.min.unsigned_abs());
+    let returns = match sim.height {
+        Some(height) => usize::try_from(takes as isize + height).ok()?,
+        // Without falling through, only the takes can be validated
+        None => macro_def.returns,
+    };
+    Some((takes, returns))
+}
+
+/// Simulates a list of statements, returning `None` if their stack effect is unknown
+fn simulate_statements(
+    contract: &Contract,
+    statements: &[Statement],
+    args: &BTreeMap<&str, Effect>,
+    sim: &mut Simulation,
+) -> Option<()> {
+    for statement in statements {
+        let pushed_label = sim.pushed_label.take();
+        let effect = match &statement.ty {
+            StatementType::Label(label) => {
+                sim.height = sim.height.or_else(|| sim.labels.get(&label.name).copied());
+                simulate_statements(contract, &label.inner, args, sim)?;
+                continue
+            }
+            StatementType::LabelCall(label) => {
+                sim.pushed_label = Some(label.clone());
+                (0, 1)
+            }
+            // The value of a push opcode is accounted for by the literal following it
+            StatementType::Opcode(o) if o.is_value_push() => (0, 0),
+            StatementType::Opcode(o) => o.stack_effect(),
+            StatementType::Literal(_) | StatementType::Constant(_) => (0, 1),
+            StatementType::ArgCall(arg) => *args.get(arg.as_str())?,
+            StatementType::BuiltinFunctionCall(b) => match b.kind {
+                BuiltinFunctionKind::Verbatim |
+                BuiltinFunctionKind::DynConstructorArg |
+                BuiltinFunctionKind::Custom(_) => {
+                    return None
+                }
+                _ => (0, 1),
+            },
+            StatementType::MacroInvocation(mi) => {
+                let invoked = contract.find_macro_by_name(&mi.macro_name)?;
+                (invoked.takes, invoked.returns)
+            }
+            StatementType::Code(_) | StatementType::Repeat(_) => return None,
+        };
+
+        let Some(height) = sim.height else { continue };
+        let taken = height - effect.0 as isize;
+        sim.min = sim.min.min(taken);
+        let height = taken + effect.1 as isize;
+        sim.height = Some(height);
+
+        if let StatementType::Opcode(o) = &statement.ty {
+            // Remember the height a pushed label is jumped to with
+            if matches!(o, Opcode::Jump | Opcode::Jumpi) {
+                if let Some(label) = pushed_label {
+                    sim.labels.entry(label).or_insert(height);
+                }
+            }
+            if o.is_terminating() {
+                sim.height = None;
+            }
+        }
+    }
+    Some(())
+}
@@ -0,0 +1,87 @@
+use huff_utils::prelude::*;
+
+use crate::{Lint, LintContext};
+
+/// Reports macros never invoked from an entry macro, see [check_unused_macros]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UnusedMacros;
+
+impl Lint for UnusedMacros {
+    fn id(&self) -> &'static str {
+        "unused-macros"
+    }
+
+    fn description(&self) -> &'static str {
+        "Macros never invoked from the main or constructor macro"
+    }
+
+    fn check(&self, cx: &LintContext) -> Vec<CompilerWarning> {
+        check_unused_macros(cx.contract, cx.entry_macros)
+    }
+}
+
+/// Reports constants never referenced from an entry macro, see [check_unused_constants]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UnusedConstants;
+
+impl Lint for UnusedConstants {
+    fn id(&self) -> &'static str {
+        "unused-constants"
+    }
+
+    fn description(&self) -> &'static str {
+        "Constants never referenced from the main or constructor macro"
+    }
+
+    fn check(&self, cx: &LintContext) -> Vec<CompilerWarning> {
+        check_unused_constants(cx.contract, cx.entry_macros)
+    }
+}
+
+/// Finds macros that are never invoked from the entry macros, directly or through other
+/// macros, tables or compile-time assertions
+///
+/// Test macros are never reported, and contracts without any of the entry macros, e.g.
+/// libraries, aren't checked at all.
+pub fn check_unused_macros(contract: &Contract, entry_macros: &[&str]) -> Vec<CompilerWarning> {
+    if entry_macros.iter().all(|r| contract.macros.iter().all(|m| m.name != *r)) {
+        return vec![]
+    }
+    let referenced = contract.referenced_definitions(entry_macros);
+    contract
+        .macros
+        .iter()
+        .filter(|m| !m.test && !referenced.contains(&m.name))
+        .map(|m| {
+            tracing::warn!(target: "lints", "UNUSED MACRO: \"{}\"", m.name);
+            CompilerWarning::new(
+                WarningKind::UnusedMacro(m.name.clone()),
+                AstSpan(m.span.0.first().into_iter().cloned().collect()),
+            )
+        })
+        .collect()
+}
+
+/// Finds constants that are never referenced by a macro invoked from the entry macros, by
+/// another such constant or by a compile-time assertion
+///
+/// Constants set with overrides are never reported, and contracts without any of the entry
+/// macros aren't checked at all.
+pub fn check_unused_constants(contract: &Contract, entry_macros: &[&str]) -> Vec<CompilerWarning> {
+    if entry_macros.iter().all(|r| contract.macros.iter().all(|m| m.name != *r)) {
+        return vec![]
+    }
+    let referenced = contract.referenced_definitions(entry_macros);
+    let constants = contract.constants.lock().unwrap();
+    constants
+        .iter()
+        .filter(|c| !c.span.0.is_empty() && !referenced.contains(&c.name))
+        .map(|c| {
+            tracing::warn!(target: "lints", "UNUSED CONSTANT: \"{}\"", c.name);
+            CompilerWarning::new(
+                WarningKind::UnusedConstant(c.name.clone()),
+                AstSpan(c.span.0.first().into_iter().cloned().collect()),
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,73 @@
+//! ## Inline Suppressions
+//!
+//! Lint rules can be allowed inline with a `// huff-lint: allow(<id>, ...)` comment, which
+//! applies to its own line and to the line below it.
+//!
+//! ```huff
+//! // huff-lint: allow(unused-macros)
+//! #define macro HELPER() = takes(0) returns(0) {}
+//!
+//! #define constant OWNER = 0x5aaeb6053F3E94C9b9A09f33669435E7Ef1BeAed // huff-lint: allow(address-checksum)
+//! ```
+
+use huff_utils::prelude::*;
+
+/// The prefix of suppression comments
+pub const SUPPRESSION_PREFIX: &str = "huff-lint:";
+
+/// The lint rules allowed by inline comments
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Suppressions {
+    /// The offsets of the line starts in the source
+    line_starts: Vec<usize>,
+    /// The allowed rule ids, with the line of their comment
+    allowed: Vec<(usize, String)>,
+}
+
+impl Suppressions {
+    /// Collects the suppression comments of a source's tokens
+    pub fn new(tokens: &[Token], source: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect::<Vec<usize>>();
+        let mut suppressions = Self { line_starts, allowed: vec![] };
+        for token in tokens {
+            let TokenKind::Comment(comment) = &token.kind else { continue };
+            let line = suppressions.line(token.span.start);
+            for id in parse_suppression(comment) {
+                tracing::debug!(target: "lints", "ALLOWED LINT \"{}\" ON LINE {}", id, line + 1);
+                suppressions.allowed.push((line, id));
+            }
+        }
+        suppressions
+    }
+
+    /// Whether a rule is allowed where a warning's span starts
+    pub fn allows(&self, id: &str, span: &AstSpan) -> bool {
+        let Some(start) = span.0.first().map(|s| s.start) else { return false };
+        let line = self.line(start);
+        self.allowed.iter().any(|(l, allowed)| allowed == id && (*l == line || *l + 1 == line))
+    }
+
+    /// Gets the zero-based line of an offset
+    fn line(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|start| *start <= offset).saturating_sub(1)
+    }
+}
+
+/// Parses the rule ids allowed by a comment, e.g. `// huff-lint: allow(unused-macros)`
+pub fn parse_suppression(comment: &str) -> Vec<String> {
+    let content = comment
+        .strip_prefix("//")
+        .or_else(|| comment.strip_prefix("/*").map(|c| c.strip_suffix("*/").unwrap_or(c)))
+        .unwrap_or(comment);
+    content
+        .trim()
+        .strip_prefix(SUPPRESSION_PREFIX)
+        .and_then(|c| c.trim().strip_prefix("allow("))
+        .and_then(|c| c.trim_end().strip_suffix(')'))
+        .map(|ids| {
+            ids.split(',').map(str::trim).filter(|id| !id.is_empty()).map(String::from).collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,38 @@
+use huff_lexer::*;
+use huff_lints::rules::check_event_topics;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+#[test]
+fn test_event_topic_collisions() {
+    let source: &str = r#"
+        #define event Transfer(address indexed, address indexed, uint256)
+        #define event Approval(address indexed, address indexed, uint256)
+        #define event Transfer(address, address, uint256 indexed)
+        #define macro MAIN() = takes (0) returns (0) {}
+    "#;
+
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+    let contract = parser.parse().unwrap();
+
+    let warnings = check_event_topics(&contract);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        warnings[0].kind,
+        WarningKind::EventTopicCollision(
+            "Transfer".to_string(),
+            "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef".to_string()
+        )
+    );
+    let starts = warnings[0].span.0.iter().map(|s| s.start).collect::<Vec<usize>>();
+    assert_eq!(
+        starts,
+        vec![
+            source.find("#define event Transfer").unwrap(),
+            source.rfind("#define event Transfer").unwrap()
+        ]
+    );
+}
@@ -0,0 +1,188 @@
+use std::sync::Arc;
+
+use huff_lexer::*;
+use huff_lints::{config::LintConfig, Lint, LintContext, Linter};
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+fn lint(linter: &Linter, source: &str) -> Result<Vec<CompilerWarning>, CompilerWarning> {
+    let tokens = Lexer::new(source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens.clone(), None).unwrap();
+    let contract = parser.parse().unwrap();
+    let cx = LintContext {
+        contract: &contract,
+        source,
+        tokens: &tokens,
+        main: "MAIN",
+        entry_macros: &["MAIN", "CONSTRUCTOR"],
+        max_expansion_depth: MAX_EXPANSION_DEPTH,
+    };
+    linter.run(&cx)
+}
+
+fn kinds(warnings: &[CompilerWarning]) -> Vec<(Option<&'static str>, WarningKind)> {
+    warnings.iter().map(|w| (w.lint, w.kind.clone())).collect()
+}
+
+/// Reports every macro named in lower case
+struct LowerCaseMacros;
+
+impl Lint for LowerCaseMacros {
+    fn id(&self) -> &'static str {
+        "lower-case-macros"
+    }
+
+    fn description(&self) -> &'static str {
+        "Macros named in lower case"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, cx: &LintContext) -> Vec<CompilerWarning> {
+        cx.contract
+            .macros
+            .iter()
+            .filter(|m| m.name.chars().any(|c| c.is_ascii_lowercase()))
+            .map(|m| {
+                CompilerWarning::new(
+                    WarningKind::Lint(format!("Lower Case Macro: \"{}\"", m.name)),
+                    m.span.clone(),
+                )
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_default_severities() {
+    let source = r#"
+    #define constant UNUSED = 0x00
+
+    #define macro HELPER() = takes(0) returns(0) {
+        0x01 0x02
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x00 mstore
+    }
+    "#;
+
+    let linter = Linter::default();
+    assert_eq!(linter.severity("unused-macros"), Some(Severity::Warn));
+    assert_eq!(linter.severity("stack-effects"), Some(Severity::Allow));
+    assert_eq!(linter.severity("unknown"), None);
+    assert_eq!(
+        kinds(&lint(&linter, source).unwrap()),
+        vec![
+            (Some("unused-macros"), WarningKind::UnusedMacro("HELPER".to_string())),
+            (Some("unused-constants"), WarningKind::UnusedConstant("UNUSED".to_string())),
+        ]
+    );
+}
+
+#[test]
+fn test_configured_severities() {
+    let source = r#"
+    #define macro HELPER() = takes(0) returns(0) {}
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x00 mstore
+    }
+    "#;
+
+    let config = LintConfig::parse(
+        r#"
+        [lints]
+        unused-macros = "allow"
+        stack-effects = "warn"
+        "#,
+    )
+    .unwrap();
+    let mut linter = Linter::default();
+    linter.configure(&config).unwrap();
+    assert_eq!(
+        kinds(&lint(&linter, source).unwrap()),
+        vec![(
+            Some("stack-effects"),
+            WarningKind::StackEffectMismatch("MAIN".to_string(), (0, 0), (1, 0))
+        )]
+    );
+
+    linter.set_severity("stack-effects", Severity::Error).unwrap();
+    let error = lint(&linter, source).unwrap_err();
+    assert_eq!(error.lint, Some("stack-effects"));
+    assert!(error.to_string().starts_with("\nWarning[stack-effects]: Stack Effect Mismatch"));
+}
+
+#[test]
+fn test_invalid_configurations() {
+    let mut linter = Linter::default();
+    assert_eq!(
+        linter.set_severity("unused-functions", Severity::Error),
+        Err("Unknown lint \"unused-functions\"".to_string())
+    );
+
+    let config = LintConfig::parse("[lints]\nunused-functions = \"error\"").unwrap();
+    assert_eq!(linter.configure(&config), Err("Unknown lint \"unused-functions\"".to_string()));
+
+    assert_eq!(
+        LintConfig::parse("[lints]\nunused-macros = \"deny\""),
+        Err("Invalid severity \"deny\", expected one of: allow, warn, error".to_string())
+    );
+    assert!(LintConfig::parse("[lints]\nunused-macros = 1").is_err());
+    assert_eq!(LintConfig::parse("[profile]\nname = \"huff\""), Ok(LintConfig::default()));
+}
+
+#[test]
+fn test_inline_suppressions() {
+    let source = r#"
+    // huff-lint: allow(unused-macros)
+    #define macro ALLOWED_ABOVE() = takes(0) returns(0) {}
+
+    #define macro ALLOWED_INLINE() = takes(0) returns(0) {} // huff-lint: allow(unused-macros)
+
+    /* huff-lint: allow(unused-constants, unused-macros) */
+    #define macro ALLOWED_BY_BLOCK() = takes(0) returns(0) {}
+
+    // huff-lint: allow(unused-constants)
+    #define macro NOT_ALLOWED() = takes(0) returns(0) {}
+
+    // huff-lint: allow(unused-macros)
+
+    #define macro TOO_FAR() = takes(0) returns(0) {}
+
+    #define macro MAIN() = takes(0) returns(0) {}
+    "#;
+
+    let warnings = lint(&Linter::default(), source).unwrap();
+    assert_eq!(
+        kinds(&warnings),
+        vec![
+            (Some("unused-macros"), WarningKind::UnusedMacro("NOT_ALLOWED".to_string())),
+            (Some("unused-macros"), WarningKind::UnusedMacro("TOO_FAR".to_string())),
+        ]
+    );
+}
+
+#[test]
+fn test_custom_rules() {
+    let source = r#"
+    #define macro helper() = takes(0) returns(0) {}
+
+    #define macro MAIN() = takes(0) returns(0) {
+        helper()
+    }
+    "#;
+
+    let mut linter = Linter::new();
+    linter.register(Arc::new(LowerCaseMacros));
+    assert_eq!(linter.rules().len(), 1);
+    let error = lint(&linter, source).unwrap_err();
+    assert_eq!(error.lint, Some("lower-case-macros"));
+    assert_eq!(error.kind, WarningKind::Lint("Lower Case Macro: \"helper\"".to_string()));
+
+    linter.set_severity("lower-case-macros", Severity::Allow).unwrap();
+    assert!(lint(&linter, source).unwrap().is_empty());
+}
@@ -0,0 +1,635 @@
+//! ## Analysis
+//!
+//! The state of an open document: its source, parsed incrementally, and the symbols of its
+//! partial AST. Every language feature is answered from this state, in the document's own
+//! coordinates.
+
+use crate::line_index::LineIndex;
+use huff_lexer::{
+    semantic::{semantic_tokens, SemanticTokenKind},
+    Lexer,
+};
+use huff_lints::{LintContext, Linter};
+use huff_parser::incremental::{Document, TextEdit};
+use huff_utils::{
+    diagnostics::Diagnostic as HuffDiagnostic,
+    prelude::*,
+    symbols::{Definition, SymbolKind, SymbolTable},
+};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, Diagnostic, DiagnosticSeverity, DocumentSymbol, Hover,
+    HoverContents, InsertTextFormat, MarkupContent, MarkupKind, NumberOrString, Position, Range,
+    SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokensLegend,
+    SymbolKind as LspSymbolKind, TextDocumentContentChangeEvent,
+};
+
+/// The builtin functions, completed with their parentheses
+pub const BUILTINS: [&str; 12] = [
+    "__tablesize",
+    "__codesize",
+    "__tablestart",
+    "__FUNC_SIG",
+    "__EVENT_HASH",
+    "__ERROR",
+    "__RIGHTPAD",
+    "__LEFTPAD",
+    "__CODECOPY_DYN_ARG",
+    "__VERBATIM",
+    "__keccak",
+    "__BYTES",
+];
+
+/// The semantic token types, indexed by [semantic_token_type]
+pub const SEMANTIC_TOKEN_TYPES: [SemanticTokenType; 15] = [
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::DECORATOR,
+    SemanticTokenType::OPERATOR,
+    SemanticTokenType::MACRO,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::EVENT,
+    SemanticTokenType::CLASS,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::ENUM,
+    SemanticTokenType::new("label"),
+    SemanticTokenType::PARAMETER,
+    SemanticTokenType::TYPE,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::STRING,
+    SemanticTokenType::COMMENT,
+];
+
+/// The semantic token modifiers, as bits of [semantic_token_type]'s bitset
+pub const SEMANTIC_TOKEN_MODIFIERS: [SemanticTokenModifier; 3] = [
+    SemanticTokenModifier::DECLARATION,
+    SemanticTokenModifier::READONLY,
+    SemanticTokenModifier::DEFAULT_LIBRARY,
+];
+
+/// The legend of the semantic tokens, announced when initializing
+pub fn semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: SEMANTIC_TOKEN_TYPES.to_vec(),
+        token_modifiers: SEMANTIC_TOKEN_MODIFIERS.to_vec(),
+    }
+}
+
+/// The index of the semantic token type of a kind, and the bitset of its modifiers
+pub fn semantic_token_type(kind: SemanticTokenKind) -> (u32, u32) {
+    const READONLY: u32 = 1 << 1;
+    const DEFAULT_LIBRARY: u32 = 1 << 2;
+    match kind {
+        SemanticTokenKind::Keyword => (0, 0),
+        SemanticTokenKind::Decorator => (1, 0),
+        SemanticTokenKind::Opcode => (2, 0),
+        SemanticTokenKind::Macro => (3, 0),
+        SemanticTokenKind::Function => (4, 0),
+        SemanticTokenKind::Event => (5, 0),
+        SemanticTokenKind::Error => (6, 0),
+        SemanticTokenKind::Constant => (7, READONLY),
+        SemanticTokenKind::Table => (8, 0),
+        SemanticTokenKind::Label => (9, 0),
+        SemanticTokenKind::Parameter => (10, 0),
+        SemanticTokenKind::Builtin => (4, DEFAULT_LIBRARY),
+        SemanticTokenKind::Type => (11, 0),
+        SemanticTokenKind::Literal => (12, 0),
+        SemanticTokenKind::String => (13, 0),
+        SemanticTokenKind::Comment => (14, 0),
+    }
+}
+
+/// An open document and its analysis
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    /// The incrementally parsed source
+    document: Document,
+    /// The line index of the source
+    index: LineIndex,
+    /// The partial AST of the source
+    contract: Contract,
+    /// The symbols of the contract
+    symbols: SymbolTable,
+}
+
+impl Analysis {
+    /// Public associated function that parses and analyzes a source
+    pub fn new(source: &str) -> Self {
+        let document = Document::new(source, None);
+        let contract = document.contract();
+        let symbols = SymbolTable::new(&contract);
+        Self { index: LineIndex::new(source), document, contract, symbols }
+    }
+
+    /// The current source of the document
+    pub fn source(&self) -> &str {
+        self.document.source()
+    }
+
+    /// The partial AST of the document
+    pub fn contract(&self) -> &Contract {
+        &self.contract
+    }
+
+    /// The symbols of the document
+    pub fn symbols(&self) -> &SymbolTable {
+        &self.symbols
+    }
+
+    /// The line index of the document
+    pub fn line_index(&self) -> &LineIndex {
+        &self.index
+    }
+
+    /// Applies a change sent by the client, re-parsing the definitions it touches
+    ///
+    /// Changes without a range replace the whole source.
+    pub fn apply_change(&mut self, change: &TextDocumentContentChangeEvent) {
+        match change.range {
+            Some(range) => {
+                let range = self.index.offset(range.start)..self.index.offset(range.end);
+                self.document.apply_edit(&TextEdit { range, text: change.text.clone() });
+            }
+            None => self.document = Document::new(&change.text, None),
+        }
+        self.index = LineIndex::new(self.document.source());
+        self.contract = self.document.contract();
+        self.symbols = SymbolTable::new(&self.contract);
+    }
+
+    /// The lexical and parser errors of the document, undefined references and the warnings of
+    /// the lint rules
+    pub fn diagnostics(&self, linter: &Linter) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        for error in self.document.lexical_errors() {
+            let span = error.span.clone();
+            diagnostics.push(self.error(&CompilerError::LexicalError(error), &[span]));
+        }
+        for invalid in &self.contract.invalid_definitions {
+            let spans = match invalid.error.spans.0.is_empty() {
+                true => &invalid.span.0,
+                false => &invalid.error.spans.0,
+            };
+            diagnostics.push(self.error(&CompilerError::ParserError(invalid.error.clone()), spans));
+        }
+
+        // Definitions can come from included files, which aren't resolved
+        let imports = !self.contract.imports.is_empty() ||
+            !self.contract.selective_imports.is_empty() ||
+            !self.contract.namespaced_imports.is_empty();
+        if !imports {
+            diagnostics.extend(self.undefined_references());
+        }
+
+        let tokens = Lexer::new(self.source()).filter_map(Result::ok).collect::<Vec<Token>>();
+        let cx = LintContext {
+            contract: &self.contract,
+            source: self.source(),
+            tokens: &tokens,
+            main: "MAIN",
+            entry_macros: &["MAIN", "CONSTRUCTOR"],
+            max_expansion_depth: MAX_EXPANSION_DEPTH,
+        };
+        let (warnings, error) = match linter.run(&cx) {
+            Ok(warnings) => (warnings, None),
+            Err(error) => (vec![], Some(error)),
+        };
+        for warning in &warnings {
+            let message = HuffDiagnostic::from_warning(warning).message;
+            diagnostics.push(Diagnostic {
+                range: self.index.range_of(&warning.span.0).unwrap_or_default(),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: warning.lint.map(|id| NumberOrString::String(id.to_string())),
+                source: Some("huff".to_string()),
+                message,
+                ..Default::default()
+            });
+        }
+        if let Some(error) = error {
+            let spans = error.span.0.clone();
+            diagnostics.push(self.error(&CompilerError::Warning(error), &spans));
+        }
+        diagnostics
+    }
+
+    /// The documentation of the opcode or symbol at a position
+    pub fn hover(&self, position: Position) -> Option<Hover> {
+        let offset = self.index.offset(position);
+        let token = Lexer::new(self.source())
+            .filter_map(Result::ok)
+            .find(|t| t.span.start <= offset && offset <= t.span.end)?;
+        let value = match &token.kind {
+            TokenKind::Opcode(opcode) => {
+                let (takes, returns) = opcode.stack_effect();
+                let mnemonic = self
+                    .source()
+                    .chars()
+                    .skip(token.span.start)
+                    .take(token.span.end + 1 - token.span.start)
+                    .collect::<String>();
+                format!(
+                    "```huff\n{mnemonic}\n```\n`0x{}` · takes {takes}, returns {returns}\n\n{}",
+                    opcode.string(),
+                    opcode.description()
+                )
+            }
+            _ => {
+                let definition = self.symbols.symbol_at(None, offset)?;
+                format!("```huff\n{}\n```", self.signature(definition))
+            }
+        };
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value }),
+            range: Some(self.index.range(&token.span)),
+        })
+    }
+
+    /// The range of the definition of the symbol at a position
+    pub fn definition(&self, position: Position) -> Option<Range> {
+        let definition = self.symbols.symbol_at(None, self.index.offset(position))?;
+        Some(self.index.range(&definition.span))
+    }
+
+    /// The ranges of the references to the symbol at a position, and of its definition if
+    /// `include_declaration` is set
+    pub fn references(&self, position: Position, include_declaration: bool) -> Vec<Range> {
+        let Some(definition) = self.symbols.symbol_at(None, self.index.offset(position)) else {
+            return vec![]
+        };
+        include_declaration
+            .then_some(&definition.span)
+            .into_iter()
+            .chain(self.symbols.references_to(definition).map(|r| &r.span))
+            .map(|span| self.index.range(span))
+            .collect()
+    }
+
+    /// The completions at a position
+    ///
+    /// Within brackets, constants and immutables are completed, and within angle brackets the
+    /// parameters of the macro. Otherwise, opcodes, macros, constants, builtins and the labels of
+    /// the macro are.
+    pub fn completions(&self, position: Position) -> Vec<CompletionItem> {
+        let offset = self.index.offset(position);
+        let before = self.source().chars().take(offset).collect::<Vec<char>>();
+        let trigger = before.iter().rev().find(|c| !c.is_ascii_alphanumeric() && **c != '_');
+        let scope = self.macro_at(offset).map(|m| m.name.as_str());
+
+        let item = |label: &str, kind, detail: String| CompletionItem {
+            label: label.to_string(),
+            kind: Some(kind),
+            detail: Some(detail),
+            ..Default::default()
+        };
+        let symbols = |kinds: &[SymbolKind]| {
+            self.symbols
+                .definitions()
+                .iter()
+                .filter(|d| kinds.contains(&d.kind))
+                .filter(|d| d.scope.is_none() || d.scope.as_deref() == scope)
+                .collect::<Vec<&Definition>>()
+        };
+
+        match trigger {
+            Some('[') => symbols(&[SymbolKind::Constant, SymbolKind::Immutable])
+                .into_iter()
+                .map(|d| item(&d.name, CompletionItemKind::CONSTANT, self.signature(d)))
+                .collect(),
+            Some('<') => symbols(&[SymbolKind::Argument])
+                .into_iter()
+                .map(|d| item(&d.name, CompletionItemKind::VARIABLE, self.signature(d)))
+                .collect(),
+            _ => {
+                let mut items = OPCODES
+                    .iter()
+                    .filter_map(|name| OPCODES_MAP.get(name).map(|o| (name, o)))
+                    .map(|(name, opcode)| {
+                        item(name, CompletionItemKind::KEYWORD, opcode.description().to_string())
+                    })
+                    .collect::<Vec<CompletionItem>>();
+                items.extend(BUILTINS.iter().map(|name| CompletionItem {
+                    insert_text: Some(format!("{name}($1)")),
+                    insert_text_format: Some(InsertTextFormat::SNIPPET),
+                    ..item(name, CompletionItemKind::FUNCTION, "builtin function".to_string())
+                }));
+                for definition in symbols(&[
+                    SymbolKind::Macro,
+                    SymbolKind::Constant,
+                    SymbolKind::Immutable,
+                    SymbolKind::Label,
+                ]) {
+                    let detail = self.signature(definition);
+                    items.push(match definition.kind {
+                        SymbolKind::Macro => CompletionItem {
+                            insert_text: Some(format!("{}($1)", definition.name)),
+                            insert_text_format: Some(InsertTextFormat::SNIPPET),
+                            ..item(&definition.name, CompletionItemKind::FUNCTION, detail)
+                        },
+                        SymbolKind::Label => {
+                            item(&definition.name, CompletionItemKind::REFERENCE, detail)
+                        }
+                        _ => CompletionItem {
+                            insert_text: Some(format!("[{}]", definition.name)),
+                            ..item(&definition.name, CompletionItemKind::CONSTANT, detail)
+                        },
+                    });
+                }
+                items
+            }
+        }
+    }
+
+    /// The outline of the document, with the labels of each macro nested in it
+    pub fn document_symbols(&self) -> Vec<DocumentSymbol> {
+        let definitions = self.symbols.definitions();
+        definitions
+            .iter()
+            .filter(|d| d.scope.is_none())
+            .filter_map(|d| {
+                let children = definitions
+                    .iter()
+                    .filter(|c| c.kind == SymbolKind::Label && c.scope.as_ref() == Some(&d.name))
+                    .filter(|_| d.kind == SymbolKind::Macro)
+                    .filter_map(|c| self.document_symbol(c, vec![]))
+                    .collect::<Vec<DocumentSymbol>>();
+                self.document_symbol(d, children)
+            })
+            .collect()
+    }
+
+    /// The semantic tokens of the document, relative to the previous token
+    ///
+    /// Tokens spanning several lines, eg. block comments, are split into a token per line.
+    pub fn semantic_tokens(&self) -> Vec<SemanticToken> {
+        let mut tokens = vec![];
+        let mut previous = Position::new(0, 0);
+        for token in semantic_tokens(self.source()) {
+            let (token_type, mut modifiers) = semantic_token_type(token.kind);
+            modifiers |= u32::from(token.definition);
+
+            let mut start = token.span.start;
+            while start <= token.span.end {
+                let position = self.index.position(start);
+                let line_end = self.index.offset(Position::new(position.line, u32::MAX));
+                let end = (token.span.end + 1).min(line_end);
+                let length = self.index.position(end).character - position.character;
+                if length > 0 {
+                    let delta_line = position.line - previous.line;
+                    tokens.push(SemanticToken {
+                        delta_line,
+                        delta_start: match delta_line {
+                            0 => position.character - previous.character,
+                            _ => position.character,
+                        },
+                        length,
+                        token_type,
+                        token_modifiers_bitset: modifiers,
+                    });
+                    previous = position;
+                }
+                start = line_end + 1;
+            }
+        }
+        tokens
+    }
+
+    /// Converts an error into an LSP diagnostic over its spans
+    fn error(&self, error: &CompilerError, spans: &[Span]) -> Diagnostic {
+        let mut message = HuffDiagnostic::from_error(error)
+            .into_iter()
+            .next()
+            .map(|d| match d.hint {
+                Some(hint) => format!("{}\n{hint}", d.message),
+                None => d.message,
+            })
+            .unwrap_or_default();
+        if message.is_empty() {
+            message = error.to_string();
+        }
+        Diagnostic {
+            range: self.index.range_of(spans).unwrap_or_default(),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: error.code().map(|c| NumberOrString::String(c.to_string())),
+            source: Some("huff".to_string()),
+            message,
+            ..Default::default()
+        }
+    }
+
+    /// Errors for references to undefined symbols, suggesting the closest definition
+    fn undefined_references(&self) -> Vec<Diagnostic> {
+        self.symbols
+            .references()
+            .iter()
+            .filter(|r| r.definition.is_none())
+            .map(|reference| {
+                let kind = match reference.kind {
+                    SymbolKind::Macro => "macro",
+                    SymbolKind::Constant | SymbolKind::Immutable => "constant",
+                    SymbolKind::Table => "table",
+                    SymbolKind::Function => "function",
+                    SymbolKind::Event => "event",
+                    SymbolKind::Error => "error",
+                    SymbolKind::Label => "label",
+                    SymbolKind::Argument => "argument",
+                };
+                let candidates = self
+                    .symbols
+                    .definitions()
+                    .iter()
+                    .filter(|d| d.kind == reference.kind)
+                    .map(|d| d.name.as_str());
+                let message = match did_you_mean(&reference.name, candidates) {
+                    Some(hint) => format!("Undefined {kind} \"{}\"\n{hint}", reference.name),
+                    None => format!("Undefined {kind} \"{}\"", reference.name),
+                };
+                Diagnostic {
+                    range: self.index.range(&reference.span),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    source: Some("huff".to_string()),
+                    message,
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
+    /// The macro whose body contains an offset
+    fn macro_at(&self, offset: usize) -> Option<&MacroDefinition> {
+        self.contract.macros.iter().find(|m| {
+            let start = m.span.0.iter().map(|s| s.start).min();
+            let end = m.span.0.iter().map(|s| s.end).max();
+            matches!((start, end), (Some(start), Some(end)) if start <= offset && offset <= end)
+        })
+    }
+
+    /// The spans of the node a definition names
+    fn node_spans(&self, definition: &Definition) -> Option<Vec<Span>> {
+        let name = &definition.name;
+        let spans = match definition.kind {
+            SymbolKind::Macro => {
+                self.contract.macros.iter().find(|m| &m.name == name)?.span.clone()
+            }
+            SymbolKind::Constant => {
+                let constants = self.contract.constants.lock().ok()?;
+                constants.iter().find(|c| &c.name == name)?.span.clone()
+            }
+            SymbolKind::Immutable => {
+                self.contract.immutables.iter().find(|i| &i.name == name)?.span.clone()
+            }
+            SymbolKind::Table => {
+                self.contract.tables.iter().find(|t| &t.name == name)?.span.clone()
+            }
+            SymbolKind::Function => {
+                self.contract.functions.iter().find(|f| &f.name == name)?.span.clone()
+            }
+            SymbolKind::Event => {
+                self.contract.events.iter().find(|e| &e.name == name)?.span.clone()
+            }
+            SymbolKind::Error => {
+                self.contract.errors.iter().find(|e| &e.name == name)?.span.clone()
+            }
+            SymbolKind::Label | SymbolKind::Argument => AstSpan(vec![definition.span.clone()]),
+        };
+        Some(spans.0)
+    }
+
+    /// The signature of a definition, as written in the source
+    fn signature(&self, definition: &Definition) -> String {
+        let name = &definition.name;
+        let types = |args: &[Argument]| {
+            args.iter()
+                .map(|a| {
+                    let ty = a.arg_type.clone().unwrap_or_default();
+                    if a.indexed {
+                        format!("{ty} indexed")
+                    } else {
+                        ty
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+        match definition.kind {
+            SymbolKind::Macro => match self.contract.macros.iter().find(|m| &m.name == name) {
+                Some(m) => {
+                    let keyword = match (m.outlined, m.test) {
+                        (true, _) => "fn",
+                        (_, true) => "test",
+                        _ => "macro",
+                    };
+                    let parameters = m
+                        .parameters
+                        .iter()
+                        .filter_map(|p| p.name.clone())
+                        .collect::<Vec<String>>()
+                        .join(", ");
+                    format!(
+                        "#define {keyword} {name}({parameters}) = takes({}) returns({})",
+                        m.takes, m.returns
+                    )
+                }
+                None => format!("#define macro {name}()"),
+            },
+            SymbolKind::Constant => {
+                let constants =
+                    self.contract.constants.lock().map(|c| c.clone()).unwrap_or_default();
+                let value =
+                    constants.iter().find(|c| &c.name == name).and_then(|c| match &c.value {
+                        ConstVal::Literal(l) => Some(bytes32_to_string(l, true)),
+                        ConstVal::StorageSlot(l) => {
+                            Some(format!("STORAGE_SLOT({})", bytes32_to_string(l, true)))
+                        }
+                        ConstVal::FreeStoragePointer(_) => {
+                            Some("FREE_STORAGE_POINTER()".to_string())
+                        }
+                        ConstVal::Env(var) => Some(format!("env(\"{var}\")")),
+                        ConstVal::Expression(e) => e
+                            .evaluate(&constants, &c.span)
+                            .ok()
+                            .map(|l| bytes32_to_string(&l, true)),
+                    });
+                match value {
+                    Some(value) => format!("#define constant {name} = {value}"),
+                    None => format!("#define constant {name}"),
+                }
+            }
+            SymbolKind::Immutable => format!("#define immutable {name}"),
+            SymbolKind::Table => {
+                let table = self.contract.tables.iter().find(|t| &t.name == name);
+                let keyword = match table.map(|t| &t.kind) {
+                    Some(TableKind::JumpTablePacked) => "jumptable__packed",
+                    Some(TableKind::CodeTable) => "table",
+                    _ => "jumptable",
+                };
+                format!("#define {keyword} {name}")
+            }
+            SymbolKind::Function => {
+                match self.contract.functions.iter().find(|f| &f.name == name) {
+                    Some(f) => {
+                        let fn_type = match f.fn_type {
+                            FunctionType::View => "view",
+                            FunctionType::Payable => "payable",
+                            FunctionType::NonPayable => "nonpayable",
+                            FunctionType::Pure => "pure",
+                        };
+                        format!(
+                            "#define function {name}({}) {fn_type} returns ({})",
+                            types(&f.inputs),
+                            types(&f.outputs)
+                        )
+                    }
+                    None => format!("#define function {name}()"),
+                }
+            }
+            SymbolKind::Event => {
+                let parameters = self.contract.events.iter().find(|e| &e.name == name);
+                format!(
+                    "#define event {name}({})",
+                    parameters.map_or(String::new(), |e| types(&e.parameters))
+                )
+            }
+            SymbolKind::Error => {
+                let parameters = self.contract.errors.iter().find(|e| &e.name == name);
+                format!(
+                    "#define error {name}({})",
+                    parameters.map_or(String::new(), |e| types(&e.parameters))
+                )
+            }
+            SymbolKind::Label => {
+                format!("{name}: // label in {}", definition.scope.as_deref().unwrap_or_default())
+            }
+            SymbolKind::Argument => format!(
+                "<{name}> // parameter of {}",
+                definition.scope.as_deref().unwrap_or_default()
+            ),
+        }
+    }
+
+    /// Converts a definition into a document symbol
+    #[allow(deprecated)]
+    fn document_symbol(
+        &self,
+        definition: &Definition,
+        children: Vec<DocumentSymbol>,
+    ) -> Option<DocumentSymbol> {
+        let kind = match definition.kind {
+            SymbolKind::Macro => LspSymbolKind::FUNCTION,
+            SymbolKind::Constant | SymbolKind::Immutable => LspSymbolKind::CONSTANT,
+            SymbolKind::Table => LspSymbolKind::ARRAY,
+            SymbolKind::Function => LspSymbolKind::METHOD,
+            SymbolKind::Event => LspSymbolKind::EVENT,
+            SymbolKind::Error => LspSymbolKind::STRUCT,
+            SymbolKind::Label => LspSymbolKind::KEY,
+            SymbolKind::Argument => LspSymbolKind::VARIABLE,
+        };
+        Some(DocumentSymbol {
+            name: definition.name.clone(),
+            detail: Some(self.signature(definition)),
+            kind,
+            tags: None,
+            deprecated: None,
+            range: self.index.range_of(&self.node_spans(definition)?)?,
+            selection_range: self.index.range(&definition.span),
+            children: (!children.is_empty()).then_some(children),
+        })
+    }
+}
@@ -0,0 +1,8 @@
+#![doc = include_str!("../README.md")]
+#![warn(missing_docs)]
+#![warn(unused_extern_crates)]
+#![forbid(unsafe_code)]
+
+pub mod analysis;
+pub mod line_index;
+pub mod server;
@@ -0,0 +1,65 @@
+//! ## Line Index
+//!
+//! Converts between the character offsets of [Span]s and LSP positions, which count lines and
+//! UTF-16 code units within a line.
+
+use huff_utils::prelude::Span;
+use lsp_types::{Position, Range};
+
+/// The line starts of a source, for converting offsets to positions and back
+#[derive(Debug, Clone, Default)]
+pub struct LineIndex {
+    /// The characters of the source
+    chars: Vec<char>,
+    /// The character offset each line starts at
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    /// Public associated function that indexes the lines of a source
+    pub fn new(source: &str) -> Self {
+        let chars = source.chars().collect::<Vec<char>>();
+        let line_starts = std::iter::once(0)
+            .chain(chars.iter().enumerate().filter(|(_, c)| **c == '\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { chars, line_starts }
+    }
+
+    /// The position of a character offset, clamped to the end of the source
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.chars.len());
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let start = self.line_starts[line];
+        let character = self.chars[start..offset].iter().map(|c| c.len_utf16()).sum::<usize>();
+        Position::new(line as u32, character as u32)
+    }
+
+    /// The character offset of a position, clamped to the end of its line
+    pub fn offset(&self, position: Position) -> usize {
+        let Some(&start) = self.line_starts.get(position.line as usize) else {
+            return self.chars.len()
+        };
+        let end =
+            self.line_starts.get(position.line as usize + 1).map_or(self.chars.len(), |e| e - 1);
+        let mut units = 0;
+        for (offset, c) in self.chars[start..end].iter().enumerate() {
+            if units >= position.character as usize {
+                return start + offset
+            }
+            units += c.len_utf16();
+        }
+        end
+    }
+
+    /// The range of a span, whose end is inclusive
+    pub fn range(&self, span: &Span) -> Range {
+        Range::new(self.position(span.start), self.position(span.end + 1))
+    }
+
+    /// The range covering every span of a node
+    pub fn range_of(&self, spans: &[Span]) -> Option<Range> {
+        let start = spans.iter().map(|s| s.start).min()?;
+        let end = spans.iter().map(|s| s.end).max()?;
+        Some(Range::new(self.position(start), self.position(end + 1)))
+    }
+}
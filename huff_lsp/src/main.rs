@@ -0,0 +1,12 @@
+#![warn(missing_docs)]
+#![warn(unused_extern_crates)]
+#![forbid(unsafe_code)]
+
+//! The Huff Language Server, speaking LSP over stdin and stdout
+
+fn main() {
+    if let Err(e) = huff_lsp::server::run_stdio() {
+        eprintln!("huff-lsp: {e}");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,218 @@
+//! ## Server
+//!
+//! Dispatches the messages of an LSP client to the [Analysis] of its open documents. Documents
+//! are synced incrementally, and their diagnostics are published after every change.
+
+use crate::analysis::{semantic_tokens_legend, Analysis};
+use huff_lints::{config::LintConfig, Linter};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, RequestId, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+        Notification as LspNotification, PublishDiagnostics,
+    },
+    request::{
+        Completion, DocumentSymbolRequest, GotoDefinition, HoverRequest, References,
+        Request as LspRequest, SemanticTokensFullRequest,
+    },
+    CompletionOptions, CompletionResponse, DocumentSymbolResponse, GotoDefinitionResponse,
+    HoverProviderCapability, Location, OneOf, PublishDiagnosticsParams, SemanticTokens,
+    SemanticTokensFullOptions, SemanticTokensOptions, SemanticTokensResult,
+    SemanticTokensServerCapabilities, ServerCapabilities, TextDocumentSyncCapability,
+    TextDocumentSyncKind, Url,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{collections::HashMap, error::Error};
+
+/// The capabilities of the server, announced when initializing
+pub fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(
+            TextDocumentSyncKind::INCREMENTAL,
+        )),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec!["[".to_string(), "<".to_string()]),
+            ..Default::default()
+        }),
+        semantic_tokens_provider: Some(SemanticTokensServerCapabilities::SemanticTokensOptions(
+            SemanticTokensOptions {
+                legend: semantic_tokens_legend(),
+                full: Some(SemanticTokensFullOptions::Bool(true)),
+                ..Default::default()
+            },
+        )),
+        ..Default::default()
+    }
+}
+
+/// Runs the server over stdin and stdout until the client exits
+pub fn run_stdio() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let (connection, io_threads) = Connection::stdio();
+    connection.initialize(serde_json::to_value(capabilities())?)?;
+
+    // Lint rules are configured by the `huff.toml` of the workspace, like for `huffc`
+    let mut linter = Linter::default();
+    if let Err(e) = LintConfig::load(".").and_then(|config| linter.configure(&config)) {
+        tracing::error!(target: "lsp", "INVALID LINT CONFIGURATION: {}", e);
+    }
+    Server::new(linter).run(&connection)?;
+    io_threads.join()?;
+    Ok(())
+}
+
+/// The open documents of a client
+#[derive(Debug, Default)]
+pub struct Server {
+    /// The analysis of each open document
+    documents: HashMap<Url, Analysis>,
+    /// The lint rules reported as diagnostics
+    linter: Linter,
+}
+
+impl Server {
+    /// Public associated function that creates a server reporting the warnings of a linter
+    pub fn new(linter: Linter) -> Self {
+        Self { documents: HashMap::new(), linter }
+    }
+
+    /// Handles the messages of a connection until it shuts down
+    pub fn run(&mut self, connection: &Connection) -> Result<(), Box<dyn Error + Send + Sync>> {
+        for message in &connection.receiver {
+            if let Message::Request(request) = &message {
+                if connection.handle_shutdown(request)? {
+                    return Ok(())
+                }
+            }
+            for response in self.handle(message) {
+                connection.sender.send(response)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The analysis of an open document
+    pub fn document(&self, uri: &Url) -> Option<&Analysis> {
+        self.documents.get(uri)
+    }
+
+    /// Handles a message, returning the messages to send back
+    pub fn handle(&mut self, message: Message) -> Vec<Message> {
+        match message {
+            Message::Request(request) => vec![Message::Response(self.handle_request(request))],
+            Message::Notification(notification) => self.handle_notification(notification),
+            Message::Response(_) => vec![],
+        }
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        let id = request.id.clone();
+        match request.method.as_str() {
+            HoverRequest::METHOD => respond::<HoverRequest, _>(id, request, |params| {
+                let position = params.text_document_position_params;
+                self.document(&position.text_document.uri)?.hover(position.position)
+            }),
+            GotoDefinition::METHOD => respond::<GotoDefinition, _>(id, request, |params| {
+                let position = params.text_document_position_params;
+                let uri = position.text_document.uri;
+                let range = self.document(&uri)?.definition(position.position)?;
+                Some(GotoDefinitionResponse::Scalar(Location::new(uri, range)))
+            }),
+            References::METHOD => respond::<References, _>(id, request, |params| {
+                let position = params.text_document_position;
+                let uri = position.text_document.uri;
+                let ranges = self
+                    .document(&uri)?
+                    .references(position.position, params.context.include_declaration);
+                Some(ranges.into_iter().map(|range| Location::new(uri.clone(), range)).collect())
+            }),
+            Completion::METHOD => respond::<Completion, _>(id, request, |params| {
+                let position = params.text_document_position;
+                let items =
+                    self.document(&position.text_document.uri)?.completions(position.position);
+                Some(CompletionResponse::Array(items))
+            }),
+            DocumentSymbolRequest::METHOD => {
+                respond::<DocumentSymbolRequest, _>(id, request, |params| {
+                    let symbols = self.document(&params.text_document.uri)?.document_symbols();
+                    Some(DocumentSymbolResponse::Nested(symbols))
+                })
+            }
+            SemanticTokensFullRequest::METHOD => {
+                respond::<SemanticTokensFullRequest, _>(id, request, |params| {
+                    let data = self.document(&params.text_document.uri)?.semantic_tokens();
+                    Some(SemanticTokensResult::Tokens(SemanticTokens { result_id: None, data }))
+                })
+            }
+            method => Response::new_err(
+                id,
+                ErrorCode::MethodNotFound as i32,
+                format!("Unsupported request \"{method}\""),
+            ),
+        }
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Vec<Message> {
+        let uri = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let Some(params) = params::<DidOpenTextDocument>(notification) else {
+                    return vec![]
+                };
+                let document = params.text_document;
+                self.documents.insert(document.uri.clone(), Analysis::new(&document.text));
+                document.uri
+            }
+            DidChangeTextDocument::METHOD => {
+                let Some(params) = params::<DidChangeTextDocument>(notification) else {
+                    return vec![]
+                };
+                let uri = params.text_document.uri;
+                let Some(analysis) = self.documents.get_mut(&uri) else { return vec![] };
+                params.content_changes.iter().for_each(|change| analysis.apply_change(change));
+                uri
+            }
+            DidCloseTextDocument::METHOD => {
+                let Some(params) = params::<DidCloseTextDocument>(notification) else {
+                    return vec![]
+                };
+                self.documents.remove(&params.text_document.uri);
+                // Clear the diagnostics of the closed document
+                return vec![publish_diagnostics(params.text_document.uri, vec![])]
+            }
+            _ => return vec![],
+        };
+        let diagnostics =
+            self.documents.get(&uri).map(|a| a.diagnostics(&self.linter)).unwrap_or_default();
+        vec![publish_diagnostics(uri, diagnostics)]
+    }
+}
+
+/// Deserializes the parameters of a notification, `None` if they're invalid
+fn params<N: LspNotification>(notification: Notification) -> Option<N::Params> {
+    serde_json::from_value(notification.params)
+        .map_err(|e| tracing::error!(target: "lsp", "INVALID {} PARAMS: {}", N::METHOD, e))
+        .ok()
+}
+
+/// Responds to a request with the result of a handler, `null` if it has none
+fn respond<R, F>(id: RequestId, request: Request, handler: F) -> Response
+where
+    R: LspRequest,
+    R::Params: DeserializeOwned,
+    R::Result: Serialize,
+    F: FnOnce(R::Params) -> R::Result,
+{
+    match serde_json::from_value::<R::Params>(request.params) {
+        Ok(params) => Response::new_ok(id, handler(params)),
+        Err(e) => Response::new_err(id, ErrorCode::InvalidParams as i32, e.to_string()),
+    }
+}
+
+/// The notification publishing the diagnostics of a document
+fn publish_diagnostics(uri: Url, diagnostics: Vec<lsp_types::Diagnostic>) -> Message {
+    let params = PublishDiagnosticsParams { uri, diagnostics, version: None };
+    Message::Notification(Notification::new(PublishDiagnostics::METHOD.to_string(), params))
+}
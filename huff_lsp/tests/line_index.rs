@@ -0,0 +1,41 @@
+use huff_lsp::line_index::LineIndex;
+use huff_utils::prelude::Span;
+use lsp_types::{Position, Range};
+
+#[test]
+fn test_positions_count_utf16_units() {
+    // `𝔸` is two UTF-16 code units but a single character
+    let source = "#define macro A() = takes(0) returns(0) {}\n// 𝔸 é\nstop\n";
+    let index = LineIndex::new(source);
+
+    assert_eq!(index.position(0), Position::new(0, 0));
+    assert_eq!(index.position(43), Position::new(1, 0));
+    assert_eq!(index.position(46), Position::new(1, 3));
+    assert_eq!(index.position(48), Position::new(1, 6));
+    assert_eq!(index.position(50), Position::new(2, 0));
+    assert_eq!(index.position(1000), Position::new(3, 0));
+
+    for offset in 0..=source.chars().count() {
+        assert_eq!(index.offset(index.position(offset)), offset);
+    }
+}
+
+#[test]
+fn test_offsets_are_clamped() {
+    let index = LineIndex::new("add\nstop");
+    assert_eq!(index.offset(Position::new(0, 100)), 3);
+    assert_eq!(index.offset(Position::new(1, 100)), 8);
+    assert_eq!(index.offset(Position::new(5, 0)), 8);
+}
+
+#[test]
+fn test_ranges_of_spans() {
+    let index = LineIndex::new("add\nstop");
+    let span = |start, end| Span { start, end, file: None };
+    assert_eq!(index.range(&span(4, 7)), Range::new(Position::new(1, 0), Position::new(1, 4)));
+    assert_eq!(
+        index.range_of(&[span(4, 7), span(0, 2)]),
+        Some(Range::new(Position::new(0, 0), Position::new(1, 4)))
+    );
+    assert_eq!(index.range_of(&[]), None);
+}
@@ -0,0 +1,269 @@
+use huff_lsp::server::Server;
+use lsp_server::{Message, Notification, Request, RequestId};
+use serde_json::{json, Value};
+
+const URI: &str = "file:///contracts/Main.huff";
+
+const SOURCE: &str = r#"#define constant OWNER = FREE_STORAGE_POINTER()
+#define constant FEE = 0x10
+
+/* Reverts unless the caller is the owner */
+#define macro ONLY_OWNER(error) = takes(0) returns(0) {
+    [OWNER] sload caller eq authorized jumpi
+    <error> 0x00 mstore 0x20 0x00 revert
+    authorized:
+}
+
+#define macro MAIN() = takes(0) returns(0) {
+    ONLY_OWNER(0x01)
+    [FEE] 0x00 mstore
+    0x20 0x00 return
+}
+"#;
+
+fn notify(server: &mut Server, method: &str, params: Value) -> Vec<Message> {
+    server.handle(Message::Notification(Notification::new(method.to_string(), params)))
+}
+
+fn request(server: &mut Server, method: &str, params: Value) -> Value {
+    let responses = server.handle(Message::Request(Request::new(
+        RequestId::from(1),
+        method.to_string(),
+        params,
+    )));
+    match &responses[..] {
+        [Message::Response(response)] => {
+            assert!(response.error.is_none(), "{:?}", response.error);
+            response.result.clone().unwrap_or_default()
+        }
+        _ => panic!("Expected a single response, got {responses:?}"),
+    }
+}
+
+/// The params of a request at the position of the nth occurrence of a string
+fn at(find: &str, nth: usize) -> Value {
+    let (byte, _) = SOURCE.match_indices(find).nth(nth).unwrap();
+    let line = SOURCE[..byte].matches('\n').count();
+    let character = SOURCE[..byte].rsplit('\n').next().unwrap().chars().count();
+    json!({
+        "textDocument": { "uri": URI },
+        "position": { "line": line, "character": character },
+        "context": { "includeDeclaration": true },
+    })
+}
+
+fn open(server: &mut Server, text: &str) -> Value {
+    let messages = notify(
+        server,
+        "textDocument/didOpen",
+        json!({ "textDocument": { "uri": URI, "languageId": "huff", "version": 1, "text": text } }),
+    );
+    published(&messages)
+}
+
+/// The diagnostics published by a notification
+fn published(messages: &[Message]) -> Value {
+    match messages {
+        [Message::Notification(notification)] => {
+            assert_eq!(notification.method, "textDocument/publishDiagnostics");
+            assert_eq!(notification.params["uri"], URI);
+            notification.params["diagnostics"].clone()
+        }
+        _ => panic!("Expected diagnostics, got {messages:?}"),
+    }
+}
+
+#[test]
+fn test_diagnostics_follow_edits() {
+    let mut server = Server::default();
+    assert_eq!(open(&mut server, SOURCE), json!([]));
+
+    // Misspell an invocation, replacing the `ONLY_OWNER` of line 11
+    let diagnostics = published(&notify(
+        &mut server,
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": URI, "version": 2 },
+            "contentChanges": [{
+                "range": {
+                    "start": { "line": 11, "character": 4 },
+                    "end": { "line": 11, "character": 14 },
+                },
+                "text": "ONLY_ONWER",
+            }],
+        }),
+    ));
+    assert_eq!(diagnostics[0]["severity"], 1);
+    assert_eq!(
+        diagnostics[0]["message"],
+        "Undefined macro \"ONLY_ONWER\"\nDid you mean \"ONLY_OWNER\"?"
+    );
+    assert_eq!(diagnostics[0]["range"]["start"], json!({ "line": 11, "character": 4 }));
+    // The macro isn't invoked anymore
+    assert_eq!(diagnostics[1]["severity"], 2);
+    assert_eq!(diagnostics[1]["code"], "unused-macros");
+
+    // Syntax errors are reported with their code
+    let diagnostics = published(&notify(
+        &mut server,
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": URI, "version": 3 },
+            "contentChanges": [{ "text": "#define macro MAIN() = takes(0) returns(0) {\n    )\n}" }],
+        }),
+    ));
+    assert_eq!(diagnostics.as_array().unwrap().len(), 1);
+    assert_eq!(diagnostics[0]["code"], "H0106");
+    assert_eq!(diagnostics[0]["range"]["start"], json!({ "line": 1, "character": 4 }));
+
+    // Closing the document clears its diagnostics
+    let messages =
+        notify(&mut server, "textDocument/didClose", json!({ "textDocument": { "uri": URI } }));
+    assert_eq!(published(&messages), json!([]));
+    assert!(server.document(&URI.parse().unwrap()).is_none());
+}
+
+#[test]
+fn test_hover() {
+    let mut server = Server::default();
+    open(&mut server, SOURCE);
+
+    let hover = request(&mut server, "textDocument/hover", at("sload", 0));
+    let value = hover["contents"]["value"].as_str().unwrap();
+    assert!(value.contains("`0x54` · takes 1, returns 1"), "{value}");
+    assert!(value.contains("Load a word from Storage"), "{value}");
+
+    let hover = request(&mut server, "textDocument/hover", at("ONLY_OWNER(0x01)", 0));
+    assert_eq!(
+        hover["contents"]["value"],
+        "```huff\n#define macro ONLY_OWNER(error) = takes(0) returns(0)\n```"
+    );
+    assert_eq!(hover["range"]["start"], json!({ "line": 11, "character": 4 }));
+
+    let hover = request(&mut server, "textDocument/hover", at("FEE]", 0));
+    assert_eq!(hover["contents"]["value"], "```huff\n#define constant FEE = 0x10\n```");
+
+    assert_eq!(request(&mut server, "textDocument/hover", at("0x20", 0)), Value::Null);
+}
+
+#[test]
+fn test_definition_and_references() {
+    let mut server = Server::default();
+    open(&mut server, SOURCE);
+
+    let definition = request(&mut server, "textDocument/definition", at("authorized jumpi", 0));
+    assert_eq!(definition["uri"], URI);
+    assert_eq!(
+        definition["range"],
+        json!({ "start": { "line": 7, "character": 4 }, "end": { "line": 7, "character": 14 } })
+    );
+
+    let definition = request(&mut server, "textDocument/definition", at("error> 0x00", 0));
+    assert_eq!(definition["range"]["start"], json!({ "line": 4, "character": 25 }));
+
+    let references = request(&mut server, "textDocument/references", at("OWNER =", 0));
+    let lines = references
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["range"]["start"]["line"].as_u64().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(lines, vec![0, 5]);
+}
+
+#[test]
+fn test_completions() {
+    let mut server = Server::default();
+    let source = SOURCE.replace("[FEE] 0x00 mstore", "[F] <e> ");
+    open(&mut server, &source);
+
+    let labels = |completions: &Value| {
+        completions
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["label"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    // Constants within brackets
+    let mut params = at("FREE_STORAGE", 0);
+    params.as_object_mut().unwrap().remove("context");
+    params["position"] = json!({ "line": 12, "character": 5 });
+    let completions = request(&mut server, "textDocument/completion", params.clone());
+    assert_eq!(labels(&completions), vec!["OWNER", "FEE"]);
+
+    // Parameters of the current macro within angle brackets, `MAIN` has none
+    params["position"] = json!({ "line": 12, "character": 9 });
+    let completions = request(&mut server, "textDocument/completion", params.clone());
+    assert!(labels(&completions).is_empty());
+    params["position"] = json!({ "line": 6, "character": 5 });
+    let completions = request(&mut server, "textDocument/completion", params.clone());
+    assert_eq!(labels(&completions), vec!["error"]);
+
+    // Opcodes, builtins, macros, constants and the labels of the current macro elsewhere
+    params["position"] = json!({ "line": 6, "character": 0 });
+    let completions = request(&mut server, "textDocument/completion", params);
+    let labels = labels(&completions);
+    for label in ["sload", "push0", "__FUNC_SIG", "ONLY_OWNER", "MAIN", "OWNER", "authorized"] {
+        assert!(labels.contains(&label.to_string()), "{label}");
+    }
+    let owner = completions.as_array().unwrap().iter().find(|c| c["label"] == "OWNER").unwrap();
+    assert_eq!(owner["insertText"], "[OWNER]");
+}
+
+#[test]
+fn test_document_symbols() {
+    let mut server = Server::default();
+    open(&mut server, SOURCE);
+
+    let symbols = request(
+        &mut server,
+        "textDocument/documentSymbol",
+        json!({ "textDocument": { "uri": URI } }),
+    );
+    let names =
+        symbols.as_array().unwrap().iter().map(|s| s["name"].as_str().unwrap()).collect::<Vec<_>>();
+    assert_eq!(names, vec!["OWNER", "FEE", "ONLY_OWNER", "MAIN"]);
+
+    let only_owner = &symbols[2];
+    assert_eq!(only_owner["detail"], "#define macro ONLY_OWNER(error) = takes(0) returns(0)");
+    assert_eq!(only_owner["range"]["start"], json!({ "line": 4, "character": 0 }));
+    assert_eq!(only_owner["range"]["end"], json!({ "line": 8, "character": 1 }));
+    assert_eq!(only_owner["selectionRange"]["start"], json!({ "line": 4, "character": 14 }));
+    assert_eq!(only_owner["children"][0]["name"], "authorized");
+}
+
+#[test]
+fn test_semantic_tokens() {
+    let mut server = Server::default();
+    open(&mut server, SOURCE);
+
+    let tokens = request(
+        &mut server,
+        "textDocument/semanticTokens/full",
+        json!({ "textDocument": { "uri": URI } }),
+    );
+    let data =
+        tokens["data"].as_array().unwrap().iter().filter_map(Value::as_u64).collect::<Vec<_>>();
+    // Each token is its line and start relative to the previous token, length, type and
+    // modifiers: `#define`, `constant`, the `OWNER` declaration and the builtin
+    assert_eq!(data[..20], [0, 0, 7, 0, 0, 0, 8, 8, 0, 0, 0, 9, 5, 7, 3, 0, 8, 22, 4, 4]);
+
+    // The comment on its own line, then `macro` and the `ONLY_OWNER` declaration
+    let comment = data.chunks(5).position(|t| t[3] == 14).unwrap();
+    assert_eq!(data[comment * 5..comment * 5 + 5], [2, 0, 44, 14, 0]);
+    assert_eq!(data[comment * 5 + 10..comment * 5 + 20], [0, 8, 5, 0, 0, 0, 6, 10, 3, 1]);
+}
+
+#[test]
+fn test_unsupported_requests() {
+    let mut server = Server::default();
+    let responses = server.handle(Message::Request(Request::new(
+        RequestId::from(1),
+        "textDocument/formatting".to_string(),
+        json!({}),
+    )));
+    let [Message::Response(response)] = &responses[..] else { panic!() };
+    assert!(response.error.is_some());
+}
@@ -0,0 +1,237 @@
+//! ## Incremental Parsing
+//!
+//! A [Document] keeps the source of a file split into its top-level items - definitions,
+//! imports and assertions - each parsed on its own. Applying a [TextEdit] only re-lexes and
+//! re-parses the items the edit touches, reusing the AST of every other item.
+//!
+//! Offsets are in characters, like the [Span]s of tokens. Items are parsed tolerantly, see
+//! [Parser::parse_tolerant], and `#ifdef` conditionals aren't applied.
+
+use crate::Parser;
+use huff_lexer::Lexer;
+use huff_utils::{
+    ast::*,
+    error::LexicalError,
+    prelude::Span,
+    token::{Token, TokenKind},
+    visit::VisitMut,
+};
+use std::ops::Range;
+
+/// A replacement of a range of the source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// The replaced range, in characters
+    pub range: Range<usize>,
+    /// The text replacing the range
+    pub text: String,
+}
+
+/// A top-level item of a document
+#[derive(Debug, Clone)]
+struct Item {
+    /// The character offset the item starts at, up to the start of the next item
+    start: usize,
+    /// The definitions parsed from the item
+    contract: Contract,
+    /// The lexical errors of the item
+    lexical_errors: Vec<LexicalError>,
+}
+
+/// A source file parsed item by item, for incremental re-parsing
+#[derive(Debug, Clone)]
+pub struct Document {
+    /// The source of the document
+    source: String,
+    /// The base path for resolving imports
+    base: Option<String>,
+    /// The items of the source, in order
+    items: Vec<Item>,
+}
+
+impl Document {
+    /// Public associated function that lexes and parses a Document.
+    pub fn new(source: &str, base: Option<String>) -> Self {
+        let mut document = Self { source: source.to_string(), base, items: vec![] };
+        document.items = document.parse_region(0, document.len()).0;
+        document
+    }
+
+    /// The current source of the document
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The number of top-level items in the document
+    pub fn item_count(&self) -> usize {
+        self.items.len()
+    }
+
+    /// The contract of the document, merged from the AST of each item
+    pub fn contract(&self) -> Contract {
+        let mut contract = Contract::default();
+        for item in &self.items {
+            let c = &item.contract;
+            contract.macros.extend(c.macros.iter().cloned());
+            contract.invocations.extend(c.invocations.iter().cloned());
+            contract.imports.extend(c.imports.iter().cloned());
+            contract.selective_imports.extend(c.selective_imports.iter().cloned());
+            contract.full_imports.extend(c.full_imports.iter().cloned());
+            contract.namespaced_imports.extend(c.namespaced_imports.iter().cloned());
+            if let (Ok(mut constants), Ok(item_constants)) =
+                (contract.constants.lock(), c.constants.lock())
+            {
+                constants.extend(item_constants.iter().cloned());
+            }
+            contract.immutables.extend(c.immutables.iter().cloned());
+            contract.errors.extend(c.errors.iter().cloned());
+            contract.functions.extend(c.functions.iter().cloned());
+            contract.events.extend(c.events.iter().cloned());
+            contract.tables.extend(c.tables.iter().cloned());
+            contract.assertions.extend(c.assertions.iter().cloned());
+            contract.invalid_definitions.extend(c.invalid_definitions.iter().cloned());
+            contract.trivia.0.extend(c.trivia.0.iter().map(|(k, v)| (*k, v.clone())));
+        }
+        contract
+    }
+
+    /// The lexical errors of the document
+    pub fn lexical_errors(&self) -> Vec<LexicalError> {
+        self.items.iter().flat_map(|i| i.lexical_errors.iter().cloned()).collect()
+    }
+
+    /// Applies an edit to the source, re-parsing the items it touches
+    ///
+    /// The range is clamped to the source. Returns the number of items re-parsed.
+    pub fn apply_edit(&mut self, edit: &TextEdit) -> usize {
+        let len = self.len();
+        let end = edit.range.end.min(len);
+        let start = edit.range.start.min(end);
+
+        // Edits can break the token an item starts with, merging it into the item before
+        let first = self.items.partition_point(|i| i.start < start).saturating_sub(2);
+        let last = self.items.partition_point(|i| i.start <= end).saturating_sub(1).max(first);
+        let region_start = self.items.get(first).map_or(0, |i| i.start);
+        let region_end = self.items.get(last + 1).map_or(len, |i| i.start);
+
+        let (byte_start, byte_end) = (self.byte_offset(start), self.byte_offset(end));
+        self.source.replace_range(byte_start..byte_end, &edit.text);
+        let delta = edit.text.chars().count() as isize - (end - start) as isize;
+
+        // A comment or string left open swallows the rest of the source, so re-parse all of it
+        let region_end = (region_end as isize + delta) as usize;
+        let mut replaced = first..(last + 1).min(self.items.len());
+        let (mut items, open_ended) = self.parse_region(region_start, region_end);
+        if open_ended && region_end < self.len() {
+            items = self.parse_region(region_start, self.len()).0;
+            replaced.end = self.items.len();
+        }
+
+        for item in &mut self.items[replaced.end..] {
+            item.start = (item.start as isize + delta) as usize;
+            shift_contract(&mut item.contract, delta);
+            item.lexical_errors.iter_mut().for_each(|e| shift_span(&mut e.span, delta));
+        }
+        let count = items.len();
+        self.items.splice(replaced, items);
+        count
+    }
+
+    /// The length of the source in characters
+    fn len(&self) -> usize {
+        self.source.chars().count()
+    }
+
+    /// The byte offset of a character offset
+    fn byte_offset(&self, offset: usize) -> usize {
+        self.source.char_indices().nth(offset).map_or(self.source.len(), |(i, _)| i)
+    }
+
+    /// Lexes a region of the source and parses its items
+    ///
+    /// Also returns whether the region ends with a comment or string, which could continue past
+    /// the region.
+    fn parse_region(&self, start: usize, end: usize) -> (Vec<Item>, bool) {
+        let region = &self.source[self.byte_offset(start)..self.byte_offset(end)];
+        let mut chunks: Vec<(usize, Vec<Token>, Vec<LexicalError>)> = vec![(start, vec![], vec![])];
+        let mut started = false;
+        let mut decorated = false;
+        let mut last = None;
+        for result in Lexer::new(region) {
+            let mut token = match result {
+                Ok(token) => token,
+                Err(mut e) => {
+                    shift_span(&mut e.span, start as isize);
+                    if let Some((_, _, errors)) = chunks.last_mut() {
+                        errors.push(e);
+                    }
+                    continue
+                }
+            };
+            shift_span(&mut token.span, start as isize);
+            if !matches!(token.kind, TokenKind::Whitespace | TokenKind::Eof) {
+                last = Some(token.clone());
+            }
+            // A definition following a decorator is part of the same item
+            let starts_item = match token.kind {
+                TokenKind::Pound | TokenKind::Include | TokenKind::Assert => true,
+                TokenKind::Define => !std::mem::take(&mut decorated),
+                _ => false,
+            };
+            decorated |= token.kind == TokenKind::Pound;
+            if starts_item && started {
+                chunks.push((token.span.start, vec![], vec![]));
+            }
+            started |= !matches!(token.kind, TokenKind::Whitespace | TokenKind::Comment(_));
+            if let (Some((_, tokens, _)), false) = (chunks.last_mut(), token.kind == TokenKind::Eof)
+            {
+                tokens.push(token);
+            }
+        }
+
+        let ends = chunks.iter().skip(1).map(|(start, ..)| *start).chain([end]).collect::<Vec<_>>();
+        let items = chunks
+            .into_iter()
+            .zip(ends)
+            .map(|((start, mut tokens, lexical_errors), end)| {
+                tokens.push(Token::new(TokenKind::Eof, Span { start: end, end, file: None }));
+                let contract = match Parser::new(tokens, self.base.clone()) {
+                    Ok(mut parser) => parser.parse_tolerant(),
+                    Err(_) => Contract::default(),
+                };
+                Item { start, contract, lexical_errors }
+            })
+            .collect();
+        let open_ended = last.map_or(false, |t| {
+            matches!(t.kind, TokenKind::Comment(_) | TokenKind::Str(_)) && t.span.end + 1 >= end
+        });
+        (items, open_ended)
+    }
+}
+
+/// Shifts a span by a number of characters
+fn shift_span(span: &mut Span, delta: isize) {
+    span.start = (span.start as isize + delta) as usize;
+    span.end = (span.end as isize + delta) as usize;
+}
+
+/// Shifts the spans of every node of a contract by a number of characters
+struct Shift(isize);
+
+impl VisitMut for Shift {
+    fn visit_span_mut(&mut self, span: &mut AstSpan) {
+        span.0.iter_mut().for_each(|s| shift_span(s, self.0));
+    }
+}
+
+/// Shifts the spans of every definition of a contract, and its trivia
+fn shift_contract(contract: &mut Contract, delta: isize) {
+    Shift(delta).visit_contract_mut(contract);
+    contract.trivia.0 = std::mem::take(&mut contract.trivia.0)
+        .into_iter()
+        .map(|(start, mut trivia)| {
+            trivia.iter_mut().for_each(|t| shift_span(&mut t.span, delta));
+            ((start as isize + delta) as usize, trivia)
+        })
+        .collect();
+}
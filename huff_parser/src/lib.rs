@@ -7,12 +7,18 @@
 use huff_utils::{
     ast::*,
     error::*,
-    files,
-    prelude::{bytes32_to_string, hash_bytes, str_to_bytes32, Span},
+    evm::Opcode,
+    prelude::{
+        bytes32_to_string, format_even_bytes, hash_bytes, pad_n_bytes, str_to_bytes32, Span,
+    },
     token::{Token, TokenKind},
     types::*,
 };
 use regex::Regex;
+use std::str::FromStr;
+
+/// Incremental Parsing Module
+pub mod incremental;
 
 /// The Parser
 #[derive(Debug, Clone)]
@@ -27,116 +33,267 @@ pub struct Parser {
     pub base: Option<String>,
     /// A collection of current spans
     pub spans: Vec<Span>,
-    /// Our remapper
-    pub remapper: files::Remapper,
 }
 
 impl Parser {
     /// Public associated function that instantiates a Parser.
-    pub fn new(tokens: Vec<Token>, base: Option<String>) -> Self {
-        let initial_token = tokens.get(0).unwrap().clone();
-        let remapper = files::Remapper::new("./");
-        Self { tokens, cursor: 0, current_token: initial_token, base, spans: vec![], remapper }
+    ///
+    /// Errors if the tokens vec is empty.
+    pub fn new(tokens: Vec<Token>, base: Option<String>) -> Result<Self, ParserError> {
+        let initial_token = tokens.get(0).cloned().ok_or_else(Self::empty_token_stream)?;
+        Ok(Self { tokens, cursor: 0, current_token: initial_token, base, spans: vec![] })
     }
 
     /// Resets the current token and cursor to the first token in the parser's token vec
     ///
-    /// PANICS if the tokens vec is empty!
-    pub fn reset(&mut self) {
-        self.current_token = self.tokens.get(0).unwrap().clone();
+    /// Errors if the tokens vec is empty.
+    pub fn reset(&mut self) -> Result<(), ParserError> {
+        self.current_token = self.tokens.get(0).cloned().ok_or_else(Self::empty_token_stream)?;
         self.cursor = 0;
+        Ok(())
+    }
+
+    /// The error for a parser without tokens
+    fn empty_token_stream() -> ParserError {
+        ParserError {
+            kind: ParserErrorKind::EmptyTokenStream,
+            hint: Some("Lex the source before parsing it".to_string()),
+            spans: AstSpan(vec![]),
+        }
     }
 
     /// Parse
     pub fn parse(&mut self) -> Result<Contract, ParserError> {
-        // Remove all whitespaces, newlines, and comments first
-        self.tokens
-            .retain(|token| !matches!(token.kind, TokenKind::Whitespace | TokenKind::Comment(_)));
+        let (contract, mut errors) = self.parse_definitions(false);
+        match errors.is_empty() {
+            true => Ok(contract),
+            false => Err(errors.remove(0)),
+        }
+    }
 
-        // Reset the initial token
-        self.reset();
+    /// Parses every definition, recovering from errors by skipping to the next `#define`,
+    /// `#include`, `#assert` or decorator
+    ///
+    /// Returns all the errors found instead of stopping at the first one.
+    pub fn parse_recovering(&mut self) -> Result<Contract, Vec<ParserError>> {
+        let (contract, errors) = self.parse_definitions(true);
+        match errors.is_empty() {
+            true => Ok(contract),
+            false => Err(errors),
+        }
+    }
+
+    /// Parses a best-effort, partial contract, eg. for an editor while the source is being typed
+    ///
+    /// Recovers from errors like [parse_recovering](Parser::parse_recovering), keeping each item
+    /// that failed to parse as an [InvalidDefinition] of the contract.
+    pub fn parse_tolerant(&mut self) -> Contract {
+        let (mut contract, errors) = self.parse_definitions(true);
+        // An empty token stream errors before any item is parsed
+        if contract.invalid_definitions.is_empty() {
+            contract.invalid_definitions.extend(errors.into_iter().map(|error| {
+                InvalidDefinition { kind: None, name: None, error, span: AstSpan(vec![]) }
+            }));
+        }
+        contract
+    }
+
+    /// Parses the definitions of the contract, stopping at the first error unless recovering
+    fn parse_definitions(&mut self, recover: bool) -> (Contract, Vec<ParserError>) {
+        // Move all whitespaces, newlines, and comments to the trivia first
+        let trivia = self.take_trivia();
 
         // Initialize an empty Contract
-        let mut contract = Contract::default();
+        let mut contract = Contract { trivia, ..Default::default() };
+        let mut errors = vec![];
+
+        // Reset the initial token
+        if let Err(e) = self.reset() {
+            errors.push(e);
+            return (contract, errors)
+        }
 
         // Iterate over tokens and construct the Contract aka AST
         while !self.check(TokenKind::Eof) {
             // Reset our spans
             self.spans = vec![];
 
-            // Check for imports with the "#include" keyword
-            if self.check(TokenKind::Include) {
-                contract.imports.push(self.parse_imports()?);
+            let start = self.cursor;
+            if let Err(e) = self.parse_definition(&mut contract) {
+                tracing::error!(target: "parser", "FAILED TO PARSE DEFINITION: {:?}", e.kind);
+                errors.push(e.clone());
+                if !recover {
+                    break
+                }
+                self.synchronize(start);
+                contract.invalid_definitions.push(self.invalid_definition(start, e));
             }
-            // Check for a decorator above a test macro
-            else if self.check(TokenKind::Pound) {
-                let m = self.parse_macro()?;
-                tracing::info!(target: "parser", "SUCCESSFULLY PARSED MACRO {}", m.name);
-                contract.macros.push(m);
+        }
+
+        (contract, errors)
+    }
+
+    /// Removes the whitespace and comment tokens, collecting them into a trivia table
+    fn take_trivia(&mut self) -> TriviaTable {
+        let mut table = TriviaTable::default();
+        let mut pending = vec![];
+        for token in &self.tokens {
+            match &token.kind {
+                TokenKind::Whitespace => {
+                    pending.push(Trivia { kind: TriviaKind::Whitespace, span: token.span.clone() })
+                }
+                TokenKind::Comment(c) => pending.push(Trivia {
+                    kind: TriviaKind::Comment(c.clone()),
+                    span: token.span.clone(),
+                }),
+                _ if !pending.is_empty() => {
+                    table.0.insert(token.span.start, std::mem::take(&mut pending));
+                }
+                _ => {}
             }
-            // Check for a defition with the "#define" keyword
-            else if self.check(TokenKind::Define) {
-                // Consume the definition token
-                self.match_kind(TokenKind::Define)?;
-
-                // match to fucntion, constant, macro, or event
-                match self.current_token.kind {
-                    TokenKind::Function => {
-                        let func = self.parse_function()?;
-                        tracing::info!(target: "parser", "SUCCESSFULLY PARSED FUNCTION {}", func.name);
-                        contract.functions.push(func);
-                    }
-                    TokenKind::Event => {
-                        let ev = self.parse_event()?;
-                        tracing::info!(target: "parser", "SUCCESSFULLY PARSED EVENT {}", ev.name);
-                        contract.events.push(ev);
-                    }
-                    TokenKind::Constant => {
-                        let c = self.parse_constant()?;
-                        tracing::info!(target: "parser", "SUCCESSFULLY PARSED CONSTANT {}", c.name);
-                        contract.constants.lock().unwrap().push(c);
-                    }
-                    TokenKind::Error => {
-                        let e = self.parse_custom_error()?;
-                        tracing::info!(target: "parser", "SUCCESSFULLY PARSED ERROR {}", e.name);
-                        contract.errors.push(e);
-                    }
-                    TokenKind::Macro | TokenKind::Fn | TokenKind::Test => {
-                        let m = self.parse_macro()?;
-                        tracing::info!(target: "parser", "SUCCESSFULLY PARSED MACRO {}", m.name);
-                        contract.macros.push(m);
-                    }
-                    TokenKind::JumpTable | TokenKind::JumpTablePacked | TokenKind::CodeTable => {
-                        contract.tables.push(self.parse_table()?);
-                    }
-                    _ => {
-                        tracing::error!(
-                            target: "parser",
-                            "Invalid definition. Must be a function, event, constant, error, or macro. Got: {}",
-                            self.current_token.kind
-                        );
-                        return Err(ParserError {
-                            kind: ParserErrorKind::InvalidDefinition(self.current_token.kind.clone()),
-                            hint: Some("Definition must be one of: `function`, `event`, `constant`, `error`, `macro`, `fn`, or `test`.".to_string()),
-                            spans: AstSpan(vec![self.current_token.span.clone()]),
-                        });
-                    }
-                };
+        }
+        // Without an EOF token, the trailing trivia is keyed by the end of the source
+        if let Some(last) = pending.last() {
+            table.0.insert(last.span.end + 1, pending);
+        }
+        self.tokens
+            .retain(|token| !matches!(token.kind, TokenKind::Whitespace | TokenKind::Comment(_)));
+        table
+    }
+
+    /// Parses a top-level item into the contract: an import, assertion or definition
+    pub fn parse_definition(&mut self, contract: &mut Contract) -> Result<(), ParserError> {
+        // Check for imports with the "#include" keyword
+        if self.check(TokenKind::Include) {
+            let path = self.parse_imports()?;
+            // Check for a list of selectively imported definitions
+            if self.check(TokenKind::OpenBrace) {
+                contract.selective_imports.push(self.parse_import_selection(path.clone())?);
             } else {
-                // If we don't have an "#include" or "#define" keyword, we have an invalid token
-                return Err(ParserError {
-                    kind: ParserErrorKind::UnexpectedType(self.current_token.kind.clone()),
-                    hint: Some(format!(
-                        "Expected either \"{}\" or \"{}\"",
-                        TokenKind::Define,
-                        TokenKind::Include
-                    )),
-                    spans: AstSpan(self.spans.clone()),
-                })
+                contract.full_imports.push((path.clone(), AstSpan(self.spans.clone())));
+            }
+            // Check for a namespace for the imported definitions
+            if self.check(TokenKind::Ident("as".to_string())) {
+                contract.namespaced_imports.push(self.parse_import_namespace(path.clone())?);
             }
+            contract.imports.push(path);
+        }
+        // Check for compile-time assertions
+        else if self.check(TokenKind::Assert) {
+            let a = self.parse_assertion()?;
+            tracing::info!(target: "parser", "SUCCESSFULLY PARSED ASSERTION {:?}", a);
+            contract.assertions.push(a);
+        }
+        // Check for a decorator above a test macro
+        else if self.check(TokenKind::Pound) {
+            let m = self.parse_macro()?;
+            tracing::info!(target: "parser", "SUCCESSFULLY PARSED MACRO {}", m.name);
+            contract.macros.push(m);
+        }
+        // Check for a defition with the "#define" keyword
+        else if self.check(TokenKind::Define) {
+            // Consume the definition token
+            self.match_kind(TokenKind::Define)?;
+
+            // match to fucntion, constant, macro, or event
+            match self.current_token.kind {
+                TokenKind::Function => {
+                    let func = self.parse_function()?;
+                    tracing::info!(target: "parser", "SUCCESSFULLY PARSED FUNCTION {}", func.name);
+                    contract.functions.push(func);
+                }
+                TokenKind::Event => {
+                    let ev = self.parse_event()?;
+                    tracing::info!(target: "parser", "SUCCESSFULLY PARSED EVENT {}", ev.name);
+                    contract.events.push(ev);
+                }
+                TokenKind::Constant => {
+                    let c = self.parse_constant()?;
+                    tracing::info!(target: "parser", "SUCCESSFULLY PARSED CONSTANT {}", c.name);
+                    contract.constants.lock().unwrap().push(c);
+                }
+                TokenKind::Immutable => {
+                    let i = self.parse_immutable()?;
+                    tracing::info!(target: "parser", "SUCCESSFULLY PARSED IMMUTABLE {}", i.name);
+                    contract.immutables.push(i);
+                }
+                TokenKind::Error => {
+                    let e = self.parse_custom_error()?;
+                    tracing::info!(target: "parser", "SUCCESSFULLY PARSED ERROR {}", e.name);
+                    contract.errors.push(e);
+                }
+                TokenKind::Macro | TokenKind::Fn | TokenKind::Test => {
+                    let m = self.parse_macro()?;
+                    tracing::info!(target: "parser", "SUCCESSFULLY PARSED MACRO {}", m.name);
+                    contract.macros.push(m);
+                }
+                TokenKind::JumpTable | TokenKind::JumpTablePacked | TokenKind::CodeTable => {
+                    contract.tables.push(self.parse_table()?);
+                }
+                _ => {
+                    tracing::error!(
+                        target: "parser",
+                        "Invalid definition. Must be a function, event, constant, error, or macro. Got: {}",
+                        self.current_token.kind
+                    );
+                    return Err(ParserError {
+                        kind: ParserErrorKind::InvalidDefinition(self.current_token.kind.clone()),
+                        hint: Some("Definition must be one of: `function`, `event`, `constant`, `immutable`, `error`, `macro`, `fn`, or `test`.".to_string()),
+                        spans: AstSpan(vec![self.current_token.span.clone()]),
+                    });
+                }
+            };
+        } else {
+            // If we don't have an "#include" or "#define" keyword, we have an invalid token
+            return Err(ParserError {
+                kind: ParserErrorKind::UnexpectedType(self.current_token.kind.clone()),
+                hint: Some(format!(
+                    "Expected either \"{}\", \"{}\" or \"{}\"",
+                    TokenKind::Define,
+                    TokenKind::Include,
+                    TokenKind::Assert
+                )),
+                spans: AstSpan(self.spans.clone()),
+            })
         }
 
-        Ok(contract)
+        Ok(())
+    }
+
+    /// Skips tokens up to the next top-level item, to resume parsing after an error in the item
+    /// starting at the `start` cursor
+    fn synchronize(&mut self, start: usize) {
+        let at_item = |p: &mut Self| {
+            p.check(TokenKind::Define) ||
+                p.check(TokenKind::Include) ||
+                p.check(TokenKind::Assert) ||
+                p.check(TokenKind::Pound)
+        };
+        // Skip at least the token the failed item starts with
+        if self.cursor == start && !self.check(TokenKind::Eof) {
+            self.consume();
+        }
+        while !self.check(TokenKind::Eof) && !at_item(self) {
+            self.consume();
+        }
+        tracing::debug!(target: "parser", "RESUMING PARSING AT TOKEN {}", self.cursor);
+    }
+
+    /// Builds the error node for the item between the start and the current token
+    fn invalid_definition(&self, start: usize, error: ParserError) -> InvalidDefinition {
+        let tokens = &self.tokens[start..self.cursor.max(start)];
+        // Skip any decorator to the `#define`
+        let mut definition = tokens.iter().skip_while(|t| t.kind != TokenKind::Define).skip(1);
+        let kind = definition
+            .next()
+            .map(|t| t.kind.clone())
+            .filter(|kind| !matches!(kind, TokenKind::Ident(_)));
+        let name = definition.next().and_then(|t| match &t.kind {
+            TokenKind::Ident(name) => Some(name.clone()),
+            _ => None,
+        });
+        let span = AstSpan(tokens.iter().map(|t| t.span.clone()).collect());
+        InvalidDefinition { kind, name, error, span }
     }
 
     /// Parses Contract Imports
@@ -164,6 +321,63 @@ impl Parser {
         Ok(std::path::PathBuf::from(p))
     }
 
+    /// Parses the brace-enclosed list of definitions following an import path
+    pub fn parse_import_selection(
+        &mut self,
+        path: FilePath,
+    ) -> Result<SelectiveImport, ParserError> {
+        self.match_kind(TokenKind::OpenBrace)?;
+
+        let mut names: Vec<String> = vec![];
+        while !self.check(TokenKind::CloseBrace) {
+            match self.current_token.kind.clone() {
+                TokenKind::Ident(name) => {
+                    names.push(name);
+                    self.consume();
+                }
+                kind => {
+                    tracing::error!(target: "parser", "INVALID IMPORT SELECTION: {}", kind);
+                    return Err(ParserError {
+                        kind: ParserErrorKind::InvalidName(kind.clone()),
+                        hint: Some(format!("Expected the name of a definition. Got: \"{kind}\"")),
+                        spans: AstSpan(vec![self.current_token.span.clone()]),
+                    })
+                }
+            }
+            if self.check(TokenKind::Comma) {
+                self.consume();
+            } else {
+                break
+            }
+        }
+        self.match_kind(TokenKind::CloseBrace)?;
+
+        Ok(SelectiveImport { path, names, span: AstSpan(self.spans.clone()) })
+    }
+
+    /// Parses the `as Namespace` following an import path
+    pub fn parse_import_namespace(
+        &mut self,
+        path: FilePath,
+    ) -> Result<NamespacedImport, ParserError> {
+        self.match_kind(TokenKind::Ident("as".to_string()))?;
+
+        match self.current_token.kind.clone() {
+            TokenKind::Ident(namespace) if !namespace.contains('.') => {
+                self.consume();
+                Ok(NamespacedImport { path, namespace, span: AstSpan(self.spans.clone()) })
+            }
+            kind => {
+                tracing::error!(target: "parser", "INVALID IMPORT NAMESPACE: {}", kind);
+                Err(ParserError {
+                    kind: ParserErrorKind::InvalidName(kind.clone()),
+                    hint: Some(format!("Expected a namespace for the import. Got: \"{kind}\"")),
+                    spans: AstSpan(vec![self.current_token.span.clone()]),
+                })
+            }
+        }
+    }
+
     /// Match current token to a type.
     pub fn match_kind(&mut self, kind: TokenKind) -> Result<TokenKind, ParserError> {
         if std::mem::discriminant(&self.current_token.kind) == std::mem::discriminant(&kind) {
@@ -186,16 +400,22 @@ impl Parser {
     }
 
     /// Consumes the next token.
+    ///
+    /// Past the last token, the current token is EOF.
     pub fn consume(&mut self) {
         self.spans.push(self.current_token.span.clone());
-        self.current_token = self.peek().unwrap();
-        self.cursor += 1;
+        match self.peek() {
+            Some(token) => {
+                self.current_token = token;
+                self.cursor += 1;
+            }
+            None => self.current_token = self.eof(),
+        }
     }
 
     /// Consumes following tokens until not contained in the kinds vec of TokenKinds.
     pub fn consume_all(&mut self, kinds: Vec<TokenKind>) {
-        loop {
-            let token = self.peek().unwrap();
+        while let Some(token) = self.peek() {
             if !kinds.contains(&token.kind) {
                 break
             }
@@ -206,20 +426,19 @@ impl Parser {
 
     /// Take a look at next token without consuming.
     pub fn peek(&mut self) -> Option<Token> {
-        if self.cursor >= self.tokens.len() {
-            None
-        } else {
-            Some(self.tokens.get(self.cursor + 1).unwrap().clone())
-        }
+        self.tokens.get(self.cursor + 1).cloned()
     }
 
     /// Take a look at the previous token.
     pub fn peek_behind(&self) -> Option<Token> {
-        if self.cursor == 0 || self.cursor > self.tokens.len() {
-            None
-        } else {
-            Some(self.tokens.get(self.cursor - 1).unwrap().clone())
-        }
+        self.cursor.checked_sub(1).and_then(|i| self.tokens.get(i)).cloned()
+    }
+
+    /// An EOF token at the end of the last token, for token streams missing one
+    fn eof(&self) -> Token {
+        let end = self.tokens.last().map(|t| t.span.end).unwrap_or_default();
+        let file = self.tokens.last().and_then(|t| t.span.file.clone());
+        Token::new(TokenKind::Eof, Span { start: end, end, file })
     }
 
     /// Parses a function.
@@ -343,16 +562,39 @@ impl Parser {
                 self.consume();
                 ConstVal::FreeStoragePointer(FreeStoragePointer {})
             }
-            TokenKind::Literal(l) => {
+            TokenKind::Literal(l, _) => {
                 self.consume();
                 ConstVal::Literal(l)
             }
+            TokenKind::Ident(ident) if ident == "env" => {
+                self.consume();
+                self.match_kind(TokenKind::OpenParen)?;
+                let var = match self.match_kind(TokenKind::Str(String::default()))? {
+                    TokenKind::Str(var) => var,
+                    _ => unreachable!(),
+                };
+                self.match_kind(TokenKind::CloseParen)?;
+                ConstVal::Env(var)
+            }
+            TokenKind::Ident(ident) if ident == "STORAGE_SLOT" => {
+                self.consume();
+                self.match_kind(TokenKind::OpenParen)?;
+                let slot = match self.match_kind(TokenKind::Literal(Literal::default(), 0))? {
+                    TokenKind::Literal(slot, _) => slot,
+                    _ => unreachable!(),
+                };
+                self.match_kind(TokenKind::CloseParen)?;
+                ConstVal::StorageSlot(slot)
+            }
+            TokenKind::Ident(_) | TokenKind::OpenBracket => {
+                ConstVal::Expression(self.parse_constant_expression()?)
+            }
             kind => {
-                tracing::error!(target: "parser", "TOKEN MISMATCH - EXPECTED FreeStoragePointer OR Literal, GOT: {}", self.current_token.kind);
+                tracing::error!(target: "parser", "TOKEN MISMATCH - EXPECTED FreeStoragePointer, Literal OR Expression, GOT: {}", self.current_token.kind);
                 return Err(ParserError {
                     kind: ParserErrorKind::InvalidConstantValue(kind),
                     hint: Some(
-                        "Expected constant value to be a literal or `FREE_STORAGE_POINTER()`"
+                        "Expected constant value to be a literal, an expression, `env(\"VAR\")`, `STORAGE_SLOT(n)` or `FREE_STORAGE_POINTER()`"
                             .to_string(),
                     ),
                     spans: AstSpan(vec![self.current_token.span.clone()]),
@@ -368,6 +610,150 @@ impl Parser {
         Ok(ConstantDefinition { name, value, span: AstSpan(new_spans) })
     }
 
+    /// Parses an immutable.
+    pub fn parse_immutable(&mut self) -> Result<ImmutableDefinition, ParserError> {
+        // Immutable Identifier
+        self.match_kind(TokenKind::Immutable)?;
+
+        // Parse the immutable name
+        let name = match self.match_kind(TokenKind::Ident("x".to_string()))? {
+            TokenKind::Ident(immutable_name) => immutable_name,
+            _ => unreachable!(),
+        };
+
+        // Clone spans and set to nothing
+        let new_spans = self.spans.clone();
+        self.spans = vec![];
+
+        Ok(ImmutableDefinition { name, span: AstSpan(new_spans) })
+    }
+
+    /// Parses a compile-time assertion, eg. `#assert [SLOT_A] != [SLOT_B]`.
+    pub fn parse_assertion(&mut self) -> Result<AssertionDefinition, ParserError> {
+        self.match_kind(TokenKind::Assert)?;
+
+        let lhs = self.parse_assertion_operand()?;
+        let op = ComparisonOperator::try_from(&self.current_token.kind).map_err(|_| {
+            tracing::error!(target: "parser", "INVALID ASSERTION COMPARISON: {}", self.current_token.kind);
+            ParserError {
+                kind: ParserErrorKind::UnexpectedType(self.current_token.kind.clone()),
+                hint: Some(
+                    "Expected a comparison: `==`, `!=`, `<`, `<=`, `>` or `>=`".to_string(),
+                ),
+                spans: AstSpan(vec![self.current_token.span.clone()]),
+            }
+        })?;
+        self.consume();
+        let rhs = self.parse_assertion_operand()?;
+
+        // Clone spans and set to nothing
+        let new_spans = self.spans.clone();
+        self.spans = vec![];
+
+        Ok(AssertionDefinition { lhs, op, rhs, span: AstSpan(new_spans) })
+    }
+
+    /// Parses an operand of a compile-time assertion: a constant expression or a
+    /// `__codesize(MACRO)` call.
+    pub fn parse_assertion_operand(&mut self) -> Result<AssertionOperand, ParserError> {
+        match self.current_token.kind.clone() {
+            TokenKind::Ident(builtin) if builtin == "__codesize" => {
+                self.consume();
+                self.match_kind(TokenKind::OpenParen)?;
+                let name = match self.match_kind(TokenKind::Ident("x".to_string()))? {
+                    TokenKind::Ident(macro_name) => macro_name,
+                    _ => unreachable!(),
+                };
+                self.match_kind(TokenKind::CloseParen)?;
+                Ok(AssertionOperand::Codesize(name))
+            }
+            _ => Ok(AssertionOperand::Expression(self.parse_constant_expression()?)),
+        }
+    }
+
+    /// Parse a constant expression.
+    ///
+    /// Expressions are literals, constant references (`[CONST]`) or operations applied to
+    /// nested expressions, eg. `add(0x20, mul([OTHER_CONST], 0x02))`.
+    pub fn parse_constant_expression(&mut self) -> Result<ConstExpression, ParserError> {
+        match self.current_token.kind.clone() {
+            TokenKind::Literal(l, _) => {
+                self.consume();
+                Ok(ConstExpression::Literal(l))
+            }
+            // Numbers outside of macro bodies and constants, eg. in `#assert` directives
+            TokenKind::Num(n) => {
+                self.consume();
+                Ok(ConstExpression::Literal(str_to_bytes32(&format!("{n:x}"))))
+            }
+            TokenKind::OpenBracket => {
+                self.consume();
+                let name = match self.current_token.kind.clone() {
+                    TokenKind::Ident(name) => name,
+                    kind => {
+                        tracing::error!(target: "parser", "TOKEN MISMATCH - EXPECTED IDENT, GOT: {}", kind);
+                        return Err(ParserError {
+                            kind: ParserErrorKind::InvalidConstant(kind),
+                            hint: Some("Expected a constant name inside brackets.".to_string()),
+                            spans: AstSpan(vec![self.current_token.span.clone()]),
+                        })
+                    }
+                };
+                self.consume();
+                self.match_kind(TokenKind::CloseBracket)?;
+                Ok(ConstExpression::Constant(name))
+            }
+            TokenKind::Ident(op_name) => {
+                let op = ConstOperator::try_from(&op_name).map_err(|_| {
+                    tracing::error!(target: "parser", "INVALID CONSTANT EXPRESSION OPERATOR: {}", op_name);
+                    ParserError {
+                        kind: ParserErrorKind::InvalidConstantValue(TokenKind::Ident(
+                            op_name.clone(),
+                        )),
+                        hint: Some(
+                            "Expected one of: add, sub, mul, div, mod, exp, shl, shr, and, or, xor, not"
+                                .to_string(),
+                        ),
+                        spans: AstSpan(vec![self.current_token.span.clone()]),
+                    }
+                })?;
+                let op_span = self.current_token.span.clone();
+                self.consume();
+                self.match_kind(TokenKind::OpenParen)?;
+                let mut args = vec![self.parse_constant_expression()?];
+                while self.check(TokenKind::Comma) {
+                    self.consume();
+                    args.push(self.parse_constant_expression()?);
+                }
+                self.match_kind(TokenKind::CloseParen)?;
+
+                if args.len() != op.arity() {
+                    tracing::error!(target: "parser", "INVALID ARGUMENT COUNT FOR \"{}\": {}", op, args.len());
+                    return Err(ParserError {
+                        kind: ParserErrorKind::InvalidConstantValue(TokenKind::Ident(op_name)),
+                        hint: Some(format!(
+                            "\"{op}\" expects {} argument(s), got {}",
+                            op.arity(),
+                            args.len()
+                        )),
+                        spans: AstSpan(vec![op_span]),
+                    })
+                }
+                Ok(ConstExpression::Operation(op, args))
+            }
+            kind => {
+                tracing::error!(target: "parser", "TOKEN MISMATCH - EXPECTED CONSTANT EXPRESSION, GOT: {}", kind);
+                Err(ParserError {
+                    kind: ParserErrorKind::InvalidConstantValue(kind),
+                    hint: Some(
+                        "Expected a literal, a constant reference or an operation".to_string(),
+                    ),
+                    spans: AstSpan(vec![self.current_token.span.clone()]),
+                })
+            }
+        }
+    }
+
     /// Parse a custom error definition.
     pub fn parse_custom_error(&mut self) -> Result<ErrorDefinition, ParserError> {
         // Error Identifier
@@ -414,6 +800,15 @@ impl Parser {
 
         while !self.check(TokenKind::CloseBracket) {
             if let TokenKind::Ident(s) = self.match_kind(TokenKind::Ident(String::default()))? {
+                // The unique_labels flag takes no arguments
+                if let Ok(DecoratorFlag::UniqueLabels) = DecoratorFlag::try_from(&s) {
+                    flags.push(DecoratorFlag::UniqueLabels);
+                    if self.check(TokenKind::Comma) {
+                        self.consume();
+                    }
+                    continue
+                }
+
                 // Consume the open parenthesis
                 self.consume();
 
@@ -434,12 +829,16 @@ impl Parser {
                             })
                         }
                     }
-                    // The value flag accepts a single literal as an argument
-                    Ok(DecoratorFlag::Value(_)) => {
-                        if let TokenKind::Literal(l) =
-                            self.match_kind(TokenKind::Literal(Literal::default()))?
+                    // The value, fuzz and seed flags accept a single literal as an argument
+                    Ok(flag) => {
+                        if let TokenKind::Literal(l, _) =
+                            self.match_kind(TokenKind::Literal(Literal::default(), 0))?
                         {
-                            flags.push(DecoratorFlag::Value(l));
+                            flags.push(match flag {
+                                DecoratorFlag::Fuzz(_) => DecoratorFlag::Fuzz(l),
+                                DecoratorFlag::Seed(_) => DecoratorFlag::Seed(l),
+                                _ => DecoratorFlag::Value(l),
+                            });
                         } else {
                             return Err(ParserError {
                                 kind: ParserErrorKind::InvalidDecoratorFlagArg(
@@ -543,7 +942,7 @@ impl Parser {
         tracing::info!(target: "parser", "PARSING MACRO BODY");
         while !self.check(TokenKind::CloseBrace) {
             match self.current_token.kind.clone() {
-                TokenKind::Literal(val) => {
+                TokenKind::Literal(val, _) => {
                     let curr_spans = vec![self.current_token.span.clone()];
                     tracing::info!(target: "parser", "PARSING MACRO BODY: [LITERAL: {}]", hex::encode(val));
                     self.consume();
@@ -564,7 +963,7 @@ impl Parser {
                     // literal
                     if o.is_value_push() {
                         match self.current_token.kind.clone() {
-                            TokenKind::Literal(val) => {
+                            TokenKind::Literal(val, _) => {
                                 let curr_spans = vec![self.current_token.span.clone()];
                                 tracing::info!(target: "parser", "PARSING MACRO BODY: [LITERAL: {}]", hex::encode(val));
                                 self.consume();
@@ -665,7 +1064,7 @@ impl Parser {
                 TokenKind::BuiltinFunction(f) => {
                     let mut curr_spans = vec![self.current_token.span.clone()];
                     self.match_kind(TokenKind::BuiltinFunction(String::default()))?;
-                    let args = self.parse_args(true, false, false, true)?;
+                    let args = self.parse_builtin_args(&f)?;
                     args.iter().for_each(|a| curr_spans.extend_from_slice(&a.span.0));
                     tracing::info!(target: "parser", "PARSING MACRO BODY: [BUILTIN FN: {}({:?})]", f, args);
                     statements.push(Statement {
@@ -677,6 +1076,14 @@ impl Parser {
                         span: AstSpan(curr_spans),
                     });
                 }
+                TokenKind::Repeat => {
+                    let repeat = self.parse_repeat()?;
+                    tracing::info!(target: "parser", "PARSING MACRO BODY: [REPEAT: {} STATEMENTS]", repeat.statements.len());
+                    statements.push(Statement {
+                        span: repeat.span.clone(),
+                        ty: StatementType::Repeat(repeat),
+                    });
+                }
                 kind => {
                     tracing::error!(target: "parser", "TOKEN MISMATCH - MACRO BODY: {}", kind);
                     return Err(ParserError {
@@ -710,7 +1117,7 @@ impl Parser {
             !self.check(TokenKind::CloseBrace)
         {
             match self.current_token.kind.clone() {
-                TokenKind::Literal(val) => {
+                TokenKind::Literal(val, _) => {
                     let curr_spans = vec![self.current_token.span.clone()];
                     tracing::info!(target: "parser", "PARSING LABEL BODY: [LITERAL: {}]", hex::encode(val));
                     self.consume();
@@ -778,7 +1185,7 @@ impl Parser {
                 TokenKind::BuiltinFunction(f) => {
                     let mut curr_spans = vec![self.current_token.span.clone()];
                     self.match_kind(TokenKind::BuiltinFunction(String::default()))?;
-                    let args = self.parse_args(true, false, false, true)?;
+                    let args = self.parse_builtin_args(&f)?;
                     args.iter().for_each(|a| curr_spans.extend_from_slice(&a.span.0));
                     tracing::info!(target: "parser", "PARSING LABEL BODY: [BUILTIN FN: {}({:?})]", f, args);
                     statements.push(Statement {
@@ -790,6 +1197,14 @@ impl Parser {
                         span: AstSpan(curr_spans),
                     });
                 }
+                TokenKind::Repeat => {
+                    let repeat = self.parse_repeat()?;
+                    tracing::info!(target: "parser", "PARSING LABEL BODY: [REPEAT: {} STATEMENTS]", repeat.statements.len());
+                    statements.push(Statement {
+                        span: repeat.span.clone(),
+                        ty: StatementType::Repeat(repeat),
+                    });
+                }
                 kind => {
                     tracing::error!(target: "parser", "TOKEN MISMATCH - LABEL BODY: {}", kind);
                     return Err(ParserError {
@@ -803,6 +1218,31 @@ impl Parser {
         Ok(statements)
     }
 
+    /// Parse a compile-time loop.
+    ///
+    /// It should parse the following : __REPEAT(count[, index]) {...}
+    ///
+    /// The count is a constant expression, eg. a literal or a constant reference.
+    pub fn parse_repeat(&mut self) -> Result<RepeatBlock, ParserError> {
+        let mut curr_spans = vec![self.current_token.span.clone()];
+        self.match_kind(TokenKind::Repeat)?;
+        self.match_kind(TokenKind::OpenParen)?;
+        let count = self.parse_constant_expression()?;
+        let index = if self.check(TokenKind::Comma) {
+            self.consume();
+            match self.match_kind(TokenKind::Ident("INDEX".to_string()))? {
+                TokenKind::Ident(index) => Some(index),
+                _ => unreachable!(),
+            }
+        } else {
+            None
+        };
+        self.match_kind(TokenKind::CloseParen)?;
+        let statements = self.parse_body()?;
+        statements.iter().for_each(|s| curr_spans.extend_from_slice(&s.span.0));
+        Ok(RepeatBlock { count, index, statements, span: AstSpan(curr_spans) })
+    }
+
     /// Parse new lines.
     ///
     /// No-return since newlines are non-essential.
@@ -852,10 +1292,30 @@ impl Parser {
                 }
 
                 // Check for literals
-                if let TokenKind::Literal(l) = &self.current_token.kind {
+                if let TokenKind::Literal(l, width) = &self.current_token.kind {
+                    let hex = Self::builtin_literal_hex(l, *width);
                     args.push(Argument {
                         // Place literal in the "name" field
-                        name: Some(bytes32_to_string(l, false)),
+                        name: Some(hex),
+                        arg_location: None,
+                        arg_type: None,
+                        indexed: false,
+                        span: AstSpan(vec![self.current_token.span.clone()]),
+                    });
+                    self.consume();
+
+                    // multiple args possible
+                    if self.check(TokenKind::Comma) {
+                        self.consume();
+                        on_type = true;
+                    }
+                    continue
+                }
+
+                // Check for decimal numbers, which have no significant leading zeros
+                if let TokenKind::Num(n) = self.current_token.kind {
+                    args.push(Argument {
+                        name: Some(format_even_bytes(format!("{n:x}"))),
                         arg_location: None,
                         arg_type: None,
                         indexed: false,
@@ -972,6 +1432,123 @@ impl Parser {
         Ok(args)
     }
 
+    /// Parses the arguments of a builtin function call
+    pub fn parse_builtin_args(&mut self, builtin: &str) -> Result<Vec<Argument>, ParserError> {
+        match BuiltinFunctionKind::from(builtin.to_string()) {
+            BuiltinFunctionKind::Keccak => self.parse_keccak_args(),
+            BuiltinFunctionKind::Bytes => self.parse_bytes_args(),
+            _ => self.parse_args(true, false, false, true),
+        }
+    }
+
+    /// Parses the argument of a `__keccak` call: a string, a literal or a constant reference
+    ///
+    /// Strings and literals are hashed as raw bytes, so their hex encoded bytes are placed in the
+    /// "name" field with a `bytes` type. Constants are hashed as a full 32 byte word, so they are
+    /// referenced by name with a `bytes32` type.
+    pub fn parse_keccak_args(&mut self) -> Result<Vec<Argument>, ParserError> {
+        self.match_kind(TokenKind::OpenParen)?;
+        let span = self.current_token.span.clone();
+        let arg = match self.current_token.kind.clone() {
+            TokenKind::Str(s) => {
+                self.consume();
+                Argument {
+                    name: Some(hex::encode(s.as_bytes())),
+                    arg_type: Some("bytes".to_string()),
+                    span: AstSpan(vec![span]),
+                    ..Default::default()
+                }
+            }
+            TokenKind::Literal(l, width) => {
+                let hex = Self::builtin_literal_hex(&l, width);
+                self.consume();
+                Argument {
+                    name: Some(hex),
+                    arg_type: Some("bytes".to_string()),
+                    span: AstSpan(vec![span]),
+                    ..Default::default()
+                }
+            }
+            TokenKind::Num(n) => {
+                self.consume();
+                Argument {
+                    name: Some(format_even_bytes(format!("{n:x}"))),
+                    arg_type: Some("bytes".to_string()),
+                    span: AstSpan(vec![span]),
+                    ..Default::default()
+                }
+            }
+            TokenKind::OpenBracket => {
+                let (constant, const_span) = self.parse_constant_push()?;
+                Argument {
+                    name: Some(constant),
+                    arg_type: Some("bytes32".to_string()),
+                    span: AstSpan(vec![const_span]),
+                    ..Default::default()
+                }
+            }
+            kind => {
+                tracing::error!(target: "parser", "INVALID __keccak ARGUMENT: {:?}", kind);
+                return Err(ParserError {
+                    kind: ParserErrorKind::InvalidArgs(kind),
+                    hint: Some(
+                        "Expected a string, a literal or a constant reference, eg. `__keccak(\"eip1967.proxy.implementation\")`"
+                            .to_string(),
+                    ),
+                    spans: AstSpan(vec![span]),
+                })
+            }
+        };
+        self.match_kind(TokenKind::CloseParen)?;
+        Ok(vec![arg])
+    }
+
+    /// Parses the argument of a `__BYTES` call: a string of at most 32 bytes
+    ///
+    /// The hex encoded bytes of the string are placed in the "name" field with a `bytes` type.
+    pub fn parse_bytes_args(&mut self) -> Result<Vec<Argument>, ParserError> {
+        self.match_kind(TokenKind::OpenParen)?;
+        let span = self.current_token.span.clone();
+        let s = match self.current_token.kind.clone() {
+            TokenKind::Str(s) if s.len() <= 32 => s,
+            TokenKind::Str(s) => {
+                tracing::error!(target: "parser", "STRING PASSED TO __BYTES EXCEEDS 32 BYTES: \"{}\"", s);
+                return Err(ParserError {
+                    kind: ParserErrorKind::InvalidArgs(TokenKind::Str(s.clone())),
+                    hint: Some(format!(
+                        "`__BYTES` pushes at most 32 bytes, \"{s}\" is {} bytes long",
+                        s.len()
+                    )),
+                    spans: AstSpan(vec![span]),
+                })
+            }
+            kind => {
+                tracing::error!(target: "parser", "INVALID __BYTES ARGUMENT: {:?}", kind);
+                return Err(ParserError {
+                    kind: ParserErrorKind::InvalidArgs(kind),
+                    hint: Some("Expected a string, eg. `__BYTES(\"ERC20\")`".to_string()),
+                    spans: AstSpan(vec![span]),
+                })
+            }
+        };
+        self.consume();
+        self.match_kind(TokenKind::CloseParen)?;
+        Ok(vec![Argument {
+            name: Some(hex::encode(s.as_bytes())),
+            arg_type: Some("bytes".to_string()),
+            span: AstSpan(vec![span]),
+            ..Default::default()
+        }])
+    }
+
+    /// Returns the hex of a literal passed to a builtin function
+    ///
+    /// Keeps any leading zero bytes written in the source, they are significant for builtins that
+    /// pad, inject or hash the raw value.
+    fn builtin_literal_hex(l: &Literal, width: usize) -> String {
+        pad_n_bytes(&bytes32_to_string(l, false), width)
+    }
+
     /// Parses the following : (x)
     pub fn parse_single_arg(&mut self) -> Result<usize, ParserError> {
         self.match_kind(TokenKind::OpenParen)?;
@@ -1002,12 +1579,30 @@ impl Parser {
         while !self.check(TokenKind::CloseParen) {
             // We can pass either directly hex values or labels (without the ":")
             match self.current_token.kind.clone() {
-                TokenKind::Literal(lit) => {
+                TokenKind::Literal(lit, _) => {
                     args.push(MacroArg::Literal(lit));
                     self.consume();
                 }
                 TokenKind::Ident(ident) => {
-                    args.push(MacroArg::Ident(ident));
+                    let span = self.current_token.span.clone();
+                    self.consume();
+                    if self.check(TokenKind::OpenParen) {
+                        // Passed into the Macro Call like:
+                        // OUTER(INNER(0x01))
+                        let inner_args = self.parse_macro_call_args()?;
+                        args.push(MacroArg::MacroCall(MacroInvocation {
+                            macro_name: ident,
+                            args: inner_args,
+                            span: AstSpan(vec![span]),
+                        }));
+                    } else if let Ok(o) = Opcode::from_str(&ident) {
+                        args.push(MacroArg::Opcode(o));
+                    } else {
+                        args.push(MacroArg::Ident(ident));
+                    }
+                }
+                TokenKind::Opcode(o) => {
+                    args.push(MacroArg::Opcode(o));
                     self.consume();
                 }
                 TokenKind::Calldata => {
@@ -1034,7 +1629,7 @@ impl Parser {
                     return Err(ParserError {
                         kind: ParserErrorKind::InvalidMacroArgs(arg),
                         hint: Some(
-                            "Expected literal, identifier (string), or an argument call"
+                            "Expected literal, identifier (string), argument call or macro invocation"
                                 .to_string(),
                         ),
                         spans: AstSpan(new_spans),
@@ -1101,7 +1696,8 @@ impl Parser {
 
     /// Parse the body of a table.
     ///
-    /// Only `LabelCall` and `Code` Statements should be authorized.
+    /// Only `LabelCall` and `Code` Statements should be authorized. Code tables accept raw hex,
+    /// with or without a `0x` prefix, and string literals which are embedded as bytes.
     pub fn parse_table_body(&mut self, is_code_table: bool) -> Result<Vec<Statement>, ParserError> {
         let mut statements: Vec<Statement> = Vec::new();
         let code_statement_regex = Regex::new(r"^([a-fA-F\d]+)$").unwrap();
@@ -1135,6 +1731,15 @@ impl Parser {
                     });
                     self.consume();
                 }
+                TokenKind::Str(string) if is_code_table => {
+                    // Strings are embedded as their raw bytes
+                    let code = string.bytes().map(|b| format!("{b:02x}")).collect::<String>();
+                    statements.push(Statement {
+                        ty: StatementType::Code(code),
+                        span: AstSpan(new_spans),
+                    });
+                    self.consume();
+                }
                 kind => {
                     tracing::error!("Invalid Table Body Token: {:?}", kind);
                     return Err(ParserError {
@@ -13,7 +13,7 @@ fn build_abi_from_ast() {
         .map(|x| x.unwrap())
         .filter(|x| !matches!(x.kind, TokenKind::Whitespace))
         .collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     let contract = parser.parse().unwrap();
     let abi = Abi::from(contract);
 
@@ -23,3 +23,23 @@ fn build_abi_from_ast() {
     );
     assert_eq!(abi.functions.get("test").unwrap().inputs[1].kind, FunctionParamType::String);
 }
+
+#[test]
+fn build_abi_with_custom_errors() {
+    let source = "#define error PanicError(uint256)\n#define error NotOwner()";
+
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer
+        .into_iter()
+        .map(|x| x.unwrap())
+        .filter(|x| !matches!(x.kind, TokenKind::Whitespace))
+        .collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+    let contract = parser.parse().unwrap();
+    let abi = Abi::from(contract);
+
+    assert_eq!(abi.errors.len(), 2);
+    assert_eq!(abi.errors.get("PanicError").unwrap().inputs[0].kind, FunctionParamType::Uint(256));
+    assert!(abi.errors.get("NotOwner").unwrap().inputs.is_empty());
+}
@@ -0,0 +1,59 @@
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+#[test]
+fn test_parses_assertions() {
+    let source = r#"
+        #define constant SLOT_A = FREE_STORAGE_POINTER()
+        #assert [SLOT_A] != add([SLOT_B], 0x01)
+        #assert __codesize(MAIN) < 0x6000
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+    let contract = parser.parse().unwrap();
+    assert_eq!(parser.current_token.kind, TokenKind::Eof);
+
+    let assertions = contract
+        .assertions
+        .iter()
+        .map(|a| (a.lhs.clone(), a.op, a.rhs.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        assertions,
+        vec![
+            (
+                AssertionOperand::Expression(ConstExpression::Constant("SLOT_A".to_string())),
+                ComparisonOperator::Ne,
+                AssertionOperand::Expression(ConstExpression::Operation(
+                    ConstOperator::Add,
+                    vec![
+                        ConstExpression::Constant("SLOT_B".to_string()),
+                        ConstExpression::Literal(str_to_bytes32("01"))
+                    ]
+                )),
+            ),
+            (
+                AssertionOperand::Codesize("MAIN".to_string()),
+                ComparisonOperator::Lt,
+                AssertionOperand::Expression(ConstExpression::Literal(str_to_bytes32("6000"))),
+            ),
+        ]
+    );
+    assert_eq!(contract.assertions[1].span.0.first().unwrap().start, 114);
+}
+
+#[test]
+fn test_assertion_without_comparison() {
+    let source = "#assert [SLOT_A] [SLOT_B]";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+
+    let err = parser.parse().unwrap_err();
+    assert_eq!(err.kind, ParserErrorKind::UnexpectedType(TokenKind::OpenBracket));
+    assert_eq!(err.spans, AstSpan(vec![Span { start: 17, end: 17, file: None }]));
+}
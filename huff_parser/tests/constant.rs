@@ -8,7 +8,7 @@ fn test_parses_free_storage_pointer_constant() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     let contract = parser.parse().unwrap();
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
 
@@ -35,7 +35,7 @@ fn test_parses_literal_constant() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     let contract = parser.parse().unwrap();
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
 
@@ -60,3 +60,106 @@ fn test_parses_literal_constant() {
         }
     );
 }
+
+#[test]
+fn test_parses_constant_expression() {
+    let source = "#define constant OFFSET = add(0x20, mul([OTHER_CONST], 0x02))";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+    let contract = parser.parse().unwrap();
+    assert_eq!(parser.current_token.kind, TokenKind::Eof);
+
+    let constant = contract.constants.lock().unwrap()[0].clone();
+    assert_eq!(constant.name, "OFFSET");
+    assert_eq!(
+        constant.value,
+        ConstVal::Expression(ConstExpression::Operation(
+            ConstOperator::Add,
+            vec![
+                ConstExpression::Literal(str_to_bytes32("20")),
+                ConstExpression::Operation(
+                    ConstOperator::Mul,
+                    vec![
+                        ConstExpression::Constant("OTHER_CONST".to_string()),
+                        ConstExpression::Literal(str_to_bytes32("02")),
+                    ]
+                ),
+            ]
+        ))
+    );
+}
+
+#[test]
+fn test_constant_expression_invalid_operator() {
+    let source = "#define constant OFFSET = foo(0x20, 0x01)";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+    let err = parser.parse().unwrap_err();
+    assert_eq!(
+        err.kind,
+        ParserErrorKind::InvalidConstantValue(TokenKind::Ident("foo".to_string()))
+    );
+}
+
+#[test]
+fn test_constant_expression_invalid_arg_count() {
+    let source = "#define constant OFFSET = not(0x20, 0x01)";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+    let err = parser.parse().unwrap_err();
+    assert_eq!(
+        err.kind,
+        ParserErrorKind::InvalidConstantValue(TokenKind::Ident("not".to_string()))
+    );
+}
+
+#[test]
+fn test_parses_env_constant() {
+    let source = r#"#define constant DEPLOYER = env("DEPLOYER_ADDR")"#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+    let contract = parser.parse().unwrap();
+    assert_eq!(parser.current_token.kind, TokenKind::Eof);
+
+    let constant = contract.constants.lock().unwrap()[0].clone();
+    assert_eq!(constant.name, "DEPLOYER");
+    assert_eq!(constant.value, ConstVal::Env("DEPLOYER_ADDR".to_string()));
+}
+
+#[test]
+fn test_resolves_env_constants() {
+    let source = r#"#define constant DEPLOYER = env("DEPLOYER_ADDR")"#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+    let contract = parser.parse().unwrap();
+
+    // Unset variables
+    let err = contract.resolve_env_constants(|_| None).unwrap_err();
+    assert_eq!(err.kind, CodegenErrorKind::MissingEnvironmentVariable("DEPLOYER_ADDR".to_string()));
+
+    // Values that aren't hex literals
+    let err = contract.resolve_env_constants(|_| Some("deployer".to_string())).unwrap_err();
+    assert_eq!(
+        err.kind,
+        CodegenErrorKind::InvalidEnvironmentVariable(
+            "DEPLOYER_ADDR".to_string(),
+            "deployer".to_string()
+        )
+    );
+
+    contract
+        .resolve_env_constants(|var| (var == "DEPLOYER_ADDR").then(|| "0xa57b".to_string()))
+        .unwrap();
+    let constant = contract.constants.lock().unwrap()[0].clone();
+    assert_eq!(constant.value, ConstVal::Literal(str_to_bytes32("a57b")));
+}
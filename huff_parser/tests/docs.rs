@@ -0,0 +1,120 @@
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::{docs::*, prelude::*, symbols::SymbolKind};
+
+const SOURCE: &str = r#"/// Transfers tokens to a recipient
+///
+/// Reverts if the balance is too low.
+#define function transfer(address to, uint256 amount) nonpayable returns (bool)
+
+/** Emitted on transfers */
+#define event Transfer(address indexed, address indexed, uint256)
+
+// Not a doc comment
+#define error InsufficientBalance(uint256)
+
+/// The fee, in basis points
+#define constant FEE = 0x1e
+
+/**
+ * Stores the caller as the owner
+ *
+ * @dev Doesn't check the current owner
+ */
+#define macro SET_OWNER() = takes(0) returns(0) {
+    caller 0x00 sstore
+}
+
+#[calldata("0x")]
+#define test TEST_SET_OWNER() = takes(0) returns(0) {
+    SET_OWNER()
+}
+
+#define macro MAIN() = takes(0) returns(0) {
+    SET_OWNER()
+    __REPEAT(0x02) { [FEE] pop }
+    done jump
+    done:
+        stop
+}
+"#;
+
+fn docs() -> ContractDocs {
+    let tokens = Lexer::new(SOURCE).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let contract = Parser::new(tokens, None).unwrap().parse().unwrap();
+    ContractDocs::new("Token", &contract)
+}
+
+fn item<'a>(docs: &'a ContractDocs, name: &str) -> &'a DocItem {
+    docs.items.iter().find(|i| i.name == name).unwrap()
+}
+
+#[test]
+fn test_doc_comments() {
+    let docs = docs();
+    assert_eq!(
+        item(&docs, "transfer").doc.as_deref(),
+        Some("Transfers tokens to a recipient\n\nReverts if the balance is too low.")
+    );
+    assert_eq!(item(&docs, "Transfer").doc.as_deref(), Some("Emitted on transfers"));
+    assert_eq!(item(&docs, "InsufficientBalance").doc, None);
+    assert_eq!(item(&docs, "FEE").doc.as_deref(), Some("The fee, in basis points"));
+    assert_eq!(
+        item(&docs, "SET_OWNER").doc.as_deref(),
+        Some("Stores the caller as the owner\n\n@dev Doesn't check the current owner")
+    );
+    assert_eq!(item(&docs, "MAIN").doc, None);
+
+    // Tests aren't documented
+    assert!(docs.items.iter().all(|i| i.name != "TEST_SET_OWNER"));
+}
+
+#[test]
+fn test_details() {
+    let docs = docs();
+    let transfer = item(&docs, "transfer");
+    assert_eq!(transfer.kind, SymbolKind::Function);
+    assert_eq!(
+        transfer.signature,
+        "#define function transfer(address to, uint256 amount) nonpayable returns (bool)"
+    );
+    assert_eq!(
+        transfer.details,
+        vec![
+            ("Selector", "0xa9059cbb".to_string()),
+            ("Signature", "transfer(address,uint256)".to_string())
+        ]
+    );
+    assert_eq!(
+        item(&docs, "Transfer").details[0].1,
+        "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+    );
+    assert_eq!(item(&docs, "InsufficientBalance").details[0].1, "0x92665351");
+    assert_eq!(item(&docs, "FEE").details, vec![("Value", "0x1e".to_string())]);
+
+    // `caller 0x00 sstore` costs 2 + 3 + 100 gas
+    let set_owner = item(&docs, "SET_OWNER");
+    assert_eq!(set_owner.signature, "#define macro SET_OWNER() = takes(0) returns(0)");
+    assert_eq!(set_owner.details[2], ("Static gas", "105".to_string()));
+    // With 2 iterations of `[FEE] pop`, `done jump` and `done: stop`
+    assert_eq!(item(&docs, "MAIN").details[2], ("Static gas", (105 + 2 * 5 + 11 + 1).to_string()));
+}
+
+#[test]
+fn test_rendering() {
+    let docs = docs();
+    let markdown = docs.to_markdown();
+    assert!(markdown.starts_with("# Token\n\n## Functions\n\n### transfer\n\n```huff\n"));
+    assert!(markdown.contains(
+        "### FEE\n\n```huff\n#define constant FEE\n```\n\nThe fee, in basis points\n\n| | |\n| - | - |\n| Value | `0x1e` |\n"
+    ));
+    let sections = ["## Functions", "## Events", "## Errors", "## Constants", "## Macros"];
+    let positions = sections.iter().map(|s| markdown.find(s).unwrap()).collect::<Vec<_>>();
+    assert!(positions.windows(2).all(|w| w[0] < w[1]));
+
+    let html = docs.to_html();
+    assert!(html.contains("<title>Token</title>"));
+    assert!(html.contains("<h3 id=\"SET_OWNER\">SET_OWNER</h3>"));
+    assert!(html.contains("<p>@dev Doesn't check the current owner</p>"));
+    assert!(html.contains("<tr><th>Static gas</th><td><code>105</code></td></tr>"));
+}
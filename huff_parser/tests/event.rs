@@ -66,7 +66,7 @@ fn test_prefix_event_arg_names_with_reserved_keywords() {
         Token { kind: TokenKind::Eof, span: Span { start: 99, end: 99, file: None } },
     ];
     assert_eq!(expected_tokens, tokens);
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     parser.parse().unwrap();
 }
 
@@ -253,7 +253,7 @@ fn test_parse_event() {
             .map(|x| x.unwrap())
             .filter(|x| !matches!(x.kind, TokenKind::Whitespace))
             .collect::<Vec<Token>>();
-        let mut parser = Parser::new(tokens, None);
+        let mut parser = Parser::new(tokens, None).unwrap();
         let _ = parser.match_kind(TokenKind::Define);
         let event = parser.parse_event().unwrap();
 
@@ -203,7 +203,7 @@ fn parses_valid_function_definition() {
             .map(|x| x.unwrap())
             .filter(|x| !matches!(x.kind, TokenKind::Whitespace))
             .collect::<Vec<Token>>();
-        let mut parser = Parser::new(tokens, None);
+        let mut parser = Parser::new(tokens, None).unwrap();
         let _ = parser.match_kind(TokenKind::Define);
         let function = parser.parse_function().unwrap();
 
@@ -219,7 +219,7 @@ fn cannot_parse_invalid_function_definition() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     parser.parse().unwrap();
 }
 
@@ -231,7 +231,7 @@ fn test_functions_with_keyword_arg_names_errors() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     parser.parse().unwrap();
 }
 
@@ -241,7 +241,7 @@ fn test_functions_with_argument_locations() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     parser.parse().unwrap();
 }
 
@@ -284,6 +284,6 @@ fn test_can_prefix_function_arg_names_with_reserved_keywords() {
         Token { kind: TokenKind::Eof, span: Span { start: 73, end: 73, file: None } },
     ];
     assert_eq!(expected_tokens, tokens);
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     parser.parse().unwrap();
 }
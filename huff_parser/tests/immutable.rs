@@ -0,0 +1,27 @@
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+#[test]
+fn test_parses_immutable() {
+    let source = "#define immutable OWNER\n#define constant FEE = 0x01";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+    let contract = parser.parse().unwrap();
+    assert_eq!(parser.current_token.kind, TokenKind::Eof);
+
+    assert_eq!(
+        contract.immutables,
+        vec![ImmutableDefinition {
+            name: "OWNER".to_string(),
+            span: AstSpan(vec![
+                Span { start: 0, end: 6, file: None },
+                Span { start: 8, end: 16, file: None },
+                Span { start: 18, end: 22, file: None },
+            ])
+        }]
+    );
+    assert_eq!(contract.constants.lock().unwrap().len(), 1);
+}
@@ -8,7 +8,7 @@ fn parses_import() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     let contract = parser.parse().unwrap();
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
 
@@ -27,10 +27,61 @@ fn parses_deep_imports() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     let contract = parser.parse().unwrap();
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
 
     let import_path = contract.imports[0].clone();
     assert_eq!(import_path.to_str().unwrap(), "../huff-examples/erc20/contracts/ERC20.huff");
 }
+
+#[test]
+fn parses_selective_import() {
+    let source = r#"#include "./utils.huff" { ADD_ONE, ONE }"#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+    let contract = parser.parse().unwrap();
+    assert_eq!(parser.current_token.kind, TokenKind::Eof);
+
+    assert_eq!(contract.imports[0].to_str().unwrap(), "./utils.huff");
+    let import = contract.selective_imports[0].clone();
+    assert_eq!(import.path.to_str().unwrap(), "./utils.huff");
+    assert_eq!(import.names, vec!["ADD_ONE".to_string(), "ONE".to_string()]);
+}
+
+#[test]
+fn fails_on_invalid_import_selection() {
+    let source = r#"#include "./utils.huff" { ADD_ONE, 0x01 }"#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+
+    let err = parser.parse().unwrap_err();
+    assert!(matches!(err.kind, ParserErrorKind::InvalidName(TokenKind::Literal(..))));
+}
+
+#[test]
+fn parses_namespaced_import() {
+    let source = r#"
+        #include "./math.huff" as Math
+        #define macro MAIN() = takes (0) returns (0) { Math.SAFE_ADD() }
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+    let contract = parser.parse().unwrap();
+    assert_eq!(parser.current_token.kind, TokenKind::Eof);
+
+    assert_eq!(contract.imports[0].to_str().unwrap(), "./math.huff");
+    let import = contract.namespaced_imports[0].clone();
+    assert_eq!(import.path.to_str().unwrap(), "./math.huff");
+    assert_eq!(import.namespace, "Math");
+    match &contract.macros[0].statements[0].ty {
+        StatementType::MacroInvocation(mi) => assert_eq!(mi.macro_name, "Math.SAFE_ADD"),
+        ty => panic!("Expected a macro invocation, got: {ty:?}"),
+    }
+}
@@ -0,0 +1,133 @@
+use huff_lexer::*;
+use huff_parser::{incremental::*, *};
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"#include "./utils.huff"
+#define constant ONE = 0x01
+#define function transfer(address,uint256) nonpayable returns (bool)
+
+/* Adds one */
+#define macro ADD_ONE() = takes(1) returns(1) {
+    [ONE] add
+}
+
+#[calldata("0x")]
+#define test TEST_ADD() = takes(0) returns(0) {
+    0x01 ADD_ONE() 0x02 eq done jumpi
+    0x00 0x00 revert
+    done:
+}
+
+#define macro MAIN() = takes(0) returns(0) {
+    0x00 calldataload ADD_ONE()
+}
+"#;
+
+/// Asserts the incrementally parsed document matches a document parsed from scratch
+fn assert_reparsed(document: &Document) {
+    let expected = Document::new(document.source(), None);
+    assert_eq!(format!("{document:?}"), format!("{expected:?}"));
+}
+
+fn edit(document: &mut Document, find: &str, text: &str) -> usize {
+    let start = document.source().find(find).unwrap();
+    let start = document.source()[..start].chars().count();
+    let range = start..start + find.chars().count();
+    let count = document.apply_edit(&TextEdit { range, text: text.to_string() });
+    assert_reparsed(document);
+    count
+}
+
+#[test]
+fn test_document_matches_parser() {
+    let document = Document::new(SOURCE, None);
+    assert_eq!(document.item_count(), 6);
+
+    let tokens = Lexer::new(SOURCE).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let contract = Parser::new(tokens, None).unwrap().parse().unwrap();
+    let incremental = document.contract();
+    assert_eq!(incremental.macros, contract.macros);
+    assert_eq!(incremental.functions, contract.functions);
+    assert_eq!(incremental.imports, contract.imports);
+    assert_eq!(*incremental.constants.lock().unwrap(), *contract.constants.lock().unwrap());
+    assert!(incremental.invalid_definitions.is_empty());
+}
+
+#[test]
+fn test_edits_reparse_touched_items() {
+    let mut document = Document::new(SOURCE, None);
+
+    // Edits within a definition only re-parse it and the one before, in case the edit breaks the
+    // `#define` it starts with, shifting the spans of later definitions
+    assert_eq!(edit(&mut document, "[ONE] add", "[ONE] add 0x00 pop"), 2);
+    assert_eq!(edit(&mut document, "0x02 eq", "0x03 eq"), 2);
+    assert_eq!(edit(&mut document, "#include", "#include"), 1);
+
+    // Invalid code becomes an error node
+    assert_eq!(edit(&mut document, "0x00 0x00 revert", "0x00 0x00 revert )"), 2);
+    assert_eq!(document.contract().invalid_definitions[0].name, Some("TEST_ADD".to_string()));
+    assert_eq!(edit(&mut document, "revert )", "revert"), 2);
+    assert!(document.contract().invalid_definitions.is_empty());
+
+    // New definitions split the item
+    assert_eq!(
+        edit(
+            &mut document,
+            "\n#define macro MAIN",
+            "\n#define constant TWO = 0x02\n#define macro MAIN"
+        ),
+        4
+    );
+    assert_eq!(document.item_count(), 7);
+
+    // Deleting a definition merges the items around it
+    assert_eq!(edit(&mut document, "#define constant TWO = 0x02\n", ""), 3);
+    assert_eq!(document.item_count(), 6);
+
+    // Breaking the `#define` of a definition merges it into the one before
+    assert_eq!(edit(&mut document, "#define macro MAIN", "#defin macro MAIN"), 2);
+    assert_eq!(document.item_count(), 5);
+
+    // Non-ASCII characters are counted as single characters, like spans
+    edit(&mut document, "/* Adds one */", "/* Adds ① */");
+}
+
+#[test]
+fn test_open_comments_and_strings_reparse_rest() {
+    let mut document = Document::new(SOURCE, None);
+    edit(&mut document, "/* Adds one */", "/* Adds one");
+    assert_eq!(document.item_count(), 3);
+    assert!(document.contract().macros.is_empty());
+
+    edit(&mut document, "/* Adds one", "/* Adds one */");
+    assert_eq!(document.item_count(), 6);
+
+    edit(&mut document, "(\"0x\")", "(\"0x)");
+    edit(&mut document, "(\"0x)", "(\"0x\")");
+    assert_eq!(document.item_count(), 6);
+}
+
+#[test]
+fn test_typing_source() {
+    // Type the source one character at a time, then delete it backwards
+    let mut document = Document::new("", None);
+    for (i, c) in SOURCE.chars().enumerate() {
+        document.apply_edit(&TextEdit { range: i..i, text: c.to_string() });
+        assert_reparsed(&document);
+    }
+    assert_eq!(document.source(), SOURCE);
+
+    let len = SOURCE.chars().count();
+    for i in (0..len).rev().step_by(7) {
+        document.apply_edit(&TextEdit { range: i.saturating_sub(7)..i + 1, text: String::new() });
+        assert_reparsed(&document);
+    }
+}
+
+#[test]
+fn test_edit_range_is_clamped() {
+    let mut document = Document::new("#define constant ONE = 0x01", None);
+    document.apply_edit(&TextEdit { range: 100..200, text: "\n".to_string() });
+    assert_eq!(document.source(), "#define constant ONE = 0x01\n");
+    assert_reparsed(&document);
+}
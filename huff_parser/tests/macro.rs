@@ -8,7 +8,7 @@ fn empty_macro() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Grab the first macro
     let macro_definition = parser.parse().unwrap().macros[0].clone();
@@ -50,7 +50,7 @@ fn empty_macro_without_takes_returns() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Grab the first macro
     let macro_definition = parser.parse().unwrap().macros[0].clone();
@@ -84,7 +84,7 @@ fn empty_macro_only_takes() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Grab the first macro
     let macro_definition = parser.parse().unwrap().macros[0].clone();
@@ -122,7 +122,7 @@ fn empty_macro_only_returns() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Grab the first macro
     let macro_definition = parser.parse().unwrap().macros[0].clone();
@@ -161,7 +161,7 @@ fn macro_with_simple_body() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Grab the first macro
     let macro_definition = parser.parse().unwrap().macros[0].clone();
@@ -251,7 +251,7 @@ fn macro_with_arg_calls() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Grab the first macro
     let macro_definition = parser.parse().unwrap().macros[0].clone();
@@ -423,7 +423,7 @@ fn macro_labels() {
     // let lexer = Lexer::new(flattened_source.source);
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Grab the first macro
     let macro_definition = parser.parse().unwrap().macros[0].clone();
@@ -598,7 +598,7 @@ fn macro_invocation_with_arg_call() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Grab the first macro
     let macro_definition = parser.parse().unwrap().macros[0].clone();
@@ -740,7 +740,7 @@ fn test_macro_opcode_arguments() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Grab the first macro
     let macro_definition = parser.parse().unwrap().macros[0].clone();
@@ -751,7 +751,7 @@ fn test_macro_opcode_arguments() {
         statements: vec![Statement {
             ty: StatementType::MacroInvocation(MacroInvocation {
                 macro_name: "RETURN1".to_string(),
-                args: vec![MacroArg::Ident("returndatasize".to_string())],
+                args: vec![MacroArg::Opcode(Opcode::Returndatasize)],
                 span: AstSpan(vec![
                     Span { start: 58, end: 64, file: None },
                     Span { start: 65, end: 65, file: None },
@@ -811,7 +811,7 @@ fn macro_with_builtin_fn_call() {
     let lexer = Lexer::new(flattened_source.source);
 
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Grab the first macro
     let macro_definition = parser.parse().unwrap().macros[0].clone();
@@ -879,7 +879,7 @@ fn empty_outlined_macro() {
     let lexer = Lexer::new(flattened_source.source);
 
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Grab the first macro
     let macro_definition = parser.parse().unwrap().macros[0].clone();
@@ -921,7 +921,7 @@ fn outlined_macro_with_simple_body() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Grab the first macro
     let macro_definition = parser.parse().unwrap().macros[0].clone();
@@ -989,7 +989,7 @@ fn empty_test() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Grab the first macro
     let macro_definition = parser.parse().unwrap().macros[0].clone();
@@ -1032,7 +1032,7 @@ fn test_with_simple_body() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Grab the first macro
     let macro_definition = parser.parse().unwrap().macros[0].clone();
@@ -1120,7 +1120,7 @@ fn empty_test_with_decorator() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Grab the first macro
     let macro_definition = parser.parse().unwrap().macros[0].clone();
@@ -1177,7 +1177,7 @@ fn empty_test_with_multi_flag_decorator() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Grab the first macro
     let macro_definition = parser.parse().unwrap().macros[0].clone();
@@ -1232,3 +1232,131 @@ fn empty_test_with_multi_flag_decorator() {
     assert_eq!(macro_definition, expected);
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
 }
+
+#[test]
+fn test_with_fuzz_decorator() {
+    let source = r#"
+    #[fuzz(0x20), seed(0x2a)]
+    #define test MY_TEST() = takes(1) returns(0) {}
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+
+    let macro_definition = parser.parse().unwrap().macros[0].clone();
+    assert_eq!(
+        macro_definition.decorator,
+        Some(Decorator {
+            flags: vec![
+                DecoratorFlag::Fuzz(str_to_bytes32("20")),
+                DecoratorFlag::Seed(str_to_bytes32("2a")),
+            ]
+        })
+    );
+    assert_eq!(macro_definition.takes, 1);
+    assert_eq!(parser.current_token.kind, TokenKind::Eof);
+}
+
+#[test]
+fn contract_collects_test_macros() {
+    let source = r#"
+    #define macro HELPER() = takes(0) returns(0) {}
+
+    #[calldata("0x02")]
+    #define test FIRST_TEST() = takes(0) returns(0) {}
+
+    #define macro MAIN() = takes(0) returns(0) {}
+
+    #define test SECOND_TEST() = takes(0) returns(0) {}
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+    let contract = parser.parse().unwrap();
+
+    let tests = contract.tests();
+    assert_eq!(
+        tests.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(),
+        vec!["FIRST_TEST", "SECOND_TEST"]
+    );
+    assert_eq!(
+        tests[0].decorator,
+        Some(Decorator { flags: vec![DecoratorFlag::Calldata(String::from("0x02"))] })
+    );
+    assert_eq!(tests[1].decorator, None);
+}
+
+#[test]
+fn test_unique_labels_decorator() {
+    let source = r#"
+    #[unique_labels]
+    #define macro SKIP() = takes(0) returns(0) {
+        skip jump
+        skip:
+    }
+
+    #[unique_labels, calldata("0x02")]
+    #define test SKIP_TEST() = takes(0) returns(0) {
+        SKIP()
+    }
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+    let contract = parser.parse().unwrap();
+
+    assert_eq!(
+        contract.macros[0].decorator,
+        Some(Decorator { flags: vec![DecoratorFlag::UniqueLabels] })
+    );
+    assert_eq!(
+        contract.macros[1].decorator,
+        Some(Decorator {
+            flags: vec![DecoratorFlag::UniqueLabels, DecoratorFlag::Calldata(String::from("0x02"))]
+        })
+    );
+}
+
+#[test]
+fn test_macro_call_with_nested_macro_invocation() {
+    let source = r#"
+    #define macro MAIN() = takes(0) returns(0) {
+        OUTER(INNER(0x01, add), 0x02)
+    }
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+    let contract = parser.parse().unwrap();
+    assert_eq!(parser.current_token.kind, TokenKind::Eof);
+
+    let inner_start = source.find("INNER").unwrap();
+    match &contract.macros[0].statements[0].ty {
+        StatementType::MacroInvocation(mi) => {
+            assert_eq!(mi.macro_name, "OUTER");
+            assert_eq!(
+                mi.args,
+                vec![
+                    MacroArg::MacroCall(MacroInvocation {
+                        macro_name: "INNER".to_string(),
+                        args: vec![
+                            MacroArg::Literal(str_to_bytes32("01")),
+                            MacroArg::Opcode(Opcode::Add)
+                        ],
+                        span: AstSpan(vec![Span {
+                            start: inner_start,
+                            end: inner_start + "INNER".len() - 1,
+                            file: None
+                        }]),
+                    }),
+                    MacroArg::Literal(str_to_bytes32("02")),
+                ]
+            );
+        }
+        ty => panic!("Expected a macro invocation, got: {ty}"),
+    }
+}
@@ -27,7 +27,7 @@ fn not_mistaken_as_opcode() {
 
         let actual_label_arg = tokens[tokens.len() - 7].kind.clone();
         let actual_label = tokens[tokens.len() - 5].kind.clone();
-        let mut parser = Parser::new(tokens, None);
+        let mut parser = Parser::new(tokens, None).unwrap();
         // parsing to ensure tokens syntax is valid
         let _contract = parser.parse().unwrap();
         assert_eq!(actual_label_arg, TokenKind::Ident(label.clone()));
@@ -51,7 +51,7 @@ fn test_invalid_push_non_literal() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
 
     // Should fail here
     parser.parse().unwrap();
@@ -95,10 +95,13 @@ fn test_push_literals() {
         },
         Token { kind: TokenKind::Whitespace, span: Span { start: 51, end: 51, file: None } },
         Token {
-            kind: TokenKind::Literal([
-                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-                0, 0, 0, 16,
-            ]),
+            kind: TokenKind::Literal(
+                [
+                    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                    0, 0, 0, 0, 0, 16,
+                ],
+                1,
+            ),
             span: Span { start: 54, end: 55, file: None },
         },
         Token { kind: TokenKind::Whitespace, span: Span { start: 56, end: 68, file: None } },
@@ -108,10 +111,13 @@ fn test_push_literals() {
         },
         Token { kind: TokenKind::Whitespace, span: Span { start: 75, end: 75, file: None } },
         Token {
-            kind: TokenKind::Literal([
-                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-                0, 0, 1, 8,
-            ]),
+            kind: TokenKind::Literal(
+                [
+                    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                    0, 0, 0, 0, 1, 8,
+                ],
+                2,
+            ),
             span: Span { start: 78, end: 80, file: None },
         },
         Token { kind: TokenKind::Whitespace, span: Span { start: 81, end: 93, file: None } },
@@ -121,18 +127,24 @@ fn test_push_literals() {
         },
         Token { kind: TokenKind::Whitespace, span: Span { start: 99, end: 99, file: None } },
         Token {
-            kind: TokenKind::Literal([
-                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-                0, 0, 0, 16,
-            ]),
+            kind: TokenKind::Literal(
+                [
+                    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                    0, 0, 0, 0, 0, 16,
+                ],
+                1,
+            ),
             span: Span { start: 102, end: 103, file: None },
         },
         Token { kind: TokenKind::Whitespace, span: Span { start: 104, end: 104, file: None } },
         Token {
-            kind: TokenKind::Literal([
-                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-                0, 0, 0, 16,
-            ]),
+            kind: TokenKind::Literal(
+                [
+                    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                    0, 0, 0, 0, 0, 16,
+                ],
+                1,
+            ),
             span: Span { start: 107, end: 108, file: None },
         },
         Token { kind: TokenKind::Whitespace, span: Span { start: 109, end: 117, file: None } },
@@ -143,7 +155,7 @@ fn test_push_literals() {
     assert_eq!(expected_tokens, tokens);
 
     // This should parse correctly
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     parser.parse().unwrap();
 }
 
@@ -186,10 +198,13 @@ fn test_push0() {
         },
         Token { kind: TokenKind::Whitespace, span: Span { start: 51, end: 51, file: None } },
         Token {
-            kind: TokenKind::Literal([
-                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-                0, 0, 0, 16,
-            ]),
+            kind: TokenKind::Literal(
+                [
+                    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                    0, 0, 0, 0, 0, 16,
+                ],
+                1,
+            ),
             span: Span { start: 54, end: 55, file: None },
         },
         Token { kind: TokenKind::Whitespace, span: Span { start: 56, end: 68, file: None } },
@@ -199,10 +214,13 @@ fn test_push0() {
         },
         Token { kind: TokenKind::Whitespace, span: Span { start: 75, end: 75, file: None } },
         Token {
-            kind: TokenKind::Literal([
-                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-                0, 0, 1, 8,
-            ]),
+            kind: TokenKind::Literal(
+                [
+                    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                    0, 0, 0, 0, 1, 8,
+                ],
+                2,
+            ),
             span: Span { start: 78, end: 80, file: None },
         },
         Token { kind: TokenKind::Whitespace, span: Span { start: 81, end: 93, file: None } },
@@ -212,18 +230,24 @@ fn test_push0() {
         },
         Token { kind: TokenKind::Whitespace, span: Span { start: 99, end: 99, file: None } },
         Token {
-            kind: TokenKind::Literal([
-                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-                0, 0, 0, 16,
-            ]),
+            kind: TokenKind::Literal(
+                [
+                    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                    0, 0, 0, 0, 0, 16,
+                ],
+                1,
+            ),
             span: Span { start: 102, end: 103, file: None },
         },
         Token { kind: TokenKind::Whitespace, span: Span { start: 104, end: 104, file: None } },
         Token {
-            kind: TokenKind::Literal([
-                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-                0, 0, 0, 16,
-            ]),
+            kind: TokenKind::Literal(
+                [
+                    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                    0, 0, 0, 0, 0, 16,
+                ],
+                1,
+            ),
             span: Span { start: 107, end: 108, file: None },
         },
         Token { kind: TokenKind::Whitespace, span: Span { start: 109, end: 121, file: None } },
@@ -239,6 +263,6 @@ fn test_push0() {
     assert_eq!(expected_tokens, tokens);
 
     // This should parse correctly
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     parser.parse().unwrap();
 }
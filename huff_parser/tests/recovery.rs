@@ -0,0 +1,160 @@
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+fn parser(source: &str) -> Parser {
+    let tokens = Lexer::new(source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    Parser::new(tokens, None).unwrap()
+}
+
+#[test]
+fn test_recovers_at_next_definition() {
+    let source = r#"
+    #define macro A() = takes(0) returns(0) {
+        FREE_STORAGE_POINTER()
+    }
+    #define function f() internal returns ()
+    #define macro MAIN() = takes(0) returns(0) {
+        0x01
+    }
+    #define foo
+    "#;
+
+    let errors = parser(source).parse_recovering().unwrap_err();
+    assert_eq!(
+        errors.iter().map(|e| e.kind.clone()).collect::<Vec<_>>(),
+        vec![
+            ParserErrorKind::InvalidTokenInMacroBody(TokenKind::FreeStoragePointer),
+            ParserErrorKind::UnexpectedType(TokenKind::Ident("internal".to_string())),
+            ParserErrorKind::InvalidDefinition(TokenKind::Ident("foo".to_string())),
+        ]
+    );
+
+    // Without recovery, parsing stops at the first error
+    let error = parser(source).parse().unwrap_err();
+    assert_eq!(error, errors[0]);
+}
+
+#[test]
+fn test_recovers_from_unexpected_top_level_tokens() {
+    let source = r#"
+    0x01 add
+    #define constant ONE = 0x01
+    #define macro MAIN() = takes(0) returns(0) {
+        [ONE]
+    }
+    "#;
+
+    let errors = parser(source).parse_recovering().unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].kind,
+        ParserErrorKind::UnexpectedType(TokenKind::Literal(str_to_bytes32("01"), 1))
+    );
+}
+
+#[test]
+fn test_recovering_valid_source() {
+    let source = r#"
+    #define constant ONE = 0x01
+    #define macro MAIN() = takes(0) returns(0) {
+        [ONE]
+    }
+    "#;
+
+    let contract = parser(source).parse_recovering().unwrap();
+    assert_eq!(contract.macros.len(), 1);
+    assert_eq!(contract.constants.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn test_empty_token_stream() {
+    let error = Parser::new(vec![], None).unwrap_err();
+    assert_eq!(error.kind, ParserErrorKind::EmptyTokenStream);
+
+    let mut parser = parser("");
+    parser.tokens.clear();
+    assert_eq!(parser.reset().unwrap_err().kind, ParserErrorKind::EmptyTokenStream);
+    assert_eq!(parser.parse().unwrap_err().kind, ParserErrorKind::EmptyTokenStream);
+}
+
+#[test]
+fn test_truncated_token_streams_dont_panic() {
+    let source = r#"
+    #include "./utils.huff"
+    #define constant ONE = 0x01
+    #define function transfer(address,uint256) nonpayable returns (bool)
+    #define event Transfer(address indexed, uint256)
+    #define table JUMPS { done }
+    #define jumptable__packed PACKED { done }
+    #[calldata("0x")]
+    #define test TEST() = takes(0) returns(0) {}
+    #define macro ADD(a) = takes(1) returns(1) {
+        <a> add
+    }
+    #define macro MAIN() = takes(0) returns(0) {
+        [ONE] ADD(0x02) __tablesize(JUMPS)
+        done:
+            0x00 mstore
+    }
+    "#;
+    let tokens = Lexer::new(source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+
+    // Cut the stream before its EOF token at every position
+    for end in 1..tokens.len() {
+        let _ = Parser::new(tokens[..end].to_vec(), None).unwrap().parse_recovering();
+    }
+}
+
+#[test]
+fn test_parse_tolerant() {
+    let source = r#"
+    #define constant ONE = 0x01
+    #define macro TRANSFER(amount) = takes(0) returns(0) {
+        <amount> FREE_STORAGE_POINTER()
+    }
+    #[calldata("0x")]
+    #define test CHECK() = takes(0) returns(0) {
+        0x01 )
+    }
+    #define macro MAIN() = takes(0) returns(0) {
+        [ONE]
+    "#;
+
+    let contract = parser(source).parse_tolerant();
+    assert_eq!(contract.constants.lock().unwrap().len(), 1);
+    assert!(contract.macros.is_empty());
+
+    let invalid = contract
+        .invalid_definitions
+        .iter()
+        .map(|d| (d.kind.clone(), d.name.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        invalid,
+        vec![
+            (Some(TokenKind::Macro), Some("TRANSFER".to_string())),
+            (Some(TokenKind::Test), Some("CHECK".to_string())),
+            (Some(TokenKind::Macro), Some("MAIN".to_string())),
+        ]
+    );
+    assert_eq!(
+        contract.invalid_definitions[0].error.kind,
+        ParserErrorKind::InvalidTokenInMacroBody(TokenKind::FreeStoragePointer)
+    );
+    // The error node spans the skipped tokens, from the decorator to the next definition
+    let span = &contract.invalid_definitions[1].span;
+    assert_eq!(span.0.first().unwrap().start, source.find("#[calldata").unwrap());
+    assert_eq!(span.0.last().unwrap().end, source.rfind('}').unwrap());
+
+    // A valid source has no error nodes
+    let contract = parser("#define constant ONE = 0x01").parse_tolerant();
+    assert!(contract.invalid_definitions.is_empty());
+
+    // An empty token stream keeps its error without a span
+    let mut parser = parser("");
+    parser.tokens.clear();
+    let contract = parser.parse_tolerant();
+    assert_eq!(contract.invalid_definitions.len(), 1);
+    assert_eq!(contract.invalid_definitions[0].error.kind, ParserErrorKind::EmptyTokenStream);
+}
@@ -0,0 +1,77 @@
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+#[test]
+fn test_parses_repeat() {
+    let source = r#"
+        #define macro MAIN() = takes(0) returns (0) {
+            __REPEAT([COUNT], i) {
+                <i> mstore
+            }
+            __REPEAT(0x02) { pop }
+        }
+
+        #define macro OTHER() = takes(0) returns (0) {
+            0x01
+        }
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+    let contract = parser.parse().unwrap();
+    assert_eq!(parser.current_token.kind, TokenKind::Eof);
+
+    // The closing brace of a repeat body doesn't end the macro body
+    assert_eq!(contract.macros.len(), 2);
+    let repeats = contract.macros[0]
+        .statements
+        .iter()
+        .map(|s| match &s.ty {
+            StatementType::Repeat(r) => (
+                r.count.clone(),
+                r.index.clone(),
+                r.statements.iter().map(|s| s.ty.clone()).collect::<Vec<_>>(),
+            ),
+            ty => panic!("Expected a repeat, got {ty}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        repeats,
+        vec![
+            (
+                ConstExpression::Constant("COUNT".to_string()),
+                Some("i".to_string()),
+                vec![
+                    StatementType::ArgCall("i".to_string()),
+                    StatementType::Opcode(Opcode::Mstore)
+                ],
+            ),
+            (
+                ConstExpression::Literal(str_to_bytes32("02")),
+                None,
+                vec![StatementType::Opcode(Opcode::Pop)],
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_repeat_without_body() {
+    let source = r#"
+        #define macro MAIN() = takes(0) returns (0) {
+            __REPEAT(0x02, i) 0x01
+        }
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+
+    let err = parser.parse().unwrap_err();
+    assert_eq!(
+        err.kind,
+        ParserErrorKind::UnexpectedType(TokenKind::Literal(str_to_bytes32("01"), 1))
+    );
+}
@@ -10,7 +10,7 @@ fn derives_storage_pointers() {
     let flattened_source = FullFileSource { source, file: None, spans: vec![] };
     let lexer = Lexer::new(flattened_source.source);
     let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
-    let mut parser = Parser::new(tokens, None);
+    let mut parser = Parser::new(tokens, None).unwrap();
     let mut contract = parser.parse().unwrap();
     assert_eq!(parser.current_token.kind, TokenKind::Eof);
 
@@ -80,3 +80,79 @@ fn derives_storage_pointers() {
         ConstVal::Literal(str_to_bytes32("a57B"))
     );
 }
+
+#[test]
+fn free_storage_pointers_skip_reserved_slots() {
+    let source = r#"
+        #define constant BALANCES_SLOT = STORAGE_SLOT(0x01)
+        #define constant FSP_LOCATION = FREE_STORAGE_POINTER()
+        #define constant FSP_LOCATION_2 = FREE_STORAGE_POINTER()
+        #define constant UNUSED_SLOT = STORAGE_SLOT(0x02)
+
+        #define macro MAIN() = takes(0) returns(0) {
+            [FSP_LOCATION] [BALANCES_SLOT] [FSP_LOCATION_2]
+        }
+    "#;
+
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+    let mut contract = parser.parse().unwrap();
+    assert_eq!(parser.current_token.kind, TokenKind::Eof);
+    assert_eq!(
+        contract.constants.lock().unwrap()[0].value,
+        ConstVal::StorageSlot(str_to_bytes32("01"))
+    );
+
+    contract.derive_storage_pointers();
+
+    // Slots 1 and 2 are reserved, even though `UNUSED_SLOT` is never referenced
+    let constants = contract.constants.lock().unwrap();
+    assert_eq!(constants[0].value, ConstVal::StorageSlot(str_to_bytes32("01")));
+    assert_eq!(constants[1].value, ConstVal::StorageSlot(str_to_bytes32("00")));
+    assert_eq!(constants[2].value, ConstVal::StorageSlot(str_to_bytes32("03")));
+    assert_eq!(constants[3].value, ConstVal::StorageSlot(str_to_bytes32("02")));
+    drop(constants);
+
+    assert_eq!(
+        contract.storage_layout().unwrap(),
+        vec![
+            ("FSP_LOCATION".to_string(), str_to_bytes32("00")),
+            ("BALANCES_SLOT".to_string(), str_to_bytes32("01")),
+            ("UNUSED_SLOT".to_string(), str_to_bytes32("02")),
+            ("FSP_LOCATION_2".to_string(), str_to_bytes32("03")),
+        ]
+    );
+}
+
+#[test]
+fn storage_layout_errors_on_collisions() {
+    let source = r#"
+        #define constant BALANCES_SLOT = STORAGE_SLOT(0x01)
+        #define constant FSP_LOCATION = FREE_STORAGE_POINTER()
+        #define constant ALLOWANCES_SLOT = STORAGE_SLOT(0x01)
+
+        #define macro MAIN() = takes(0) returns(0) {
+            [FSP_LOCATION] [BALANCES_SLOT] [ALLOWANCES_SLOT]
+        }
+    "#;
+
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+    let mut contract = parser.parse().unwrap();
+    contract.derive_storage_pointers();
+
+    let err = contract.storage_layout().unwrap_err();
+    assert_eq!(
+        err.kind,
+        CodegenErrorKind::StorageSlotCollision(
+            "BALANCES_SLOT".to_string(),
+            "ALLOWANCES_SLOT".to_string()
+        )
+    );
+    let constants = contract.constants.lock().unwrap();
+    assert_eq!(err.span.0, [constants[0].span.0.clone(), constants[2].span.0.clone()].concat());
+}
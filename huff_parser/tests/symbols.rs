@@ -0,0 +1,114 @@
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::{prelude::*, symbols::*};
+
+const SOURCE: &str = r#"#define function transfer(address,uint256) nonpayable returns ()
+#define constant OWNER = FREE_STORAGE_POINTER()
+#define immutable DEPLOYER
+#define jumptable TABLE {
+    done
+}
+
+#define macro CHECK(err) = takes(0) returns(0) {
+    [OWNER] sload caller eq done jumpi
+    <err> 0x00 mstore 0x20 0x00 revert
+}
+
+#[calldata("0x")]
+#define test TEST_CHECK() = takes(0) returns(0) {
+    CHECK(0x01)
+}
+
+#define macro MAIN() = takes(0) returns(0) {
+    __FUNC_SIG(transfer) [DEPLOYER] pop
+    CHECK(0x02)
+    __tablesize(TABLE) [MISSING] pop
+    done:
+}
+"#;
+
+fn symbols() -> SymbolTable {
+    let tokens = Lexer::new(SOURCE).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let contract = Parser::new(tokens, None).unwrap().parse().unwrap();
+    SymbolTable::new(&contract)
+}
+
+/// The text of a span
+fn text(span: &Span) -> String {
+    SOURCE.chars().skip(span.start).take(span.end + 1 - span.start).collect()
+}
+
+/// The character offset of the nth occurrence of a string
+fn offset(find: &str, nth: usize) -> usize {
+    let (byte, _) = SOURCE.match_indices(find).nth(nth).unwrap();
+    SOURCE[..byte].chars().count()
+}
+
+#[test]
+fn test_definitions_span_their_names() {
+    let symbols = symbols();
+    let definitions = symbols
+        .definitions()
+        .iter()
+        .map(|d| (d.kind, text(&d.span), d.scope.as_deref()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        definitions,
+        vec![
+            (SymbolKind::Constant, "OWNER".to_string(), None),
+            (SymbolKind::Immutable, "DEPLOYER".to_string(), None),
+            (SymbolKind::Function, "transfer".to_string(), None),
+            (SymbolKind::Table, "TABLE".to_string(), None),
+            (SymbolKind::Macro, "CHECK".to_string(), None),
+            (SymbolKind::Argument, "err".to_string(), Some("CHECK")),
+            (SymbolKind::Macro, "TEST_CHECK".to_string(), None),
+            (SymbolKind::Macro, "MAIN".to_string(), None),
+            (SymbolKind::Label, "done".to_string(), Some("MAIN")),
+        ]
+    );
+}
+
+#[test]
+fn test_references_resolve_to_definitions() {
+    let symbols = symbols();
+    for reference in symbols.references() {
+        assert_eq!(text(&reference.span), reference.name);
+    }
+
+    let resolved = |name: &str| {
+        let reference = symbols.references().iter().find(|r| r.name == name).unwrap();
+        symbols.definition(reference).map(|d| (d.kind, d.name.as_str()))
+    };
+    assert_eq!(resolved("OWNER"), Some((SymbolKind::Constant, "OWNER")));
+    assert_eq!(resolved("DEPLOYER"), Some((SymbolKind::Immutable, "DEPLOYER")));
+    assert_eq!(resolved("err"), Some((SymbolKind::Argument, "err")));
+    assert_eq!(resolved("transfer"), Some((SymbolKind::Function, "transfer")));
+    assert_eq!(resolved("TABLE"), Some((SymbolKind::Table, "TABLE")));
+    assert_eq!(resolved("MISSING"), None);
+
+    // Labels resolve across macros, eg. from a jump table or a macro invoked by the label's macro
+    let done = symbols.definitions().iter().find(|d| d.name == "done").unwrap();
+    assert_eq!(symbols.references_to(done).count(), 2);
+
+    let check = symbols.definitions().iter().find(|d| d.name == "CHECK").unwrap();
+    let invocations = symbols.references_to(check).map(|r| r.scope.as_deref()).collect::<Vec<_>>();
+    assert_eq!(invocations, vec![Some("TEST_CHECK"), Some("MAIN")]);
+}
+
+#[test]
+fn test_symbol_at_offset() {
+    let symbols = symbols();
+    let at = |find: &str, nth: usize| symbols.symbol_at(None, offset(find, nth)).map(|d| &d.name);
+
+    // On a reference, or the definition itself
+    assert_eq!(at("OWNER]", 0).map(String::as_str), Some("OWNER"));
+    assert_eq!(at("OWNER =", 0).map(String::as_str), Some("OWNER"));
+    assert_eq!(at("CHECK(0x02)", 0).map(String::as_str), Some("CHECK"));
+    assert_eq!(at("TEST_CHECK", 0).map(String::as_str), Some("TEST_CHECK"));
+    assert_eq!(at("done jumpi", 0).map(String::as_str), Some("done"));
+    assert_eq!(at("sload", 0), None);
+    assert_eq!(at("MISSING", 0), None);
+
+    // Spans without a file don't match a path
+    assert_eq!(symbols.symbol_at(Some("./Main.huff"), offset("OWNER]", 0)), None);
+}
@@ -12,7 +12,7 @@ fn table_with_no_body() {
         let lexer = Lexer::new(flattened_source.source);
         let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
 
-        let mut parser = Parser::new(tokens, None);
+        let mut parser = Parser::new(tokens, None).unwrap();
 
         let kind_offset = kind.to_string().len() + 8;
 
@@ -58,7 +58,7 @@ fn table_with_body() {
         let lexer = Lexer::new(flattened_source.source);
         let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
 
-        let mut parser = Parser::new(tokens, None);
+        let mut parser = Parser::new(tokens, None).unwrap();
 
         let kind_offset = kind.to_string().len() + 8;
 
@@ -113,3 +113,22 @@ fn table_with_body() {
         assert_eq!(parser.current_token.kind, TokenKind::Eof);
     }
 }
+
+#[test]
+fn code_table_with_raw_hex_body() {
+    let source = "#define table TEST_TABLE() = {\n0xdeadbeef 0x0001\n}";
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+
+    let mut parser = Parser::new(tokens, None).unwrap();
+
+    let table_definition = parser.parse().unwrap().tables[0].clone();
+    assert_eq!(table_definition.kind, TableKind::CodeTable);
+    assert_eq!(
+        table_definition.statements.iter().map(|s| s.ty.clone()).collect::<Vec<_>>(),
+        vec![StatementType::Code("deadbeef".to_string()), StatementType::Code("0001".to_string())]
+    );
+    assert_eq!(table_definition.size, str_to_bytes32("06"));
+}
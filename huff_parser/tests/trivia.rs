@@ -0,0 +1,84 @@
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::prelude::*;
+
+const SOURCE: &str = r#"// SPDX-License-Identifier: MIT
+#define constant ONE = 0x01 // one
+
+/// Adds one
+/* to the top of the stack */
+#define macro ADD_ONE() = takes(1) returns(1) {
+    // [x]
+    [ONE] add   // [x + 1]
+}
+// trailing
+"#;
+
+fn parse(source: &str) -> (Contract, Vec<Token>) {
+    let tokens = Lexer::new(source).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let mut parser = Parser::new(tokens, None).unwrap();
+    let contract = parser.parse().unwrap();
+    (contract, parser.tokens)
+}
+
+#[test]
+fn test_trivia_round_trips_source() {
+    let (contract, tokens) = parse(SOURCE);
+
+    // The tokens and trivia cover every character of the source exactly once, except the `0x`
+    // prefixes left out of the spans of literals
+    let mut spans = tokens
+        .iter()
+        .filter(|t| t.kind != TokenKind::Eof)
+        .map(|t| t.span.clone())
+        .chain(contract.trivia.0.values().flatten().map(|t| t.span.clone()))
+        .collect::<Vec<_>>();
+    spans.sort();
+    let chars = SOURCE.chars().collect::<Vec<_>>();
+    let mut rebuilt = String::new();
+    for span in spans {
+        let gap = chars[rebuilt.chars().count()..span.start].iter().collect::<String>();
+        assert!(gap.is_empty() || gap == "0x", "unexpected gap {gap:?}");
+        rebuilt.push_str(&gap);
+        rebuilt.extend(&chars[span.start..=span.end]);
+    }
+    assert_eq!(rebuilt, SOURCE);
+}
+
+#[test]
+fn test_trivia_of_nodes() {
+    let (contract, _) = parse(SOURCE);
+
+    // Doc comments lead their definition
+    let comments = |trivia: &[Trivia]| {
+        trivia
+            .iter()
+            .filter_map(|t| match &t.kind {
+                TriviaKind::Comment(c) => Some(c.clone()),
+                TriviaKind::Whitespace => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    let add_one = &contract.macros[0];
+    assert_eq!(
+        comments(contract.trivia.leading(&add_one.span)),
+        vec!["// one", "/// Adds one", "/* to the top of the stack */"]
+    );
+
+    // Comments within the macro body
+    let within = contract.trivia.within(&add_one.span).cloned().collect::<Vec<_>>();
+    assert_eq!(comments(&within), vec!["// [x]", "// [x + 1]"]);
+
+    assert_eq!(
+        contract.trivia.comments().map(|(c, _)| c).collect::<Vec<_>>(),
+        vec![
+            "// SPDX-License-Identifier: MIT",
+            "// one",
+            "/// Adds one",
+            "/* to the top of the stack */",
+            "// [x]",
+            "// [x + 1]",
+            "// trailing"
+        ]
+    );
+}
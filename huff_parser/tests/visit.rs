@@ -0,0 +1,155 @@
+use huff_lexer::*;
+use huff_parser::*;
+use huff_utils::{prelude::*, visit::*};
+
+const SOURCE: &str = r#"
+    #define constant ONE = 0x01
+    #define constant TWO = add([ONE], [ONE])
+    #define jumptable TABLE {
+        done
+    }
+
+    #define macro WRAP(inner) = takes(0) returns(0) {
+        <inner>
+    }
+
+    #define macro ADD() = takes(2) returns(1) {
+        add
+    }
+
+    #define macro MAIN() = takes(0) returns(0) {
+        [ONE] [TWO] WRAP(ADD())
+        __REPEAT(0x02) {
+            done jump
+            done:
+                __tablesize(TABLE) pop
+        }
+    }
+
+    #assert add([ONE], [TWO]) == 0x03
+"#;
+
+fn parse() -> Contract {
+    let tokens = Lexer::new(SOURCE).map(|x| x.unwrap()).collect::<Vec<Token>>();
+    Parser::new(tokens, None).unwrap().parse().unwrap()
+}
+
+/// Records the nodes visited
+#[derive(Default)]
+struct Recorder {
+    invocations: Vec<String>,
+    opcodes: Vec<Opcode>,
+    labels: Vec<String>,
+    builtins: usize,
+    constant_refs: usize,
+    spans: usize,
+}
+
+impl Visit for Recorder {
+    fn visit_macro_invocation(&mut self, invocation: &MacroInvocation) {
+        self.invocations.push(invocation.macro_name.clone());
+        walk_macro_invocation(self, invocation);
+    }
+
+    fn visit_statement(&mut self, statement: &Statement) {
+        if let StatementType::Opcode(o) = statement.ty {
+            self.opcodes.push(o);
+        }
+        walk_statement(self, statement);
+    }
+
+    fn visit_label(&mut self, label: &Label) {
+        self.labels.push(label.name.clone());
+        walk_label(self, label);
+    }
+
+    fn visit_builtin_function_call(&mut self, call: &BuiltinFunctionCall) {
+        self.builtins += 1;
+        walk_builtin_function_call(self, call);
+    }
+
+    fn visit_const_expression(&mut self, expression: &ConstExpression) {
+        if let ConstExpression::Constant(_) = expression {
+            self.constant_refs += 1;
+        }
+        walk_const_expression(self, expression);
+    }
+
+    fn visit_span(&mut self, _span: &AstSpan) {
+        self.spans += 1;
+    }
+}
+
+#[test]
+fn test_visit_walks_nested_nodes() {
+    let contract = parse();
+    let mut recorder = Recorder::default();
+    recorder.visit_contract(&contract);
+
+    // Nested invocations in arguments, statements in labels and repeat blocks, and constant
+    // references in constant expressions and assertions are all reached
+    assert_eq!(recorder.invocations, vec!["WRAP", "ADD"]);
+    assert_eq!(recorder.opcodes, vec![Opcode::Add, Opcode::Jump, Opcode::Pop]);
+    assert_eq!(recorder.labels, vec!["done"]);
+    assert_eq!(recorder.builtins, 1);
+    assert_eq!(recorder.constant_refs, 4);
+    assert!(recorder.spans > 0);
+}
+
+#[test]
+fn test_overridden_methods_can_skip_children() {
+    /// Only counts the statements of macros, skipping tables
+    #[derive(Default)]
+    struct Statements(usize);
+
+    impl Visit for Statements {
+        fn visit_table_definition(&mut self, _table: &TableDefinition) {}
+
+        fn visit_statement(&mut self, statement: &Statement) {
+            self.0 += 1;
+            walk_statement(self, statement);
+        }
+    }
+
+    let mut statements = Statements::default();
+    statements.visit_contract(&parse());
+    // `<inner>`, `add`, `[ONE] [TWO] WRAP(ADD()) __REPEAT`, `done jump done:` and the 2 statements
+    // of the label
+    assert_eq!(statements.0, 11);
+}
+
+#[test]
+fn test_visit_mut_rewrites_nodes() {
+    /// Renames a macro at its definition and every invocation
+    struct Rename(&'static str, &'static str);
+
+    impl VisitMut for Rename {
+        fn visit_macro_definition_mut(&mut self, macro_def: &mut MacroDefinition) {
+            if macro_def.name == self.0 {
+                macro_def.name = self.1.to_string();
+            }
+            walk_macro_definition_mut(self, macro_def);
+        }
+
+        fn visit_macro_invocation_mut(&mut self, invocation: &mut MacroInvocation) {
+            if invocation.macro_name == self.0 {
+                invocation.macro_name = self.1.to_string();
+            }
+            walk_macro_invocation_mut(self, invocation);
+        }
+
+        fn visit_span_mut(&mut self, span: &mut AstSpan) {
+            span.0.clear();
+        }
+    }
+
+    let mut contract = parse();
+    Rename("ADD", "SUM").visit_contract_mut(&mut contract);
+
+    assert!(contract.find_macro_by_name("SUM").is_some());
+    let mut recorder = Recorder::default();
+    recorder.visit_contract(&contract);
+    assert_eq!(recorder.invocations, vec!["WRAP", "SUM"]);
+    assert!(contract.macros.iter().all(|m| m.span.0.is_empty()));
+    assert!(contract.constants.lock().unwrap().iter().all(|c| c.span.0.is_empty()));
+}
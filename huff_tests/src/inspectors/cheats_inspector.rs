@@ -1,9 +1,10 @@
+use super::gas_inspector::GasInspector;
 use crate::cheats::{HuffCheatCode, HUFF_CHEATS_MAP};
 use bytes::Bytes;
 use ethers_core::{types::Address, utils::hex};
 use lazy_static::lazy_static;
 use revm::{
-    interpreter::{CallInputs, CreateInputs, Gas, InstructionResult},
+    interpreter::{CallInputs, CreateInputs, Gas, InstructionResult, Interpreter},
     primitives::B160,
     Database, EVMData, Inspector,
 };
@@ -17,12 +18,32 @@ lazy_static! {
 #[derive(Debug, Default)]
 pub struct CheatsInspector {
     pub logs: Vec<(u32, String)>,
+    pub gas: GasInspector,
 }
 
 impl<DB> Inspector<DB> for CheatsInspector
 where
     DB: Database,
 {
+    fn step(
+        &mut self,
+        interp: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        is_static: bool,
+    ) -> InstructionResult {
+        self.gas.step(interp, data, is_static)
+    }
+
+    fn step_end(
+        &mut self,
+        interp: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        is_static: bool,
+        eval: InstructionResult,
+    ) -> InstructionResult {
+        self.gas.step_end(interp, data, is_static, eval)
+    }
+
     fn log(
         &mut self,
         _: &mut EVMData<'_, DB>,
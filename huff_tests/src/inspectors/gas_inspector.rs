@@ -0,0 +1,50 @@
+use revm::{
+    interpreter::{InstructionResult, Interpreter},
+    Database, EVMData, Inspector,
+};
+use std::collections::BTreeMap;
+
+/// Records the gas spent at each program counter of the outermost call frame.
+#[derive(Debug, Default)]
+pub struct GasInspector {
+    /// Gas spent per program counter
+    pub pc_gas: BTreeMap<usize, u64>,
+    /// Depth of the outermost call frame
+    root_depth: Option<u64>,
+    /// Program counter and remaining gas of the step currently being executed
+    current_step: Option<(usize, u64)>,
+}
+
+impl<DB> Inspector<DB> for GasInspector
+where
+    DB: Database,
+{
+    fn step(
+        &mut self,
+        interp: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        _is_static: bool,
+    ) -> InstructionResult {
+        let depth = data.journaled_state.depth();
+        if *self.root_depth.get_or_insert(depth) == depth {
+            self.current_step = Some((interp.program_counter(), interp.gas.remaining()));
+        }
+        InstructionResult::Continue
+    }
+
+    fn step_end(
+        &mut self,
+        interp: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        _is_static: bool,
+        _eval: InstructionResult,
+    ) -> InstructionResult {
+        if self.root_depth == Some(data.journaled_state.depth()) {
+            if let Some((pc, remaining)) = self.current_step.take() {
+                *self.pc_gas.entry(pc).or_default() +=
+                    remaining.saturating_sub(interp.gas.remaining());
+            }
+        }
+        InstructionResult::Continue
+    }
+}
@@ -1,2 +1,5 @@
 /// The Cheatcodes inspector
 pub mod cheats_inspector;
+
+/// The Gas inspector
+pub mod gas_inspector;
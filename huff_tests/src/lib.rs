@@ -1,4 +1,5 @@
 use crate::{errors::RunnerError, runner::TestRunner, types::TestResult};
+use huff_codegen::CodegenSettings;
 use huff_utils::prelude::{Contract, MacroDefinition};
 use std::{borrow::Borrow, rc::Rc};
 
@@ -52,7 +53,7 @@ impl<'t> HuffTester<'t> {
             ast,
             macros: {
                 // Filter all macros within the AST for `test` macros only
-                let mut macros: TestMacros<'t> = ast.macros.iter().filter(|m| m.test).collect();
+                let mut macros: TestMacros<'t> = ast.tests();
                 // If the match flag is present, only retain the test macro
                 // that was queried
                 if let Some(match_) = match_.borrow() {
@@ -64,6 +65,12 @@ impl<'t> HuffTester<'t> {
         }
     }
 
+    /// Sets the settings the test macros are generated with
+    pub fn with_settings(mut self, settings: CodegenSettings) -> Self {
+        self.runner.settings = settings;
+        self
+    }
+
     /// Execute tests
     pub fn execute(mut self) -> Result<Vec<TestResult>, RunnerError> {
         // Check if any test macros exist
@@ -38,7 +38,10 @@ pub fn print_test_report(results: Vec<TestResult>, report_kind: ReportKind, star
                 table.add_row(Row::from(vec![
                     Cell::new(result.name).add_attribute(Attribute::Bold).fg(Color::Cyan),
                     Cell::new(result.return_data.unwrap_or_else(|| String::from("None"))),
-                    Cell::new(result.gas.to_string()),
+                    Cell::new(match &result.fuzz {
+                        Some(fuzz) => format!("{} (μ, {} runs)", result.gas, fuzz.runs),
+                        None => result.gas.to_string(),
+                    }),
                     Cell::from(result.status),
                 ]));
             }
@@ -57,6 +60,43 @@ pub fn print_test_report(results: Vec<TestResult>, report_kind: ReportKind, star
 
                 let num_logs = result.logs.len().saturating_sub(1);
 
+                if let Some(fuzz) = &result.fuzz {
+                    println!(
+                        "├─ {} {} runs, seed {:#x}",
+                        Paint::cyan("FUZZ"),
+                        fuzz.runs,
+                        fuzz.seed
+                    );
+                    if let Some(inputs) = &fuzz.counterexample {
+                        let has_more = !result.macro_gas.is_empty() ||
+                            result.return_data.is_some() ||
+                            num_logs > 0;
+                        println!("├─ {}", Paint::cyan("COUNTEREXAMPLE"));
+                        inputs.iter().enumerate().for_each(|(i, input)| {
+                            println!(
+                                "{} 0x{input}",
+                                if i == inputs.len() - 1 && !has_more {
+                                    "╰─"
+                                } else {
+                                    "├─"
+                                }
+                            );
+                        });
+                    }
+                }
+
+                if !result.macro_gas.is_empty() {
+                    let has_more = result.return_data.is_some() || num_logs > 0;
+                    let last = result.macro_gas.len() - 1;
+                    println!("├─ {}", Paint::cyan("GAS BY MACRO"));
+                    result.macro_gas.iter().enumerate().for_each(|(i, (name, gas))| {
+                        println!(
+                            "{} {name}: {gas}",
+                            if i == last && !has_more { "╰─" } else { "├─" }
+                        );
+                    });
+                }
+
                 if let Some(return_data) = result.return_data {
                     println!("├─ {}", Paint::cyan("RETURN DATA"));
                     println!("{} {return_data}", if num_logs == 0 { "╰─" } else { "├─" });
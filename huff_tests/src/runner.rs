@@ -1,14 +1,18 @@
-use crate::prelude::{cheats_inspector::CheatsInspector, RunnerError, TestResult, TestStatus};
+use crate::prelude::{
+    cheats_inspector::CheatsInspector, FuzzSummary, RunnerError, TestResult, TestStatus,
+};
 use bytes::Bytes;
 use ethers_core::{
     types::{Address, U256},
     utils::hex,
 };
-use huff_codegen::Codegen;
+use huff_codegen::{passes::PassContext, Codegen, CodegenSettings};
 use huff_utils::{
     ast::{DecoratorFlag, MacroDefinition},
-    prelude::{pad_n_bytes, CompilerError, Contract, EVMVersion},
+    evm::Opcode,
+    prelude::{pad_n_bytes, CompilerError, Contract, EVMVersion, MacroRange},
 };
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use revm::{
     db::DbAccount,
     primitives::{
@@ -16,6 +20,7 @@ use revm::{
     },
     Database, InMemoryDB, EVM,
 };
+use std::collections::BTreeMap;
 
 /// The test runner allows execution of test macros within an in-memory REVM
 /// instance.
@@ -23,6 +28,8 @@ use revm::{
 pub struct TestRunner {
     pub database: InMemoryDB,
     pub env: Env,
+    /// The settings test macros are generated with
+    pub settings: CodegenSettings,
 }
 
 impl TestRunner {
@@ -108,6 +115,19 @@ impl TestRunner {
         value: U256,
         data: String,
     ) -> Result<TestResult, RunnerError> {
+        self.profiled_call(name, caller, address, value, data).map(|(res, _)| res)
+    }
+
+    /// Perform a call to a deployed contract, additionally returning the gas spent at each
+    /// program counter of the called contract.
+    fn profiled_call(
+        &mut self,
+        name: String,
+        caller: Address,
+        address: Address,
+        value: U256,
+        data: String,
+    ) -> Result<(TestResult, BTreeMap<usize, u64>), RunnerError> {
         let mut evm = EVM::new();
         let mut inspector = CheatsInspector::default();
         self.set_balance(caller, U256::MAX);
@@ -161,7 +181,18 @@ impl TestRunner {
         // Return our test result
         // NOTE: We subtract 21000 gas from the gas result to account for the
         // base cost of the CALL.
-        Ok(TestResult { name, return_data, gas: gas_used - 21000, status, logs: inspector.logs })
+        Ok((
+            TestResult {
+                name,
+                return_data,
+                gas: gas_used - 21000,
+                status,
+                logs: inspector.logs,
+                macro_gas: BTreeMap::new(),
+                fuzz: None,
+            },
+            inspector.gas.pc_gas,
+        ))
     }
 
     /// Compile a test macro and run it in an in-memory REVM instance.
@@ -175,50 +206,141 @@ impl TestRunner {
 
         let name = m.name.to_owned();
 
+        // Set environment flags passed through the test decorator
+        let mut data = String::default();
+        let mut value = U256::zero();
+        let mut runs = None;
+        let mut seed = None;
+        if let Some(decorator) = &m.decorator {
+            for flag in &decorator.flags {
+                match flag {
+                    DecoratorFlag::Calldata(s) => {
+                        // Strip calldata of 0x prefix, if it is present.
+                        data = if let Some(s) = s.strip_prefix("0x") {
+                            s.to_owned()
+                        } else {
+                            s.to_owned()
+                        };
+                    }
+                    DecoratorFlag::Value(v) => value = U256::from(v),
+                    DecoratorFlag::Fuzz(r) => runs = Some(U256::from(r).low_u64() as usize),
+                    DecoratorFlag::Seed(s) => seed = Some(U256::from(s).low_u64()),
+                    DecoratorFlag::UniqueLabels => {}
+                }
+            }
+        }
+
+        // Only tests with a `fuzz` flag are fuzzed. Their inputs are pushed onto the stack ahead
+        // of their code, so they're compiled at the offset following the pushes (PUSH32 + 32 bytes)
+        let inputs_len = if runs.is_some() { m.takes * 33 } else { 0 };
+
         // Compile the passed test macro
-        match Codegen::macro_to_bytecode(
+        let mut res = Codegen::macro_to_bytecode(
             &evm_version,
             m,
             contract,
+            &self.settings,
             &mut vec![m],
-            0,
+            inputs_len,
             &mut Vec::default(),
             false,
             None,
-        ) {
-            // Generate table bytecode for compiled test macro
-            Ok(res) => match Codegen::gen_table_bytecode(res) {
-                Ok(bytecode) => {
-                    // Deploy compiled test macro
-                    let address = self.deploy_code(bytecode)?;
-
-                    // Set environment flags passed through the test decorator
-                    let mut data = String::default();
-                    let mut value = U256::zero();
-                    if let Some(decorator) = &m.decorator {
-                        for flag in &decorator.flags {
-                            match flag {
-                                DecoratorFlag::Calldata(s) => {
-                                    // Strip calldata of 0x prefix, if it is present.
-                                    data = if let Some(s) = s.strip_prefix("0x") {
-                                        s.to_owned()
-                                    } else {
-                                        s.to_owned()
-                                    };
-                                }
-                                DecoratorFlag::Value(v) => value = U256::from(v),
-                            }
-                        }
-                    }
+            None,
+        )
+        .map_err(CompilerError::CodegenError)?;
 
-                    // Call the deployed test
-                    let res = self.call(name, Address::zero(), address, value, data)?;
-                    Ok(res)
-                }
-                Err(e) => Err(CompilerError::CodegenError(e).into()),
-            },
-            Err(e) => Err(CompilerError::CodegenError(e).into()),
+        // Generate table bytecode for compiled test macro, running the passes over it
+        let macro_ranges = res.macro_ranges.clone();
+        let cx = PassContext {
+            evm_version: &evm_version,
+            contract,
+            macro_name: &name,
+            macro_ranges: &macro_ranges,
+        };
+        let bytecode = Codegen::gen_bytecode_with_passes(&mut res, &self.settings.pipeline, &cx)
+            .map_err(CompilerError::CodegenError)?;
+        let macro_ranges = res.macro_ranges;
+        // Test macros aren't deployed with constructor arguments, so immutables are zero
+        let bytecode = Codegen::fill_immutable_placeholders(&bytecode).0;
+
+        // Tests that aren't fuzzed are only executed once
+        let Some(runs) = runs else {
+            // Deploy compiled test macro
+            let address = self.deploy_code(bytecode)?;
+
+            // Call the deployed test
+            let (mut res, pc_gas) =
+                self.profiled_call(name, Address::zero(), address, value, data)?;
+            res.macro_gas = Self::aggregate_macro_gas(&pc_gas, &macro_ranges);
+            return Ok(res)
+        };
+
+        let runs = runs.max(1);
+        let seed = seed.unwrap_or_else(rand::random);
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let mut gas = 0;
+        let mut macro_gas: BTreeMap<String, u64> = BTreeMap::new();
+        let mut completed = 0;
+        let mut counterexample = None;
+        let mut result = None;
+        for _ in 0..runs {
+            // Generate the inputs for this run, the first input ends up on top of the stack
+            let inputs =
+                (0..m.takes).map(|_| hex::encode(rng.gen::<[u8; 32]>())).collect::<Vec<_>>();
+            let pushes =
+                inputs.iter().rev().map(|i| format!("{}{i}", Opcode::Push32)).collect::<String>();
+
+            // Deploy compiled test macro along with its inputs
+            let address = self.deploy_code(format!("{pushes}{bytecode}"))?;
+
+            // Call the deployed test
+            let (res, pc_gas) =
+                self.profiled_call(name.clone(), Address::zero(), address, value, data.clone())?;
+            completed += 1;
+            // Don't charge the test for pushing its inputs (3 gas per PUSH32)
+            gas += res.gas.saturating_sub(3 * m.takes as u64);
+            for (name, g) in Self::aggregate_macro_gas(&pc_gas, &macro_ranges) {
+                *macro_gas.entry(name).or_default() += g;
+            }
+
+            // Stop fuzzing at the first failing run and report its inputs
+            let failed = matches!(res.status, TestStatus::Revert);
+            result = Some(res);
+            if failed {
+                counterexample = Some(inputs);
+                break
+            }
+        }
+
+        // Report the mean gas usage over all completed runs
+        let mut res = result.expect("fuzzed tests are run at least once");
+        res.gas = gas / completed;
+        res.macro_gas = macro_gas.into_iter().map(|(n, g)| (n, g / completed)).collect();
+        res.fuzz = Some(FuzzSummary { runs: completed, seed, counterexample });
+        Ok(res)
+    }
+
+    /// Attribute the gas spent at each program counter to every macro whose bytecode contains it.
+    fn aggregate_macro_gas(
+        pc_gas: &BTreeMap<usize, u64>,
+        macro_ranges: &[MacroRange],
+    ) -> BTreeMap<String, u64> {
+        let mut macro_gas = BTreeMap::new();
+        for (pc, gas) in pc_gas {
+            let mut names = macro_ranges
+                .iter()
+                .filter(|r| r.contains(*pc))
+                .map(|r| &r.name)
+                .collect::<Vec<_>>();
+            // A recursively invoked macro should only be charged once per instruction
+            names.sort();
+            names.dedup();
+            for name in names {
+                *macro_gas.entry(name.clone()).or_default() += gas;
+            }
         }
+        macro_gas
     }
 
     /// Build an EVM transaction environment.
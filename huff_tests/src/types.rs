@@ -1,5 +1,6 @@
 use comfy_table::{Cell, Color};
 use serde::Serialize;
+use std::collections::BTreeMap;
 use yansi::Paint;
 
 /// A test result
@@ -10,6 +11,21 @@ pub struct TestResult {
     pub gas: u64,
     pub status: TestStatus,
     pub logs: Vec<(u32, String)>,
+    /// Gas consumed within each macro invoked by the test, including nested invocations
+    pub macro_gas: BTreeMap<String, u64>,
+    /// The summary of the runs of a test with a `fuzz` flag, `None` if it isn't fuzzed
+    pub fuzz: Option<FuzzSummary>,
+}
+
+/// A summary of the runs of a fuzzed test
+#[derive(Debug, Clone, Serialize)]
+pub struct FuzzSummary {
+    /// The number of completed runs
+    pub runs: u64,
+    /// The seed the inputs were generated from
+    pub seed: u64,
+    /// The inputs of the failing run, if any
+    pub counterexample: Option<Vec<String>>,
 }
 
 /// A test status variant
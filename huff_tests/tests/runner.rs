@@ -1,5 +1,8 @@
 use ethers_core::types::{Address, U256};
+use huff_lexer::Lexer;
+use huff_parser::Parser;
 use huff_tests::prelude::{TestRunner, TestStatus};
+use huff_utils::prelude::{FullFileSource, Token};
 
 #[test]
 fn test_runner_return() {
@@ -66,3 +69,98 @@ fn test_runner_revert() {
     assert_eq!(result.gas, 6);
     assert_eq!(result.return_data, None);
 }
+
+#[test]
+fn test_runner_macro_gas() {
+    let source = r#"
+        #define macro ADD_ONE() = takes (1) returns (1) {
+            0x01 add
+        }
+
+        #define macro ADD_TWO() = takes (1) returns (1) {
+            ADD_ONE() ADD_ONE()
+        }
+
+        #define test GAS() = takes (0) returns (0) {
+            0x00 ADD_TWO() pop
+        }
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let contract = Parser::new(tokens, None).unwrap().parse().unwrap();
+
+    let mut runner = TestRunner::default();
+    let result = runner.run_test(contract.tests()[0], &contract).unwrap();
+
+    assert_eq!(result.name, "GAS");
+    assert_eq!(result.gas, 16);
+    assert_eq!(result.macro_gas.get("ADD_ONE"), Some(&12));
+    assert_eq!(result.macro_gas.get("ADD_TWO"), Some(&12));
+    assert_eq!(result.macro_gas.len(), 2);
+}
+
+#[test]
+fn test_runner_fuzzed_test() {
+    let source = r#"
+        #[fuzz(0x08)]
+        #define test ADD_COMMUTES() = takes (2) returns (0) {
+            dup2 dup2 add       // [a + b, a, b]
+            swap2 add           // [b + a, a + b]
+            eq iszero fail jumpi
+            stop
+            fail:
+                0x00 dup1 revert
+        }
+
+        #[fuzz(0x08), seed(0x2a)]
+        #define test IS_ZERO() = takes (1) returns (0) {
+            fail jumpi
+            stop
+            fail:
+                0x00 dup1 revert
+        }
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let contract = Parser::new(tokens, None).unwrap().parse().unwrap();
+    let tests = contract.tests();
+
+    let mut runner = TestRunner::default();
+    let result = runner.run_test(tests[0], &contract).unwrap();
+    assert!(matches!(result.status, TestStatus::Success));
+    let fuzz = result.fuzz.unwrap();
+    assert_eq!(fuzz.runs, 8);
+    assert_eq!(fuzz.counterexample, None);
+
+    // The first run fails, and the same seed always reproduces the same counterexample
+    let result = runner.run_test(tests[1], &contract).unwrap();
+    assert!(matches!(result.status, TestStatus::Revert));
+    let fuzz = result.fuzz.unwrap();
+    assert_eq!(fuzz.runs, 1);
+    assert_eq!(fuzz.seed, 0x2a);
+    let counterexample = fuzz.counterexample.unwrap();
+    assert_eq!(counterexample.len(), 1);
+    let rerun = runner.run_test(tests[1], &contract).unwrap();
+    assert_eq!(rerun.fuzz.unwrap().counterexample, Some(counterexample));
+}
+
+#[test]
+fn test_runner_only_fuzzes_with_flag() {
+    let source = r#"
+        #define test TAKES_ONE() = takes (1) returns (0) {
+            stop
+        }
+    "#;
+    let flattened_source = FullFileSource { source, file: None, spans: vec![] };
+    let lexer = Lexer::new(flattened_source.source);
+    let tokens = lexer.into_iter().map(|x| x.unwrap()).collect::<Vec<Token>>();
+    let contract = Parser::new(tokens, None).unwrap().parse().unwrap();
+
+    // Taking stack inputs doesn't fuzz a test without a `fuzz` flag
+    let mut runner = TestRunner::default();
+    let result = runner.run_test(contract.tests()[0], &contract).unwrap();
+    assert!(matches!(result.status, TestStatus::Success));
+    assert!(result.fuzz.is_none());
+}
@@ -0,0 +1,100 @@
+use huff_core::Compiler;
+use huff_tests::{prelude::TestStatus, HuffTester};
+use huff_utils::prelude::EVMVersion;
+use std::{collections::HashMap, rc::Rc, sync::Arc};
+
+#[test]
+fn test_stdlib_macros() {
+    let source = r#"
+        #include <huffstd/math/SafeMath.huff>
+        #include <huffstd/auth/Owned.huff>
+        #include <huffstd/utils/Memory.huff>
+        #include <huffstd/tokens/ERC20Transfers.huff>
+
+        #define macro MAIN() = takes (0) returns (0) {
+            GET_OWNER() RETURN_WORD()
+        }
+
+        #define macro ASSERT() = takes (1) returns (0) {
+            ok jumpi
+            0x00 dup1 revert
+            ok:
+        }
+
+        #define test TEST_ADD() = {
+            0x02 0x01 SAFE_ADD() 0x03 eq ASSERT()
+        }
+
+        #define test TEST_ADD_OVERFLOW() = {
+            0x01 0x00 not SAFE_ADD()
+        }
+
+        #define test TEST_SUB() = {
+            0x01 0x03 SAFE_SUB() 0x02 eq ASSERT()
+        }
+
+        #define test TEST_SUB_UNDERFLOW() = {
+            0x03 0x01 SAFE_SUB()
+        }
+
+        #define test TEST_MUL() = {
+            0x03 0x02 SAFE_MUL() 0x06 eq ASSERT()
+            0x03 0x00 SAFE_MUL() iszero ASSERT()
+        }
+
+        #define test TEST_MUL_OVERFLOW() = {
+            0x02 0x00 not SAFE_MUL()
+        }
+
+        #define test TEST_DIV() = {
+            0x02 0x07 SAFE_DIV() 0x03 eq ASSERT()
+        }
+
+        #define test TEST_DIV_BY_ZERO() = {
+            0x00 0x07 SAFE_DIV()
+        }
+
+        #define test TEST_OWNED() = {
+            OWNED_CONSTRUCTOR()
+            ONLY_OWNER()
+            GET_OWNER() caller eq ASSERT()
+        }
+
+        #define test TEST_ALLOCATE() = {
+            INIT_FREE_MEMORY_POINTER()
+            0x20 ALLOCATE() 0x80 eq ASSERT()
+            0x40 mload 0xa0 eq ASSERT()
+        }
+
+        #define test TEST_TRANSFER_WITHOUT_RETURN_DATA() = {
+            0x01 0xbeef 0xdead SAFE_TRANSFER()
+        }
+    "#;
+
+    let mut file_sources = HashMap::new();
+    file_sources.insert(String::from("contracts/stdlib.huff"), String::from(source));
+    let evm_version = EVMVersion::default();
+    let compiler = Compiler::new_in_memory(
+        &evm_version,
+        Arc::new(vec![String::from("contracts/stdlib.huff")]),
+        file_sources,
+        None,
+        None,
+        None,
+        None,
+        false,
+    );
+    let contracts = compiler.grab_contracts().unwrap();
+    let results = HuffTester::new(&contracts[0], Rc::new(None)).execute().unwrap();
+
+    let reverted = results
+        .iter()
+        .filter(|r| matches!(r.status, TestStatus::Revert))
+        .map(|r| r.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 11);
+    assert_eq!(
+        reverted,
+        vec!["TEST_ADD_OVERFLOW", "TEST_SUB_UNDERFLOW", "TEST_MUL_OVERFLOW", "TEST_DIV_BY_ZERO"]
+    );
+}
@@ -18,7 +18,11 @@
 //!     macros: vec![],
 //!     invocations: vec![],
 //!     imports: vec![],
+//!     selective_imports: vec![],
+//!     full_imports: vec![],
+//!     namespaced_imports: vec![],
 //!     constants: Arc::new(Mutex::new(vec![])),
+//!     immutables: vec![],
 //!     errors: vec![],
 //!     functions: vec![huff_utils::ast::FunctionDefinition {
 //!         name: "CONSTRUCTOR".to_string(),
@@ -30,12 +34,17 @@
 //!     }],
 //!     events: vec![],
 //!     tables: vec![],
+//!     assertions: vec![],
+//!     invalid_definitions: vec![],
+//!     trivia: TriviaTable::default(),
+//!     custom_builtins: CustomBuiltins::default(),
 //! };
 //!
 //! // Create an ABI using that generate contract
 //! let abi: Abi = contract.into();
 //! ```
 
+use ethers_core::utils::hex;
 use serde::{Deserialize, Serialize};
 use std::{collections::BTreeMap, fmt};
 
@@ -169,6 +178,7 @@ impl From<ast::Contract> for Abi {
                         })
                         .collect(),
                     anonymous: false,
+                    topic: format!("0x{}", hex::encode(event.hash)),
                 },
             )
         }));
@@ -224,6 +234,9 @@ pub struct Event {
     pub inputs: Vec<EventParam>,
     /// Anonymity
     pub anonymous: bool,
+    /// The topic of the event, the keccak256 hash of its signature
+    #[serde(default)]
+    pub topic: String,
 }
 
 /// #### EventParam
@@ -298,6 +311,29 @@ pub enum FunctionParamType {
 }
 
 impl FunctionParamType {
+    /// Converts the param type into its ethers [ParamType](ethers_core::abi::ParamType)
+    pub fn as_param_type(&self) -> ethers_core::abi::ParamType {
+        use ethers_core::abi::ParamType;
+        match self {
+            FunctionParamType::Address => ParamType::Address,
+            FunctionParamType::Bytes => ParamType::Bytes,
+            FunctionParamType::Int(size) => ParamType::Int(*size),
+            FunctionParamType::Uint(size) => ParamType::Uint(*size),
+            FunctionParamType::Bool => ParamType::Bool,
+            FunctionParamType::String => ParamType::String,
+            FunctionParamType::Array(fpt, sizes) => {
+                sizes.iter().fold(fpt.as_param_type(), |inner, size| match size {
+                    0 => ParamType::Array(Box::new(inner)),
+                    size => ParamType::FixedArray(Box::new(inner), *size),
+                })
+            }
+            FunctionParamType::FixedBytes(size) => ParamType::FixedBytes(*size),
+            FunctionParamType::Tuple(inner) => {
+                ParamType::Tuple(inner.iter().map(|fpt| fpt.as_param_type()).collect())
+            }
+        }
+    }
+
     /// Checks if the param type should be designated as "memory" for solidity interface
     /// generation.
     pub fn is_memory_type(&self) -> bool {
@@ -2,23 +2,358 @@
 //!
 //! The artifacts generated from codegen.
 
+use ethers_core::{
+    types::{Address, U256},
+    utils::{get_contract_address, get_create2_address_from_hash, hex, keccak256},
+};
 use serde::{Deserialize, Serialize};
-use std::{fs, path::Path, sync::Arc};
+use std::{collections::BTreeMap, fs, path::Path, sync::Arc};
 
 pub use crate::abi::Abi;
-use crate::prelude::FileSource;
+use crate::{
+    disassembler::{annotate, disassemble},
+    docs::{doc_comment, NatSpec},
+    prelude::{
+        Argument, BytecodeRes, CompilerFingerprint, CompilerWarning, Contract, FileSource,
+        FunctionDefinition, Literal, MacroRange,
+    },
+};
 
 /// A Codegen Artifact
 #[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct Artifact {
     /// The file source
     pub file: Arc<FileSource>,
-    /// The deployed bytecode
+    /// The compiler the artifact was built with
+    #[serde(default)]
+    pub compiler: CompilerFingerprint,
+    /// The creation bytecode, with the constructor arguments appended
     pub bytecode: String,
-    /// The runtime bytecode
+    /// The runtime bytecode, as deployed by the creation bytecode
+    #[serde(rename = "deployedBytecode", alias = "runtime")]
     pub runtime: String,
+    /// The keccak256 hash of the deployed bytecode, with the constructor arguments, that CREATE2
+    /// addresses are derived from
+    #[serde(default, rename = "initcodeHash")]
+    pub initcode_hash: String,
+    /// The source map of the creation bytecode, in solc's `s:l:f:j` format
+    #[serde(default, rename = "sourceMap")]
+    pub source_map: String,
+    /// The source map of the runtime bytecode
+    #[serde(default, rename = "deployedSourceMap", alias = "runtimeSourceMap")]
+    pub runtime_source_map: String,
+    /// The paths of the files the source maps refer to by index
+    #[serde(default, rename = "sourceList")]
+    pub source_list: Vec<String>,
     /// The abi
     pub abi: Option<Abi>,
+    /// The settings the bootstrap code was generated with
+    #[serde(default)]
+    pub bootstrap: BootstrapSettings,
+    /// The storage slots assigned to constants
+    #[serde(default, rename = "storageLayout")]
+    pub storage_layout: StorageLayout,
+    /// The NatSpec notices of the functions
+    #[serde(default)]
+    pub userdoc: UserDoc,
+    /// The NatSpec details of the functions
+    #[serde(default)]
+    pub devdoc: DevDoc,
+    /// The macros and labels behind each program counter
+    #[serde(default)]
+    pub debug: DebugInfo,
+    /// The metadata JSON hashed into the CBOR trailer of the runtime code, if one was appended
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<String>,
+    /// The warnings raised while compiling the contract, not stored but raised again when the
+    /// artifact is read from the cache
+    #[serde(skip)]
+    pub warnings: Vec<CompilerWarning>,
+}
+
+/// The macro expansion stack and label at each program counter of the constructor and runtime
+/// code, for stack traces like `MAIN > TRANSFER > error:` instead of raw program counters
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct DebugInfo {
+    /// The ranges of the constructor code
+    pub constructor: Vec<DebugRange>,
+    /// The ranges of the runtime code
+    pub runtime: Vec<DebugRange>,
+}
+
+/// A range of program counters generated within the same macros and label
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct DebugRange {
+    /// The first program counter of the range
+    pub start: usize,
+    /// The program counter directly after the range
+    pub end: usize,
+    /// The macros being expanded, starting with the outermost
+    pub macros: Vec<String>,
+    /// The last label of the innermost macro preceding the range
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl DebugRange {
+    /// The stack trace of the range, eg. `MAIN > TRANSFER > error:`
+    pub fn trace(&self) -> String {
+        let label = self.label.as_ref().map(|l| format!("{l}:"));
+        self.macros.iter().cloned().chain(label).collect::<Vec<String>>().join(" > ")
+    }
+}
+
+impl DebugInfo {
+    /// Splits the code generated from the `root` macro into ranges of the same macros and label
+    ///
+    /// Jump tables following the code aren't covered.
+    pub fn ranges(root: &str, res: &BytecodeRes) -> Vec<DebugRange> {
+        let pcs = res.instructions.iter().filter_map(|i| i.origin.map(|pc| (pc, pc + i.ty.size())));
+        let start = pcs.clone().map(|(start, _)| start).min().unwrap_or_default();
+        let end = pcs.map(|(_, end)| end).max().unwrap_or_default();
+        let root =
+            MacroRange { name: root.to_string(), start, end, labels: res.defined_labels.clone() };
+
+        // Order the macros from the outermost, an invoking macro being pushed after the macros
+        // it invokes
+        let mut macros = std::iter::once(&root)
+            .chain(res.macro_ranges.iter().rev())
+            .filter(|m| m.start < m.end)
+            .collect::<Vec<&MacroRange>>();
+        macros.sort_by_key(|m| (m.start, std::cmp::Reverse(m.end)));
+
+        let mut boundaries = macros
+            .iter()
+            .flat_map(|m| [m.start, m.end].into_iter().chain(m.labels.values().copied()))
+            .filter(|pc| (start..=end).contains(pc))
+            .collect::<Vec<usize>>();
+        boundaries.sort_unstable();
+        boundaries.dedup();
+
+        let mut ranges: Vec<DebugRange> = vec![];
+        for window in boundaries.windows(2) {
+            let (start, end) = (window[0], window[1]);
+            let stack = macros.iter().filter(|m| m.contains(start)).collect::<Vec<_>>();
+            let label = stack.last().and_then(|m| {
+                m.labels.iter().filter(|(_, pc)| **pc <= start).max_by_key(|(_, pc)| **pc)
+            });
+            let range = DebugRange {
+                start,
+                end,
+                macros: stack.iter().map(|m| m.name.clone()).collect(),
+                label: label.map(|(name, _)| name.clone()),
+            };
+            match ranges.last_mut() {
+                Some(last) if last.macros == range.macros && last.label == range.label => {
+                    last.end = end
+                }
+                _ => ranges.push(range),
+            }
+        }
+        ranges
+    }
+
+    /// The bytes generated by each macro itself, not counting the macros it invokes, from the
+    /// largest
+    pub fn macro_sizes<'a>(
+        ranges: impl IntoIterator<Item = &'a DebugRange>,
+    ) -> Vec<(String, usize)> {
+        let mut sizes: BTreeMap<&str, usize> = BTreeMap::new();
+        for range in ranges {
+            if let Some(name) = range.macros.last() {
+                *sizes.entry(name).or_default() += range.end - range.start;
+            }
+        }
+        let mut sizes =
+            sizes.into_iter().map(|(name, size)| (name.to_string(), size)).collect::<Vec<_>>();
+        sizes.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+        sizes
+    }
+
+    /// The range of the runtime code containing a program counter
+    pub fn runtime_at(&self, pc: usize) -> Option<&DebugRange> {
+        self.runtime.iter().find(|r| r.start <= pc && pc < r.end)
+    }
+}
+
+/// The storage layout of a contract, in the shape of solc's `storageLayout` output
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct StorageLayout {
+    /// The constants assigned a storage slot, ordered by slot
+    pub storage: Vec<StorageLayoutEntry>,
+}
+
+/// A constant assigned a storage slot
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct StorageLayoutEntry {
+    /// The constant's name
+    pub label: String,
+    /// The storage slot as a decimal string
+    pub slot: String,
+}
+
+impl From<Vec<(String, Literal)>> for StorageLayout {
+    fn from(layout: Vec<(String, Literal)>) -> Self {
+        Self {
+            storage: layout
+                .into_iter()
+                .map(|(label, slot)| StorageLayoutEntry {
+                    label,
+                    slot: U256::from_big_endian(&slot).to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The user documentation of a contract, in the shape of solc's `userdoc` output
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct UserDoc {
+    /// Always `user`
+    pub kind: String,
+    /// The `@notice` of each function with one, by signature, eg. `transfer(address,uint256)`
+    pub methods: BTreeMap<String, UserDocMethod>,
+    /// The version of the format
+    pub version: u8,
+}
+
+impl Default for UserDoc {
+    fn default() -> Self {
+        Self { kind: "user".to_string(), methods: BTreeMap::new(), version: 1 }
+    }
+}
+
+/// The user documentation of a function
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct UserDocMethod {
+    /// The `@notice` of the function
+    pub notice: String,
+}
+
+/// The developer documentation of a contract, in the shape of solc's `devdoc` output
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct DevDoc {
+    /// Always `dev`
+    pub kind: String,
+    /// The `@dev`, `@param` and `@return` tags of each function with any, by signature
+    pub methods: BTreeMap<String, DevDocMethod>,
+    /// The version of the format
+    pub version: u8,
+}
+
+impl Default for DevDoc {
+    fn default() -> Self {
+        Self { kind: "dev".to_string(), methods: BTreeMap::new(), version: 1 }
+    }
+}
+
+/// The developer documentation of a function
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct DevDocMethod {
+    /// The `@dev` details of the function
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+    /// The `@param` descriptions, by parameter name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, String>,
+    /// The `@return` descriptions, by the name of the output, or `_<index>` if it's unnamed
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub returns: BTreeMap<String, String>,
+}
+
+/// The NatSpec of the documented functions of a contract, by signature
+fn function_natspecs(
+    contract: &Contract,
+) -> impl Iterator<Item = (String, &FunctionDefinition, NatSpec)> {
+    contract.functions.iter().filter_map(|f| {
+        let doc = doc_comment(contract.trivia.leading(&f.span))?;
+        let types =
+            f.inputs.iter().map(|a| a.arg_type.clone().unwrap_or_default()).collect::<Vec<_>>();
+        Some((format!("{}({})", f.name, types.join(",")), f, NatSpec::parse(&doc)))
+    })
+}
+
+impl UserDoc {
+    /// Public associated function that collects the `@notice` tags of a contract's functions
+    pub fn new(contract: &Contract) -> Self {
+        let methods = function_natspecs(contract)
+            .filter_map(|(signature, _, natspec)| {
+                Some((signature, UserDocMethod { notice: natspec.notice? }))
+            })
+            .collect();
+        Self { methods, ..Default::default() }
+    }
+}
+
+impl DevDoc {
+    /// Public associated function that collects the `@dev`, `@param` and `@return` tags of a
+    /// contract's functions
+    ///
+    /// Like solc, a `@return` starting with the name of its output is keyed by that name.
+    pub fn new(contract: &Contract) -> Self {
+        let methods = function_natspecs(contract)
+            .map(|(signature, f, natspec)| {
+                let returns = natspec
+                    .returns
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, text)| return_doc(f.outputs.get(i), i, text))
+                    .collect();
+                let method = DevDocMethod {
+                    details: natspec.dev,
+                    params: natspec.params.into_iter().collect(),
+                    returns,
+                };
+                (signature, method)
+            })
+            .filter(|(_, m)| m.details.is_some() || !m.params.is_empty() || !m.returns.is_empty())
+            .collect();
+        Self { methods, ..Default::default() }
+    }
+}
+
+/// The key and description of a `@return` tag
+fn return_doc(output: Option<&Argument>, index: usize, text: String) -> (String, String) {
+    if let Some(name) = output.and_then(|o| o.name.as_ref()) {
+        if let Some(description) = text.strip_prefix(name.as_str()) {
+            if description.is_empty() || description.starts_with(char::is_whitespace) {
+                return (name.clone(), description.trim_start().to_string())
+            }
+        }
+    }
+    (format!("_{index}"), text)
+}
+
+/// Settings for generating the bootstrap code, which copies the runtime code into memory and
+/// returns it at the end of the constructor
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct BootstrapSettings {
+    /// The memory offset the runtime code is copied to
+    pub memory_offset: usize,
+    /// A macro replacing the default bootstrap code
+    ///
+    /// The macro takes the runtime code's offset in the creation code and its size, as
+    /// `[runtime_offset, runtime_size]`, and must return the runtime code itself.
+    pub shim: Option<String>,
+    /// A code table whose contents are returned as the runtime code, in place of the code
+    /// generated from `MAIN`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub table: Option<String>,
+}
+
+/// How a contract is deployed, to predict its address
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Deployment {
+    /// Deployed with CREATE by an account with a nonce
+    Create {
+        /// The nonce of the deployer
+        nonce: u64,
+    },
+    /// Deployed with CREATE2 by a contract with a salt
+    Create2 {
+        /// The salt, as a 32 byte word
+        salt: [u8; 32],
+    },
 }
 
 impl Artifact {
@@ -32,4 +367,45 @@ impl Artifact {
         }
         fs::write(file_path, serialized_artifact)
     }
+
+    /// Hashes the deployed bytecode, including the constructor arguments appended to it
+    pub fn hash_initcode(&self) -> String {
+        let initcode = hex::decode(&self.bytecode).unwrap_or_default();
+        format!("0x{}", hex::encode(keccak256(initcode)))
+    }
+
+    /// The address the contract is deployed at by a deployer
+    ///
+    /// With CREATE the address only depends on the deployer's nonce, with CREATE2 it depends on
+    /// the salt and the [initcode hash](Self::initcode_hash), and so on the constructor arguments.
+    pub fn predict_address(&self, deployer: Address, deployment: &Deployment) -> Address {
+        match deployment {
+            Deployment::Create { nonce } => get_contract_address(deployer, *nonce),
+            Deployment::Create2 { salt } => {
+                let initcode_hash = match hex::decode(self.initcode_hash.trim_start_matches("0x")) {
+                    Ok(hash) if hash.len() == 32 => hash,
+                    _ => keccak256(hex::decode(&self.bytecode).unwrap_or_default()).to_vec(),
+                };
+                get_create2_address_from_hash(deployer, salt, initcode_hash)
+            }
+        }
+    }
+
+    /// The assembly of the creation and runtime code, one instruction per line with their
+    /// labels and the macros they were expanded from, like `solc --asm`
+    ///
+    /// The creation code excludes the runtime code and the constructor arguments following it.
+    pub fn assembly(&self) -> String {
+        let creation = match self.bytecode.rfind(&self.runtime) {
+            Some(end) if !self.runtime.is_empty() => &self.bytecode[..end],
+            _ => &self.bytecode,
+        };
+        let indent =
+            |asm: String| asm.lines().map(|line| format!("    {line}\n")).collect::<String>();
+        format!(
+            "creation {{\n{}}}\n\nruntime {{\n{}}}\n",
+            indent(annotate(&disassemble(creation), &self.debug.constructor)),
+            indent(annotate(&disassemble(&self.runtime), &self.debug.runtime))
+        )
+    }
 }
@@ -1,16 +1,23 @@
+use ethers_core::types::U256;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    builtins::CustomBuiltins,
     bytecode::*,
     bytes_util::*,
-    error::CodegenError,
+    error::{
+        CodegenError, CodegenErrorKind, CompilerWarning, ParserError, ParserErrorKind, WarningKind,
+    },
     evm::Opcode,
     evm_version::EVMVersion,
+    files::{normalize_path, FileSource, FullFileSource, Remapper},
+    ir::IRInstructionType,
     prelude::{MacroArg::Ident, Span, TokenKind},
+    suggestions::did_you_mean,
 };
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     fmt::{Display, Formatter},
     path::PathBuf,
     sync::{Arc, Mutex},
@@ -24,8 +31,34 @@ pub type Literal = [u8; 32];
 /// Used for parsing the huff imports.
 pub type FilePath = PathBuf;
 
+/// An `#include` that only imports the listed definitions of a file
+///
+/// e.g. `#include "./utils.huff" { MACRO_A, CONST_B }`
+#[derive(Serialize, Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SelectiveImport {
+    /// The path of the included file, as written in the source
+    pub path: FilePath,
+    /// The names of the imported definitions
+    pub names: Vec<String>,
+    /// The span of the import
+    pub span: AstSpan,
+}
+
+/// An `#include` whose definitions are accessed through a namespace
+///
+/// e.g. `#include "./math.huff" as Math`, with invocations like `Math.SAFE_ADD()`
+#[derive(Serialize, Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NamespacedImport {
+    /// The path of the included file, as written in the source
+    pub path: FilePath,
+    /// The namespace of the file's definitions
+    pub namespace: String,
+    /// The span of the import
+    pub span: AstSpan,
+}
+
 /// An AST-level Span
-#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct AstSpan(pub Vec<Span>);
 
 impl AstSpan {
@@ -85,7 +118,7 @@ impl AstSpan {
 /// Thus, it is also the root of the AST.
 ///
 /// For examples of Huff contracts, see the [huff-examples repository](https://github.com/huff-language/huff-examples).
-#[derive(Debug, Default, Clone)]
+#[derive(Serialize, Debug, Default, Clone)]
 pub struct Contract {
     /// Macro definitions
     pub macros: Vec<MacroDefinition>,
@@ -93,8 +126,16 @@ pub struct Contract {
     pub invocations: Vec<MacroInvocation>,
     /// File Imports
     pub imports: Vec<FilePath>,
+    /// File Imports restricted to a list of definitions
+    pub selective_imports: Vec<SelectiveImport>,
+    /// File Imports of all of a file's definitions, with the span of the import
+    pub full_imports: Vec<(FilePath, AstSpan)>,
+    /// File Imports under a namespace
+    pub namespaced_imports: Vec<NamespacedImport>,
     /// Constants
     pub constants: Arc<Mutex<Vec<ConstantDefinition>>>,
+    /// Immutables
+    pub immutables: Vec<ImmutableDefinition>,
     /// Custom Errors
     pub errors: Vec<ErrorDefinition>,
     /// Functions
@@ -103,6 +144,15 @@ pub struct Contract {
     pub events: Vec<EventDefinition>,
     /// Tables
     pub tables: Vec<TableDefinition>,
+    /// Compile-time assertions
+    pub assertions: Vec<AssertionDefinition>,
+    /// Definitions that failed to parse, only collected when parsing tolerantly
+    pub invalid_definitions: Vec<InvalidDefinition>,
+    /// The comments and whitespace between the tokens of the definitions
+    pub trivia: TriviaTable,
+    /// The custom builtins calls are expanded with
+    #[serde(skip)]
+    pub custom_builtins: CustomBuiltins,
 }
 
 impl Contract {
@@ -116,6 +166,270 @@ impl Contract {
         }
     }
 
+    /// Removes the definitions of selectively imported files that were not imported.
+    ///
+    /// Definitions of such a file are kept if they are listed in any of its imports, or are
+    /// transitively referenced by a listed definition. Files that are also imported in full keep
+    /// all of their definitions. Errors if a listed definition isn't found in the file.
+    pub fn apply_selective_imports(
+        &mut self,
+        full_source: &FullFileSource,
+        remapper: &Remapper,
+    ) -> Result<(), ParserError> {
+        // Merge the names imported from each file
+        let mut selections: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for import in &self.selective_imports {
+            let imported = imported_file(full_source, remapper, &import.path, &import.span);
+            let defined = self.defined_names(full_source, &imported);
+            if let Some(missing) = import.names.iter().find(|n| !defined.contains(n)) {
+                tracing::error!(target: "ast", "\"{}\" IS NOT DEFINED IN \"{}\"", missing, imported);
+                return Err(ParserError {
+                    kind: ParserErrorKind::InvalidImportSelection(missing.clone()),
+                    hint: Some(format!("\"{missing}\" is not defined in \"{imported}\"")),
+                    spans: import.span.clone(),
+                })
+            }
+            selections.entry(imported).or_default().extend(import.names.iter().cloned());
+        }
+        for (path, span) in &self.full_imports {
+            selections.remove(&imported_file(full_source, remapper, path, span));
+        }
+
+        for (imported, names) in selections {
+            let in_file = |span: &AstSpan| defined_in(full_source, span, &imported);
+            let defined = self.defined_names(full_source, &imported);
+            let constants = self.constants.lock().unwrap().clone();
+
+            // Walk the references of the imported definitions
+            let mut keep: Vec<String> = vec![];
+            let mut queue: Vec<String> = names;
+            while let Some(name) = queue.pop() {
+                if keep.contains(&name) || !defined.contains(&name) {
+                    continue
+                }
+                if let Some(m) = self.macros.iter().find(|m| m.name == name && in_file(&m.span)) {
+                    referenced_names(&m.statements, &mut queue);
+                }
+                if let Some(t) = self.tables.iter().find(|t| t.name == name && in_file(&t.span)) {
+                    referenced_names(&t.statements, &mut queue);
+                }
+                if let Some(ConstVal::Expression(e)) =
+                    constants.iter().find(|c| c.name == name && in_file(&c.span)).map(|c| &c.value)
+                {
+                    queue.extend(e.referenced_constants());
+                }
+                keep.push(name);
+            }
+
+            let retained = |name: &String, span: &AstSpan| keep.contains(name) || !in_file(span);
+            self.macros.retain(|m| retained(&m.name, &m.span));
+            self.tables.retain(|t| retained(&t.name, &t.span));
+            self.functions.retain(|f| retained(&f.name, &f.span));
+            self.events.retain(|e| retained(&e.name, &e.span));
+            self.errors.retain(|e| retained(&e.name, &e.span));
+            self.constants.lock().unwrap().retain(|c| retained(&c.name, &c.span));
+        }
+        Ok(())
+    }
+
+    /// The names of the definitions of the file at `path`
+    fn defined_names(&self, full_source: &FullFileSource, path: &str) -> Vec<String> {
+        let in_file = |span: &AstSpan| defined_in(full_source, span, path);
+        let constants = self.constants.lock().unwrap();
+        let mut defined: Vec<String> = vec![];
+        defined.extend(self.macros.iter().filter(|m| in_file(&m.span)).map(|m| m.name.clone()));
+        defined.extend(constants.iter().filter(|c| in_file(&c.span)).map(|c| c.name.clone()));
+        defined.extend(self.tables.iter().filter(|t| in_file(&t.span)).map(|t| t.name.clone()));
+        defined.extend(self.functions.iter().filter(|f| in_file(&f.span)).map(|f| f.name.clone()));
+        defined.extend(self.events.iter().filter(|e| in_file(&e.span)).map(|e| e.name.clone()));
+        defined.extend(self.errors.iter().filter(|e| in_file(&e.span)).map(|e| e.name.clone()));
+        defined
+    }
+
+    /// Prefixes the definitions of files imported under a namespace with that namespace.
+    ///
+    /// Macros, constants and tables of such a file become `Namespace.NAME`, and references to
+    /// them from within the file are rewritten accordingly. ABI definitions keep their names.
+    pub fn apply_namespaced_imports(&mut self, full_source: &FullFileSource, remapper: &Remapper) {
+        for import in self.namespaced_imports.clone() {
+            let imported = imported_file(full_source, remapper, &import.path, &import.span);
+            let in_file = |span: &AstSpan| defined_in(full_source, span, &imported);
+            let mut constants = self.constants.lock().unwrap();
+
+            let mut renames: BTreeMap<String, String> = BTreeMap::new();
+            let mut qualify = |name: &mut String| {
+                let qualified = format!("{}.{}", import.namespace, name);
+                renames.insert(std::mem::replace(name, qualified.clone()), qualified);
+            };
+            self.macros.iter_mut().filter(|m| in_file(&m.span)).for_each(|m| qualify(&mut m.name));
+            constants.iter_mut().filter(|c| in_file(&c.span)).for_each(|c| qualify(&mut c.name));
+            self.tables.iter_mut().filter(|t| in_file(&t.span)).for_each(|t| qualify(&mut t.name));
+            tracing::debug!(target: "ast", "NAMESPACED {} DEFINITIONS OF \"{}\" AS \"{}\"", renames.len(), imported, import.namespace);
+
+            for m in self.macros.iter_mut().filter(|m| in_file(&m.span)) {
+                rename_references(&mut m.statements, &renames);
+            }
+            for t in self.tables.iter_mut().filter(|t| in_file(&t.span)) {
+                rename_references(&mut t.statements, &renames);
+            }
+            for c in constants.iter_mut().filter(|c| in_file(&c.span)) {
+                if let ConstVal::Expression(e) = &mut c.value {
+                    e.rename_constants(&renames);
+                }
+            }
+            for a in self.assertions.iter_mut().filter(|a| in_file(&a.span)) {
+                for operand in [&mut a.lhs, &mut a.rhs] {
+                    match operand {
+                        AssertionOperand::Expression(e) => e.rename_constants(&renames),
+                        AssertionOperand::Codesize(name) => {
+                            if let Some(renamed) = renames.get(name) {
+                                *name = renamed.clone();
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Removes imported macros, constants and tables that are redefined by a file importing them.
+    ///
+    /// The definition of the importing file wins, and a warning pointing at both definitions is
+    /// returned for each shadowed definition. Definitions clashing within a file or across files
+    /// not importing each other are left to [check_duplicate_definitions].
+    ///
+    /// [check_duplicate_definitions]: Contract::check_duplicate_definitions
+    pub fn apply_shadowed_definitions(
+        &mut self,
+        full_source: &FullFileSource,
+    ) -> Vec<CompilerWarning> {
+        fn includes(file: &FileSource, dependency: &str) -> bool {
+            file.dependencies
+                .iter()
+                .flatten()
+                .any(|d| normalize_path(&d.path) == dependency || includes(d, dependency))
+        }
+
+        let mut constants = self.constants.lock().unwrap();
+        let definitions = self
+            .macros
+            .iter()
+            .map(|m| ("macro", &m.name, &m.span))
+            .chain(constants.iter().map(|c| ("constant", &c.name, &c.span)))
+            .chain(self.tables.iter().map(|t| ("table", &t.name, &t.span)))
+            .filter_map(|(kind, name, span)| {
+                let first = span.0.first()?;
+                Some((kind, name.clone(), first.clone(), full_source.file_at(first.start)?))
+            })
+            .collect::<Vec<_>>();
+
+        let mut warnings = vec![];
+        let mut shadowed: Vec<(&str, &Span)> = vec![];
+        for (kind, name, span, file) in definitions.iter() {
+            let path = normalize_path(&file.path);
+            let winner = definitions.iter().find(|(k, n, _, f)| {
+                k == kind && n == name && normalize_path(&f.path) != path && includes(f, &path)
+            });
+            if let Some((_, _, winner_span, winner_file)) = winner {
+                tracing::warn!(target: "ast", "{} \"{}\" OF \"{}\" IS SHADOWED BY \"{}\"", kind.to_uppercase(), name, file.path, winner_file.path);
+                warnings.push(CompilerWarning::new(
+                    WarningKind::ShadowedDefinition(
+                        name.clone(),
+                        file.path.clone(),
+                        winner_file.path.clone(),
+                    ),
+                    AstSpan(vec![full_source.locate(winner_span), full_source.locate(span)]),
+                ));
+                shadowed.push((kind, span));
+            }
+        }
+
+        let kept = |kind: &str, span: &AstSpan| {
+            !shadowed.iter().any(|(k, s)| *k == kind && span.0.first() == Some(*s))
+        };
+        self.macros.retain(|m| kept("macro", &m.span));
+        constants.retain(|c| kept("constant", &c.span));
+        self.tables.retain(|t| kept("table", &t.span));
+        warnings
+    }
+
+    /// Errors if a macro, constant, table or function is defined more than once, pointing at
+    /// both definitions. Functions may be overloaded, so only functions sharing a selector clash.
+    pub fn check_duplicate_definitions(
+        &self,
+        full_source: &FullFileSource,
+    ) -> Result<(), ParserError> {
+        let constants = self.constants.lock().unwrap();
+        let definitions = self
+            .macros
+            .iter()
+            .map(|m| ("macro", &m.name, None, &m.span))
+            .chain(constants.iter().map(|c| ("constant", &c.name, None, &c.span)))
+            .chain(self.tables.iter().map(|t| ("table", &t.name, None, &t.span)))
+            .chain(
+                self.functions.iter().map(|f| ("function", &f.name, Some(f.signature), &f.span)),
+            );
+
+        let mut defined: BTreeMap<(&str, &String, Option<[u8; 4]>), &AstSpan> = BTreeMap::new();
+        for (kind, name, selector, span) in definitions {
+            if let Some(first) = defined.insert((kind, name, selector), span) {
+                tracing::error!(target: "ast", "{} \"{}\" IS DEFINED MORE THAN ONCE", kind.to_uppercase(), name);
+                return Err(ParserError {
+                    kind: ParserErrorKind::DuplicateDefinition(name.clone()),
+                    hint: Some(format!("The {kind} \"{name}\" is already defined")),
+                    spans: AstSpan(
+                        [first, span]
+                            .iter()
+                            .filter_map(|s| s.0.first())
+                            .map(|s| full_source.locate(s))
+                            .collect(),
+                    ),
+                })
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the names of the macros, constants and tables transitively referenced by the given
+    /// definitions and by the compile-time assertions, including the given definitions
+    pub fn referenced_definitions(&self, roots: &[&str]) -> BTreeSet<String> {
+        let constants = self.constants.lock().unwrap();
+        let mut queue: Vec<String> = roots.iter().map(|r| r.to_string()).collect();
+        for assertion in self.assertions.iter() {
+            for operand in [&assertion.lhs, &assertion.rhs] {
+                match operand {
+                    AssertionOperand::Expression(e) => queue.extend(e.referenced_constants()),
+                    AssertionOperand::Codesize(name) => queue.push(name.clone()),
+                }
+            }
+        }
+
+        let mut referenced = BTreeSet::new();
+        while let Some(name) = queue.pop() {
+            if referenced.contains(&name) {
+                continue
+            }
+            if let Some(m) = self.macros.iter().find(|m| m.name == name) {
+                referenced_names(&m.statements, &mut queue);
+            }
+            if let Some(t) = self.tables.iter().find(|t| t.name == name) {
+                referenced_names(&t.statements, &mut queue);
+            }
+            if let Some(ConstVal::Expression(e)) =
+                constants.iter().find(|c| c.name == name).map(|c| &c.value)
+            {
+                queue.extend(e.referenced_constants());
+            }
+            referenced.insert(name);
+        }
+        referenced
+    }
+
+    /// Returns all test macros defined in the contract, in definition order
+    pub fn tests(&self) -> Vec<&MacroDefinition> {
+        self.macros.iter().filter(|m| m.test).collect()
+    }
+
     /// Returns the first table that matches the provided name
     pub fn find_table_by_name(&self, name: &str) -> Option<TableDefinition> {
         if let Some(t) = self.tables.iter().find(|t| t.name == name) {
@@ -170,9 +484,16 @@ impl Contract {
                 .get(0)
             {
                 Some(p) => {
+                    // Storage slots keep their kind so the storage layout can be checked
+                    let value = match c.value {
+                        ConstVal::FreeStoragePointer(_) | ConstVal::StorageSlot(_) => {
+                            ConstVal::StorageSlot(p.1)
+                        }
+                        _ => ConstVal::Literal(p.1),
+                    };
                     *c = ConstantDefinition {
                         name: c.name.to_string(),
-                        value: ConstVal::Literal(p.1),
+                        value,
                         span: c.span.clone(),
                     };
                 }
@@ -220,6 +541,22 @@ impl Contract {
                 StatementType::MacroInvocation(mi) => {
                     tracing::debug!(target: "ast", "Found macro invocation: \"{}\" in macro def: \"{}\"!", mi.macro_name, macro_def.name);
 
+                    // Nested macro invocations passed as arguments are expanded in this macro,
+                    // so visit them as if they were invoked directly
+                    let nested = mi.args.iter().filter_map(|arg| match arg {
+                        MacroArg::MacroCall(inner) => Some(inner),
+                        _ => None,
+                    });
+                    for (j, inner) in nested.enumerate() {
+                        statements.insert(
+                            i + j + 1,
+                            Statement {
+                                ty: StatementType::MacroInvocation(inner.clone()),
+                                span: inner.span.clone(),
+                            },
+                        );
+                    }
+
                     // Check for constant references in macro arguments
                     let mut constant_args: Vec<String> = Vec::new();
                     for arg in &mi.args {
@@ -272,6 +609,16 @@ impl Contract {
                 StatementType::BuiltinFunctionCall(bfc) => {
                     tracing::debug!(target: "ast", "Deriving Storage Pointers: Found builtin function {:?}", bfc.kind);
                     for a in &bfc.args {
+                        // Constants hashed by `__keccak`
+                        if let (Some(name), Some("bytes32")) = (&a.name, a.arg_type.as_deref()) {
+                            self.assign_free_storage_pointers(
+                                name,
+                                &macro_def.name,
+                                storage_pointers,
+                                last_p,
+                            );
+                            continue
+                        }
                         if let Some(name) = &a.name {
                             match self
                                 .macros
@@ -311,6 +658,11 @@ impl Contract {
                         statements.insert(i + 1, state.clone());
                     }
                 }
+                StatementType::Repeat(r) => {
+                    for state in r.statements.iter().rev() {
+                        statements.insert(i + 1, state.clone());
+                    }
+                }
                 _ => {}
             }
             i += 1;
@@ -340,23 +692,46 @@ impl Contract {
         {
             tracing::debug!(target: "ast", "No storage pointer already set for \"{}\"!", const_name);
             // Get the associated constant
-            match self
+            let value = self
                 .constants
                 .lock()
                 .unwrap()
                 .iter()
-                .filter(|c| c.name.eq(const_name))
-                .collect::<Vec<&ConstantDefinition>>()
-                .get(0)
-            {
-                Some(c) => {
-                    let new_value = match c.value {
+                .find(|c| c.name.eq(const_name))
+                .map(|c| c.value.clone());
+            match value {
+                Some(value) => {
+                    let new_value = match value {
                         ConstVal::Literal(l) => l,
+                        ConstVal::StorageSlot(l) => l,
                         ConstVal::FreeStoragePointer(_) => {
-                            let old_p = *last_p;
+                            // Skip over slots reserved with `STORAGE_SLOT(n)`
+                            let mut slot = str_to_bytes32(&format!("{last_p}"));
+                            while self.is_reserved_slot(&slot) {
+                                *last_p += 1;
+                                slot = str_to_bytes32(&format!("{last_p}"));
+                            }
                             *last_p += 1;
-                            str_to_bytes32(&format!("{old_p}"))
+                            slot
                         }
+                        ConstVal::Expression(expr) => {
+                            // Expressions are evaluated once all storage pointers are
+                            // derived, but the constants they reference need slots first.
+                            // A placeholder guards against circular references while recursing.
+                            storage_pointers.push((const_name.to_string(), [0u8; 32]));
+                            for referenced in expr.referenced_constants() {
+                                self.assign_free_storage_pointers(
+                                    &referenced,
+                                    macro_name,
+                                    storage_pointers,
+                                    last_p,
+                                );
+                            }
+                            storage_pointers.retain(|pointer| pointer.0.ne(const_name));
+                            return
+                        }
+                        // Resolved separately by `resolve_env_constants`
+                        ConstVal::Env(_) => return,
                     };
                     storage_pointers.push((const_name.to_string(), new_value));
                 }
@@ -367,6 +742,99 @@ impl Contract {
         }
     }
 
+    /// Returns the storage slots assigned to constants, ordered by slot
+    ///
+    /// Must be called after [`derive_storage_pointers`](Contract::derive_storage_pointers), and
+    /// errors if two constants share a storage slot.
+    pub fn storage_layout(&self) -> Result<Vec<(String, Literal)>, CodegenError> {
+        let constants = self.constants.lock().unwrap();
+        let mut slots = constants
+            .iter()
+            .filter_map(|c| match c.value {
+                ConstVal::StorageSlot(slot) => Some((slot, c)),
+                _ => None,
+            })
+            .collect::<Vec<(Literal, &ConstantDefinition)>>();
+        slots.sort_by_key(|(slot, _)| *slot);
+
+        if let Some(pair) = slots.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            let (first, second) = (pair[0].1, pair[1].1);
+            tracing::error!(target: "ast", "STORAGE SLOT COLLISION BETWEEN \"{}\" AND \"{}\"", first.name, second.name);
+            return Err(CodegenError::new(
+                CodegenErrorKind::StorageSlotCollision(first.name.clone(), second.name.clone()),
+                AstSpan(first.span.0.iter().chain(second.span.0.iter()).cloned().collect()),
+                None,
+            ))
+        }
+
+        Ok(slots.into_iter().map(|(slot, c)| (c.name.clone(), slot)).collect())
+    }
+
+    /// Returns whether a storage slot is reserved by a `STORAGE_SLOT(n)` constant
+    fn is_reserved_slot(&self, slot: &[u8; 32]) -> bool {
+        self.constants
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|c| matches!(&c.value, ConstVal::StorageSlot(reserved) if reserved == slot))
+    }
+
+    /// Resolves all `env("VAR")` constants in the AST into literals
+    ///
+    /// `lookup` returns the value of an environment variable, which must be a hex literal of at
+    /// most 32 bytes.
+    pub fn resolve_env_constants(
+        &self,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<(), CodegenError> {
+        let mut constants = self.constants.lock().map_err(|_| {
+            CodegenError::new(CodegenErrorKind::LockingError, AstSpan(vec![]), None)
+        })?;
+        for c in constants.iter_mut() {
+            if let ConstVal::Env(var) = &c.value {
+                let value = lookup(var).ok_or_else(|| {
+                    tracing::error!(target: "ast", "ENVIRONMENT VARIABLE \"{}\" NOT SET", var);
+                    CodegenError::new(
+                        CodegenErrorKind::MissingEnvironmentVariable(var.to_string()),
+                        c.span.clone(),
+                        None,
+                    )
+                })?;
+                let literal = parse_hex_literal(value.trim()).ok_or_else(|| {
+                    tracing::error!(target: "ast", "INVALID VALUE FOR ENVIRONMENT VARIABLE \"{}\"", var);
+                    CodegenError::new(
+                        CodegenErrorKind::InvalidEnvironmentVariable(var.to_string(), value.clone()),
+                        c.span.clone(),
+                        None,
+                    )
+                })?;
+                tracing::debug!(target: "ast", "RESOLVED CONSTANT \"{}\" FROM \"{}\"", c.name, var);
+                c.value = ConstVal::Literal(literal);
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluates all constant expressions in the AST into literals
+    ///
+    /// Must be called after [`derive_storage_pointers`](Contract::derive_storage_pointers) so
+    /// that any free storage pointers referenced by an expression are already set.
+    pub fn evaluate_constants(&self) -> Result<(), CodegenError> {
+        let mut constants = self.constants.lock().map_err(|_| {
+            CodegenError::new(CodegenErrorKind::LockingError, AstSpan(vec![]), None)
+        })?;
+        let snapshot = constants.clone();
+        for c in constants.iter_mut() {
+            if let ConstVal::Expression(expr) = &c.value {
+                let mut visited = vec![c.name.to_string()];
+                let value = expr.evaluate_inner(&snapshot, &c.span, &mut visited)?;
+                tracing::debug!(target: "ast", "EVALUATED CONSTANT \"{}\"", c.name);
+                c.value = ConstVal::Literal(value);
+            }
+        }
+        Ok(())
+    }
+
     /// Add override constants to the AST
     ///
     /// ## Overview
@@ -404,7 +872,7 @@ pub enum ArgumentLocation {
 }
 
 /// A function, event, or macro argument
-#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Argument {
     /// Type of the argument
     pub arg_type: Option<String>,
@@ -419,7 +887,7 @@ pub struct Argument {
 }
 
 /// A Function Signature
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct FunctionDefinition {
     /// The name of the function
     pub name: String,
@@ -461,7 +929,7 @@ impl FunctionType {
 }
 
 /// An Event Signature
-#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct EventDefinition {
     /// The name of the event
     pub name: String,
@@ -470,11 +938,12 @@ pub struct EventDefinition {
     /// The event span
     pub span: AstSpan,
     /// The event hash
+    #[serde(serialize_with = "serialize_literal")]
     pub hash: Literal,
 }
 
 /// A Table Definition
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct TableDefinition {
     /// The name of the table
     pub name: String,
@@ -483,6 +952,7 @@ pub struct TableDefinition {
     /// The table's statements
     pub statements: Vec<Statement>,
     /// Size of table
+    #[serde(serialize_with = "serialize_literal")]
     pub size: Literal,
     /// The table span
     pub span: AstSpan,
@@ -502,7 +972,7 @@ impl TableDefinition {
 }
 
 /// A Table Kind
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum TableKind {
     /// A regular jump table
     JumpTable,
@@ -525,7 +995,7 @@ impl From<TokenKind> for TableKind {
 }
 
 /// A Macro Definition
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct MacroDefinition {
     /// The Macro Name
     pub name: String,
@@ -593,29 +1063,19 @@ impl MacroDefinition {
         while let Some(statement) = statement_iter.next() {
             match &statement.ty {
                 StatementType::Literal(l) => {
-                    let push_bytes = literal_gen(evm_version, l);
                     inner_irbytes.push(IRBytes {
-                        ty: IRByteType::Bytes(Bytes(push_bytes)),
+                        ty: IRByteType::Instruction(IRInstructionType::literal(evm_version, l)),
                         span: &statement.span,
                     });
                 }
                 StatementType::Opcode(o) => {
-                    let opcode_str = o.string();
-                    inner_irbytes.push(IRBytes {
-                        ty: IRByteType::Bytes(Bytes(opcode_str)),
-                        span: &statement.span,
-                    });
                     // If the opcode is a push that takes a literal value, we need to consume the
                     // next statement, which must be a literal as checked in the parser
-                    if o.is_value_push() {
+                    let ty = if o.is_value_push() {
                         match statement_iter.next() {
                             Some(Statement { ty: StatementType::Literal(l), span: _ }) => {
                                 let hex_literal: String = bytes32_to_string(l, false);
-                                let prefixed_hex_literal = o.prefix_push_literal(&hex_literal);
-                                inner_irbytes.push(IRBytes {
-                                    ty: IRByteType::Bytes(Bytes(prefixed_hex_literal)),
-                                    span: &statement.span,
-                                });
+                                IRInstructionType::push(o.prefix_push_literal(&hex_literal))
                             }
                             _ => {
                                 // We have a push without a literal - this should be caught by the
@@ -623,11 +1083,15 @@ impl MacroDefinition {
                                 panic!("Invalid push statement");
                             }
                         }
-                    }
+                    } else {
+                        IRInstructionType::Opcode(*o)
+                    };
+                    inner_irbytes
+                        .push(IRBytes { ty: IRByteType::Instruction(ty), span: &statement.span });
                 }
                 StatementType::Code(c) => {
                     inner_irbytes.push(IRBytes {
-                        ty: IRByteType::Bytes(Bytes(c.to_owned())),
+                        ty: IRByteType::Instruction(IRInstructionType::Raw(c.to_owned())),
                         span: &statement.span,
                     });
                 }
@@ -686,6 +1150,13 @@ impl MacroDefinition {
                         span: &statement.span,
                     });
                 }
+                StatementType::Repeat(_) => {
+                    /* Repeats are unrolled by codegen before translation to IRBytes */
+                    inner_irbytes.push(IRBytes {
+                        ty: IRByteType::Statement(statement.clone()),
+                        span: &statement.span,
+                    });
+                }
             }
         }
 
@@ -694,7 +1165,7 @@ impl MacroDefinition {
 }
 
 /// A Macro Invocation
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct MacroInvocation {
     /// The Macro Name
     pub macro_name: String,
@@ -705,31 +1176,275 @@ pub struct MacroInvocation {
 }
 
 /// An argument passed when invoking a maco
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum MacroArg {
     /// Macro Literal Argument
-    Literal(Literal),
+    Literal(#[serde(serialize_with = "serialize_literal")] Literal),
     /// Macro Iden String Argument
     Ident(String),
+    /// An Opcode, substituted as-is wherever the argument is used
+    Opcode(Opcode),
     /// An Arg Call
     ArgCall(String),
+    /// A nested Macro Invocation, expanded wherever the argument is used
+    MacroCall(MacroInvocation),
 }
 
 /// Free Storage Pointer Unit Struct
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct FreeStoragePointer;
 
 /// A Constant Value
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ConstVal {
     /// A literal value for the constant
-    Literal(Literal),
+    Literal(#[serde(serialize_with = "serialize_literal")] Literal),
     /// A Free Storage Pointer
     FreeStoragePointer(FreeStoragePointer),
+    /// A storage slot, either reserved with `STORAGE_SLOT(n)` or derived from a free storage
+    /// pointer. Free storage pointers skip reserved slots.
+    StorageSlot(#[serde(serialize_with = "serialize_literal")] Literal),
+    /// A compile-time expression, eg. `add(0x20, [OTHER_CONST])`
+    Expression(ConstExpression),
+    /// An environment variable resolved at compile time, eg. `env("DEPLOYER_ADDR")`
+    Env(String),
+}
+
+/// A Constant Expression
+///
+/// Evaluated at compile time with EVM semantics: arithmetic wraps at 2^256 and
+/// division or modulo by zero yields zero.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConstExpression {
+    /// A literal value
+    Literal(#[serde(serialize_with = "serialize_literal")] Literal),
+    /// A reference to another constant, eg. `[OTHER_CONST]`
+    Constant(String),
+    /// An operation applied to its arguments, eg. `add(0x20, 0x40)`
+    Operation(ConstOperator, Vec<ConstExpression>),
+}
+
+impl ConstExpression {
+    /// Evaluates the expression into a literal, resolving constant references against
+    /// `constants`.
+    pub fn evaluate(
+        &self,
+        constants: &[ConstantDefinition],
+        span: &AstSpan,
+    ) -> Result<Literal, CodegenError> {
+        self.evaluate_inner(constants, span, &mut vec![])
+    }
+
+    fn evaluate_inner(
+        &self,
+        constants: &[ConstantDefinition],
+        span: &AstSpan,
+        visited: &mut Vec<String>,
+    ) -> Result<Literal, CodegenError> {
+        match self {
+            ConstExpression::Literal(l) => Ok(*l),
+            ConstExpression::Constant(name) => {
+                let constant = constants.iter().find(|c| c.name.eq(name)).ok_or_else(|| {
+                    tracing::error!(target: "ast", "MISSING CONSTANT DEFINITION \"{}\"", name);
+                    CodegenError {
+                        hint: did_you_mean(name, constants.iter().map(|c| c.name.as_str())),
+                        ..CodegenError::new(
+                            CodegenErrorKind::MissingConstantDefinition(name.to_string()),
+                            span.clone(),
+                            None,
+                        )
+                    }
+                })?;
+                match &constant.value {
+                    ConstVal::Literal(l) | ConstVal::StorageSlot(l) => Ok(*l),
+                    ConstVal::FreeStoragePointer(_) => Err(CodegenError::new(
+                        CodegenErrorKind::StoragePointersNotDerived,
+                        constant.span.clone(),
+                        None,
+                    )),
+                    ConstVal::Env(var) => Err(CodegenError::new(
+                        CodegenErrorKind::MissingEnvironmentVariable(var.to_string()),
+                        constant.span.clone(),
+                        None,
+                    )),
+                    ConstVal::Expression(expr) => {
+                        if visited.contains(name) {
+                            tracing::error!(target: "ast", "CIRCULAR CONSTANT REFERENCE \"{}\"", name);
+                            return Err(CodegenError::new(
+                                CodegenErrorKind::CircularConstantReference(name.to_string()),
+                                constant.span.clone(),
+                                None,
+                            ))
+                        }
+                        visited.push(name.to_string());
+                        let res = expr.evaluate_inner(constants, &constant.span, visited);
+                        visited.pop();
+                        res
+                    }
+                }
+            }
+            ConstExpression::Operation(op, args) => {
+                let args = args
+                    .iter()
+                    .map(|a| {
+                        a.evaluate_inner(constants, span, visited)
+                            .map(|l| U256::from_big_endian(&l))
+                    })
+                    .collect::<Result<Vec<U256>, CodegenError>>()?;
+                let res = match (op, args.as_slice()) {
+                    (ConstOperator::Add, [a, b]) => a.overflowing_add(*b).0,
+                    (ConstOperator::Sub, [a, b]) => a.overflowing_sub(*b).0,
+                    (ConstOperator::Mul, [a, b]) => a.overflowing_mul(*b).0,
+                    (ConstOperator::Div, [a, b]) => a.checked_div(*b).unwrap_or_default(),
+                    (ConstOperator::Mod, [a, b]) => a.checked_rem(*b).unwrap_or_default(),
+                    (ConstOperator::Exp, [a, b]) => a.overflowing_pow(*b).0,
+                    (ConstOperator::Shl, [shift, value]) => {
+                        if *shift >= U256::from(256) {
+                            U256::zero()
+                        } else {
+                            *value << shift.as_usize()
+                        }
+                    }
+                    (ConstOperator::Shr, [shift, value]) => {
+                        if *shift >= U256::from(256) {
+                            U256::zero()
+                        } else {
+                            *value >> shift.as_usize()
+                        }
+                    }
+                    (ConstOperator::And, [a, b]) => *a & *b,
+                    (ConstOperator::Or, [a, b]) => *a | *b,
+                    (ConstOperator::Xor, [a, b]) => *a ^ *b,
+                    (ConstOperator::Not, [a]) => !*a,
+                    _ => {
+                        return Err(CodegenError::new(
+                            CodegenErrorKind::InvalidArguments(format!(
+                                "\"{op}\" expects {} argument(s), got {}",
+                                op.arity(),
+                                args.len()
+                            )),
+                            span.clone(),
+                            None,
+                        ))
+                    }
+                };
+                let mut literal = [0u8; 32];
+                res.to_big_endian(&mut literal);
+                Ok(literal)
+            }
+        }
+    }
+
+    /// Renames the constants referenced by the expression
+    pub fn rename_constants(&mut self, renames: &BTreeMap<String, String>) {
+        match self {
+            ConstExpression::Literal(_) => {}
+            ConstExpression::Constant(name) => {
+                if let Some(renamed) = renames.get(name) {
+                    *name = renamed.clone();
+                }
+            }
+            ConstExpression::Operation(_, args) => {
+                args.iter_mut().for_each(|a| a.rename_constants(renames))
+            }
+        }
+    }
+
+    /// Returns the names of all constants referenced by the expression
+    pub fn referenced_constants(&self) -> Vec<String> {
+        match self {
+            ConstExpression::Literal(_) => vec![],
+            ConstExpression::Constant(name) => vec![name.to_string()],
+            ConstExpression::Operation(_, args) => {
+                args.iter().flat_map(|a| a.referenced_constants()).collect()
+            }
+        }
+    }
+}
+
+/// An operator available in constant expressions
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConstOperator {
+    /// Addition
+    Add,
+    /// Subtraction
+    Sub,
+    /// Multiplication
+    Mul,
+    /// Division
+    Div,
+    /// Modulo
+    Mod,
+    /// Exponentiation
+    Exp,
+    /// Left shift, `shl(shift, value)`
+    Shl,
+    /// Right shift, `shr(shift, value)`
+    Shr,
+    /// Bitwise and
+    And,
+    /// Bitwise or
+    Or,
+    /// Bitwise xor
+    Xor,
+    /// Bitwise not
+    Not,
+}
+
+impl ConstOperator {
+    /// The number of arguments the operator takes
+    pub fn arity(&self) -> usize {
+        match self {
+            ConstOperator::Not => 1,
+            _ => 2,
+        }
+    }
+}
+
+impl Display for ConstOperator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let op = match self {
+            ConstOperator::Add => "add",
+            ConstOperator::Sub => "sub",
+            ConstOperator::Mul => "mul",
+            ConstOperator::Div => "div",
+            ConstOperator::Mod => "mod",
+            ConstOperator::Exp => "exp",
+            ConstOperator::Shl => "shl",
+            ConstOperator::Shr => "shr",
+            ConstOperator::And => "and",
+            ConstOperator::Or => "or",
+            ConstOperator::Xor => "xor",
+            ConstOperator::Not => "not",
+        };
+        write!(f, "{op}")
+    }
+}
+
+impl TryFrom<&String> for ConstOperator {
+    type Error = ();
+
+    fn try_from(value: &String) -> Result<Self, <ConstOperator as TryFrom<&String>>::Error> {
+        match value.as_str() {
+            "add" => Ok(ConstOperator::Add),
+            "sub" => Ok(ConstOperator::Sub),
+            "mul" => Ok(ConstOperator::Mul),
+            "div" => Ok(ConstOperator::Div),
+            "mod" => Ok(ConstOperator::Mod),
+            "exp" => Ok(ConstOperator::Exp),
+            "shl" => Ok(ConstOperator::Shl),
+            "shr" => Ok(ConstOperator::Shr),
+            "and" => Ok(ConstOperator::And),
+            "or" => Ok(ConstOperator::Or),
+            "xor" => Ok(ConstOperator::Xor),
+            "not" => Ok(ConstOperator::Not),
+            _ => Err(()),
+        }
+    }
 }
 
 /// A Constant Definition
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ConstantDefinition {
     /// The Constant name
     pub name: String,
@@ -739,8 +1454,196 @@ pub struct ConstantDefinition {
     pub span: AstSpan,
 }
 
+impl ConstantDefinition {
+    /// Parses a constant override of the form `NAME=0x..`, as passed to `huffc -c`
+    ///
+    /// The name can be qualified by the namespace of an import, e.g. `Math.FEE`, and the value
+    /// must be a hex literal of at most 32 bytes.
+    pub fn parse_override(arg: &str) -> Result<(&str, Literal), String> {
+        let (name, value) =
+            arg.split_once('=').ok_or(format!("Expected NAME=0x.., found \"{arg}\""))?;
+
+        let valid_name = name.split('.').all(|part| {
+            part.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') &&
+                part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+        if !valid_name {
+            return Err(format!("Invalid constant name \"{name}\""))
+        }
+
+        let literal = parse_hex_literal(value).ok_or(format!(
+            "Invalid value \"{value}\" for constant \"{name}\", expected a hex literal of at most 32 bytes"
+        ))?;
+
+        Ok((name, literal))
+    }
+}
+
+/// A comment or whitespace, skipped by the parser
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Trivia {
+    /// The kind of trivia
+    pub kind: TriviaKind,
+    /// The Span of the trivia
+    pub span: Span,
+}
+
+/// The kind of trivia
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TriviaKind {
+    /// Whitespace, whose text is the spanned source
+    Whitespace,
+    /// A line or block comment, including its delimiters
+    Comment(String),
+}
+
+/// A side-table of the trivia of a source, keyed by the start of the token following it
+///
+/// Trivia at the end of a source is keyed by the end of the source.
+#[derive(Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct TriviaTable(pub BTreeMap<usize, Vec<Trivia>>);
+
+impl TriviaTable {
+    /// The trivia preceding a node, eg. the doc comments of a definition
+    pub fn leading(&self, span: &AstSpan) -> &[Trivia] {
+        span.0.first().and_then(|s| self.0.get(&s.start)).map_or(&[], Vec::as_slice)
+    }
+
+    /// The trivia within a node, eg. the comments of a macro body
+    pub fn within<'a>(&'a self, span: &AstSpan) -> impl Iterator<Item = &'a Trivia> + 'a {
+        let range = match (span.0.first(), span.0.last()) {
+            (Some(first), Some(last)) if first.start < last.start => {
+                (first.start + 1)..(last.start + 1)
+            }
+            _ => 0..0,
+        };
+        self.0.range(range).flat_map(|(_, trivia)| trivia)
+    }
+
+    /// Every comment of the source, in order
+    pub fn comments(&self) -> impl Iterator<Item = (&str, &Span)> {
+        self.0.values().flatten().filter_map(|t| match &t.kind {
+            TriviaKind::Comment(c) => Some((c.as_str(), &t.span)),
+            TriviaKind::Whitespace => None,
+        })
+    }
+}
+
+/// A top-level item that failed to parse, kept as an error node of a partial AST
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct InvalidDefinition {
+    /// The keyword of the definition, eg. `TokenKind::Macro`, if it got that far
+    pub kind: Option<TokenKind>,
+    /// The name of the definition, if it got that far
+    pub name: Option<String>,
+    /// The error the definition failed to parse with
+    pub error: ParserError,
+    /// The Span of the tokens skipped over
+    pub span: AstSpan,
+}
+
+/// A compile-time assertion, eg. `#assert __codesize(MAIN) < 0x6000`
+///
+/// Compilation fails if the comparison of its operands doesn't hold.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AssertionDefinition {
+    /// The left hand side of the comparison
+    pub lhs: AssertionOperand,
+    /// The comparison operator
+    pub op: ComparisonOperator,
+    /// The right hand side of the comparison
+    pub rhs: AssertionOperand,
+    /// The Span of the Assertion
+    pub span: AstSpan,
+}
+
+/// An operand of a compile-time assertion
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AssertionOperand {
+    /// A constant expression, eg. `[SLOT_A]` or `add([SLOT_A], 0x01)`
+    Expression(ConstExpression),
+    /// The size of a macro's bytecode, eg. `__codesize(MAIN)`
+    Codesize(String),
+}
+
+/// A comparison of two words
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ComparisonOperator {
+    /// Equal, `==`
+    Eq,
+    /// Not equal, `!=`
+    Ne,
+    /// Less than, `<`
+    Lt,
+    /// Less than or equal, `<=`
+    Le,
+    /// Greater than, `>`
+    Gt,
+    /// Greater than or equal, `>=`
+    Ge,
+}
+
+impl ComparisonOperator {
+    /// Compares two words
+    pub fn compare(&self, lhs: U256, rhs: U256) -> bool {
+        match self {
+            ComparisonOperator::Eq => lhs == rhs,
+            ComparisonOperator::Ne => lhs != rhs,
+            ComparisonOperator::Lt => lhs < rhs,
+            ComparisonOperator::Le => lhs <= rhs,
+            ComparisonOperator::Gt => lhs > rhs,
+            ComparisonOperator::Ge => lhs >= rhs,
+        }
+    }
+}
+
+impl Display for ComparisonOperator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let op = match self {
+            ComparisonOperator::Eq => "==",
+            ComparisonOperator::Ne => "!=",
+            ComparisonOperator::Lt => "<",
+            ComparisonOperator::Le => "<=",
+            ComparisonOperator::Gt => ">",
+            ComparisonOperator::Ge => ">=",
+        };
+        write!(f, "{op}")
+    }
+}
+
+impl TryFrom<&TokenKind> for ComparisonOperator {
+    type Error = ();
+
+    fn try_from(
+        kind: &TokenKind,
+    ) -> Result<Self, <ComparisonOperator as TryFrom<&TokenKind>>::Error> {
+        match kind {
+            TokenKind::Equal => Ok(ComparisonOperator::Eq),
+            TokenKind::NotEqual => Ok(ComparisonOperator::Ne),
+            TokenKind::LeftAngle => Ok(ComparisonOperator::Lt),
+            TokenKind::LessEqual => Ok(ComparisonOperator::Le),
+            TokenKind::RightAngle => Ok(ComparisonOperator::Gt),
+            TokenKind::GreaterEqual => Ok(ComparisonOperator::Ge),
+            _ => Err(()),
+        }
+    }
+}
+
+/// An Immutable Definition
+///
+/// Immutables are pushed as 32 byte placeholders in the runtime code, which the bootstrap code
+/// fills at deployment. The n-th immutable is filled with the first word of the n-th ABI
+/// encoded constructor argument, which must be statically sized.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ImmutableDefinition {
+    /// The Immutable name
+    pub name: String,
+    /// The Span of the Immutable Definition
+    pub span: AstSpan,
+}
+
 /// An Error Definition
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ErrorDefinition {
     /// The Error name
     pub name: String,
@@ -753,7 +1656,7 @@ pub struct ErrorDefinition {
 }
 
 /// A Jump Destination
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Label {
     /// The JumpDest Name
     pub name: String,
@@ -763,8 +1666,30 @@ pub struct Label {
     pub span: AstSpan,
 }
 
+/// The maximum number of `__REPEAT` iterations, the EIP-170 contract size limit in bytes
+pub const MAX_REPEAT_COUNT: usize = 0x6000;
+
+/// The default maximum depth of nested macro invocations
+pub const MAX_EXPANSION_DEPTH: usize = 256;
+
+/// A Compile-Time Loop, eg. `__REPEAT(0x03, i) { <i> mstore }`
+///
+/// The body is unrolled `count` times during code generation, with arg calls to the index
+/// variable replaced by the index of the current iteration.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RepeatBlock {
+    /// The number of iterations
+    pub count: ConstExpression,
+    /// The name of the index variable, if any
+    pub index: Option<String>,
+    /// Statements Inside The Loop Body
+    pub statements: Vec<Statement>,
+    /// The repeat span
+    pub span: AstSpan,
+}
+
 /// A Builtin Function Call
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct BuiltinFunctionCall {
     /// The Builtin Kind
     pub kind: BuiltinFunctionKind,
@@ -777,7 +1702,7 @@ pub struct BuiltinFunctionCall {
 }
 
 /// A Builtin Function Kind
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum BuiltinFunctionKind {
     /// Table size function
     Tablesize,
@@ -793,10 +1718,18 @@ pub enum BuiltinFunctionKind {
     Error,
     /// Rightpad function
     RightPad,
+    /// Leftpad function
+    LeftPad,
     /// Dynamic constructor arg function
     DynConstructorArg,
     /// Inject Raw Bytes
     Verbatim,
+    /// Compile-time keccak256 hash
+    Keccak,
+    /// Push a short string
+    Bytes,
+    /// A custom builtin registered by the embedder of the compiler
+    Custom(String),
 }
 
 impl From<String> for BuiltinFunctionKind {
@@ -809,11 +1742,14 @@ impl From<String> for BuiltinFunctionKind {
             "__EVENT_HASH" => BuiltinFunctionKind::EventHash,
             "__ERROR" => BuiltinFunctionKind::Error,
             "__RIGHTPAD" => BuiltinFunctionKind::RightPad,
+            "__LEFTPAD" => BuiltinFunctionKind::LeftPad,
             "__CODECOPY_DYN_ARG" => BuiltinFunctionKind::DynConstructorArg,
             "__VERBATIM" => BuiltinFunctionKind::Verbatim,
-            _ => panic!("Invalid Builtin Function Kind"), /* This should never be reached,
-                                                           * builtins are validated with a
-                                                           * `try_from` call in the lexer. */
+            "__keccak" => BuiltinFunctionKind::Keccak,
+            "__BYTES" => BuiltinFunctionKind::Bytes,
+            // Builtins of the language are validated with a `try_from` call in the lexer, any
+            // other builtin was registered
+            _ => BuiltinFunctionKind::Custom(value),
         }
     }
 }
@@ -830,15 +1766,18 @@ impl TryFrom<&String> for BuiltinFunctionKind {
             "__EVENT_HASH" => Ok(BuiltinFunctionKind::EventHash),
             "__ERROR" => Ok(BuiltinFunctionKind::Error),
             "__RIGHTPAD" => Ok(BuiltinFunctionKind::RightPad),
+            "__LEFTPAD" => Ok(BuiltinFunctionKind::LeftPad),
             "__CODECOPY_DYN_ARG" => Ok(BuiltinFunctionKind::DynConstructorArg),
             "__VERBATIM" => Ok(BuiltinFunctionKind::Verbatim),
+            "__keccak" => Ok(BuiltinFunctionKind::Keccak),
+            "__BYTES" => Ok(BuiltinFunctionKind::Bytes),
             _ => Err(()),
         }
     }
 }
 
 /// A Statement
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Statement {
     /// The type of statement
     pub ty: StatementType,
@@ -847,10 +1786,10 @@ pub struct Statement {
 }
 
 /// The Statement Type
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum StatementType {
     /// A Literal Statement
-    Literal(Literal),
+    Literal(#[serde(serialize_with = "serialize_literal")] Literal),
     /// An Opcode Statement
     Opcode(Opcode),
     /// A Code Statement
@@ -867,6 +1806,8 @@ pub enum StatementType {
     LabelCall(String),
     /// A built-in function call
     BuiltinFunctionCall(BuiltinFunctionCall),
+    /// A compile-time loop
+    Repeat(RepeatBlock),
 }
 
 impl Display for StatementType {
@@ -885,6 +1826,7 @@ impl Display for StatementType {
             StatementType::BuiltinFunctionCall(b) => {
                 write!(f, "BUILTIN FUNCTION CALL: {:?}", b.kind)
             }
+            StatementType::Repeat(r) => write!(f, "REPEAT: {} STATEMENTS", r.statements.len()),
         }
     }
 }
@@ -894,19 +1836,25 @@ impl Display for StatementType {
 /// At the moment, the decorator tag can only be placed over test definitions. Developers
 /// can use decorators to define environment variables and other metadata for their individual
 /// tests.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Decorator {
     /// Vector of flags passed within the decorator
     pub flags: Vec<DecoratorFlag>,
 }
 
 /// A decorator flag
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum DecoratorFlag {
     /// Sets the calldata of the test call transaction
     Calldata(String),
     /// Sets the value of the test call transaction
-    Value(Literal),
+    Value(#[serde(serialize_with = "serialize_literal")] Literal),
+    /// Sets the number of runs for a fuzzed test
+    Fuzz(#[serde(serialize_with = "serialize_literal")] Literal),
+    /// Sets the seed used to generate the inputs of a fuzzed test
+    Seed(#[serde(serialize_with = "serialize_literal")] Literal),
+    /// Makes the labels of the macro unique to each of its invocations
+    UniqueLabels,
 }
 
 impl TryFrom<&String> for DecoratorFlag {
@@ -916,7 +1864,108 @@ impl TryFrom<&String> for DecoratorFlag {
         match value.as_str() {
             "calldata" => Ok(DecoratorFlag::Calldata(String::default())),
             "value" => Ok(DecoratorFlag::Value(Literal::default())),
+            "fuzz" => Ok(DecoratorFlag::Fuzz(Literal::default())),
+            "seed" => Ok(DecoratorFlag::Seed(Literal::default())),
+            "unique_labels" => Ok(DecoratorFlag::UniqueLabels),
             _ => Err(()),
         }
     }
 }
+
+/// Resolves the file imported by an `#include` located at `span`
+fn imported_file(
+    full_source: &FullFileSource,
+    remapper: &Remapper,
+    path: &FilePath,
+    span: &AstSpan,
+) -> String {
+    let start = span.0.first().map(|s| s.start).unwrap_or_default();
+    let includer = full_source.file_at(start).map(|f| f.path.clone()).unwrap_or_default();
+    normalize_path(&FileSource::resolve_import(remapper, &includer, &path.to_string_lossy()))
+}
+
+/// Whether a definition located at `span` is defined in the file at `path`
+fn defined_in(full_source: &FullFileSource, span: &AstSpan, path: &str) -> bool {
+    span.0
+        .first()
+        .and_then(|s| full_source.file_at(s.start))
+        .map_or(false, |f| normalize_path(&f.path) == path)
+}
+
+/// Renames the references to definitions in a list of statements
+fn rename_references(statements: &mut [Statement], renames: &BTreeMap<String, String>) {
+    fn rename(name: &mut String, renames: &BTreeMap<String, String>) {
+        if let Some(renamed) = renames.get(name) {
+            *name = renamed.clone();
+        }
+    }
+
+    fn rename_args(args: &mut [MacroArg], renames: &BTreeMap<String, String>) {
+        for arg in args {
+            match arg {
+                MacroArg::Ident(name) => rename(name, renames),
+                MacroArg::MacroCall(mi) => {
+                    rename(&mut mi.macro_name, renames);
+                    rename_args(&mut mi.args, renames);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for statement in statements {
+        match &mut statement.ty {
+            StatementType::MacroInvocation(mi) => {
+                rename(&mut mi.macro_name, renames);
+                rename_args(&mut mi.args, renames);
+            }
+            StatementType::Constant(name) => rename(name, renames),
+            StatementType::Label(label) => rename_references(&mut label.inner, renames),
+            StatementType::Repeat(r) => {
+                if let ConstExpression::Constant(name) = &mut r.count {
+                    rename(name, renames)
+                }
+                rename_references(&mut r.statements, renames)
+            }
+            StatementType::BuiltinFunctionCall(bf) => {
+                bf.args.iter_mut().filter_map(|a| a.name.as_mut()).for_each(|n| rename(n, renames))
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Collects the names of the definitions referenced by a list of statements
+fn referenced_names(statements: &[Statement], names: &mut Vec<String>) {
+    fn arg_names(args: &[MacroArg], names: &mut Vec<String>) {
+        for arg in args {
+            match arg {
+                MacroArg::Ident(name) => names.push(name.clone()),
+                MacroArg::MacroCall(mi) => {
+                    names.push(mi.macro_name.clone());
+                    arg_names(&mi.args, names);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for statement in statements {
+        match &statement.ty {
+            StatementType::MacroInvocation(mi) => {
+                names.push(mi.macro_name.clone());
+                arg_names(&mi.args, names);
+            }
+            StatementType::Constant(name) => names.push(name.clone()),
+            StatementType::Label(label) => referenced_names(&label.inner, names),
+            StatementType::Repeat(r) => {
+                names.extend(r.count.referenced_constants());
+                referenced_names(&r.statements, names)
+            }
+            StatementType::BuiltinFunctionCall(bf) => {
+                names.extend(bf.args.iter().filter_map(|a| a.name.clone()))
+            }
+            _ => {}
+        }
+    }
+}
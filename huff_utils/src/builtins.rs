@@ -0,0 +1,85 @@
+//! ## Custom Builtins
+//!
+//! Builtin functions registered by embedders of the compiler, alongside the builtins of the
+//! language like `__FUNC_SIG` or `__tablesize`. The lexer accepts their names in macro bodies,
+//! and codegen expands each call into the bytecode returned by its callback.
+
+use crate::prelude::{BuiltinFunctionCall, CodegenError, Contract};
+use std::{fmt, sync::Arc};
+
+/// Expands a call of a custom builtin into hex bytecode
+pub type BuiltinExpansion =
+    dyn Fn(&BuiltinFunctionCall, &Contract) -> Result<String, CodegenError> + Send + Sync;
+
+/// A custom builtin function
+#[derive(Clone)]
+pub struct CustomBuiltin {
+    /// The name the builtin is called by, e.g. `__SELECTOR_OF`
+    pub name: String,
+    /// The number of arguments the builtin takes
+    pub arity: usize,
+    /// Expands a call into the bytecode it's replaced by
+    pub expand: Arc<BuiltinExpansion>,
+}
+
+impl CustomBuiltin {
+    /// Creates a custom builtin from its name, arity and expansion callback
+    pub fn new(
+        name: impl Into<String>,
+        arity: usize,
+        expand: impl Fn(&BuiltinFunctionCall, &Contract) -> Result<String, CodegenError>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self { name: name.into(), arity, expand: Arc::new(expand) }
+    }
+}
+
+impl fmt::Debug for CustomBuiltin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CustomBuiltin")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The custom builtins registered with a compiler
+///
+/// Builtins of the language can't be overridden, as their names are lexed as such first.
+#[derive(Debug, Default, Clone)]
+pub struct CustomBuiltins {
+    /// The registered builtins
+    builtins: Vec<CustomBuiltin>,
+}
+
+impl CustomBuiltins {
+    /// Creates an empty set of custom builtins
+    pub fn new() -> Self {
+        Self { builtins: vec![] }
+    }
+
+    /// Registers a builtin, replacing any builtin registered with the same name
+    pub fn register(&mut self, builtin: CustomBuiltin) {
+        match self.builtins.iter_mut().find(|b| b.name == builtin.name) {
+            Some(registered) => *registered = builtin,
+            None => self.builtins.push(builtin),
+        }
+    }
+
+    /// Gets a registered builtin by its name
+    pub fn get(&self, name: &str) -> Option<&CustomBuiltin> {
+        self.builtins.iter().find(|b| b.name == name)
+    }
+
+    /// The names of the registered builtins
+    pub fn names(&self) -> Vec<String> {
+        self.builtins.iter().map(|b| b.name.clone()).collect()
+    }
+
+    /// Whether no builtins are registered
+    pub fn is_empty(&self) -> bool {
+        self.builtins.is_empty()
+    }
+}
@@ -4,7 +4,8 @@
 
 use crate::{
     evm_version::EVMVersion,
-    prelude::{AstSpan, Statement, TableDefinition},
+    ir::{IRInstruction, IRInstructionType},
+    prelude::{AstSpan, MacroArg, Span, Statement, TableDefinition},
 };
 use std::{
     collections::{BTreeMap, BTreeSet},
@@ -27,8 +28,8 @@ pub struct IRBytes<'a> {
 /// IRBytes Type
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum IRByteType {
-    /// Bytes
-    Bytes(Bytes),
+    /// An instruction generated directly from a statement
+    Instruction(IRInstructionType),
     /// Macro Statement to be expanded
     Statement(Statement),
     /// A Constant to be referenced
@@ -71,16 +72,59 @@ impl From<Vec<Bytes>> for Bytecode {
 /// [`recurse_bytecode`](../../huff_codegen/src/lib.rs#recurse_bytecode)
 #[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct BytecodeRes {
-    /// Resulting bytes
-    pub bytes: Vec<(usize, Bytes)>,
+    /// The instructions of the expanded code, at the program counters they were generated at
+    pub instructions: Vec<IRInstruction>,
+    /// The hex values of the constants pushed by the instructions
+    pub constants: BTreeMap<String, String>,
     /// Jump Indices
     pub label_indices: LabelIndices,
+    /// Jump indices of the labels defined in the macro itself, not in the macros it invokes
+    pub defined_labels: LabelIndices,
+    /// The spans of the label definitions behind the jump indices
+    pub label_definitions: LabelDefinitions,
     /// Unmatched Jumps
     pub unmatched_jumps: Jumps,
     /// Table Instances
     pub table_instances: Jumps,
     /// Utilized Tables
     pub utilized_tables: Vec<TableDefinition>,
+    /// Bytecode ranges of the macros invoked while generating the bytecode
+    pub macro_ranges: Vec<MacroRange>,
+    /// Bytecode ranges of the statements the bytecode was generated from
+    pub source_spans: Vec<BytecodeSpan>,
+    /// Program counters of the pushes of jump destinations resolved, eg. of label calls
+    pub label_references: Vec<usize>,
+}
+
+/// The bytecode range covered by an invoked macro
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MacroRange {
+    /// The name of the invoked macro
+    pub name: String,
+    /// The offset of the first byte of the macro's code
+    pub start: usize,
+    /// The offset directly after the last byte of the macro's code
+    pub end: usize,
+    /// Jump indices of the labels defined in the macro itself
+    pub labels: LabelIndices,
+}
+
+impl MacroRange {
+    /// Returns whether the given program counter falls within the range
+    pub fn contains(&self, pc: usize) -> bool {
+        self.start <= pc && pc < self.end
+    }
+}
+
+/// The bytecode range generated from a statement
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BytecodeSpan {
+    /// The offset of the first byte generated from the statement
+    pub start: usize,
+    /// The offset directly after the last byte generated from the statement
+    pub end: usize,
+    /// The span of the statement
+    pub span: AstSpan,
 }
 
 impl Display for BytecodeRes {
@@ -88,12 +132,12 @@ impl Display for BytecodeRes {
         write!(
             f,
             r#"BytecodeRes(
-            bytes: [{}],
+            instructions: [{}],
             label_indices: {:?},
             unmatched_jumps: {:?}
             table_instances: {:?}
         )"#,
-            self.bytes.iter().fold("".to_string(), |acc, b| format!("{acc}{}", b.0)),
+            self.instructions.iter().map(|i| format!("{:?}", i.ty)).collect::<Vec<_>>().join(", "),
             self.label_indices,
             self.unmatched_jumps,
             self.table_instances
@@ -110,6 +154,9 @@ pub struct Jump {
     pub bytecode_index: usize,
     /// The Jump Span
     pub span: AstSpan,
+    /// The scope depth of the macro a label passed as a macro argument was written in. Such
+    /// jumps only resolve to labels of that macro or its callers.
+    pub scope_depth: Option<usize>,
 }
 
 /// Type for a vec of `Jump`s
@@ -118,8 +165,15 @@ pub type Jumps = Vec<Jump>;
 /// Type to map `Jump` labels to their bytecode indices
 pub type LabelIndices = BTreeMap<String, usize>;
 
+/// Type to map `Jump` labels to the spans of their definitions, one per expanded definition
+pub type LabelDefinitions = BTreeMap<String, Vec<Span>>;
+
 /// Typw to map circular_codesize labels to their bytecode indices
 pub type CircularCodeSizeIndices = BTreeSet<(String, usize)>;
 
+/// Type to map the names of the macros in scope and the arguments of the invocations leading to
+/// a macro invocation to its expanded code, along with the offset it was expanded at
+pub type ExpansionCache = BTreeMap<(Vec<String>, Vec<Vec<MacroArg>>), (usize, BytecodeRes)>;
+
 /// Type for a map of bytecode indexes to `Jumps`. Represents a Jump Table.
 pub type JumpTable = BTreeMap<usize, Jumps>;
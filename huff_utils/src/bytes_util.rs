@@ -1,4 +1,5 @@
 use crate::{evm::Opcode, evm_version::EVMVersion};
+use ethers_core::types::U256;
 use std::num::ParseIntError;
 use tiny_keccak::{Hasher, Keccak};
 
@@ -21,6 +22,53 @@ pub fn str_to_bytes32(s: &str) -> [u8; 32] {
     padded
 }
 
+/// Parses a `0x` prefixed hex string of at most 32 bytes, ignoring leading zeros.
+pub fn parse_hex_literal(s: &str) -> Option<[u8; 32]> {
+    let hex = s.strip_prefix("0x")?;
+    if hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None
+    }
+    let significant = hex.trim_start_matches('0');
+    if significant.len() > 64 {
+        return None
+    }
+    Some(str_to_bytes32(if significant.is_empty() { "0" } else { significant }))
+}
+
+/// Parses a decimal integer string of at most 32 bytes, eg. `1000000`.
+pub fn parse_decimal_literal(s: &str) -> Option<[u8; 32]> {
+    if s.is_empty() || !s.chars().all(|c| c.is_ascii_digit()) {
+        return None
+    }
+    let mut literal = [0u8; 32];
+    U256::from_dec_str(s).ok()?.to_big_endian(&mut literal);
+    Some(literal)
+}
+
+/// Parses a binary string of at most 256 bits, ignoring leading zeros, eg. `1010`.
+pub fn parse_binary_literal(s: &str) -> Option<[u8; 32]> {
+    if s.is_empty() || !s.chars().all(|c| c == '0' || c == '1') {
+        return None
+    }
+    let significant = s.trim_start_matches('0');
+    if significant.len() > 256 {
+        return None
+    }
+    let value = significant
+        .chars()
+        .fold(U256::zero(), |value, c| (value << 1) | U256::from((c == '1') as u8));
+    let mut literal = [0u8; 32];
+    value.to_big_endian(&mut literal);
+    Some(literal)
+}
+
+/// Negates a literal, returning its 32 byte two's complement, eg. `0x01` becomes `0xff..ff`.
+pub fn negate_literal(l: &[u8; 32]) -> [u8; 32] {
+    let mut literal = [0u8; 32];
+    (!U256::from_big_endian(l)).overflowing_add(U256::one()).0.to_big_endian(&mut literal);
+    literal
+}
+
 /// Convert a `[u8; 32]` to a bytes string.
 pub fn bytes32_to_string(bytes: &[u8; 32], prefixed: bool) -> String {
     let mut s = String::default();
@@ -31,6 +79,19 @@ pub fn bytes32_to_string(bytes: &[u8; 32], prefixed: bool) -> String {
     format!("{}{s}", if prefixed { "0x" } else { "" })
 }
 
+/// The number of significant bytes of a literal, at least one
+pub fn literal_width(l: &[u8; 32]) -> usize {
+    32 - l.iter().position(|b| *b != 0).unwrap_or(31)
+}
+
+/// Serializes a literal as a `0x` prefixed hex string, eg. in the JSON of the AST
+pub fn serialize_literal<S: serde::Serializer>(
+    literal: &[u8; 32],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&bytes32_to_string(literal, true))
+}
+
 /// Wrapper to convert a hex string to a usize.
 pub fn hex_to_usize(s: &str) -> Result<usize, ParseIntError> {
     usize::from_str_radix(s, 16)
@@ -46,6 +107,11 @@ pub fn pad_n_bytes(hex: &str, num_bytes: usize) -> String {
     hex
 }
 
+/// The number of bytes needed to hold a value, at least one
+pub fn bytes_needed(value: usize) -> usize {
+    (usize::BITS - value.leading_zeros()).div_ceil(8).max(1) as usize
+}
+
 /// Pad odd-length byte string with a leading 0
 pub fn format_even_bytes(hex: String) -> String {
     if hex.len() % 2 == 1 {
@@ -90,3 +156,21 @@ fn format_push0(evm_version: &EVMVersion, hex_literal: String) -> String {
 pub fn format_literal(hex_literal: String) -> String {
     format!("{:02x}{hex_literal}", 95 + hex_literal.len() / 2)
 }
+
+/// Splits hex bytecode into its instructions, as tuples of the program counter, the opcode and
+/// the hex data pushed by `PUSHX` opcodes
+///
+/// The data of a push running past the end of the bytecode is truncated.
+pub fn split_instructions(bytecode: &str) -> Vec<(usize, u8, &str)> {
+    let mut instructions = vec![];
+    let mut pc = 0;
+    while pc * 2 + 2 <= bytecode.len() {
+        let Ok(opcode) = u8::from_str_radix(&bytecode[pc * 2..pc * 2 + 2], 16) else { break };
+        let data_len = if (0x60..=0x7f).contains(&opcode) { usize::from(opcode - 0x5f) } else { 0 };
+        let data = &bytecode
+            [(pc * 2 + 2).min(bytecode.len())..(pc * 2 + 2 + data_len * 2).min(bytecode.len())];
+        instructions.push((pc, opcode, data));
+        pc += 1 + data_len;
+    }
+    instructions
+}
@@ -0,0 +1,153 @@
+//! ## Decompiler
+//!
+//! Turns bytecode into a best-effort Huff skeleton for porting and auditing existing contracts:
+//! a `MAIN` macro of the disassembled instructions, with labels at the JUMPDESTs and pushed jump
+//! destinations referencing them. Selectors compared in a function dispatcher, eg.
+//! `dup1 0xa9059cbb eq 0x0042 jumpi`, are declared as `#define function` stubs whose jump
+//! destinations are named after them.
+//!
+//! Pushes keep their width, so the skeleton compiles back to the same instructions as long as the
+//! original jumps push their destinations with `PUSH2`, as Huff and solc do.
+
+use crate::{
+    disassembler::{disassemble, Instruction},
+    evm::Opcode,
+};
+use std::collections::BTreeMap;
+
+/// The name of a function stub, after its selector
+fn function_name(selector: &str) -> String {
+    format!("func_{selector}")
+}
+
+/// Splits the metadata appended by solc off the end of bytecode, if there is any
+///
+/// The metadata is a CBOR map followed by its length as two bytes.
+fn split_metadata(bytecode: &str) -> (&str, &str) {
+    let len = bytecode.len() / 2;
+    let metadata_len = (len >= 2)
+        .then(|| usize::from_str_radix(&bytecode[(len - 2) * 2..], 16).ok())
+        .flatten()
+        .filter(|metadata_len| metadata_len + 2 < len);
+    match metadata_len {
+        Some(metadata_len) => {
+            let start = (len - metadata_len - 2) * 2;
+            let cbor_map = u8::from_str_radix(&bytecode[start..start + 2], 16)
+                .map_or(false, |byte| (0xa1..=0xb7).contains(&byte));
+            if cbor_map {
+                bytecode.split_at(start)
+            } else {
+                (bytecode, "")
+            }
+        }
+        None => (bytecode, ""),
+    }
+}
+
+/// The selector compared by a dispatcher at `instructions[i]`, with the destination it jumps to
+///
+/// Matches `PUSH4 <selector> [DUPX] EQ PUSHX <destination> JUMPI`.
+fn dispatch(instructions: &[Instruction], i: usize) -> Option<(String, usize)> {
+    let selector = instructions.get(i).filter(|p| p.opcode == Some(Opcode::Push4))?;
+    let mut j = i + 1;
+    let byte = instructions.get(j).and_then(|d| d.opcode).map(|o| o.string());
+    // Dup1 - Dup16
+    if byte.map_or(false, |b| b.starts_with('8')) {
+        j += 1;
+    }
+    let eq = instructions.get(j)?;
+    let destination = instructions.get(j + 1)?;
+    let jumpi = instructions.get(j + 2)?;
+    if eq.opcode != Some(Opcode::Eq) || jumpi.opcode != Some(Opcode::Jumpi) {
+        return None;
+    }
+    Some((selector.data.clone(), destination_of(instructions, destination)?))
+}
+
+/// The JUMPDEST pushed by an instruction, if its data is the program counter of one
+fn destination_of(instructions: &[Instruction], push: &Instruction) -> Option<usize> {
+    if !push.opcode.map_or(false, |o| o.is_value_push()) {
+        return None;
+    }
+    usize::from_str_radix(&push.data, 16)
+        .ok()
+        .filter(|pc| instructions.iter().any(|d| d.pc == *pc && d.opcode == Some(Opcode::Jumpdest)))
+}
+
+/// Decompiles hex bytecode, with or without a `0x` prefix, into the source of a Huff contract
+///
+/// The code ends at the first unknown opcode or truncated push. The remaining bytes, eg. data
+/// or metadata, are kept in a `DATA` code table for reference.
+pub fn decompile(bytecode: &str) -> String {
+    let bytecode = bytecode.trim();
+    let bytecode = bytecode.strip_prefix("0x").unwrap_or(bytecode);
+    let (code, metadata) = split_metadata(bytecode);
+
+    let mut instructions = disassemble(code);
+    let end = instructions.iter().position(|i| i.opcode.is_none() || i.is_truncated());
+    let data = match end {
+        Some(end) => {
+            let data = format!("{}{metadata}", &code[instructions[end].pc * 2..]);
+            instructions.truncate(end);
+            data
+        }
+        None => metadata.to_string(),
+    };
+
+    // Name the JUMPDESTs, after the selectors dispatched to them where there are any
+    let mut labels = instructions
+        .iter()
+        .filter(|i| i.opcode == Some(Opcode::Jumpdest))
+        .map(|i| (i.pc, format!("label_{:04x}", i.pc)))
+        .collect::<BTreeMap<usize, String>>();
+    let mut selectors = vec![];
+    for i in 0..instructions.len() {
+        if let Some((selector, destination)) = dispatch(&instructions, i) {
+            if !selectors.contains(&selector) {
+                labels.insert(destination, format!("{}_dest", function_name(&selector)));
+                selectors.push(selector);
+            }
+        }
+    }
+
+    let mut source = format!("// Decompiled from {} bytes of bytecode\n\n", bytecode.len() / 2);
+    if !selectors.is_empty() {
+        source
+            .push_str("// Function stubs of the dispatched selectors, without their signatures\n");
+        for selector in &selectors {
+            source.push_str(&format!(
+                "#define function {}() nonpayable returns ()\n",
+                function_name(selector)
+            ));
+        }
+        source.push('\n');
+    }
+
+    source.push_str("#define macro MAIN() = takes (0) returns (0) {\n");
+    let mut labelled = false;
+    for (i, instruction) in instructions.iter().enumerate() {
+        if let Some(label) = labels.get(&instruction.pc) {
+            source.push_str(&format!("    {label}:\n"));
+            labelled = true;
+            continue
+        }
+        let jumps = instructions
+            .get(i + 1)
+            .map_or(false, |next| matches!(next.opcode, Some(Opcode::Jump | Opcode::Jumpi)));
+        let destination = destination_of(&instructions, instruction).filter(|_| jumps);
+        let line = match destination.and_then(|pc| labels.get(&pc)) {
+            Some(label) => label.clone(),
+            None if instruction.data.is_empty() => instruction.mnemonic.clone(),
+            None => format!("{} 0x{}", instruction.mnemonic, instruction.data),
+        };
+        let indent = if labelled { "        " } else { "    " };
+        source.push_str(&format!("{indent}{line}\n"));
+    }
+    source.push_str("}\n");
+
+    if !data.is_empty() {
+        source.push_str("\n// The bytes following the code, eg. data or metadata\n");
+        source.push_str(&format!("#define table DATA {{\n    0x{data}\n}}\n"));
+    }
+    source
+}
@@ -0,0 +1,453 @@
+//! ## Diagnostics
+//!
+//! Structured errors and warnings, with the lines and columns of their spans, for tools that
+//! can't scrape the human-readable output: editors and wrappers reading JSON diagnostics, and
+//! code-scanning UIs consuming [SARIF](https://sarifweb.azurewebsites.net/) reports.
+//!
+//! The human-readable output renders them as code frames, underlining each span with its label:
+//!
+//! ```text
+//! error[H0123]: Duplicate Definition: "HELPER"
+//!  --> contracts/Main.huff:1:15
+//!   |
+//! 1 | #define macro HELPER() = takes(0) returns(0) {}
+//!   |               ^^^^^^ first defined here
+//! 2 | #define macro HELPER() = takes(0) returns(0) {}
+//!   |               ------ defined again here
+//!   |
+//!   = note: run `huffc explain H0123` for an extended explanation
+//! ```
+
+use crate::{
+    error::{CodegenErrorKind, CompilerError, CompilerWarning, ParserErrorKind, WarningKind},
+    error_codes::explain,
+    files::{FileSource, Span},
+};
+use codemap::CodeMap;
+use codemap_diagnostic::{ColorConfig, Emitter, Level, SpanLabel, SpanStyle};
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, sync::Arc};
+
+/// The version of the SARIF format
+pub const SARIF_VERSION: &str = "2.1.0";
+
+/// The schema of SARIF reports
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// The level of a diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticLevel {
+    /// The diagnostic fails the compilation
+    Error,
+    /// The diagnostic doesn't fail the compilation
+    Warning,
+}
+
+impl DiagnosticLevel {
+    /// The name of the level, as used by SARIF and JSON diagnostics
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagnosticLevel::Error => "error",
+            DiagnosticLevel::Warning => "warning",
+        }
+    }
+}
+
+/// A one-based line and column in a source file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    /// The line, starting at 1
+    pub line: usize,
+    /// The column in characters, starting at 1
+    pub column: usize,
+}
+
+impl Position {
+    /// Gets the position of an offset in a source
+    pub fn from_offset(source: &str, offset: usize) -> Self {
+        let offset = offset.min(source.len());
+        let before = source.get(..offset).unwrap_or(source);
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+/// A span of a diagnostic, located in its file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagnosticSpan {
+    /// The path of the file
+    pub file: String,
+    /// The offset of the first character
+    pub start: usize,
+    /// The offset of the last character
+    pub end: usize,
+    /// The position of the first character, if the file's source is known
+    pub start_position: Option<Position>,
+    /// The position of the last character, if the file's source is known
+    pub end_position: Option<Position>,
+    /// What the span points at, e.g. `first defined here`
+    pub label: Option<String>,
+    /// The file, to render the span's source
+    pub source: Option<Arc<FileSource>>,
+}
+
+impl DiagnosticSpan {
+    /// Locates a span in its file, `None` if it has no file
+    pub fn new(span: &Span) -> Option<Self> {
+        let file = span.file.as_ref()?;
+        let source = file.source.as_deref().filter(|_| span.range().is_some());
+        Some(Self {
+            file: file.path.clone(),
+            start: span.start,
+            end: span.end,
+            start_position: source.map(|s| Position::from_offset(s, span.start)),
+            end_position: source.map(|s| Position::from_offset(s, span.end)),
+            label: None,
+            source: Some(Arc::clone(file)),
+        })
+    }
+
+    /// Converts the span to JSON, with its lines and columns if they're known
+    pub fn to_json(&self) -> Value {
+        json!({
+            "file": self.file,
+            "start": self.start,
+            "end": self.end,
+            "line_start": self.start_position.map(|p| p.line),
+            "column_start": self.start_position.map(|p| p.column),
+            "line_end": self.end_position.map(|p| p.line),
+            "column_end": self.end_position.map(|p| p.column),
+            "label": self.label,
+        })
+    }
+}
+
+/// A structured error or warning
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Whether the diagnostic fails the compilation
+    pub level: DiagnosticLevel,
+    /// The code of an error, or the id of the lint rule that raised a warning
+    pub code: Option<String>,
+    /// The message, without the rendered source
+    pub message: String,
+    /// A hint on how to fix the diagnostic
+    pub hint: Option<String>,
+    /// Further details that don't point at the source
+    pub notes: Vec<String>,
+    /// The spans of the diagnostic, the primary span first
+    pub spans: Vec<DiagnosticSpan>,
+}
+
+impl Diagnostic {
+    /// Converts a warning into a diagnostic
+    pub fn from_warning(warning: &CompilerWarning) -> Self {
+        let labels = match &warning.kind {
+            WarningKind::EventTopicCollision(..) => {
+                (Some("first defined here"), Some("defined again with the same topic"))
+            }
+            WarningKind::ShadowedDefinition(..) => {
+                (Some("used instead"), Some("shadowed definition"))
+            }
+            _ => (None, None),
+        };
+        let notes = match &warning.kind {
+            WarningKind::CodeSizeExceeded(.., largest) => largest
+                .iter()
+                .map(|(name, size)| format!("macro \"{name}\" expands to {size} bytes"))
+                .collect(),
+            _ => vec![],
+        };
+        Self {
+            level: DiagnosticLevel::Warning,
+            code: warning.lint.map(String::from),
+            message: warning.kind.to_string(),
+            hint: None,
+            notes,
+            spans: label_spans(merge_spans(warning.span.0.iter()), labels),
+        }
+    }
+
+    /// Converts an error into diagnostics, one for each failed compilation
+    pub fn from_error(error: &CompilerError) -> Vec<Self> {
+        let (hint, spans) = match error {
+            CompilerError::FailedCompiles(errors) => {
+                return errors.iter().flat_map(Self::from_error).collect()
+            }
+            CompilerError::Warning(warning) => {
+                return vec![Self { level: DiagnosticLevel::Error, ..Self::from_warning(warning) }]
+            }
+            CompilerError::LexicalError(le) => (None, vec![&le.span]),
+            CompilerError::ParserError(pe) => (pe.hint.clone(), pe.spans.0.iter().collect()),
+            CompilerError::CodegenError(ce) => (ce.hint.clone(), ce.span.0.iter().collect()),
+            CompilerError::CircularImport(_, spans) => (None, spans.0.iter().collect()),
+            _ => (None, vec![]),
+        };
+        let labels = match error {
+            CompilerError::ParserError(pe) => match pe.kind {
+                ParserErrorKind::DuplicateDefinition(_) => {
+                    (Some("first defined here"), Some("defined again here"))
+                }
+                _ => (None, None),
+            },
+            CompilerError::CodegenError(ce) => match ce.kind {
+                CodegenErrorKind::DuplicateLabel(_) => {
+                    (Some("first defined here"), Some("defined again here"))
+                }
+                CodegenErrorKind::StorageSlotCollision(..) => {
+                    (Some("slot assigned here"), Some("same slot assigned here"))
+                }
+                CodegenErrorKind::RecursiveMacro(_) => (Some("invoked recursively here"), None),
+                CodegenErrorKind::InvalidMacroInvocation(_) => (Some("not defined"), None),
+                _ => (None, None),
+            },
+            CompilerError::CircularImport(..) => (Some("included here"), Some("included here")),
+            _ => (None, None),
+        };
+        let spans = merge_spans(spans.into_iter());
+        // Without spans, the lines below the heading hold the details of the error
+        let notes = match spans.is_empty() {
+            true => error_details(error).into_iter().filter(|d| Some(d) != hint.as_ref()).collect(),
+            false => vec![],
+        };
+        vec![Self {
+            level: DiagnosticLevel::Error,
+            code: error.code().map(String::from),
+            message: error_message(error),
+            hint,
+            notes,
+            spans: label_spans(spans, labels),
+        }]
+    }
+
+    /// Converts the diagnostic to JSON, with the file of its primary span and the other spans
+    /// as related spans
+    ///
+    /// ```json
+    /// {
+    ///   "severity": "warning",
+    ///   "code": "unused-macros",
+    ///   "message": "Unused Macro: \"HELPER\"",
+    ///   "hint": null,
+    ///   "notes": [],
+    ///   "file": "src/Token.huff",
+    ///   "span": { "file": "src/Token.huff", "start": 0, "end": 6, "line_start": 1, ... },
+    ///   "related_spans": []
+    /// }
+    /// ```
+    pub fn to_json(&self) -> Value {
+        let primary = self.spans.first();
+        json!({
+            "severity": self.level.as_str(),
+            "code": self.code,
+            "message": self.message,
+            "hint": self.hint,
+            "notes": self.notes,
+            "file": primary.map(|s| s.file.clone()),
+            "span": primary.map(DiagnosticSpan::to_json),
+            "related_spans": self.spans.iter().skip(1).map(DiagnosticSpan::to_json).collect::<Vec<Value>>(),
+        })
+    }
+}
+
+/// Locates spans in their files, merging consecutive spans only separated by spaces, like the
+/// tokens of a statement
+fn merge_spans<'a>(spans: impl Iterator<Item = &'a Span>) -> Vec<DiagnosticSpan> {
+    let mut merged: Vec<(&Span, DiagnosticSpan)> = vec![];
+    for span in spans {
+        let Some(located) = DiagnosticSpan::new(span) else { continue };
+        if let Some((last, last_located)) = merged.last_mut() {
+            let source = span.file.as_ref().and_then(|f| f.source.as_deref());
+            let between = source.and_then(|s| s.get(last_located.end + 1..span.start));
+            let adjacent = last.file == span.file &&
+                span.start > last_located.end &&
+                between.map_or(false, |b| b.chars().all(|c| c == ' ' || c == '\t'));
+            if adjacent {
+                last_located.end = located.end;
+                last_located.end_position = located.end_position;
+                *last = span;
+                continue
+            }
+        }
+        merged.push((span, located));
+    }
+    merged.into_iter().map(|(_, located)| located).collect()
+}
+
+/// Labels the primary span and the other spans of a diagnostic
+fn label_spans(
+    mut spans: Vec<DiagnosticSpan>,
+    (primary, other): (Option<&str>, Option<&str>),
+) -> Vec<DiagnosticSpan> {
+    for (i, span) in spans.iter_mut().enumerate() {
+        span.label = if i == 0 { primary } else { other }.map(String::from);
+    }
+    spans
+}
+
+/// Gets the lines of an error's display below its heading
+fn error_details(error: &CompilerError) -> Vec<String> {
+    let display = error.to_string();
+    display
+        .trim()
+        .lines()
+        .skip(1)
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Gets the heading of an error's display, without the `Error[code]: ` prefix and the rendered
+/// source
+fn error_message(error: &CompilerError) -> String {
+    let display = error.to_string();
+    let heading = display.trim_start().lines().next().unwrap_or_default();
+    heading.split_once(": ").map_or(heading, |(_, message)| message).trim().to_string()
+}
+
+/// Builds a SARIF report of diagnostics, raised by the given version of `huffc`
+///
+/// The `rules` are the ids and descriptions of the lint rules and error codes that may be
+/// referenced by the diagnostics' codes.
+pub fn to_sarif(diagnostics: &[Diagnostic], rules: &[(&str, &str)], version: &str) -> Value {
+    let rules = rules
+        .iter()
+        .map(|(id, description)| json!({ "id": id, "shortDescription": { "text": description } }))
+        .collect::<Vec<Value>>();
+    let results = diagnostics.iter().map(sarif_result).collect::<Vec<Value>>();
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": SARIF_VERSION,
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "huffc",
+                    "version": version,
+                    "informationUri": "https://github.com/huff-language/huff-rs",
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }]
+    })
+}
+
+/// Builds the SARIF result of a diagnostic
+fn sarif_result(diagnostic: &Diagnostic) -> Value {
+    let message = std::iter::once(&diagnostic.message)
+        .chain(&diagnostic.hint)
+        .chain(&diagnostic.notes)
+        .cloned()
+        .collect::<Vec<String>>()
+        .join("\n");
+    let mut locations = diagnostic.spans.iter().map(sarif_location);
+    let mut result = json!({
+        "level": diagnostic.level.as_str(),
+        "message": { "text": message },
+        "locations": locations.next().into_iter().collect::<Vec<Value>>(),
+        "relatedLocations": locations.collect::<Vec<Value>>(),
+    });
+    if let Some(code) = &diagnostic.code {
+        result["ruleId"] = json!(code);
+    }
+    result
+}
+
+/// Builds the SARIF location of a span, with its region if the positions are known
+///
+/// SARIF end columns are exclusive, so they point past the span's last character.
+fn sarif_location(span: &DiagnosticSpan) -> Value {
+    let uri = span.file.strip_prefix("./").unwrap_or(&span.file);
+    let mut location = json!({ "artifactLocation": { "uri": uri } });
+    if let (Some(start), Some(end)) = (span.start_position, span.end_position) {
+        location["region"] = json!({
+            "startLine": start.line,
+            "startColumn": start.column,
+            "endLine": end.line,
+            "endColumn": end.column + 1,
+        });
+    }
+    json!({ "physicalLocation": location })
+}
+
+/// Renders diagnostics as code frames, with their spans underlined and labeled, followed by
+/// their hints and notes
+pub fn render(diagnostics: &[Diagnostic]) -> String {
+    let (codemap, frames) = code_frames(diagnostics);
+    let mut out = vec![];
+    Emitter::vec(&mut out, Some(&codemap)).emit(&frames);
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Renders diagnostics as code frames to stderr, colored if it's a terminal
+pub fn render_stderr(diagnostics: &[Diagnostic]) {
+    let (codemap, frames) = code_frames(diagnostics);
+    Emitter::stderr(ColorConfig::Auto, Some(&codemap)).emit(&frames);
+}
+
+/// Builds the code frames of diagnostics, with the code map of their files
+fn code_frames(diagnostics: &[Diagnostic]) -> (CodeMap, Vec<codemap_diagnostic::Diagnostic>) {
+    let mut codemap = CodeMap::new();
+    let mut files = BTreeMap::new();
+    let mut emitted = vec![];
+    for diagnostic in diagnostics {
+        let level = match diagnostic.level {
+            DiagnosticLevel::Error => Level::Error,
+            DiagnosticLevel::Warning => Level::Warning,
+        };
+        let mut notes = vec![];
+        let mut spans = vec![];
+        for (i, span) in diagnostic.spans.iter().enumerate() {
+            let Some(source) = span.source.as_ref().and_then(|f| f.source.as_ref()) else {
+                // Without the source, only the location of the span can be shown
+                let label = span.label.as_deref().unwrap_or("at");
+                notes.push(format!("{label} {}:{}-{}", span.file, span.start, span.end));
+                continue
+            };
+            let file = files
+                .entry(span.file.clone())
+                .or_insert_with(|| codemap.add_file(span.file.clone(), source.clone()).span);
+            let end = (span.end + 1).clamp(span.start.min(source.len()), source.len());
+            spans.push(SpanLabel {
+                span: file.subspan(span.start.min(source.len()) as u64, end as u64),
+                label: span.label.clone(),
+                style: if i == 0 { SpanStyle::Primary } else { SpanStyle::Secondary },
+            });
+        }
+        emitted.push(codemap_diagnostic::Diagnostic {
+            level,
+            message: diagnostic.message.clone(),
+            code: diagnostic.code.clone(),
+            spans,
+        });
+        let help = diagnostic.hint.iter().map(|hint| (Level::Help, hint.clone()));
+        let notes = diagnostic.notes.iter().cloned().chain(notes).map(|note| (Level::Note, note));
+        let explanation =
+            diagnostic.code.as_deref().filter(|code| explain(code).is_some()).map(|code| {
+                (Level::Note, format!("run `huffc explain {code}` for an extended explanation"))
+            });
+        let allow = diagnostic
+            .code
+            .as_deref()
+            .filter(|_| diagnostic.level == DiagnosticLevel::Warning)
+            .map(|id| {
+                (
+                    Level::Note,
+                    format!("allow the warning with a `// huff-lint: allow({id})` comment"),
+                )
+            });
+        emitted.extend(help.chain(notes).chain(explanation).chain(allow).map(
+            |(level, message)| codemap_diagnostic::Diagnostic {
+                level,
+                message,
+                code: None,
+                spans: vec![],
+            },
+        ));
+    }
+    (codemap, emitted)
+}
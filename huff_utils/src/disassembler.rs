@@ -0,0 +1,102 @@
+//! ## Disassembler
+//!
+//! Splits bytecode into its instructions and prints them with their program counters, naming
+//! JUMPDESTs and the destinations of pushed jumps. Given the [DebugRange]s of an artifact, the
+//! JUMPDESTs are named after their labels and each run of instructions is headed by the macros
+//! it was expanded from.
+
+use crate::{
+    artifact::DebugRange,
+    bytes_util::split_instructions,
+    evm::{Opcode, OPCODES, OPCODES_MAP},
+};
+
+/// A disassembled instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    /// The program counter of the instruction
+    pub pc: usize,
+    /// The opcode, `None` if the byte isn't a known opcode
+    pub opcode: Option<Opcode>,
+    /// The mnemonic of the opcode, or the byte of an unknown opcode, eg. `unknown(0x0c)`
+    pub mnemonic: String,
+    /// The hex data pushed by a `PUSHX` opcode, truncated at the end of the bytecode
+    pub data: String,
+}
+
+impl Instruction {
+    /// Whether a push's data runs past the end of the bytecode
+    pub fn is_truncated(&self) -> bool {
+        let byte = self.opcode.and_then(|o| u8::from_str_radix(&o.string(), 16).ok());
+        byte.map_or(false, |b| {
+            (0x60..=0x7f).contains(&b) && self.data.len() < usize::from(b - 0x5f) * 2
+        })
+    }
+}
+
+/// Disassembles hex bytecode, with or without a `0x` prefix
+pub fn disassemble(bytecode: &str) -> Vec<Instruction> {
+    let bytecode = bytecode.trim();
+    let bytecode = bytecode.strip_prefix("0x").unwrap_or(bytecode);
+    split_instructions(bytecode)
+        .into_iter()
+        .map(|(pc, byte, data)| {
+            // `0x44` is named `prevrandao`, following `difficulty` in the list of opcodes
+            let hex = format!("{byte:02x}");
+            let known = OPCODES.iter().rev().find_map(|mnemonic| {
+                OPCODES_MAP.get(mnemonic).filter(|o| o.string() == hex).map(|o| (*mnemonic, *o))
+            });
+            Instruction {
+                pc,
+                opcode: known.map(|(_, opcode)| opcode),
+                mnemonic: known.map_or(format!("unknown(0x{hex})"), |(m, _)| m.to_string()),
+                data: data.to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Prints disassembled instructions, cross-referenced with the debug ranges of their code
+///
+/// Without debug ranges, JUMPDESTs are named after their program counter, eg. `label_0x0009`.
+pub fn annotate(instructions: &[Instruction], debug: &[DebugRange]) -> String {
+    let range_at = |pc: usize| debug.iter().find(|r| r.start <= pc && pc < r.end);
+    let label = |pc: usize| {
+        range_at(pc)
+            .filter(|r| r.start == pc)
+            .and_then(|r| r.label.clone())
+            .unwrap_or_else(|| format!("label_{pc:#06x}"))
+    };
+
+    let mut lines = vec![];
+    let mut macros: Option<&[String]> = None;
+    for (i, instruction) in instructions.iter().enumerate() {
+        if let Some(range) = range_at(instruction.pc) {
+            if macros != Some(&range.macros[..]) {
+                lines.push(format!("// {}", range.macros.join(" > ")));
+                macros = Some(&range.macros);
+            }
+        }
+        if instruction.opcode == Some(Opcode::Jumpdest) {
+            lines.push(format!("{}:", label(instruction.pc)));
+        }
+
+        let mut line = format!("{:#06x}  {}", instruction.pc, instruction.mnemonic);
+        if !instruction.data.is_empty() {
+            line.push_str(&format!(" 0x{}", instruction.data));
+        }
+        let jumps = instructions
+            .get(i + 1)
+            .map_or(false, |next| matches!(next.opcode, Some(Opcode::Jump | Opcode::Jumpi)));
+        let destination = usize::from_str_radix(&instruction.data, 16).ok().filter(|pc| {
+            instructions.iter().any(|d| d.pc == *pc && d.opcode == Some(Opcode::Jumpdest))
+        });
+        if instruction.is_truncated() {
+            line = format!("{line:<32}// truncated");
+        } else if let Some(destination) = destination.filter(|_| jumps) {
+            line = format!("{line:<32}// -> {}", label(destination));
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
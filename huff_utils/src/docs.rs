@@ -0,0 +1,423 @@
+//! ## Documentation Generator
+//!
+//! Generates the Markdown or HTML documentation of a [Contract] from the doc comments of its
+//! functions, events, errors, constants and macros.
+//!
+//! Doc comments are the `///` and `/** */` comments directly preceding a definition, read from
+//! the contract's [TriviaTable]. Each documented item also lists its selector or topic, the
+//! value of a constant, or the takes, returns and estimated static gas of a macro.
+
+use crate::{ast::*, bytes_util::bytes32_to_string, evm::Opcode, symbols::SymbolKind};
+use ethers_core::utils::hex;
+use std::fmt::Write;
+
+/// The documentation of a definition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocItem {
+    /// The kind of the definition
+    pub kind: SymbolKind,
+    /// The name of the definition
+    pub name: String,
+    /// The signature of the definition, as it's defined
+    pub signature: String,
+    /// The text of its doc comments, without their delimiters
+    pub doc: Option<String>,
+    /// Labelled details, eg. `("Selector", "0xa9059cbb")`
+    pub details: Vec<(&'static str, String)>,
+}
+
+/// The documentation of a contract
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractDocs {
+    /// The title of the documentation, eg. the name of the contract's file
+    pub title: String,
+    /// The documented definitions, grouped by kind
+    pub items: Vec<DocItem>,
+}
+
+/// The sections of the documentation, in order
+const SECTIONS: [(SymbolKind, &str); 5] = [
+    (SymbolKind::Function, "Functions"),
+    (SymbolKind::Event, "Events"),
+    (SymbolKind::Error, "Errors"),
+    (SymbolKind::Constant, "Constants"),
+    (SymbolKind::Macro, "Macros"),
+];
+
+impl ContractDocs {
+    /// Public associated function that documents the definitions of a contract, except tests
+    pub fn new(title: &str, contract: &Contract) -> Self {
+        let doc = |span: &AstSpan| doc_comment(contract.trivia.leading(span));
+        let mut items = vec![];
+
+        for f in &contract.functions {
+            let fn_type = match f.fn_type {
+                FunctionType::View => "view",
+                FunctionType::Payable => "payable",
+                FunctionType::NonPayable => "nonpayable",
+                FunctionType::Pure => "pure",
+            };
+            items.push(DocItem {
+                kind: SymbolKind::Function,
+                name: f.name.clone(),
+                signature: format!(
+                    "#define function {}({}) {fn_type} returns ({})",
+                    f.name,
+                    arguments(&f.inputs),
+                    arguments(&f.outputs)
+                ),
+                doc: doc(&f.span),
+                details: vec![
+                    ("Selector", format!("0x{}", hex::encode(f.signature))),
+                    ("Signature", format!("{}({})", f.name, types(&f.inputs))),
+                ],
+            });
+        }
+
+        for e in &contract.events {
+            items.push(DocItem {
+                kind: SymbolKind::Event,
+                name: e.name.clone(),
+                signature: format!("#define event {}({})", e.name, arguments(&e.parameters)),
+                doc: doc(&e.span),
+                details: vec![
+                    ("Topic", format!("0x{}", hex::encode(e.hash))),
+                    ("Signature", format!("{}({})", e.name, types(&e.parameters))),
+                ],
+            });
+        }
+
+        for e in &contract.errors {
+            items.push(DocItem {
+                kind: SymbolKind::Error,
+                name: e.name.clone(),
+                signature: format!("#define error {}({})", e.name, arguments(&e.parameters)),
+                doc: doc(&e.span),
+                details: vec![
+                    ("Selector", format!("0x{}", hex::encode(e.selector))),
+                    ("Signature", format!("{}({})", e.name, types(&e.parameters))),
+                ],
+            });
+        }
+
+        let constants = contract.constants.lock().map(|c| c.clone()).unwrap_or_default();
+        for c in &constants {
+            // Expressions are evaluated once the contract's constants are, unless they're invalid
+            let value = match &c.value {
+                ConstVal::Literal(l) => Some(bytes32_to_string(l, true)),
+                ConstVal::StorageSlot(l) => {
+                    Some(format!("STORAGE_SLOT({})", bytes32_to_string(l, true)))
+                }
+                ConstVal::FreeStoragePointer(_) => Some("FREE_STORAGE_POINTER()".to_string()),
+                ConstVal::Env(var) => Some(format!("env(\"{var}\")")),
+                ConstVal::Expression(e) => {
+                    e.evaluate(&constants, &c.span).ok().map(|l| bytes32_to_string(&l, true))
+                }
+            };
+            items.push(DocItem {
+                kind: SymbolKind::Constant,
+                name: c.name.clone(),
+                signature: format!("#define constant {}", c.name),
+                doc: doc(&c.span),
+                details: value.map(|value| ("Value", value)).into_iter().collect(),
+            });
+        }
+
+        for m in contract.macros.iter().filter(|m| !m.test) {
+            let keyword = if m.outlined { "fn" } else { "macro" };
+            let parameters = m
+                .parameters
+                .iter()
+                .filter_map(|p| p.name.clone())
+                .collect::<Vec<String>>()
+                .join(", ");
+            items.push(DocItem {
+                kind: SymbolKind::Macro,
+                name: m.name.clone(),
+                signature: format!(
+                    "#define {keyword} {}({parameters}) = takes({}) returns({})",
+                    m.name, m.takes, m.returns
+                ),
+                doc: doc(&m.span),
+                details: vec![
+                    ("Takes", m.takes.to_string()),
+                    ("Returns", m.returns.to_string()),
+                    ("Static gas", static_gas(contract, m).to_string()),
+                ],
+            });
+        }
+
+        Self { title: title.to_string(), items }
+    }
+
+    /// The documented items of a section, in the order they're defined
+    fn section(&self, kind: SymbolKind) -> impl Iterator<Item = &DocItem> {
+        self.items.iter().filter(move |item| item.kind == kind)
+    }
+
+    /// Renders the documentation as Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("# {}\n", self.title);
+        for (kind, heading) in SECTIONS {
+            if self.section(kind).next().is_none() {
+                continue
+            }
+            let _ = write!(md, "\n## {heading}\n");
+            for item in self.section(kind) {
+                let _ = write!(md, "\n### {}\n\n```huff\n{}\n```\n", item.name, item.signature);
+                if let Some(doc) = &item.doc {
+                    let _ = write!(md, "\n{doc}\n");
+                }
+                md.push_str("\n| | |\n| - | - |\n");
+                for (label, value) in &item.details {
+                    let _ = writeln!(md, "| {label} | `{value}` |");
+                }
+            }
+        }
+        md
+    }
+
+    /// Renders the documentation as a standalone HTML page
+    pub fn to_html(&self) -> String {
+        let title = escape(&self.title);
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n</head>\n<body>\n<h1>{title}</h1>\n"
+        );
+        for (kind, heading) in SECTIONS {
+            if self.section(kind).next().is_none() {
+                continue
+            }
+            let _ = writeln!(html, "<h2>{heading}</h2>");
+            for item in self.section(kind) {
+                let name = escape(&item.name);
+                let _ = writeln!(html, "<h3 id=\"{name}\">{name}</h3>");
+                let _ = writeln!(html, "<pre><code>{}</code></pre>", escape(&item.signature));
+                for paragraph in item.doc.iter().flat_map(|doc| doc.split("\n\n")) {
+                    let _ = writeln!(html, "<p>{}</p>", escape(paragraph));
+                }
+                html.push_str("<table>\n");
+                for (label, value) in &item.details {
+                    let _ = writeln!(
+                        html,
+                        "<tr><th>{label}</th><td><code>{}</code></td></tr>",
+                        escape(value)
+                    );
+                }
+                html.push_str("</table>\n");
+            }
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+/// The text of the doc comments ending a node's leading trivia, without their delimiters
+///
+/// Consecutive `///` comments are joined by newlines, while any other comment ends them.
+pub fn doc_comment(trivia: &[Trivia]) -> Option<String> {
+    let mut lines = vec![];
+    for trivia in trivia.iter().rev() {
+        let TriviaKind::Comment(comment) = &trivia.kind else { continue };
+        if let Some(line) = comment.strip_prefix("///") {
+            lines.push(line.strip_prefix(' ').unwrap_or(line).trim_end().to_string());
+        } else if let Some(block) = comment.strip_prefix("/**") {
+            if !lines.is_empty() {
+                break
+            }
+            let block = block.strip_suffix("*/").unwrap_or(block);
+            let mut text = block
+                .lines()
+                .map(|line| {
+                    let line = line.trim_start();
+                    let line = line.strip_prefix('*').unwrap_or(line);
+                    line.strip_prefix(' ').unwrap_or(line).trim_end().to_string()
+                })
+                .collect::<Vec<String>>();
+            while text.last().map_or(false, String::is_empty) {
+                text.pop();
+            }
+            lines.extend(text.into_iter().rev());
+            break
+        } else {
+            break
+        }
+    }
+    while lines.last().map_or(false, String::is_empty) {
+        lines.pop();
+    }
+    lines.reverse();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// The NatSpec tags of a doc comment
+///
+/// Text before the first tag is read as the `@notice`, and unknown tags are skipped.
+/// Continuation lines are joined to their tag by a space.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct NatSpec {
+    /// The `@notice` for users
+    pub notice: Option<String>,
+    /// The `@dev` details for developers
+    pub dev: Option<String>,
+    /// The `@param` descriptions, by parameter name
+    pub params: Vec<(String, String)>,
+    /// The `@return` descriptions, in order
+    pub returns: Vec<String>,
+}
+
+impl NatSpec {
+    /// Parses the tags of a doc comment's text, eg. from [doc_comment]
+    pub fn parse(doc: &str) -> Self {
+        let mut natspec = Self::default();
+        // The tag being read and its text
+        let mut current: Option<(&str, String)> = None;
+        for line in doc.lines().map(str::trim) {
+            let (tag, text) = match line.strip_prefix('@') {
+                Some(tagged) => {
+                    let (tag, text) =
+                        tagged.split_once(char::is_whitespace).unwrap_or((tagged, ""));
+                    (tag, text.trim_start())
+                }
+                None => match &mut current {
+                    Some((_, content)) => {
+                        if !line.is_empty() {
+                            if !content.is_empty() {
+                                content.push(' ');
+                            }
+                            content.push_str(line);
+                        }
+                        continue
+                    }
+                    None if line.is_empty() => continue,
+                    None => ("notice", line),
+                },
+            };
+            if let Some((tag, content)) = current.take() {
+                natspec.add(tag, content);
+            }
+            current = Some((tag, text.to_string()));
+        }
+        if let Some((tag, content)) = current {
+            natspec.add(tag, content);
+        }
+        natspec
+    }
+
+    /// Adds the text of a tag, appending repeated `@notice` and `@dev` tags
+    fn add(&mut self, tag: &str, content: String) {
+        let append = |text: &mut Option<String>| match text {
+            Some(text) => {
+                text.push(' ');
+                text.push_str(&content);
+            }
+            None => *text = Some(content.clone()),
+        };
+        match tag {
+            "notice" => append(&mut self.notice),
+            "dev" => append(&mut self.dev),
+            "param" => {
+                let (name, description) =
+                    content.split_once(char::is_whitespace).unwrap_or((&content, ""));
+                self.params.push((name.to_string(), description.trim_start().to_string()));
+            }
+            "return" => self.returns.push(content),
+            _ => tracing::debug!(target: "docs", "SKIPPING UNKNOWN NATSPEC TAG \"@{}\"", tag),
+        }
+    }
+}
+
+/// Estimates the static gas of a macro, as if each of its instructions and those of the macros
+/// it invokes ran once
+///
+/// Branches, loops over jumps and the dynamic costs of opcodes aren't accounted for, while
+/// `__REPEAT` blocks count each of their iterations.
+pub fn static_gas(contract: &Contract, macro_def: &MacroDefinition) -> u64 {
+    let constants = contract.constants.lock().map(|c| c.clone()).unwrap_or_default();
+    statements_gas(contract, &constants, &macro_def.statements, &mut vec![macro_def.name.clone()])
+}
+
+/// The static gas of statements, expanding the macros they invoke
+fn statements_gas(
+    contract: &Contract,
+    constants: &[ConstantDefinition],
+    statements: &[Statement],
+    expanding: &mut Vec<String>,
+) -> u64 {
+    let push = Opcode::Push1.static_gas();
+    statements
+        .iter()
+        .map(|statement| match &statement.ty {
+            StatementType::Opcode(o) => o.static_gas(),
+            StatementType::Literal(_) |
+            StatementType::Constant(_) |
+            StatementType::ArgCall(_) |
+            StatementType::LabelCall(_) => push,
+            StatementType::Code(_) => 0,
+            StatementType::Label(label) => {
+                Opcode::Jumpdest.static_gas() +
+                    statements_gas(contract, constants, &label.inner, expanding)
+            }
+            StatementType::BuiltinFunctionCall(call) => match call.kind {
+                BuiltinFunctionKind::Verbatim |
+                BuiltinFunctionKind::DynConstructorArg |
+                BuiltinFunctionKind::Custom(_) => 0,
+                _ => push,
+            },
+            StatementType::MacroInvocation(invocation) => {
+                // Recursive invocations fail to compile, and are counted once
+                if expanding.contains(&invocation.macro_name) {
+                    return 0
+                }
+                let Some(invoked) =
+                    contract.macros.iter().find(|m| m.name == invocation.macro_name)
+                else {
+                    return 0
+                };
+                expanding.push(invoked.name.clone());
+                let gas = statements_gas(contract, constants, &invoked.statements, expanding);
+                expanding.pop();
+                gas
+            }
+            StatementType::Repeat(repeat) => {
+                let count = repeat.count.evaluate(constants, &repeat.span).map_or(1, |count| {
+                    let mut bytes = [0u8; 8];
+                    bytes.copy_from_slice(&count[24..]);
+                    u64::from_be_bytes(bytes)
+                });
+                count.saturating_mul(statements_gas(
+                    contract,
+                    constants,
+                    &repeat.statements,
+                    expanding,
+                ))
+            }
+        })
+        .fold(0, u64::saturating_add)
+}
+
+/// The types of arguments, eg. `address,uint256`
+fn types(args: &[Argument]) -> String {
+    args.iter().map(|a| a.arg_type.clone().unwrap_or_default()).collect::<Vec<String>>().join(",")
+}
+
+/// Arguments as they're defined, eg. `address indexed from, uint256`
+fn arguments(args: &[Argument]) -> String {
+    args.iter()
+        .map(|a| {
+            let mut arg = a.arg_type.clone().unwrap_or_default();
+            if a.indexed {
+                arg.push_str(" indexed");
+            }
+            if let Some(name) = &a.name {
+                let _ = write!(arg, " {name}");
+            }
+            arg
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Escapes the special characters of HTML
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -1,14 +1,16 @@
 use crate::{
     files::{Span, Spanned},
     io::UnpackError,
-    prelude::{parse_extension, AstSpan, Opcode},
+    prelude::{parse_extension, AstSpan, Opcode, MAX_REPEAT_COUNT},
+    remote::RemoteImportError,
     report::{Report, Reporter},
     token::TokenKind,
 };
+use serde::Serialize;
 use std::{ffi::OsString, fmt, io::Write};
 
 /// A Parser Error
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+#[derive(Serialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
 pub struct ParserError {
     /// The type of Parser Error
     pub kind: ParserErrorKind,
@@ -19,7 +21,7 @@ pub struct ParserError {
 }
 
 /// A Type of Parser Error
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+#[derive(Serialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
 pub enum ParserErrorKind {
     /// An invalid literal was passed to a push opcode
     InvalidPush(Opcode),
@@ -63,6 +65,44 @@ pub enum ParserErrorKind {
     InvalidDecoratorFlag(String),
     /// Invalid decorator flag argument
     InvalidDecoratorFlagArg(TokenKind),
+    /// A selectively imported definition is not defined in the imported file
+    InvalidImportSelection(String),
+    /// A macro, constant, table or function defined more than once
+    DuplicateDefinition(String),
+    /// The parser was given no tokens
+    EmptyTokenStream,
+}
+
+impl ParserErrorKind {
+    /// The stable code of the error, see [error_codes](crate::error_codes)
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParserErrorKind::InvalidPush(..) => "H0101",
+            ParserErrorKind::UnexpectedType(..) => "H0102",
+            ParserErrorKind::InvalidTypeAsArgumentName(..) => "H0103",
+            ParserErrorKind::InvalidDefinition(..) => "H0104",
+            ParserErrorKind::InvalidConstantValue(..) => "H0105",
+            ParserErrorKind::InvalidTokenInMacroBody(..) => "H0106",
+            ParserErrorKind::InvalidTokenInLabelDefinition(..) => "H0107",
+            ParserErrorKind::InvalidSingleArg(..) => "H0108",
+            ParserErrorKind::InvalidTableBodyToken(..) => "H0109",
+            ParserErrorKind::InvalidConstant(..) => "H0110",
+            ParserErrorKind::InvalidArgCallIdent(..) => "H0111",
+            ParserErrorKind::InvalidName(..) => "H0112",
+            ParserErrorKind::InvalidArgs(..) => "H0113",
+            ParserErrorKind::InvalidUint256(..) => "H0114",
+            ParserErrorKind::InvalidBytes(..) => "H0115",
+            ParserErrorKind::InvalidInt(..) => "H0116",
+            ParserErrorKind::InvalidMacroArgs(..) => "H0117",
+            ParserErrorKind::InvalidReturnArgs => "H0118",
+            ParserErrorKind::InvalidImportPath(..) => "H0119",
+            ParserErrorKind::InvalidDecoratorFlag(..) => "H0120",
+            ParserErrorKind::InvalidDecoratorFlagArg(..) => "H0121",
+            ParserErrorKind::InvalidImportSelection(..) => "H0122",
+            ParserErrorKind::DuplicateDefinition(..) => "H0123",
+            ParserErrorKind::EmptyTokenStream => "H0124",
+        }
+    }
 }
 
 /// A Lexing Error
@@ -93,6 +133,30 @@ pub enum LexicalErrorKind {
     InvalidArraySize(String),
     /// Invalid Primitive EVM Type
     InvalidPrimitiveType(String),
+    /// A conditional compilation directive without a matching `#ifdef` or `#ifndef`
+    UnmatchedDirective(TokenKind),
+    /// An `#ifdef` or `#ifndef` without a closing `#endif`
+    UnterminatedConditional,
+    /// An `#ifdef` or `#ifndef` not followed by a flag
+    MissingConditionalFlag(TokenKind),
+    /// An invalid numeric literal, eg. exceeding 32 bytes or with misplaced `_` separators
+    InvalidLiteral(String),
+}
+
+impl LexicalErrorKind {
+    /// The stable code of the error, see [error_codes](crate::error_codes)
+    pub fn code(&self) -> &'static str {
+        match self {
+            LexicalErrorKind::UnexpectedEof => "H0001",
+            LexicalErrorKind::InvalidCharacter(..) => "H0002",
+            LexicalErrorKind::InvalidArraySize(..) => "H0003",
+            LexicalErrorKind::InvalidPrimitiveType(..) => "H0004",
+            LexicalErrorKind::UnmatchedDirective(..) => "H0005",
+            LexicalErrorKind::UnterminatedConditional => "H0006",
+            LexicalErrorKind::MissingConditionalFlag(..) => "H0007",
+            LexicalErrorKind::InvalidLiteral(..) => "H0008",
+        }
+    }
 }
 
 impl Spanned for LexicalError {
@@ -112,6 +176,18 @@ impl<W: Write> Report<W> for LexicalError {
             LexicalErrorKind::InvalidPrimitiveType(str) => {
                 write!(f.out, "Invalid Primitive EVM Type '{str}'")
             }
+            LexicalErrorKind::UnmatchedDirective(kind) => {
+                write!(f.out, "Unmatched '{kind}' directive")
+            }
+            LexicalErrorKind::UnterminatedConditional => {
+                write!(f.out, "Conditional block without '#endif'")
+            }
+            LexicalErrorKind::MissingConditionalFlag(kind) => {
+                write!(f.out, "Expected a flag after '{kind}'")
+            }
+            LexicalErrorKind::InvalidLiteral(str) => {
+                write!(f.out, "Invalid literal '{str}'")
+            }
         }
     }
 }
@@ -125,12 +201,14 @@ pub struct CodegenError {
     pub span: AstSpan,
     /// An Optional Token Kind
     pub token: Option<TokenKind>,
+    /// Hints about the error
+    pub hint: Option<String>,
 }
 
 impl CodegenError {
     /// Public associated function to instatiate a new CodegenError.
     pub fn new(kind: CodegenErrorKind, spans: AstSpan, token: Option<TokenKind>) -> Self {
-        Self { kind, span: spans, token }
+        Self { kind, span: spans, token, hint: None }
     }
 }
 
@@ -179,6 +257,98 @@ pub enum CodegenErrorKind {
     TestInvocation(String),
     /// Incorrect dynamic argument index
     InvalidDynArgIndex,
+    /// A constant expression references itself
+    CircularConstantReference(String),
+    /// An `env` constant references an unset environment variable
+    MissingEnvironmentVariable(String),
+    /// An `env` constant references an environment variable that isn't a valid literal
+    InvalidEnvironmentVariable(String, String),
+    /// Immutables are used with a constructor that returns the runtime code itself
+    ImmutablesWithCustomBootstrap,
+    /// Two constants are assigned the same storage slot
+    StorageSlotCollision(String, String),
+    /// A compile-time assertion doesn't hold
+    FailedAssertion(String),
+    /// A `__REPEAT` count exceeds the maximum number of iterations
+    InvalidRepeatCount(String),
+    /// A jump to a label that is defined more than once
+    DuplicateLabel(String),
+    /// A macro is invoked with a different number of arguments than it has parameters
+    InvalidMacroArgCount(String, usize, usize),
+    /// A macro invokes itself, with the chain of invocations forming the cycle
+    RecursiveMacro(Vec<String>),
+    /// Macro invocations are nested deeper than the maximum expansion depth
+    MaxExpansionDepth(usize),
+    /// A label passed as a macro argument isn't defined by the invoking macro or its callers
+    UndefinedLabelArgument(String),
+    /// A label that is jumped to doesn't end up as a valid JUMPDEST in the bytecode
+    InvalidJumpDestination(String),
+    /// An argument call references a parameter the macro doesn't declare
+    UndefinedArgument(String, String),
+    /// A custom builtin is called without being registered with the contract
+    MissingBuiltinDefinition(String),
+    /// The offset of a label or table needs a wider push than reserved for it, with the bytes
+    /// reserved and the bytes needed
+    OffsetExceedsPushWidth(String, usize, usize),
+    /// An immutable is read in constructor code, which runs before its value is written
+    ImmutableInConstructor(String),
+    /// `__codesize` is passed an enclosing macro while passes run over the code, whose size
+    /// would depend on the size it pushes
+    OptimizedCircularCodesize(String),
+    /// An immutable is filled from a dynamically sized constructor argument, whose head only
+    /// holds the offset of its value
+    DynamicImmutableArgument(String),
+    /// The code table the bootstrap returns as the runtime code isn't defined
+    MissingCodeTable(String),
+}
+
+impl CodegenErrorKind {
+    /// The stable code of the error, see [error_codes](crate::error_codes)
+    pub fn code(&self) -> &'static str {
+        match self {
+            CodegenErrorKind::LockingError => "H0201",
+            CodegenErrorKind::StoragePointersNotDerived => "H0202",
+            CodegenErrorKind::InvalidMacroStatement => "H0203",
+            CodegenErrorKind::MissingMacroDefinition(..) => "H0204",
+            CodegenErrorKind::MissingFunctionInterface(..) => "H0205",
+            CodegenErrorKind::MissingEventInterface(..) => "H0206",
+            CodegenErrorKind::MissingConstantDefinition(..) => "H0207",
+            CodegenErrorKind::MissingErrorDefinition(..) => "H0208",
+            CodegenErrorKind::AbiGenerationFailure => "H0209",
+            CodegenErrorKind::UnmatchedJumpLabel => "H0210",
+            CodegenErrorKind::IOError(..) => "H0211",
+            CodegenErrorKind::UnkownArgcallType => "H0212",
+            CodegenErrorKind::MissingMacroInvocation(..) => "H0213",
+            CodegenErrorKind::InvalidMacroInvocation(..) => "H0214",
+            CodegenErrorKind::UsizeConversion(..) => "H0215",
+            CodegenErrorKind::InvalidArguments(..) => "H0216",
+            CodegenErrorKind::InvalidHex(..) => "H0217",
+            CodegenErrorKind::InvalidTableStatement(..) => "H0218",
+            CodegenErrorKind::InvalidCodeLength(..) => "H0219",
+            CodegenErrorKind::TestInvocation(..) => "H0220",
+            CodegenErrorKind::InvalidDynArgIndex => "H0221",
+            CodegenErrorKind::CircularConstantReference(..) => "H0222",
+            CodegenErrorKind::MissingEnvironmentVariable(..) => "H0223",
+            CodegenErrorKind::InvalidEnvironmentVariable(..) => "H0224",
+            CodegenErrorKind::ImmutablesWithCustomBootstrap => "H0225",
+            CodegenErrorKind::StorageSlotCollision(..) => "H0226",
+            CodegenErrorKind::FailedAssertion(..) => "H0227",
+            CodegenErrorKind::InvalidRepeatCount(..) => "H0228",
+            CodegenErrorKind::DuplicateLabel(..) => "H0229",
+            CodegenErrorKind::InvalidMacroArgCount(..) => "H0230",
+            CodegenErrorKind::RecursiveMacro(..) => "H0231",
+            CodegenErrorKind::MaxExpansionDepth(..) => "H0232",
+            CodegenErrorKind::UndefinedLabelArgument(..) => "H0233",
+            CodegenErrorKind::InvalidJumpDestination(..) => "H0234",
+            CodegenErrorKind::UndefinedArgument(..) => "H0235",
+            CodegenErrorKind::MissingBuiltinDefinition(..) => "H0236",
+            CodegenErrorKind::OffsetExceedsPushWidth(..) => "H0237",
+            CodegenErrorKind::ImmutableInConstructor(_) => "H0238",
+            CodegenErrorKind::OptimizedCircularCodesize(_) => "H0239",
+            CodegenErrorKind::DynamicImmutableArgument(_) => "H0240",
+            CodegenErrorKind::MissingCodeTable(_) => "H0241",
+        }
+    }
 }
 
 impl Spanned for CodegenError {
@@ -243,10 +413,198 @@ impl<W: Write> Report<W> for CodegenError {
             CodegenErrorKind::InvalidDynArgIndex => {
                 write!(f.out, "Invalid Dynamic Constructor Argument Index")
             }
+            CodegenErrorKind::CircularConstantReference(name) => {
+                write!(f.out, "Circular reference in constant \"{name}\"")
+            }
+            CodegenErrorKind::MissingEnvironmentVariable(var) => {
+                write!(f.out, "Environment variable \"{var}\" is not set")
+            }
+            CodegenErrorKind::InvalidEnvironmentVariable(var, value) => {
+                write!(f.out, "Environment variable \"{var}\" is not a hex literal: \"{value}\"")
+            }
+            CodegenErrorKind::ImmutablesWithCustomBootstrap => {
+                write!(f.out, "Immutables can't be filled by a constructor returning its own code")
+            }
+            CodegenErrorKind::StorageSlotCollision(first, second) => {
+                write!(f.out, "Constants \"{first}\" and \"{second}\" share a storage slot")
+            }
+            CodegenErrorKind::FailedAssertion(comparison) => {
+                write!(f.out, "Assertion failed: {comparison}")
+            }
+            CodegenErrorKind::InvalidRepeatCount(count) => {
+                write!(f.out, "Invalid repeat count: {count}")
+            }
+            CodegenErrorKind::DuplicateLabel(label) => {
+                write!(f.out, "Label \"{label}\" is defined more than once")
+            }
+            CodegenErrorKind::InvalidMacroArgCount(name, expected, found) => {
+                write!(
+                    f.out,
+                    "Macro \"{name}\" takes {expected} arguments, but {found} were passed"
+                )
+            }
+            CodegenErrorKind::RecursiveMacro(cycle) => {
+                write!(f.out, "Recursive macro invocation: {}", cycle.join(" -> "))
+            }
+            CodegenErrorKind::MaxExpansionDepth(depth) => {
+                write!(f.out, "Macro invocations exceed the maximum expansion depth of {depth}")
+            }
+            CodegenErrorKind::UndefinedLabelArgument(label) => {
+                write!(f.out, "Label \"{label}\" passed as a macro argument is not defined")
+            }
+            CodegenErrorKind::InvalidJumpDestination(label) => {
+                write!(f.out, "Label \"{label}\" is not a valid jump destination")
+            }
+            CodegenErrorKind::UndefinedArgument(arg, name) => {
+                write!(f.out, "Macro \"{name}\" has no argument \"{arg}\"")
+            }
+            CodegenErrorKind::MissingBuiltinDefinition(name) => {
+                write!(f.out, "Builtin function \"{name}\" is not registered")
+            }
+            CodegenErrorKind::OffsetExceedsPushWidth(label, width, needed) => {
+                write!(
+                    f.out,
+                    "Offset of \"{label}\" needs {needed} bytes, but its push only has {width}"
+                )
+            }
+            CodegenErrorKind::ImmutableInConstructor(name) => {
+                write!(f.out, "Immutable \"{name}\" can only be read in runtime code")
+            }
+            CodegenErrorKind::OptimizedCircularCodesize(name) => {
+                write!(f.out, "Circular __codesize({name}) can't be resolved with passes enabled")
+            }
+            CodegenErrorKind::DynamicImmutableArgument(name) => {
+                write!(f.out, "Immutable \"{name}\" is filled from a dynamically sized argument")
+            }
+            CodegenErrorKind::MissingCodeTable(name) => {
+                write!(f.out, "Missing code table \"{name}\" to return as the runtime code")
+            }
+        }
+    }
+}
+
+/// The severity a class of warnings is reported with
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The warning is not reported
+    Allow,
+    /// The warning is reported without failing the compilation
+    #[default]
+    Warn,
+    /// The warning fails the compilation
+    Error,
+}
+
+impl std::str::FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(Severity::Allow),
+            "warn" => Ok(Severity::Warn),
+            "error" => Ok(Severity::Error),
+            _ => Err(format!("Invalid severity \"{s}\", expected one of: allow, warn, error")),
+        }
+    }
+}
+
+/// A Compiler Warning
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CompilerWarning {
+    /// The kind of warning
+    pub kind: WarningKind,
+    /// The span the warning points at
+    pub span: AstSpan,
+    /// The id of the lint rule that raised the warning, if any
+    pub lint: Option<&'static str>,
+}
+
+impl CompilerWarning {
+    /// Public associated function to instatiate a new CompilerWarning.
+    pub fn new(kind: WarningKind, span: AstSpan) -> Self {
+        Self { kind, span, lint: None }
+    }
+
+    /// Formats the lint rule id as a suffix of the warning's heading, e.g. `[unused-macros]`
+    fn lint_suffix(&self) -> String {
+        self.lint.map(|l| format!("[{l}]")).unwrap_or_default()
+    }
+}
+
+/// The Compiler Warning Kind
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum WarningKind {
+    /// An address literal with an invalid EIP-55 checksum, with the found and expected casing
+    InvalidAddressChecksum(String, String),
+    /// A stack comment not matching the simulated stack, with the found and expected stack
+    StackCommentMismatch(String, String),
+    /// A macro whose stack effect doesn't match its declaration, with the declared and computed
+    /// takes and returns
+    StackEffectMismatch(String, (usize, usize), (usize, usize)),
+    /// An instruction taking more stack items than available, with the taken and available items
+    StackUnderflow(usize, usize),
+    /// An instruction exceeding the maximum stack height, with the resulting height
+    StackOverflow(usize),
+    /// Events sharing the same topic, with the event name and the topic
+    EventTopicCollision(String, String),
+    /// A macro that is never invoked from the main or constructor macro
+    UnusedMacro(String),
+    /// A constant that is never referenced from the main or constructor macro
+    UnusedConstant(String),
+    /// An imported definition redefined by an importing file, with the name, the path of the
+    /// imported file and the path of the importing file whose definition wins
+    ShadowedDefinition(String, String, String),
+    /// Code exceeding the size limit of its kind, with the kind of code, its size, the limit, and
+    /// the largest macros expanded into it with their sizes
+    CodeSizeExceeded(String, usize, usize, Vec<(String, usize)>),
+    /// A warning raised by a custom lint rule, with its message
+    Lint(String),
+}
+
+impl fmt::Display for WarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WarningKind::InvalidAddressChecksum(found, expected) => {
+                write!(f, "Invalid Address Checksum: \"{found}\", Expected \"{expected}\"")
+            }
+            WarningKind::StackCommentMismatch(found, expected) => {
+                write!(f, "Stack Comment Mismatch: \"{found}\", Expected \"{expected}\"")
+            }
+            WarningKind::StackEffectMismatch(name, declared, computed) => write!(
+                f,
+                "Stack Effect Mismatch: Macro \"{name}\" Declares takes({}) returns({}), Computed takes({}) returns({})",
+                declared.0, declared.1, computed.0, computed.1
+            ),
+            WarningKind::StackUnderflow(taken, available) => {
+                write!(f, "Stack Underflow: Takes {taken} Items, Found {available}")
+            }
+            WarningKind::StackOverflow(height) => {
+                write!(f, "Stack Overflow: Height {height} Exceeds 1024")
+            }
+            WarningKind::EventTopicCollision(name, topic) => {
+                write!(f, "Event Topic Collision: \"{name}\" Is Defined More Than Once With Topic {topic}")
+            }
+            WarningKind::UnusedMacro(name) => write!(f, "Unused Macro: \"{name}\""),
+            WarningKind::UnusedConstant(name) => write!(f, "Unused Constant: \"{name}\""),
+            WarningKind::ShadowedDefinition(name, shadowed, winner) => write!(
+                f,
+                "Shadowed Definition: \"{name}\" Of \"{shadowed}\" Is Shadowed By The Definition In \"{winner}\""
+            ),
+            WarningKind::CodeSizeExceeded(kind, size, limit, _) => write!(
+                f,
+                "Code Size Exceeded: {kind} Code Of {size} Bytes Exceeds The Limit Of {limit} Bytes"
+            ),
+            WarningKind::Lint(message) => write!(f, "{message}"),
         }
     }
 }
 
+impl fmt::Display for CompilerWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\nWarning{}: {}\n{}\n", self.lint_suffix(), self.kind, self.span.error(None))
+    }
+}
+
 /// CompilerError
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CompilerError {
@@ -262,10 +620,43 @@ pub enum CompilerError {
     CodegenError(CodegenError),
     /// Multiple Failed Compiles
     FailedCompiles(Vec<CompilerError>),
+    /// Files that include each other, with the spans of the `#include`s forming the cycle
+    CircularImport(Vec<String>, AstSpan),
+    /// Remote Import Error
+    RemoteImportError(RemoteImportError),
+    /// A warning configured to fail the compilation
+    Warning(CompilerWarning),
 }
 
-impl fmt::Display for CompilerError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl CompilerError {
+    /// The stable code of the error, see [error_codes](crate::error_codes)
+    ///
+    /// `None` for failed compiles, which hold the codes of each error, and for warnings
+    /// configured to fail the compilation, identified by their lint rule instead.
+    pub fn code(&self) -> Option<&'static str> {
+        match self {
+            CompilerError::LexicalError(le) => Some(le.kind.code()),
+            CompilerError::ParserError(pe) => Some(pe.kind.code()),
+            CompilerError::CodegenError(ce) => Some(ce.kind.code()),
+            CompilerError::FileUnpackError(ue) => Some(match ue {
+                UnpackError::InvalidDirectory(_) => "H0301",
+                UnpackError::UnsupportedExtension(_) => "H0302",
+                UnpackError::MissingFile(_) => "H0303",
+            }),
+            CompilerError::PathBufRead(_) => Some("H0304"),
+            CompilerError::CircularImport(..) => Some("H0305"),
+            CompilerError::RemoteImportError(rie) => Some(match rie {
+                RemoteImportError::Disabled(_) => "H0306",
+                RemoteImportError::FetchFailed(..) => "H0307",
+                RemoteImportError::HashMismatch { .. } => "H0308",
+                RemoteImportError::Io(..) => "H0309",
+            }),
+            CompilerError::FailedCompiles(_) | CompilerError::Warning(_) => None,
+        }
+    }
+
+    /// Formats the error without its code
+    fn fmt_message(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             CompilerError::LexicalError(le) => match &le.kind {
                 LexicalErrorKind::UnexpectedEof => {
@@ -303,6 +694,41 @@ impl fmt::Display for CompilerError {
                         le.span.source_seg()
                     )
                 }
+                LexicalErrorKind::UnmatchedDirective(kind) => {
+                    write!(
+                        f,
+                        "\nError: Unmatched Directive: \"{}\" {}{}\n",
+                        kind,
+                        le.span.identifier(),
+                        le.span.source_seg()
+                    )
+                }
+                LexicalErrorKind::UnterminatedConditional => {
+                    write!(
+                        f,
+                        "\nError: Conditional Block Without \"#endif\" {}{}\n",
+                        le.span.identifier(),
+                        le.span.source_seg()
+                    )
+                }
+                LexicalErrorKind::MissingConditionalFlag(kind) => {
+                    write!(
+                        f,
+                        "\nError: Missing Flag After \"{}\" {}{}\n",
+                        kind,
+                        le.span.identifier(),
+                        le.span.source_seg()
+                    )
+                }
+                LexicalErrorKind::InvalidLiteral(l) => {
+                    write!(
+                        f,
+                        "\nError: Invalid Literal: \"{}\" {}{}\n",
+                        l,
+                        le.span.identifier(),
+                        le.span.source_seg()
+                    )
+                }
             },
             CompilerError::FileUnpackError(ue) => match ue {
                 UnpackError::InvalidDirectory(id) => {
@@ -353,6 +779,22 @@ impl fmt::Display for CompilerError {
                         pe.spans.error(pe.hint.as_ref())
                     )
                 }
+                ParserErrorKind::InvalidImportSelection(name) => {
+                    write!(
+                        f,
+                        "\nError: Invalid Import Selection: \"{}\" \n{}\n",
+                        name,
+                        pe.spans.error(pe.hint.as_ref())
+                    )
+                }
+                ParserErrorKind::DuplicateDefinition(name) => {
+                    write!(
+                        f,
+                        "\nError: Duplicate Definition: \"{}\" \n{}\n",
+                        name,
+                        pe.spans.error(pe.hint.as_ref())
+                    )
+                }
                 ParserErrorKind::InvalidConstantValue(cv) => {
                     write!(
                         f,
@@ -464,6 +906,9 @@ impl fmt::Display for CompilerError {
                         pe.spans.error(pe.hint.as_ref())
                     )
                 }
+                ParserErrorKind::EmptyTokenStream => {
+                    write!(f, "\nError: Empty Token Stream\n{}\n", pe.spans.error(pe.hint.as_ref()))
+                }
                 ParserErrorKind::InvalidImportPath(ip) => {
                     write!(
                         f,
@@ -501,10 +946,18 @@ impl fmt::Display for CompilerError {
                     write!(f, "\nError: Synchronisation Failure\n")
                 }
                 CodegenErrorKind::StoragePointersNotDerived => {
-                    write!(f, "\nError: Storage Pointers Not Derived\n{}\n", ce.span.error(None))
+                    write!(
+                        f,
+                        "\nError: Storage Pointers Not Derived\n{}\n",
+                        ce.span.error(ce.hint.as_ref())
+                    )
                 }
                 CodegenErrorKind::InvalidMacroStatement => {
-                    write!(f, "\nError: Invalid Macro Statement\n{}\n", ce.span.error(None))
+                    write!(
+                        f,
+                        "\nError: Invalid Macro Statement\n{}\n",
+                        ce.span.error(ce.hint.as_ref())
+                    )
                 }
                 CodegenErrorKind::MissingMacroDefinition(md) => {
                     write!(
@@ -519,7 +972,7 @@ impl fmt::Display for CompilerError {
                         f,
                         "\nError: Missing Macro Definition For Invocation: \"{}\"\n{}\n",
                         mmi,
-                        ce.span.error(None)
+                        ce.span.error(ce.hint.as_ref())
                     )
                 }
                 CodegenErrorKind::MissingFunctionInterface(func) => {
@@ -527,7 +980,7 @@ impl fmt::Display for CompilerError {
                         f,
                         "\nError: Missing Function Interface: \"{}\"\n{}\n",
                         func,
-                        ce.span.error(None)
+                        ce.span.error(ce.hint.as_ref())
                     )
                 }
                 CodegenErrorKind::MissingEventInterface(event) => {
@@ -535,61 +988,288 @@ impl fmt::Display for CompilerError {
                         f,
                         "\nError: Missing Event Interface: \"{}\"\n{}\n",
                         event,
-                        ce.span.error(None)
+                        ce.span.error(ce.hint.as_ref())
                     )
                 }
                 CodegenErrorKind::MissingConstantDefinition(_) => {
-                    write!(f, "\nError: Missing Constant Definition\n{}\n", ce.span.error(None))
+                    write!(
+                        f,
+                        "\nError: Missing Constant Definition\n{}\n",
+                        ce.span.error(ce.hint.as_ref())
+                    )
                 }
                 CodegenErrorKind::MissingErrorDefinition(_) => {
-                    write!(f, "\nError: Missing Error Definition\n{}\n", ce.span.error(None))
+                    write!(
+                        f,
+                        "\nError: Missing Error Definition\n{}\n",
+                        ce.span.error(ce.hint.as_ref())
+                    )
                 }
                 CodegenErrorKind::AbiGenerationFailure => {
-                    write!(f, "\nError: ABI Generation Failed\n{}\n", ce.span.error(None))
+                    write!(
+                        f,
+                        "\nError: ABI Generation Failed\n{}\n",
+                        ce.span.error(ce.hint.as_ref())
+                    )
                 }
                 CodegenErrorKind::IOError(ioe) => {
                     write!(f, "\nError: IO Error: {ioe}\n{}", ce.span.file())
                 }
                 CodegenErrorKind::UnkownArgcallType => {
-                    write!(f, "\nError: Unknown Arg Call Type\n{}\n", ce.span.error(None))
+                    write!(
+                        f,
+                        "\nError: Unknown Arg Call Type\n{}\n",
+                        ce.span.error(ce.hint.as_ref())
+                    )
                 }
                 CodegenErrorKind::MissingMacroInvocation(mmi) => {
                     write!(
                         f,
                         "\nError: Missing Macro Invocation: \"{}\"\n{}\n",
                         mmi,
-                        ce.span.error(None)
+                        ce.span.error(ce.hint.as_ref())
                     )
                 }
                 CodegenErrorKind::UnmatchedJumpLabel => {
-                    write!(f, "\nError: Unmatched Jump Label\n{}\n", ce.span.error(None))
+                    write!(
+                        f,
+                        "\nError: Unmatched Jump Label\n{}\n",
+                        ce.span.error(ce.hint.as_ref())
+                    )
                 }
                 CodegenErrorKind::UsizeConversion(_) => {
-                    write!(f, "\nError: Usize Conversion\n{}\n", ce.span.error(None))
+                    write!(f, "\nError: Usize Conversion\n{}\n", ce.span.error(ce.hint.as_ref()))
                 }
-                CodegenErrorKind::InvalidArguments(_) => {
-                    write!(f, "\nError: Invalid Arguments\n{}\n", ce.span.error(None))
+                CodegenErrorKind::InvalidArguments(msg) => {
+                    write!(
+                        f,
+                        "\nError: Invalid Arguments: {}\n{}\n",
+                        msg,
+                        ce.span.error(ce.hint.as_ref())
+                    )
                 }
                 CodegenErrorKind::InvalidHex(_) => {
-                    write!(f, "\nError: Invalid Hex\n{}\n", ce.span.error(None))
+                    write!(f, "\nError: Invalid Hex\n{}\n", ce.span.error(ce.hint.as_ref()))
                 }
                 CodegenErrorKind::InvalidTableStatement(_) => {
-                    write!(f, "\nError: Invalid Table Statement\n{}\n", ce.span.error(None))
+                    write!(
+                        f,
+                        "\nError: Invalid Table Statement\n{}\n",
+                        ce.span.error(ce.hint.as_ref())
+                    )
                 }
                 CodegenErrorKind::InvalidCodeLength(_) => {
-                    write!(f, "\nError: Invalid Code Length\n{}\n", ce.span.error(None))
+                    write!(f, "\nError: Invalid Code Length\n{}\n", ce.span.error(ce.hint.as_ref()))
                 }
                 CodegenErrorKind::TestInvocation(_) => {
-                    write!(f, "\nError: Test Invocation\n{}\n", ce.span.error(None))
+                    write!(f, "\nError: Test Invocation\n{}\n", ce.span.error(ce.hint.as_ref()))
                 }
                 CodegenErrorKind::InvalidDynArgIndex => {
                     write!(
                         f,
                         "\nError: Invalid Dynamic Constructor Argument Index:\n{}\n",
-                        ce.span.error(None)
+                        ce.span.error(ce.hint.as_ref())
+                    )
+                }
+                CodegenErrorKind::CircularConstantReference(name) => {
+                    write!(
+                        f,
+                        "\nError: Circular Reference In Constant \"{}\"\n{}\n",
+                        name,
+                        ce.span.error(ce.hint.as_ref())
+                    )
+                }
+                CodegenErrorKind::MissingEnvironmentVariable(var) => {
+                    write!(
+                        f,
+                        "\nError: Environment Variable \"{}\" Is Not Set\n{}\n",
+                        var,
+                        ce.span.error(ce.hint.as_ref())
+                    )
+                }
+                CodegenErrorKind::ImmutablesWithCustomBootstrap => {
+                    write!(
+                        f,
+                        "\nError: Immutables Can't Be Filled By A Constructor Returning Its Own Code\n{}\n",
+                        ce.span.file()
+                    )
+                }
+                CodegenErrorKind::InvalidEnvironmentVariable(var, value) => {
+                    write!(
+                        f,
+                        "\nError: Environment Variable \"{}\" Is Not A Hex Literal Of At Most 32 Bytes: \"{}\"\n{}\n",
+                        var,
+                        value,
+                        ce.span.error(ce.hint.as_ref())
+                    )
+                }
+                CodegenErrorKind::FailedAssertion(comparison) => {
+                    write!(
+                        f,
+                        "\nError: Assertion Failed: {}\n{}\n",
+                        comparison,
+                        ce.span.error(ce.hint.as_ref())
+                    )
+                }
+                CodegenErrorKind::InvalidRepeatCount(count) => {
+                    write!(
+                        f,
+                        "\nError: Repeat Count {} Exceeds The Maximum Of {} Iterations\n{}\n",
+                        count,
+                        MAX_REPEAT_COUNT,
+                        ce.span.error(ce.hint.as_ref())
+                    )
+                }
+                CodegenErrorKind::DuplicateLabel(label) => {
+                    write!(
+                        f,
+                        "\nError: Label \"{}\" Is Defined More Than Once\n{}\n",
+                        label,
+                        ce.span.error(ce.hint.as_ref())
+                    )
+                }
+                CodegenErrorKind::InvalidMacroArgCount(name, expected, found) => {
+                    write!(
+                        f,
+                        "\nError: Macro \"{}\" Takes {} Arguments, But {} Were Passed\n{}\n",
+                        name,
+                        expected,
+                        found,
+                        ce.span.error(ce.hint.as_ref())
+                    )
+                }
+                CodegenErrorKind::RecursiveMacro(cycle) => {
+                    write!(
+                        f,
+                        "\nError: Recursive Macro Invocation: {}\n{}\n",
+                        cycle.join(" -> "),
+                        ce.span.error(ce.hint.as_ref())
+                    )
+                }
+                CodegenErrorKind::MaxExpansionDepth(depth) => {
+                    write!(
+                        f,
+                        "\nError: Macro Invocations Exceed The Maximum Expansion Depth Of {}\n{}\n",
+                        depth,
+                        ce.span.error(ce.hint.as_ref())
+                    )
+                }
+                CodegenErrorKind::UndefinedLabelArgument(label) => {
+                    write!(
+                        f,
+                        "\nError: Label \"{}\" Passed As A Macro Argument Is Not Defined By The Invoking Macro Or Its Callers\n{}\n",
+                        label,
+                        ce.span.error(ce.hint.as_ref())
+                    )
+                }
+                CodegenErrorKind::InvalidJumpDestination(label) => {
+                    write!(
+                        f,
+                        "\nError: Label \"{}\" Is Not A Valid JUMPDEST In The Generated Bytecode\n{}\n",
+                        label,
+                        ce.span.error(ce.hint.as_ref())
+                    )
+                }
+                CodegenErrorKind::UndefinedArgument(arg, name) => {
+                    write!(
+                        f,
+                        "\nError: Macro \"{}\" Has No Argument \"{}\"\n{}\n",
+                        name,
+                        arg,
+                        ce.span.error(ce.hint.as_ref())
+                    )
+                }
+                CodegenErrorKind::MissingBuiltinDefinition(name) => {
+                    write!(
+                        f,
+                        "\nError: Builtin Function \"{}\" Is Not Registered\n{}\n",
+                        name,
+                        ce.span.error(ce.hint.as_ref())
+                    )
+                }
+                CodegenErrorKind::OffsetExceedsPushWidth(label, width, needed) => {
+                    write!(
+                        f,
+                        "\nError: Offset Of \"{}\" Needs A {} Byte Push, Exceeding The {} Bytes Reserved\n{}\n",
+                        label,
+                        needed,
+                        width,
+                        ce.span.error(ce.hint.as_ref())
+                    )
+                }
+                CodegenErrorKind::ImmutableInConstructor(name) => {
+                    write!(
+                        f,
+                        "\nError: Immutable \"{}\" Can Only Be Read In Runtime Code\n{}\n",
+                        name,
+                        ce.span.error(ce.hint.as_ref())
+                    )
+                }
+                CodegenErrorKind::OptimizedCircularCodesize(name) => {
+                    write!(
+                        f,
+                        "\nError: Circular __codesize({}) Can't Be Resolved With Passes Enabled\n{}\n",
+                        name,
+                        ce.span.error(ce.hint.as_ref())
+                    )
+                }
+                CodegenErrorKind::DynamicImmutableArgument(name) => {
+                    write!(
+                        f,
+                        "\nError: Immutable \"{}\" Is Filled From A Dynamically Sized Constructor Argument\n{}\n",
+                        name,
+                        ce.span.error(ce.hint.as_ref())
+                    )
+                }
+                CodegenErrorKind::MissingCodeTable(name) => {
+                    write!(
+                        f,
+                        "\nError: Missing Code Table \"{}\" To Return As The Runtime Code\n{}\n",
+                        name,
+                        ce.span.error(ce.hint.as_ref())
+                    )
+                }
+                CodegenErrorKind::StorageSlotCollision(first, second) => {
+                    write!(
+                        f,
+                        "\nError: Constants \"{}\" And \"{}\" Share A Storage Slot\n{}\n",
+                        first,
+                        second,
+                        ce.span.error(ce.hint.as_ref())
                     )
                 }
             },
+            CompilerError::CircularImport(cycle, spans) => {
+                write!(
+                    f,
+                    "\nError: Circular Import: {}\n{}\n",
+                    cycle.join(" -> "),
+                    spans.error(None)
+                )
+            }
+            CompilerError::RemoteImportError(rie) => match rie {
+                RemoteImportError::Disabled(url) => {
+                    write!(
+                        f,
+                        "\nError: Remote Import \"{url}\"\nRemote imports must be enabled with `--remote-imports`\n"
+                    )
+                }
+                RemoteImportError::FetchFailed(url, reason) => {
+                    write!(f, "\nError: Failed To Fetch \"{url}\"\n{reason}\n")
+                }
+                RemoteImportError::HashMismatch { url, expected, found } => {
+                    write!(
+                        f,
+                        "\nError: Content Hash Mismatch For \"{url}\"\nExpected: {expected}\nFound: {found}\n"
+                    )
+                }
+                RemoteImportError::Io(url, reason) => {
+                    write!(f, "\nError: Failed To Cache \"{url}\"\n{reason}\n")
+                }
+            },
+            CompilerError::Warning(w) => {
+                write!(f, "\nError{}: {}\n{}\n", w.lint_suffix(), w.kind, w.span.error(None))
+            }
             CompilerError::FailedCompiles(v) => {
                 v.iter().for_each(|ce| {
                     let _ = write!(f, "{ce}");
@@ -599,3 +1279,20 @@ impl fmt::Display for CompilerError {
         }
     }
 }
+
+impl fmt::Display for CompilerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Some(code) = self.code() else { return self.fmt_message(f) };
+        let message = MessageDisplay(self).to_string();
+        write!(f, "{}", message.replacen("Error: ", &format!("Error[{code}]: "), 1))
+    }
+}
+
+/// Displays an error without its code
+struct MessageDisplay<'a>(&'a CompilerError);
+
+impl fmt::Display for MessageDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt_message(f)
+    }
+}
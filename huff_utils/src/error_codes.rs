@@ -0,0 +1,1103 @@
+//! ## Error Codes
+//!
+//! Every error `huffc` reports has a stable code, like `H0101`, shown in its heading as
+//! `Error[H0101]: ...`. Codes are grouped by the stage raising them:
+//!
+//! | Codes   | Stage               |
+//! | ------- | ------------------- |
+//! | `H00xx` | Lexing              |
+//! | `H01xx` | Parsing             |
+//! | `H02xx` | Code generation     |
+//! | `H03xx` | Files and imports   |
+//!
+//! Codes are never reused once assigned. `huffc explain <code>` prints the extended explanation
+//! of a code, with an example of the error and how to fix it.
+
+/// The extended explanation of an error code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorCode {
+    /// The code, e.g. `H0101`
+    pub code: &'static str,
+    /// A short title of the error
+    pub title: &'static str,
+    /// What causes the error, with an example and how to fix it
+    pub explanation: &'static str,
+}
+
+/// Gets the explanation of an error code, case-insensitively
+pub fn explain(code: &str) -> Option<&'static ErrorCode> {
+    ERROR_CODES.iter().find(|c| c.code.eq_ignore_ascii_case(code.trim()))
+}
+
+/// The explanations of all error codes, ordered by code
+pub const ERROR_CODES: &[ErrorCode] = &[
+    // Lexing
+    ErrorCode {
+        code: "H0001",
+        title: "Unexpected end of file",
+        explanation: r#"The source ended in the middle of a token, most often an unterminated
+string or block comment.
+
+Erroneous example:
+
+    #include "./utils.huff
+
+Fix: close the string or comment.
+
+    #include "./utils.huff""#,
+    },
+    ErrorCode {
+        code: "H0002",
+        title: "Invalid character",
+        explanation: r#"The lexer found a character that can't start any Huff token.
+
+Erroneous example:
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x01 @ 0x02 add
+    }
+
+Fix: remove the character, or turn the line into a comment with `//`."#,
+    },
+    ErrorCode {
+        code: "H0003",
+        title: "Invalid array size",
+        explanation: r#"The size of an array type in a function, event or error definition isn't a
+number.
+
+Erroneous example:
+
+    #define function batch(uint256[n]) nonpayable returns ()
+
+Fix: use a fixed size, or `[]` for a dynamic array.
+
+    #define function batch(uint256[]) nonpayable returns ()"#,
+    },
+    ErrorCode {
+        code: "H0004",
+        title: "Invalid primitive type",
+        explanation: r#"A type in a function, event or error definition isn't a valid EVM type.
+
+Erroneous example:
+
+    #define function transfer(address, uint257) nonpayable returns ()
+
+Fix: use a valid type. `uint` and `int` sizes are multiples of 8 up to 256, `bytes` sizes go
+from 1 to 32.
+
+    #define function transfer(address, uint256) nonpayable returns ()"#,
+    },
+    ErrorCode {
+        code: "H0005",
+        title: "Unmatched directive",
+        explanation: r#"An `#else` or `#endif` directive has no opening `#ifdef` or `#ifndef`.
+
+Erroneous example:
+
+    #define constant OWNER = 0x01
+    #endif
+
+Fix: remove the directive, or open the conditional block it closes.
+
+    #ifdef TESTING
+    #define constant OWNER = 0x01
+    #endif"#,
+    },
+    ErrorCode {
+        code: "H0006",
+        title: "Unterminated conditional",
+        explanation: r#"An `#ifdef` or `#ifndef` block isn't closed by an `#endif`.
+
+Erroneous example:
+
+    #ifdef TESTING
+    #define constant OWNER = 0x01
+
+Fix: close the block.
+
+    #ifdef TESTING
+    #define constant OWNER = 0x01
+    #endif"#,
+    },
+    ErrorCode {
+        code: "H0007",
+        title: "Missing conditional flag",
+        explanation: r#"An `#ifdef` or `#ifndef` directive isn't followed by the name of a flag.
+
+Erroneous example:
+
+    #ifdef
+    #define constant OWNER = 0x01
+    #endif
+
+Fix: name the flag, as passed with `--define`.
+
+    #ifdef TESTING
+    #define constant OWNER = 0x01
+    #endif"#,
+    },
+    ErrorCode {
+        code: "H0008",
+        title: "Invalid literal",
+        explanation: r#"A numeric literal is longer than 32 bytes, or has misplaced `_` separators.
+
+Erroneous example:
+
+    #define constant MASK = 0x_ff__ff
+
+Fix: separate digits with single underscores, and keep literals within 32 bytes.
+
+    #define constant MASK = 0xff_ff"#,
+    },
+    // Parsing
+    ErrorCode {
+        code: "H0101",
+        title: "Invalid push",
+        explanation: r#"A `push` opcode isn't followed by a literal, or the literal doesn't fit in the
+number of bytes the opcode pushes.
+
+Erroneous example:
+
+    push1 0x0102
+
+Fix: use a push opcode large enough for the literal, or a bare literal, which `huffc` pushes
+with the smallest opcode.
+
+    push2 0x0102"#,
+    },
+    ErrorCode {
+        code: "H0102",
+        title: "Unexpected type",
+        explanation: r#"The parser expected a token of another type, e.g. a missing parenthesis,
+brace or keyword.
+
+Erroneous example:
+
+    #define macro MAIN() = takes(0) returns(0)
+        0x01
+    }
+
+Fix: add the expected token, as shown in the error's hint.
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x01
+    }"#,
+    },
+    ErrorCode {
+        code: "H0103",
+        title: "Argument name is an EVM type",
+        explanation: r#"An argument is named after an EVM type, which is reserved.
+
+Erroneous example:
+
+    #define function transfer(address to, uint256 uint256) nonpayable returns ()
+
+Fix: rename the argument.
+
+    #define function transfer(address to, uint256 amount) nonpayable returns ()"#,
+    },
+    ErrorCode {
+        code: "H0104",
+        title: "Invalid definition",
+        explanation: r#"`#define` isn't followed by a definition keyword: `function`, `event`,
+`error`, `constant`, `macro`, `fn`, `test`, `table` or `jumptable`.
+
+Erroneous example:
+
+    #define const OWNER = 0x01
+
+Fix: use a definition keyword.
+
+    #define constant OWNER = 0x01"#,
+    },
+    ErrorCode {
+        code: "H0105",
+        title: "Invalid constant value",
+        explanation: r#"A constant is assigned something other than a literal, `FREE_STORAGE_POINTER()`
+or a constant expression.
+
+Erroneous example:
+
+    #define constant OWNER = caller
+
+Fix: assign a literal, or compute the value at runtime in a macro.
+
+    #define constant OWNER = 0x01"#,
+    },
+    ErrorCode {
+        code: "H0106",
+        title: "Invalid token in macro body",
+        explanation: r#"A macro body contains a token that isn't an opcode, literal, label,
+invocation, argument or constant reference.
+
+Erroneous example:
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x01 "one"
+    }
+
+Fix: remove the token, or replace it with its bytes.
+
+    #define macro MAIN() = takes(0) returns(0) {
+        0x01 0x6f6e65
+    }"#,
+    },
+    ErrorCode {
+        code: "H0107",
+        title: "Invalid token in label definition",
+        explanation: r#"The body of a label contains a token that isn't valid in a macro body.
+
+Erroneous example:
+
+    done:
+        "done"
+
+Fix: remove the token, or replace it with valid statements.
+
+    done:
+        0x00 dup1 revert"#,
+    },
+    ErrorCode {
+        code: "H0108",
+        title: "Invalid single argument",
+        explanation: r#"`takes` or `returns` isn't given a number of stack items.
+
+Erroneous example:
+
+    #define macro ADD() = takes(a) returns(1) {
+        add
+    }
+
+Fix: pass the number of stack items the macro takes or returns.
+
+    #define macro ADD() = takes(2) returns(1) {
+        add
+    }"#,
+    },
+    ErrorCode {
+        code: "H0109",
+        title: "Invalid table body token",
+        explanation: r#"A table contains a token that isn't a label or code literal.
+
+Erroneous example:
+
+    #define jumptable TABLE {
+        0x01
+    }
+
+Fix: list labels in jump tables, and code in code tables.
+
+    #define jumptable TABLE {
+        first second
+    }"#,
+    },
+    ErrorCode {
+        code: "H0110",
+        title: "Invalid constant",
+        explanation: r#"A constant reference in square brackets isn't a constant name.
+
+Erroneous example:
+
+    [0x01]
+
+Fix: reference a defined constant by name.
+
+    #define constant SLOT = 0x01
+
+    [SLOT] sload"#,
+    },
+    ErrorCode {
+        code: "H0111",
+        title: "Invalid argument call",
+        explanation: r#"An argument reference in angle brackets isn't an argument name.
+
+Erroneous example:
+
+    #define macro STORE(slot) = takes(1) returns(0) {
+        <0x01> sstore
+    }
+
+Fix: reference one of the macro's arguments by name.
+
+    #define macro STORE(slot) = takes(1) returns(0) {
+        <slot> sstore
+    }"#,
+    },
+    ErrorCode {
+        code: "H0112",
+        title: "Invalid name",
+        explanation: r#"A definition's name isn't an identifier.
+
+Erroneous example:
+
+    #define macro 0x01() = takes(0) returns(0) {}
+
+Fix: name it with an identifier.
+
+    #define macro HELPER() = takes(0) returns(0) {}"#,
+    },
+    ErrorCode {
+        code: "H0113",
+        title: "Invalid arguments",
+        explanation: r#"An argument list contains a token that's neither a type nor a name.
+
+Erroneous example:
+
+    #define function transfer(address, 0x01) nonpayable returns ()
+
+Fix: list types, optionally followed by names.
+
+    #define function transfer(address to, uint256 amount) nonpayable returns ()"#,
+    },
+    ErrorCode {
+        code: "H0114",
+        title: "Invalid uint size",
+        explanation: r#"The size of a `uint` type isn't a multiple of 8 between 8 and 256.
+
+Erroneous example:
+
+    #define event Deposit(uint7)
+
+Fix: use a valid size.
+
+    #define event Deposit(uint8)"#,
+    },
+    ErrorCode {
+        code: "H0115",
+        title: "Invalid bytes size",
+        explanation: r#"The size of a fixed `bytes` type isn't between 1 and 32.
+
+Erroneous example:
+
+    #define event Deposit(bytes33)
+
+Fix: use a valid size, or `bytes` for dynamic bytes.
+
+    #define event Deposit(bytes32)"#,
+    },
+    ErrorCode {
+        code: "H0116",
+        title: "Invalid int size",
+        explanation: r#"The size of an `int` type isn't a multiple of 8 between 8 and 256.
+
+Erroneous example:
+
+    #define event Deposit(int250)
+
+Fix: use a valid size.
+
+    #define event Deposit(int256)"#,
+    },
+    ErrorCode {
+        code: "H0117",
+        title: "Invalid macro arguments",
+        explanation: r#"A macro invocation is passed something that isn't a literal, identifier,
+argument or constant reference.
+
+Erroneous example:
+
+    STORE("owner")
+
+Fix: pass a valid argument.
+
+    STORE([OWNER_SLOT])"#,
+    },
+    ErrorCode {
+        code: "H0118",
+        title: "Invalid return arguments",
+        explanation: r#"The return arguments of a function definition are invalid.
+
+Erroneous example:
+
+    #define function balanceOf(address) view returns uint256
+
+Fix: wrap the return types in parentheses.
+
+    #define function balanceOf(address) view returns (uint256)"#,
+    },
+    ErrorCode {
+        code: "H0119",
+        title: "Invalid import path",
+        explanation: r#"An `#include` path isn't a valid path to a Huff file.
+
+Erroneous example:
+
+    #include utils
+
+Fix: include the file's path as a string.
+
+    #include "./utils.huff""#,
+    },
+    ErrorCode {
+        code: "H0120",
+        title: "Invalid decorator flag",
+        explanation: r#"A test decorator uses an unknown flag. The known flags are `calldata`,
+`value`, `fuzz` and `seed`.
+
+Erroneous example:
+
+    #[gas(0x01)]
+    #define test MY_TEST() = {}
+
+Fix: use a known flag.
+
+    #[value(0x01)]
+    #define test MY_TEST() = {}"#,
+    },
+    ErrorCode {
+        code: "H0121",
+        title: "Invalid decorator flag argument",
+        explanation: r#"A test decorator flag is passed an argument of the wrong type: `calldata`
+takes a string, the other flags a literal.
+
+Erroneous example:
+
+    #[calldata(0x01)]
+    #define test MY_TEST() = {}
+
+Fix: pass an argument of the right type.
+
+    #[calldata("0x01")]
+    #define test MY_TEST() = {}"#,
+    },
+    ErrorCode {
+        code: "H0122",
+        title: "Invalid import selection",
+        explanation: r#"A selective import names a definition the imported file doesn't define.
+
+Erroneous example:
+
+    #include { TRANSFRE } from "./utils.huff"
+
+Fix: select definitions of the imported file.
+
+    #include { TRANSFER } from "./utils.huff""#,
+    },
+    ErrorCode {
+        code: "H0123",
+        title: "Duplicate definition",
+        explanation: r#"A macro, constant, table or function is defined more than once, including
+across included files.
+
+Erroneous example:
+
+    #define constant OWNER = 0x01
+    #define constant OWNER = 0x02
+
+Fix: rename or remove one of the definitions.
+
+    #define constant OWNER = 0x01
+    #define constant ADMIN = 0x02"#,
+    },
+    ErrorCode {
+        code: "H0124",
+        title: "Empty token stream",
+        explanation: r#"The parser was given no tokens. The lexer always ends its tokens with an
+EOF token, so this only happens when embedding the parser with tokens from elsewhere.
+
+Fix: lex the source with `huff_lexer::Lexer` and pass all of its tokens to the parser."#,
+    },
+    // Code generation
+    ErrorCode {
+        code: "H0201",
+        title: "Locking error",
+        explanation: r#"The compiler failed to lock a shared definition, because another thread
+panicked while holding it.
+
+This is a bug in `huffc`. Please report it with the source that triggers it."#,
+    },
+    ErrorCode {
+        code: "H0202",
+        title: "Storage pointers not derived",
+        explanation: r#"A `FREE_STORAGE_POINTER()` constant was used before the storage pointers were
+derived.
+
+This is a bug in `huffc`, or in a tool generating code without deriving the storage pointers
+of the contract first."#,
+    },
+    ErrorCode {
+        code: "H0203",
+        title: "Invalid macro statement",
+        explanation: r#"A statement can't be compiled where it's used, e.g. a label definition
+containing another label definition, or a builtin function outside of a macro body.
+
+Fix: move the statement into a macro body, outside of any label."#,
+    },
+    ErrorCode {
+        code: "H0204",
+        title: "Missing macro definition",
+        explanation: r#"A macro is referenced but never defined, most often the `MAIN` macro.
+
+Erroneous example:
+
+    #define macro HELPER() = takes(0) returns(0) {}
+
+Fix: define the macro, or pass the right name with `--alt-main`.
+
+    #define macro MAIN() = takes(0) returns(0) {
+        HELPER()
+    }"#,
+    },
+    ErrorCode {
+        code: "H0205",
+        title: "Missing function interface",
+        explanation: r#"`__FUNC_SIG` is passed a function that isn't defined.
+
+Erroneous example:
+
+    __FUNC_SIG(transfer)
+
+Fix: define the function, or pass its signature as a string.
+
+    #define function transfer(address, uint256) nonpayable returns ()
+
+    __FUNC_SIG(transfer)"#,
+    },
+    ErrorCode {
+        code: "H0206",
+        title: "Missing event interface",
+        explanation: r#"`__EVENT_HASH` is passed an event that isn't defined.
+
+Erroneous example:
+
+    __EVENT_HASH(Transfer)
+
+Fix: define the event, or pass its signature as a string.
+
+    #define event Transfer(address, address, uint256)
+
+    __EVENT_HASH(Transfer)"#,
+    },
+    ErrorCode {
+        code: "H0207",
+        title: "Missing constant definition",
+        explanation: r#"A constant is referenced but never defined.
+
+Erroneous example:
+
+    [OWNER] sload
+
+Fix: define the constant, or pass it with `--constants`.
+
+    #define constant OWNER = FREE_STORAGE_POINTER()
+
+    [OWNER] sload"#,
+    },
+    ErrorCode {
+        code: "H0208",
+        title: "Missing error definition",
+        explanation: r#"`__ERROR` is passed an error that isn't defined.
+
+Erroneous example:
+
+    __ERROR(Unauthorized)
+
+Fix: define the error.
+
+    #define error Unauthorized()
+
+    __ERROR(Unauthorized)"#,
+    },
+    ErrorCode {
+        code: "H0209",
+        title: "ABI generation failure",
+        explanation: r#"The ABI of the contract couldn't be generated, e.g. because a function uses
+a type that can't be represented in the ABI.
+
+Fix: check the types of the contract's functions, events and errors."#,
+    },
+    ErrorCode {
+        code: "H0210",
+        title: "Unmatched jump label",
+        explanation: r#"A label is jumped to, or listed in a jump table, but never defined.
+
+Erroneous example:
+
+    done jump
+
+Fix: define the label in the macro, or one of its callers.
+
+    done jump
+    done:
+        stop"#,
+    },
+    ErrorCode {
+        code: "H0211",
+        title: "IO error",
+        explanation: r#"Reading or writing a file failed while generating the contract's artifacts.
+
+Fix: check the path and permissions of the output directory."#,
+    },
+    ErrorCode {
+        code: "H0212",
+        title: "Unknown argument call type",
+        explanation: r#"An argument reference resolved to a value that can't be compiled, e.g. an
+argument passed down from a macro that wasn't invoked with it.
+
+Fix: pass a literal, label, opcode or constant to the macro."#,
+    },
+    ErrorCode {
+        code: "H0213",
+        title: "Missing macro invocation",
+        explanation: r#"The invocation of a macro couldn't be found while resolving its arguments.
+
+This is a bug in `huffc`. Please report it with the source that triggers it."#,
+    },
+    ErrorCode {
+        code: "H0214",
+        title: "Invalid macro invocation",
+        explanation: r#"A macro invokes a macro that isn't defined.
+
+Erroneous example:
+
+    #define macro MAIN() = takes(0) returns(0) {
+        HLEPER()
+    }
+
+Fix: invoke a defined macro.
+
+    #define macro MAIN() = takes(0) returns(0) {
+        HELPER()
+    }"#,
+    },
+    ErrorCode {
+        code: "H0215",
+        title: "Usize conversion",
+        explanation: r#"A size or offset doesn't fit in the target's pointer size, e.g. the size of a
+table in very large bytecode.
+
+Fix: reduce the size of the contract."#,
+    },
+    ErrorCode {
+        code: "H0216",
+        title: "Invalid arguments",
+        explanation: r#"A builtin function or constructor argument is passed invalid arguments.
+
+Erroneous example:
+
+    __tablesize(OWNER)
+
+Fix: pass the arguments the builtin expects, as described in the error.
+
+    __tablesize(TABLE)"#,
+    },
+    ErrorCode {
+        code: "H0217",
+        title: "Invalid hex",
+        explanation: r#"A `__VERBATIM` argument or bytecode literal isn't valid hex.
+
+Erroneous example:
+
+    __VERBATIM("0xzz")
+
+Fix: pass an even number of hex digits.
+
+    __VERBATIM("0x1234")"#,
+    },
+    ErrorCode {
+        code: "H0218",
+        title: "Invalid table statement",
+        explanation: r#"A table contains a statement that can't be compiled into it.
+
+Fix: list labels in jump tables, and code in code tables."#,
+    },
+    ErrorCode {
+        code: "H0219",
+        title: "Invalid code length",
+        explanation: r#"A code table entry has an odd number of hex digits, so it isn't whole bytes.
+
+Erroneous example:
+
+    #define table CODE {
+        0x123
+    }
+
+Fix: pad the entry to whole bytes.
+
+    #define table CODE {
+        0x0123
+    }"#,
+    },
+    ErrorCode {
+        code: "H0220",
+        title: "Test invocation",
+        explanation: r#"A macro invokes a test, which can only be run with `huffc test`.
+
+Erroneous example:
+
+    #define macro MAIN() = takes(0) returns(0) {
+        MY_TEST()
+    }
+
+Fix: move the shared code into a macro invoked by both."#,
+    },
+    ErrorCode {
+        code: "H0221",
+        title: "Invalid dynamic argument index",
+        explanation: r#"A constructor argument index is out of range.
+
+Fix: pass as many constructor arguments as the contract reads."#,
+    },
+    ErrorCode {
+        code: "H0222",
+        title: "Circular constant reference",
+        explanation: r#"A constant expression references itself, directly or through other constants.
+
+Erroneous example:
+
+    #define constant A = B + 0x01
+    #define constant B = A
+
+Fix: break the cycle.
+
+    #define constant A = B + 0x01
+    #define constant B = 0x01"#,
+    },
+    ErrorCode {
+        code: "H0223",
+        title: "Missing environment variable",
+        explanation: r#"An `env` constant references an environment variable that isn't set.
+
+Erroneous example:
+
+    #define constant OWNER = env("OWNER")
+
+Fix: set the variable when compiling, e.g. `OWNER=0x01 huffc src/Token.huff`."#,
+    },
+    ErrorCode {
+        code: "H0224",
+        title: "Invalid environment variable",
+        explanation: r#"An `env` constant references an environment variable that isn't a valid
+literal.
+
+Erroneous example:
+
+    OWNER=owner huffc src/Token.huff
+
+Fix: set the variable to a hex or decimal literal, e.g. `OWNER=0x01 huffc src/Token.huff`."#,
+    },
+    ErrorCode {
+        code: "H0225",
+        title: "Immutables with custom bootstrap",
+        explanation: r#"Immutables are used with a constructor that returns the runtime code itself,
+so they can't be written into it.
+
+Fix: let `huffc` append the bootstrap code, by not returning from the constructor, or replace
+the immutables with constants."#,
+    },
+    ErrorCode {
+        code: "H0226",
+        title: "Storage slot collision",
+        explanation: r#"Two constants are assigned the same storage slot.
+
+Erroneous example:
+
+    #define constant OWNER = STORAGE_SLOT(0x00)
+    #define constant ADMIN = STORAGE_SLOT(0x00)
+
+Fix: give the constants distinct slots.
+
+    #define constant OWNER = STORAGE_SLOT(0x00)
+    #define constant ADMIN = STORAGE_SLOT(0x01)"#,
+    },
+    ErrorCode {
+        code: "H0227",
+        title: "Failed assertion",
+        explanation: r#"A compile-time assertion doesn't hold.
+
+Erroneous example:
+
+    #define constant DECIMALS = 0x13
+    #assert [DECIMALS] <= 0x12
+
+Fix: change the values so the assertion holds."#,
+    },
+    ErrorCode {
+        code: "H0228",
+        title: "Invalid repeat count",
+        explanation: r#"A `__REPEAT` count isn't a number, or exceeds the maximum number of
+iterations.
+
+Erroneous example:
+
+    __REPEAT(0x010000) { caller }
+
+Fix: lower the count, or loop at runtime."#,
+    },
+    ErrorCode {
+        code: "H0229",
+        title: "Duplicate label",
+        explanation: r#"A label that is jumped to is defined more than once, so the jump is ambiguous.
+
+Erroneous example:
+
+    done jump
+    done:
+        stop
+    done:
+        stop
+
+Fix: rename or remove one of the labels."#,
+    },
+    ErrorCode {
+        code: "H0230",
+        title: "Invalid macro argument count",
+        explanation: r#"A macro is invoked with a different number of arguments than it has
+parameters.
+
+Erroneous example:
+
+    #define macro STORE(slot) = takes(1) returns(0) {
+        <slot> sstore
+    }
+
+    STORE()
+
+Fix: pass an argument for each parameter.
+
+    STORE([OWNER])
+
+Only the number of arguments is checked. Any kind of argument can be expanded by `<slot>`, a
+label argument that doesn't exist is reported as an undefined label argument (H0233)."#,
+    },
+    ErrorCode {
+        code: "H0231",
+        title: "Recursive macro",
+        explanation: r#"A macro invokes itself, directly or through other macros, so its expansion
+never ends.
+
+Erroneous example:
+
+    #define macro LOOP() = takes(0) returns(0) {
+        LOOP()
+    }
+
+Fix: loop at runtime with a label and a jump.
+
+    #define macro LOOP() = takes(0) returns(0) {
+        start:
+            start jump
+    }"#,
+    },
+    ErrorCode {
+        code: "H0232",
+        title: "Maximum expansion depth",
+        explanation: r#"Macro invocations are nested deeper than the maximum expansion depth.
+
+Fix: flatten the invocations, or turn some macros into functions with `#define fn`."#,
+    },
+    ErrorCode {
+        code: "H0233",
+        title: "Undefined label argument",
+        explanation: r#"A label passed as a macro argument isn't defined by the invoking macro or its
+callers.
+
+Erroneous example:
+
+    #define macro MAIN() = takes(0) returns(0) {
+        JUMP_TO(done)
+    }
+
+Fix: define the label.
+
+    #define macro MAIN() = takes(0) returns(0) {
+        JUMP_TO(done)
+        done:
+            stop
+    }"#,
+    },
+    ErrorCode {
+        code: "H0234",
+        title: "Invalid jump destination",
+        explanation: r#"A label that is jumped to doesn't end up as a valid `JUMPDEST` in the
+bytecode, e.g. because it's inside pushed data.
+
+Fix: check the label is defined in code, not in a `__VERBATIM` or table."#,
+    },
+    ErrorCode {
+        code: "H0235",
+        title: "Undefined argument",
+        explanation: r#"An argument call references a parameter the macro doesn't declare.
+
+Erroneous example:
+
+    #define macro TRANSFER(amount) = takes(0) returns(0) {
+        <amout>
+    }
+
+Fix: use one of the macro's parameters, or add it to the parameter list.
+
+    #define macro TRANSFER(amount) = takes(0) returns(0) {
+        <amount>
+    }"#,
+    },
+    ErrorCode {
+        code: "H0236",
+        title: "Missing builtin definition",
+        explanation: r#"A custom builtin function is called, but isn't registered with the contract
+being compiled, e.g. because the tokens were lexed with a different set of custom builtins.
+
+Fix: register the builtin with the `builtins` of the compiler, which are used for both lexing
+and code generation."#,
+    },
+    ErrorCode {
+        code: "H0237",
+        title: "Offset exceeds push width",
+        explanation: r#"The offset of a label or table doesn't fit in the push reserved for it, e.g.
+a jump destination past the first 64 KiB of code, which is pushed with a `PUSH2`. The bytecode
+would otherwise jump to a truncated offset.
+
+Fix: make the code smaller, or move the label or table closer to the start of the code."#,
+    },
+    ErrorCode {
+        code: "H0238",
+        title: "Immutable in constructor",
+        explanation: r#"An immutable is read in constructor code. Immutables are written into the
+runtime code by the bootstrap code, which runs after the constructor, so there is no value to
+read yet.
+
+Erroneous example:
+
+    #define immutable OWNER
+    #define macro CONSTRUCTOR() = takes(0) returns(0) {
+        [OWNER] 0x00 sstore
+    }
+
+Fix: read the value from the constructor arguments instead, e.g. with `__CODECOPY_DYN_ARG` or
+`codecopy`, and only read the immutable in runtime code."#,
+    },
+    ErrorCode {
+        code: "H0239",
+        title: "Circular codesize with passes",
+        explanation: r#"`__codesize` is passed the macro it's invoked in, or one of the macros
+enclosing it, while passes like the optimizer run over the code. The passes change the size of
+the code after the size was pushed, so it can't be resolved.
+
+Erroneous example:
+
+    #define macro MAIN() = takes(0) returns(0) {
+        __codesize(MAIN) 0x00 mstore
+    }
+
+compiled with `huffc -z`.
+
+Fix: compile without the passes, or use the `codesize` opcode at runtime."#,
+    },
+    ErrorCode {
+        code: "H0240",
+        title: "Dynamic immutable argument",
+        explanation: r#"An immutable is filled from a dynamically sized constructor argument. The
+n-th immutable is filled with the head of the n-th constructor argument, which only holds the
+offset of a `string`, `bytes` or array value in the encoded arguments.
+
+Erroneous example:
+
+    #define function constructor(string, address) nonpayable returns ()
+    #define immutable NAME
+
+Fix: move the dynamically sized parameters after the ones filling immutables, and read them with
+`__CODECOPY_DYN_ARG` instead."#,
+    },
+    ErrorCode {
+        code: "H0241",
+        title: "Missing code table",
+        explanation: r#"The bootstrap is set to return a code table as the runtime code, with
+`--bootstrap-table`, but no code table of that name is defined.
+
+Erroneous example:
+
+    huffc ./src/Proxy.huff --bootstrap-table RUNTIME
+
+Fix: define the code table, e.g. `#define table RUNTIME { 0x60016000f3 }`."#,
+    },
+    // Files and imports
+    ErrorCode {
+        code: "H0301",
+        title: "Invalid file directory",
+        explanation: r#"The directory passed to `huffc` couldn't be read.
+
+Fix: check the path exists and is readable, e.g. `huffc ./src`."#,
+    },
+    ErrorCode {
+        code: "H0302",
+        title: "Unsupported file extension",
+        explanation: r#"A file passed to `huffc` isn't a `.huff` file.
+
+Erroneous example:
+
+    huffc src/Token.sol
+
+Fix: pass Huff files.
+
+    huffc src/Token.huff"#,
+    },
+    ErrorCode {
+        code: "H0303",
+        title: "File not found",
+        explanation: r#"A file passed to `huffc`, or included by another file, doesn't exist.
+
+Erroneous example:
+
+    #include "./utlis.huff"
+
+Fix: include the file with a path relative to the including file.
+
+    #include "./utils.huff""#,
+    },
+    ErrorCode {
+        code: "H0304",
+        title: "Path read failure",
+        explanation: r#"A path passed to `huffc` couldn't be read.
+
+Fix: check the path exists and is readable."#,
+    },
+    ErrorCode {
+        code: "H0305",
+        title: "Circular import",
+        explanation: r#"Files include each other, directly or through other files.
+
+Erroneous example:
+
+    // a.huff
+    #include "./b.huff"
+
+    // b.huff
+    #include "./a.huff"
+
+Fix: move the shared definitions into a file both include."#,
+    },
+    ErrorCode {
+        code: "H0306",
+        title: "Remote imports disabled",
+        explanation: r#"A file is included from a URL without enabling remote imports.
+
+Erroneous example:
+
+    #include "https://example.com/utils.huff"
+
+Fix: compile with `--remote-imports`, or vendor the file."#,
+    },
+    ErrorCode {
+        code: "H0307",
+        title: "Remote import fetch failure",
+        explanation: r#"A remote file couldn't be fetched.
+
+Fix: check the URL and your connection, or vendor the file."#,
+    },
+    ErrorCode {
+        code: "H0308",
+        title: "Remote import hash mismatch",
+        explanation: r#"The contents of a remote file don't match the hash pinned in the lockfile,
+so they changed since they were first fetched.
+
+Fix: review the changes, then remove the file's entry from the lockfile to pin the new
+contents."#,
+    },
+    ErrorCode {
+        code: "H0309",
+        title: "Remote import cache failure",
+        explanation: r#"The lockfile or the cache of remote files couldn't be read or written.
+
+Fix: check the permissions of the lockfile and the cache directory."#,
+    },
+];
@@ -1,4 +1,5 @@
 use phf::phf_map;
+use serde::Serialize;
 use std::fmt;
 use strum_macros::EnumString;
 
@@ -309,8 +310,9 @@ pub static OPCODES_MAP: phf::Map<&'static str, Opcode> = phf_map! {
 
 /// EVM Opcodes
 /// References <https://evm.codes>
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, EnumString)]
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, EnumString)]
 #[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
 pub enum Opcode {
     /// Halts execution.
     Stop,
@@ -610,6 +612,161 @@ pub enum Opcode {
 }
 
 impl Opcode {
+    /// The description of an Opcode, eg. for hovers in editors
+    pub fn description(&self) -> &'static str {
+        match self {
+            Opcode::Stop => "Halts execution",
+            Opcode::Add => "Addition operation",
+            Opcode::Mul => "Multiplication Operation",
+            Opcode::Sub => "Subtraction Operation",
+            Opcode::Div => "Integer Division Operation",
+            Opcode::Sdiv => "Signed Integer Division Operation",
+            Opcode::Mod => "Modulo Remainder Operation",
+            Opcode::Smod => "Signed Modulo Remainder Operation",
+            Opcode::Addmod => "Modulo Addition Operation",
+            Opcode::Mulmod => "Modulo Multiplication Operation",
+            Opcode::Exp => "Exponential Operation",
+            Opcode::Signextend => "Extend Length of Two's Complement Signed Integer",
+            Opcode::Lt => "Less-than Comparison",
+            Opcode::Gt => "Greater-than Comparison",
+            Opcode::Slt => "Signed Less-than Comparison",
+            Opcode::Sgt => "Signed Greater-than Comparison",
+            Opcode::Eq => "Equality Comparison",
+            Opcode::Iszero => "Not Operation",
+            Opcode::And => "Bitwise AND Operation",
+            Opcode::Or => "Bitwise OR Operation",
+            Opcode::Xor => "Bitwise XOR Operation",
+            Opcode::Not => "Bitwise NOT Operation",
+            Opcode::Byte => "Retrieve Single Byte from Word",
+            Opcode::Shl => "Left Shift Operation",
+            Opcode::Shr => "Right Shift Operation",
+            Opcode::Sar => "Arithmetic Shift Right Operation",
+            Opcode::Sha3 => "Compute the Keccak-256 hash of a 32-byte word",
+            Opcode::Address => "Address of currently executing account",
+            Opcode::Balance => "Balance of a given account",
+            Opcode::Origin => "Address of execution origination",
+            Opcode::Caller => "Address of the caller",
+            Opcode::Callvalue => "Value of the call",
+            Opcode::Calldataload => "Loads Calldata",
+            Opcode::Calldatasize => "Size of the Calldata",
+            Opcode::Calldatacopy => "Copies the Calldata to Memory",
+            Opcode::Codesize => "Size of the Executing Code",
+            Opcode::Codecopy => "Copies Executing Code to Memory",
+            Opcode::Gasprice => "Current Price of Gas",
+            Opcode::Extcodesize => "Size of an Account's Code",
+            Opcode::Extcodecopy => "Copies an Account's Code to Memory",
+            Opcode::Returndatasize => "Size of Output Data from Previous Call",
+            Opcode::Returndatacopy => "Copies Output Data from Previous Call to Memory",
+            Opcode::Blockhash => "Hash of a Block from the most recent 256 blocks",
+            Opcode::Coinbase => "The Current Blocks Beneficiary Address",
+            Opcode::Timestamp => "The Current Blocks Timestamp",
+            Opcode::Number => "The Current Blocks Number",
+            Opcode::Difficulty => "The Current Blocks Difficulty",
+            Opcode::Prevrandao => "Pseudorandomness from the Beacon Chain",
+            Opcode::Gaslimit => "The Current Blocks Gas Limit",
+            Opcode::Chainid => "The Chain ID",
+            Opcode::Selfbalance => "Balance of the Currently Executing Account",
+            Opcode::Basefee => "Base Fee",
+            Opcode::Pop => "Removes an Item from the Stack",
+            Opcode::Mload => "Loads a word from Memory",
+            Opcode::Mstore => "Stores a word in Memory",
+            Opcode::Mstore8 => "Stores a byte in Memory",
+            Opcode::Sload => "Load a word from Storage",
+            Opcode::Sstore => "Store a word in Storage",
+            Opcode::Jump => "Alter the Program Counter",
+            Opcode::Jumpi => "Conditionally Alter the Program Counter",
+            Opcode::Pc => "Value of the Program Counter Before the Current Instruction",
+            Opcode::Msize => "Size of Active Memory in Bytes",
+            Opcode::Gas => "Amount of available gas including the cost of the current instruction",
+            Opcode::Jumpdest => "Marks a valid destination for jumps",
+            Opcode::Push0 => "Places a zero on top of the stack",
+            Opcode::Push1 => "Places 1 byte item on top of the stack",
+            Opcode::Push2 => "Places 2 byte item on top of the stack",
+            Opcode::Push3 => "Places 3 byte item on top of the stack",
+            Opcode::Push4 => "Places 4 byte item on top of the stack",
+            Opcode::Push5 => "Places 5 byte item on top of the stack",
+            Opcode::Push6 => "Places 6 byte item on top of the stack",
+            Opcode::Push7 => "Places 7 byte item on top of the stack",
+            Opcode::Push8 => "Places 8 byte item on top of the stack",
+            Opcode::Push9 => "Places 9 byte item on top of the stack",
+            Opcode::Push10 => "Places 10 byte item on top of the stack",
+            Opcode::Push11 => "Places 11 byte item on top of the stack",
+            Opcode::Push12 => "Places 12 byte item on top of the stack",
+            Opcode::Push13 => "Places 13 byte item on top of the stack",
+            Opcode::Push14 => "Places 14 byte item on top of the stack",
+            Opcode::Push15 => "Places 15 byte item on top of the stack",
+            Opcode::Push16 => "Places 16 byte item on top of the stack",
+            Opcode::Push17 => "Places 17 byte item on top of the stack",
+            Opcode::Push18 => "Places 18 byte item on top of the stack",
+            Opcode::Push19 => "Places 19 byte item on top of the stack",
+            Opcode::Push20 => "Places 20 byte item on top of the stack",
+            Opcode::Push21 => "Places 21 byte item on top of the stack",
+            Opcode::Push22 => "Places 22 byte item on top of the stack",
+            Opcode::Push23 => "Places 23 byte item on top of the stack",
+            Opcode::Push24 => "Places 24 byte item on top of the stack",
+            Opcode::Push25 => "Places 25 byte item on top of the stack",
+            Opcode::Push26 => "Places 26 byte item on top of the stack",
+            Opcode::Push27 => "Places 27 byte item on top of the stack",
+            Opcode::Push28 => "Places 28 byte item on top of the stack",
+            Opcode::Push29 => "Places 29 byte item on top of the stack",
+            Opcode::Push30 => "Places 30 byte item on top of the stack",
+            Opcode::Push31 => "Places 31 byte item on top of the stack",
+            Opcode::Push32 => "Places 32 byte item on top of the stack",
+            Opcode::Dup1 => "Duplicates the first stack item",
+            Opcode::Dup2 => "Duplicates the 2nd stack item",
+            Opcode::Dup3 => "Duplicates the 3rd stack item",
+            Opcode::Dup4 => "Duplicates the 4th stack item",
+            Opcode::Dup5 => "Duplicates the 5th stack item",
+            Opcode::Dup6 => "Duplicates the 6th stack item",
+            Opcode::Dup7 => "Duplicates the 7th stack item",
+            Opcode::Dup8 => "Duplicates the 8th stack item",
+            Opcode::Dup9 => "Duplicates the 9th stack item",
+            Opcode::Dup10 => "Duplicates the 10th stack item",
+            Opcode::Dup11 => "Duplicates the 11th stack item",
+            Opcode::Dup12 => "Duplicates the 12th stack item",
+            Opcode::Dup13 => "Duplicates the 13th stack item",
+            Opcode::Dup14 => "Duplicates the 14th stack item",
+            Opcode::Dup15 => "Duplicates the 15th stack item",
+            Opcode::Dup16 => "Duplicates the 16th stack item",
+            Opcode::Swap1 => "Exchange the top two stack items",
+            Opcode::Swap2 => "Exchange the first and third stack items",
+            Opcode::Swap3 => "Exchange the first and fourth stack items",
+            Opcode::Swap4 => "Exchange the first and fifth stack items",
+            Opcode::Swap5 => "Exchange the first and sixth stack items",
+            Opcode::Swap6 => "Exchange the first and seventh stack items",
+            Opcode::Swap7 => "Exchange the first and eighth stack items",
+            Opcode::Swap8 => "Exchange the first and ninth stack items",
+            Opcode::Swap9 => "Exchange the first and tenth stack items",
+            Opcode::Swap10 => "Exchange the first and eleventh stack items",
+            Opcode::Swap11 => "Exchange the first and twelfth stack items",
+            Opcode::Swap12 => "Exchange the first and thirteenth stack items",
+            Opcode::Swap13 => "Exchange the first and fourteenth stack items",
+            Opcode::Swap14 => "Exchange the first and fifteenth stack items",
+            Opcode::Swap15 => "Exchange the first and sixteenth stack items",
+            Opcode::Swap16 => "Exchange the first and seventeenth stack items",
+            Opcode::Log0 => "Append Log Record with no Topics",
+            Opcode::Log1 => "Append Log Record with 1 Topic",
+            Opcode::Log2 => "Append Log Record with 2 Topics",
+            Opcode::Log3 => "Append Log Record with 3 Topics",
+            Opcode::Log4 => "Append Log Record with 4 Topics",
+            Opcode::TLoad => "Transaction-persistent, but storage-ephemeral variable load",
+            Opcode::TStore => "Transaction-persistent, but storage-ephemeral variable store",
+            Opcode::Create => "Create a new account with associated code",
+            Opcode::Call => "Message-call into an account",
+            Opcode::Callcode => "Message-call into this account with an alternative accounts code",
+            Opcode::Return => "Halt execution, returning output data",
+            Opcode::Delegatecall => {
+                "Message-call into this account with an alternative accounts code, persisting the sender and value"
+            }
+            Opcode::Create2 => "Create a new account with associated code",
+            Opcode::Staticcall => "Static Message-call into an account",
+            Opcode::Revert => "Halt execution, reverting state changes, but returning data and remaining gas",
+            Opcode::Invalid => "Invalid Instruction",
+            Opcode::Selfdestruct => "Halt Execution and Register Account for later deletion",
+            Opcode::Extcodehash => "Get hash of an account’s code",
+        }
+    }
+
     /// Translates an Opcode into a string
     pub fn string(&self) -> String {
         let opcode_str = match self {
@@ -764,6 +921,12 @@ impl Opcode {
         opcode_str.to_string()
     }
 
+    /// Returns the opcode of a byte, if it's a known opcode
+    pub fn from_byte(byte: u8) -> Option<Opcode> {
+        let hex = format!("{byte:02x}");
+        OPCODES_MAP.values().find(|o| o.string() == hex).copied()
+    }
+
     /// Returns true if the current opcode is a push opcode that takes a literal value
     pub fn is_value_push(&self) -> bool {
         matches!(
@@ -803,6 +966,146 @@ impl Opcode {
         )
     }
 
+    /// Returns the number of stack items the opcode takes and returns
+    pub fn stack_effect(&self) -> (usize, usize) {
+        let byte = u8::from_str_radix(&self.string(), 16).unwrap_or_default();
+        match self {
+            Opcode::Stop | Opcode::Jumpdest | Opcode::Invalid => (0, 0),
+            Opcode::Address |
+            Opcode::Origin |
+            Opcode::Caller |
+            Opcode::Callvalue |
+            Opcode::Calldatasize |
+            Opcode::Codesize |
+            Opcode::Gasprice |
+            Opcode::Returndatasize |
+            Opcode::Coinbase |
+            Opcode::Timestamp |
+            Opcode::Number |
+            Opcode::Difficulty |
+            Opcode::Prevrandao |
+            Opcode::Gaslimit |
+            Opcode::Chainid |
+            Opcode::Selfbalance |
+            Opcode::Basefee |
+            Opcode::Pc |
+            Opcode::Msize |
+            Opcode::Gas => (0, 1),
+            Opcode::Iszero |
+            Opcode::Not |
+            Opcode::Balance |
+            Opcode::Calldataload |
+            Opcode::Extcodesize |
+            Opcode::Extcodehash |
+            Opcode::Blockhash |
+            Opcode::Mload |
+            Opcode::Sload => (1, 1),
+            Opcode::Pop | Opcode::Jump | Opcode::Selfdestruct => (1, 0),
+            Opcode::Mstore |
+            Opcode::Mstore8 |
+            Opcode::Sstore |
+            Opcode::Jumpi |
+            Opcode::Return |
+            Opcode::Revert => (2, 0),
+            Opcode::Addmod | Opcode::Mulmod => (3, 1),
+            Opcode::Calldatacopy | Opcode::Codecopy | Opcode::Returndatacopy => (3, 0),
+            Opcode::Extcodecopy => (4, 0),
+            Opcode::Log0 | Opcode::Log1 | Opcode::Log2 | Opcode::Log3 | Opcode::Log4 => {
+                (usize::from(byte - 0xa0) + 2, 0)
+            }
+            Opcode::Create => (3, 1),
+            Opcode::Create2 => (4, 1),
+            Opcode::Call | Opcode::Callcode => (7, 1),
+            Opcode::Delegatecall | Opcode::Staticcall => (6, 1),
+            o if o.is_value_push() || *o == Opcode::Push0 => (0, 1),
+            // Dup1 - Dup16
+            _ if (0x80..=0x8f).contains(&byte) => {
+                let n = usize::from(byte - 0x7f);
+                (n, n + 1)
+            }
+            // Swap1 - Swap16
+            _ if (0x90..=0x9f).contains(&byte) => {
+                let n = usize::from(byte - 0x8e);
+                (n, n)
+            }
+            // The remaining arithmetic, comparison and bitwise operations
+            _ => (2, 1),
+        }
+    }
+
+    /// Returns the static gas cost of the opcode, without the dynamic costs of memory expansion,
+    /// cold accesses, copied words or logged data
+    pub fn static_gas(&self) -> u64 {
+        let byte = u8::from_str_radix(&self.string(), 16).unwrap_or_default();
+        match self {
+            Opcode::Stop | Opcode::Return | Opcode::Revert | Opcode::Invalid => 0,
+            Opcode::Jumpdest => 1,
+            Opcode::Address |
+            Opcode::Origin |
+            Opcode::Caller |
+            Opcode::Callvalue |
+            Opcode::Calldatasize |
+            Opcode::Codesize |
+            Opcode::Gasprice |
+            Opcode::Returndatasize |
+            Opcode::Coinbase |
+            Opcode::Timestamp |
+            Opcode::Number |
+            Opcode::Difficulty |
+            Opcode::Prevrandao |
+            Opcode::Gaslimit |
+            Opcode::Chainid |
+            Opcode::Basefee |
+            Opcode::Pop |
+            Opcode::Pc |
+            Opcode::Msize |
+            Opcode::Gas |
+            Opcode::Push0 => 2,
+            Opcode::Mul |
+            Opcode::Div |
+            Opcode::Sdiv |
+            Opcode::Mod |
+            Opcode::Smod |
+            Opcode::Signextend |
+            Opcode::Selfbalance => 5,
+            Opcode::Addmod | Opcode::Mulmod | Opcode::Jump => 8,
+            Opcode::Exp | Opcode::Jumpi => 10,
+            Opcode::Blockhash => 20,
+            Opcode::Sha3 => 30,
+            // Warm accesses
+            Opcode::Balance |
+            Opcode::Extcodesize |
+            Opcode::Extcodecopy |
+            Opcode::Extcodehash |
+            Opcode::Sload |
+            Opcode::Sstore |
+            Opcode::Call |
+            Opcode::Callcode |
+            Opcode::Delegatecall |
+            Opcode::Staticcall => 100,
+            Opcode::Log0 | Opcode::Log1 | Opcode::Log2 | Opcode::Log3 | Opcode::Log4 => {
+                375 * (u64::from(byte - 0xa0) + 1)
+            }
+            Opcode::Selfdestruct => 5000,
+            Opcode::Create | Opcode::Create2 => 32000,
+            // The remaining arithmetic, comparison, bitwise, memory, push, dup and swap operations
+            _ => 3,
+        }
+    }
+
+    /// Returns true if execution doesn't continue with the next instruction
+    pub fn is_terminating(&self) -> bool {
+        matches!(
+            self,
+            Opcode::Stop |
+                Opcode::Jump |
+                Opcode::Return |
+                Opcode::Revert |
+                Opcode::Invalid |
+                Opcode::Selfdestruct
+        )
+    }
+
     /// Prefixes the literal if necessary
     pub fn prefix_push_literal(&self, literal: &str) -> String {
         if self.is_value_push() {
@@ -12,6 +12,12 @@ pub enum SupportedEVMVersions {
     Shanghai,
 }
 
+/// The maximum size of runtime code in bytes, as of EIP-170
+pub const MAX_CODE_SIZE: usize = 0x6000;
+
+/// The maximum size of initcode in bytes, as of EIP-3860 in Shanghai
+pub const MAX_INITCODE_SIZE: usize = 2 * MAX_CODE_SIZE;
+
 #[derive(Debug)]
 /// EVM Version
 pub struct EVMVersion {
@@ -28,6 +34,20 @@ impl EVMVersion {
     pub fn has_push0(&self) -> bool {
         self.version >= SupportedEVMVersions::Shanghai
     }
+
+    /// Whether the size of initcode is limited, as of EIP-3860 in Shanghai
+    pub fn has_initcode_limit(&self) -> bool {
+        self.version >= SupportedEVMVersions::Shanghai
+    }
+}
+
+impl std::fmt::Display for EVMVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.version {
+            SupportedEVMVersions::Paris => write!(f, "paris"),
+            SupportedEVMVersions::Shanghai => write!(f, "shanghai"),
+        }
+    }
 }
 
 impl Default for EVMVersion {
@@ -1,7 +1,8 @@
 use crate::{
     error::CompilerError,
-    files::FileSource,
+    files::{FileSource, Remapper},
     io::{unpack_files, UnpackError},
+    remote::{self, RemoteCache, RemoteImportError},
     time,
 };
 use std::{
@@ -19,6 +20,9 @@ pub trait FileProvider<'a>: Send + Sync + Debug {
 
     /// Takes a list of strings and returns a transformed list PathBufs.
     fn transform_paths(&self, sources: &[String]) -> Result<Vec<PathBuf>, CompilerError>;
+
+    /// Returns the import remappings configured for the provided files.
+    fn remapper(&self) -> Remapper;
 }
 
 /// A FileReader that reads files from the filesystem.
@@ -41,6 +45,10 @@ impl FileSystemFileProvider {
 impl<'a> FileProvider<'a> for FileSystemFileProvider {
     fn read_file(&self, pb: PathBuf) -> Result<Arc<FileSource>, CompilerError> {
         let file_loc = String::from(pb.to_string_lossy());
+        if remote::is_remote(&file_loc) {
+            tracing::error!(target: "core", "REMOTE IMPORTS DISABLED: \"{}\"!", file_loc);
+            return Err(CompilerError::RemoteImportError(RemoteImportError::Disabled(file_loc)))
+        }
         match std::fs::read_to_string(&file_loc) {
             Ok(source) => Ok(Arc::new(FileSource {
                 id: Uuid::new_v4(),
@@ -59,9 +67,9 @@ impl<'a> FileProvider<'a> for FileSystemFileProvider {
     fn transform_paths(&self, sources: &[String]) -> Result<Vec<PathBuf>, CompilerError> {
         let mut paths = vec![];
         for f in sources {
-            // If the file is huff, use the path, otherwise unpack
+            // If the file is huff or remote, use the path, otherwise unpack
             let ext = Path::new(&f).extension().unwrap_or_default();
-            if ext.eq("huff") {
+            if ext.eq("huff") || remote::is_remote(f) {
                 paths.push(Path::new(&f).to_path_buf())
             } else {
                 // Otherwise, override the source files and use all files in the provided dir
@@ -78,6 +86,51 @@ impl<'a> FileProvider<'a> for FileSystemFileProvider {
         }
         Ok(paths)
     }
+
+    fn remapper(&self) -> Remapper {
+        Remapper::new("./")
+    }
+}
+
+/// A FileReader that reads files from the filesystem, and fetches remote files.
+#[derive(Debug)]
+pub struct RemoteFileProvider {
+    /// The provider of local files
+    pub local: FileSystemFileProvider,
+    /// The cache of remote files
+    pub cache: RemoteCache,
+}
+
+impl RemoteFileProvider {
+    /// Creates a new instance of a RemoteFileProvider.
+    pub fn new(cache: RemoteCache) -> Self {
+        RemoteFileProvider { local: FileSystemFileProvider::new(), cache }
+    }
+}
+
+impl<'a> FileProvider<'a> for RemoteFileProvider {
+    fn read_file(&self, pb: PathBuf) -> Result<Arc<FileSource>, CompilerError> {
+        let url = String::from(pb.to_string_lossy());
+        if !remote::is_remote(&url) {
+            return self.local.read_file(pb)
+        }
+        let source = self.cache.get(&url).map_err(CompilerError::RemoteImportError)?;
+        Ok(Arc::new(FileSource {
+            id: Uuid::new_v4(),
+            path: url,
+            source: Some(source),
+            access: Some(time::get_current_time()),
+            dependencies: None,
+        }))
+    }
+
+    fn transform_paths(&self, sources: &[String]) -> Result<Vec<PathBuf>, CompilerError> {
+        self.local.transform_paths(sources)
+    }
+
+    fn remapper(&self) -> Remapper {
+        self.local.remapper()
+    }
 }
 
 /// A FileReader which reads files from memory via a supplied HashMap.
@@ -130,6 +183,15 @@ impl<'a> FileProvider<'a> for InMemoryFileProvider {
         }
         Ok(paths)
     }
+
+    /// Reads remappings from a supplied `remappings.txt`, never from the filesystem.
+    fn remapper(&self) -> Remapper {
+        let mut remappings = HashMap::new();
+        if let Some(content) = self.sources.get("remappings.txt") {
+            Remapper::parse_remappings(content, &mut remappings);
+        }
+        Remapper { remappings, base_dir: "./".to_string() }
+    }
 }
 
 fn strip_path_prefix(path: &str) -> &str {
@@ -27,6 +27,29 @@ pub struct FullFileSource<'a> {
 }
 
 impl<'a> FullFileSource<'a> {
+    /// Get the file whose own source contains the given offset in the flattened source
+    pub fn file_at(&self, offset: usize) -> Option<Arc<FileSource>> {
+        self.spans
+            .iter()
+            .filter(|(_, s)| s.start <= offset && offset < s.end)
+            .min_by_key(|(_, s)| s.end - s.start)
+            .map(|(f, _)| Arc::clone(f))
+    }
+
+    /// Get the span relative to the file whose own source contains it
+    pub fn locate(&self, span: &Span) -> Span {
+        self.spans
+            .iter()
+            .filter(|(_, s)| s.start <= span.start && span.start < s.end)
+            .min_by_key(|(_, s)| s.end - s.start)
+            .map(|(f, s)| Span {
+                start: span.start - s.start,
+                end: span.end - s.start,
+                file: Some(Arc::clone(f)),
+            })
+            .unwrap_or_else(|| span.clone())
+    }
+
     /// Get the relative span
     pub fn relative_span(&self, span: Ref<'a, Span>) -> Option<Span> {
         self.spans
@@ -55,10 +78,15 @@ pub struct Remapper {
 impl Remapper {
     /// Extracts remappings from configuration files.
     ///
-    /// Currently only supports `foundry.toml` remapping definitions.
+    /// Reads remapping definitions from `foundry.toml` and `remappings.txt` in `root`, and maps
+    /// each package installed with `huffc install` to its directory. Entries in
+    /// `remappings.txt` take precedence.
     pub fn new(root: impl AsRef<str>) -> Self {
         let mut inner = HashMap::<String, String>::new();
 
+        // Map installed packages
+        inner.extend(packages::package_remappings(root.as_ref()));
+
         // Gracefully parse remappings from foundry.toml
         Remapper::from_foundry(root.as_ref(), &mut inner);
 
@@ -69,6 +97,21 @@ impl Remapper {
         Self { remappings: inner, base_dir: root.as_ref().to_string() }
     }
 
+    /// Adds remappings of the form `from=to`, e.g. passed through the command line.
+    ///
+    /// These take precedence over remappings read from configuration files.
+    pub fn with_remappings(mut self, remappings: &[String]) -> Self {
+        remappings.iter().for_each(|remapping| match Remapper::split(remapping) {
+            Some((from, to)) => {
+                self.remappings.insert(from, to);
+            }
+            None => {
+                tracing::warn!(target: "parser", "Failed to split remapping using \"=\" at \"{}\"!", remapping)
+            }
+        });
+        self
+    }
+
     /// Helper to break apart a remapping gracefully
     pub fn split(remapping: &str) -> Option<(String, String)> {
         let mut split = remapping.splitn(2, '=');
@@ -158,42 +201,59 @@ impl Remapper {
     }
 
     /// Get remappings from a remappings.txt file
+    ///
+    /// Follows the Foundry format: one `from=to` remapping per line, optionally prefixed by a
+    /// `context:` that is ignored. Blank lines and lines starting with `#` are skipped.
     pub fn from_file(root: &str, inner: &mut HashMap<String, String>) {
-        let mut remappings: HashMap<String, String> = HashMap::new();
         let remappings_file = PathBuf::new().join(root).join("remappings.txt");
-        if remappings_file.is_file() {
-            let content =
-                fs::read_to_string(remappings_file).map_err(|err| err.to_string()).unwrap();
-
-            let rem_lines = content.split('\n').collect::<Vec<&str>>();
-            let rem = rem_lines
-                .iter()
-                .filter(|l| l != &&"")
-                .map(|l| l.split_once('='))
-                .collect::<Vec<Option<(&str, &str)>>>();
-            rem.iter().for_each(|pair| {
-                if let Some((lib, path)) = pair {
-                    remappings.insert(lib.to_string(), path.to_string());
+        if !remappings_file.is_file() {
+            return
+        }
+        let content = match fs::read_to_string(&remappings_file) {
+            Ok(c) => c,
+            Err(e) => {
+                tracing::warn!(target: "parser", "Failed to read \"{}\"!\nError: {:?}", remappings_file.to_string_lossy(), e);
+                return
+            }
+        };
+
+        Remapper::parse_remappings(&content, inner);
+    }
+
+    /// Parses remappings in the `remappings.txt` format
+    pub fn parse_remappings(content: &str, inner: &mut HashMap<String, String>) {
+        content
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .for_each(|l| {
+                // Strip the optional context, which ends before the first `=`
+                let remapping = match l.split_once(':') {
+                    Some((context, rest)) if !context.contains('=') => rest,
+                    _ => l,
+                };
+                match Remapper::split(remapping) {
+                    Some((from, to)) => {
+                        inner.insert(from, to);
+                    }
+                    None => tracing::warn!(target: "parser", "Failed to split remapping using \"=\" at \"{}\"!", l),
                 }
             });
-
-            inner.extend(remappings);
-        }
     }
 }
 
 impl Remapper {
-    /// Tries to replace path segments in a string with our remappings
+    /// Tries to replace the path prefix in a string with our remappings
+    ///
+    /// If multiple remappings match, the one with the longest prefix is applied.
     pub fn remap(&self, path: &str) -> Option<String> {
-        let mut path = path.to_string();
-        for (k, v) in self.remappings.iter() {
-            if path.starts_with(k) {
-                tracing::debug!(target: "parser", "found key {} and value {}", k, v);
-                path = path.replace(k, v);
-                return Some(format!("{}{path}", self.base_dir))
-            }
-        }
-        None
+        let (k, v) = self
+            .remappings
+            .iter()
+            .filter(|(k, _)| path.starts_with(k.as_str()))
+            .max_by_key(|(k, _)| k.len())?;
+        tracing::debug!(target: "parser", "found key {} and value {}", k, v);
+        Some(format!("{}{}{}", self.base_dir, v, &path[k.len()..]))
     }
 }
 
@@ -221,30 +281,74 @@ impl FileSource {
     /// Let's say you have a file, `a.txt` with two dependencies, `b.txt` and `c.txt`,
     /// `fully_flatten()` will generate a source code string with the contents of `b.txt` and
     /// `c.txt` appended to the end of the contents of `a.txt`.
+    ///
+    /// If `b.txt` and `c.txt` both depend on `d.txt`, the contents of `d.txt` are only appended
+    /// once.
     pub fn fully_flatten(self_ref: Arc<FileSource>) -> (String, Vec<(Arc<FileSource>, Span)>) {
+        let mut visited = vec![normalize_path(&self_ref.path)];
+        FileSource::flatten_once(self_ref, &mut visited)
+    }
+
+    /// Flattens a file source, skipping dependencies whose path was already `visited` so that
+    /// a file included from multiple places contributes its source exactly once.
+    fn flatten_once(
+        self_ref: Arc<FileSource>,
+        visited: &mut Vec<String>,
+    ) -> (String, Vec<(Arc<FileSource>, Span)>) {
         // First grab the parent file source
         let mut full_source =
             if let Some(s) = &self_ref.source { s.clone() } else { String::default() };
         let span = Span::new(0..full_source.len(), None);
-        let mut relative_positions = vec![(Arc::clone(&self_ref), span)];
+        let mut positions = vec![(Arc::clone(&self_ref), span)];
 
         // Then recursively grab source code for dependencies
         match &self_ref.dependencies {
             Some(vfs) => {
                 for fs in vfs {
-                    let mut flattened = FileSource::fully_flatten(Arc::clone(fs));
+                    let path = normalize_path(&fs.path);
+                    if visited.contains(&path) {
+                        tracing::debug!(target: "core", "SKIPPING ALREADY INCLUDED FILE \"{}\"", fs.path);
+                        continue
+                    }
+                    visited.push(path);
+
+                    let mut flattened = FileSource::flatten_once(Arc::clone(fs), visited);
                     let span =
                         Span::new(full_source.len()..(full_source.len() + flattened.0.len()), None);
+                    // Offset the dependency's positions into the flattened source
+                    flattened.1.iter_mut().for_each(|(_, s)| {
+                        s.start += full_source.len();
+                        s.end += full_source.len();
+                    });
                     full_source.push_str(&flattened.0);
-                    relative_positions.append(&mut flattened.1);
-                    relative_positions.push((Arc::clone(fs), span))
+                    positions.append(&mut flattened.1);
+                    positions.push((Arc::clone(fs), span))
                 }
             }
             None => {}
         }
 
         // Return the full source
-        (full_source, relative_positions)
+        (full_source, positions)
+    }
+
+    /// Resolves the path of a file included from `parent`, applying any matching remapping
+    ///
+    /// Standard library imports resolve to the bundled library, remote imports are kept as is,
+    /// and relative imports of remote files are resolved against their url.
+    pub fn resolve_import(remapper: &Remapper, parent: &str, import: &str) -> String {
+        if let Some(path) = stdlib::resolve(import) {
+            return path
+        }
+        match remapper.remap(import) {
+            Some(remapped) => {
+                tracing::debug!(target: "core", "REMAPPED IMPORT PATH \"{}\"", import);
+                remapped
+            }
+            None if remote::is_remote(import) => import.to_string(),
+            None if remote::is_remote(parent) => remote::join(parent, import),
+            None => FileSource::localize_file(parent, import).unwrap_or_default(),
+        }
     }
 
     /// Derives a File Path's directory
@@ -259,7 +363,7 @@ impl FileSource {
     /// Localizes a file path, if path is relative
     pub fn localize_file(parent: &str, child: &str) -> Option<String> {
         let mut prefixed_parent;
-        if !parent.starts_with('.') {
+        if !parent.starts_with(['.', '/']) {
             prefixed_parent = "./".to_owned();
             prefixed_parent.push_str(parent);
         } else {
@@ -315,7 +419,27 @@ impl FileSource {
     }
 }
 
-use crate::time;
+/// Lexically normalizes a file path, resolving `.` and `..` segments
+pub fn normalize_path(path: &str) -> String {
+    let mut segments: Vec<&str> = vec![];
+    for seg in path.split('/') {
+        match seg {
+            "" | "." => {}
+            ".." if segments.last().map_or(false, |s| *s != "..") => {
+                segments.pop();
+            }
+            _ => segments.push(seg),
+        }
+    }
+    let normalized = segments.join("/");
+    if path.starts_with('/') {
+        format!("/{normalized}")
+    } else {
+        normalized
+    }
+}
+
+use crate::{packages, remote, stdlib, time};
 use std::ops::{Add, Range};
 
 /// A Span is a section of a source file.
@@ -325,10 +449,22 @@ pub struct Span {
     pub start: usize,
     /// The end of the span.
     pub end: usize,
-    /// The Associated File
+    /// The Associated File, serialized as its path
+    #[serde(serialize_with = "serialize_file_path", skip_deserializing)]
     pub file: Option<Arc<FileSource>>,
 }
 
+/// Serializes the file of a span as its path, rather than its full source
+fn serialize_file_path<S: serde::Serializer>(
+    file: &Option<Arc<FileSource>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match file {
+        Some(file) => serializer.serialize_some(&file.path),
+        None => serializer.serialize_none(),
+    }
+}
+
 impl Span {
     /// An EOF spans [0, 0].
     pub const EOF: Span = Span { start: 0, end: 0, file: None };
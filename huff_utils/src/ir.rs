@@ -0,0 +1,164 @@
+//! ## Intermediate Representation
+//!
+//! The fully expanded code of a macro as a flat list of instructions, generated by macro
+//! expansion and emitted as bytes. Pushes of jump destinations, table offsets and constants
+//! reference their labels, tables and constants by name rather than by value, so passes may
+//! insert, remove or resize instructions before the program counters are assigned on emission.
+//! Values only known once the contract is deployed, like immutables, are emitted as placeholders.
+
+use crate::{bytecode::LabelIndices, evm::Opcode, evm_version::EVMVersion, prelude::AstSpan};
+use std::collections::BTreeMap;
+
+/// An expanded program: its instructions, followed by the tables it uses
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IRProgram {
+    /// The program counter of the first instruction
+    pub offset: usize,
+    /// The instructions of the code
+    pub instructions: Vec<IRInstruction>,
+    /// The tables appended to the code, in order
+    pub tables: Vec<IRTable>,
+    /// The hex values of the constants pushed by the instructions
+    pub constants: BTreeMap<String, String>,
+}
+
+/// An instruction of an [IRProgram]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct IRInstruction {
+    /// The type of instruction
+    pub ty: IRInstructionType,
+    /// The span of the statement the instruction was generated from
+    pub span: AstSpan,
+    /// The program counter the instruction was generated at in the expanded code, `None` for
+    /// instructions added by passes
+    pub origin: Option<usize>,
+}
+
+/// IRInstruction Type
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IRInstructionType {
+    /// An opcode without immediate data
+    Opcode(Opcode),
+    /// A `PUSHX` of the given number of bytes, a `PUSH0` for none
+    Push(usize, IRValue),
+    /// A label marking the program counter of the following instruction, without any bytes of
+    /// its own
+    Label(String),
+    /// Hex bytes emitted as is, ie. verbatim code
+    Raw(String),
+}
+
+/// A value pushed by an [IRInstruction]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IRValue {
+    /// Hex data
+    Literal(String),
+    /// The program counter of a label
+    Label(String),
+    /// The offset of a table in the code
+    TableStart(String),
+    /// The value of a constant
+    Constant(String),
+    /// The value of the immutable at the index, emitted as a placeholder filled on deployment
+    Immutable(usize),
+    /// The length of the dynamic constructor argument at the index, emitted as a placeholder
+    /// filled once the arguments are encoded
+    DynArgLength(usize),
+    /// The offset of the contents of the dynamic constructor argument at the index in the
+    /// creation code, emitted as a placeholder filled once the arguments are encoded
+    DynArgOffset(usize),
+    /// The size of the code of a macro invoked within its own expansion, filled in once the
+    /// expansion is done
+    Codesize(String),
+}
+
+/// A table appended to the code of an [IRProgram]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IRTable {
+    /// The name of the table
+    pub name: String,
+    /// The size of the table in bytes
+    pub size: usize,
+    /// Whether labels are packed into 2 bytes, rather than padded to 32 bytes
+    pub packed: bool,
+    /// The entries of the table
+    pub entries: Vec<IRTableEntry>,
+    /// The span of the table definition
+    pub span: AstSpan,
+}
+
+/// An entry of an [IRTable]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IRTableEntry {
+    /// The program counter of a label
+    Label(String),
+    /// Hex code
+    Code(String),
+}
+
+impl IRInstruction {
+    /// An instruction generated at a program counter, the span of its statement being set once
+    /// the statement is expanded
+    pub fn at(origin: usize, ty: IRInstructionType) -> Self {
+        IRInstruction { ty, span: AstSpan::default(), origin: Some(origin) }
+    }
+}
+
+impl IRInstructionType {
+    /// Pushes hex data with the push of its width
+    pub fn push(hex: String) -> Self {
+        IRInstructionType::Push(hex.len() / 2, IRValue::Literal(hex))
+    }
+
+    /// Pushes a literal with the narrowest push it fits in, zero with `PUSH0` if the EVM version
+    /// supports it
+    pub fn literal(evm_version: &EVMVersion, l: &[u8; 32]) -> Self {
+        match crate::bytes_util::bytes32_to_string(l, false) {
+            hex if hex == "00" && evm_version.has_push0() => {
+                IRInstructionType::Opcode(Opcode::Push0)
+            }
+            hex => IRInstructionType::push(hex),
+        }
+    }
+
+    /// The number of bytes the instruction is emitted as
+    pub fn size(&self) -> usize {
+        match self {
+            IRInstructionType::Opcode(_) => 1,
+            IRInstructionType::Push(width, _) => 1 + width,
+            IRInstructionType::Label(_) => 0,
+            IRInstructionType::Raw(hex) => hex.len() / 2,
+        }
+    }
+}
+
+impl IRProgram {
+    /// The size of the code in bytes, without the tables
+    pub fn code_size(&self) -> usize {
+        self.instructions.iter().map(|i| i.ty.size()).sum()
+    }
+
+    /// The offsets of the tables, laid out in order after the code
+    pub fn table_offsets(&self) -> BTreeMap<String, usize> {
+        let mut offset = self.offset + self.code_size();
+        let mut table_offsets = BTreeMap::new();
+        for table in &self.tables {
+            table_offsets.insert(table.name.clone(), offset);
+            offset += table.size;
+        }
+        table_offsets
+    }
+
+    /// The program counters of the labels, as the instructions are laid out
+    pub fn label_indices(&self) -> LabelIndices {
+        let mut label_indices = LabelIndices::new();
+        let mut pc = self.offset;
+        for instruction in &self.instructions {
+            if let IRInstructionType::Label(name) = &instruction.ty {
+                label_indices.insert(name.clone(), pc);
+            }
+            pc += instruction.ty.size();
+        }
+        label_indices
+    }
+}
@@ -15,15 +15,48 @@ pub mod artifact;
 /// AST Module
 pub mod ast;
 
+/// AST Visitor Module
+pub mod visit;
+
+/// Symbol Table Module
+pub mod symbols;
+
+/// Documentation Generator Module
+pub mod docs;
+
+/// Custom Builtins Module
+pub mod builtins;
+
 /// Bytecode Traits Module
 pub mod bytecode;
 
+/// Intermediate Representation Module
+pub mod ir;
+
+/// Source Map Module
+pub mod source_map;
+
+/// Disassembler Module
+pub mod disassembler;
+
+/// Decompiler Module
+pub mod decompiler;
+
 /// Token Module
 pub mod token;
 
 /// Lexing Error Module
 pub mod error;
 
+/// Error Codes Module
+pub mod error_codes;
+
+/// Suggestions Module
+pub mod suggestions;
+
+/// Diagnostics Module
+pub mod diagnostics;
+
 /// EVM Module
 pub mod evm;
 
@@ -48,6 +81,15 @@ pub mod sol_interface;
 /// File Provider Module
 pub mod file_provider;
 
+/// Remote Imports Module
+pub mod remote;
+
+/// Packages Module
+pub mod packages;
+
+/// Standard Library Module
+pub mod stdlib;
+
 /// Time Module
 pub mod time;
 
@@ -57,10 +99,13 @@ pub mod wasm;
 /// EVM Version Module
 pub mod evm_version;
 
+/// Metadata Module
+pub mod metadata;
+
 /// Prelude wraps common utilities.
 pub mod prelude {
     pub use crate::{
-        abi::*, artifact::*, ast::*, bytecode::*, bytes_util::*, error::*, evm::*, evm_version::*,
-        files::*, io::*, report::*, sol_interface::*, token::*, types::*,
+        abi::*, artifact::*, ast::*, builtins::*, bytecode::*, bytes_util::*, error::*, evm::*, evm_version::*,
+        files::*, io::*, ir::*, metadata::*, report::*, sol_interface::*, suggestions::*, token::*, types::*,
     };
 }
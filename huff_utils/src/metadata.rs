@@ -0,0 +1,336 @@
+//! ## Metadata
+//!
+//! The metadata identifying the compiler, settings and sources a contract was built with, and the
+//! solc-style CBOR trailer embedding it at the end of the runtime bytecode, so verification
+//! services can recognize Huff builds.
+
+use crate::prelude::{BootstrapSettings, FileSource};
+use ethers_core::utils::{hex, keccak256};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, sync::Arc};
+
+/// The hash of the metadata embedded in the CBOR trailer
+#[derive(Default, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MetadataHash {
+    /// Only the compiler version is embedded
+    #[default]
+    None,
+    /// The IPFS multihash of the metadata, under the `ipfs` key
+    Ipfs,
+    /// The keccak256 hash of the metadata, under the `keccak256` key
+    Keccak,
+}
+
+impl std::str::FromStr for MetadataHash {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(MetadataHash::None),
+            "ipfs" => Ok(MetadataHash::Ipfs),
+            "keccak" => Ok(MetadataHash::Keccak),
+            _ => Err(format!("Invalid metadata hash \"{s}\", expected one of: none, ipfs, keccak")),
+        }
+    }
+}
+
+impl std::fmt::Display for MetadataHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetadataHash::None => write!(f, "none"),
+            MetadataHash::Ipfs => write!(f, "ipfs"),
+            MetadataHash::Keccak => write!(f, "keccak"),
+        }
+    }
+}
+
+/// The metadata of a contract, laid out like solc's
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Metadata {
+    /// The compiler the contract was built with
+    pub compiler: MetadataCompiler,
+    /// The source language, always `Huff`
+    pub language: String,
+    /// The settings the contract was compiled with
+    pub settings: MetadataSettings,
+    /// The files the contract was compiled from, by path
+    pub sources: BTreeMap<String, MetadataSource>,
+    /// The version of the metadata format
+    pub version: u8,
+}
+
+/// The compiler a contract was built with
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct MetadataCompiler {
+    /// The name of the compiler, `huffc`
+    pub name: String,
+    /// The version of the compiler
+    pub version: String,
+}
+
+/// The settings affecting the code of a contract
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataSettings {
+    /// The EVM version the code targets
+    pub evm_version: String,
+    /// The macro the runtime code is generated from
+    pub main: String,
+    /// The macro the constructor code is generated from
+    pub constructor: String,
+    /// Whether the optimization passes ran
+    pub optimize: bool,
+    /// Whether repeated macros were outlined
+    pub outline: bool,
+    /// Whether the labels of every macro are unique to each of its invocations
+    #[serde(default)]
+    pub unique_labels: bool,
+    /// The maximum depth of nested macro invocations, if not the default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_expansion_depth: Option<usize>,
+    /// The flags defined for conditional compilation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub defines: Vec<String>,
+    /// The overridden constants and their values
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub constants: BTreeMap<String, String>,
+    /// The settings the bootstrap code is generated with
+    #[serde(default)]
+    pub bootstrap: BootstrapSettings,
+    /// The hash of the metadata embedded in the runtime code, if a trailer is appended
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata_hash: Option<MetadataHash>,
+}
+
+impl MetadataSettings {
+    /// The keccak256 hash of the settings as compact JSON
+    pub fn hash(&self) -> String {
+        let json = serde_json::to_string(self).unwrap_or_default();
+        format!("0x{}", hex::encode(keccak256(json)))
+    }
+}
+
+/// The compiler an artifact was built with, and a fingerprint of its settings, so reproducible
+/// builds can assert the exact toolchain
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct CompilerFingerprint {
+    /// The name of the compiler, `huffc`
+    pub name: String,
+    /// The version of the compiler
+    pub version: String,
+    /// The keccak256 hash of the [MetadataSettings] the code was generated with
+    #[serde(rename = "settingsHash")]
+    pub settings_hash: String,
+}
+
+/// A file a contract was compiled from
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct MetadataSource {
+    /// The keccak256 hash of the file's content
+    pub keccak256: String,
+    /// The urls the file can be retrieved from, its `dweb:/ipfs/` url when the metadata is
+    /// hashed for IPFS
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub urls: Vec<String>,
+}
+
+impl Metadata {
+    /// Collects the metadata of a contract compiled from `file` and the files it includes
+    pub fn new(file: &Arc<FileSource>, compiler_version: &str, settings: MetadataSettings) -> Self {
+        let sources = included_files(file)
+            .into_iter()
+            .map(|file| {
+                let content = file.source.as_deref().unwrap_or_default();
+                let keccak256 = format!("0x{}", hex::encode(keccak256(content)));
+                let urls = match settings.metadata_hash {
+                    Some(MetadataHash::Ipfs) => vec![format!("dweb:/ipfs/{}", ipfs_cid(content))],
+                    _ => vec![],
+                };
+                (file.path.clone(), MetadataSource { keccak256, urls })
+            })
+            .collect();
+        Self {
+            compiler: MetadataCompiler {
+                name: "huffc".to_string(),
+                version: compiler_version.to_string(),
+            },
+            language: "Huff".to_string(),
+            settings,
+            sources,
+            version: 1,
+        }
+    }
+
+    /// The metadata as compact JSON, as it's hashed
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// The IPFS CID of the metadata JSON, which the `ipfs` hash of the trailer is the multihash of
+    pub fn cid(&self) -> String {
+        ipfs_cid(self.to_json())
+    }
+
+    /// Encodes the CBOR trailer appended to the runtime code as hex
+    ///
+    /// Like solc's, the trailer is a map of the metadata hash, if any, and the compiler version,
+    /// as three bytes for release versions, followed by the length of the map in two bytes.
+    pub fn cbor_trailer(&self, hash: MetadataHash) -> String {
+        let json = self.to_json();
+        let mut entries: Vec<(&str, Vec<u8>)> = vec![];
+        match hash {
+            MetadataHash::None => {}
+            MetadataHash::Ipfs => {
+                entries.push(("ipfs", cbor_bytes(&ipfs_hash(json.as_bytes()))));
+            }
+            MetadataHash::Keccak => {
+                entries.push(("keccak256", cbor_bytes(&keccak256(json.as_bytes()))));
+            }
+        }
+        let release = self
+            .compiler
+            .version
+            .split('.')
+            .map(|part| part.parse::<u8>().ok())
+            .collect::<Option<Vec<u8>>>()
+            .filter(|parts| parts.len() == 3);
+        let version = match release {
+            Some(parts) => cbor_bytes(&parts),
+            None => cbor_text(&self.compiler.version),
+        };
+        entries.push((&self.compiler.name, version));
+
+        let mut map = cbor_head(5, entries.len());
+        for (key, value) in entries {
+            map.extend(cbor_text(key));
+            map.extend(value);
+        }
+        map.extend((map.len() as u16).to_be_bytes());
+        hex::encode(map)
+    }
+}
+
+/// Gets a file and the files it includes, once each
+pub fn included_files(file: &Arc<FileSource>) -> Vec<Arc<FileSource>> {
+    let mut included: Vec<Arc<FileSource>> = vec![];
+    let mut files = vec![Arc::clone(file)];
+    while let Some(file) = files.pop() {
+        if included.iter().all(|f| f.path != file.path) {
+            files.extend(file.dependencies.iter().flatten().cloned());
+            included.push(file);
+        }
+    }
+    included
+}
+
+/// Gets the IPFS CID a file is added as, in the base58 encoded version 0 the metadata hash
+/// embeds
+pub fn ipfs_cid(content: impl AsRef<[u8]>) -> String {
+    const ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+    let hash = ipfs_hash(content.as_ref());
+
+    // Repeatedly divide the big-endian number by 58, the multihash never starting with zeros
+    let mut digits: Vec<u8> = vec![];
+    for byte in hash {
+        let mut carry = usize::from(byte);
+        for digit in digits.iter_mut() {
+            carry += usize::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    digits.iter().rev().map(|d| ALPHABET[usize::from(*d)] as char).collect()
+}
+
+/// Pins a file to the IPFS node behind an HTTP API url, eg. `http://127.0.0.1:5001`, returning
+/// the CID it was added as
+#[cfg(not(target_arch = "wasm32"))]
+pub fn pin(api: &str, name: &str, content: &[u8]) -> Result<String, String> {
+    let boundary = "huffc-metadata-boundary";
+    let body = [
+        format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{name}\"\r\n\
+             Content-Type: application/octet-stream\r\n\r\n"
+        )
+        .as_bytes(),
+        content,
+        format!("\r\n--{boundary}--\r\n").as_bytes(),
+    ]
+    .concat();
+    let url = format!("{}/api/v0/add?pin=true&cid-version=0", api.trim_end_matches('/'));
+    tracing::info!(target: "metadata", "PINNING \"{}\" TO \"{}\"", name, url);
+    let response = ureq::post(&url)
+        .set("Content-Type", &format!("multipart/form-data; boundary={boundary}"))
+        .send_bytes(&body)
+        .map_err(|e| e.to_string())?
+        .into_string()
+        .map_err(|e| e.to_string())?;
+    let added: serde_json::Value = serde_json::from_str(&response).map_err(|e| e.to_string())?;
+    added["Hash"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| format!("Unexpected response from the IPFS API: {response}"))
+}
+
+/// Pins a file to the IPFS node behind an HTTP API url, eg. `http://127.0.0.1:5001`, returning
+/// the CID it was added as
+#[cfg(target_arch = "wasm32")]
+pub fn pin(_: &str, _: &str, _: &[u8]) -> Result<String, String> {
+    Err("Pinning is unsupported on wasm".to_string())
+}
+
+/// Encodes the head of a CBOR item of a major type and length
+fn cbor_head(major: u8, len: usize) -> Vec<u8> {
+    match len {
+        0..=23 => vec![major << 5 | len as u8],
+        24..=0xff => vec![major << 5 | 24, len as u8],
+        _ => [vec![major << 5 | 25], (len as u16).to_be_bytes().to_vec()].concat(),
+    }
+}
+
+/// Encodes a CBOR byte string
+fn cbor_bytes(bytes: &[u8]) -> Vec<u8> {
+    [cbor_head(2, bytes.len()), bytes.to_vec()].concat()
+}
+
+/// Encodes a CBOR text string
+fn cbor_text(text: &str) -> Vec<u8> {
+    [cbor_head(3, text.len()), text.as_bytes().to_vec()].concat()
+}
+
+/// Encodes an unsigned protobuf varint
+fn varint(mut n: usize) -> Vec<u8> {
+    let mut bytes = vec![];
+    while n >= 0x80 {
+        bytes.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    bytes.push(n as u8);
+    bytes
+}
+
+/// Gets the IPFS multihash of a file, the sha256 hash of the UnixFS node it's added as
+///
+/// Files are added as a single node, IPFS only splitting files larger than 256 KiB into chunks,
+/// which the metadata stays far below.
+fn ipfs_hash(content: &[u8]) -> Vec<u8> {
+    let mut unixfs = vec![0x08, 0x02];
+    if !content.is_empty() {
+        unixfs.push(0x12);
+        unixfs.extend(varint(content.len()));
+        unixfs.extend(content);
+    }
+    unixfs.push(0x18);
+    unixfs.extend(varint(content.len()));
+
+    let mut node = vec![0x0a];
+    node.extend(varint(unixfs.len()));
+    node.extend(unixfs);
+    [vec![0x12, 0x20], Sha256::digest(node).to_vec()].concat()
+}
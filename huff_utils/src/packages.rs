@@ -0,0 +1,105 @@
+//! Packages
+//!
+//! Huff libraries installed from git into `lib/`, and the lockfile recording their revisions.
+
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+/// The directory packages are installed in
+pub const LIB_DIR: &str = "lib";
+
+/// The lockfile recording the installed packages
+pub const PACKAGES_LOCKFILE: &str = "huff-packages.lock";
+
+/// A package to install
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageSpec {
+    /// The package name, which is also its directory in `lib/`
+    pub name: String,
+    /// The git url of the package
+    pub url: String,
+    /// The git revision to install, defaulting to the repository's HEAD
+    pub rev: Option<String>,
+}
+
+impl PackageSpec {
+    /// Creates a package, `None` if it could be mistaken for a git option or escape `lib/`
+    ///
+    /// The name must be a single path component, and neither the url nor the revision may start
+    /// with a `-`.
+    pub fn new(name: String, url: String, rev: Option<String>) -> Option<Self> {
+        let valid_name = !name.is_empty() &&
+            !name.starts_with('-') &&
+            name != "." &&
+            name != ".." &&
+            !name.contains(['/', '\\']);
+        let valid_url = !url.is_empty() && !url.starts_with('-');
+        let valid_rev = rev.as_ref().map_or(true, |rev| !rev.is_empty() && !rev.starts_with('-'));
+        (valid_name && valid_url && valid_rev).then_some(Self { name, url, rev })
+    }
+
+    /// Parses a package of the form `owner/repo[@rev]` hosted on GitHub, or `<git url>[@rev]`
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (source, rev) = match spec.rsplit_once('@') {
+            // Don't split the user of `git@host:owner/repo` urls
+            Some((source, rev)) if !source.is_empty() && !rev.contains(':') => {
+                (source, Some(rev.to_string()))
+            }
+            _ => (spec, None),
+        };
+        let is_url = source.contains("://") || source.starts_with("git@");
+        let url = if is_url {
+            source.to_string()
+        } else {
+            match source.split('/').collect::<Vec<_>>()[..] {
+                [owner, repo] if !owner.is_empty() && !repo.is_empty() => {
+                    format!("https://github.com/{owner}/{repo}")
+                }
+                _ => return None,
+            }
+        };
+        let name = url.trim_end_matches('/').rsplit(['/', ':']).next()?.trim_end_matches(".git");
+        Self::new(name.to_string(), url, rev)
+    }
+}
+
+/// An installed package as recorded in the lockfile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedPackage {
+    /// The git url of the package
+    pub url: String,
+    /// The installed commit
+    pub rev: String,
+}
+
+/// Reads the installed packages by name from the lockfile in `root`
+pub fn read_lockfile(root: &str) -> BTreeMap<String, LockedPackage> {
+    let path = Path::new(root).join(PACKAGES_LOCKFILE);
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            tracing::warn!(target: "packages", "Failed to parse \"{}\": {:?}", path.display(), e);
+            BTreeMap::new()
+        }),
+        Err(_) => BTreeMap::new(),
+    }
+}
+
+/// Writes the installed packages to the lockfile in `root`
+pub fn write_lockfile(root: &str, packages: &BTreeMap<String, LockedPackage>) -> io::Result<()> {
+    let serialized = serde_json::to_string_pretty(packages)?;
+    fs::write(Path::new(root).join(PACKAGES_LOCKFILE), serialized)
+}
+
+/// Derives a `name/` remapping for each installed package, pointing to its `src/` directory if
+/// it has one
+pub fn package_remappings(root: &str) -> Vec<(String, String)> {
+    read_lockfile(root)
+        .into_keys()
+        .map(|name| {
+            let dir = format!("{LIB_DIR}/{name}/");
+            let src = format!("{dir}src/");
+            let target = if Path::new(root).join(&src).is_dir() { src } else { dir };
+            (format!("{name}/"), target)
+        })
+        .collect()
+}
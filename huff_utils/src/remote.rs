@@ -0,0 +1,157 @@
+//! Remote Imports
+//!
+//! Fetches files included over HTTPS or IPFS. Fetched contents are cached by their keccak256
+//! hash, and the hash of each url is pinned in a lockfile so later fetches are verified.
+
+use crate::{bytes_util::hash_bytes, files::normalize_path};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// The gateway `ipfs://` imports are fetched through
+pub const IPFS_GATEWAY: &str = "https://ipfs.io/ipfs/";
+
+/// The default lockfile path
+pub const DEFAULT_LOCKFILE: &str = "./huff.lock";
+
+/// The default content cache directory
+pub const DEFAULT_CACHE_DIR: &str = "./.huff/cache";
+
+/// Remote import errors
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub enum RemoteImportError {
+    /// A remote file was included without enabling remote imports
+    Disabled(String),
+    /// Failed to fetch a remote file
+    FetchFailed(String, String),
+    /// The fetched contents don't match the hash pinned in the lockfile
+    HashMismatch {
+        /// The url of the file
+        url: String,
+        /// The hash pinned in the lockfile
+        expected: String,
+        /// The hash of the fetched contents
+        found: String,
+    },
+    /// Failed to read or write the lockfile or the cache
+    Io(String, String),
+}
+
+/// Whether an import path points to a remote file, plain `http://` urls aren't fetched
+pub fn is_remote(path: &str) -> bool {
+    path.starts_with("https://") || path.starts_with("ipfs://")
+}
+
+/// Resolves a relative import against the url of the including remote file
+pub fn join(parent: &str, child: &str) -> String {
+    let (scheme, rest) = parent.split_once("://").unwrap_or(("", parent));
+    let dir = rest.rsplit_once('/').map_or(rest, |(dir, _)| dir);
+    format!("{scheme}://{}", normalize_path(&format!("{dir}/{child}")))
+}
+
+/// Returns the keccak256 hash of some contents as a hex string
+pub fn content_hash(contents: &str) -> String {
+    let mut hash = [0u8; 32];
+    hash_bytes(&mut hash, &contents.to_string());
+    format!("0x{}", ethers_core::utils::hex::encode(hash))
+}
+
+/// A cache of remote files, verified against a lockfile of content hashes
+#[derive(Debug)]
+pub struct RemoteCache {
+    /// The directory fetched contents are cached in
+    pub cache_dir: PathBuf,
+    /// The path of the lockfile
+    pub lockfile: PathBuf,
+    /// The pinned content hashes by url
+    pub hashes: Mutex<BTreeMap<String, String>>,
+}
+
+impl RemoteCache {
+    /// Creates a cache, reading the pinned hashes from `lockfile` if it exists
+    pub fn new(cache_dir: impl AsRef<Path>, lockfile: impl AsRef<Path>) -> Self {
+        let hashes = fs::read_to_string(lockfile.as_ref())
+            .ok()
+            .and_then(|content| match serde_json::from_str(&content) {
+                Ok(hashes) => Some(hashes),
+                Err(e) => {
+                    tracing::warn!(target: "remote", "Failed to parse lockfile \"{}\": {:?}", lockfile.as_ref().display(), e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Self {
+            cache_dir: cache_dir.as_ref().to_path_buf(),
+            lockfile: lockfile.as_ref().to_path_buf(),
+            hashes: Mutex::new(hashes),
+        }
+    }
+
+    /// Gets the contents of a remote file, from the cache if possible
+    ///
+    /// Newly fetched files are pinned in the lockfile, errors if the contents of a file don't
+    /// match its pinned hash.
+    pub fn get(&self, url: &str) -> Result<String, RemoteImportError> {
+        let pinned = self.hashes.lock().unwrap().get(url).cloned();
+
+        // Serve pinned files from the cache
+        if let Some(hash) = &pinned {
+            if let Ok(contents) = fs::read_to_string(self.cache_dir.join(hash)) {
+                if content_hash(&contents) == *hash {
+                    tracing::debug!(target: "remote", "CACHE HIT FOR \"{}\"", url);
+                    return Ok(contents)
+                }
+                tracing::warn!(target: "remote", "CORRUPTED CACHE ENTRY FOR \"{}\"", url);
+            }
+        }
+
+        let contents = fetch(url)?;
+        let hash = content_hash(&contents);
+        if let Some(expected) = pinned {
+            if expected != hash {
+                tracing::error!(target: "remote", "HASH MISMATCH FOR \"{}\"", url);
+                return Err(RemoteImportError::HashMismatch {
+                    url: url.to_string(),
+                    expected,
+                    found: hash,
+                })
+            }
+        }
+
+        // Cache the contents and pin their hash
+        let io_err = |e: std::io::Error| RemoteImportError::Io(url.to_string(), e.to_string());
+        fs::create_dir_all(&self.cache_dir).map_err(io_err)?;
+        fs::write(self.cache_dir.join(&hash), &contents).map_err(io_err)?;
+        let mut hashes = self.hashes.lock().unwrap();
+        if hashes.insert(url.to_string(), hash).is_none() {
+            let serialized = serde_json::to_string_pretty(&*hashes).unwrap_or_default();
+            fs::write(&self.lockfile, serialized).map_err(io_err)?;
+        }
+
+        Ok(contents)
+    }
+}
+
+/// Fetches a remote file, resolving `ipfs://` urls through the [IPFS_GATEWAY]
+#[cfg(not(target_arch = "wasm32"))]
+pub fn fetch(url: &str) -> Result<String, RemoteImportError> {
+    let location = match url.strip_prefix("ipfs://") {
+        Some(cid) => format!("{IPFS_GATEWAY}{cid}"),
+        None => url.to_string(),
+    };
+    tracing::info!(target: "remote", "FETCHING \"{}\"", location);
+    ureq::get(&location)
+        .call()
+        .map_err(|e| RemoteImportError::FetchFailed(url.to_string(), e.to_string()))?
+        .into_string()
+        .map_err(|e| RemoteImportError::FetchFailed(url.to_string(), e.to_string()))
+}
+
+/// Fetches a remote file, resolving `ipfs://` urls through the [IPFS_GATEWAY]
+#[cfg(target_arch = "wasm32")]
+pub fn fetch(url: &str) -> Result<String, RemoteImportError> {
+    Err(RemoteImportError::FetchFailed(url.to_string(), "unsupported on wasm".to_string()))
+}
@@ -0,0 +1,73 @@
+//! ## Source Maps
+//!
+//! Encodes the bytecode ranges of statements as a source map in solc's compressed `s:l:f:j`
+//! format, so that debuggers can step through the Huff source. Each instruction gets an entry
+//! with the byte offset and length of its statement in its file, the index of the file in the
+//! source list and the jump type. Fields equal to the previous entry's are left empty.
+//!
+//! Instructions that weren't generated from a statement, eg. the bootstrap code, map to
+//! `-1:-1:-1`.
+
+use crate::{
+    bytecode::BytecodeSpan,
+    bytes_util::split_instructions,
+    prelude::{AstSpan, Span},
+};
+
+/// Encodes the source map of a bytecode, adding the paths of the files it maps to `sources`
+///
+/// The bytecode ranges of `spans` must not overlap, and their spans should be located within
+/// their files.
+pub fn source_map(bytecode: &str, spans: &[BytecodeSpan], sources: &mut Vec<String>) -> String {
+    let mut previous: Option<[String; 4]> = None;
+    let mut entries = vec![];
+    for (pc, _, _) in split_instructions(bytecode) {
+        let (start, length, file) = spans
+            .iter()
+            .find(|s| s.start <= pc && pc < s.end)
+            .and_then(|s| locate(&s.span, sources))
+            .unwrap_or((-1, -1, -1));
+        // Huff has no calls to tell jumps into or out of a function from, every jump is regular
+        let fields = [start.to_string(), length.to_string(), file.to_string(), "-".to_string()];
+
+        let mut entry = match &previous {
+            Some(previous) => fields
+                .iter()
+                .zip(previous)
+                .map(|(field, previous)| if field == previous { "" } else { field.as_str() })
+                .collect::<Vec<&str>>()
+                .join(":"),
+            None => fields.join(":"),
+        };
+        entry.truncate(entry.trim_end_matches(':').len());
+        entries.push(entry);
+        previous = Some(fields);
+    }
+    entries.join(";")
+}
+
+/// The byte offset, byte length and file index of a statement's span, covering its spans
+/// within the file of the first
+fn locate(span: &AstSpan, sources: &mut Vec<String>) -> Option<(i64, i64, i64)> {
+    let first = span.0.first()?;
+    let spans = span.0.iter().filter(|s| s.file == first.file).collect::<Vec<&Span>>();
+    let start = spans.iter().map(|s| s.start).min()?;
+    let end = spans.iter().map(|s| s.end + 1).max()?;
+
+    let Some(file) = &first.file else { return Some((start as i64, (end - start) as i64, -1)) };
+    let index = sources.iter().position(|p| *p == file.path).unwrap_or_else(|| {
+        sources.push(file.path.clone());
+        sources.len() - 1
+    });
+    // Spans count characters, source maps count bytes
+    let (start, end) = match &file.source {
+        Some(source) => (byte_offset(source, start), byte_offset(source, end)),
+        None => (start, end),
+    };
+    Some((start as i64, (end - start) as i64, index as i64))
+}
+
+/// The byte offset of a character offset in a source
+fn byte_offset(source: &str, offset: usize) -> usize {
+    source.char_indices().nth(offset).map_or(source.len(), |(i, _)| i)
+}
@@ -0,0 +1,31 @@
+//! Standard Library
+//!
+//! Huff macros bundled with the compiler, included with `#include <huffstd/...>`.
+
+/// The path prefix of resolved standard library files
+pub const STDLIB_SCHEME: &str = "huffstd://";
+
+/// The bundled standard library files by their path in the library
+pub const STDLIB: &[(&str, &str)] = &[
+    ("auth/Owned.huff", include_str!("../stdlib/auth/Owned.huff")),
+    ("math/SafeMath.huff", include_str!("../stdlib/math/SafeMath.huff")),
+    ("tokens/ERC20Transfers.huff", include_str!("../stdlib/tokens/ERC20Transfers.huff")),
+    ("utils/Memory.huff", include_str!("../stdlib/utils/Memory.huff")),
+];
+
+/// Resolves an include of the form `<huffstd/path>` to the path of a standard library file
+pub fn resolve(import: &str) -> Option<String> {
+    let path = import.strip_prefix("<huffstd/")?.strip_suffix('>')?;
+    Some(format!("{STDLIB_SCHEME}{path}"))
+}
+
+/// Whether a resolved path points to the standard library
+pub fn is_stdlib(path: &str) -> bool {
+    path.starts_with(STDLIB_SCHEME)
+}
+
+/// Gets the source of a resolved standard library file
+pub fn source(path: &str) -> Option<&'static str> {
+    let path = path.strip_prefix(STDLIB_SCHEME)?;
+    STDLIB.iter().find(|(p, _)| *p == path).map(|(_, source)| *source)
+}
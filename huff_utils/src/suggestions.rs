@@ -0,0 +1,36 @@
+//! ## Suggestions
+//!
+//! "Did you mean" suggestions for identifiers that don't resolve, e.g. `TRANSFER` for an
+//! invocation of `TRANFER()`.
+
+/// Finds the candidate closest to a name by edit distance, if it's close enough to be a typo
+///
+/// A candidate only differing in case always matches. Otherwise, up to a third of the name's
+/// characters may differ, and the first candidate with the smallest distance wins.
+pub fn closest_match<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let max_distance = (name.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .filter(|c| *c != name)
+        .map(|c| {
+            let distance = match c.eq_ignore_ascii_case(name) {
+                true => 0,
+                false => strsim::levenshtein(name, c),
+            };
+            (distance, c)
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, c)| c)
+}
+
+/// Formats the hint suggesting the closest candidate to a name, if any
+pub fn did_you_mean<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<String> {
+    closest_match(name, candidates).map(|c| format!("Did you mean \"{c}\"?"))
+}
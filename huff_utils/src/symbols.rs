@@ -0,0 +1,283 @@
+//! ## Symbol Table
+//!
+//! A [SymbolTable] indexes the definitions of a [Contract] and every identifier referencing
+//! them: macro invocations, constant pushes, label calls, arg calls and the arguments of builtin
+//! functions. Each occurrence carries the span of its identifier token, for go-to-definition and
+//! find-references in editors.
+//!
+//! Identifiers passed as macro arguments and constants referenced by constant expressions don't
+//! carry spans of their own, so they aren't indexed.
+
+use crate::{ast::*, files::Span, visit::*};
+use serde::Serialize;
+
+/// The kind of a symbol
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SymbolKind {
+    /// A macro, fn or test
+    Macro,
+    /// A constant
+    Constant,
+    /// An immutable
+    Immutable,
+    /// A table
+    Table,
+    /// A function
+    Function,
+    /// An event
+    Event,
+    /// A custom error
+    Error,
+    /// A jump label, scoped to its macro
+    Label,
+    /// A macro parameter, scoped to its macro
+    Argument,
+}
+
+/// The definition of a symbol
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Definition {
+    /// The name of the symbol
+    pub name: String,
+    /// The kind of the symbol
+    pub kind: SymbolKind,
+    /// The macro a label or parameter is defined in
+    pub scope: Option<String>,
+    /// The span of the name of the symbol
+    pub span: Span,
+}
+
+/// An identifier referencing a symbol
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    /// The referenced name
+    pub name: String,
+    /// The kind of the referenced symbol, the kind expected if it isn't defined
+    pub kind: SymbolKind,
+    /// The macro the reference is in, if any
+    pub scope: Option<String>,
+    /// The span of the identifier
+    pub span: Span,
+    /// The index of the referenced definition, `None` if it isn't defined
+    pub definition: Option<usize>,
+}
+
+/// The definitions of a contract and the references to them
+#[derive(Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct SymbolTable {
+    definitions: Vec<Definition>,
+    references: Vec<Reference>,
+}
+
+impl SymbolTable {
+    /// Public associated function that indexes the symbols of a contract
+    pub fn new(contract: &Contract) -> Self {
+        let mut indexer = Indexer::default();
+        indexer.visit_contract(contract);
+        let Indexer { mut table, unresolved, .. } = indexer;
+        for (reference, kinds) in unresolved {
+            let definition = table.resolve(&reference, kinds);
+            let kind =
+                definition.and_then(|i| table.definitions.get(i)).map_or(kinds[0], |d| d.kind);
+            table.references.push(Reference { kind, definition, ..reference });
+        }
+        table
+    }
+
+    /// The definitions of the contract, in the order they are walked
+    pub fn definitions(&self) -> &[Definition] {
+        &self.definitions
+    }
+
+    /// The references of the contract, in the order they are walked
+    pub fn references(&self) -> &[Reference] {
+        &self.references
+    }
+
+    /// The definition a reference resolves to
+    pub fn definition(&self, reference: &Reference) -> Option<&Definition> {
+        reference.definition.and_then(|i| self.definitions.get(i))
+    }
+
+    /// The references resolving to a definition
+    pub fn references_to<'a>(
+        &'a self,
+        definition: &'a Definition,
+    ) -> impl Iterator<Item = &'a Reference> {
+        self.references.iter().filter(move |r| self.definition(r) == Some(definition))
+    }
+
+    /// The definition of the symbol at a character offset, whether the offset is on the
+    /// definition itself or on a reference to it
+    ///
+    /// Only spans of the file at `path` are considered, or of any file if it's `None`.
+    pub fn symbol_at(&self, path: Option<&str>, offset: usize) -> Option<&Definition> {
+        let contains = |span: &Span| {
+            span.start <= offset &&
+                offset <= span.end &&
+                path.map_or(true, |p| span.file.as_ref().map_or(false, |f| f.path == p))
+        };
+        self.definitions.iter().find(|d| contains(&d.span)).or_else(|| {
+            self.references.iter().find(|r| contains(&r.span)).and_then(|r| self.definition(r))
+        })
+    }
+
+    /// Resolves a reference to the first definition of one of the kinds
+    ///
+    /// Parameters resolve within their macro. Labels prefer their own macro, but can be defined
+    /// by any macro invoking it, or referenced from a jump table.
+    fn resolve(&self, reference: &Reference, kinds: &[SymbolKind]) -> Option<usize> {
+        let position = |same_scope: bool| {
+            kinds.iter().find_map(|kind| {
+                self.definitions.iter().position(|d| {
+                    d.kind == *kind &&
+                        d.name == reference.name &&
+                        (!same_scope || d.scope == reference.scope)
+                })
+            })
+        };
+        match kinds {
+            [SymbolKind::Argument] => position(true),
+            [SymbolKind::Label] => position(true).or_else(|| position(false)),
+            _ => position(false),
+        }
+    }
+}
+
+/// Finds the span of a definition's name, following `#define` and its keyword
+///
+/// Decorators come before the `#define` of a test.
+fn name_span(span: &AstSpan, name: &str) -> Option<Span> {
+    let len = |s: &Span| (s.end + 1).saturating_sub(s.start);
+    let chars = name.chars().count();
+    span.0
+        .windows(3)
+        .find(|w| len(&w[0]) == "#define".len() && len(&w[2]) == chars)
+        .map(|w| w[2].clone())
+        .or_else(|| span.0.get(2).cloned())
+}
+
+/// Collects the definitions and references of a contract
+#[derive(Default)]
+struct Indexer {
+    table: SymbolTable,
+    /// References and the kinds they can resolve to, resolved once every definition is known
+    unresolved: Vec<(Reference, &'static [SymbolKind])>,
+    /// The macro being walked
+    scope: Option<String>,
+}
+
+impl Indexer {
+    fn define(&mut self, name: &str, kind: SymbolKind, span: Option<Span>) {
+        if let Some(span) = span {
+            let scope = matches!(kind, SymbolKind::Label | SymbolKind::Argument)
+                .then(|| self.scope.clone())
+                .flatten();
+            self.table.definitions.push(Definition { name: name.to_string(), kind, scope, span });
+        }
+    }
+
+    fn reference(&mut self, name: &str, kinds: &'static [SymbolKind], span: Option<&Span>) {
+        if let Some(span) = span {
+            let reference = Reference {
+                name: name.to_string(),
+                kind: kinds[0],
+                scope: self.scope.clone(),
+                span: span.clone(),
+                definition: None,
+            };
+            self.unresolved.push((reference, kinds));
+        }
+    }
+}
+
+impl Visit for Indexer {
+    fn visit_macro_definition(&mut self, macro_def: &MacroDefinition) {
+        self.define(
+            &macro_def.name,
+            SymbolKind::Macro,
+            name_span(&macro_def.span, &macro_def.name),
+        );
+        self.scope = Some(macro_def.name.clone());
+        for parameter in &macro_def.parameters {
+            if let Some(name) = &parameter.name {
+                self.define(name, SymbolKind::Argument, parameter.span.0.first().cloned());
+            }
+        }
+        macro_def.statements.iter().for_each(|s| self.visit_statement(s));
+        self.scope = None;
+    }
+
+    fn visit_macro_invocation(&mut self, invocation: &MacroInvocation) {
+        self.reference(&invocation.macro_name, &[SymbolKind::Macro], invocation.span.0.first());
+        walk_macro_invocation(self, invocation);
+    }
+
+    fn visit_statement(&mut self, statement: &Statement) {
+        let span = statement.span.0.first();
+        match &statement.ty {
+            StatementType::Constant(name) => {
+                self.reference(name, &[SymbolKind::Constant, SymbolKind::Immutable], span)
+            }
+            StatementType::ArgCall(name) => self.reference(name, &[SymbolKind::Argument], span),
+            StatementType::LabelCall(name) => self.reference(name, &[SymbolKind::Label], span),
+            _ => {}
+        }
+        walk_statement(self, statement);
+    }
+
+    fn visit_label(&mut self, label: &Label) {
+        self.define(&label.name, SymbolKind::Label, label.span.0.first().cloned());
+        walk_label(self, label);
+    }
+
+    fn visit_builtin_function_call(&mut self, call: &BuiltinFunctionCall) {
+        let kinds: &'static [SymbolKind] = match call.kind {
+            BuiltinFunctionKind::Tablesize | BuiltinFunctionKind::Tablestart => {
+                &[SymbolKind::Table]
+            }
+            BuiltinFunctionKind::Codesize => &[SymbolKind::Macro],
+            BuiltinFunctionKind::FunctionSignature => &[SymbolKind::Function],
+            BuiltinFunctionKind::EventHash => &[SymbolKind::Event],
+            BuiltinFunctionKind::Error => &[SymbolKind::Error],
+            _ => return,
+        };
+        for arg in &call.args {
+            // Signatures passed as strings, eg. `__FUNC_SIG("transfer(address,uint256)")`, don't
+            // reference a definition
+            let Some(name) = arg.name.as_ref().filter(|n| !n.contains('(')) else { continue };
+            self.reference(name, kinds, arg.span.0.first());
+        }
+    }
+
+    fn visit_constant_definition(&mut self, constant: &ConstantDefinition) {
+        self.define(
+            &constant.name,
+            SymbolKind::Constant,
+            name_span(&constant.span, &constant.name),
+        );
+    }
+
+    fn visit_immutable_definition(&mut self, immutable: &ImmutableDefinition) {
+        let span = name_span(&immutable.span, &immutable.name);
+        self.define(&immutable.name, SymbolKind::Immutable, span);
+    }
+
+    fn visit_error_definition(&mut self, error: &ErrorDefinition) {
+        self.define(&error.name, SymbolKind::Error, name_span(&error.span, &error.name));
+    }
+
+    fn visit_function_definition(&mut self, function: &FunctionDefinition) {
+        let span = name_span(&function.span, &function.name);
+        self.define(&function.name, SymbolKind::Function, span);
+    }
+
+    fn visit_event_definition(&mut self, event: &EventDefinition) {
+        self.define(&event.name, SymbolKind::Event, name_span(&event.span, &event.name));
+    }
+
+    fn visit_table_definition(&mut self, table: &TableDefinition) {
+        self.define(&table.name, SymbolKind::Table, name_span(&table.span, &table.name));
+        walk_table_definition(self, table);
+    }
+}
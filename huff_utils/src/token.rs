@@ -1,10 +1,11 @@
-use crate::{evm::Opcode, files::Span, types::PrimitiveEVMType};
+use crate::{bytes_util::serialize_literal, evm::Opcode, files::Span, types::PrimitiveEVMType};
+use serde::Serialize;
 use std::{fmt, fmt::Write};
 
 type Literal = [u8; 32];
 
 /// A single Token
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
 pub struct Token {
     /// The kind of token
     pub kind: TokenKind,
@@ -20,7 +21,7 @@ impl Token {
 }
 
 /// The kind of token
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+#[derive(Serialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
 pub enum TokenKind {
     /// EOF Token
     Eof,
@@ -33,6 +34,16 @@ pub enum TokenKind {
     Define,
     /// "#include" keyword
     Include,
+    /// "#ifdef" directive
+    IfDef,
+    /// "#ifndef" directive
+    IfNDef,
+    /// "#else" directive
+    Else,
+    /// "#endif" directive
+    EndIf,
+    /// "#assert" directive
+    Assert,
     /// "macro" keyword
     Macro,
     /// "fn" keyword
@@ -45,6 +56,8 @@ pub enum TokenKind {
     Event,
     /// "constant" keyword
     Constant,
+    /// "immutable" keyword
+    Immutable,
     /// "error" keyword
     Error,
     /// "takes" keyword
@@ -83,6 +96,14 @@ pub enum TokenKind {
     LeftAngle,
     /// A Greater-Than Angle Bracket
     RightAngle,
+    /// An equality comparison, "=="
+    Equal,
+    /// An inequality comparison, "!="
+    NotEqual,
+    /// A less-than-or-equal comparison, "<="
+    LessEqual,
+    /// A greater-than-or-equal comparison, ">="
+    GreaterEqual,
     /// Addition
     Add,
     /// Subtraction
@@ -101,8 +122,8 @@ pub enum TokenKind {
     Whitespace,
     /// A string literal
     Str(String),
-    /// Hex
-    Literal(Literal),
+    /// Hex, with the number of bytes it's written with, leading zeros included
+    Literal(#[serde(serialize_with = "serialize_literal")] Literal, usize),
     /// Opcode
     Opcode(Opcode),
     /// Huff label (aka PC)
@@ -123,6 +144,8 @@ pub enum TokenKind {
     CodeTable,
     /// A builtin function (__codesize, __tablesize, __tablestart)
     BuiltinFunction(String),
+    /// A compile-time loop, "__REPEAT"
+    Repeat,
     /// Calldata Data Location
     Calldata,
     /// Memory Data Location
@@ -151,12 +174,18 @@ impl fmt::Display for TokenKind {
             TokenKind::Div => "/",
             TokenKind::Define => "#define",
             TokenKind::Include => "#include",
+            TokenKind::IfDef => "#ifdef",
+            TokenKind::IfNDef => "#ifndef",
+            TokenKind::Else => "#else",
+            TokenKind::EndIf => "#endif",
+            TokenKind::Assert => "#assert",
             TokenKind::Macro => "macro",
             TokenKind::Fn => "fn",
             TokenKind::Test => "test",
             TokenKind::Function => "function",
             TokenKind::Event => "event",
             TokenKind::Constant => "constant",
+            TokenKind::Immutable => "immutable",
             TokenKind::Error => "error",
             TokenKind::View => "view",
             TokenKind::Pure => "pure",
@@ -176,6 +205,10 @@ impl fmt::Display for TokenKind {
             TokenKind::CloseBrace => "}",
             TokenKind::LeftAngle => "<",
             TokenKind::RightAngle => ">",
+            TokenKind::Equal => "==",
+            TokenKind::NotEqual => "!=",
+            TokenKind::LessEqual => "<=",
+            TokenKind::GreaterEqual => ">=",
             TokenKind::Add => "+",
             TokenKind::Sub => "-",
             TokenKind::Mul => "*",
@@ -185,7 +218,7 @@ impl fmt::Display for TokenKind {
             TokenKind::Num(num) => return write!(f, "{num}"),
             TokenKind::Whitespace => " ",
             TokenKind::Str(str) => str,
-            TokenKind::Literal(l) => {
+            TokenKind::Literal(l, _) => {
                 let mut s = String::new();
                 for b in l.iter() {
                     let _ = write!(&mut s, "{b:02x}");
@@ -207,6 +240,7 @@ impl fmt::Display for TokenKind {
             TokenKind::JumpTablePacked => "jumptable__packed",
             TokenKind::CodeTable => "table",
             TokenKind::BuiltinFunction(s) => return write!(f, "BuiltinFunction({s})"),
+            TokenKind::Repeat => "__REPEAT",
             TokenKind::Calldata => return write!(f, "calldata"),
             TokenKind::Memory => return write!(f, "memory"),
             TokenKind::Storage => return write!(f, "storage"),
@@ -2,10 +2,11 @@ use crate::bytes_util::*;
 use ethers_core::abi::{ethereum_types::*, token::*, Tokenizable};
 use lazy_static::lazy_static;
 use regex::Regex;
+use serde::Serialize;
 use std::{fmt, str::FromStr};
 
 /// Primitive EVM types
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[derive(Serialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub enum PrimitiveEVMType {
     /// String type
     String,
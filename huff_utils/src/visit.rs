@@ -0,0 +1,565 @@
+//! ## AST Traversal
+//!
+//! The [Visit] and [VisitMut] traits walk the AST of a [Contract] depth-first, in source order
+//! within each kind of definition. Every method defaults to walking the children of its node, so
+//! implementors only override the nodes they care about, calling the matching `walk_*` function to
+//! keep descending.
+//!
+//! ```rust
+//! use huff_utils::{prelude::*, visit::*};
+//!
+//! /// Counts the opcodes used by a contract
+//! #[derive(Default)]
+//! struct OpcodeCounter(usize);
+//!
+//! impl Visit for OpcodeCounter {
+//!     fn visit_statement(&mut self, statement: &Statement) {
+//!         if let StatementType::Opcode(_) = statement.ty {
+//!             self.0 += 1;
+//!         }
+//!         walk_statement(self, statement);
+//!     }
+//! }
+//!
+//! let mut counter = OpcodeCounter::default();
+//! counter.visit_contract(&Contract::default());
+//! assert_eq!(counter.0, 0);
+//! ```
+
+use crate::ast::*;
+
+/// A read-only visitor of the AST
+pub trait Visit {
+    /// Visits a contract and all of its definitions
+    fn visit_contract(&mut self, contract: &Contract) {
+        walk_contract(self, contract);
+    }
+
+    /// Visits a selective import
+    fn visit_selective_import(&mut self, import: &SelectiveImport) {
+        walk_selective_import(self, import);
+    }
+
+    /// Visits a namespaced import
+    fn visit_namespaced_import(&mut self, import: &NamespacedImport) {
+        walk_namespaced_import(self, import);
+    }
+
+    /// Visits a macro definition
+    fn visit_macro_definition(&mut self, macro_def: &MacroDefinition) {
+        walk_macro_definition(self, macro_def);
+    }
+
+    /// Visits a macro invocation, in a statement, an argument or at the top-level
+    fn visit_macro_invocation(&mut self, invocation: &MacroInvocation) {
+        walk_macro_invocation(self, invocation);
+    }
+
+    /// Visits an argument passed to a macro invocation
+    fn visit_macro_arg(&mut self, arg: &MacroArg) {
+        walk_macro_arg(self, arg);
+    }
+
+    /// Visits a statement of a macro, label, table or repeat block
+    fn visit_statement(&mut self, statement: &Statement) {
+        walk_statement(self, statement);
+    }
+
+    /// Visits a label and the statements following it
+    fn visit_label(&mut self, label: &Label) {
+        walk_label(self, label);
+    }
+
+    /// Visits a builtin function call
+    fn visit_builtin_function_call(&mut self, call: &BuiltinFunctionCall) {
+        walk_builtin_function_call(self, call);
+    }
+
+    /// Visits a compile-time loop
+    fn visit_repeat_block(&mut self, repeat: &RepeatBlock) {
+        walk_repeat_block(self, repeat);
+    }
+
+    /// Visits a parameter of a macro, function, event or error, or a builtin function argument
+    fn visit_argument(&mut self, argument: &Argument) {
+        walk_argument(self, argument);
+    }
+
+    /// Visits a constant definition
+    fn visit_constant_definition(&mut self, constant: &ConstantDefinition) {
+        walk_constant_definition(self, constant);
+    }
+
+    /// Visits a constant expression, of a constant, repeat count or assertion
+    fn visit_const_expression(&mut self, expression: &ConstExpression) {
+        walk_const_expression(self, expression);
+    }
+
+    /// Visits an immutable definition
+    fn visit_immutable_definition(&mut self, immutable: &ImmutableDefinition) {
+        walk_immutable_definition(self, immutable);
+    }
+
+    /// Visits an error definition
+    fn visit_error_definition(&mut self, error: &ErrorDefinition) {
+        walk_error_definition(self, error);
+    }
+
+    /// Visits a function definition
+    fn visit_function_definition(&mut self, function: &FunctionDefinition) {
+        walk_function_definition(self, function);
+    }
+
+    /// Visits an event definition
+    fn visit_event_definition(&mut self, event: &EventDefinition) {
+        walk_event_definition(self, event);
+    }
+
+    /// Visits a table definition
+    fn visit_table_definition(&mut self, table: &TableDefinition) {
+        walk_table_definition(self, table);
+    }
+
+    /// Visits a compile-time assertion
+    fn visit_assertion_definition(&mut self, assertion: &AssertionDefinition) {
+        walk_assertion_definition(self, assertion);
+    }
+
+    /// Visits a definition that failed to parse
+    fn visit_invalid_definition(&mut self, invalid: &InvalidDefinition) {
+        walk_invalid_definition(self, invalid);
+    }
+
+    /// Visits the span of a node
+    fn visit_span(&mut self, _span: &AstSpan) {}
+}
+
+/// Walks the definitions of a contract
+pub fn walk_contract<V: Visit + ?Sized>(v: &mut V, contract: &Contract) {
+    contract.selective_imports.iter().for_each(|i| v.visit_selective_import(i));
+    contract.namespaced_imports.iter().for_each(|i| v.visit_namespaced_import(i));
+    if let Ok(constants) = contract.constants.lock() {
+        constants.iter().for_each(|c| v.visit_constant_definition(c));
+    }
+    contract.immutables.iter().for_each(|i| v.visit_immutable_definition(i));
+    contract.errors.iter().for_each(|e| v.visit_error_definition(e));
+    contract.functions.iter().for_each(|f| v.visit_function_definition(f));
+    contract.events.iter().for_each(|e| v.visit_event_definition(e));
+    contract.tables.iter().for_each(|t| v.visit_table_definition(t));
+    contract.macros.iter().for_each(|m| v.visit_macro_definition(m));
+    contract.invocations.iter().for_each(|mi| v.visit_macro_invocation(mi));
+    contract.assertions.iter().for_each(|a| v.visit_assertion_definition(a));
+    contract.invalid_definitions.iter().for_each(|d| v.visit_invalid_definition(d));
+}
+
+/// Walks a selective import
+pub fn walk_selective_import<V: Visit + ?Sized>(v: &mut V, import: &SelectiveImport) {
+    v.visit_span(&import.span);
+}
+
+/// Walks a namespaced import
+pub fn walk_namespaced_import<V: Visit + ?Sized>(v: &mut V, import: &NamespacedImport) {
+    v.visit_span(&import.span);
+}
+
+/// Walks the parameters and statements of a macro definition
+pub fn walk_macro_definition<V: Visit + ?Sized>(v: &mut V, macro_def: &MacroDefinition) {
+    v.visit_span(&macro_def.span);
+    macro_def.parameters.iter().for_each(|a| v.visit_argument(a));
+    macro_def.statements.iter().for_each(|s| v.visit_statement(s));
+}
+
+/// Walks the arguments of a macro invocation
+pub fn walk_macro_invocation<V: Visit + ?Sized>(v: &mut V, invocation: &MacroInvocation) {
+    v.visit_span(&invocation.span);
+    invocation.args.iter().for_each(|a| v.visit_macro_arg(a));
+}
+
+/// Walks a macro argument, descending into nested invocations
+pub fn walk_macro_arg<V: Visit + ?Sized>(v: &mut V, arg: &MacroArg) {
+    if let MacroArg::MacroCall(invocation) = arg {
+        v.visit_macro_invocation(invocation);
+    }
+}
+
+/// Walks a statement, descending into invocations, labels, builtins and repeat blocks
+pub fn walk_statement<V: Visit + ?Sized>(v: &mut V, statement: &Statement) {
+    v.visit_span(&statement.span);
+    match &statement.ty {
+        StatementType::MacroInvocation(mi) => v.visit_macro_invocation(mi),
+        StatementType::Label(l) => v.visit_label(l),
+        StatementType::BuiltinFunctionCall(bf) => v.visit_builtin_function_call(bf),
+        StatementType::Repeat(r) => v.visit_repeat_block(r),
+        StatementType::Literal(_) |
+        StatementType::Opcode(_) |
+        StatementType::Code(_) |
+        StatementType::Constant(_) |
+        StatementType::ArgCall(_) |
+        StatementType::LabelCall(_) => {}
+    }
+}
+
+/// Walks the statements of a label
+pub fn walk_label<V: Visit + ?Sized>(v: &mut V, label: &Label) {
+    v.visit_span(&label.span);
+    label.inner.iter().for_each(|s| v.visit_statement(s));
+}
+
+/// Walks the arguments of a builtin function call
+pub fn walk_builtin_function_call<V: Visit + ?Sized>(v: &mut V, call: &BuiltinFunctionCall) {
+    v.visit_span(&call.span);
+    call.args.iter().for_each(|a| v.visit_argument(a));
+}
+
+/// Walks the count and statements of a repeat block
+pub fn walk_repeat_block<V: Visit + ?Sized>(v: &mut V, repeat: &RepeatBlock) {
+    v.visit_span(&repeat.span);
+    v.visit_const_expression(&repeat.count);
+    repeat.statements.iter().for_each(|s| v.visit_statement(s));
+}
+
+/// Walks an argument
+pub fn walk_argument<V: Visit + ?Sized>(v: &mut V, argument: &Argument) {
+    v.visit_span(&argument.span);
+}
+
+/// Walks the value of a constant definition
+pub fn walk_constant_definition<V: Visit + ?Sized>(v: &mut V, constant: &ConstantDefinition) {
+    v.visit_span(&constant.span);
+    if let ConstVal::Expression(expression) = &constant.value {
+        v.visit_const_expression(expression);
+    }
+}
+
+/// Walks the operands of a constant expression
+pub fn walk_const_expression<V: Visit + ?Sized>(v: &mut V, expression: &ConstExpression) {
+    if let ConstExpression::Operation(_, operands) = expression {
+        operands.iter().for_each(|e| v.visit_const_expression(e));
+    }
+}
+
+/// Walks an immutable definition
+pub fn walk_immutable_definition<V: Visit + ?Sized>(v: &mut V, immutable: &ImmutableDefinition) {
+    v.visit_span(&immutable.span);
+}
+
+/// Walks the parameters of an error definition
+pub fn walk_error_definition<V: Visit + ?Sized>(v: &mut V, error: &ErrorDefinition) {
+    v.visit_span(&error.span);
+    error.parameters.iter().for_each(|a| v.visit_argument(a));
+}
+
+/// Walks the inputs and outputs of a function definition
+pub fn walk_function_definition<V: Visit + ?Sized>(v: &mut V, function: &FunctionDefinition) {
+    v.visit_span(&function.span);
+    function.inputs.iter().for_each(|a| v.visit_argument(a));
+    function.outputs.iter().for_each(|a| v.visit_argument(a));
+}
+
+/// Walks the parameters of an event definition
+pub fn walk_event_definition<V: Visit + ?Sized>(v: &mut V, event: &EventDefinition) {
+    v.visit_span(&event.span);
+    event.parameters.iter().for_each(|a| v.visit_argument(a));
+}
+
+/// Walks the statements of a table definition
+pub fn walk_table_definition<V: Visit + ?Sized>(v: &mut V, table: &TableDefinition) {
+    v.visit_span(&table.span);
+    table.statements.iter().for_each(|s| v.visit_statement(s));
+}
+
+/// Walks the operands of a compile-time assertion
+pub fn walk_assertion_definition<V: Visit + ?Sized>(v: &mut V, assertion: &AssertionDefinition) {
+    v.visit_span(&assertion.span);
+    for operand in [&assertion.lhs, &assertion.rhs] {
+        if let AssertionOperand::Expression(expression) = operand {
+            v.visit_const_expression(expression);
+        }
+    }
+}
+
+/// Walks a definition that failed to parse, including the spans of its error
+pub fn walk_invalid_definition<V: Visit + ?Sized>(v: &mut V, invalid: &InvalidDefinition) {
+    v.visit_span(&invalid.span);
+    v.visit_span(&invalid.error.spans);
+}
+
+/// A visitor of the AST that can modify the nodes it visits
+pub trait VisitMut {
+    /// Visits a contract and all of its definitions
+    fn visit_contract_mut(&mut self, contract: &mut Contract) {
+        walk_contract_mut(self, contract);
+    }
+
+    /// Visits a selective import
+    fn visit_selective_import_mut(&mut self, import: &mut SelectiveImport) {
+        walk_selective_import_mut(self, import);
+    }
+
+    /// Visits a namespaced import
+    fn visit_namespaced_import_mut(&mut self, import: &mut NamespacedImport) {
+        walk_namespaced_import_mut(self, import);
+    }
+
+    /// Visits a macro definition
+    fn visit_macro_definition_mut(&mut self, macro_def: &mut MacroDefinition) {
+        walk_macro_definition_mut(self, macro_def);
+    }
+
+    /// Visits a macro invocation, in a statement, an argument or at the top-level
+    fn visit_macro_invocation_mut(&mut self, invocation: &mut MacroInvocation) {
+        walk_macro_invocation_mut(self, invocation);
+    }
+
+    /// Visits an argument passed to a macro invocation
+    fn visit_macro_arg_mut(&mut self, arg: &mut MacroArg) {
+        walk_macro_arg_mut(self, arg);
+    }
+
+    /// Visits a statement of a macro, label, table or repeat block
+    fn visit_statement_mut(&mut self, statement: &mut Statement) {
+        walk_statement_mut(self, statement);
+    }
+
+    /// Visits a label and the statements following it
+    fn visit_label_mut(&mut self, label: &mut Label) {
+        walk_label_mut(self, label);
+    }
+
+    /// Visits a builtin function call
+    fn visit_builtin_function_call_mut(&mut self, call: &mut BuiltinFunctionCall) {
+        walk_builtin_function_call_mut(self, call);
+    }
+
+    /// Visits a compile-time loop
+    fn visit_repeat_block_mut(&mut self, repeat: &mut RepeatBlock) {
+        walk_repeat_block_mut(self, repeat);
+    }
+
+    /// Visits a parameter of a macro, function, event or error, or a builtin function argument
+    fn visit_argument_mut(&mut self, argument: &mut Argument) {
+        walk_argument_mut(self, argument);
+    }
+
+    /// Visits a constant definition
+    fn visit_constant_definition_mut(&mut self, constant: &mut ConstantDefinition) {
+        walk_constant_definition_mut(self, constant);
+    }
+
+    /// Visits a constant expression, of a constant, repeat count or assertion
+    fn visit_const_expression_mut(&mut self, expression: &mut ConstExpression) {
+        walk_const_expression_mut(self, expression);
+    }
+
+    /// Visits an immutable definition
+    fn visit_immutable_definition_mut(&mut self, immutable: &mut ImmutableDefinition) {
+        walk_immutable_definition_mut(self, immutable);
+    }
+
+    /// Visits an error definition
+    fn visit_error_definition_mut(&mut self, error: &mut ErrorDefinition) {
+        walk_error_definition_mut(self, error);
+    }
+
+    /// Visits a function definition
+    fn visit_function_definition_mut(&mut self, function: &mut FunctionDefinition) {
+        walk_function_definition_mut(self, function);
+    }
+
+    /// Visits an event definition
+    fn visit_event_definition_mut(&mut self, event: &mut EventDefinition) {
+        walk_event_definition_mut(self, event);
+    }
+
+    /// Visits a table definition
+    fn visit_table_definition_mut(&mut self, table: &mut TableDefinition) {
+        walk_table_definition_mut(self, table);
+    }
+
+    /// Visits a compile-time assertion
+    fn visit_assertion_definition_mut(&mut self, assertion: &mut AssertionDefinition) {
+        walk_assertion_definition_mut(self, assertion);
+    }
+
+    /// Visits a definition that failed to parse
+    fn visit_invalid_definition_mut(&mut self, invalid: &mut InvalidDefinition) {
+        walk_invalid_definition_mut(self, invalid);
+    }
+
+    /// Visits the span of a node
+    fn visit_span_mut(&mut self, _span: &mut AstSpan) {}
+}
+
+/// Walks the definitions of a contract
+pub fn walk_contract_mut<V: VisitMut + ?Sized>(v: &mut V, contract: &mut Contract) {
+    contract.selective_imports.iter_mut().for_each(|i| v.visit_selective_import_mut(i));
+    contract.namespaced_imports.iter_mut().for_each(|i| v.visit_namespaced_import_mut(i));
+    if let Ok(mut constants) = contract.constants.lock() {
+        constants.iter_mut().for_each(|c| v.visit_constant_definition_mut(c));
+    }
+    contract.immutables.iter_mut().for_each(|i| v.visit_immutable_definition_mut(i));
+    contract.errors.iter_mut().for_each(|e| v.visit_error_definition_mut(e));
+    contract.functions.iter_mut().for_each(|f| v.visit_function_definition_mut(f));
+    contract.events.iter_mut().for_each(|e| v.visit_event_definition_mut(e));
+    contract.tables.iter_mut().for_each(|t| v.visit_table_definition_mut(t));
+    contract.macros.iter_mut().for_each(|m| v.visit_macro_definition_mut(m));
+    contract.invocations.iter_mut().for_each(|mi| v.visit_macro_invocation_mut(mi));
+    contract.assertions.iter_mut().for_each(|a| v.visit_assertion_definition_mut(a));
+    contract.invalid_definitions.iter_mut().for_each(|d| v.visit_invalid_definition_mut(d));
+}
+
+/// Walks a selective import
+pub fn walk_selective_import_mut<V: VisitMut + ?Sized>(v: &mut V, import: &mut SelectiveImport) {
+    v.visit_span_mut(&mut import.span);
+}
+
+/// Walks a namespaced import
+pub fn walk_namespaced_import_mut<V: VisitMut + ?Sized>(v: &mut V, import: &mut NamespacedImport) {
+    v.visit_span_mut(&mut import.span);
+}
+
+/// Walks the parameters and statements of a macro definition
+pub fn walk_macro_definition_mut<V: VisitMut + ?Sized>(v: &mut V, macro_def: &mut MacroDefinition) {
+    v.visit_span_mut(&mut macro_def.span);
+    macro_def.parameters.iter_mut().for_each(|a| v.visit_argument_mut(a));
+    macro_def.statements.iter_mut().for_each(|s| v.visit_statement_mut(s));
+}
+
+/// Walks the arguments of a macro invocation
+pub fn walk_macro_invocation_mut<V: VisitMut + ?Sized>(
+    v: &mut V,
+    invocation: &mut MacroInvocation,
+) {
+    v.visit_span_mut(&mut invocation.span);
+    invocation.args.iter_mut().for_each(|a| v.visit_macro_arg_mut(a));
+}
+
+/// Walks a macro argument, descending into nested invocations
+pub fn walk_macro_arg_mut<V: VisitMut + ?Sized>(v: &mut V, arg: &mut MacroArg) {
+    if let MacroArg::MacroCall(invocation) = arg {
+        v.visit_macro_invocation_mut(invocation);
+    }
+}
+
+/// Walks a statement, descending into invocations, labels, builtins and repeat blocks
+pub fn walk_statement_mut<V: VisitMut + ?Sized>(v: &mut V, statement: &mut Statement) {
+    v.visit_span_mut(&mut statement.span);
+    match &mut statement.ty {
+        StatementType::MacroInvocation(mi) => v.visit_macro_invocation_mut(mi),
+        StatementType::Label(l) => v.visit_label_mut(l),
+        StatementType::BuiltinFunctionCall(bf) => v.visit_builtin_function_call_mut(bf),
+        StatementType::Repeat(r) => v.visit_repeat_block_mut(r),
+        StatementType::Literal(_) |
+        StatementType::Opcode(_) |
+        StatementType::Code(_) |
+        StatementType::Constant(_) |
+        StatementType::ArgCall(_) |
+        StatementType::LabelCall(_) => {}
+    }
+}
+
+/// Walks the statements of a label
+pub fn walk_label_mut<V: VisitMut + ?Sized>(v: &mut V, label: &mut Label) {
+    v.visit_span_mut(&mut label.span);
+    label.inner.iter_mut().for_each(|s| v.visit_statement_mut(s));
+}
+
+/// Walks the arguments of a builtin function call
+pub fn walk_builtin_function_call_mut<V: VisitMut + ?Sized>(
+    v: &mut V,
+    call: &mut BuiltinFunctionCall,
+) {
+    v.visit_span_mut(&mut call.span);
+    call.args.iter_mut().for_each(|a| v.visit_argument_mut(a));
+}
+
+/// Walks the count and statements of a repeat block
+pub fn walk_repeat_block_mut<V: VisitMut + ?Sized>(v: &mut V, repeat: &mut RepeatBlock) {
+    v.visit_span_mut(&mut repeat.span);
+    v.visit_const_expression_mut(&mut repeat.count);
+    repeat.statements.iter_mut().for_each(|s| v.visit_statement_mut(s));
+}
+
+/// Walks an argument
+pub fn walk_argument_mut<V: VisitMut + ?Sized>(v: &mut V, argument: &mut Argument) {
+    v.visit_span_mut(&mut argument.span);
+}
+
+/// Walks the value of a constant definition
+pub fn walk_constant_definition_mut<V: VisitMut + ?Sized>(
+    v: &mut V,
+    constant: &mut ConstantDefinition,
+) {
+    v.visit_span_mut(&mut constant.span);
+    if let ConstVal::Expression(expression) = &mut constant.value {
+        v.visit_const_expression_mut(expression);
+    }
+}
+
+/// Walks the operands of a constant expression
+pub fn walk_const_expression_mut<V: VisitMut + ?Sized>(
+    v: &mut V,
+    expression: &mut ConstExpression,
+) {
+    if let ConstExpression::Operation(_, operands) = expression {
+        operands.iter_mut().for_each(|e| v.visit_const_expression_mut(e));
+    }
+}
+
+/// Walks an immutable definition
+pub fn walk_immutable_definition_mut<V: VisitMut + ?Sized>(
+    v: &mut V,
+    immutable: &mut ImmutableDefinition,
+) {
+    v.visit_span_mut(&mut immutable.span);
+}
+
+/// Walks the parameters of an error definition
+pub fn walk_error_definition_mut<V: VisitMut + ?Sized>(v: &mut V, error: &mut ErrorDefinition) {
+    v.visit_span_mut(&mut error.span);
+    error.parameters.iter_mut().for_each(|a| v.visit_argument_mut(a));
+}
+
+/// Walks the inputs and outputs of a function definition
+pub fn walk_function_definition_mut<V: VisitMut + ?Sized>(
+    v: &mut V,
+    function: &mut FunctionDefinition,
+) {
+    v.visit_span_mut(&mut function.span);
+    function.inputs.iter_mut().for_each(|a| v.visit_argument_mut(a));
+    function.outputs.iter_mut().for_each(|a| v.visit_argument_mut(a));
+}
+
+/// Walks the parameters of an event definition
+pub fn walk_event_definition_mut<V: VisitMut + ?Sized>(v: &mut V, event: &mut EventDefinition) {
+    v.visit_span_mut(&mut event.span);
+    event.parameters.iter_mut().for_each(|a| v.visit_argument_mut(a));
+}
+
+/// Walks the statements of a table definition
+pub fn walk_table_definition_mut<V: VisitMut + ?Sized>(v: &mut V, table: &mut TableDefinition) {
+    v.visit_span_mut(&mut table.span);
+    table.statements.iter_mut().for_each(|s| v.visit_statement_mut(s));
+}
+
+/// Walks the operands of a compile-time assertion
+pub fn walk_assertion_definition_mut<V: VisitMut + ?Sized>(
+    v: &mut V,
+    assertion: &mut AssertionDefinition,
+) {
+    v.visit_span_mut(&mut assertion.span);
+    for operand in [&mut assertion.lhs, &mut assertion.rhs] {
+        if let AssertionOperand::Expression(expression) = operand {
+            v.visit_const_expression_mut(expression);
+        }
+    }
+}
+
+/// Walks a definition that failed to parse, including the spans of its error
+pub fn walk_invalid_definition_mut<V: VisitMut + ?Sized>(
+    v: &mut V,
+    invalid: &mut InvalidDefinition,
+) {
+    v.visit_span_mut(&mut invalid.span);
+    v.visit_span_mut(&mut invalid.error.spans);
+}
@@ -0,0 +1,72 @@
+use ethers_core::utils::{hex, keccak256, to_checksum};
+use huff_utils::{artifact::*, bytes_util::str_to_bytes32};
+
+fn artifact(bytecode: &str) -> Artifact {
+    let mut artifact = Artifact { bytecode: bytecode.to_string(), ..Default::default() };
+    artifact.initcode_hash = artifact.hash_initcode();
+    artifact
+}
+
+fn predict(artifact: &Artifact, deployer: &str, deployment: Deployment) -> String {
+    to_checksum(&artifact.predict_address(deployer.parse().unwrap(), &deployment), None)
+}
+
+#[test]
+fn hashes_initcode() {
+    let artifact = artifact("deadbeef");
+    assert_eq!(
+        artifact.initcode_hash,
+        format!("0x{}", hex::encode(keccak256(hex::decode("deadbeef").unwrap())))
+    );
+}
+
+#[test]
+fn predicts_create2_addresses() {
+    // The examples of EIP-1014
+    let salt = Deployment::Create2 { salt: [0; 32] };
+    assert_eq!(
+        predict(&artifact("00"), "0x0000000000000000000000000000000000000000", salt),
+        "0x4D1A2e2bB4F88F0250f26Ffff098B0b30B26BF38"
+    );
+    assert_eq!(
+        predict(&artifact("00"), "0xdeadbeef00000000000000000000000000000000", salt),
+        "0xB928f69Bb1D91Cd65274e3c79d8986362984fDA3"
+    );
+    let salt = Deployment::Create2 { salt: str_to_bytes32("cafebabe") };
+    assert_eq!(
+        predict(&artifact("deadbeef"), "0x00000000000000000000000000000000deadbeef", salt),
+        "0x60f3f640a8508fC6a86d45DF051962668E1e8AC7"
+    );
+}
+
+#[test]
+fn predicts_create_addresses() {
+    let deployer = "0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0";
+    let address = predict(&artifact("00"), deployer, Deployment::Create { nonce: 0 });
+    assert_eq!(address.to_lowercase(), "0xcd234a471b72ba2f1ccf0a70fcaba648a5eecd8d");
+
+    // The initcode doesn't affect CREATE addresses
+    let address = predict(&artifact("deadbeef"), deployer, Deployment::Create { nonce: 1 });
+    assert_eq!(address.to_lowercase(), "0x343c43a37d37dff08ae8c4a11544c718abb4fcf8");
+}
+
+#[test]
+fn serializes_creation_and_deployed_bytecode() {
+    let artifact = Artifact {
+        bytecode: "60018060093d393df35f".to_string(),
+        runtime: "5f".to_string(),
+        ..Default::default()
+    };
+    let json: serde_json::Value = serde_json::to_value(&artifact).unwrap();
+    assert_eq!(json["bytecode"], "60018060093d393df35f");
+    assert_eq!(json["deployedBytecode"], "5f");
+    assert!(json.get("runtime").is_none());
+
+    // Artifacts written before the rename still load
+    let legacy = serde_json::to_string(&json)
+        .unwrap()
+        .replace("\"deployedBytecode\"", "\"runtime\"")
+        .replace("\"deployedSourceMap\"", "\"runtimeSourceMap\"");
+    let loaded: Artifact = serde_json::from_str(&legacy).unwrap();
+    assert_eq!(loaded, artifact);
+}
@@ -18,3 +18,51 @@ fn test_hex_to_usize() {
         assert_eq!(converted_usize, i);
     }
 }
+
+#[test]
+fn test_parse_hex_literal() {
+    assert_eq!(parse_hex_literal("0xa57b"), Some(str_to_bytes32("a57b")));
+    assert_eq!(parse_hex_literal("0x00"), Some([0u8; 32]));
+    assert_eq!(parse_hex_literal(&format!("0x0000{}", "ff".repeat(32))), Some([0xff; 32]));
+    assert_eq!(parse_hex_literal(&format!("0x01{}", "00".repeat(32))), None);
+    assert_eq!(parse_hex_literal("a57b"), None);
+    assert_eq!(parse_hex_literal("0x"), None);
+    assert_eq!(parse_hex_literal("0xzz"), None);
+}
+
+#[test]
+fn test_parse_decimal_literal() {
+    assert_eq!(parse_decimal_literal("1000000"), Some(str_to_bytes32("0f4240")));
+    assert_eq!(parse_decimal_literal("0"), Some([0u8; 32]));
+    assert_eq!(
+        parse_decimal_literal(
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        ),
+        Some([0xff; 32])
+    );
+    assert_eq!(
+        parse_decimal_literal(
+            "115792089237316195423570985008687907853269984665640564039457584007913129639936"
+        ),
+        None
+    );
+    assert_eq!(parse_decimal_literal("0x10"), None);
+    assert_eq!(parse_decimal_literal(""), None);
+}
+
+#[test]
+fn test_parse_binary_literal() {
+    assert_eq!(parse_binary_literal("1010"), Some(str_to_bytes32("0a")));
+    assert_eq!(parse_binary_literal("0000"), Some([0u8; 32]));
+    assert_eq!(parse_binary_literal(&format!("0{}", "1".repeat(256))), Some([0xff; 32]));
+    assert_eq!(parse_binary_literal(&format!("1{}", "0".repeat(256))), None);
+    assert_eq!(parse_binary_literal("102"), None);
+    assert_eq!(parse_binary_literal(""), None);
+}
+
+#[test]
+fn test_negate_literal() {
+    assert_eq!(negate_literal(&str_to_bytes32("01")), [0xff; 32]);
+    assert_eq!(negate_literal(&[0xff; 32]), str_to_bytes32("01"));
+    assert_eq!(negate_literal(&[0u8; 32]), [0u8; 32]);
+}
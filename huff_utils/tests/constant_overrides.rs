@@ -0,0 +1,41 @@
+use huff_utils::prelude::*;
+
+#[test]
+fn parses_constant_overrides() {
+    let (name, value) = ConstantDefinition::parse_override("OWNER_2=0xa57b").unwrap();
+    assert_eq!(name, "OWNER_2");
+    assert_eq!(value, str_to_bytes32("a57b"));
+
+    let (name, _) = ConstantDefinition::parse_override("Math.FEE=0x64").unwrap();
+    assert_eq!(name, "Math.FEE");
+
+    // Leading zeros don't count towards the width
+    let padded = format!("0x00{}", "ff".repeat(32));
+    let (_, value) = ConstantDefinition::parse_override(&format!("MAX={padded}")).unwrap();
+    assert_eq!(value, [0xff; 32]);
+}
+
+#[test]
+fn rejects_invalid_constant_overrides() {
+    for arg in [
+        "OWNER",
+        "2OWNER=0x01",
+        "OWN-ER=0x01",
+        ".FEE=0x01",
+        "FEE=",
+        "FEE=100",
+        "FEE=0x",
+        "FEE=0xzz",
+    ] {
+        assert!(ConstantDefinition::parse_override(arg).is_err(), "{arg}");
+    }
+
+    let too_wide = format!("FEE=0x01{}", "00".repeat(32));
+    assert_eq!(
+        ConstantDefinition::parse_override(&too_wide).unwrap_err(),
+        format!(
+            "Invalid value \"{}\" for constant \"FEE\", expected a hex literal of at most 32 bytes",
+            &too_wide[4..]
+        )
+    );
+}
@@ -0,0 +1,236 @@
+use std::sync::Arc;
+
+use huff_utils::{diagnostics::*, prelude::*, remote::RemoteImportError};
+
+fn file(path: &str, source: &str) -> Arc<FileSource> {
+    Arc::new(FileSource {
+        id: uuid::Uuid::new_v4(),
+        path: path.to_string(),
+        source: Some(source.to_string()),
+        access: None,
+        dependencies: None,
+    })
+}
+
+#[test]
+fn test_positions() {
+    let source = "#define macro MAIN() = takes(0) returns(0) {\n    0x00 mstore\n}";
+    assert_eq!(Position::from_offset(source, 0), Position { line: 1, column: 1 });
+    assert_eq!(Position::from_offset(source, 49), Position { line: 2, column: 5 });
+    assert_eq!(Position::from_offset(source, source.len()), Position { line: 3, column: 2 });
+}
+
+#[test]
+fn test_error_diagnostics() {
+    let source = "#define macro MAIN() = takes(0) returns(0) {\n    UNKNOWN()\n}";
+    let main = file("./contracts/Main.huff", source);
+    let start = source.find("UNKNOWN").unwrap();
+    let spans = [(start, start + 6), (start + 7, start + 7), (start + 8, start + 8)]
+        .into_iter()
+        .map(|(start, end)| Span::new(start..end, Some(Arc::clone(&main))))
+        .collect();
+    let codegen_error = CompilerError::CodegenError(CodegenError::new(
+        CodegenErrorKind::InvalidMacroInvocation("UNKNOWN".to_string()),
+        AstSpan(spans),
+        None,
+    ));
+    let parser_error = CompilerError::ParserError(ParserError {
+        kind: ParserErrorKind::InvalidName(TokenKind::Eof),
+        hint: Some("Expected a macro name".to_string()),
+        spans: AstSpan(vec![Span::new(14..17, Some(Arc::clone(&main)))]),
+    });
+    let error = CompilerError::FailedCompiles(vec![codegen_error, parser_error]);
+
+    let diagnostics = Diagnostic::from_error(&error);
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic {
+                level: DiagnosticLevel::Error,
+                code: Some("H0214".to_string()),
+                message: "Missing Macro Definition For Invocation: \"UNKNOWN\"".to_string(),
+                hint: None,
+                notes: vec![],
+                // The tokens of the invocation are merged into a single span
+                spans: vec![DiagnosticSpan {
+                    file: "./contracts/Main.huff".to_string(),
+                    start,
+                    end: start + 8,
+                    start_position: Some(Position { line: 2, column: 5 }),
+                    end_position: Some(Position { line: 2, column: 13 }),
+                    label: Some("not defined".to_string()),
+                    source: Some(Arc::clone(&main)),
+                }],
+            },
+            Diagnostic {
+                level: DiagnosticLevel::Error,
+                code: Some("H0112".to_string()),
+                message: "Invalid Name: \"EOF\"".to_string(),
+                hint: Some("Expected a macro name".to_string()),
+                notes: vec![],
+                spans: vec![DiagnosticSpan {
+                    file: "./contracts/Main.huff".to_string(),
+                    start: 14,
+                    end: 17,
+                    start_position: Some(Position { line: 1, column: 15 }),
+                    end_position: Some(Position { line: 1, column: 18 }),
+                    label: None,
+                    source: Some(Arc::clone(&main)),
+                }],
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_sarif_report() {
+    let source = "#define macro HELPER() = takes(0) returns(0) {}\n#define macro HELPER() = takes(0) returns(0) {}";
+    let main = file("./contracts/Main.huff", source);
+    let mut warning = CompilerWarning::new(
+        WarningKind::UnusedMacro("HELPER".to_string()),
+        AstSpan(vec![Span::new(48..54, Some(Arc::clone(&main)))]),
+    );
+    warning.lint = Some("unused-macros");
+    let error = CompilerError::ParserError(ParserError {
+        kind: ParserErrorKind::DuplicateDefinition("HELPER".to_string()),
+        hint: None,
+        spans: AstSpan(vec![
+            Span::new(0..6, Some(Arc::clone(&main))),
+            Span::new(48..54, Some(Arc::clone(&main))),
+        ]),
+    });
+
+    let diagnostics = [Diagnostic::from_warning(&warning)]
+        .into_iter()
+        .chain(Diagnostic::from_error(&error))
+        .collect::<Vec<_>>();
+    let report = to_sarif(&diagnostics, &[("unused-macros", "Unused macros")], "0.3.2");
+
+    assert_eq!(report["version"], "2.1.0");
+    let run = &report["runs"][0];
+    assert_eq!(run["tool"]["driver"]["name"], "huffc");
+    assert_eq!(run["tool"]["driver"]["version"], "0.3.2");
+    assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "unused-macros");
+
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["ruleId"], "unused-macros");
+    assert_eq!(results[0]["level"], "warning");
+    assert_eq!(results[0]["message"]["text"], "Unused Macro: \"HELPER\"");
+    let location = &results[0]["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "contracts/Main.huff");
+    assert_eq!(
+        location["region"],
+        serde_json::json!({ "startLine": 2, "startColumn": 1, "endLine": 2, "endColumn": 8 })
+    );
+
+    assert_eq!(results[1]["ruleId"], "H0123");
+    assert_eq!(results[1]["level"], "error");
+    assert_eq!(results[1]["message"]["text"], "Duplicate Definition: \"HELPER\"");
+    assert_eq!(results[1]["locations"][0]["physicalLocation"]["region"]["startLine"], 1);
+    assert_eq!(results[1]["relatedLocations"][0]["physicalLocation"]["region"]["startLine"], 2);
+}
+
+#[test]
+fn test_json_diagnostics() {
+    let source = "#define constant A = 0x01\n#define constant A = 0x02";
+    let main = file("contracts/Main.huff", source);
+    let error = CompilerError::ParserError(ParserError {
+        kind: ParserErrorKind::DuplicateDefinition("A".to_string()),
+        hint: Some("Remove one of the definitions".to_string()),
+        spans: AstSpan(vec![
+            Span::new(0..6, Some(Arc::clone(&main))),
+            Span::new(26..32, Some(Arc::clone(&main))),
+        ]),
+    });
+
+    let diagnostics = Diagnostic::from_error(&error);
+    assert_eq!(
+        diagnostics[0].to_json(),
+        serde_json::json!({
+            "severity": "error",
+            "code": "H0123",
+            "message": "Duplicate Definition: \"A\"",
+            "hint": "Remove one of the definitions",
+            "notes": [],
+            "file": "contracts/Main.huff",
+            "span": {
+                "file": "contracts/Main.huff",
+                "start": 0,
+                "end": 6,
+                "line_start": 1,
+                "column_start": 1,
+                "line_end": 1,
+                "column_end": 7,
+                "label": "first defined here"
+            },
+            "related_spans": [{
+                "file": "contracts/Main.huff",
+                "start": 26,
+                "end": 32,
+                "line_start": 2,
+                "column_start": 1,
+                "line_end": 2,
+                "column_end": 7,
+                "label": "defined again here"
+            }]
+        })
+    );
+
+    // Spans of files without a known source have no lines and columns
+    let error = CompilerError::CodegenError(CodegenError::new(
+        CodegenErrorKind::MissingMacroDefinition("MAIN".to_string()),
+        AstSpan(vec![Span::new(
+            0..0,
+            Some(Arc::new(FileSource {
+                path: "contracts/Main.huff".to_string(),
+                ..Default::default()
+            })),
+        )]),
+        None,
+    ));
+    let json = Diagnostic::from_error(&error)[0].to_json();
+    assert_eq!(json["message"], "Missing Macro Definition For \"MAIN\"");
+    assert_eq!(json["span"]["line_start"], serde_json::Value::Null);
+}
+
+#[test]
+fn test_render_code_frames() {
+    let source = "#define macro HELPER() = takes(0) returns(0) {}\n#define macro HELPER() = takes(0) returns(0) {}";
+    let main = file("contracts/Main.huff", source);
+    let error = CompilerError::ParserError(ParserError {
+        kind: ParserErrorKind::DuplicateDefinition("HELPER".to_string()),
+        hint: Some("Remove one of the definitions".to_string()),
+        spans: AstSpan(vec![
+            Span::new(14..19, Some(Arc::clone(&main))),
+            Span::new(62..67, Some(Arc::clone(&main))),
+        ]),
+    });
+
+    let rendered = render(&Diagnostic::from_error(&error));
+    assert_eq!(
+        rendered,
+        r#"error[H0123]: Duplicate Definition: "HELPER"
+ --> contracts/Main.huff:1:15
+  |
+1 | #define macro HELPER() = takes(0) returns(0) {}
+  |               ^^^^^^ first defined here
+2 | #define macro HELPER() = takes(0) returns(0) {}
+  |               ------ defined again here
+help: Remove one of the definitions
+note: run `huffc explain H0123` for an extended explanation
+
+"#
+    );
+
+    // Errors without spans show the details below their heading as notes
+    let error = CompilerError::RemoteImportError(RemoteImportError::Disabled(
+        "https://example.com/Utils.huff".to_string(),
+    ));
+    let diagnostics = Diagnostic::from_error(&error);
+    assert_eq!(
+        diagnostics[0].notes,
+        vec!["Remote imports must be enabled with `--remote-imports`"]
+    );
+    assert!(render(&diagnostics).contains("note: Remote imports must be enabled"));
+}
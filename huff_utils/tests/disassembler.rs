@@ -0,0 +1,66 @@
+use huff_utils::{
+    artifact::DebugRange,
+    disassembler::{annotate, disassemble},
+    evm::Opcode,
+};
+
+#[test]
+fn disassembles_instructions() {
+    let instructions = disassemble("0x5f35600c57445b0c61ff");
+    let mnemonics = instructions.iter().map(|i| (i.pc, i.mnemonic.as_str())).collect::<Vec<_>>();
+    assert_eq!(
+        mnemonics,
+        vec![
+            (0, "push0"),
+            (1, "calldataload"),
+            (2, "push1"),
+            (4, "jumpi"),
+            (5, "prevrandao"),
+            (6, "jumpdest"),
+            (7, "unknown(0x0c)"),
+            (8, "push2"),
+        ]
+    );
+    assert_eq!(instructions[2].data, "0c");
+    assert_eq!(instructions[6].opcode, None);
+    assert_eq!(instructions[7].opcode, Some(Opcode::Push2));
+    assert!(instructions[7].is_truncated());
+    assert!(!instructions[2].is_truncated());
+}
+
+#[test]
+fn annotates_jumps() {
+    let disassembly = annotate(&disassemble("6003565b00"), &[]);
+    assert_eq!(
+        disassembly,
+        "0x0000  push1 0x03              // -> label_0x0003\n\
+         0x0002  jump\n\
+         label_0x0003:\n\
+         0x0003  jumpdest\n\
+         0x0004  stop"
+    );
+}
+
+#[test]
+fn annotates_with_debug_ranges() {
+    let debug = vec![
+        DebugRange { start: 0, end: 3, macros: vec!["MAIN".to_string()], label: None },
+        DebugRange {
+            start: 3,
+            end: 5,
+            macros: vec!["MAIN".to_string(), "FAIL".to_string()],
+            label: Some("error".to_string()),
+        },
+    ];
+    let disassembly = annotate(&disassemble("6003565b00"), &debug);
+    assert_eq!(
+        disassembly,
+        "// MAIN\n\
+         0x0000  push1 0x03              // -> error\n\
+         0x0002  jump\n\
+         // MAIN > FAIL\n\
+         error:\n\
+         0x0003  jumpdest\n\
+         0x0004  stop"
+    );
+}
@@ -0,0 +1,57 @@
+use std::collections::BTreeSet;
+
+use huff_utils::{error_codes::*, prelude::*};
+
+#[test]
+fn test_error_codes_are_unique_and_explained() {
+    let codes = ERROR_CODES.iter().map(|c| c.code).collect::<BTreeSet<&str>>();
+    assert_eq!(codes.len(), ERROR_CODES.len());
+    assert!(codes.iter().copied().eq(ERROR_CODES.iter().map(|c| c.code)), "codes aren't ordered");
+
+    let errors = [
+        CompilerError::LexicalError(LexicalError::new(
+            LexicalErrorKind::UnterminatedConditional,
+            Span::EOF,
+        )),
+        CompilerError::ParserError(ParserError {
+            kind: ParserErrorKind::InvalidReturnArgs,
+            hint: None,
+            spans: AstSpan(vec![]),
+        }),
+        CompilerError::CodegenError(CodegenError::new(
+            CodegenErrorKind::InvalidJumpDestination("done".to_string()),
+            AstSpan(vec![]),
+            None,
+        )),
+        CompilerError::CircularImport(vec![], AstSpan(vec![])),
+    ];
+    for error in errors {
+        let code = error.code().unwrap();
+        assert!(explain(code).is_some(), "{code} isn't explained");
+    }
+}
+
+#[test]
+fn test_explain() {
+    let explained = explain("h0123").unwrap();
+    assert_eq!(explained.code, "H0123");
+    assert_eq!(explained.title, "Duplicate definition");
+    assert!(explained.explanation.contains("Erroneous example"));
+    assert!(explain("H9999").is_none());
+}
+
+#[test]
+fn test_error_display_includes_code() {
+    let error = CompilerError::CodegenError(CodegenError::new(
+        CodegenErrorKind::MissingMacroDefinition("MAIN".to_string()),
+        AstSpan(vec![]),
+        None,
+    ));
+    assert_eq!(error.code(), Some("H0204"));
+    assert!(error.to_string().starts_with("\nError[H0204]: Missing Macro Definition For \"MAIN\""));
+
+    // Failed compiles display the code of each error
+    let failed = CompilerError::FailedCompiles(vec![error.clone(), error]);
+    assert_eq!(failed.code(), None);
+    assert_eq!(failed.to_string().matches("Error[H0204]").count(), 2);
+}
@@ -27,6 +27,35 @@ fn test_remappings_from_file() {
     );
 }
 
+#[test]
+fn test_remappings_from_foundry_style_file() {
+    let remapper = files::Remapper::new("./tests/foundry_project");
+    assert_eq!(remapper.remappings.len(), 2);
+    assert_eq!(remapper.remappings.get("@solmate/").unwrap(), "lib/solmate/src/");
+    assert_eq!(remapper.remappings.get("@huffmate/").unwrap(), "lib/huffmate/src/");
+}
+
+#[test]
+fn test_remappings_from_cli() {
+    let remapper = files::Remapper::new("./tests/").with_remappings(&[
+        "@huffmate/=lib/huffmate-fork/src/".to_string(),
+        "@huffmate/tokens/=lib/tokens/".to_string(),
+    ]);
+    assert_eq!(remapper.remappings.len(), 3);
+    assert_eq!(remapper.remappings.get("@huffmate/").unwrap(), "lib/huffmate-fork/src/");
+
+    // The longest matching prefix wins
+    assert_eq!(
+        remapper.remap("@huffmate/tokens/ERC20.huff"),
+        Some("./tests/lib/tokens/ERC20.huff".to_string())
+    );
+    assert_eq!(
+        remapper.remap("@huffmate/auth/Owned.huff"),
+        Some("./tests/lib/huffmate-fork/src/auth/Owned.huff".to_string())
+    );
+    assert_eq!(remapper.remap("lib/Owned.huff"), None);
+}
+
 #[test]
 fn test_source_seg() {
     let span = Span {
@@ -125,4 +154,44 @@ fn test_localize_file() {
         files::FileSource::localize_file("examples/ERC20.huff", "../random_dir/Address.huff")
             .unwrap();
     assert_eq!(localized, "./random_dir/Address.huff");
+    let localized =
+        files::FileSource::localize_file("/tmp/examples/ERC20.huff", "./Address.huff").unwrap();
+    assert_eq!(localized, "/tmp/examples/Address.huff");
+    let localized =
+        files::FileSource::localize_file("/tmp/examples/ERC20.huff", "../Address.huff").unwrap();
+    assert_eq!(localized, "/tmp/Address.huff");
+}
+
+#[test]
+fn test_fully_flatten_includes_shared_dependency_once() {
+    let utils = Arc::new(files::FileSource {
+        path: "./contracts/utils.huff".to_string(),
+        source: Some("UTILS".to_string()),
+        ..Default::default()
+    });
+    let a = Arc::new(files::FileSource {
+        path: "./contracts/a.huff".to_string(),
+        source: Some("A".to_string()),
+        dependencies: Some(vec![Arc::clone(&utils)]),
+        ..Default::default()
+    });
+    let b = Arc::new(files::FileSource {
+        path: "./contracts/b.huff".to_string(),
+        source: Some("B".to_string()),
+        // The same file, referenced through a different relative path
+        dependencies: Some(vec![Arc::new(files::FileSource {
+            path: "./contracts/../contracts/utils.huff".to_string(),
+            ..(*utils).clone()
+        })]),
+        ..Default::default()
+    });
+    let main = Arc::new(files::FileSource {
+        path: "contracts/main.huff".to_string(),
+        source: Some("MAIN".to_string()),
+        dependencies: Some(vec![a, b]),
+        ..Default::default()
+    });
+
+    let (source, _) = files::FileSource::fully_flatten(main);
+    assert_eq!(source, "MAINAUTILSB");
 }
@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use ethers_core::utils::{hex, keccak256};
+use huff_utils::prelude::*;
+
+fn metadata(version: &str) -> Metadata {
+    let file = Arc::new(FileSource {
+        path: "./src/Main.huff".to_string(),
+        source: Some("#include \"./Lib.huff\"".to_string()),
+        dependencies: Some(vec![Arc::new(FileSource {
+            path: "./src/Lib.huff".to_string(),
+            source: Some(String::new()),
+            ..Default::default()
+        })]),
+        ..Default::default()
+    });
+    Metadata::new(&file, version, MetadataSettings::default())
+}
+
+#[test]
+fn test_metadata_lists_included_sources() {
+    let metadata = metadata("0.3.2");
+    assert_eq!(
+        metadata.sources.keys().collect::<Vec<_>>(),
+        vec!["./src/Lib.huff", "./src/Main.huff"]
+    );
+    assert_eq!(
+        metadata.sources["./src/Lib.huff"].keccak256,
+        "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+    );
+}
+
+#[test]
+fn test_cbor_trailer_without_hash() {
+    // {"huffc": h'000302'}, 11 bytes long
+    assert_eq!(metadata("0.3.2").cbor_trailer(MetadataHash::None), "a165687566666343000302000b");
+
+    // Prerelease versions are embedded as text
+    assert_eq!(
+        metadata("0.4.0-rc.1").cbor_trailer(MetadataHash::None),
+        "a16568756666636a302e342e302d72632e310012"
+    );
+}
+
+#[test]
+fn test_cbor_trailer_with_hash() {
+    let metadata = metadata("0.3.2");
+
+    // {"keccak256": h'...', "huffc": h'000302'}, 55 bytes long
+    let hash = hex::encode(keccak256(metadata.to_json()));
+    assert_eq!(
+        metadata.cbor_trailer(MetadataHash::Keccak),
+        format!("a2696b656363616b3235365820{hash}656875666663430003020037")
+    );
+
+    // {"ipfs": h'1220...', "huffc": h'000302'}, 52 bytes long
+    let trailer = metadata.cbor_trailer(MetadataHash::Ipfs);
+    assert!(trailer.starts_with("a2646970667358221220"));
+    assert!(trailer.ends_with("656875666663430003020034"));
+    assert_eq!(trailer.len(), (52 + 2) * 2);
+}
+
+#[test]
+fn test_ipfs_cid() {
+    assert_eq!(ipfs_cid(""), "QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH");
+    assert_eq!(ipfs_cid("hello world\n"), "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o");
+}
+
+#[test]
+fn test_ipfs_metadata_references_sources_by_cid() {
+    let file = Arc::new(FileSource {
+        path: "./src/Main.huff".to_string(),
+        source: Some("hello world\n".to_string()),
+        ..Default::default()
+    });
+    let settings =
+        MetadataSettings { metadata_hash: Some(MetadataHash::Ipfs), ..Default::default() };
+    let metadata = Metadata::new(&file, "0.3.2", settings);
+    assert_eq!(
+        metadata.sources["./src/Main.huff"].urls,
+        vec!["dweb:/ipfs/QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o"]
+    );
+
+    // The trailer embeds the multihash the CID of the metadata encodes
+    let trailer = metadata.cbor_trailer(MetadataHash::Ipfs);
+    assert_eq!(&trailer[..20], "a2646970667358221220");
+    assert_eq!(metadata.cid(), ipfs_cid(metadata.to_json()));
+}
@@ -0,0 +1,70 @@
+use huff_utils::{files::Remapper, packages::PackageSpec};
+
+#[test]
+fn test_parse_package_specs() {
+    assert_eq!(
+        PackageSpec::parse("huff-language/huffmate"),
+        Some(PackageSpec {
+            name: "huffmate".to_string(),
+            url: "https://github.com/huff-language/huffmate".to_string(),
+            rev: None,
+        })
+    );
+    assert_eq!(
+        PackageSpec::parse("huff-language/huffmate@v0.1.0"),
+        Some(PackageSpec {
+            name: "huffmate".to_string(),
+            url: "https://github.com/huff-language/huffmate".to_string(),
+            rev: Some("v0.1.0".to_string()),
+        })
+    );
+    assert_eq!(
+        PackageSpec::parse("git@github.com:huff-language/huffmate.git"),
+        Some(PackageSpec {
+            name: "huffmate".to_string(),
+            url: "git@github.com:huff-language/huffmate.git".to_string(),
+            rev: None,
+        })
+    );
+    assert_eq!(
+        PackageSpec::parse("https://gitlab.com/huff/math.git@main"),
+        Some(PackageSpec {
+            name: "math".to_string(),
+            url: "https://gitlab.com/huff/math.git".to_string(),
+            rev: Some("main".to_string()),
+        })
+    );
+    assert_eq!(PackageSpec::parse("huffmate"), None);
+    assert_eq!(PackageSpec::parse("a/b/c"), None);
+}
+
+#[test]
+fn test_remappings_from_installed_packages() {
+    let remapper = Remapper::new("./tests/packages_project/");
+    assert_eq!(
+        remapper.remap("math/Math.huff"),
+        Some("./tests/packages_project/lib/math/src/Math.huff".to_string())
+    );
+    assert_eq!(
+        remapper.remap("flat/Flat.huff"),
+        Some("./tests/packages_project/lib/flat/Flat.huff".to_string())
+    );
+}
+
+#[test]
+fn test_rejects_package_specs_mistaken_for_options_or_paths() {
+    assert_eq!(PackageSpec::parse("huff-language/huffmate@--orphan"), None);
+    assert_eq!(PackageSpec::parse("huff-language/huffmate@"), None);
+    assert_eq!(PackageSpec::parse("--upload-pack=touch /tmp/pwned"), None);
+    assert_eq!(PackageSpec::parse("huff-language/.."), None);
+    assert_eq!(PackageSpec::parse("huff-language/-x"), None);
+    assert_eq!(PackageSpec::parse("https://example.com/.."), None);
+    assert_eq!(
+        PackageSpec::new("..".to_string(), "https://github.com/a/b".to_string(), None),
+        None
+    );
+    assert_eq!(
+        PackageSpec::new("b".to_string(), "-uhttps://github.com/a/b".to_string(), None),
+        None
+    );
+}
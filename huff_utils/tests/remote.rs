@@ -0,0 +1,54 @@
+use huff_utils::{
+    files::{FileSource, Remapper},
+    remote::{self, RemoteCache},
+};
+use std::{collections::HashMap, fs};
+
+#[test]
+fn test_resolve_remote_imports() {
+    let remapper = Remapper { remappings: HashMap::new(), base_dir: "./".to_string() };
+    let url = "https://example.com/huff/src/ERC20.huff";
+
+    // Remote imports are kept as is
+    assert_eq!(FileSource::resolve_import(&remapper, "./contracts/main.huff", url), url);
+
+    // Relative imports of remote files resolve against their url
+    assert_eq!(
+        FileSource::resolve_import(&remapper, url, "./utils/Ownable.huff"),
+        "https://example.com/huff/src/utils/Ownable.huff"
+    );
+    assert_eq!(
+        FileSource::resolve_import(&remapper, url, "../lib/Math.huff"),
+        "https://example.com/huff/lib/Math.huff"
+    );
+    assert_eq!(
+        FileSource::resolve_import(&remapper, "ipfs://bafybeigdyr/ERC20.huff", "./Math.huff"),
+        "ipfs://bafybeigdyr/Math.huff"
+    );
+}
+
+#[test]
+fn test_only_fetches_secure_urls() {
+    assert!(remote::is_remote("https://example.com/huff/ERC20.huff"));
+    assert!(remote::is_remote("ipfs://bafybeigdyr/ERC20.huff"));
+    assert!(!remote::is_remote("http://example.com/huff/ERC20.huff"));
+}
+
+#[test]
+fn test_remote_cache_serves_pinned_contents() {
+    let dir = std::env::temp_dir().join(format!("huff_remote_cache_{}", std::process::id()));
+    let cache_dir = dir.join("cache");
+    fs::create_dir_all(&cache_dir).unwrap();
+
+    let url = "https://example.com/huff/ERC20.huff";
+    let contents = "#define macro MINT() = takes(0) returns (0) {}";
+    let hash = remote::content_hash(contents);
+    fs::write(cache_dir.join(&hash), contents).unwrap();
+    fs::write(dir.join("huff.lock"), format!("{{\"{url}\": \"{hash}\"}}")).unwrap();
+
+    let cache = RemoteCache::new(&cache_dir, dir.join("huff.lock"));
+    assert_eq!(cache.hashes.lock().unwrap().get(url), Some(&hash));
+    assert_eq!(cache.get(url).unwrap(), contents);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,19 @@
+use huff_utils::prelude::*;
+
+#[test]
+fn test_closest_match() {
+    let candidates = ["TRANSFER", "TRANSFER_FROM", "APPROVE"];
+    assert_eq!(closest_match("TRANFER", candidates), Some("TRANSFER"));
+    assert_eq!(closest_match("transfer", candidates), Some("TRANSFER"));
+    assert_eq!(closest_match("APROVE", candidates), Some("APPROVE"));
+    assert_eq!(closest_match("TRANSFER", candidates), None);
+    assert_eq!(closest_match("MINT", candidates), None);
+    assert_eq!(closest_match("a", ["b", "ab"]), Some("b"));
+    assert_eq!(closest_match("x", []), None);
+}
+
+#[test]
+fn test_did_you_mean() {
+    assert_eq!(did_you_mean("amout", ["amount"]), Some("Did you mean \"amount\"?".to_string()));
+    assert_eq!(did_you_mean("amout", ["slot"]), None);
+}